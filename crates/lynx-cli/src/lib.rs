@@ -147,6 +147,16 @@ pub enum RulesCommands {
         #[command(flatten)]
         args: RulesFileArgs,
     },
+    /// Explain which rules match a request and which handlers would run
+    Test {
+        #[command(flatten)]
+        args: RulesTestArgs,
+    },
+    /// Report rules that can never fire and overlapping rules with conflicting handlers
+    Lint {
+        #[command(flatten)]
+        args: RulesLintArgs,
+    },
     /// Export JSON Schema for `.lynx.json` project rule config
     Schema {
         #[command(subcommand)]
@@ -154,6 +164,47 @@ pub enum RulesCommands {
     },
 }
 
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RulesTestArgs {
+    /// Absolute request URL, e.g. https://api.example.com/v2/users?id=1
+    pub url: String,
+
+    /// Request method
+    #[arg(short = 'X', long = "request", default_value = "GET")]
+    pub method: String,
+
+    /// Request header as `Name: value` (repeatable)
+    #[arg(short = 'H', long = "header")]
+    pub headers: Vec<String>,
+
+    /// Proxy data directory
+    #[arg(long)]
+    pub data_dir: Option<String>,
+
+    /// Rule project id (default: active project from data_dir/settings/projects.json)
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Print the full explanation as JSON
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(ClapArgs, Debug, Clone)]
+pub struct RulesLintArgs {
+    /// Proxy data directory
+    #[arg(long)]
+    pub data_dir: Option<String>,
+
+    /// Rule project id (default: active project from data_dir/settings/projects.json)
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Print the lint report as JSON
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum RulesSchemaCommands {
     /// Export the JSON Schema to a file
@@ -2,7 +2,10 @@ use anyhow::Result;
 use clap::Parser;
 use lynx_cli::cert_cmd::{self, CertOptions};
 use lynx_cli::daemon::DaemonManager;
-use lynx_cli::rules_cmd::{RulesOptions, run_apply, run_pull, run_push, run_schema_export};
+use lynx_cli::rules_cmd::{
+    RulesLintOptions, RulesOptions, RulesTestOptions, run_apply, run_lint, run_pull, run_push,
+    run_schema_export, run_test,
+};
 use lynx_cli::version_check;
 use lynx_cli::{
     Args, CertCommands, Commands, LogConfig, ProxyServerApp, RulesCommands, RulesSchemaCommands,
@@ -143,6 +146,25 @@ async fn main() -> Result<()> {
                 })
                 .await?;
             }
+            RulesCommands::Test { args } => {
+                run_test(RulesTestOptions {
+                    url: args.url,
+                    method: args.method,
+                    headers: args.headers,
+                    data_dir: args.data_dir,
+                    project: args.project,
+                    json: args.json,
+                })
+                .await?;
+            }
+            RulesCommands::Lint { args } => {
+                run_lint(RulesLintOptions {
+                    data_dir: args.data_dir,
+                    project: args.project,
+                    json: args.json,
+                })
+                .await?;
+            }
             RulesCommands::Schema { command } => match command {
                 RulesSchemaCommands::Export { out } => {
                     run_schema_export(out).await?;
@@ -1,8 +1,12 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use lynx_storage::DataStore;
-use lynx_storage::project_config::{apply_config, pull_rules, push_rules};
+use lynx_storage::dao::request_processing_dao::explain::probe_request;
+use lynx_storage::dao::request_processing_dao::{
+    RequestProcessingDao, RulesExplainReport, RulesLintReport,
+};
+use lynx_storage::project_config::{apply_config, pull_rules, push_rules, resolve_project_id};
 use serde_json::json;
 
 use crate::resolve_data_dir;
@@ -13,6 +17,21 @@ pub struct RulesOptions {
     pub project: Option<String>,
 }
 
+pub struct RulesTestOptions {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<String>,
+    pub data_dir: Option<String>,
+    pub project: Option<String>,
+    pub json: bool,
+}
+
+pub struct RulesLintOptions {
+    pub data_dir: Option<String>,
+    pub project: Option<String>,
+    pub json: bool,
+}
+
 pub fn resolve_config_path(file: Option<PathBuf>) -> Result<PathBuf> {
     match file {
         Some(path) => Ok(path),
@@ -103,6 +122,164 @@ pub async fn run_apply(options: RulesOptions) -> Result<()> {
     Ok(())
 }
 
+pub async fn run_test(options: RulesTestOptions) -> Result<()> {
+    let data_dir = resolve_data_dir(options.data_dir)?;
+    let store = DataStore::new(&data_dir).await?;
+    let project = resolve_project_id(store.clone(), options.project).await?;
+
+    let headers = options
+        .headers
+        .iter()
+        .map(|raw| parse_header_arg(raw))
+        .collect::<Result<Vec<_>>>()?;
+    let request = probe_request(&options.method, &options.url, &headers)?;
+    let report = RequestProcessingDao::new(store)
+        .explain_request(&request, &project)
+        .await?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_explain_report(&report);
+    }
+    Ok(())
+}
+
+/// Print lint issues; fails when any issue is an error so CI can gate on it.
+pub async fn run_lint(options: RulesLintOptions) -> Result<()> {
+    let data_dir = resolve_data_dir(options.data_dir)?;
+    let store = DataStore::new(&data_dir).await?;
+    let project = resolve_project_id(store.clone(), options.project).await?;
+    let report = RequestProcessingDao::new(store)
+        .lint_project(&project)
+        .await?;
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_lint_report(&report);
+    }
+    if report.errors > 0 {
+        return Err(anyhow!(
+            "{} rule lint error(s) in project '{}'",
+            report.errors,
+            report.project
+        ));
+    }
+    Ok(())
+}
+
+fn print_lint_report(report: &RulesLintReport) {
+    if report.issues.is_empty() {
+        println!("No issues in project '{}'.", report.project);
+        return;
+    }
+    for issue in &report.issues {
+        println!(
+            "{}: #{} {}: {}",
+            issue.severity,
+            issue.rule_id.unwrap_or(-1),
+            issue.rule_name,
+            issue.message
+        );
+    }
+    println!(
+        "{} error(s), {} warning(s) in project '{}'",
+        report.errors, report.warnings, report.project
+    );
+}
+
+fn parse_header_arg(raw: &str) -> Result<(String, String)> {
+    let (name, value) = raw
+        .split_once(':')
+        .ok_or_else(|| anyhow!("invalid header {raw:?}, expected `Name: value`"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+fn print_explain_report(report: &RulesExplainReport) {
+    println!(
+        "{} {} (project '{}')",
+        report.facts.method,
+        request_target(report),
+        report.project
+    );
+    if report.rules.is_empty() {
+        println!("No rules in project.");
+        return;
+    }
+
+    println!();
+    for rule in &report.rules {
+        let mark = if rule.applied {
+            "MATCH"
+        } else if rule.matched {
+            "MATCH (disabled)"
+        } else {
+            "no match"
+        };
+        println!(
+            "#{} {} [priority {}]: {mark}",
+            rule.rule_id.unwrap_or(0),
+            rule.name,
+            rule.priority
+        );
+        println!("    matchExpr: {}", rule.match_expr);
+        if let Some(error) = &rule.error {
+            println!("    error: {error}");
+        }
+        for failed in &rule.failed {
+            match failed.span {
+                Some(span) => println!(
+                    "    failed: {} (at {}..{})",
+                    failed.description, span.start, span.end
+                ),
+                None => println!("    failed: {}", failed.description),
+            }
+        }
+    }
+
+    println!();
+    if report.handlers.is_empty() {
+        println!("No handlers would run.");
+        return;
+    }
+    println!("Handlers (execution order):");
+    for (index, handler) in report.handlers.iter().enumerate() {
+        let note = if handler.reached {
+            ""
+        } else {
+            " (skipped: request already answered)"
+        };
+        println!(
+            "  {}. {} [order {}] from #{} {}{note}",
+            index + 1,
+            handler.kind,
+            handler.execution_order,
+            handler.rule_id.unwrap_or(0),
+            handler.rule_name
+        );
+    }
+}
+
+fn request_target(report: &RulesExplainReport) -> String {
+    let facts = &report.facts;
+    let mut target = String::new();
+    if let Some(scheme) = &facts.scheme {
+        target.push_str(scheme);
+        target.push_str("://");
+    }
+    target.push_str(&facts.host);
+    if let Some(port) = facts.port {
+        target.push_str(&format!(":{port}"));
+    }
+    target.push_str(&facts.path);
+    if let Some(query) = &facts.query {
+        target.push('?');
+        target.push_str(query);
+    }
+    target
+}
+
 pub async fn run_schema_export(out: Option<PathBuf>) -> Result<()> {
     use schemars::schema_for;
 
@@ -194,3 +194,138 @@ async fn cli_rules_pull_creates_missing_config() -> Result<()> {
     assert!(config.rules.is_empty());
     Ok(())
 }
+
+#[tokio::test]
+async fn cli_rules_test_explains_matches_and_handler_order() -> Result<()> {
+    let data = tempdir()?;
+    let data_dir = data.path().to_string_lossy().to_string();
+
+    let store = DataStore::new(data.path()).await?;
+    let dao = RequestProcessingDao::new(store.clone());
+
+    dao.create_rule(RequestRule {
+        name: "post-block".to_string(),
+        priority: 50,
+        capture: CaptureRule {
+            id: None,
+            match_expr: "api.example.com -X POST".to_string(),
+        },
+        handlers: vec![HandlerRule::block_handler(Some(403), None)],
+        ..Default::default()
+    })
+    .await?;
+    dao.create_rule(RequestRule {
+        name: "json-header".to_string(),
+        priority: 10,
+        capture: CaptureRule {
+            id: None,
+            match_expr: "api.example.com -H x-env=stg".to_string(),
+        },
+        handlers: vec![HandlerRule::block_handler(Some(404), None)],
+        ..Default::default()
+    })
+    .await?;
+
+    let output = lynx_bin()
+        .args([
+            "rules",
+            "test",
+            "https://api.example.com/v2/users",
+            "-X",
+            "GET",
+            "-H",
+            "x-env: stg",
+            "--data-dir",
+            &data_dir,
+            "--json",
+        ])
+        .output()?;
+    assert!(
+        output.status.success(),
+        "rules test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let rules = report["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["name"], "post-block");
+    assert_eq!(rules[0]["matched"], false);
+    assert_eq!(rules[0]["failed"][0]["description"], "method = POST");
+    assert_eq!(rules[1]["name"], "json-header");
+    assert_eq!(rules[1]["matched"], true);
+
+    let handlers = report["handlers"].as_array().unwrap();
+    assert_eq!(handlers.len(), 1);
+    assert_eq!(handlers[0]["kind"], "block");
+    assert_eq!(handlers[0]["ruleName"], "json-header");
+
+    let text = lynx_bin()
+        .args([
+            "rules",
+            "test",
+            "https://api.example.com/v2/users",
+            "-X",
+            "POST",
+            "--data-dir",
+            &data_dir,
+        ])
+        .output()?;
+    assert!(text.status.success());
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains("#1 post-block [priority 50]: MATCH"));
+    assert!(stdout.contains("failed: header x-env = stg"));
+    assert!(stdout.contains("1. block [order 100] from #1 post-block"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn cli_rules_lint_fails_on_shadowed_rule() -> Result<()> {
+    let data = tempdir()?;
+    let data_dir = data.path().to_string_lossy().to_string();
+
+    let store = DataStore::new(data.path()).await?;
+    let dao = RequestProcessingDao::new(store.clone());
+
+    dao.create_rule(RequestRule {
+        name: "block-example".to_string(),
+        priority: 50,
+        capture: CaptureRule {
+            id: None,
+            match_expr: "example.com".to_string(),
+        },
+        handlers: vec![HandlerRule::block_handler(Some(403), None)],
+        ..Default::default()
+    })
+    .await?;
+
+    let clean = lynx_bin()
+        .args(["rules", "lint", "--data-dir", &data_dir])
+        .output()?;
+    assert!(clean.status.success());
+    assert!(String::from_utf8_lossy(&clean.stdout).contains("No issues"));
+
+    dao.create_rule(RequestRule {
+        name: "mock-users".to_string(),
+        priority: 10,
+        capture: CaptureRule {
+            id: None,
+            match_expr: "api.example.com /users".to_string(),
+        },
+        handlers: vec![HandlerRule::block_handler(Some(404), None)],
+        ..Default::default()
+    })
+    .await?;
+
+    let output = lynx_bin()
+        .args(["rules", "lint", "--data-dir", &data_dir])
+        .output()?;
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("error: #2 mock-users: Rule never fires"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("1 error(s), 0 warning(s)"), "{stdout}");
+    Ok(())
+}
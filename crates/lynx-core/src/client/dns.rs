@@ -0,0 +1,505 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use http::header::{ACCEPT, CONTENT_TYPE};
+use hyper_util::client::legacy::connect::dns::Name;
+use lynx_storage::dao::dns_dao::{DnsConfig, DnsResolverConfig};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+    time::timeout,
+};
+use tower::Service;
+use tracing::warn;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Answers from a DNS server are kept for their TTL, but no longer than this.
+const MAX_CACHE_TTL: Duration = Duration::from_secs(300);
+const MAX_CACHE_ENTRIES: usize = 1024;
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const DNS_MESSAGE: &str = "application/dns-message";
+
+/// Where names without an override are looked up.
+enum Upstream {
+    System,
+    Server(SocketAddr),
+    DnsOverHttps {
+        url: String,
+        client: reqwest::Client,
+    },
+}
+
+struct CachedAnswer {
+    ips: Vec<IpAddr>,
+    expires: Instant,
+}
+
+struct Inner {
+    config: DnsConfig,
+    upstream: Upstream,
+    cache: Mutex<HashMap<String, CachedAnswer>>,
+}
+
+/// Resolves upstream host names: DNS overrides first, then the configured
+/// resolver. Also a resolver for hyper's `HttpConnector`.
+#[derive(Clone)]
+pub struct DnsResolver {
+    inner: Arc<Inner>,
+}
+
+impl Default for DnsResolver {
+    fn default() -> Self {
+        Self::from_parts(DnsConfig::default(), Upstream::System)
+    }
+}
+
+impl DnsResolver {
+    pub fn new(config: DnsConfig) -> Result<Self> {
+        let upstream = match &config.resolver {
+            DnsResolverConfig::System => Upstream::System,
+            DnsResolverConfig::Server { address } => Upstream::Server(
+                DnsResolverConfig::server_addr(address.trim())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid DNS server address: {address}"))?,
+            ),
+            DnsResolverConfig::DnsOverHttps { url } => Upstream::DnsOverHttps {
+                url: url.clone(),
+                client: reqwest::Client::builder().timeout(QUERY_TIMEOUT).build()?,
+            },
+        };
+        Ok(Self::from_parts(config, upstream))
+    }
+
+    fn from_parts(config: DnsConfig, upstream: Upstream) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config,
+                upstream,
+                cache: Mutex::default(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &DnsConfig {
+        &self.inner.config
+    }
+
+    /// Addresses for `host`, IPv4 first when the resolver returns both.
+    pub async fn lookup_ip(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        if let Some(ips) = self.inner.config.override_for(host) {
+            return Ok(ips.to_vec());
+        }
+
+        let mut ips: Vec<IpAddr> = match &self.inner.upstream {
+            Upstream::System => tokio::net::lookup_host((host, 0))
+                .await?
+                .map(|addr| addr.ip())
+                .collect(),
+            _ => self.lookup_cached(host).await?,
+        };
+        ips.sort_by_key(IpAddr::is_ipv6);
+        if ips.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no addresses found for {host}"),
+            ));
+        }
+        Ok(ips)
+    }
+
+    /// Connects to the first address of `host` that accepts.
+    pub async fn connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let mut last_error = None;
+        for ip in self.lookup_ip(host).await? {
+            match TcpStream::connect(SocketAddr::new(ip, port)).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| io::Error::other(format!("cannot connect to {host}"))))
+    }
+
+    async fn lookup_cached(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let key = host.trim_end_matches('.').to_ascii_lowercase();
+        if let Some(cached) = self.cache().get(&key)
+            && cached.expires > Instant::now()
+        {
+            return Ok(cached.ips.clone());
+        }
+
+        let (v4, v6) = tokio::join!(self.query(&key, TYPE_A), self.query(&key, TYPE_AAAA));
+        let (ips, ttl) = match (v4, v6) {
+            (Err(e), Err(_)) => return Err(e),
+            (v4, v6) => {
+                let answers = [v4.ok(), v6.ok()];
+                let answers = answers.iter().flatten();
+                let ips: Vec<IpAddr> = answers.clone().flat_map(|a| a.ips.clone()).collect();
+                let ttl = answers
+                    .filter(|a| !a.ips.is_empty())
+                    .map(|a| a.ttl)
+                    .min()
+                    .unwrap_or_default();
+                (ips, ttl)
+            }
+        };
+
+        if !ips.is_empty() && ttl > 0 {
+            let mut cache = self.cache();
+            let now = Instant::now();
+            if cache.len() >= MAX_CACHE_ENTRIES {
+                cache.retain(|_, cached| cached.expires > now);
+                if cache.len() >= MAX_CACHE_ENTRIES {
+                    cache.clear();
+                }
+            }
+            let ttl = Duration::from_secs(ttl.into()).min(MAX_CACHE_TTL);
+            cache.insert(
+                key,
+                CachedAnswer {
+                    ips: ips.clone(),
+                    expires: now + ttl,
+                },
+            );
+        }
+        Ok(ips)
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedAnswer>> {
+        self.inner.cache.lock().expect("dns cache poisoned")
+    }
+
+    async fn query(&self, host: &str, qtype: u16) -> io::Result<Answer> {
+        match &self.inner.upstream {
+            Upstream::System => Err(io::Error::other("system resolver has no DNS server")),
+            Upstream::Server(server) => {
+                let id = rand::random::<u16>();
+                let query = encode_query(id, host, qtype)?;
+                let answer =
+                    decode_response(&with_timeout(exchange_udp(*server, &query)).await?, id)?;
+                if !answer.truncated {
+                    return Ok(answer);
+                }
+                decode_response(&with_timeout(exchange_tcp(*server, &query)).await?, id)
+            }
+            Upstream::DnsOverHttps { url, client } => {
+                // RFC 8484 asks for ID 0 so responses stay cacheable.
+                let query = encode_query(0, host, qtype)?;
+                let response = client
+                    .post(url)
+                    .header(CONTENT_TYPE, DNS_MESSAGE)
+                    .header(ACCEPT, DNS_MESSAGE)
+                    .body(query)
+                    .send()
+                    .await
+                    .map_err(io::Error::other)?;
+                if !response.status().is_success() {
+                    return Err(io::Error::other(format!(
+                        "DNS-over-HTTPS server answered {}",
+                        response.status()
+                    )));
+                }
+                let body = response.bytes().await.map_err(io::Error::other)?;
+                decode_response(&body, 0)
+            }
+        }
+    }
+}
+
+impl Service<Name> for DnsResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let ips = resolver.lookup_ip(name.as_str()).await?;
+            Ok(ips
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, 0))
+                .collect::<Vec<_>>()
+                .into_iter())
+        })
+    }
+}
+
+/// Hands out one resolver, and so one answer cache, while the DNS settings
+/// stay the same.
+#[derive(Default)]
+pub struct DnsResolverCache {
+    current: Mutex<Option<DnsResolver>>,
+}
+
+impl DnsResolverCache {
+    pub fn resolver_for(&self, config: DnsConfig) -> DnsResolver {
+        let mut current = self.current.lock().expect("dns resolver cache poisoned");
+        if let Some(resolver) = current.as_ref()
+            && resolver.config() == &config
+        {
+            return resolver.clone();
+        }
+        let resolver = DnsResolver::new(config).unwrap_or_else(|e| {
+            warn!("Invalid DNS settings, using the system resolver: {e}");
+            DnsResolver::default()
+        });
+        *current = Some(resolver.clone());
+        resolver
+    }
+}
+
+#[derive(Debug)]
+struct Answer {
+    ips: Vec<IpAddr>,
+    /// Smallest TTL of the address records, in seconds
+    ttl: u32,
+    truncated: bool,
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed DNS response")
+}
+
+async fn with_timeout<T>(future: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+    timeout(QUERY_TIMEOUT, future)
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "DNS query timed out"))?
+}
+
+/// A recursive query for one name and record type.
+fn encode_query(id: u16, host: &str, qtype: u16) -> io::Result<Vec<u8>> {
+    let mut query = Vec::with_capacity(host.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question.
+    query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in host.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid host name {host:?}"),
+            ));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&qtype.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    Ok(query)
+}
+
+/// Skips a possibly compressed name, returning the offset after it.
+fn skip_name(buf: &[u8], mut pos: usize) -> io::Result<usize> {
+    loop {
+        let len = *buf.get(pos).ok_or_else(malformed)?;
+        match len {
+            0 => return Ok(pos + 1),
+            // A pointer ends the name.
+            len if len & 0xc0 == 0xc0 => {
+                buf.get(pos + 1).ok_or_else(malformed)?;
+                return Ok(pos + 2);
+            }
+            len => pos += 1 + len as usize,
+        }
+    }
+}
+
+/// The A and AAAA records of a response to the query with `id`.
+fn decode_response(buf: &[u8], id: u16) -> io::Result<Answer> {
+    let read_u16 = |pos: usize| -> io::Result<u16> {
+        buf.get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(malformed)
+    };
+    if buf.len() < 12 {
+        return Err(malformed());
+    }
+    if read_u16(0)? != id || buf[2] & 0x80 == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected DNS response",
+        ));
+    }
+    let truncated = buf[2] & 0x02 != 0;
+    match buf[3] & 0x0f {
+        0 => {}
+        // NXDOMAIN: no addresses.
+        3 => {
+            return Ok(Answer {
+                ips: Vec::new(),
+                ttl: 0,
+                truncated,
+            });
+        }
+        rcode => {
+            return Err(io::Error::other(format!(
+                "DNS server answered with rcode {rcode}"
+            )));
+        }
+    }
+
+    let questions = read_u16(4)?;
+    let answers = read_u16(6)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(buf, pos)? + 4;
+    }
+    let mut ips = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answers {
+        pos = skip_name(buf, pos)?;
+        let header = buf.get(pos..pos + 10).ok_or_else(malformed)?;
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let record_ttl = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let len = u16::from_be_bytes([header[8], header[9]]) as usize;
+        pos += 10;
+        let data = buf.get(pos..pos + len).ok_or_else(malformed)?;
+        pos += len;
+        let ip = match (rtype, data.len()) {
+            (TYPE_A, 4) => IpAddr::V4(Ipv4Addr::new(data[0], data[1], data[2], data[3])),
+            (TYPE_AAAA, 16) => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(data);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            // CNAMEs come with the records they point to.
+            _ => continue,
+        };
+        ips.push(ip);
+        ttl = ttl.min(record_ttl);
+    }
+    Ok(Answer {
+        ttl: if ips.is_empty() { 0 } else { ttl },
+        ips,
+        truncated,
+    })
+}
+
+async fn exchange_udp(server: SocketAddr, query: &[u8]) -> io::Result<Vec<u8>> {
+    let bind = if server.is_ipv4() {
+        SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+    } else {
+        SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect(server).await?;
+    socket.send(query).await?;
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let len = socket.recv(&mut buf).await?;
+        // Ignore stray datagrams for other queries.
+        if len >= 2 && buf[..2] == query[..2] {
+            buf.truncate(len);
+            return Ok(buf);
+        }
+    }
+}
+
+async fn exchange_tcp(server: SocketAddr, query: &[u8]) -> io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(server).await?;
+    stream
+        .write_all(&(query.len() as u16).to_be_bytes())
+        .await?;
+    stream.write_all(query).await?;
+    let len = stream.read_u16().await? as usize;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lynx_storage::dao::dns_dao::DnsOverride;
+
+    /// Answers every query with `ip` for A and nothing for AAAA, compressing
+    /// the answer name like real servers do.
+    async fn spawn_dns_server(ip: Ipv4Addr) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let query = &buf[..len];
+                let qtype = u16::from_be_bytes([query[len - 4], query[len - 3]]);
+                let mut response = query.to_vec();
+                response[2] = 0x81;
+                response[3] = 0x80;
+                if qtype == TYPE_A {
+                    response[7] = 1;
+                    response.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                    response.extend_from_slice(&ip.octets());
+                }
+                let _ = socket.send_to(&response, peer).await;
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn overrides_win_over_the_dns_server() -> Result<()> {
+        let server = spawn_dns_server(Ipv4Addr::new(10, 1, 2, 3)).await;
+        let resolver = DnsResolver::new(DnsConfig {
+            overrides: vec![DnsOverride {
+                host: "*.staging.test".to_string(),
+                ips: vec!["127.0.0.9".parse()?],
+                enabled: true,
+            }],
+            resolver: DnsResolverConfig::Server {
+                address: server.to_string(),
+            },
+        })?;
+
+        assert_eq!(
+            resolver.lookup_ip("api.staging.test").await?,
+            vec!["127.0.0.9".parse::<IpAddr>()?]
+        );
+        assert_eq!(
+            resolver.lookup_ip("www.example.test").await?,
+            vec!["10.1.2.3".parse::<IpAddr>()?]
+        );
+        assert_eq!(resolver.cache().len(), 1);
+        assert_eq!(
+            resolver.lookup_ip("[::1]").await?,
+            vec!["::1".parse::<IpAddr>()?]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_answers_and_rejects_mismatched_ids() -> Result<()> {
+        let query = encode_query(7, "example.test", TYPE_AAAA)?;
+        let mut response = query.clone();
+        response[2] = 0x81;
+        response[3] = 0x80;
+        response[7] = 2;
+        // A CNAME, then the AAAA it points to.
+        response.extend_from_slice(&[0xc0, 12, 0, 5, 0, 1, 0, 0, 1, 0, 0, 2, 0xc0, 12]);
+        response.extend_from_slice(&[0xc0, 12, 0, 28, 0, 1, 0, 0, 0, 30, 0, 16]);
+        response.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>()?.octets());
+
+        let answer = decode_response(&response, 7)?;
+        assert_eq!(answer.ips, vec!["2001:db8::1".parse::<IpAddr>()?]);
+        assert_eq!(answer.ttl, 30);
+        assert!(!answer.truncated);
+        assert!(decode_response(&response, 8).is_err());
+        assert!(decode_response(&response[..response.len() - 3], 7).is_err());
+        Ok(())
+    }
+}
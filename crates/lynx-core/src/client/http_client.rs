@@ -2,7 +2,7 @@ use std::{sync::Arc, time::Duration};
 
 use anyhow::{Result, anyhow};
 use bytes::Bytes;
-use http::Uri;
+use http::{HeaderValue, Uri, header::PROXY_AUTHORIZATION};
 use http_body_util::combinators::BoxBody;
 use hyper_http_proxy::{Intercept, Proxy, ProxyConnector};
 use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
@@ -18,21 +18,35 @@ use hyper_util::{
     rt::TokioExecutor,
 };
 use lynx_cert::gen_client_config_by_cert;
+use lynx_storage::dao::request_processing_dao::handlers::{
+    UpstreamProxyConfig, UpstreamProxyProtocol,
+};
 use rcgen::Certificate;
 
-use super::ProxyType;
+use super::{
+    ProxyType,
+    dns::DnsResolver,
+    upstream_proxy::{UpstreamConnector, proxy_authorization},
+};
 use crate::common::{HyperRes, Req};
 
 const DEFAULT_HEADERS_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub enum HttpClient {
     Direct {
-        client: Client<HttpsConnector<HttpConnector>, BoxBody<Bytes, anyhow::Error>>,
+        client: Client<HttpsConnector<HttpConnector<DnsResolver>>, BoxBody<Bytes, anyhow::Error>>,
         headers_timeout: Duration,
     },
     Proxy {
-        client: Client<ProxyConnector<HttpConnector>, BoxBody<Bytes, anyhow::Error>>,
+        client: Client<ProxyConnector<HttpConnector<DnsResolver>>, BoxBody<Bytes, anyhow::Error>>,
+        headers_timeout: Duration,
+    },
+    /// Chosen by an `UpstreamProxy` rule
+    Upstream {
+        client: Client<HttpsConnector<UpstreamConnector>, BoxBody<Bytes, anyhow::Error>>,
         headers_timeout: Duration,
+        /// Sent with plain `http` requests, which go to an HTTP proxy in absolute form
+        proxy_authorization: Option<HeaderValue>,
     },
 }
 
@@ -40,6 +54,8 @@ pub enum HttpClient {
 pub struct HttpClientBuilder {
     custom_certs: Option<Arc<Vec<Arc<Certificate>>>>,
     proxy_config: ProxyType,
+    upstream_proxy: Option<UpstreamProxyConfig>,
+    dns_resolver: DnsResolver,
     headers_timeout: Option<Duration>,
 }
 
@@ -52,10 +68,13 @@ impl HttpClient {
             HttpClient::Proxy {
                 headers_timeout, ..
             } => *headers_timeout,
+            HttpClient::Upstream {
+                headers_timeout, ..
+            } => *headers_timeout,
         }
     }
 
-    pub async fn request(&self, req: Req) -> Result<HyperRes> {
+    pub async fn request(&self, mut req: Req) -> Result<HyperRes> {
         let headers_timeout = self.headers_timeout();
         let target = req.uri().to_string();
         let request_future = match self {
@@ -67,6 +86,22 @@ impl HttpClient {
                 trace!("HTTP Client: Making proxied request to {}", req.uri());
                 client.request(req)
             }
+            HttpClient::Upstream {
+                client,
+                proxy_authorization,
+                ..
+            } => {
+                trace!(
+                    "HTTP Client: Making upstream proxy request to {}",
+                    req.uri()
+                );
+                if let Some(value) = proxy_authorization
+                    && req.uri().scheme_str() == Some("http")
+                {
+                    req.headers_mut().insert(PROXY_AUTHORIZATION, value.clone());
+                }
+                client.request(req)
+            }
         };
 
         timeout(headers_timeout, request_future)
@@ -92,17 +127,69 @@ impl HttpClientBuilder {
         self
     }
 
+    /// Route through the upstream of an `UpstreamProxy` rule instead of `proxy_config`.
+    pub fn upstream_proxy(mut self, upstream_proxy: Option<UpstreamProxyConfig>) -> Self {
+        self.upstream_proxy = upstream_proxy;
+        self
+    }
+
+    /// Resolves target hosts, and proxy hosts when a proxy is configured.
+    pub fn dns_resolver(mut self, dns_resolver: DnsResolver) -> Self {
+        self.dns_resolver = dns_resolver;
+        self
+    }
+
     pub fn headers_timeout(mut self, headers_timeout: Duration) -> Self {
         self.headers_timeout = Some(headers_timeout);
         self
     }
 
+    fn http_connector(&self) -> HttpConnector<DnsResolver> {
+        let mut connector = HttpConnector::new_with_resolver(self.dns_resolver.clone());
+        // HttpsConnector checks the scheme itself.
+        connector.enforce_http(false);
+        connector
+    }
+
     pub fn build(&self) -> Result<HttpClient> {
         let cert_chain = self.custom_certs.clone();
         let client_config = gen_client_config_by_cert(cert_chain.clone())?;
         let headers_timeout = self.headers_timeout.unwrap_or(DEFAULT_HEADERS_TIMEOUT);
 
-        match &self.proxy_config {
+        let mut proxy_config = &self.proxy_config;
+        if let Some(upstream) = &self.upstream_proxy {
+            if upstream.protocol != UpstreamProxyProtocol::Direct {
+                trace!("HTTP Client: Using upstream proxy: {}", upstream.label());
+                // The proxy itself only speaks HTTP/1.1, so its TLS offers no ALPN.
+                let mut proxy_tls = client_config.clone();
+                proxy_tls.alpn_protocols.clear();
+                let connector = HttpsConnectorBuilder::new()
+                    .with_tls_config(client_config)
+                    .https_or_http()
+                    .enable_all_versions()
+                    .wrap_connector(UpstreamConnector::new(
+                        upstream.clone(),
+                        Arc::new(proxy_tls),
+                        self.dns_resolver.clone(),
+                    ));
+                let proxy_authorization = match upstream.protocol {
+                    UpstreamProxyProtocol::Http | UpstreamProxyProtocol::Https => {
+                        proxy_authorization(upstream)
+                    }
+                    _ => None,
+                };
+
+                let client = Client::builder(TokioExecutor::new()).build(connector);
+                return Ok(HttpClient::Upstream {
+                    client,
+                    headers_timeout,
+                    proxy_authorization,
+                });
+            }
+            proxy_config = &ProxyType::None;
+        }
+
+        match proxy_config {
             ProxyType::None => {
                 trace!("HTTP Client: Using direct connection (no proxy)");
                 // 直接使用 HTTPS 连接器
@@ -110,7 +197,7 @@ impl HttpClientBuilder {
                     .with_tls_config(client_config)
                     .https_or_http()
                     .enable_all_versions()
-                    .build();
+                    .wrap_connector(self.http_connector());
 
                 let client = Client::builder(TokioExecutor::new()).build(connector);
                 Ok(HttpClient::Direct {
@@ -133,7 +220,7 @@ impl HttpClientBuilder {
                     trace!("HTTP Client: Using system proxy: {}", proxy_uri);
                     let proxy = Proxy::new(Intercept::All, proxy_uri);
 
-                    let base_connector = HttpConnector::new();
+                    let base_connector = self.http_connector();
                     let mut proxy_connector = ProxyConnector::from_proxy(base_connector, proxy)?;
                     proxy_connector.set_tls(Some(TlsConnector::from(Arc::new(client_config))));
 
@@ -148,7 +235,7 @@ impl HttpClientBuilder {
                         .with_tls_config(client_config)
                         .https_or_http()
                         .enable_all_versions()
-                        .build();
+                        .wrap_connector(self.http_connector());
 
                     let client = Client::builder(TokioExecutor::new()).build(connector);
                     Ok(HttpClient::Direct {
@@ -166,7 +253,7 @@ impl HttpClientBuilder {
                 // 使用 Intercept::All 来确保所有请求都通过代理
                 let proxy = Proxy::new(Intercept::All, proxy_uri);
 
-                let base_connector = HttpConnector::new();
+                let base_connector = self.http_connector();
                 let mut proxy_connector = ProxyConnector::from_proxy(base_connector, proxy)?;
                 proxy_connector.set_tls(Some(TlsConnector::from(Arc::new(client_config))));
 
@@ -225,7 +312,9 @@ mod tests {
             HttpClient::Direct { .. } => {
                 println!("✓ ProxyType::None correctly creates Direct client")
             }
-            HttpClient::Proxy { .. } => panic!("Expected Direct client for ProxyType::None"),
+            HttpClient::Proxy { .. } | HttpClient::Upstream { .. } => {
+                panic!("Expected Direct client for ProxyType::None")
+            }
         }
 
         // 测试系统代理配置
@@ -240,6 +329,9 @@ mod tests {
             HttpClient::Proxy { .. } => {
                 println!("✓ ProxyType::System correctly creates Proxy client")
             }
+            HttpClient::Upstream { .. } => {
+                panic!("Unexpected Upstream client for ProxyType::System")
+            }
         }
 
         // 测试自定义代理配置
@@ -251,7 +343,9 @@ mod tests {
             HttpClient::Proxy { .. } => {
                 println!("✓ ProxyType::Custom correctly creates Proxy client")
             }
-            HttpClient::Direct { .. } => panic!("Expected Proxy client for ProxyType::Custom"),
+            HttpClient::Direct { .. } | HttpClient::Upstream { .. } => {
+                panic!("Expected Proxy client for ProxyType::Custom")
+            }
         }
 
         // 测试无效的自定义代理 URL (使用明显无效的格式)
@@ -279,7 +373,7 @@ mod tests {
                 HttpClient::Proxy { .. } => {
                     println!("✓ Valid proxy URL '{}' creates Proxy client", proxy_url)
                 }
-                HttpClient::Direct { .. } => {
+                HttpClient::Direct { .. } | HttpClient::Upstream { .. } => {
                     panic!("Expected Proxy client for valid proxy URL '{}'", proxy_url)
                 }
             }
@@ -288,6 +382,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_upstream_proxy_overrides_proxy_config() -> Result<()> {
+        let socks = UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Socks5,
+            address: "127.0.0.1:1080".to_string(),
+            ..Default::default()
+        };
+        let client = HttpClientBuilder::default()
+            .proxy_config(ProxyType::Custom("http://127.0.0.1:7788".to_string()))
+            .upstream_proxy(Some(socks))
+            .build()?;
+        assert!(matches!(client, HttpClient::Upstream { .. }));
+
+        let client = HttpClientBuilder::default()
+            .proxy_config(ProxyType::Custom("http://127.0.0.1:7788".to_string()))
+            .upstream_proxy(Some(UpstreamProxyConfig::default()))
+            .build()?;
+        assert!(matches!(client, HttpClient::Direct { .. }));
+        Ok(())
+    }
+
     #[test]
     fn test_system_proxy_detection() -> Result<()> {
         // 测试系统代理检测逻辑
@@ -1,6 +1,8 @@
+pub mod dns;
 pub mod http_client;
 pub mod request_client;
 pub mod reqwest_client;
+pub mod upstream_proxy;
 pub mod websocket_client;
 
 #[derive(Debug, Clone, Default)]
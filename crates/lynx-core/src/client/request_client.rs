@@ -1,15 +1,26 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex, OnceLock},
+};
 
 use anyhow::Result;
 use http::Extensions;
+use lynx_cert::gen_client_config_by_cert;
+use lynx_storage::dao::{
+    dns_dao::DnsConfig, request_processing_dao::handlers::UpstreamProxyConfig,
+};
 use rcgen::Certificate;
+use tokio_rustls::rustls::ClientConfig;
 
 use crate::error::{CoreError, CoreResult};
 
 use crate::client::{ProxyType, ReqwestClient, ReqwestClientBuilder};
 
 use super::{
+    dns::DnsResolver,
     http_client::{HttpClient, HttpClientBuilder},
+    upstream_proxy::{UpstreamStream, open_tunnel},
     websocket_client::{WebsocketClient, WebsocketClientBuilder},
 };
 
@@ -18,6 +29,25 @@ pub struct RequestClient {
     http_client: Arc<HttpClient>,
     websocket_client: Arc<WebsocketClient>,
     reqwest_client: Arc<ReqwestClient>,
+    custom_certs: Option<Arc<Vec<Arc<Certificate>>>>,
+    dns_resolver: DnsResolver,
+    upstream: Arc<UpstreamClientCache>,
+}
+
+/// Clients for `UpstreamProxy` rules, shared by every connection of a server
+/// and built on first use, one per upstream.
+#[derive(Default)]
+pub struct UpstreamClientCache {
+    /// TLS to `https` proxies on CONNECT tunnels
+    tunnel_tls: OnceLock<Arc<ClientConfig>>,
+    http_clients: Mutex<UpstreamHttpClients>,
+}
+
+#[derive(Default)]
+struct UpstreamHttpClients {
+    /// DNS settings the clients resolve with; they are rebuilt when it changes
+    dns_config: Option<DnsConfig>,
+    clients: HashMap<UpstreamProxyConfig, Arc<HttpClient>>,
 }
 
 #[derive(Default)]
@@ -26,6 +56,8 @@ pub struct RequestClientBuilder {
     api_custom_certs: Option<Arc<Vec<Arc<Certificate>>>>,
     proxy_requests_config: ProxyType,
     api_debug_proxy_config: ProxyType,
+    dns_resolver: DnsResolver,
+    upstream_clients: Arc<UpstreamClientCache>,
 }
 
 impl RequestClientBuilder {
@@ -51,6 +83,18 @@ impl RequestClientBuilder {
         self
     }
 
+    /// Resolves the hosts of proxied requests and tunnels.
+    pub fn dns_resolver(mut self, dns_resolver: DnsResolver) -> Self {
+        self.dns_resolver = dns_resolver;
+        self
+    }
+
+    /// Clients for `UpstreamProxy` rules, shared with other connections.
+    pub fn upstream_clients(mut self, upstream_clients: Arc<UpstreamClientCache>) -> Self {
+        self.upstream_clients = upstream_clients;
+        self
+    }
+
     pub fn build(&self) -> Result<RequestClient> {
         let custom_certs = self.custom_certs.clone();
 
@@ -58,11 +102,12 @@ impl RequestClientBuilder {
             HttpClientBuilder::default()
                 .custom_certs(custom_certs.clone())
                 .proxy_config(self.proxy_requests_config.clone())
+                .dns_resolver(self.dns_resolver.clone())
                 .build()?,
         );
         let websocket_client = Arc::new(
             WebsocketClientBuilder::default()
-                .custom_certs(custom_certs)
+                .custom_certs(custom_certs.clone())
                 .build()?,
         );
 
@@ -77,10 +122,59 @@ impl RequestClientBuilder {
             reqwest_client,
             http_client,
             websocket_client,
+            custom_certs,
+            dns_resolver: self.dns_resolver.clone(),
+            upstream: Arc::clone(&self.upstream_clients),
         })
     }
 }
 
+impl RequestClient {
+    /// The `HttpClient` that sends requests through `upstream`.
+    pub fn upstream_http_client(&self, upstream: &UpstreamProxyConfig) -> Result<Arc<HttpClient>> {
+        let mut cached = self
+            .upstream
+            .http_clients
+            .lock()
+            .expect("upstream http clients poisoned");
+        if cached.dns_config.as_ref() != Some(self.dns_resolver.config()) {
+            cached.dns_config = Some(self.dns_resolver.config().clone());
+            cached.clients.clear();
+        }
+        if let Some(client) = cached.clients.get(upstream) {
+            return Ok(Arc::clone(client));
+        }
+        let client = Arc::new(
+            HttpClientBuilder::default()
+                .custom_certs(self.custom_certs.clone())
+                .upstream_proxy(Some(upstream.clone()))
+                .dns_resolver(self.dns_resolver.clone())
+                .build()?,
+        );
+        cached.clients.insert(upstream.clone(), Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Opens a byte tunnel to `host:port` through `upstream`, for CONNECT
+    /// requests that are not decrypted and for WebSocket upgrades.
+    pub async fn open_upstream_tunnel(
+        &self,
+        upstream: &UpstreamProxyConfig,
+        host: &str,
+        port: u16,
+    ) -> io::Result<UpstreamStream> {
+        let tls = match self.upstream.tunnel_tls.get() {
+            Some(tls) => Arc::clone(tls),
+            None => {
+                let tls = gen_client_config_by_cert(self.custom_certs.clone())
+                    .map_err(io::Error::other)?;
+                Arc::clone(self.upstream.tunnel_tls.get_or_init(|| Arc::new(tls)))
+            }
+        };
+        open_tunnel(upstream, &tls, &self.dns_resolver, host, port).await
+    }
+}
+
 pub type ShareRequestClient = Arc<RequestClient>;
 
 pub trait RequestClientExt {
@@ -135,10 +229,35 @@ impl RequestClientExt for Extensions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lynx_storage::dao::request_processing_dao::handlers::UpstreamProxyProtocol;
 
     #[test]
     fn build_request_client_test() {
         let client = RequestClientBuilder::default().custom_certs(None).build();
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn upstream_http_client_is_cached_per_upstream() -> Result<()> {
+        let upstream_clients = Arc::new(UpstreamClientCache::default());
+        let build = || {
+            RequestClientBuilder::default()
+                .upstream_clients(Arc::clone(&upstream_clients))
+                .build()
+        };
+        let client = build()?;
+        let upstream = |address: &str| UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Http,
+            address: address.to_string(),
+            ..Default::default()
+        };
+        let first = client.upstream_http_client(&upstream("127.0.0.1:3128"))?;
+        let again = client.upstream_http_client(&upstream("127.0.0.1:3128"))?;
+        let other = client.upstream_http_client(&upstream("127.0.0.1:3129"))?;
+        let next_connection = build()?.upstream_http_client(&upstream("127.0.0.1:3128"))?;
+        assert!(Arc::ptr_eq(&first, &again));
+        assert!(Arc::ptr_eq(&first, &next_connection));
+        assert!(!Arc::ptr_eq(&first, &other));
+        Ok(())
+    }
 }
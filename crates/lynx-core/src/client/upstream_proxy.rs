@@ -0,0 +1,482 @@
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use http::{HeaderValue, Uri};
+use hyper_util::{
+    client::legacy::connect::{Connected, Connection},
+    rt::TokioIo,
+};
+use lynx_storage::dao::request_processing_dao::handlers::{
+    UpstreamProxyConfig, UpstreamProxyProtocol,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    TlsConnector,
+    client::TlsStream,
+    rustls::{ClientConfig, pki_types::ServerName},
+};
+use tower::Service;
+
+use super::dns::DnsResolver;
+
+/// Largest CONNECT response head accepted from an HTTP proxy.
+const MAX_CONNECT_RESPONSE: usize = 8 * 1024;
+
+fn proxy_error(message: impl Into<String>) -> io::Error {
+    io::Error::other(message.into())
+}
+
+/// `Proxy-Authorization` value for the configured credentials.
+pub fn proxy_authorization(config: &UpstreamProxyConfig) -> Option<HeaderValue> {
+    let (username, password) = config.credentials()?;
+    let encoded = STANDARD.encode(format!("{username}:{password}"));
+    HeaderValue::from_str(&format!("Basic {encoded}")).ok()
+}
+
+/// Response extension with the peer of an upstream connection, which is the
+/// proxy unless the connection is direct.
+#[derive(Debug, Clone, Copy)]
+pub struct UpstreamPeerAddr(pub SocketAddr);
+
+enum StreamKind {
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+/// Connection to the target, made directly or through an upstream proxy.
+pub struct UpstreamStream {
+    kind: StreamKind,
+    /// Requests go to the proxy in absolute form instead of through a tunnel
+    absolute_form: bool,
+}
+
+impl UpstreamStream {
+    /// The TCP socket to the proxy, or to the target when direct.
+    pub fn tcp(&self) -> &TcpStream {
+        match &self.kind {
+            StreamKind::Tcp(stream) => stream,
+            StreamKind::Tls(stream) => stream.get_ref().0,
+        }
+    }
+}
+
+impl AsyncRead for UpstreamStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().kind {
+            StreamKind::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            StreamKind::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for UpstreamStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.get_mut().kind {
+            StreamKind::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            StreamKind::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().kind {
+            StreamKind::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            StreamKind::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().kind {
+            StreamKind::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            StreamKind::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+impl Connection for UpstreamStream {
+    fn connected(&self) -> Connected {
+        let connected = Connected::new().proxy(self.absolute_form);
+        match self.tcp().peer_addr() {
+            Ok(addr) => connected.extra(UpstreamPeerAddr(addr)),
+            Err(_) => connected,
+        }
+    }
+}
+
+/// `host` and `port` of the proxy `address`.
+fn split_address(address: &str) -> io::Result<(&str, u16)> {
+    address
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+        .map(|(host, port)| (host.trim_start_matches('[').trim_end_matches(']'), port))
+        .ok_or_else(|| proxy_error(format!("invalid proxy address {address:?}")))
+}
+
+/// Opens the TCP connection to the proxy, resolving its host with `resolver`.
+async fn connect_proxy_tcp(
+    config: &UpstreamProxyConfig,
+    resolver: &DnsResolver,
+) -> io::Result<TcpStream> {
+    let (host, port) = split_address(&config.address)?;
+    resolver.connect(host, port).await
+}
+
+/// Opens the connection to the proxy itself, over TLS for `https` proxies.
+async fn connect_proxy(
+    config: &UpstreamProxyConfig,
+    tls: &Arc<ClientConfig>,
+    resolver: &DnsResolver,
+) -> io::Result<StreamKind> {
+    let tcp = connect_proxy_tcp(config, resolver).await?;
+    if config.protocol != UpstreamProxyProtocol::Https {
+        return Ok(StreamKind::Tcp(tcp));
+    }
+    let (host, _) = split_address(&config.address)?;
+    let server_name = ServerName::try_from(host.to_string())
+        .map_err(|e| proxy_error(format!("invalid proxy host {host:?}: {e}")))?;
+    let stream = TlsConnector::from(Arc::clone(tls))
+        .connect(server_name, tcp)
+        .await?;
+    Ok(StreamKind::Tls(Box::new(stream)))
+}
+
+/// Asks an HTTP proxy to open a tunnel to `host:port`.
+async fn http_connect<S>(
+    stream: &mut S,
+    config: &UpstreamProxyConfig,
+    host: &str,
+    port: u16,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let target = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+    let mut head = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(value) = proxy_authorization(config)
+        && let Ok(value) = value.to_str()
+    {
+        head.push_str(&format!("Proxy-Authorization: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+
+    // Read byte by byte so nothing after the response head is consumed.
+    let mut response = Vec::with_capacity(256);
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE {
+            return Err(proxy_error("proxy CONNECT response is too large"));
+        }
+        let byte = stream.read_u8().await?;
+        response.push(byte);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(proxy_error(format!(
+            "proxy refused CONNECT to {target}: {status_line}"
+        )));
+    }
+    Ok(())
+}
+
+/// Runs the SOCKS5 handshake and `CONNECT` to `host:port` (RFC 1928, RFC 1929).
+async fn socks5_connect<S>(
+    stream: &mut S,
+    config: &UpstreamProxyConfig,
+    host: &str,
+    port: u16,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let credentials = config.credentials();
+    let greeting: &[u8] = if credentials.is_some() {
+        &[5, 2, 0, 2]
+    } else {
+        &[5, 1, 0]
+    };
+    stream.write_all(greeting).await?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    if choice[0] != 5 {
+        return Err(proxy_error("upstream is not a SOCKS5 proxy"));
+    }
+    match (choice[1], credentials) {
+        (0, _) => {}
+        (2, Some((username, password))) => {
+            let mut auth = vec![1, username.len() as u8];
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).await?;
+            let mut reply = [0u8; 2];
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                return Err(proxy_error("SOCKS5 proxy rejected the credentials"));
+            }
+        }
+        _ => {
+            return Err(proxy_error(
+                "SOCKS5 proxy accepts none of the offered methods",
+            ));
+        }
+    }
+
+    let mut request = vec![5, 1, 0];
+    match host.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(std::net::IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            let name = host.as_bytes();
+            if name.len() > 255 {
+                return Err(proxy_error(format!(
+                    "host name {host:?} is too long for SOCKS5"
+                )));
+            }
+            request.push(3);
+            request.push(name.len() as u8);
+            request.extend_from_slice(name);
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(proxy_error(format!(
+            "SOCKS5 proxy failed to connect to {host}:{port} (reply {})",
+            reply[1]
+        )));
+    }
+    let bound_len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => usize::from(stream.read_u8().await?),
+        other => return Err(proxy_error(format!("unknown SOCKS5 address type {other}"))),
+    };
+    let mut bound = vec![0u8; bound_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// Opens a byte tunnel to `host:port` as the upstream proxy configuration says.
+///
+/// `resolver` resolves `host` for direct connections and the proxy host
+/// otherwise; proxies resolve targets themselves.
+pub async fn open_tunnel(
+    config: &UpstreamProxyConfig,
+    tls: &Arc<ClientConfig>,
+    resolver: &DnsResolver,
+    host: &str,
+    port: u16,
+) -> io::Result<UpstreamStream> {
+    let kind = match config.protocol {
+        UpstreamProxyProtocol::Direct => StreamKind::Tcp(resolver.connect(host, port).await?),
+        UpstreamProxyProtocol::Http | UpstreamProxyProtocol::Https => {
+            let mut kind = connect_proxy(config, tls, resolver).await?;
+            match &mut kind {
+                StreamKind::Tcp(stream) => http_connect(stream, config, host, port).await?,
+                StreamKind::Tls(stream) => {
+                    http_connect(stream.as_mut(), config, host, port).await?
+                }
+            }
+            kind
+        }
+        UpstreamProxyProtocol::Socks5 => {
+            let mut stream = connect_proxy_tcp(config, resolver).await?;
+            socks5_connect(&mut stream, config, host, port).await?;
+            StreamKind::Tcp(stream)
+        }
+    };
+    Ok(UpstreamStream {
+        kind,
+        absolute_form: false,
+    })
+}
+
+/// Connector for `HttpClient` that reaches targets through one upstream proxy.
+///
+/// Plain `http` targets behind an HTTP proxy are sent to the proxy in absolute
+/// form; everything else goes through a tunnel.
+#[derive(Clone)]
+pub struct UpstreamConnector {
+    config: Arc<UpstreamProxyConfig>,
+    tls: Arc<ClientConfig>,
+    resolver: DnsResolver,
+}
+
+impl UpstreamConnector {
+    /// `tls` is used for `https` proxies; it should not offer ALPN.
+    pub fn new(config: UpstreamProxyConfig, tls: Arc<ClientConfig>, resolver: DnsResolver) -> Self {
+        Self {
+            config: Arc::new(config),
+            tls,
+            resolver,
+        }
+    }
+}
+
+impl Service<Uri> for UpstreamConnector {
+    type Response = TokioIo<UpstreamStream>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let config = Arc::clone(&self.config);
+        let tls = Arc::clone(&self.tls);
+        let resolver = self.resolver.clone();
+        Box::pin(async move {
+            let host = uri
+                .host()
+                .ok_or_else(|| proxy_error(format!("missing host in {uri}")))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string();
+            let is_https = uri.scheme_str() == Some("https");
+            let port = uri.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+
+            let forwards_plain_http = !is_https
+                && matches!(
+                    config.protocol,
+                    UpstreamProxyProtocol::Http | UpstreamProxyProtocol::Https
+                );
+            let stream = if forwards_plain_http {
+                UpstreamStream {
+                    kind: connect_proxy(&config, &tls, &resolver).await?,
+                    absolute_form: true,
+                }
+            } else {
+                open_tunnel(&config, &tls, &resolver, &host, port).await?
+            };
+            Ok(TokioIo::new(stream))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{io::duplex, net::TcpListener};
+
+    fn socks_config(username: Option<&str>) -> UpstreamProxyConfig {
+        UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Socks5,
+            address: "127.0.0.1:1080".to_string(),
+            username: username.map(str::to_string),
+            password: username.map(|_| "secret".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_socks5_handshake_authenticates_and_connects_by_name() {
+        let (mut client, mut proxy) = duplex(1024);
+        let config = socks_config(Some("alice"));
+        let handshake =
+            tokio::spawn(
+                async move { socks5_connect(&mut client, &config, "api.test", 8443).await },
+            );
+
+        let mut greeting = [0u8; 4];
+        proxy.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [5, 2, 0, 2]);
+        proxy.write_all(&[5, 2]).await.unwrap();
+
+        let mut auth = [0u8; 1 + 1 + 5 + 1 + 6];
+        proxy.read_exact(&mut auth).await.unwrap();
+        assert_eq!(&auth[2..7], b"alice");
+        assert_eq!(&auth[8..], b"secret");
+        proxy.write_all(&[1, 0]).await.unwrap();
+
+        let mut request = [0u8; 4 + 1 + 8 + 2];
+        proxy.read_exact(&mut request).await.unwrap();
+        assert_eq!(&request[..5], &[5, 1, 0, 3, 8]);
+        assert_eq!(&request[5..13], b"api.test");
+        assert_eq!(u16::from_be_bytes([request[13], request[14]]), 8443);
+        proxy
+            .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0x1f, 0x90])
+            .await
+            .unwrap();
+
+        handshake.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_sends_credentials_and_checks_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let proxy = tokio::spawn(async move {
+            let mut heads = Vec::new();
+            for reply in [
+                "HTTP/1.1 200 Connection established\r\n\r\n",
+                "HTTP/1.1 407 Proxy Authentication Required\r\n\r\n",
+            ] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut head = Vec::new();
+                while !head.ends_with(b"\r\n\r\n") {
+                    head.push(socket.read_u8().await.unwrap());
+                }
+                heads.push(String::from_utf8(head).unwrap());
+                socket.write_all(reply.as_bytes()).await.unwrap();
+                if reply.contains("200") {
+                    socket.write_all(b"tunneled").await.unwrap();
+                }
+            }
+            heads
+        });
+
+        let config = UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Http,
+            address,
+            username: Some("alice".to_string()),
+            password: Some("secret".to_string()),
+        };
+        let tls = Arc::new(lynx_cert::gen_client_config_by_cert(None).unwrap());
+        let resolver = DnsResolver::default();
+        let mut stream = open_tunnel(&config, &tls, &resolver, "api.test", 443)
+            .await
+            .unwrap();
+        let mut tunneled = [0u8; 8];
+        stream.read_exact(&mut tunneled).await.unwrap();
+        assert_eq!(&tunneled, b"tunneled");
+
+        let refused = open_tunnel(&config, &tls, &resolver, "api.test", 443).await;
+        assert!(refused.err().unwrap().to_string().contains("407"));
+
+        let heads = proxy.await.unwrap();
+        assert!(heads[0].starts_with("CONNECT api.test:443 HTTP/1.1\r\n"));
+        assert!(heads[0].contains("Proxy-Authorization: Basic YWxpY2U6c2VjcmV0\r\n"));
+    }
+}
@@ -3,9 +3,10 @@ use std::{fmt::Debug, sync::Arc};
 use anyhow::Result;
 use lynx_cert::gen_client_config_by_cert;
 use rcgen::Certificate;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_tungstenite::{
-    Connector, WebSocketStream, connect_async_tls_with_config,
-    tungstenite::client::IntoClientRequest,
+    Connector, MaybeTlsStream, WebSocketStream, client_async_tls_with_config,
+    connect_async_tls_with_config, tungstenite::client::IntoClientRequest,
 };
 use tracing::trace;
 
@@ -35,6 +36,27 @@ impl WebsocketClient {
 
         Ok(websocket_stream)
     }
+
+    /// Runs the handshake over an already open `stream`, such as a tunnel
+    /// through an upstream proxy.
+    pub async fn request_through<R, S>(
+        &self,
+        req: R,
+        stream: S,
+    ) -> Result<(
+        WebSocketStream<MaybeTlsStream<S>>,
+        http::Response<Option<Vec<u8>>>,
+    )>
+    where
+        R: IntoClientRequest + Unpin + Debug,
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        trace!(?req, "websocket upstream handshake through tunnel");
+        let websocket_stream =
+            client_async_tls_with_config(req, stream, None, Some(self.connector.clone())).await?;
+
+        Ok(websocket_stream)
+    }
 }
 
 impl WebsocketClientBuilder {
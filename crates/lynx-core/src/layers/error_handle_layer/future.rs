@@ -11,6 +11,7 @@ use serde_json::to_string;
 use tracing::error;
 
 use crate::error::{CoreError, root_cause_message};
+use crate::layers::request_processing_layer::fault_handler_trait::AbortConnection;
 
 pin_project! {
     pub struct ErrorHandleFuture<F> {
@@ -29,6 +30,14 @@ where
         let this = self.project();
         let res = ready!(this.f.poll(cx));
 
+        // A fault handler asked for the connection to drop before anything is
+        // written; an error is the only way to make hyper close it silently.
+        if let Ok(response) = &res
+            && response.extensions().get::<AbortConnection>().is_some()
+        {
+            return Poll::Ready(Err(anyhow::anyhow!("connection reset by fault handler")));
+        }
+
         if let Err(err) = &res {
             let _error_reason = format_error_chain(err);
             let error_response = err
@@ -16,6 +16,8 @@ use crate::{
 use super::message_package_layer::{
     MessageEventLayerExt, message_event_store::MessageEventStoreExtensionsExt,
 };
+use super::request_processing_layer::breakpoint_handler_trait::BreakpointRegistry;
+use crate::proxy::websocket_connections::WebSocketConnections;
 
 #[derive(Debug, Clone)]
 pub struct ExtendExtensionsService<S> {
@@ -101,5 +103,11 @@ pub fn clone_extensions(ex: &Extensions) -> Result<Extensions> {
     nex.insert(message_event_cannel);
     nex.insert(message_event_store);
     nex.insert(store);
+    if let Some(breakpoints) = ex.get::<Arc<BreakpointRegistry>>() {
+        nex.insert(breakpoints.clone());
+    }
+    if let Some(connections) = ex.get::<Arc<WebSocketConnections>>() {
+        nex.insert(connections.clone());
+    }
     Ok(nex)
 }
@@ -3,10 +3,10 @@ use axum::extract::Request;
 use http::HeaderMap;
 use http_body::Body as HttpBody;
 use hyper_tungstenite::is_upgrade_request;
-use lynx_dsl::{RequestFacts, compile_match_expr, eval_program};
-use lynx_storage::dao::capture_rules_dao::{CaptureRule, CaptureRulesDao};
+use lynx_dsl::RequestFacts;
+use lynx_storage::dao::capture_rules_dao::CaptureRulesDao;
 use lynx_storage::dao::net_request_dao::{CaptureSwitchDao, RecordingStatus};
-use tracing::warn;
+use lynx_storage::dao::request_processing_dao::RuleMatchContext;
 
 use crate::layers::extend_extension_layer::DataStoreExtensionsExt;
 
@@ -35,18 +35,17 @@ impl CaptureGate {
         }
 
         let rules_dao = CaptureRulesDao::new(store);
-        let rules = rules_dao.get_rules().await?;
+        let rules = rules_dao.get_compiled_rules().await?;
 
         let facts = request_facts_from_request(request);
 
         // 1) Ignore has absolute priority.
-        if any_rule_matches(&rules.ignore_rules, &facts) {
+        if rules.ignore.any_matches(&facts) {
             return Ok(CaptureDecision::Bypass { reason: "ignored" });
         }
 
         // 2) Focus is capture whitelist.
-        let has_focus = rules.focus_rules.iter().any(|r| r.enabled);
-        if has_focus && !any_rule_matches(&rules.focus_rules, &facts) {
+        if rules.has_focus && !rules.focus.any_matches(&facts) {
             return Ok(CaptureDecision::Bypass {
                 reason: "not_focused",
             });
@@ -56,29 +55,6 @@ impl CaptureGate {
     }
 }
 
-fn any_rule_matches(rules: &[CaptureRule], facts: &RequestFacts) -> bool {
-    for rule in rules {
-        if !rule.enabled {
-            continue;
-        }
-        let expr = rule.match_expr.trim();
-        if expr.is_empty() {
-            continue;
-        }
-        let program = match compile_match_expr(expr) {
-            Ok(program) => program,
-            Err(err) => {
-                warn!("capture rule {} invalid DSL: {}", rule.id, err);
-                continue;
-            }
-        };
-        if eval_program(&program, facts) {
-            return true;
-        }
-    }
-    false
-}
-
 fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts {
     let uri = request.uri();
     let query = uri.query().map(|q| q.to_string());
@@ -106,6 +82,15 @@ fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts
         let val = value.to_str().unwrap_or_default();
         builder = builder.header(key, val);
     }
+    if let Some(context) = request.extensions().get::<RuleMatchContext>() {
+        builder = builder.sample_seed(context.sample_seed);
+        if let Some(client) = &context.client {
+            builder = builder.client(client.clone());
+        }
+        if let Some(minute) = context.minute_of_day {
+            builder = builder.minute_of_day(minute);
+        }
+    }
     builder.build()
 }
 
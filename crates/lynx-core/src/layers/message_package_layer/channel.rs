@@ -2,6 +2,7 @@ use axum::extract::Request;
 use bytes::Bytes;
 use hyper::body::Body;
 use std::fmt::Debug;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::{spawn, sync::broadcast};
 use tokio_stream::{StreamExt, wrappers::ReceiverStream};
@@ -142,6 +143,13 @@ impl MessageEventChannel {
         let _ = self.send_event(MessageEvent::OnTunnelEnd(request_id)).await;
     }
 
+    #[instrument(skip_all)]
+    pub async fn dispatch_on_server_connected(&self, request_id: TraceId, addr: SocketAddr) {
+        let _ = self
+            .send_event(MessageEvent::OnServerConnected(request_id, addr))
+            .await;
+    }
+
     #[instrument(skip_all)]
     pub async fn dispatch_on_error(&self, request_id: TraceId, error_reason: String) {
         let _ = self
@@ -8,6 +8,7 @@ use super::message_event_data::{
 use super::message_event_store::{MessageEvent, MessageEventStoreValue, MessageEventTimings};
 
 const MAX_WEBSOCKET_LOG_MESSAGES: usize = 1_000;
+const MAX_SCRIPT_LOGS: usize = 200;
 
 /// 处理单个消息事件
 pub async fn handle_message_event_single(
@@ -224,6 +225,29 @@ pub async fn handle_message_event_single(
                 status: TunnelStatus::Connected,
             });
         }
+        MessageEvent::OnScriptLog(id, log) => {
+            let Some(mut value) = cache.get_mut(&id) else {
+                return Ok(());
+            };
+            if value.script_logs.len() >= MAX_SCRIPT_LOGS {
+                value.script_logs.remove(0);
+            }
+            value.script_logs.push(log);
+        }
+        MessageEvent::OnServerConnected(id, addr) => {
+            let Some(mut value) = cache.get_mut(&id) else {
+                return Ok(());
+            };
+            value.server_addr = Some(addr.to_string());
+        }
+        MessageEvent::OnMirrorResult(id, result) => {
+            let Some(mut value) = cache.get_mut(&id) else {
+                return Ok(());
+            };
+            value.mirrors.push(result);
+        }
+        // Held exchanges are live state, listed by `breakpoint.list.get`.
+        MessageEvent::OnBreakpointHit(..) | MessageEvent::OnBreakpointResolved(..) => {}
     }
     Ok(())
 }
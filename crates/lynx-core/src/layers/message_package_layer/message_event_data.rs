@@ -20,6 +20,9 @@ pub struct MatchedRuleInfo {
     pub rule_id: i32,
     pub name: String,
     pub priority: i32,
+    /// Set for rules using `--sample`: whether the sample fired for this request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled: Option<bool>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
@@ -152,6 +155,140 @@ impl From<&Message> for WebSocketMessage {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptLogLevel {
+    Info,
+    Debug,
+    Error,
+}
+
+/// A line written by a script handler hook (`print`, `debug`, or a failure).
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptLog {
+    pub timestamp: u64,
+    pub level: ScriptLogLevel,
+    pub hook: String,
+    pub message: String,
+}
+
+/// How a `Mirror` target answered compared with the primary response.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorResult {
+    /// URL the request was mirrored to
+    pub target: String,
+    /// Trace the mirrored exchange was captured as, when capture is on
+    pub trace_id: Option<String>,
+    pub status: Option<u16>,
+    /// Why no response came back
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub diff: Option<MirrorDiff>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorDiff {
+    /// Set when the status codes differ
+    pub status: Option<MirrorStatusChange>,
+    pub headers: Vec<MirrorHeaderChange>,
+    /// Whether the bodies differ; JSON bodies are compared by value
+    pub body_changed: bool,
+    /// A body was too large to compare, so `body_changed` and `body` are left empty
+    #[serde(default)]
+    pub body_truncated: bool,
+    /// Differences between JSON bodies, by JSON pointer
+    pub body: Vec<MirrorBodyChange>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorStatusChange {
+    pub primary: u16,
+    pub mirror: u16,
+}
+
+/// A header whose value differs; `None` where it is missing.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorHeaderChange {
+    pub name: String,
+    pub primary: Option<String>,
+    pub mirror: Option<String>,
+}
+
+/// A JSON value that differs; `None` where the path is missing.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorBodyChange {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<serde_json::Value>,
+}
+
+/// Which side of an exchange a `Breakpoint` handler is holding.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakpointStage {
+    #[default]
+    Request,
+    Response,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BreakpointHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// An exchange held by a `Breakpoint` handler, waiting for `breakpoint.resume`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HeldBreakpoint {
+    pub id: String,
+    pub trace_id: String,
+    pub stage: BreakpointStage,
+    pub method: String,
+    pub url: String,
+    /// Upstream status, for held responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub headers: Vec<BreakpointHeader>,
+    /// Decoded body, when it is UTF-8 text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Decoded body otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_base64: Option<String>,
+    /// Milliseconds since the epoch.
+    pub held_at: u64,
+    /// When the exchange continues unchanged if nobody resumes it.
+    pub expires_at: u64,
+}
+
+/// How a held exchange was let go.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakpointOutcome {
+    Resumed,
+    Edited,
+    Aborted,
+    TimedOut,
+    /// The client went away while the exchange was held.
+    Cancelled,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointResolution {
+    pub id: String,
+    pub outcome: BreakpointOutcome,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum WebSocketDirection {
     ClientToServer,
@@ -4,13 +4,16 @@ use dashmap::DashMap;
 use dashmap::mapref::one::RefMut;
 use http::Extensions;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
 use super::message_event_data::{
-    MessageEventRequest, MessageEventResponse, MessageEventTunnel, MessageEventWebSocket,
-    TunnelStatus, WebSocketLog, WebSocketStatus,
+    BreakpointResolution, HeldBreakpoint, MessageEventRequest, MessageEventResponse,
+    MessageEventTunnel, MessageEventWebSocket, MirrorResult, ScriptLog, TunnelStatus, WebSocketLog,
+    WebSocketStatus,
 };
 use crate::layers::trace_id_layer::service::TraceId;
 
@@ -43,6 +46,21 @@ pub enum MessageEvent {
     OnTunnelStart(TraceId),
     OnTunnelEnd(TraceId),
 
+    OnScriptLog(TraceId, ScriptLog),
+
+    /// The TCP peer the request or tunnel was sent to: the resolved target,
+    /// or the proxy when one is used.
+    OnServerConnected(TraceId, SocketAddr),
+
+    /// A `Mirror` target's answer compared with this trace's response.
+    OnMirrorResult(TraceId, MirrorResult),
+
+    /// A `Breakpoint` handler started holding this trace's request or response.
+    OnBreakpointHit(TraceId, HeldBreakpoint),
+
+    /// A held exchange was resumed, aborted, timed out or abandoned.
+    OnBreakpointResolved(TraceId, BreakpointResolution),
+
     OnError(TraceId, String),
 }
 
@@ -179,6 +197,12 @@ pub struct MessageEventStoreValue {
     pub messages: Option<MessageEventWebSocket>,
     pub tunnel: Option<MessageEventTunnel>,
     pub timings: MessageEventTimings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub script_logs: Vec<ScriptLog>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_addr: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<MirrorResult>,
     /// Timestamp (ms since epoch) when this entry reached a terminal state.
     #[serde(skip)]
     pub completed_at: Option<u64>,
@@ -195,6 +219,9 @@ impl MessageEventStoreValue {
             messages: None,
             tunnel: None,
             timings: MessageEventTimings::default(),
+            script_logs: Vec::new(),
+            server_addr: None,
+            mirrors: Vec::new(),
             completed_at: None,
         }
     }
@@ -302,9 +329,15 @@ impl MessageEventStoreValue {
     }
 }
 
+/// How many distinct hosts [`MessageEventCache::recent_hosts`] remembers.
+const MAX_RECENT_HOSTS: usize = 200;
+
 #[derive(Debug, Clone)]
 pub struct MessageEventCache {
     map: Arc<DashMap<TraceId, MessageEventStoreValue>>,
+    /// Hosts of recent requests, most recent first. Kept apart from `map`,
+    /// which evicts completed entries quickly.
+    recent_hosts: Arc<Mutex<VecDeque<String>>>,
 }
 
 impl From<MessageEventStoreValue> for CacheValue {
@@ -319,7 +352,10 @@ impl MessageEventCache {
     pub fn new() -> Self {
         let map = Arc::new(DashMap::new());
 
-        Self { map }
+        Self {
+            map,
+            recent_hosts: Arc::new(Mutex::new(VecDeque::new())),
+        }
     }
 
     pub fn clear(&self) {
@@ -366,10 +402,30 @@ impl MessageEventCache {
     }
 
     pub async fn insert(&self, key: TraceId, value: MessageEventStoreValue) {
+        if let Some(host) = value.request.as_ref().and_then(request_host) {
+            self.remember_host(host);
+        }
         self.map.insert(key.clone(), value);
         self.remove_oldest_completed();
     }
 
+    fn remember_host(&self, host: String) {
+        let Ok(mut hosts) = self.recent_hosts.lock() else {
+            return;
+        };
+        hosts.retain(|known| *known != host);
+        hosts.push_front(host);
+        hosts.truncate(MAX_RECENT_HOSTS);
+    }
+
+    /// Distinct hosts of recent requests, most recent first.
+    pub fn recent_hosts(&self) -> Vec<String> {
+        self.recent_hosts
+            .lock()
+            .map(|hosts| hosts.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     pub fn get(&self, key: &TraceId) -> Option<MessageEventStoreValue> {
         self.map.get(key).map(|v| v.clone())
     }
@@ -453,6 +509,22 @@ impl MessageEventStoreExtensionsExt for Extensions {
     }
 }
 
+/// Host of a captured request: the URL authority, or the Host header for
+/// origin-form requests.
+fn request_host(request: &MessageEventRequest) -> Option<String> {
+    let uri: http::Uri = request.url.parse().ok()?;
+    if let Some(host) = uri.host() {
+        return Some(host.to_ascii_lowercase());
+    }
+    let header = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("host"))?
+        .1;
+    let authority: http::uri::Authority = header.parse().ok()?;
+    Some(authority.host().to_ascii_lowercase())
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -534,4 +606,28 @@ mod tests {
         );
         assert!(cache.get(&ids[10]).is_some());
     }
+
+    #[tokio::test]
+    async fn remembers_recent_hosts_after_eviction() {
+        let cache = MessageEventCache::new();
+        for (index, url) in [
+            "https://api.example.com/v1",
+            "/health",
+            "https://API.example.com/v2",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let id: TraceId = Arc::new(format!("host-{index}"));
+            let mut value = completed_value(&id, now_ms() - (11 * 60 * 1_000));
+            value.request = Some(MessageEventRequest {
+                url: url.to_string(),
+                headers: [("Host".to_string(), "localhost:3000".to_string())].into(),
+                ..Default::default()
+            });
+            cache.insert(id, value).await;
+        }
+
+        assert_eq!(cache.recent_hosts(), vec!["api.example.com", "localhost"]);
+    }
 }
@@ -28,7 +28,9 @@ use super::message_event_data::copy_body_stream;
 use super::message_event_data::{MatchedRuleInfo, MatchedRulesExt};
 use super::message_event_store::MessageEvent;
 use crate::layers::extend_extension_layer::DataStoreExtensionsExt;
-use lynx_storage::dao::request_processing_dao::RequestProcessingDao;
+use crate::layers::request_processing_layer::fault_handler_trait::AbortConnection;
+use crate::proxy_server::ClientAddrRequestExt;
+use lynx_storage::dao::request_processing_dao::{RequestProcessingDao, RuleMatchContext};
 
 pub trait MessageEventLayerExt {
     fn get_message_event_cannel(&self) -> Arc<MessageEventChannel>;
@@ -92,6 +94,8 @@ where
         let (copy_stream, old_body) = copy_body_stream(AxumBody::new(old_body));
 
         let mut request = Request::from_parts(part, old_body);
+        let match_context = rule_match_context(request.extensions(), &trace_id);
+        request.extensions_mut().insert(match_context);
 
         let defer_request_end = is_connect_req(&request) || is_websocket_req(&request);
 
@@ -112,16 +116,17 @@ where
             // can carry the field.
             let store = request.extensions().get_data_store();
             let dao = RequestProcessingDao::new(store.clone());
-            if let Ok(matching_rules) = dao.find_matching_rules(&request).await {
-                let matched: Vec<MatchedRuleInfo> = matching_rules
+            if let Ok(rule_matches) = dao.find_rule_matches(&request).await {
+                let matched: Vec<MatchedRuleInfo> = rule_matches
                     .into_iter()
-                    .filter(|r| r.enabled)
-                    .filter_map(|r| {
-                        let id = r.id?;
+                    .filter(|m| m.rule.enabled)
+                    .filter_map(|m| {
+                        let id = m.rule.id?;
                         Some(MatchedRuleInfo {
                             rule_id: id,
-                            name: r.name,
-                            priority: r.priority,
+                            name: m.rule.name,
+                            priority: m.rule.priority,
+                            sampled: m.sampled,
                         })
                     })
                     .collect();
@@ -151,6 +156,17 @@ where
     }
 }
 
+/// Seed `--sample` from the trace id so the capture record and request
+/// processing see the same outcome for a request.
+fn rule_match_context(extensions: &Extensions, trace_id: &TraceId) -> RuleMatchContext {
+    let mut hasher = std::hash::DefaultHasher::new();
+    std::hash::Hash::hash(trace_id.as_str(), &mut hasher);
+    let client = extensions
+        .get_client_addr()
+        .map(|client| client.addr().ip().to_string());
+    RuleMatchContext::now(client, std::hash::Hasher::finish(&hasher))
+}
+
 #[derive(Clone)]
 pub struct ProxyMessageEventService<S> {
     pub service: S,
@@ -228,6 +244,15 @@ where
                     .await;
                 guard.completed = true;
                 match result {
+                    Ok(res) if res.extensions().get::<AbortConnection>().is_some() => {
+                        message_event_channel_clone
+                            .dispatch_on_error(
+                                trace_id,
+                                "Connection reset by fault handler".to_string(),
+                            )
+                            .await;
+                        Ok(res)
+                    }
                     Ok(res) => {
                         let (part, old_body) = res.into_parts();
                         let (copy_stream, old_body) = copy_body_stream(old_body);
@@ -0,0 +1,117 @@
+use async_compression::tokio::bufread::{
+    BrotliDecoder, BrotliEncoder, DeflateDecoder, GzipDecoder, GzipEncoder, ZlibDecoder,
+    ZlibEncoder,
+};
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderValue,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+
+/// Lower-cased `Content-Encoding` of a message, ignoring `identity`.
+pub fn content_encoding(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty() && value != "identity")
+}
+
+/// Frames a buffered body of `len` bytes with `content-length` instead of
+/// chunked transfer encoding.
+pub fn set_content_length(headers: &mut HeaderMap, len: usize) {
+    headers.remove(TRANSFER_ENCODING);
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+}
+
+/// Decodes a buffered body by its content encoding.
+///
+/// Returns `Ok(None)` for encodings we don't understand, so callers can leave
+/// such bodies untouched.
+pub async fn decode_body(encoding: &str, body: &[u8]) -> std::io::Result<Option<Bytes>> {
+    let decoded = match encoding {
+        "gzip" | "x-gzip" => read_all(GzipDecoder::new(BufReader::new(body))).await?,
+        // HTTP deflate is usually zlib-wrapped, but some servers send raw deflate.
+        "deflate" => match read_all(ZlibDecoder::new(BufReader::new(body))).await {
+            Ok(decoded) => decoded,
+            Err(_) => read_all(DeflateDecoder::new(BufReader::new(body))).await?,
+        },
+        "br" => read_all(BrotliDecoder::new(BufReader::new(body))).await?,
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
+
+/// Encodes a body for a content encoding, the inverse of [`decode_body`].
+///
+/// Returns `Ok(None)` for encodings we can't produce.
+pub async fn encode_body(encoding: &str, body: &[u8]) -> std::io::Result<Option<Bytes>> {
+    let encoded = match encoding {
+        "gzip" | "x-gzip" => read_all(GzipEncoder::new(BufReader::new(body))).await?,
+        "deflate" => read_all(ZlibEncoder::new(BufReader::new(body))).await?,
+        "br" => read_all(BrotliEncoder::new(BufReader::new(body))).await?,
+        _ => return Ok(None),
+    };
+    Ok(Some(encoded))
+}
+
+async fn read_all(mut reader: impl AsyncRead + Unpin) -> std::io::Result<Bytes> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).await?;
+    Ok(Bytes::from(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::write::{BrotliEncoder, GzipEncoder, ZlibEncoder};
+    use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+    async fn encode<W: AsyncWrite + Unpin>(mut encoder: W, data: &[u8]) -> W {
+        encoder.write_all(data).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        encoder
+    }
+
+    #[tokio::test]
+    async fn decodes_supported_encodings() {
+        let text = b"hello body codec";
+        let gzip = encode(GzipEncoder::new(Vec::new()), text)
+            .await
+            .into_inner();
+        let zlib = encode(ZlibEncoder::new(Vec::new()), text)
+            .await
+            .into_inner();
+        let br = encode(BrotliEncoder::new(Vec::new()), text)
+            .await
+            .into_inner();
+
+        for (encoding, body) in [("gzip", gzip), ("deflate", zlib), ("br", br)] {
+            let decoded = decode_body(encoding, &body).await.unwrap();
+            assert_eq!(decoded.as_deref(), Some(&text[..]), "{encoding}");
+        }
+        assert!(decode_body("zstd", text).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn encoding_round_trips() {
+        let text = b"hello body codec";
+        for encoding in ["gzip", "deflate", "br"] {
+            let encoded = encode_body(encoding, text).await.unwrap().unwrap();
+            let decoded = decode_body(encoding, &encoded).await.unwrap();
+            assert_eq!(decoded.as_deref(), Some(&text[..]), "{encoding}");
+        }
+        assert!(encode_body("zstd", text).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn identity_is_not_an_encoding() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(content_encoding(&headers), None);
+        headers.insert(CONTENT_ENCODING, "identity".parse().unwrap());
+        assert_eq!(content_encoding(&headers), None);
+        headers.insert(CONTENT_ENCODING, " GZIP".parse().unwrap());
+        assert_eq!(content_encoding(&headers).as_deref(), Some("gzip"));
+    }
+}
@@ -0,0 +1,322 @@
+use bytes::Bytes;
+use http::{HeaderMap, header::CONTENT_ENCODING};
+use lynx_storage::dao::request_processing_dao::handlers::BodyTransform;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+use super::body_codec::{content_encoding, decode_body, encode_body, set_content_length};
+use super::regex_cache::cached_regex;
+use crate::error::{CoreError, CoreResult};
+
+/// Rewrites a buffered message body.
+///
+/// `replacement` replaces the body wholesale, then `transforms` run in order
+/// on the decoded text. The result is re-encoded with the message's
+/// `content-encoding`, and `content-length` is set to match.
+pub async fn rewrite_body(
+    headers: &mut HeaderMap,
+    body: Bytes,
+    replacement: Option<&str>,
+    transforms: &[BodyTransform],
+) -> CoreResult<Bytes> {
+    if replacement.is_none() && transforms.is_empty() {
+        return Ok(body);
+    }
+    let encoding = content_encoding(headers);
+
+    let plain = match (replacement, &encoding) {
+        (Some(replacement), _) => Bytes::from(replacement.to_string()),
+        (None, None) => body.clone(),
+        (None, Some(encoding)) => match decode_body(encoding, &body).await {
+            Ok(Some(decoded)) => decoded,
+            Ok(None) => {
+                tracing::warn!("Skipping body transforms: unsupported content-encoding {encoding}");
+                return Ok(body);
+            }
+            Err(e) => {
+                tracing::warn!("Skipping body transforms: body is not valid {encoding}: {e}");
+                return Ok(body);
+            }
+        },
+    };
+    let transformed = apply_transforms(plain, transforms)?;
+
+    let encoded = match &encoding {
+        Some(encoding) => match encode_body(encoding, &transformed).await? {
+            Some(encoded) => encoded,
+            None => {
+                // Only reachable with a replacement body: send it unencoded.
+                headers.remove(CONTENT_ENCODING);
+                transformed
+            }
+        },
+        None => transformed,
+    };
+    set_content_length(headers, encoded.len());
+    Ok(encoded)
+}
+
+/// Runs `transforms` over a decoded body. JSON transforms leave bodies that
+/// are not JSON, and documents a patch does not apply to, unchanged.
+pub fn apply_transforms(body: Bytes, transforms: &[BodyTransform]) -> CoreResult<Bytes> {
+    let mut body = body;
+    for transform in transforms {
+        if let BodyTransform::RegexReplace {
+            pattern,
+            replacement,
+        } = transform
+        {
+            let regex = cached_regex(pattern).map_err(|e| CoreError::Validation {
+                message: format!("invalid body transform regex {pattern:?}: {e}"),
+            })?;
+            let text = String::from_utf8_lossy(&body);
+            body = Bytes::from(regex.replace_all(&text, replacement.as_str()).into_owned());
+            continue;
+        }
+
+        let Ok(mut doc) = serde_json::from_slice::<Value>(&body) else {
+            tracing::warn!("Skipping JSON body transform: body is not JSON");
+            continue;
+        };
+        match apply_json_transform(&mut doc, transform) {
+            Ok(()) => body = Bytes::from(serde_json::to_vec(&doc).map_err(anyhow::Error::from)?),
+            Err(e) => tracing::warn!("Skipping JSON body transform: {e}"),
+        }
+    }
+    Ok(body)
+}
+
+fn apply_json_transform(doc: &mut Value, transform: &BodyTransform) -> Result<(), String> {
+    match transform {
+        BodyTransform::RegexReplace { .. } => Ok(()),
+        BodyTransform::JsonPatch { patch } => {
+            let patch: json_patch::Patch = serde_json::from_value(Value::Array(patch.clone()))
+                .map_err(|e| format!("invalid JSON Patch: {e}"))?;
+            json_patch::patch(doc, &patch).map_err(|e| e.to_string())
+        }
+        BodyTransform::MergePatch { patch } => {
+            json_patch::merge(doc, patch);
+            Ok(())
+        }
+        BodyTransform::JsonPathSet { path, value } => json_path_set(doc, path, value),
+        BodyTransform::JsonPathDelete { path } => json_path_delete(doc, path),
+    }
+}
+
+fn parse_json_path(path: &str) -> Result<JsonPath, String> {
+    JsonPath::parse(path).map_err(|e| format!("invalid JSONPath {path:?}: {e}"))
+}
+
+/// JSON Pointers of every node `path` matches, in document order.
+fn json_path_pointers(doc: &Value, path: &JsonPath) -> Vec<String> {
+    path.query_located(doc)
+        .locations()
+        .map(|location| location.to_json_pointer())
+        .collect()
+}
+
+fn json_path_set(doc: &mut Value, path: &str, value: &Value) -> Result<(), String> {
+    let pointers = json_path_pointers(doc, &parse_json_path(path)?);
+    if !pointers.is_empty() {
+        for pointer in pointers {
+            if let Some(node) = doc.pointer_mut(&pointer) {
+                *node = value.clone();
+            }
+        }
+        return Ok(());
+    }
+
+    // Nothing matched: add the named member to every object the parent path matches.
+    let Some((parent, member)) = split_last_member(path) else {
+        return Ok(());
+    };
+    let parents = json_path_pointers(doc, &parse_json_path(&parent)?);
+    for pointer in parents {
+        if let Some(Value::Object(object)) = doc.pointer_mut(&pointer) {
+            object.insert(member.clone(), value.clone());
+        }
+    }
+    Ok(())
+}
+
+fn json_path_delete(doc: &mut Value, path: &str) -> Result<(), String> {
+    let pointers = json_path_pointers(doc, &parse_json_path(path)?);
+    // Back to front, so removing an array element doesn't shift the ones still to go.
+    for pointer in pointers.iter().rev() {
+        let Some((parent, token)) = pointer.rsplit_once('/') else {
+            continue;
+        };
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match doc.pointer_mut(parent) {
+            Some(Value::Object(object)) => {
+                object.remove(&token);
+            }
+            Some(Value::Array(array)) => {
+                if let Ok(index) = token.parse::<usize>()
+                    && index < array.len()
+                {
+                    array.remove(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Splits `$.a.b` / `$.a['b']` into the parent path and the member name.
+fn split_last_member(path: &str) -> Option<(String, String)> {
+    let path = path.trim();
+    if let Some(stripped) = path.strip_suffix(']') {
+        let (parent, segment) = stripped.rsplit_once('[')?;
+        let member = segment.trim();
+        let member = member
+            .strip_prefix('\'')
+            .and_then(|m| m.strip_suffix('\''))
+            .or_else(|| member.strip_prefix('"').and_then(|m| m.strip_suffix('"')))?;
+        return Some((parent.to_string(), member.to_string()));
+    }
+    let (parent, member) = path.rsplit_once('.')?;
+    let valid = !member.is_empty()
+        && !parent.ends_with('.')
+        && member
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| (parent.to_string(), member.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::write::GzipEncoder;
+    use http::{
+        HeaderValue,
+        header::{CONTENT_LENGTH, TRANSFER_ENCODING},
+    };
+    use serde_json::json;
+    use tokio::io::AsyncWriteExt;
+
+    fn json_body(value: Value) -> Bytes {
+        Bytes::from(serde_json::to_vec(&value).unwrap())
+    }
+
+    fn apply_json(value: Value, transforms: Vec<BodyTransform>) -> Value {
+        let body = apply_transforms(json_body(value), &transforms).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn regex_replace_uses_capture_groups() {
+        let transforms = [BodyTransform::RegexReplace {
+            pattern: r#""price":\s*(\d+)"#.to_string(),
+            replacement: r#""price": $1.99"#.to_string(),
+        }];
+        let body = apply_transforms(Bytes::from(r#"{"price": 10}"#), &transforms).unwrap();
+        assert_eq!(body, r#"{"price": 10.99}"#);
+    }
+
+    #[test]
+    fn json_patch_and_merge_patch() {
+        let doc = json!({ "user": { "name": "alice", "role": "guest" }, "debug": true });
+        let patched = apply_json(
+            doc,
+            vec![
+                BodyTransform::JsonPatch {
+                    patch: vec![
+                        json!({ "op": "replace", "path": "/user/role", "value": "admin" }),
+                        json!({ "op": "add", "path": "/user/tags", "value": ["beta"] }),
+                    ],
+                },
+                BodyTransform::MergePatch {
+                    patch: json!({ "debug": null, "user": { "name": "bob" } }),
+                },
+            ],
+        );
+        assert_eq!(
+            patched,
+            json!({ "user": { "name": "bob", "role": "admin", "tags": ["beta"] } })
+        );
+    }
+
+    #[test]
+    fn failing_patches_leave_the_body_alone() {
+        let doc = json!({ "a": 1 });
+        let patched = apply_json(
+            doc.clone(),
+            vec![BodyTransform::JsonPatch {
+                patch: vec![
+                    json!({ "op": "test", "path": "/a", "value": 2 }),
+                    json!({ "op": "remove", "path": "/a" }),
+                ],
+            }],
+        );
+        assert_eq!(patched, doc);
+
+        let text = apply_transforms(
+            Bytes::from("not json"),
+            &[BodyTransform::JsonPathDelete {
+                path: "$.a".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(text, "not json");
+    }
+
+    #[test]
+    fn json_path_set_and_delete() {
+        let doc = json!({
+            "items": [
+                { "id": 1, "secret": "x", "price": 5 },
+                { "id": 2, "secret": "y", "price": 7 }
+            ],
+            "meta": {}
+        });
+        let patched = apply_json(
+            doc,
+            vec![
+                BodyTransform::JsonPathSet {
+                    path: "$.items[*].price".to_string(),
+                    value: json!(0),
+                },
+                BodyTransform::JsonPathSet {
+                    path: "$.meta['mocked-by']".to_string(),
+                    value: json!("lynx"),
+                },
+                BodyTransform::JsonPathDelete {
+                    path: "$..secret".to_string(),
+                },
+                BodyTransform::JsonPathDelete {
+                    path: "$.items[?@.id == 1]".to_string(),
+                },
+            ],
+        );
+        assert_eq!(
+            patched,
+            json!({ "items": [{ "id": 2, "price": 0 }], "meta": { "mocked-by": "lynx" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn rewrite_body_keeps_the_content_encoding() {
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(br#"{"a":1}"#).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        let gzipped = Bytes::from(encoder.into_inner());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        let transforms = [BodyTransform::MergePatch {
+            patch: json!({ "b": 2 }),
+        }];
+        let body = rewrite_body(&mut headers, gzipped, None, &transforms)
+            .await
+            .unwrap();
+
+        assert_eq!(headers[CONTENT_ENCODING], "gzip");
+        assert_eq!(headers[CONTENT_LENGTH], body.len().to_string());
+        assert!(headers.get(TRANSFER_ENCODING).is_none());
+        let decoded = decode_body("gzip", &body).await.unwrap().unwrap();
+        assert_eq!(decoded, r#"{"a":1,"b":2}"#);
+    }
+}
@@ -0,0 +1,566 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use axum::{body::Body, response::Response};
+use base64::{Engine as _, engine::general_purpose};
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+    header::{CONTENT_ENCODING, HOST},
+};
+use http_body_util::BodyExt;
+use lynx_storage::dao::request_processing_dao::handlers::BreakpointConfig;
+use serde::Deserialize;
+use tokio::sync::oneshot;
+
+use super::body_codec::set_content_length;
+use super::body_codec::{content_encoding, decode_body, encode_body};
+use super::fault_handler_trait::AbortConnection;
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    layers::{
+        message_package_layer::{
+            MessageEventChannel,
+            message_event_data::{
+                BreakpointHeader, BreakpointOutcome, BreakpointResolution, BreakpointStage,
+                HeldBreakpoint,
+            },
+            message_event_store::MessageEvent,
+        },
+        trace_id_layer::service::TraceId,
+    },
+    utils::full,
+};
+
+/// How a held exchange should continue, as sent with `breakpoint.resume`.
+///
+/// Fields left out keep their held value. `headers` replaces the whole header
+/// list, and an edited body is re-encoded with the message's
+/// `content-encoding`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointResume {
+    pub id: String,
+    #[serde(default)]
+    pub abort: bool,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub headers: Option<Vec<BreakpointHeader>>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub body_base64: Option<String>,
+}
+
+impl BreakpointResume {
+    fn is_edit(&self) -> bool {
+        self.method.is_some()
+            || self.url.is_some()
+            || self.status.is_some()
+            || self.headers.is_some()
+            || self.body.is_some()
+            || self.body_base64.is_some()
+    }
+
+    fn validate(&self, stage: BreakpointStage) -> CoreResult<()> {
+        let invalid = |message: String| CoreError::Validation { message };
+        if stage == BreakpointStage::Request && self.status.is_some() {
+            return Err(invalid("status can only be edited on a response".into()));
+        }
+        if stage == BreakpointStage::Response && (self.method.is_some() || self.url.is_some()) {
+            return Err(invalid(
+                "method and url can only be edited on a request".into(),
+            ));
+        }
+        if let Some(method) = &self.method {
+            method
+                .parse::<Method>()
+                .map_err(|_| invalid(format!("invalid method {method:?}")))?;
+        }
+        if let Some(url) = &self.url {
+            url.parse::<Uri>()
+                .map_err(|e| invalid(format!("invalid url {url:?}: {e}")))?;
+        }
+        if let Some(status) = self.status {
+            StatusCode::from_u16(status)
+                .map_err(|_| invalid(format!("invalid status code {status}")))?;
+        }
+        if let Some(headers) = &self.headers {
+            header_map(headers)?;
+        }
+        self.body_bytes()?;
+        Ok(())
+    }
+
+    fn body_bytes(&self) -> CoreResult<Option<Bytes>> {
+        match (&self.body, &self.body_base64) {
+            (Some(_), Some(_)) => Err(CoreError::Validation {
+                message: "body and bodyBase64 are mutually exclusive".into(),
+            }),
+            (Some(body), None) => Ok(Some(Bytes::from(body.clone()))),
+            (None, Some(encoded)) => general_purpose::STANDARD
+                .decode(encoded)
+                .map(|body| Some(Bytes::from(body)))
+                .map_err(|e| CoreError::Validation {
+                    message: format!("invalid bodyBase64: {e}"),
+                }),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+fn header_map(headers: &[BreakpointHeader]) -> CoreResult<HeaderMap> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for header in headers {
+        let name =
+            HeaderName::from_bytes(header.name.as_bytes()).map_err(|_| CoreError::Validation {
+                message: format!("invalid header name {:?}", header.name),
+            })?;
+        let value = HeaderValue::from_str(&header.value).map_err(|_| CoreError::Validation {
+            message: format!("invalid value for header {:?}", header.name),
+        })?;
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+struct HeldEntry {
+    breakpoint: HeldBreakpoint,
+    resume: oneshot::Sender<BreakpointResume>,
+}
+
+/// Exchanges currently held by `Breakpoint` handlers, shared by the proxy and
+/// the UI connection.
+#[derive(Default)]
+pub struct BreakpointRegistry {
+    held: Mutex<HashMap<String, HeldEntry>>,
+    next_id: AtomicU64,
+}
+
+impl BreakpointRegistry {
+    /// Held exchanges, oldest first.
+    pub fn list(&self) -> Vec<HeldBreakpoint> {
+        let held = self.held.lock().expect("breakpoint lock poisoned");
+        let mut list: Vec<_> = held
+            .values()
+            .map(|entry| entry.breakpoint.clone())
+            .collect();
+        list.sort_by_key(|breakpoint| breakpoint.held_at);
+        list
+    }
+
+    /// Lets a held exchange continue, edited or aborted as `resume` says.
+    pub fn resume(&self, resume: BreakpointResume) -> CoreResult<()> {
+        let mut held = self.held.lock().expect("breakpoint lock poisoned");
+        let Some(entry) = held.get(&resume.id) else {
+            return Err(CoreError::NotFound {
+                message: format!("no held breakpoint {:?}", resume.id),
+            });
+        };
+        resume.validate(entry.breakpoint.stage)?;
+        if let Some(entry) = held.remove(&resume.id) {
+            // The receiver is gone only if the exchange was dropped meanwhile.
+            let _ = entry.resume.send(resume);
+        }
+        Ok(())
+    }
+
+    /// Holds `breakpoint` until it is resumed or `timeout` passes, which
+    /// continues it unchanged.
+    async fn hold(
+        &self,
+        mut breakpoint: HeldBreakpoint,
+        timeout: Duration,
+        trace_id: TraceId,
+        channel: Option<Arc<MessageEventChannel>>,
+    ) -> Option<BreakpointResume> {
+        let id = format!("bp-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let held_at = chrono::Utc::now().timestamp_millis() as u64;
+        breakpoint.id = id.clone();
+        breakpoint.held_at = held_at;
+        breakpoint.expires_at = held_at + timeout.as_millis() as u64;
+
+        let (tx, rx) = oneshot::channel();
+        self.held.lock().expect("breakpoint lock poisoned").insert(
+            id.clone(),
+            HeldEntry {
+                breakpoint: breakpoint.clone(),
+                resume: tx,
+            },
+        );
+        if let Some(channel) = &channel {
+            channel.sync_send_event(MessageEvent::OnBreakpointHit(trace_id.clone(), breakpoint));
+        }
+
+        // Removes the entry and reports the outcome however the hold ends,
+        // including when the client goes away while it is held.
+        let mut guard = HoldGuard {
+            registry: self,
+            id,
+            trace_id,
+            channel,
+            outcome: BreakpointOutcome::Cancelled,
+        };
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(resume)) => {
+                guard.outcome = if resume.abort {
+                    BreakpointOutcome::Aborted
+                } else if resume.is_edit() {
+                    BreakpointOutcome::Edited
+                } else {
+                    BreakpointOutcome::Resumed
+                };
+                Some(resume)
+            }
+            Ok(Err(_)) => None,
+            Err(_) => {
+                tracing::debug!("Breakpoint {} timed out, continuing", guard.id);
+                guard.outcome = BreakpointOutcome::TimedOut;
+                None
+            }
+        }
+    }
+}
+
+struct HoldGuard<'a> {
+    registry: &'a BreakpointRegistry,
+    id: String,
+    trace_id: TraceId,
+    channel: Option<Arc<MessageEventChannel>>,
+    outcome: BreakpointOutcome,
+}
+
+impl Drop for HoldGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut held) = self.registry.held.lock() {
+            held.remove(&self.id);
+        }
+        if let Some(channel) = &self.channel {
+            channel.sync_send_event(MessageEvent::OnBreakpointResolved(
+                self.trace_id.clone(),
+                BreakpointResolution {
+                    id: self.id.clone(),
+                    outcome: self.outcome,
+                },
+            ));
+        }
+    }
+}
+
+/// What a response breakpoint needs from the request side, captured before
+/// the request is handed to the upstream service.
+#[derive(Clone, Default)]
+pub struct BreakpointContext {
+    registry: Option<Arc<BreakpointRegistry>>,
+    channel: Option<Arc<MessageEventChannel>>,
+    trace_id: Option<TraceId>,
+    method: String,
+    url: String,
+}
+
+impl BreakpointContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            registry: request
+                .extensions()
+                .get::<Arc<BreakpointRegistry>>()
+                .cloned(),
+            channel: request
+                .extensions()
+                .get::<Arc<MessageEventChannel>>()
+                .cloned(),
+            trace_id: request.extensions().get::<TraceId>().cloned(),
+            method: request.method().to_string(),
+            url: request.uri().to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for BreakpointConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        if !self.phase.holds_request() {
+            return Ok(HandleRequestType::Request(request));
+        }
+        let context = BreakpointContext::from_request(&request);
+        let (Some(registry), Some(trace_id)) = (context.registry, context.trace_id) else {
+            tracing::warn!("Breakpoint skipped: no breakpoint registry on the request");
+            return Ok(HandleRequestType::Request(request));
+        };
+
+        let (mut parts, body) = request.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let held = HeldBreakpoint {
+            trace_id: trace_id.to_string(),
+            stage: BreakpointStage::Request,
+            method: parts.method.to_string(),
+            url: parts.uri.to_string(),
+            headers: header_list(&parts.headers),
+            ..snapshot_body(&parts.headers, &body).await
+        };
+        let timeout = Duration::from_millis(self.timeout_ms());
+
+        let Some(resume) = registry
+            .hold(held, timeout, trace_id, context.channel)
+            .await
+        else {
+            return Ok(HandleRequestType::Request(Req::from_parts(
+                parts,
+                full(body),
+            )));
+        };
+        if resume.abort {
+            let mut response = Response::new(Body::empty());
+            response.extensions_mut().insert(AbortConnection);
+            return Ok(HandleRequestType::Response(response));
+        }
+
+        if let Some(method) = &resume.method {
+            parts.method = method.parse().map_err(|e| anyhow!("{e}"))?;
+        }
+        if let Some(headers) = &resume.headers {
+            parts.headers = header_map(headers)?;
+        }
+        if let Some(url) = &resume.url {
+            parts.uri = merge_url(&parts.uri, url)?;
+            // Keep Host in step with a new authority.
+            if let Some(authority) = parts.uri.authority()
+                && parts.headers.contains_key(HOST)
+            {
+                parts.headers.insert(
+                    HOST,
+                    HeaderValue::from_str(authority.as_str()).map_err(|e| anyhow!(e))?,
+                );
+            }
+        }
+        let body = apply_body(&mut parts.headers, body, &resume).await?;
+        Ok(HandleRequestType::Request(Req::from_parts(
+            parts,
+            full(body),
+        )))
+    }
+}
+
+/// Holds the upstream response for `config` until it is resumed, then passes
+/// it on, edited, or aborts the connection.
+pub async fn breakpoint_response(
+    config: &BreakpointConfig,
+    response: Response,
+    context: &BreakpointContext,
+) -> CoreResult<Response> {
+    if !config.phase.holds_response() {
+        return Ok(response);
+    }
+    let (Some(registry), Some(trace_id)) = (&context.registry, &context.trace_id) else {
+        return Ok(response);
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+    let held = HeldBreakpoint {
+        trace_id: trace_id.to_string(),
+        stage: BreakpointStage::Response,
+        method: context.method.clone(),
+        url: context.url.clone(),
+        status: Some(parts.status.as_u16()),
+        headers: header_list(&parts.headers),
+        ..snapshot_body(&parts.headers, &body).await
+    };
+    let timeout = Duration::from_millis(config.timeout_ms());
+
+    let Some(resume) = registry
+        .hold(held, timeout, trace_id.clone(), context.channel.clone())
+        .await
+    else {
+        return Ok(Response::from_parts(parts, Body::from(body)));
+    };
+    if resume.abort {
+        parts.extensions.insert(AbortConnection);
+        return Ok(Response::from_parts(parts, Body::empty()));
+    }
+
+    if let Some(status) = resume.status {
+        parts.status = StatusCode::from_u16(status).map_err(|e| anyhow!(e))?;
+    }
+    if let Some(headers) = &resume.headers {
+        parts.headers = header_map(headers)?;
+    }
+    let body = apply_body(&mut parts.headers, body, &resume).await?;
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn header_list(headers: &HeaderMap) -> Vec<BreakpointHeader> {
+    headers
+        .iter()
+        .map(|(name, value)| BreakpointHeader {
+            name: name.to_string(),
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        })
+        .collect()
+}
+
+/// The held body as shown for editing: decoded, as text when it is UTF-8.
+async fn snapshot_body(headers: &HeaderMap, body: &Bytes) -> HeldBreakpoint {
+    let decoded = match content_encoding(headers) {
+        Some(encoding) => match decode_body(&encoding, body).await {
+            Ok(Some(decoded)) => decoded,
+            _ => body.clone(),
+        },
+        None => body.clone(),
+    };
+    match String::from_utf8(decoded.to_vec()) {
+        Ok(text) => HeldBreakpoint {
+            body: Some(text),
+            ..Default::default()
+        },
+        Err(_) => HeldBreakpoint {
+            body_base64: Some(general_purpose::STANDARD.encode(&decoded)),
+            ..Default::default()
+        },
+    }
+}
+
+/// Replaces the body when `resume` edits it, re-encoding it with the
+/// (possibly edited) `content-encoding`.
+async fn apply_body(
+    headers: &mut HeaderMap,
+    body: Bytes,
+    resume: &BreakpointResume,
+) -> CoreResult<Bytes> {
+    let Some(plain) = resume.body_bytes()? else {
+        return Ok(body);
+    };
+    let encoded = match content_encoding(headers) {
+        Some(encoding) => match encode_body(&encoding, &plain).await? {
+            Some(encoded) => encoded,
+            None => {
+                headers.remove(CONTENT_ENCODING);
+                plain
+            }
+        },
+        None => plain,
+    };
+    set_content_length(headers, encoded.len());
+    Ok(encoded)
+}
+
+/// Applies an edited URL, keeping the scheme and authority it leaves out.
+fn merge_url(current: &Uri, url: &str) -> CoreResult<Uri> {
+    let edited = url
+        .parse::<Uri>()
+        .map_err(|e| anyhow!("invalid url {url:?}: {e}"))?
+        .into_parts();
+    let mut builder = Uri::builder();
+    if let Some(scheme) = edited.scheme.or(current.scheme().cloned()) {
+        builder = builder.scheme(scheme);
+    }
+    if let Some(authority) = edited.authority.or(current.authority().cloned()) {
+        builder = builder.authority(authority);
+    }
+    if let Some(path_and_query) = edited.path_and_query.or(current.path_and_query().cloned()) {
+        builder = builder.path_and_query(path_and_query);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_resume_edits_per_stage() {
+        let resume = BreakpointResume {
+            id: "bp-1".into(),
+            status: Some(404),
+            ..Default::default()
+        };
+        assert!(resume.validate(BreakpointStage::Response).is_ok());
+        assert!(resume.validate(BreakpointStage::Request).is_err());
+
+        let both_bodies = BreakpointResume {
+            id: "bp-1".into(),
+            body: Some("a".into()),
+            body_base64: Some("YQ==".into()),
+            ..Default::default()
+        };
+        assert!(both_bodies.validate(BreakpointStage::Request).is_err());
+
+        let bad_header = BreakpointResume {
+            id: "bp-1".into(),
+            headers: Some(vec![BreakpointHeader {
+                name: "bad header".into(),
+                value: "x".into(),
+            }]),
+            ..Default::default()
+        };
+        assert!(bad_header.validate(BreakpointStage::Request).is_err());
+    }
+
+    #[test]
+    fn merges_relative_urls() -> anyhow::Result<()> {
+        let current: Uri = "http://example.com/a?x=1".parse()?;
+        assert_eq!(
+            merge_url(&current, "/b?y=2")?.to_string(),
+            "http://example.com/b?y=2"
+        );
+        assert_eq!(
+            merge_url(&current, "https://other.test/c")?.to_string(),
+            "https://other.test/c"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resume_releases_held_exchange() {
+        let registry = Arc::new(BreakpointRegistry::default());
+        let holder = registry.clone();
+        let hold = tokio::spawn(async move {
+            holder
+                .hold(
+                    HeldBreakpoint::default(),
+                    Duration::from_secs(5),
+                    Arc::new("trace".to_string()),
+                    None,
+                )
+                .await
+        });
+        let id = loop {
+            if let Some(held) = registry.list().first() {
+                break held.id.clone();
+            }
+            tokio::task::yield_now().await;
+        };
+
+        assert!(matches!(
+            registry.resume(BreakpointResume {
+                id: "bp-missing".into(),
+                ..Default::default()
+            }),
+            Err(CoreError::NotFound { .. })
+        ));
+        registry
+            .resume(BreakpointResume {
+                id: id.clone(),
+                abort: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let resume = hold.await.unwrap().expect("resumed");
+        assert!(resume.abort);
+        assert!(registry.list().is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+//! Cassette files shared by the `Record` and `Replay` handlers.
+//!
+//! A cassette is JSON Lines: one [`CassetteEntry`] per recorded exchange,
+//! appended in the order responses arrived.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use base64::{Engine as _, engine::general_purpose};
+use bytes::Bytes;
+use http::{HeaderMap, Method, Uri};
+use lynx_storage::{DataStore, dao::projects_dao::ProjectsDao};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{CoreError, CoreResult};
+
+/// Prefix of a header value that was recorded as a digest.
+const REDACTED_PREFIX: &str = "redacted:sha256:";
+
+/// One recorded request/response pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CassetteEntry {
+    pub request: CassetteRequest,
+    pub response: CassetteResponse,
+    /// RFC 3339
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CassetteRequest {
+    pub method: String,
+    pub url: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Lower-case names; repeated headers are joined with `, `.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Hex SHA-256 of the request body.
+    pub body_sha256: String,
+}
+
+impl CassetteRequest {
+    pub fn new(method: &Method, uri: &Uri, headers: &HeaderMap, body: &[u8]) -> Self {
+        let mut joined: BTreeMap<String, String> = BTreeMap::new();
+        for (name, value) in headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            joined
+                .entry(name.as_str().to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+        Self {
+            method: method.as_str().to_string(),
+            url: uri.to_string(),
+            path: uri.path().to_string(),
+            query: uri.query().map(str::to_string),
+            headers: joined,
+            body_sha256: body_sha256(body),
+        }
+    }
+
+    /// Lower-case `host[:port]` the request was sent to, from the URL or else
+    /// the `host` header.
+    pub fn authority(&self) -> Option<String> {
+        self.url
+            .parse::<Uri>()
+            .ok()
+            .and_then(|uri| uri.authority().map(|authority| authority.to_string()))
+            .or_else(|| self.headers.get("host").cloned())
+            .map(|authority| authority.to_ascii_lowercase())
+    }
+
+    /// Replaces the values of headers `redacts` picks with their digest.
+    pub fn redact_headers(&mut self, redacts: impl Fn(&str) -> bool) {
+        for (name, value) in &mut self.headers {
+            if redacts(name) {
+                *value = redacted(value);
+            }
+        }
+    }
+
+    /// Query parameters, sorted so that their order does not matter.
+    pub fn sorted_query(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = self
+            .query
+            .as_deref()
+            .map(|query| {
+                form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        pairs.sort();
+        pairs
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CassetteResponse {
+    pub status: u16,
+    /// In order, repeated names kept (`set-cookie`).
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// The body as sent, when it is UTF-8 text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// The body as sent otherwise (compressed or binary).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_base64: Option<String>,
+}
+
+impl CassetteResponse {
+    pub fn new(status: u16, headers: &HeaderMap, body: &[u8]) -> Self {
+        let mut response = Self {
+            status,
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.as_str().to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            body: None,
+            body_base64: None,
+        };
+        response.set_body(body);
+        response
+    }
+
+    pub fn set_body(&mut self, body: &[u8]) {
+        (self.body, self.body_base64) = match std::str::from_utf8(body) {
+            Ok(text) => (Some(text.to_string()), None),
+            Err(_) => (None, Some(general_purpose::STANDARD.encode(body))),
+        };
+    }
+
+    /// Replaces the values of headers `redacts` picks with their digest.
+    pub fn redact_headers(&mut self, redacts: impl Fn(&str) -> bool) {
+        for (name, value) in &mut self.headers {
+            if redacts(name) {
+                *value = redacted(value);
+            }
+        }
+    }
+
+    pub fn body_bytes(&self) -> CoreResult<Bytes> {
+        match (&self.body, &self.body_base64) {
+            (_, Some(encoded)) => general_purpose::STANDARD
+                .decode(encoded)
+                .map(Bytes::from)
+                .map_err(|e| CoreError::Validation {
+                    message: format!("cassette body is not valid base64: {e}"),
+                }),
+            (Some(text), None) => Ok(Bytes::from(text.clone())),
+            (None, None) => Ok(Bytes::new()),
+        }
+    }
+}
+
+/// What a redacted header value is recorded as; equal values give equal
+/// digests, so redacted headers can still be matched on.
+pub fn redacted(value: &str) -> String {
+    format!("{REDACTED_PREFIX}{}", body_sha256(value.as_bytes()))
+}
+
+pub fn is_redacted(value: &str) -> bool {
+    value.starts_with(REDACTED_PREFIX)
+}
+
+fn body_sha256(body: &[u8]) -> String {
+    Sha256::digest(body)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The cassette file of a rule in `project`: absolute paths as-is, otherwise
+/// under the project directory, or `<data dir>/cassettes/<project>`.
+pub async fn cassette_path(
+    store: &Arc<DataStore>,
+    project: &str,
+    cassette: &str,
+) -> CoreResult<PathBuf> {
+    let fallback = store.cassettes_dir().join(project);
+    ProjectsDao::new(store.clone())
+        .project_file_path(project, cassette, &fallback)
+        .await
+        .map_err(|e| CoreError::Internal {
+            operation: "cassette lookup",
+            source: e,
+        })
+}
+
+/// Identifies one version of a file for the load caches.
+#[derive(PartialEq)]
+pub(super) struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    pub(super) fn new(metadata: &std::fs::Metadata) -> Self {
+        Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        }
+    }
+}
+
+type LoadedCassette = (FileStamp, Arc<Vec<CassetteEntry>>);
+
+static CASSETTE_CACHE: LazyLock<Mutex<HashMap<PathBuf, LoadedCassette>>> =
+    LazyLock::new(Default::default);
+
+/// Serializes appends so concurrent recordings never interleave lines.
+static APPEND_LOCK: LazyLock<tokio::sync::Mutex<()>> = LazyLock::new(Default::default);
+
+/// Entries of the cassette at `path`, re-read when the file changes. A
+/// missing file is an empty cassette; lines that don't parse are skipped.
+pub async fn load_cassette(path: &Path) -> CoreResult<Arc<Vec<CassetteEntry>>> {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Arc::default()),
+        Err(e) => return Err(e.into()),
+    };
+    let stamp = FileStamp::new(&metadata);
+    {
+        let cache = CASSETTE_CACHE.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if let Some((cached, entries)) = cache.get(path)
+            && *cached == stamp
+        {
+            return Ok(entries.clone());
+        }
+    }
+
+    let text = tokio::fs::read_to_string(path).await?;
+    let entries: Vec<CassetteEntry> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("Skipping {}:{}: {e}", path.display(), index + 1);
+                None
+            }
+        })
+        .collect();
+    let entries = Arc::new(entries);
+    CASSETTE_CACHE
+        .lock()
+        .map_err(|e| anyhow!(e.to_string()))?
+        .insert(path.to_path_buf(), (stamp, entries.clone()));
+    Ok(entries)
+}
+
+pub async fn append_entry(path: &Path, entry: &CassetteEntry) -> CoreResult<()> {
+    let mut line = serde_json::to_string(entry).map_err(|e| anyhow!(e))?;
+    line.push('\n');
+    let _guard = APPEND_LOCK.lock().await;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use axum::{body::Body, response::Response};
+use http::{
+    HeaderMap, HeaderValue, Method, StatusCode,
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        CONTENT_TYPE, ORIGIN, VARY,
+    },
+};
+use lynx_storage::dao::request_processing_dao::handlers::CorsConfig;
+
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+};
+
+/// The browser origin of a request, kept for fixing up its response.
+#[derive(Debug, Clone, Default)]
+pub struct CorsContext {
+    origin: Option<String>,
+}
+
+impl CorsContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            origin: request
+                .headers()
+                .get(ORIGIN)
+                .and_then(|origin| origin.to_str().ok())
+                .map(str::to_string),
+        }
+    }
+}
+
+fn is_preflight(request: &Req) -> bool {
+    request.method() == Method::OPTIONS
+        && request.headers().contains_key(ORIGIN)
+        && request
+            .headers()
+            .contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+fn header_value(value: &str) -> CoreResult<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|_| CoreError::Validation {
+        message: format!("invalid CORS header value {value:?}"),
+    })
+}
+
+fn joined(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| value.trim())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn add_vary(headers: &mut HeaderMap, name: &'static str) {
+    let listed = headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim() == "*" || value.trim().eq_ignore_ascii_case(name));
+    if !listed {
+        headers.append(VARY, HeaderValue::from_static(name));
+    }
+}
+
+/// Sets the headers shared by preflight and actual responses; `false` when
+/// the origin is not allowed.
+fn apply_allow_origin(
+    config: &CorsConfig,
+    headers: &mut HeaderMap,
+    origin: &str,
+) -> CoreResult<bool> {
+    let Some(allow_origin) = config.allow_origin_value(origin) else {
+        return Ok(false);
+    };
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, header_value(allow_origin)?);
+    if config.varies_by_origin() {
+        add_vary(headers, "origin");
+    }
+    if config.allow_credentials {
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_CREDENTIALS,
+            HeaderValue::from_static("true"),
+        );
+    }
+    Ok(true)
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for CorsConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        if !is_preflight(&request) {
+            return Ok(HandleRequestType::Request(request));
+        }
+        let origin = CorsContext::from_request(&request)
+            .origin
+            .unwrap_or_default();
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())?;
+        let headers = response.headers_mut();
+        if !apply_allow_origin(self, headers, &origin)? {
+            let mut forbidden = Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Body::from(format!("CORS origin {origin:?} is not allowed")))?;
+            forbidden.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; charset=utf-8"),
+            );
+            return Ok(HandleRequestType::Response(forbidden));
+        }
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_METHODS,
+            header_value(&joined(&self.allowed_methods))?,
+        );
+        if !self.allowed_headers.is_empty() {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_HEADERS,
+                header_value(&joined(&self.allowed_headers))?,
+            );
+        } else if let Some(requested) = request.headers().get(ACCESS_CONTROL_REQUEST_HEADERS) {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, requested.clone());
+            add_vary(headers, "access-control-request-headers");
+        }
+        if let Some(max_age) = self.max_age_secs {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+        }
+        Ok(HandleRequestType::Response(response))
+    }
+}
+
+/// Replaces upstream `Access-Control-*` headers with the configured ones.
+/// Responses to requests without an `Origin` are left alone.
+pub fn cors_response(
+    config: &CorsConfig,
+    mut response: Response,
+    context: &CorsContext,
+) -> CoreResult<Response> {
+    let Some(origin) = &context.origin else {
+        return Ok(response);
+    };
+    let headers = response.headers_mut();
+    for name in [
+        ACCESS_CONTROL_ALLOW_ORIGIN,
+        ACCESS_CONTROL_ALLOW_CREDENTIALS,
+        ACCESS_CONTROL_ALLOW_METHODS,
+        ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE,
+    ] {
+        headers.remove(name);
+    }
+    if apply_allow_origin(config, headers, origin)? && !config.expose_headers.is_empty() {
+        headers.insert(
+            ACCESS_CONTROL_EXPOSE_HEADERS,
+            header_value(&joined(&config.expose_headers))?,
+        );
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::empty;
+    use anyhow::Result;
+    use http::Request;
+
+    fn preflight(origin: &str) -> Result<Req> {
+        Ok(Request::builder()
+            .method(Method::OPTIONS)
+            .uri("http://api.example.com/items")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type, x-token")
+            .body(empty())?)
+    }
+
+    async fn answer(config: &CorsConfig, request: Req) -> Result<Response> {
+        match config.handle_request(request).await? {
+            HandleRequestType::Response(response) => Ok(response),
+            HandleRequestType::Request(_) => panic!("preflight passed on"),
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_preflight_for_allowed_origins_only() -> Result<()> {
+        let config = CorsConfig {
+            allowed_origins: vec!["http://localhost:5173".into()],
+            allow_credentials: true,
+            max_age_secs: Some(600),
+            ..CorsConfig::default()
+        };
+
+        let response = answer(&config, preflight("http://localhost:5173")?).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://localhost:5173"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type, x-token"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_MAX_AGE], "600");
+        assert!(
+            headers[ACCESS_CONTROL_ALLOW_METHODS]
+                .to_str()?
+                .contains("PUT")
+        );
+        let vary: Vec<_> = headers.get_all(VARY).iter().collect();
+        assert_eq!(vary, ["origin", "access-control-request-headers"]);
+
+        let response = answer(&config, preflight("http://localhost:3000")?).await?;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let plain_options = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("http://api.example.com/items")
+            .body(empty())?;
+        assert!(matches!(
+            config.handle_request(plain_options).await?,
+            HandleRequestType::Request(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn replaces_upstream_cors_headers() -> Result<()> {
+        let config = CorsConfig {
+            expose_headers: vec!["x-request-id".into()],
+            ..CorsConfig::default()
+        };
+        let upstream = || {
+            Response::builder()
+                .header(ACCESS_CONTROL_ALLOW_ORIGIN, "https://prod.example.com")
+                .header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true")
+                .body(Body::empty())
+        };
+        let context = CorsContext {
+            origin: Some("http://localhost:5173".into()),
+        };
+
+        let response = cors_response(&config, upstream()?, &context)?;
+        let headers = response.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert_eq!(headers[ACCESS_CONTROL_EXPOSE_HEADERS], "x-request-id");
+        assert!(!headers.contains_key(ACCESS_CONTROL_ALLOW_CREDENTIALS));
+        assert!(!headers.contains_key(VARY));
+
+        let response = cors_response(&config, upstream()?, &CorsContext::default())?;
+        assert_eq!(
+            response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://prod.example.com"
+        );
+        Ok(())
+    }
+}
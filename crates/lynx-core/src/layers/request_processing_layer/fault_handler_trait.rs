@@ -0,0 +1,403 @@
+use anyhow::anyhow;
+use axum::{body::Body as AxumBody, response::Response};
+use bytes::Bytes;
+use http::{
+    HeaderValue,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING},
+};
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use lynx_storage::dao::request_processing_dao::handlers::{Fault, FaultConfig, ResetPhase};
+use pin_project_lite::pin_project;
+use rand::Rng;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::time::{Duration, Sleep};
+
+use super::body_codec::set_content_length;
+use super::body_codec::{content_encoding, decode_body, encode_body};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{common::Req, error::CoreResult};
+
+/// Response extension asking the connection to be dropped before the response
+/// head is written. The error handle layer turns it into a service error.
+#[derive(Debug, Clone, Copy)]
+pub struct AbortConnection;
+
+/// How long a failing body waits before erroring, so what was already sent
+/// (at least the response head) is flushed to the client first.
+pub(super) const FLUSH_GRACE: Duration = Duration::from_millis(50);
+
+#[async_trait::async_trait]
+impl HandlerTrait for FaultConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        // Faults are applied to the upstream response
+        Ok(HandleRequestType::Request(request))
+    }
+
+    async fn handle_response(&self, response: Response) -> CoreResult<Response> {
+        inject_fault(self, response).await
+    }
+}
+
+/// Rolls each fault's probability in order and applies the first that fires.
+pub async fn inject_fault(config: &FaultConfig, response: Response) -> CoreResult<Response> {
+    let fault = {
+        let mut rng = rand::thread_rng();
+        config
+            .faults
+            .iter()
+            .find(|rule| rng.gen_range(0.0..1.0) < rule.probability)
+            .map(|rule| &rule.fault)
+    };
+    match fault {
+        Some(fault) => {
+            tracing::debug!("Injecting fault {:?}", fault);
+            apply_fault(fault, response).await
+        }
+        None => Ok(response),
+    }
+}
+
+async fn apply_fault(fault: &Fault, response: Response) -> CoreResult<Response> {
+    let (mut parts, body) = response.into_parts();
+    let body = match fault {
+        Fault::Reset {
+            phase: ResetPhase::BeforeHeaders,
+        } => {
+            parts.extensions.insert(AbortConnection);
+            body
+        }
+        Fault::Reset {
+            phase: ResetPhase::AfterHeaders,
+        } => AxumBody::new(FaultBody::new(body).abort_at(0)),
+        Fault::Truncate { bytes } => AxumBody::new(FaultBody::new(body).abort_at(*bytes)),
+        Fault::WrongContentLength { delta } => {
+            let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+            let declared = (body.len() as i64).saturating_add(*delta).max(0);
+            set_content_length(&mut parts.headers, declared as usize);
+            AxumBody::from(body)
+        }
+        Fault::Stall {
+            after_bytes,
+            duration_ms,
+        } => AxumBody::new(
+            FaultBody::new(body).stall(*after_bytes, Duration::from_millis(*duration_ms)),
+        ),
+        Fault::MalformedChunked => {
+            parts.headers.remove(CONTENT_LENGTH);
+            parts
+                .headers
+                .insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+            AxumBody::new(FaultBody::new(body).abort_at_end())
+        }
+        Fault::CorruptGzip => {
+            let raw = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+            let plain = match content_encoding(&parts.headers) {
+                Some(encoding) => decode_body(&encoding, &raw).await.ok().flatten(),
+                None => None,
+            }
+            .unwrap_or(raw);
+            let mut gzip = encode_body("gzip", &plain)
+                .await?
+                .map(|gzip| gzip.to_vec())
+                .unwrap_or_default();
+            corrupt_gzip(&mut gzip);
+            parts
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            set_content_length(&mut parts.headers, gzip.len());
+            AxumBody::from(gzip)
+        }
+    };
+    Ok(Response::from_parts(parts, body))
+}
+
+/// Flips a byte in the middle of the deflate stream and inverts the CRC32
+/// trailer, so both inflating and checksum verification fail.
+fn corrupt_gzip(gzip: &mut [u8]) {
+    // 10-byte member header, deflate data, 8-byte CRC32 + ISIZE trailer
+    let len = gzip.len();
+    if len < 18 {
+        return;
+    }
+    let middle = 10 + (len - 18) / 2;
+    gzip[middle] ^= 0xff;
+    for byte in &mut gzip[len - 8..len - 4] {
+        *byte ^= 0xff;
+    }
+}
+
+pin_project! {
+    /// Forwards a body while pausing or failing it at byte offsets.
+    pub struct FaultBody<B> {
+        #[pin]
+        inner: B,
+        sent: u64,
+        abort: Option<AbortPoint>,
+        // `None` offset stalls after the first chunk
+        stall: Option<(Option<u64>, Duration)>,
+        delay: Option<Pin<Box<Sleep>>>,
+        held: Option<Bytes>,
+        aborting: bool,
+        done: bool,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AbortPoint {
+    At(u64),
+    End,
+}
+
+impl<B> FaultBody<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            sent: 0,
+            abort: None,
+            stall: None,
+            delay: None,
+            held: None,
+            aborting: false,
+            done: false,
+        }
+    }
+
+    /// Fails the body once `bytes` bytes have been sent.
+    pub fn abort_at(mut self, bytes: u64) -> Self {
+        self.abort = Some(AbortPoint::At(bytes));
+        self
+    }
+
+    /// Fails the body where it would have ended.
+    pub fn abort_at_end(mut self) -> Self {
+        self.abort = Some(AbortPoint::End);
+        self
+    }
+
+    /// Pauses once after `after_bytes` bytes, or after the first chunk.
+    pub fn stall(mut self, after_bytes: Option<u64>, duration: Duration) -> Self {
+        self.stall = Some((after_bytes, duration));
+        self
+    }
+}
+
+impl<B> Body for FaultBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<anyhow::Error>,
+{
+    type Data = Bytes;
+    type Error = anyhow::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                if delay.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                *this.delay = None;
+                if *this.aborting {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(anyhow!("connection reset by fault handler"))));
+                }
+            }
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            let sent = *this.sent;
+            if let Some((offset, duration)) = *this.stall
+                && offset.map_or(sent > 0, |offset| sent >= offset)
+            {
+                *this.stall = None;
+                *this.delay = Some(Box::pin(tokio::time::sleep(duration)));
+                continue;
+            }
+            if let Some(AbortPoint::At(offset)) = *this.abort
+                && sent >= offset
+            {
+                *this.held = None;
+                *this.aborting = true;
+                *this.delay = Some(Box::pin(tokio::time::sleep(FLUSH_GRACE)));
+                continue;
+            }
+
+            let mut chunk = match this.held.take() {
+                Some(chunk) => chunk,
+                None => match this.inner.as_mut().poll_frame(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(None) => {
+                        if let Some(AbortPoint::End) = *this.abort {
+                            *this.abort = None;
+                            *this.aborting = true;
+                            *this.delay = Some(Box::pin(tokio::time::sleep(FLUSH_GRACE)));
+                            continue;
+                        }
+                        *this.done = true;
+                        return Poll::Ready(None);
+                    }
+                    Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                    Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                        Ok(chunk) => chunk,
+                        Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                    },
+                },
+            };
+            if chunk.is_empty() {
+                continue;
+            }
+
+            // Split the chunk at the next offset so the fault lands exactly there
+            let next_offset = [
+                this.stall.and_then(|(offset, _)| offset),
+                match *this.abort {
+                    Some(AbortPoint::At(offset)) => Some(offset),
+                    _ => None,
+                },
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|offset| *offset > sent)
+            .min();
+            if let Some(offset) = next_offset
+                && sent + chunk.len() as u64 > offset
+            {
+                let rest = chunk.split_off((offset - sent) as usize);
+                *this.held = Some(rest);
+            }
+            *this.sent += chunk.len() as u64;
+            return Poll::Ready(Some(Ok(Frame::data(chunk))));
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+            || (self.abort.is_none()
+                && self.delay.is_none()
+                && self.held.is_none()
+                && self.inner.is_end_stream())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        if self.abort.is_some() {
+            SizeHint::default()
+        } else {
+            self.inner.size_hint()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use lynx_storage::dao::request_processing_dao::handlers::FaultRule;
+    use std::time::Instant;
+
+    fn response(body: &'static str) -> Response {
+        Response::builder()
+            .header(CONTENT_LENGTH, body.len())
+            .body(AxumBody::from(body))
+            .unwrap()
+    }
+
+    fn config(fault: Fault) -> FaultConfig {
+        FaultConfig {
+            faults: vec![FaultRule {
+                probability: 1.0,
+                fault,
+            }],
+        }
+    }
+
+    /// Collects data frames until the body ends or fails.
+    async fn drain(body: AxumBody) -> (Vec<u8>, bool) {
+        let mut body = body;
+        let mut data = Vec::new();
+        while let Some(frame) = body.frame().await {
+            match frame {
+                Ok(frame) => data.extend_from_slice(&frame.into_data().unwrap_or_default()),
+                Err(_) => return (data, true),
+            }
+        }
+        (data, false)
+    }
+
+    #[tokio::test]
+    async fn body_faults_cut_and_pause_at_offsets() -> Result<()> {
+        let truncated = inject_fault(
+            &config(Fault::Truncate { bytes: 5 }),
+            response("hello world"),
+        )
+        .await?;
+        assert_eq!(truncated.headers()[CONTENT_LENGTH], "11");
+        assert_eq!(
+            drain(truncated.into_body()).await,
+            (b"hello".to_vec(), true)
+        );
+
+        let chunked = inject_fault(&config(Fault::MalformedChunked), response("hello")).await?;
+        assert!(chunked.headers().get(CONTENT_LENGTH).is_none());
+        assert_eq!(chunked.headers()[TRANSFER_ENCODING], "chunked");
+        assert_eq!(drain(chunked.into_body()).await, (b"hello".to_vec(), true));
+
+        let stall = Fault::Stall {
+            after_bytes: Some(3),
+            duration_ms: 100,
+        };
+        let start = Instant::now();
+        let stalled = inject_fault(&config(stall), response("hello")).await?;
+        assert_eq!(drain(stalled.into_body()).await, (b"hello".to_vec(), false));
+        assert!(start.elapsed() >= Duration::from_millis(90));
+
+        let wrong_length = Fault::WrongContentLength { delta: -2 };
+        let wrong = inject_fault(&config(wrong_length), response("hello")).await?;
+        assert_eq!(wrong.headers()[CONTENT_LENGTH], "3");
+
+        let skipped = FaultConfig {
+            faults: vec![FaultRule {
+                probability: 0.0,
+                fault: Fault::Truncate { bytes: 0 },
+            }],
+        };
+        let untouched = inject_fault(&skipped, response("hello")).await?;
+        assert_eq!(
+            drain(untouched.into_body()).await,
+            (b"hello".to_vec(), false)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reset_and_corrupt_gzip() -> Result<()> {
+        let before = Fault::Reset {
+            phase: ResetPhase::BeforeHeaders,
+        };
+        let reset = inject_fault(&config(before), response("hello")).await?;
+        assert!(reset.extensions().get::<AbortConnection>().is_some());
+
+        let after = Fault::Reset {
+            phase: ResetPhase::AfterHeaders,
+        };
+        let reset = inject_fault(&config(after), response("hello")).await?;
+        assert_eq!(drain(reset.into_body()).await, (Vec::new(), true));
+
+        let corrupt = inject_fault(&config(Fault::CorruptGzip), response("hello gzip")).await?;
+        assert_eq!(corrupt.headers()[CONTENT_ENCODING], "gzip");
+        let declared = corrupt.headers()[CONTENT_LENGTH]
+            .to_str()?
+            .parse::<usize>()?;
+        let body = corrupt.into_body().collect().await?.to_bytes();
+        assert_eq!(declared, body.len());
+        assert!(decode_body("gzip", &body).await.is_err());
+        Ok(())
+    }
+}
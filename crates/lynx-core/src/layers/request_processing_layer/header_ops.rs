@@ -0,0 +1,163 @@
+use http::{HeaderMap, HeaderName, HeaderValue};
+use lynx_storage::dao::request_processing_dao::handlers::HeaderOp;
+
+use super::regex_cache::cached_regex;
+use super::template::TemplateContext;
+use crate::error::{CoreError, CoreResult};
+
+/// Runs header operations in order, rendering values against `context`.
+///
+/// Operations naming an invalid header, or rendering to an invalid value, are
+/// skipped like the rest of the modify handlers do.
+pub fn apply_header_ops(
+    headers: &mut HeaderMap,
+    ops: &[HeaderOp],
+    context: &TemplateContext,
+) -> CoreResult<()> {
+    for op in ops {
+        match op {
+            HeaderOp::Set { name, value } => {
+                if let Some((name, value)) = header_pair(name, &context.render(value)?) {
+                    headers.insert(name, value);
+                }
+            }
+            HeaderOp::Append { name, value } => {
+                if let Some((name, value)) = header_pair(name, &context.render(value)?) {
+                    headers.append(name, value);
+                }
+            }
+            HeaderOp::Remove { name } => {
+                if let Some(name) = header_name(name) {
+                    headers.remove(name);
+                }
+            }
+            HeaderOp::Rename { from, to } => {
+                if let (Some(from), Some(to)) = (header_name(from), header_name(to)) {
+                    let values: Vec<_> = headers.get_all(&from).iter().cloned().collect();
+                    headers.remove(&from);
+                    for value in values {
+                        headers.append(to.clone(), value);
+                    }
+                }
+            }
+            HeaderOp::RegexRewrite {
+                name,
+                pattern,
+                replacement,
+            } => {
+                let Some(name) = header_name(name) else {
+                    continue;
+                };
+                let regex = cached_regex(pattern).map_err(|e| CoreError::Validation {
+                    message: format!("invalid header rewrite regex {pattern:?}: {e}"),
+                })?;
+                let replacement = context.render(replacement)?;
+                let values: Vec<_> = headers
+                    .get_all(&name)
+                    .iter()
+                    .map(|value| {
+                        let text = String::from_utf8_lossy(value.as_bytes());
+                        regex.replace_all(&text, replacement.as_str()).into_owned()
+                    })
+                    .collect();
+                headers.remove(&name);
+                for value in values {
+                    match HeaderValue::from_str(&value) {
+                        Ok(value) => {
+                            headers.append(name.clone(), value);
+                        }
+                        Err(_) => tracing::warn!("Dropping invalid rewritten {name} value"),
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn header_name(name: &str) -> Option<HeaderName> {
+    let parsed = name.parse::<HeaderName>().ok();
+    if parsed.is_none() {
+        tracing::warn!("Skipping header op on invalid header name {name:?}");
+    }
+    parsed
+}
+
+fn header_pair(name: &str, value: &str) -> Option<(HeaderName, HeaderValue)> {
+    let name = header_name(name)?;
+    match HeaderValue::from_str(value) {
+        Ok(value) => Some((name, value)),
+        Err(_) => {
+            tracing::warn!("Skipping invalid value for header {name}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{Method, Uri};
+
+    fn op_set(name: &str, value: &str) -> HeaderOp {
+        HeaderOp::Set {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn ops_run_in_order() {
+        let mut headers = HeaderMap::new();
+        headers.insert("set-cookie", HeaderValue::from_static("a=1"));
+        headers.insert("server", HeaderValue::from_static("nginx"));
+        headers.insert("x-old", HeaderValue::from_static("kept"));
+        headers.insert("location", HeaderValue::from_static("http://example.com/a"));
+
+        let ops = vec![
+            HeaderOp::Append {
+                name: "set-cookie".to_string(),
+                value: "b=2".to_string(),
+            },
+            HeaderOp::Remove {
+                name: "server".to_string(),
+            },
+            HeaderOp::Rename {
+                from: "x-old".to_string(),
+                to: "x-new".to_string(),
+            },
+            HeaderOp::RegexRewrite {
+                name: "location".to_string(),
+                pattern: "^http://([^/]+)".to_string(),
+                replacement: "https://$1".to_string(),
+            },
+            op_set("x-invalid name", "ignored"),
+        ];
+        apply_header_ops(&mut headers, &ops, &TemplateContext::default()).unwrap();
+
+        let cookies: Vec<_> = headers.get_all("set-cookie").iter().collect();
+        assert_eq!(cookies, ["a=1", "b=2"]);
+        assert!(headers.get("server").is_none());
+        assert!(headers.get("x-old").is_none());
+        assert_eq!(headers["x-new"], "kept");
+        assert_eq!(headers["location"], "https://example.com/a");
+    }
+
+    #[test]
+    fn values_are_templates() {
+        let mut request_headers = HeaderMap::new();
+        request_headers.insert("x-request-id", HeaderValue::from_static("req-42"));
+        let uri: Uri = "http://example.com/a".parse().unwrap();
+        let context = TemplateContext::from_request_parts(&Method::GET, &uri, &request_headers);
+
+        let mut headers = HeaderMap::new();
+        let ops = vec![
+            op_set("x-correlation-id", "{{req.header.x-request-id}}"),
+            op_set("x-trace", "{{uuid}}"),
+        ];
+        apply_header_ops(&mut headers, &ops, &context).unwrap();
+
+        assert_eq!(headers["x-correlation-id"], "req-42");
+        assert_eq!(headers["x-trace"].len(), 36);
+    }
+}
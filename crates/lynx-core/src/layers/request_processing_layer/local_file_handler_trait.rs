@@ -2,71 +2,383 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use lynx_storage::dao::request_processing_dao::handlers::LocalFileConfig;
+use http::{
+    HeaderMap, HeaderValue, Method,
+    header::{ACCEPT_ENCODING, IF_NONE_MATCH, RANGE},
+};
+use lynx_storage::dao::request_processing_dao::handlers::{LocalFileConfig, LocalFileMode};
 use mime_guess::from_path;
-use std::path::Path;
-use tokio::fs;
+use percent_encoding::percent_decode_str;
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
 
 use crate::{common::Req, error::CoreResult, utils::full};
 
 use super::handler_trait::{HandleRequestType, HandlerTrait};
 
+fn text_response(status: StatusCode, message: String) -> CoreResult<HandleRequestType> {
+    let response = Response::builder()
+        .status(status)
+        .header("content-type", "text/plain")
+        .body(full(message))?;
+    Ok(HandleRequestType::Response(response.into_response()))
+}
+
+/// Byte range asked for by a `Range` header, resolved against the file length.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    Full,
+    /// Inclusive start and end offsets
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Parses a single `bytes=` range; anything else, including multiple ranges,
+/// is answered with the whole file.
+fn parse_range(headers: &HeaderMap, len: u64) -> ByteRange {
+    let Some(spec) = headers
+        .get(RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("bytes="))
+    else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Partial(len.saturating_sub(suffix), len - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = if end.is_empty() {
+        len.saturating_sub(1)
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+            _ => return ByteRange::Full,
+        }
+    };
+    if start >= len {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+/// Whether `Accept-Encoding` allows `coding` (a `q=0` weight refuses it).
+fn accepts_encoding(headers: &HeaderMap, coding: &str) -> bool {
+    headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            name.eq_ignore_ascii_case(coding) && !refused
+        })
+}
+
+/// Whether `If-None-Match` lists `etag`, compared weakly.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = etag.trim_start_matches("W/");
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == opaque)
+}
+
+async fn read_range(path: &Path, start: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = fs::File::open(path).await?;
+    file.seek(SeekFrom::Start(start)).await?;
+    let mut content = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut content).await?;
+    Ok(content)
+}
+
+/// A `LocalFile` handler bound to the path glob captures of its rule match.
+pub struct LocalFile<'a> {
+    config: &'a LocalFileConfig,
+    params: &'a [String],
+}
+
+impl<'a> LocalFile<'a> {
+    pub fn new(config: &'a LocalFileConfig, params: &'a [String]) -> Self {
+        Self { config, params }
+    }
+
+    /// The part of the request path resolved under the root in directory mode.
+    fn relative_path(&self, request: &Req) -> String {
+        let path = request.uri().path();
+        if let Some(prefix) = &self.config.strip_prefix {
+            let prefix = prefix.trim_end_matches('/');
+            return match path.strip_prefix(prefix) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.to_string(),
+                _ => path.to_string(),
+            };
+        }
+        if let Some(tail) = self.params.last() {
+            return tail.clone();
+        }
+        path.to_string()
+    }
+
+    /// Resolves the request under the root folder, or the error status and
+    /// message when it escapes the root or nothing is there.
+    async fn resolve_in_directory(&self, request: &Req) -> Result<PathBuf, (StatusCode, String)> {
+        let root = Path::new(&self.config.file_path);
+        if !root.is_dir() {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Directory not found: {}", self.config.file_path),
+            ));
+        }
+
+        let relative = self.relative_path(request);
+        let mut candidate = root.to_path_buf();
+        let mut last_segment = String::new();
+        for segment in relative.split('/').filter(|s| !s.is_empty() && *s != ".") {
+            let decoded = percent_decode_str(segment).decode_utf8_lossy();
+            if decoded == ".."
+                || decoded.contains(['/', '\\', '\0'])
+                || Path::new(&*decoded).has_root()
+            {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    format!("Path escapes the directory: {relative}"),
+                ));
+            }
+            candidate.push(&*decoded);
+            last_segment = decoded.into_owned();
+        }
+
+        if candidate.is_dir() {
+            candidate.push("index.html");
+        }
+        if !candidate.is_file() {
+            let fallback = root.join("index.html");
+            if self.config.spa_fallback && !last_segment.contains('.') && fallback.is_file() {
+                candidate = fallback;
+            } else {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    format!("File not found: /{}", relative.trim_start_matches('/')),
+                ));
+            }
+        }
+
+        // Symlinks may still point outside the root.
+        let inside = match (
+            fs::canonicalize(root).await,
+            fs::canonicalize(&candidate).await,
+        ) {
+            (Ok(root), Ok(resolved)) => resolved.starts_with(root),
+            _ => false,
+        };
+        if !inside {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("Path escapes the directory: {relative}"),
+            ));
+        }
+        Ok(candidate)
+    }
+
+    /// Picks a `.br` or `.gz` sibling of `path` the client accepts, and tells
+    /// whether any sibling exists so the response can `Vary` on it.
+    async fn precompressed(
+        path: &Path,
+        headers: &HeaderMap,
+    ) -> (Option<(PathBuf, &'static str)>, bool) {
+        let mut any_variant = false;
+        for (coding, extension) in [("br", "br"), ("gzip", "gz")] {
+            let mut variant = path.as_os_str().to_owned();
+            variant.push(".");
+            variant.push(extension);
+            let variant = PathBuf::from(variant);
+            if fs::metadata(&variant)
+                .await
+                .is_ok_and(|meta| meta.is_file())
+            {
+                any_variant = true;
+                if accepts_encoding(headers, coding) {
+                    return (Some((variant, coding)), true);
+                }
+            }
+        }
+        (None, any_variant)
+    }
+
+    async fn serve(&self, path: &Path, request: &Req) -> CoreResult<HandleRequestType> {
+        let headers = request.headers();
+        let content_type = self
+            .config
+            .content_type
+            .clone()
+            .unwrap_or_else(|| from_path(path).first_or_octet_stream().to_string());
+
+        // A configured status always sends the whole file, so it is never
+        // replaced by a 206 or 304.
+        let conditional = self.config.status_code.is_none();
+
+        // Ranges apply to the file itself, so variants are only picked for whole-file requests.
+        let (variant, vary) = if conditional && headers.contains_key(RANGE) {
+            (None, false)
+        } else {
+            Self::precompressed(path, headers).await
+        };
+        let (body_path, encoding) = match &variant {
+            Some((variant, coding)) => (variant.as_path(), Some(*coding)),
+            None => (path, None),
+        };
+
+        let metadata = match fs::metadata(body_path).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return text_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read file: {}", e),
+                );
+            }
+        };
+        let len = metadata.len();
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos())
+            .unwrap_or_default();
+        let etag = match encoding {
+            Some(coding) => format!("W/\"{len:x}-{modified:x}-{coding}\""),
+            None => format!("W/\"{len:x}-{modified:x}\""),
+        };
+
+        let mut builder = Response::builder()
+            .header("etag", &etag)
+            .header("x-served-by", "lynx-proxy-local-file");
+        if conditional {
+            builder = builder.header("accept-ranges", "bytes");
+        }
+        if vary {
+            builder = builder.header("vary", "accept-encoding");
+        }
+        if conditional && etag_matches(headers, &etag) {
+            let response = builder.status(StatusCode::NOT_MODIFIED).body(full(""))?;
+            return Ok(HandleRequestType::Response(response.into_response()));
+        }
+        builder = builder.header("content-type", content_type);
+        if let Some(coding) = encoding {
+            builder = builder.header("content-encoding", coding);
+        }
+
+        let range = if conditional {
+            parse_range(headers, len)
+        } else {
+            ByteRange::Full
+        };
+        let (status, start, count) = match range {
+            ByteRange::Full => (
+                StatusCode::from_u16(self.config.status_code.unwrap_or(200))?,
+                0,
+                len,
+            ),
+            ByteRange::Partial(start, end) => {
+                builder = builder.header("content-range", format!("bytes {start}-{end}/{len}"));
+                (StatusCode::PARTIAL_CONTENT, start, end - start + 1)
+            }
+            ByteRange::Unsatisfiable => {
+                let response = builder
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header("content-range", format!("bytes */{len}"))
+                    .body(full(""))?;
+                return Ok(HandleRequestType::Response(response.into_response()));
+            }
+        };
+
+        let content = if request.method() == Method::HEAD {
+            Vec::new()
+        } else {
+            match read_range(body_path, start, count).await {
+                Ok(content) => content,
+                Err(e) => {
+                    return text_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to read file: {}", e),
+                    );
+                }
+            }
+        };
+        let response = builder
+            .status(status)
+            .header("content-length", HeaderValue::from(count))
+            .body(full(content))?;
+        Ok(HandleRequestType::Response(response.into_response()))
+    }
+}
+
 #[async_trait::async_trait]
-impl HandlerTrait for LocalFileConfig {
-    async fn handle_request(&self, _request: Req) -> CoreResult<HandleRequestType> {
-        let file_path = Path::new(&self.file_path);
+impl HandlerTrait for LocalFile<'_> {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        if self.config.mode == LocalFileMode::Directory {
+            return match self.resolve_in_directory(&request).await {
+                Ok(path) => self.serve(&path, &request).await,
+                Err((status, message)) => text_response(status, message),
+            };
+        }
+
+        let file_path = Path::new(&self.config.file_path);
 
         // Check if file exists
         if !file_path.exists() {
-            let response = Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .header("content-type", "text/plain")
-                .body(full(format!("File not found: {}", self.file_path)))?;
-            return Ok(HandleRequestType::Response(response.into_response()));
+            return text_response(
+                StatusCode::NOT_FOUND,
+                format!("File not found: {}", self.config.file_path),
+            );
         }
 
         // Check if it's a file (not directory)
         if !file_path.is_file() {
-            let response = Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .header("content-type", "text/plain")
-                .body(full(format!("Path is not a file: {}", self.file_path)))?;
-            return Ok(HandleRequestType::Response(response.into_response()));
+            return text_response(
+                StatusCode::BAD_REQUEST,
+                format!("Path is not a file: {}", self.config.file_path),
+            );
         }
 
-        // Read file content
-        match fs::read(&file_path).await {
-            Ok(content) => {
-                // Determine content type
-                let content_type = self
-                    .content_type
-                    .as_deref()
-                    .map(|s| s.to_owned())
-                    .unwrap_or_else(|| {
-                        let mime_type = from_path(file_path).first_or_octet_stream();
-                        mime_type.to_string()
-                    });
-
-                // Use configured status code or default to 200
-                let status_code = self.status_code.unwrap_or(200);
-
-                let response = Response::builder()
-                    .status(StatusCode::from_u16(status_code)?)
-                    .header("content-type", content_type)
-                    .header("content-length", content.len())
-                    .header("x-served-by", "lynx-proxy-local-file")
-                    .body(full(content))?;
-
-                Ok(HandleRequestType::Response(response.into_response()))
-            }
-            Err(e) => {
-                let response = Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("content-type", "text/plain")
-                    .body(full(format!("Failed to read file: {}", e)))?;
-                Ok(HandleRequestType::Response(response.into_response()))
-            }
-        }
+        self.serve(file_path, &request).await
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for LocalFileConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        LocalFile::new(self, &[]).handle_request(request).await
     }
 }
 
@@ -85,6 +397,189 @@ mod tests {
         Ok(file_path.to_string_lossy().to_string())
     }
 
+    async fn get(handler: &LocalFile<'_>, uri: &str, headers: &[(&str, &str)]) -> Result<Response> {
+        let mut builder = Request::builder().method(Method::GET).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        match handler.handle_request(builder.body(full(""))?).await? {
+            HandleRequestType::Response(response) => Ok(response),
+            _ => panic!("Expected Response, got Request"),
+        }
+    }
+
+    async fn body_text(response: Response) -> Result<String> {
+        let body_bytes = response.into_body().collect().await?.to_bytes();
+        Ok(String::from_utf8(body_bytes.to_vec())?)
+    }
+
+    fn directory_config(root: &Path) -> LocalFileConfig {
+        LocalFileConfig {
+            file_path: root.to_string_lossy().to_string(),
+            mode: LocalFileMode::Directory,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_file_directory_serves_index_and_blocks_traversal() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let root = temp_dir.path().join("dist");
+        fs::create_dir_all(root.join("assets"))?;
+        fs::write(root.join("index.html"), "<main>app</main>")?;
+        fs::write(root.join("assets/app.js"), "console.log(1)")?;
+        fs::write(temp_dir.path().join("secret.txt"), "secret")?;
+
+        let mut config = directory_config(&root);
+        let handler = LocalFile::new(&config, &[]);
+        let response = get(&handler, "http://example.com/", &[]).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/html");
+        assert_eq!(body_text(response).await?, "<main>app</main>");
+
+        let response = get(&handler, "http://example.com/assets/app.js", &[]).await?;
+        assert_eq!(response.headers()["content-type"], "text/javascript");
+
+        for uri in [
+            "http://example.com/../secret.txt",
+            "http://example.com/assets/%2e%2e/%2e%2e/secret.txt",
+            "http://example.com/assets/..%2F..%2Fsecret.txt",
+        ] {
+            let response = get(&handler, uri, &[]).await?;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{uri}");
+        }
+
+        let response = get(&handler, "http://example.com/settings/profile", &[]).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        config.spa_fallback = true;
+        let handler = LocalFile::new(&config, &[]);
+        let response = get(&handler, "http://example.com/settings/profile", &[]).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await?, "<main>app</main>");
+        let response = get(&handler, "http://example.com/assets/missing.js", &[]).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_file_directory_resolves_strip_prefix_and_glob_tail() -> Result<()> {
+        let temp_dir = tempdir()?;
+        fs::create_dir_all(temp_dir.path().join("assets"))?;
+        fs::write(temp_dir.path().join("assets/app.css"), "body{}")?;
+
+        let config = LocalFileConfig {
+            strip_prefix: Some("/app/".to_string()),
+            ..directory_config(temp_dir.path())
+        };
+        let handler = LocalFile::new(&config, &[]);
+        let response = get(&handler, "http://example.com/app/assets/app.css", &[]).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await?, "body{}");
+
+        let config = directory_config(temp_dir.path());
+        let params = vec!["assets/app.css".to_string()];
+        let handler = LocalFile::new(&config, &params);
+        let response = get(&handler, "http://example.com/static/assets/app.css", &[]).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/css");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_file_handler_ranges_and_etag() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let file_path = create_test_file(temp_dir.path(), "digits.txt", "0123456789").await?;
+        let config = LocalFileConfig {
+            file_path,
+            ..Default::default()
+        };
+        let handler = LocalFile::new(&config, &[]);
+
+        let response = get(&handler, "http://example.com/d", &[("range", "bytes=2-4")]).await?;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["content-range"], "bytes 2-4/10");
+        assert_eq!(response.headers()["content-length"], "3");
+        assert_eq!(body_text(response).await?, "234");
+
+        let response = get(&handler, "http://example.com/d", &[("range", "bytes=-3")]).await?;
+        assert_eq!(body_text(response).await?, "789");
+
+        let response = get(&handler, "http://example.com/d", &[("range", "bytes=20-")]).await?;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()["content-range"], "bytes */10");
+
+        let response = get(&handler, "http://example.com/d", &[]).await?;
+        assert_eq!(response.headers()["accept-ranges"], "bytes");
+        let etag = response.headers()["etag"].to_str()?.to_string();
+        let response = get(
+            &handler,
+            "http://example.com/d",
+            &[("if-none-match", &etag)],
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(body_text(response).await?.is_empty());
+
+        let response = get(
+            &handler,
+            "http://example.com/d",
+            &[("if-none-match", "\"other\"")],
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_file_directory_serves_precompressed_variants() -> Result<()> {
+        let temp_dir = tempdir()?;
+        fs::write(temp_dir.path().join("app.js"), "plain")?;
+        fs::write(temp_dir.path().join("app.js.br"), "brotli")?;
+        fs::write(temp_dir.path().join("app.js.gz"), "gzip")?;
+
+        let config = directory_config(temp_dir.path());
+        let handler = LocalFile::new(&config, &[]);
+
+        let response = get(
+            &handler,
+            "http://example.com/app.js",
+            &[("accept-encoding", "gzip, br")],
+        )
+        .await?;
+        assert_eq!(response.headers()["content-encoding"], "br");
+        assert_eq!(response.headers()["content-type"], "text/javascript");
+        assert_eq!(response.headers()["vary"], "accept-encoding");
+        assert_eq!(body_text(response).await?, "brotli");
+
+        let response = get(
+            &handler,
+            "http://example.com/app.js",
+            &[("accept-encoding", "br;q=0, gzip")],
+        )
+        .await?;
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        assert_eq!(body_text(response).await?, "gzip");
+
+        let response = get(&handler, "http://example.com/app.js", &[]).await?;
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(body_text(response).await?, "plain");
+
+        let response = get(
+            &handler,
+            "http://example.com/app.js",
+            &[("accept-encoding", "br"), ("range", "bytes=0-1")],
+        )
+        .await?;
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(body_text(response).await?, "pl");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_local_file_handler_successful_file_serving() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -95,6 +590,7 @@ mod tests {
             file_path,
             content_type: None,
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -149,6 +645,7 @@ mod tests {
             file_path,
             content_type: Some("application/json".to_string()),
             status_code: Some(201),
+            ..Default::default()
         };
 
         // Create a mock request
@@ -179,9 +676,18 @@ mod tests {
                 );
 
                 // Verify response body
+                let etag = response.headers()["etag"].to_str()?.to_string();
                 let body_bytes = response.into_body().collect().await?.to_bytes();
                 let body_str = String::from_utf8(body_bytes.to_vec())?;
                 assert_eq!(body_str, file_content);
+
+                // The configured status wins over ranges and revalidation
+                let handler = LocalFile::new(&handler, &[]);
+                let headers = [("range", "bytes=0-3"), ("if-none-match", etag.as_str())];
+                let response = get(&handler, "http://example.com/data.json", &headers).await?;
+                assert_eq!(response.status(), StatusCode::CREATED);
+                assert!(!response.headers().contains_key("accept-ranges"));
+                assert_eq!(body_text(response).await?, file_content);
             }
             _ => panic!("Expected Response, got Request"),
         }
@@ -199,6 +705,7 @@ mod tests {
             file_path,
             content_type: None, // Let mime_guess determine content type
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -233,6 +740,7 @@ mod tests {
             file_path: "/non/existent/file.txt".to_string(),
             content_type: None,
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -275,6 +783,7 @@ mod tests {
             file_path: dir_path.to_string_lossy().to_string(),
             content_type: None,
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -317,6 +826,7 @@ mod tests {
             file_path: file_path.to_string_lossy().to_string(),
             content_type: None, // Let mime_guess determine content type
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -362,6 +872,7 @@ mod tests {
             file_path,
             content_type: Some("text/plain".to_string()),
             status_code: Some(204), // No Content
+            ..Default::default()
         };
 
         // Create a mock request
@@ -411,6 +922,7 @@ mod tests {
             file_path,
             content_type: None,
             status_code: None,
+            ..Default::default()
         };
 
         // Create a mock request
@@ -0,0 +1,498 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use axum::{body::Body, response::Response};
+use bytes::Bytes;
+use http::{HeaderMap, Method, Request, header::HOST};
+use http_body_util::BodyExt;
+use lynx_storage::dao::request_processing_dao::handlers::MirrorConfig;
+use serde_json::Value;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::{StreamExt, wrappers::ReceiverStream};
+
+use super::body_codec::{content_encoding, decode_body};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    client::request_client::RequestClientExt,
+    common::{Req, Res},
+    error::CoreResult,
+    layers::{
+        message_package_layer::{
+            MessageEventChannel,
+            capture_gate::{CaptureDecision, CaptureGate},
+            message_event_data::{
+                MirrorBodyChange, MirrorDiff, MirrorHeaderChange, MirrorResult, MirrorStatusChange,
+                copy_body_stream,
+            },
+            message_event_store::MessageEvent,
+        },
+        trace_id_layer::service::{TraceId, TraceIdExt, set_new_trace_id},
+    },
+    utils::full,
+};
+
+/// Body differences reported per mirror; the rest are dropped.
+const MAX_BODY_CHANGES: usize = 100;
+/// Bodies larger than this are not compared, and the primary response body
+/// is only buffered up to it.
+const MAX_COMPARED_BODY: usize = 8 * 1024 * 1024;
+
+/// What a mirror target answered, or why it did not.
+#[derive(Debug)]
+struct MirrorOutcome {
+    target: String,
+    trace_id: Option<TraceId>,
+    duration_ms: u64,
+    response: Result<MirrorResponse, String>,
+}
+
+#[derive(Debug)]
+struct MirrorResponse {
+    status: u16,
+    headers: HeaderMap,
+    body: Bytes,
+    /// The body was over `MAX_COMPARED_BODY`
+    truncated: bool,
+}
+
+/// Mirrors started by one `Mirror` handler, awaited once the primary
+/// response is known.
+struct MirrorBatch {
+    config: MirrorConfig,
+    tasks: Vec<JoinHandle<MirrorOutcome>>,
+}
+
+/// Mirrors in flight for a request, taken by the first `Mirror` response
+/// handler that runs.
+#[derive(Clone, Default)]
+struct PendingMirrors(Arc<Mutex<Vec<MirrorBatch>>>);
+
+/// What `Mirror` needs from the request side, captured before the request is
+/// handed to the upstream service.
+#[derive(Clone, Default)]
+pub struct MirrorContext {
+    pending: PendingMirrors,
+    trace_id: Option<TraceId>,
+    channel: Option<Arc<MessageEventChannel>>,
+}
+
+impl MirrorContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            pending: request
+                .extensions()
+                .get::<PendingMirrors>()
+                .cloned()
+                .unwrap_or_default(),
+            trace_id: request.extensions().get::<TraceId>().cloned(),
+            channel: request
+                .extensions()
+                .get::<Arc<MessageEventChannel>>()
+                .cloned(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for MirrorConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        // Mirrors are captured only when the primary request is.
+        let channel = match CaptureGate::decide(&request).await {
+            Ok(CaptureDecision::Capture) => request
+                .extensions()
+                .get::<Arc<MessageEventChannel>>()
+                .cloned(),
+            _ => None,
+        };
+        let (mut parts, body) = request.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let http_client = parts.extensions.try_get_http_client()?;
+
+        let path_and_query = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+        // The target's authority replaces the original Host.
+        let mut headers = parts.headers.clone();
+        headers.remove(HOST);
+        let timeout = Duration::from_millis(self.timeout_ms());
+
+        let tasks = self
+            .targets
+            .iter()
+            .map(|target| {
+                let url = MirrorConfig::target_url(target, path_and_query);
+                let request = build_mirror_request(&parts.method, &url, &headers, body.clone());
+                let http_client = http_client.clone();
+                let channel = channel.clone();
+                let body = body.clone();
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let (trace_id, response) = match request {
+                        Ok(request) => {
+                            let trace_id = request.extensions().get_trace_id();
+                            let response =
+                                send_mirror(&http_client, request, body, timeout, channel).await;
+                            (Some(trace_id), response)
+                        }
+                        Err(e) => (None, Err(e)),
+                    };
+                    MirrorOutcome {
+                        target: url,
+                        trace_id,
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        response,
+                    }
+                })
+            })
+            .collect();
+
+        let pending = parts
+            .extensions
+            .get_or_insert_default::<PendingMirrors>()
+            .clone();
+        pending
+            .0
+            .lock()
+            .expect("mirror lock poisoned")
+            .push(MirrorBatch {
+                config: self.clone(),
+                tasks,
+            });
+        Ok(HandleRequestType::Request(Req::from_parts(
+            parts,
+            full(body),
+        )))
+    }
+}
+
+fn build_mirror_request(
+    method: &Method,
+    url: &str,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Req, String> {
+    let mut request = Request::builder()
+        .method(method.clone())
+        .uri(url)
+        .body(full(body))
+        .map_err(|e| format!("invalid mirror request to {url}: {e}"))?;
+    *request.headers_mut() = headers.clone();
+    set_new_trace_id(&mut request);
+    Ok(request)
+}
+
+/// Sends one mirrored request, recording it as its own trace when `channel`
+/// is set.
+async fn send_mirror(
+    http_client: &crate::client::http_client::HttpClient,
+    request: Req,
+    body: Bytes,
+    timeout: Duration,
+    channel: Option<Arc<MessageEventChannel>>,
+) -> Result<MirrorResponse, String> {
+    let trace_id = request.extensions().get_trace_id();
+    if let Some(channel) = &channel {
+        channel
+            .dispatch_on_request_start(&request, body_stream(body))
+            .await;
+        channel.dispatch_on_request_end(trace_id.clone()).await;
+        channel.dispatch_on_before_proxy(trace_id.clone()).await;
+    }
+
+    let exchange = async {
+        let response = http_client
+            .request(request)
+            .await
+            .map_err(|e| format!("{e:#}"))?;
+        let (parts, body) = response.into_parts();
+        let body = body
+            .collect()
+            .await
+            .map_err(|e| format!("mirror response body: {e}"))?
+            .to_bytes();
+        Ok::<_, String>((parts, body))
+    };
+    let result = match tokio::time::timeout(timeout, exchange).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+    };
+
+    match result {
+        Ok((parts, body)) => {
+            if let Some(channel) = &channel {
+                let mut response = Res::new(full(body.clone()));
+                *response.status_mut() = parts.status;
+                *response.version_mut() = parts.version;
+                *response.headers_mut() = parts.headers.clone();
+                response.extensions_mut().insert(trace_id.clone());
+                channel.dispatch_on_after_proxy(trace_id).await;
+                channel
+                    .dispatch_on_response_start(&response, body_stream(body.clone()))
+                    .await;
+            }
+            Ok(MirrorResponse {
+                status: parts.status.as_u16(),
+                headers: parts.headers,
+                truncated: body.len() > MAX_COMPARED_BODY,
+                body,
+            })
+        }
+        Err(reason) => {
+            if let Some(channel) = &channel {
+                channel.dispatch_on_error(trace_id, reason.clone()).await;
+            }
+            Err(reason)
+        }
+    }
+}
+
+fn body_stream(body: Bytes) -> ReceiverStream<Bytes> {
+    let (tx, rx) = mpsc::channel(1);
+    if !body.is_empty() {
+        let _ = tx.try_send(body);
+    }
+    ReceiverStream::new(rx)
+}
+
+/// Passes the primary response on untouched and, in the background, compares
+/// it against every mirror of this request.
+pub async fn mirror_response(response: Response, context: &MirrorContext) -> CoreResult<Response> {
+    let batches = std::mem::take(&mut *context.pending.0.lock().expect("mirror lock poisoned"));
+    if batches.is_empty() {
+        return Ok(response);
+    }
+    let (parts, body) = response.into_parts();
+    let (mut copy, body) = copy_body_stream(body);
+    let status = parts.status.as_u16();
+    let headers = parts.headers.clone();
+    let trace_id = context.trace_id.clone();
+    let channel = context.channel.clone();
+
+    tokio::spawn(async move {
+        let mut primary = Vec::new();
+        let mut truncated = false;
+        // The copy is drained to the end so the client's stream never waits on it.
+        while let Some(chunk) = copy.next().await {
+            if truncated || primary.len() + chunk.len() > MAX_COMPARED_BODY {
+                truncated = true;
+                primary = Vec::new();
+                continue;
+            }
+            primary.extend_from_slice(&chunk);
+        }
+        let primary = MirrorResponse {
+            status,
+            headers,
+            body: Bytes::from(primary),
+            truncated,
+        };
+        for batch in batches {
+            for task in batch.tasks {
+                let Ok(outcome) = task.await else {
+                    continue;
+                };
+                let result = compare(&batch.config, &primary, outcome).await;
+                if let (Some(trace_id), Some(channel)) = (&trace_id, &channel) {
+                    channel.sync_send_event(MessageEvent::OnMirrorResult(trace_id.clone(), result));
+                }
+            }
+        }
+    });
+
+    Ok(Response::from_parts(parts, Body::new(body)))
+}
+
+async fn compare(
+    config: &MirrorConfig,
+    primary: &MirrorResponse,
+    outcome: MirrorOutcome,
+) -> MirrorResult {
+    let (status, error, diff) = match &outcome.response {
+        Ok(mirror) => (
+            Some(mirror.status),
+            None,
+            Some(diff_responses(config, primary, mirror).await),
+        ),
+        Err(reason) => (None, Some(reason.clone()), None),
+    };
+    MirrorResult {
+        target: outcome.target,
+        trace_id: outcome.trace_id.map(|id| id.to_string()),
+        status,
+        error,
+        duration_ms: outcome.duration_ms,
+        diff,
+    }
+}
+
+async fn diff_responses(
+    config: &MirrorConfig,
+    primary: &MirrorResponse,
+    mirror: &MirrorResponse,
+) -> MirrorDiff {
+    let status = (primary.status != mirror.status).then_some(MirrorStatusChange {
+        primary: primary.status,
+        mirror: mirror.status,
+    });
+
+    let mut names: Vec<&str> = primary
+        .headers
+        .keys()
+        .chain(mirror.headers.keys())
+        .map(|name| name.as_str())
+        .filter(|name| config.compares_header(name))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let headers = names
+        .into_iter()
+        .filter_map(|name| {
+            let primary = header_value(&primary.headers, name);
+            let mirror = header_value(&mirror.headers, name);
+            (primary != mirror).then(|| MirrorHeaderChange {
+                name: name.to_string(),
+                primary,
+                mirror,
+            })
+        })
+        .collect();
+
+    if primary.truncated || mirror.truncated {
+        return MirrorDiff {
+            status,
+            headers,
+            body_changed: false,
+            body_truncated: true,
+            body: Vec::new(),
+        };
+    }
+    let primary_body = decoded_body(primary).await;
+    let mirror_body = decoded_body(mirror).await;
+    let (body_changed, body) = match (
+        serde_json::from_slice::<Value>(&primary_body),
+        serde_json::from_slice::<Value>(&mirror_body),
+    ) {
+        (Ok(primary), Ok(mirror)) => {
+            let mut changes = Vec::new();
+            diff_json("", Some(&primary), Some(&mirror), &mut changes);
+            (!changes.is_empty(), changes)
+        }
+        _ => (primary_body != mirror_body, Vec::new()),
+    };
+
+    MirrorDiff {
+        status,
+        headers,
+        body_changed,
+        body_truncated: false,
+        body,
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .map(|value| value.to_str().unwrap_or_default())
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// The body with its content encoding removed, so gzip and plain answers compare.
+async fn decoded_body(response: &MirrorResponse) -> Bytes {
+    match content_encoding(&response.headers) {
+        Some(encoding) => match decode_body(&encoding, &response.body).await {
+            Ok(Some(decoded)) => decoded,
+            _ => response.body.clone(),
+        },
+        None => response.body.clone(),
+    }
+}
+
+/// Collects the differences between two JSON values as JSON pointers.
+fn diff_json(
+    path: &str,
+    primary: Option<&Value>,
+    mirror: Option<&Value>,
+    changes: &mut Vec<MirrorBodyChange>,
+) {
+    if changes.len() >= MAX_BODY_CHANGES {
+        return;
+    }
+    match (primary, mirror) {
+        (Some(Value::Object(primary)), Some(Value::Object(mirror))) => {
+            let mut keys: Vec<&String> = primary.keys().chain(mirror.keys()).collect();
+            keys.sort_unstable();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                diff_json(&path, primary.get(key), mirror.get(key), changes);
+            }
+        }
+        (Some(Value::Array(primary)), Some(Value::Array(mirror))) => {
+            for index in 0..primary.len().max(mirror.len()) {
+                let path = format!("{path}/{index}");
+                diff_json(&path, primary.get(index), mirror.get(index), changes);
+            }
+        }
+        (primary, mirror) if primary != mirror => changes.push(MirrorBodyChange {
+            path: path.to_string(),
+            primary: primary.cloned(),
+            mirror: mirror.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn skips_bodies_over_the_limit() {
+        let response = |body: &'static str, truncated| MirrorResponse {
+            status: 200,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+            truncated,
+        };
+        let config = MirrorConfig::default();
+
+        let diff = diff_responses(&config, &response("", true), &response("{}", false)).await;
+        assert!(diff.body_truncated);
+        assert!(!diff.body_changed);
+
+        let diff = diff_responses(&config, &response("a", false), &response("b", false)).await;
+        assert!(!diff.body_truncated);
+        assert!(diff.body_changed);
+    }
+
+    #[test]
+    fn diff_json_reports_pointers() {
+        let mut changes = Vec::new();
+        diff_json(
+            "",
+            Some(&json!({ "id": 1, "tags": ["a", "b"], "a/b": true, "same": { "x": 1 } })),
+            Some(&json!({ "id": 2, "tags": ["a"], "a/b": true, "same": { "x": 1 }, "new": null })),
+            &mut changes,
+        );
+        let changes: Vec<_> = changes
+            .into_iter()
+            .map(|c| (c.path, c.primary, c.mirror))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("/id".to_string(), Some(json!(1)), Some(json!(2))),
+                ("/new".to_string(), None, Some(json!(null))),
+                ("/tags/1".to_string(), Some(json!("b")), None),
+            ]
+        );
+    }
+}
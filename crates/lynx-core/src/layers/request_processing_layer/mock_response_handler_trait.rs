@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use axum::response::{IntoResponse, Response};
+use http::{
+    HeaderName, HeaderValue, StatusCode,
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+};
+use http_body_util::BodyExt;
+use lynx_storage::{
+    DataStore, dao::projects_dao::ProjectsDao,
+    dao::request_processing_dao::handlers::MockResponseConfig,
+};
+use serde_json::Value;
+
+use super::cassette::FileStamp;
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::template::TemplateContext;
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    utils::full,
+};
+
+type LoadedBody = (FileStamp, Arc<str>);
+
+/// `bodyFile` templates by path; an entry is re-read when the file changes.
+static BODY_FILE_CACHE: LazyLock<Mutex<HashMap<PathBuf, LoadedBody>>> =
+    LazyLock::new(Default::default);
+
+/// A `MockResponse` handler with its body template loaded for one rule match.
+pub struct MockResponse<'a> {
+    config: &'a MockResponseConfig,
+    template: Option<Arc<str>>,
+    file: Option<PathBuf>,
+    params: &'a [String],
+}
+
+impl<'a> MockResponse<'a> {
+    /// Loads `bodyFile` (project directory first, then `<data dir>/mocks`);
+    /// `params` are the path glob captures of the matching rule.
+    pub async fn resolve(
+        config: &'a MockResponseConfig,
+        store: &Arc<DataStore>,
+        project: &str,
+        params: &'a [String],
+    ) -> CoreResult<Self> {
+        let Some(body_file) = &config.body_file else {
+            return Ok(Self {
+                config,
+                template: config.body.as_deref().map(Arc::from),
+                file: None,
+                params,
+            });
+        };
+        let path = ProjectsDao::new(store.clone())
+            .resolve_project_file(project, body_file, &store.mocks_dir())
+            .await
+            .map_err(|e| CoreError::Validation {
+                message: format!("mock body {e}"),
+            })?;
+        let template = load_body_file(&path)
+            .await
+            .map_err(|e| CoreError::Validation {
+                message: format!("mock body {}: {e}", path.display()),
+            })?;
+        Ok(Self {
+            config,
+            template: Some(template),
+            file: Some(path),
+            params,
+        })
+    }
+
+    fn content_type(&self, body: &str) -> String {
+        if let Some(file) = &self.file {
+            return mime_guess::from_path(file)
+                .first_or_text_plain()
+                .to_string();
+        }
+        if serde_json::from_str::<Value>(body).is_ok() {
+            "application/json".to_string()
+        } else {
+            "text/plain; charset=utf-8".to_string()
+        }
+    }
+}
+
+/// The template at `path`, read once per version of the file.
+async fn load_body_file(path: &Path) -> std::io::Result<Arc<str>> {
+    let stamp = FileStamp::new(&tokio::fs::metadata(path).await?);
+    if let Some((cached, template)) = BODY_FILE_CACHE
+        .lock()
+        .map_err(|e| std::io::Error::other(e.to_string()))?
+        .get(path)
+        && *cached == stamp
+    {
+        return Ok(template.clone());
+    }
+
+    let template: Arc<str> = tokio::fs::read_to_string(path).await?.into();
+    BODY_FILE_CACHE
+        .lock()
+        .map_err(|e| std::io::Error::other(e.to_string()))?
+        .insert(path.to_path_buf(), (stamp, template.clone()));
+    Ok(template)
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for MockResponse<'_> {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let mut context = TemplateContext::from_request(&request);
+        let body = request
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| anyhow!(e))?
+            .to_bytes();
+        context.insert("params", Value::from(self.params.to_vec()));
+        context.insert("body", request_body_value(&body));
+
+        let status = StatusCode::from_u16(self.config.status_code.unwrap_or(200))?;
+        let body = match &self.template {
+            Some(template) => context.render(template)?,
+            None => String::new(),
+        };
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in &self.config.headers {
+            let name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| CoreError::Validation {
+                    message: format!("invalid mock header name {name:?}"),
+                })?;
+            let value = HeaderValue::from_str(&context.render(value)?).map_err(|_| {
+                CoreError::Validation {
+                    message: format!("invalid value for mock header {name}"),
+                }
+            })?;
+            headers.insert(name, value);
+        }
+        if !headers.contains_key(CONTENT_TYPE) {
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_str(&self.content_type(&body)).map_err(|e| anyhow!(e))?,
+            );
+        }
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+        headers.insert("x-served-by", HeaderValue::from_static("lynx-proxy-mock"));
+
+        if let Some(latency_ms) = self.config.latency_ms {
+            tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+        }
+
+        let mut response = Response::builder().status(status).body(full(body))?;
+        response.headers_mut().extend(headers);
+        Ok(HandleRequestType::Response(response.into_response()))
+    }
+}
+
+/// The request body as templates see it: parsed JSON, text, or `null` when empty.
+fn request_body_value(body: &[u8]) -> Value {
+    if body.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{Method, Request};
+
+    fn request(uri: &str, body: &str) -> Req {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(full(body.to_string()))
+            .unwrap()
+    }
+
+    async fn respond(mock: &MockResponse<'_>, request: Req) -> (Response, String) {
+        let HandleRequestType::Response(response) = mock.handle_request(request).await.unwrap()
+        else {
+            panic!("mock should answer the request");
+        };
+        let (parts, body) = response.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+        (
+            Response::from_parts(parts, axum::body::Body::empty()),
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_mock_renders_request_data() {
+        let mut headers = std::collections::HashMap::new();
+        headers.insert("x-user".to_string(), "{{params.[0]}}".to_string());
+        let config = MockResponseConfig {
+            status_code: Some(201),
+            headers,
+            body: Some(
+                r#"{"id":"{{params.[0]}}","name":"{{body.name}}","tab":"{{req.query.tab}}","rid":"{{uuid}}"}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let params = vec!["42".to_string()];
+        let mock = MockResponse {
+            config: &config,
+            template: config.body.as_deref().map(Arc::from),
+            file: None,
+            params: &params,
+        };
+
+        let (response, body) = respond(
+            &mock,
+            request("http://example.com/users/42?tab=posts", r#"{"name":"ada"}"#),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["x-user"], "42");
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["id"], "42");
+        assert_eq!(json["name"], "ada");
+        assert_eq!(json["tab"], "posts");
+        assert!(uuid::Uuid::parse_str(json["rid"].as_str().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mock_reads_body_file_and_guesses_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        std::fs::create_dir_all(store.mocks_dir()).unwrap();
+        std::fs::write(
+            store.mocks_dir().join("page.html"),
+            "<h1>{{req.method}}</h1>",
+        )
+        .unwrap();
+        let config = MockResponseConfig {
+            body_file: Some("page.html".to_string()),
+            ..Default::default()
+        };
+        let mock = MockResponse::resolve(&config, &store, "default", &[])
+            .await
+            .unwrap();
+
+        let (response, body) = respond(&mock, request("http://example.com/", "")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html");
+        assert_eq!(body, "<h1>POST</h1>");
+
+        let page = store.mocks_dir().join("page.html");
+        let cached = MockResponse::resolve(&config, &store, "default", &[])
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(
+            mock.template.as_ref().unwrap(),
+            cached.template.as_ref().unwrap()
+        ));
+        std::fs::write(&page, "<main>{{req.method}}</main>").unwrap();
+        let edited = MockResponse::resolve(&config, &store, "default", &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            edited.template.as_deref(),
+            Some("<main>{{req.method}}</main>")
+        );
+
+        let missing = MockResponseConfig {
+            body_file: Some("missing.json".to_string()),
+            ..Default::default()
+        };
+        assert!(
+            MockResponse::resolve(&missing, &store, "default", &[])
+                .await
+                .is_err()
+        );
+    }
+}
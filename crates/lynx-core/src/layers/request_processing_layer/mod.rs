@@ -1,16 +1,37 @@
 pub mod block_handler_trait;
+pub mod body_codec;
+pub mod body_transform;
+pub mod breakpoint_handler_trait;
+pub mod cassette;
+pub mod cors_handler_trait;
 pub mod delay_handler_trait;
+pub mod fault_handler_trait;
 pub mod future;
 pub mod handler_trait;
+pub mod header_ops;
 pub mod html_script_injector_trait;
 pub mod layout;
 pub mod local_file_handler_trait;
+pub mod mirror_handler_trait;
+pub mod mock_response_handler_trait;
 pub mod modify_request_handler_trait;
 pub mod modify_response_handler_trait;
 pub mod proxy_forward_handler_trait;
+pub mod record_handler_trait;
+pub mod regex_cache;
+pub mod replay_handler_trait;
+pub mod script_handler_trait;
+pub mod sequence_handler_trait;
 pub mod service;
+pub mod stream_mock_handler_trait;
+pub mod template;
 pub mod throttle_handler_trait;
 pub mod throttled_body;
+pub mod upstream_proxy_handler_trait;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_plugin_handler_trait;
+pub mod websocket_intercept_handler_trait;
+pub mod websocket_mock_handler_trait;
 
 pub use future::RequestProcessingFuture;
 pub use layout::RequestProcessingLayer;
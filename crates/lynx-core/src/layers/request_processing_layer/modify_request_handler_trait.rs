@@ -1,24 +1,20 @@
 use http::{Method, Uri};
-use http_body_util::{BodyExt, Full};
+use http_body_util::BodyExt;
 use lynx_storage::dao::request_processing_dao::handlers::ModifyRequestConfig;
 
+use super::body_transform::rewrite_body;
 use super::handler_trait::{HandleRequestType, HandlerTrait};
-use crate::{common::Req, error::CoreResult};
+use super::header_ops::apply_header_ops;
+use super::template::TemplateContext;
+use crate::{common::Req, error::CoreResult, utils::full};
 
 #[async_trait::async_trait]
 impl HandlerTrait for ModifyRequestConfig {
     async fn handle_request(&self, mut request: Req) -> CoreResult<HandleRequestType> {
-        // Modify headers if specified
+        // Run header operations, with templates seeing the incoming request
         if let Some(ref modify_headers) = self.modify_headers {
-            let headers = request.headers_mut();
-            for (key, value) in modify_headers {
-                if let (Ok(header_name), Ok(header_value)) = (
-                    key.parse::<http::HeaderName>(),
-                    value.parse::<http::HeaderValue>(),
-                ) {
-                    headers.insert(header_name, header_value);
-                }
-            }
+            let context = TemplateContext::from_request(&request);
+            apply_header_ops(request.headers_mut(), &modify_headers.ops(), &context)?;
         }
 
         // Modify method if specified
@@ -50,22 +46,19 @@ impl HandlerTrait for ModifyRequestConfig {
             *request.uri_mut() = new_uri;
         }
 
-        // Modify body if specified
-        if let Some(ref new_body) = self.modify_body {
-            let body_bytes = new_body.as_bytes().to_vec();
-
-            // Update content-length header
-            if let Ok(content_length) = body_bytes.len().to_string().parse() {
-                request
-                    .headers_mut()
-                    .insert("content-length", content_length);
-            }
-
-            // Replace the body with the new content
-            let new_body = Full::new(bytes::Bytes::from(body_bytes))
-                .map_err(|e| anyhow::anyhow!("Body error: {}", e))
-                .boxed();
-            *request.body_mut() = new_body;
+        // Replace and/or transform the body, keeping its content encoding
+        let transforms = self.body_transforms.as_deref().unwrap_or_default();
+        if self.modify_body.is_some() || !transforms.is_empty() {
+            let (mut parts, body) = request.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let body = rewrite_body(
+                &mut parts.headers,
+                body,
+                self.modify_body.as_deref(),
+                transforms,
+            )
+            .await?;
+            request = Req::from_parts(parts, full(body));
         }
 
         Ok(HandleRequestType::Request(request))
@@ -78,6 +71,7 @@ mod tests {
     use anyhow::Result;
     use http::Request;
     use http_body_util::Empty;
+    use lynx_storage::dao::request_processing_dao::handlers::BodyTransform;
     use std::collections::HashMap;
 
     fn create_test_request() -> Req {
@@ -98,10 +92,11 @@ mod tests {
         headers.insert("X-Test".to_string(), "test-value".to_string());
 
         let config = ModifyRequestConfig {
-            modify_headers: Some(headers),
+            modify_headers: Some(headers.into()),
             modify_body: None,
             modify_method: None,
             modify_url: None,
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -120,6 +115,7 @@ mod tests {
             modify_body: None,
             modify_method: Some("POST".to_string()),
             modify_url: None,
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -138,6 +134,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_url: Some("/new-path".to_string()),
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -157,6 +154,7 @@ mod tests {
             modify_body: Some(new_body.to_string()),
             modify_method: None,
             modify_url: None,
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -169,6 +167,32 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_body_transforms_patch_json() -> Result<()> {
+        let config = ModifyRequestConfig {
+            body_transforms: Some(vec![BodyTransform::JsonPatch {
+                patch: vec![serde_json::json!({ "op": "remove", "path": "/token" })],
+            }]),
+            ..Default::default()
+        };
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("http://example.com/login")
+            .header("content-length", "29")
+            .body(full(r#"{"user":"alice","token":"t1"}"#))
+            .unwrap();
+        let result = config.handle_request(request).await?;
+
+        let HandleRequestType::Request(req) = result else {
+            panic!("Expected Request type, got Response");
+        };
+        assert_eq!(req.headers().get("content-length").unwrap(), "16");
+        let body = req.into_body().collect().await?.to_bytes();
+        assert_eq!(body, r#"{"user":"alice"}"#);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_no_modifications() -> Result<()> {
         let config = ModifyRequestConfig {
@@ -176,6 +200,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_url: None,
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -1,9 +1,13 @@
+use anyhow::anyhow;
 use axum::{body::Body, response::Response};
 use http::StatusCode;
-use http_body_util::{BodyExt, Full};
+use http_body_util::BodyExt;
 use lynx_storage::dao::request_processing_dao::handlers::modify_response_handler::ModifyResponseConfig;
 
+use super::body_transform::rewrite_body;
 use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::header_ops::apply_header_ops;
+use super::template::TemplateContext;
 use crate::{common::Req, error::CoreResult};
 
 #[async_trait::async_trait]
@@ -13,58 +17,57 @@ impl HandlerTrait for ModifyResponseConfig {
         Ok(HandleRequestType::Request(request))
     }
 
-    async fn handle_response(&self, mut response: Response) -> CoreResult<Response> {
-        // Modify headers if specified
-        if let Some(ref modify_headers) = self.modify_headers {
-            let headers = response.headers_mut();
-            for (key, value) in modify_headers {
-                if let (Ok(header_name), Ok(header_value)) = (
-                    key.parse::<http::HeaderName>(),
-                    value.parse::<http::HeaderValue>(),
-                ) {
-                    headers.insert(header_name, header_value);
-                }
-            }
-        }
+    async fn handle_response(&self, response: Response) -> CoreResult<Response> {
+        modify_response(self, response, &TemplateContext::default()).await
+    }
+}
 
-        // Modify status code if specified
-        if let Some(status_code) = self.modify_status_code
-            && let Ok(new_status) = StatusCode::from_u16(status_code)
-        {
-            *response.status_mut() = new_status;
-        }
+/// Applies a modify-response handler. `context` describes the request that
+/// was sent upstream, for templates in header values.
+pub async fn modify_response(
+    config: &ModifyResponseConfig,
+    mut response: Response,
+    context: &TemplateContext,
+) -> CoreResult<Response> {
+    // Run header operations against the upstream response
+    if let Some(ref modify_headers) = config.modify_headers {
+        let context = context.with_response(response.status(), response.headers());
+        apply_header_ops(response.headers_mut(), &modify_headers.ops(), &context)?;
+    }
 
-        // Modify body if specified
-        if let Some(ref new_body) = self.modify_body {
-            let body_bytes = new_body.as_bytes().to_vec();
-
-            // Remove compression-related headers since the response is being modified
-            let headers = response.headers_mut();
-            headers.remove("content-encoding");
-            headers.remove("transfer-encoding");
-
-            // Update content-length header
-            if let Ok(content_length) = body_bytes.len().to_string().parse() {
-                headers.insert("content-length", content_length);
-            }
-
-            // Replace the body with the new content
-            let new_body = Full::new(bytes::Bytes::from(body_bytes))
-                .map_err(|e| anyhow::anyhow!("Body error: {}", e))
-                .boxed();
-            *response.body_mut() = Body::new(new_body)
-        }
+    // Modify status code if specified
+    if let Some(status_code) = config.modify_status_code
+        && let Ok(new_status) = StatusCode::from_u16(status_code)
+    {
+        *response.status_mut() = new_status;
+    }
 
-        Ok(response)
+    // Replace and/or transform the body, keeping its content encoding
+    let transforms = config.body_transforms.as_deref().unwrap_or_default();
+    if config.modify_body.is_some() || !transforms.is_empty() {
+        let (mut parts, body) = response.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let body = rewrite_body(
+            &mut parts.headers,
+            body,
+            config.modify_body.as_deref(),
+            transforms,
+        )
+        .await?;
+        response = Response::from_parts(parts, Body::from(body));
     }
+
+    Ok(response)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::body_codec::decode_body;
     use super::*;
     use anyhow::Result;
     use axum::{body::Body, response::Response};
     use http_body_util::Empty;
+    use lynx_storage::dao::request_processing_dao::handlers::{BodyTransform, ModifyHeaders};
     use std::collections::HashMap;
 
     fn create_test_response() -> Response {
@@ -93,10 +96,11 @@ mod tests {
         headers.insert("X-Custom-Header".to_string(), "custom-value".to_string());
 
         let config = ModifyResponseConfig {
-            modify_headers: Some(headers),
+            modify_headers: Some(headers.into()),
             modify_body: None,
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -111,6 +115,37 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_header_ops_see_the_request() -> Result<()> {
+        let ops: ModifyHeaders = serde_json::from_value(serde_json::json!([
+            { "op": "set", "name": "x-request-id", "value": "{{req.header.x-request-id}}" },
+            { "op": "append", "name": "set-cookie", "value": "seen={{res.status}}" },
+            { "op": "remove", "name": "content-type" }
+        ]))?;
+        let config = ModifyResponseConfig {
+            modify_headers: Some(ops),
+            ..Default::default()
+        };
+
+        let mut request = create_test_request();
+        request
+            .headers_mut()
+            .insert("x-request-id", "abc".parse().unwrap());
+        let context = TemplateContext::from_request(&request);
+        let mut response = create_test_response();
+        response
+            .headers_mut()
+            .insert("set-cookie", "session=1".parse().unwrap());
+        let result = modify_response(&config, response, &context).await?;
+
+        assert_eq!(result.headers().get("x-request-id").unwrap(), "abc");
+        let cookies: Vec<_> = result.headers().get_all("set-cookie").iter().collect();
+        assert_eq!(cookies, ["session=1", "seen=200"]);
+        assert!(result.headers().get("content-type").is_none());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_modify_response_status_code() -> Result<()> {
         let config = ModifyResponseConfig {
@@ -118,6 +153,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_status_code: Some(404),
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -136,6 +172,7 @@ mod tests {
             modify_body: Some(new_body_content.to_string()),
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -155,10 +192,11 @@ mod tests {
 
         let new_body_content = "New response content";
         let config = ModifyResponseConfig {
-            modify_headers: Some(headers),
+            modify_headers: Some(headers.into()),
             modify_body: Some(new_body_content.to_string()),
             modify_method: None,
             modify_status_code: Some(201),
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -182,6 +220,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         let request = create_test_request();
@@ -207,6 +246,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -231,6 +271,7 @@ mod tests {
             modify_body: None,
             modify_method: None,
             modify_status_code: Some(99), // Invalid status code (below 100)
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -252,10 +293,11 @@ mod tests {
         headers.insert("Good-Header".to_string(), "good-value".to_string());
 
         let config = ModifyResponseConfig {
-            modify_headers: Some(headers),
+            modify_headers: Some(headers.into()),
             modify_body: None,
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         let response = create_test_response();
@@ -270,13 +312,14 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_modify_body_removes_compression_headers() -> Result<()> {
+    async fn test_modify_body_keeps_content_encoding() -> Result<()> {
         let new_body_content = "Modified response body";
         let config = ModifyResponseConfig {
             modify_headers: None,
             modify_body: Some(new_body_content.to_string()),
             modify_method: None,
             modify_status_code: None,
+            body_transforms: None,
         };
 
         // Create a response with compression headers
@@ -290,13 +333,43 @@ mod tests {
 
         let result = config.handle_response(response).await?;
 
-        // Check that compression headers are removed
-        assert!(result.headers().get("content-encoding").is_none());
+        // The new body is re-encoded with the original content encoding
+        assert_eq!(result.headers().get("content-encoding").unwrap(), "gzip");
         assert!(result.headers().get("transfer-encoding").is_none());
 
-        // Check content-length header is updated
-        let content_length = result.headers().get("content-length").unwrap();
-        assert_eq!(content_length, &new_body_content.len().to_string());
+        let content_length = result.headers().get("content-length").unwrap().clone();
+        let body = result.into_body().collect().await?.to_bytes();
+        assert_eq!(content_length, &body.len().to_string());
+        let decoded = decode_body("gzip", &body).await?.unwrap();
+        assert_eq!(decoded, new_body_content);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_body_transforms_edit_one_field() -> Result<()> {
+        let config = ModifyResponseConfig {
+            body_transforms: Some(vec![
+                BodyTransform::JsonPathSet {
+                    path: "$.user.role".to_string(),
+                    value: serde_json::json!("admin"),
+                },
+                BodyTransform::RegexReplace {
+                    pattern: "alice".to_string(),
+                    replacement: "bob".to_string(),
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let response = Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"user":{"name":"alice","role":"guest"}}"#))
+            .unwrap();
+        let result = config.handle_response(response).await?;
+
+        let body = result.into_body().collect().await?.to_bytes();
+        assert_eq!(body, r#"{"user":{"name":"bob","role":"admin"}}"#);
 
         Ok(())
     }
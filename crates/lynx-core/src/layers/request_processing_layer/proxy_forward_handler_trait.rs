@@ -1,8 +1,15 @@
-use http::Uri;
+use http::header::HOST;
 use http::uri::{Authority, PathAndQuery, Scheme};
-use lynx_storage::dao::request_processing_dao::handlers::proxy_forward_handler::ProxyForwardConfig;
+use http::{HeaderValue, Uri};
+use lynx_storage::dao::request_processing_dao::handlers::proxy_forward_handler::{
+    ProxyForwardConfig, ProxyForwardTarget, QueryMode,
+};
+use rand::Rng;
+use serde_json::{Map, Value};
 
 use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::regex_cache::cached_regex;
+use super::template::TemplateContext;
 use crate::{
     common::Req,
     error::{CoreError, CoreResult},
@@ -20,40 +27,159 @@ fn proxy_forward_validation_message(
     }
 }
 
+/// The client's `Host`, kept by a forward with `preserveHost` so the
+/// websocket handshake does not replace it with the target authority.
+#[derive(Debug, Clone)]
+pub struct PreservedHost(pub HeaderValue);
+
+/// A `ProxyForward` handler bound to the path glob captures of its rule match.
+pub struct ProxyForward<'a> {
+    config: &'a ProxyForwardConfig,
+    params: &'a [String],
+}
+
+impl<'a> ProxyForward<'a> {
+    pub fn new(config: &'a ProxyForwardConfig, params: &'a [String]) -> Self {
+        Self { config, params }
+    }
+
+    /// Picks one of `targets` by weight, if any are configured.
+    fn pick_target(&self) -> Option<&'a ProxyForwardTarget> {
+        let total: u64 = self
+            .config
+            .targets
+            .iter()
+            .map(|target| u64::from(target.weight))
+            .sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rand::thread_rng().gen_range(0..total);
+        self.config.targets.iter().find(|target| {
+            let weight = u64::from(target.weight);
+            if roll < weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+    }
+
+    /// Numbered and named groups of `path_regex` on `path`; empty when it does not match.
+    fn path_groups(&self, path: &str) -> CoreResult<Map<String, Value>> {
+        let mut groups = Map::new();
+        let Some(pattern) = &self.config.path_regex else {
+            return Ok(groups);
+        };
+        // Rule validation rejects invalid patterns; this only catches rules
+        // written to disk by hand.
+        let regex = cached_regex(pattern).map_err(|e| {
+            proxy_forward_validation_message(self.config, format!("pathRegex is not valid: {e}"))
+        })?;
+        let Some(captures) = regex.captures(path) else {
+            return Ok(groups);
+        };
+        for (index, group) in captures.iter().enumerate() {
+            if let Some(group) = group {
+                groups.insert(index.to_string(), Value::from(group.as_str()));
+            }
+        }
+        for name in regex.capture_names().flatten() {
+            if let Some(group) = captures.name(name) {
+                groups.insert(name.to_string(), Value::from(group.as_str()));
+            }
+        }
+        Ok(groups)
+    }
+
+    /// The query to forward under `query_mode`. Merged requests keep the
+    /// original encoding of the parameters they do not override.
+    fn rewrite_query(
+        &self,
+        query: Option<&str>,
+        context: &TemplateContext,
+    ) -> CoreResult<Option<String>> {
+        let mut pairs: Vec<String> = match self.config.query_mode {
+            QueryMode::Keep => return Ok(query.map(str::to_string)),
+            QueryMode::Replace => Vec::new(),
+            QueryMode::Merge => query
+                .unwrap_or_default()
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .filter(|pair| {
+                    form_urlencoded::parse(pair.as_bytes())
+                        .next()
+                        .is_none_or(|(name, _)| !self.config.query_params.contains_key(&*name))
+                })
+                .map(str::to_string)
+                .collect(),
+        };
+        for (name, value) in &self.config.query_params {
+            pairs.push(
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair(name, &context.render(value)?)
+                    .finish(),
+            );
+        }
+        Ok((!pairs.is_empty()).then(|| pairs.join("&")))
+    }
+}
+
 #[async_trait::async_trait]
-impl HandlerTrait for ProxyForwardConfig {
+impl<'a> HandlerTrait for ProxyForward<'a> {
     async fn handle_request(&self, mut request: Req) -> CoreResult<HandleRequestType> {
+        let config = self.config;
+        let target = self.pick_target();
+        let target_field = |pick: fn(&ProxyForwardTarget) -> &Option<String>,
+                            fallback: &'a Option<String>| {
+            target
+                .and_then(|target| ProxyForwardConfig::optional_field(pick(target)))
+                .or_else(|| ProxyForwardConfig::optional_field(fallback))
+        };
+        let target_scheme = target_field(|t| &t.target_scheme, &config.target_scheme);
+        let target_authority = target_field(|t| &t.target_authority, &config.target_authority);
+        let target_path = target_field(|t| &t.target_path, &config.target_path);
+
+        let mut context = TemplateContext::from_request(&request);
+        context.insert("params", Value::from(self.params.to_vec()));
+        context.insert(
+            "groups",
+            Value::Object(self.path_groups(request.uri().path())?),
+        );
+
         // Get the current request URI
         let current_uri = request.uri().clone();
         let original_uri_str = current_uri.to_string();
         let current_parts = current_uri.into_parts();
+        let original_authority = current_parts.authority.clone();
 
         // Build new URI with target configuration, keeping original components as fallback
         let mut uri_builder = Uri::builder();
 
         // Use target scheme or fallback to original
-        let scheme =
-            if let Some(target_scheme) = ProxyForwardConfig::optional_field(&self.target_scheme) {
-                Some(target_scheme.parse::<Scheme>().map_err(|_| {
-                    proxy_forward_validation_message(
-                        self,
-                        format!("scheme '{target_scheme}' is not valid"),
-                    )
-                })?)
-            } else {
-                current_parts.scheme
-            };
+        let scheme = if let Some(target_scheme) = target_scheme {
+            Some(target_scheme.parse::<Scheme>().map_err(|_| {
+                proxy_forward_validation_message(
+                    config,
+                    format!("scheme '{target_scheme}' is not valid"),
+                )
+            })?)
+        } else {
+            current_parts.scheme
+        };
         if let Some(scheme) = scheme {
             uri_builder = uri_builder.scheme(scheme);
         }
 
-        // Use target authority or fallback to original
-        let authority = if let Some(target_authority) =
-            ProxyForwardConfig::optional_field(&self.target_authority)
-        {
-            Some(target_authority.parse::<Authority>().map_err(|_| {
+        // Use target authority (a template, e.g. picked from a header) or fallback to original
+        let target_authority = target_authority
+            .map(|authority| context.render(authority))
+            .transpose()?
+            .filter(|authority| !authority.trim().is_empty());
+        let authority = if let Some(target_authority) = target_authority {
+            Some(target_authority.trim().parse::<Authority>().map_err(|_| {
                 proxy_forward_validation_message(
-                    self,
+                    config,
                     format!("authority '{target_authority}' is not valid"),
                 )
             })?)
@@ -64,33 +190,27 @@ impl HandlerTrait for ProxyForwardConfig {
             uri_builder = uri_builder.authority(authority);
         }
 
-        // Use target path or original path and query
-        let path_and_query =
-            if let Some(target_path) = ProxyForwardConfig::optional_field(&self.target_path) {
-                if let Some(current_pq) = current_parts.path_and_query {
-                    // Combine target path with original query
-                    if target_path != "/" {
-                        format!(
-                            "{}{}?{}",
-                            target_path,
-                            current_pq.path(),
-                            current_pq.query().unwrap_or("")
-                        )
-                    } else {
-                        format!("{}?{}", current_pq.path(), current_pq.query().unwrap_or(""))
-                    }
-                } else {
-                    target_path.to_string()
-                }
-            } else if let Some(current_pq) = current_parts.path_and_query {
-                current_pq.to_string()
-            } else {
-                "/".to_string()
-            };
+        // A templated target path replaces the path, a plain one is prefixed to it
+        let current_pq = current_parts.path_and_query;
+        let current_path = current_pq.as_ref().map(PathAndQuery::path).unwrap_or("");
+        let mut path = match target_path {
+            Some(target_path) if target_path.contains("{{") => context.render(target_path)?,
+            Some(target_path) if target_path != "/" => format!("{target_path}{current_path}"),
+            _ => current_path.to_string(),
+        };
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+        let query =
+            self.rewrite_query(current_pq.as_ref().and_then(PathAndQuery::query), &context)?;
+        let path_and_query = match query {
+            Some(query) => format!("{path}?{query}"),
+            None => path,
+        };
 
         let path_and_query = path_and_query.parse::<PathAndQuery>().map_err(|e| {
             proxy_forward_validation_message(
-                self,
+                config,
                 format!("path and query '{path_and_query}' is not valid: {e}"),
             )
         })?;
@@ -99,9 +219,20 @@ impl HandlerTrait for ProxyForwardConfig {
         // Build the new URI and update the request
         let new_uri = uri_builder
             .build()
-            .map_err(|e| proxy_forward_validation_message(self, e))?;
+            .map_err(|e| proxy_forward_validation_message(config, e))?;
         *request.uri_mut() = new_uri;
 
+        // The HTTP client only fills in `Host` from the URI when it is missing
+        if config.preserve_host {
+            let host = original_authority
+                .and_then(|authority| HeaderValue::from_str(authority.as_str()).ok())
+                .or_else(|| request.headers().get(HOST).cloned());
+            if let Some(host) = host {
+                request.headers_mut().insert(HOST, host.clone());
+                request.extensions_mut().insert(PreservedHost(host));
+            }
+        }
+
         tracing::trace!(
             "Proxying request from {} to {}",
             original_uri_str,
@@ -112,6 +243,13 @@ impl HandlerTrait for ProxyForwardConfig {
     }
 }
 
+#[async_trait::async_trait]
+impl HandlerTrait for ProxyForwardConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        ProxyForward::new(self, &[]).handle_request(request).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::empty;
@@ -126,6 +264,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("invalid url host".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -150,6 +289,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("example.com:8080".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -178,6 +318,7 @@ mod tests {
             target_scheme: Some("".to_string()),
             target_authority: Some("127.0.0.1:9090".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -207,6 +348,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("127.0.0.1:9090".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -236,6 +378,7 @@ mod tests {
             target_scheme: Some("https".to_string()),
             target_authority: Some("secure.example.com:8443".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -265,6 +408,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("api.service.com".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -293,6 +437,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("backend.internal:5000".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -322,6 +467,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("microservice:8080".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -351,6 +497,7 @@ mod tests {
             target_scheme: Some("http".to_string()),
             target_authority: Some("development.local:65432".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -379,6 +526,7 @@ mod tests {
             target_scheme: Some("ws".to_string()),
             target_authority: Some("websocket.service:3001".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -418,6 +566,7 @@ mod tests {
                 target_scheme: Some("http".to_string()),
                 target_authority: Some(authority.to_string()),
                 target_path: None,
+                ..Default::default()
             };
 
             let request = Request::builder()
@@ -446,6 +595,7 @@ mod tests {
             target_scheme: Some("https".to_string()),
             target_authority: Some("api.backend:9443".to_string()),
             target_path: None,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -466,4 +616,108 @@ mod tests {
             HandleRequestType::Response(_) => panic!("Expected request, got response"),
         }
     }
+
+    fn forwarded_uri(result: HandleRequestType) -> Req {
+        match result {
+            HandleRequestType::Request(request) => request,
+            HandleRequestType::Response(_) => panic!("Expected request, got response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forward_templates_path_and_merges_query() {
+        let config = ProxyForwardConfig {
+            target_authority: Some("localhost:3000".to_string()),
+            target_path: Some("/{{groups.version}}/users/{{params.[0]}}".to_string()),
+            path_regex: Some("^/api/(?<version>v\\d+)/".to_string()),
+            query_mode: QueryMode::Merge,
+            query_params: [
+                ("debug".to_string(), "1".to_string()),
+                ("tenant".to_string(), "{{req.header.x-tenant}}".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let params = vec!["42/posts".to_string()];
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("https://prod.example.com/api/v1/users/42/posts?page=2&debug=0&q=a%20b")
+            .header("x-tenant", "acme")
+            .body(empty())
+            .unwrap();
+
+        let request = forwarded_uri(
+            ProxyForward::new(&config, &params)
+                .handle_request(request)
+                .await
+                .unwrap(),
+        );
+        let uri = request.uri();
+        assert_eq!(uri.scheme_str(), Some("https"));
+        assert_eq!(uri.authority().unwrap().as_str(), "localhost:3000");
+        assert_eq!(uri.path(), "/v1/users/42/posts");
+        assert_eq!(uri.query(), Some("page=2&q=a%20b&debug=1&tenant=acme"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forward_replaces_query_and_preserves_host() {
+        let config = ProxyForwardConfig {
+            target_authority: Some("{{req.header.x-upstream}}".to_string()),
+            query_mode: QueryMode::Replace,
+            preserve_host: true,
+            ..Default::default()
+        };
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("http://prod.example.com/health?verbose=1")
+            .header("x-upstream", "127.0.0.1:9090")
+            .body(empty())
+            .unwrap();
+
+        let request = forwarded_uri(config.handle_request(request).await.unwrap());
+        assert_eq!(
+            request.uri().authority().unwrap().as_str(),
+            "127.0.0.1:9090"
+        );
+        assert_eq!(request.uri().path(), "/health");
+        assert_eq!(request.uri().query(), None);
+        assert_eq!(request.headers()[HOST], "prod.example.com");
+        assert!(request.extensions().get::<PreservedHost>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forward_picks_weighted_target() {
+        let config = ProxyForwardConfig {
+            target_scheme: Some("http".to_string()),
+            targets: vec![
+                ProxyForwardTarget {
+                    target_authority: Some("drained.internal:80".to_string()),
+                    weight: 0,
+                    ..Default::default()
+                },
+                ProxyForwardTarget {
+                    target_authority: Some("canary.internal:8080".to_string()),
+                    target_path: Some("/canary".to_string()),
+                    weight: 5,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let request = Request::builder()
+                .method(Method::GET)
+                .uri("https://prod.example.com/orders")
+                .body(empty())
+                .unwrap();
+            let request = forwarded_uri(config.handle_request(request).await.unwrap());
+            let uri = request.uri();
+            assert_eq!(uri.scheme_str(), Some("http"));
+            assert_eq!(uri.authority().unwrap().as_str(), "canary.internal:8080");
+            assert_eq!(uri.path(), "/canary/orders");
+        }
+    }
 }
@@ -0,0 +1,178 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use anyhow::anyhow;
+use axum::{body::Body, response::Response};
+use bytes::{Bytes, BytesMut};
+use http_body::{Frame, SizeHint};
+use http_body_util::BodyExt;
+use lynx_storage::{DataStore, dao::request_processing_dao::handlers::RecordConfig};
+use pin_project_lite::pin_project;
+
+use super::cassette::{
+    CassetteEntry, CassetteRequest, CassetteResponse, append_entry, cassette_path,
+};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{common::Req, error::CoreResult, utils::full};
+
+/// The request as `Record` saw it, kept until its response arrives.
+#[derive(Debug, Clone)]
+struct RecordedRequest(CassetteRequest);
+
+/// What `Record` needs from the request side, captured before the request is
+/// handed to the upstream service.
+#[derive(Debug, Clone, Default)]
+pub struct RecordContext {
+    request: Option<CassetteRequest>,
+}
+
+impl RecordContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            request: request
+                .extensions()
+                .get::<RecordedRequest>()
+                .map(|request| request.0.clone()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for RecordConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let (mut parts, body) = request.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let mut recorded = CassetteRequest::new(&parts.method, &parts.uri, &parts.headers, &body);
+        recorded.redact_headers(|name| self.redacts(name));
+        parts.extensions.insert(RecordedRequest(recorded));
+        Ok(HandleRequestType::Request(Req::from_parts(
+            parts,
+            full(body),
+        )))
+    }
+}
+
+/// Passes the response on, appending the exchange to the rule's cassette
+/// once its body has been sent in full.
+pub async fn record_response(
+    config: &RecordConfig,
+    response: Response,
+    context: &RecordContext,
+    store: &Arc<DataStore>,
+    project: &str,
+) -> CoreResult<Response> {
+    let Some(request) = context.request.clone() else {
+        return Ok(response);
+    };
+    let (parts, body) = response.into_parts();
+    let mut recorded = CassetteResponse::new(parts.status.as_u16(), &parts.headers, &[]);
+    recorded.redact_headers(|name| config.redacts(name));
+    let recording = Recording {
+        entry: CassetteEntry {
+            request,
+            response: recorded,
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+        },
+        store: store.clone(),
+        project: project.to_string(),
+        cassette: config.cassette().to_string(),
+    };
+    let body = RecordingBody {
+        inner: body,
+        copy: BytesMut::new(),
+        recording: Some(recording),
+        writing: None,
+    };
+    Ok(Response::from_parts(parts, Body::new(body)))
+}
+
+/// An exchange waiting for its response body.
+struct Recording {
+    entry: CassetteEntry,
+    store: Arc<DataStore>,
+    project: String,
+    cassette: String,
+}
+
+impl Recording {
+    async fn write(self) {
+        // A cassette that can't be written must not break the response.
+        let written = match cassette_path(&self.store, &self.project, &self.cassette).await {
+            Ok(path) => append_entry(&path, &self.entry).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            tracing::warn!(
+                "Failed to record {} {}: {e}",
+                self.entry.request.method,
+                self.entry.request.url
+            );
+        }
+    }
+}
+
+pin_project! {
+    /// Forwards a response body while copying it, and writes the recording
+    /// before reporting the end of the body. Bodies that fail or are dropped
+    /// early are not recorded.
+    struct RecordingBody {
+        #[pin]
+        inner: Body,
+        copy: BytesMut,
+        recording: Option<Recording>,
+        writing: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    }
+}
+
+impl http_body::Body for RecordingBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        if let Some(writing) = this.writing {
+            ready!(writing.as_mut().poll(cx));
+            *this.writing = None;
+            return Poll::Ready(None);
+        }
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(chunk) = frame.data_ref() {
+                    this.copy.extend_from_slice(chunk);
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(e)) => {
+                *this.recording = None;
+                Poll::Ready(Some(Err(e)))
+            }
+            None => {
+                let Some(mut recording) = this.recording.take() else {
+                    return Poll::Ready(None);
+                };
+                recording.entry.response.set_body(this.copy);
+                let mut writing = Box::pin(recording.write());
+                if writing.as_mut().poll(cx).is_pending() {
+                    *this.writing = Some(writing);
+                    return Poll::Pending;
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.recording.is_none() && self.writing.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
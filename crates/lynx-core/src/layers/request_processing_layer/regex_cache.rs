@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use regex::Regex;
+
+const MAX_CACHED_PATTERNS: usize = 256;
+
+/// Patterns of rule handlers, compiled once for the cached rules that use them.
+static REGEX_CACHE: LazyLock<Mutex<HashMap<String, Regex>>> = LazyLock::new(Default::default);
+
+/// `pattern` compiled, reusing the regex from an earlier call.
+pub fn cached_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut cache = REGEX_CACHE.lock().expect("regex cache poisoned");
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(pattern)?;
+    // Edited rules leave stale patterns behind; start over rather than grow.
+    if cache.len() >= MAX_CACHED_PATTERNS {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), regex.clone());
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_compile_once() {
+        let pattern = "^cached-(\\d+)$";
+        let first = cached_regex(pattern).unwrap();
+        assert!(REGEX_CACHE.lock().unwrap().contains_key(pattern));
+        assert_eq!(cached_regex(pattern).unwrap().as_str(), first.as_str());
+        assert!(cached_regex("(unclosed").is_err());
+    }
+}
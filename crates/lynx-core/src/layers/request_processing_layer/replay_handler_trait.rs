@@ -0,0 +1,357 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use axum::{
+    body::Body,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderName, HeaderValue, StatusCode};
+use http_body_util::BodyExt;
+use lynx_storage::{
+    DataStore,
+    dao::request_processing_dao::handlers::{ReplayConfig, ReplayFallback, ReplayMatchKey},
+};
+
+use super::body_codec::set_content_length;
+use super::cassette::{
+    CassetteEntry, CassetteRequest, cassette_path, is_redacted, load_cassette, redacted,
+};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    utils::full,
+};
+
+/// A `Replay` handler with its cassette loaded.
+pub struct Replay<'a> {
+    config: &'a ReplayConfig,
+    entries: Arc<Vec<CassetteEntry>>,
+}
+
+impl<'a> Replay<'a> {
+    pub async fn resolve(
+        config: &'a ReplayConfig,
+        store: &Arc<DataStore>,
+        project: &str,
+    ) -> CoreResult<Self> {
+        let path = cassette_path(store, project, config.cassette()).await?;
+        Ok(Self {
+            config,
+            entries: load_cassette(&path).await?,
+        })
+    }
+
+    /// The most recently recorded entry matching `request` on every key.
+    fn find(&self, request: &CassetteRequest) -> Option<&CassetteEntry> {
+        let match_on = self.config.match_on();
+        let query = match_on
+            .contains(&ReplayMatchKey::Query)
+            .then(|| request.sorted_query());
+        let authority = request.authority();
+        self.entries.iter().rev().find(|entry| {
+            let recorded = &entry.request;
+            match_on.iter().all(|key| match key {
+                ReplayMatchKey::Method => recorded.method.eq_ignore_ascii_case(&request.method),
+                ReplayMatchKey::Host => recorded.authority() == authority,
+                ReplayMatchKey::Path => recorded.path == request.path,
+                ReplayMatchKey::Query => Some(recorded.sorted_query()) == query,
+                ReplayMatchKey::Body => recorded.body_sha256 == request.body_sha256,
+            }) && self.config.match_headers.iter().all(|name| {
+                let name = name.to_ascii_lowercase();
+                match (recorded.headers.get(&name), request.headers.get(&name)) {
+                    (Some(recorded), Some(value)) => {
+                        *recorded == *value || *recorded == redacted(value)
+                    }
+                    (recorded, value) => recorded == value,
+                }
+            })
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for Replay<'_> {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let (parts, body) = request.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let key = CassetteRequest::new(&parts.method, &parts.uri, &parts.headers, &body);
+
+        if let Some(entry) = self.find(&key) {
+            return Ok(HandleRequestType::Response(replay_response(entry)?));
+        }
+        match self.config.fallback {
+            ReplayFallback::Passthrough => Ok(HandleRequestType::Request(Req::from_parts(
+                parts,
+                full(body),
+            ))),
+            ReplayFallback::NotFound => {
+                let response = Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .header("content-type", "text/plain")
+                    .header("x-served-by", "lynx-proxy-replay")
+                    .body(full(format!(
+                        "No cassette entry for {} {}",
+                        key.method, key.url
+                    )))?;
+                Ok(HandleRequestType::Response(response.into_response()))
+            }
+            ReplayFallback::Error => Err(CoreError::NotFound {
+                message: format!("no cassette entry for {} {}", key.method, key.url),
+            }),
+        }
+    }
+}
+
+fn replay_response(entry: &CassetteEntry) -> CoreResult<Response> {
+    let recorded = &entry.response;
+    let body = recorded.body_bytes()?;
+    let mut response = Response::builder()
+        .status(StatusCode::from_u16(recorded.status)?)
+        .body(Body::empty())?;
+    let headers = response.headers_mut();
+    // Redacted headers were never recorded, so there is nothing to send.
+    for (name, value) in recorded
+        .headers
+        .iter()
+        .filter(|(_, value)| !is_redacted(value))
+    {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    set_content_length(headers, body.len());
+    headers.insert("x-served-by", HeaderValue::from_static("lynx-proxy-replay"));
+    *response.body_mut() = Body::from(body);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::request_processing_layer::record_handler_trait::{
+        RecordContext, record_response,
+    };
+    use axum::http::{Method, Request};
+    use lynx_storage::dao::request_processing_dao::handlers::RecordConfig;
+
+    fn request(method: Method, uri: &str, body: &str) -> Req {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-tenant", "a")
+            .body(full(body.to_string()))
+            .unwrap()
+    }
+
+    async fn record(store: &Arc<DataStore>, request: Req, status: u16, body: &'static str) {
+        let config = RecordConfig::default();
+        let HandleRequestType::Request(request) = config.handle_request(request).await.unwrap()
+        else {
+            panic!("record should pass the request on");
+        };
+        let context = RecordContext::from_request(&request);
+        let response = Response::builder()
+            .status(status)
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .body(Body::from(body))
+            .unwrap();
+        let response = record_response(&config, response, &context, store, "default")
+            .await
+            .unwrap();
+        let passed_on = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(passed_on, body);
+    }
+
+    async fn replay(
+        config: &ReplayConfig,
+        store: &Arc<DataStore>,
+        request: Req,
+    ) -> HandleRequestType {
+        Replay::resolve(config, store, "default")
+            .await
+            .unwrap()
+            .handle_request(request)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_recorded_exchange_is_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        record(
+            &store,
+            request(Method::GET, "http://api.test/users?a=1&b=2", ""),
+            200,
+            "v1",
+        )
+        .await;
+        record(
+            &store,
+            request(Method::GET, "http://api.test/users?a=1&b=2", ""),
+            200,
+            "v2",
+        )
+        .await;
+        record(
+            &store,
+            request(Method::POST, "http://api.test/users", "{}"),
+            201,
+            "created",
+        )
+        .await;
+        assert!(
+            store
+                .cassettes_dir()
+                .join("default/cassette.jsonl")
+                .exists()
+        );
+
+        let config = ReplayConfig::default();
+        let HandleRequestType::Response(response) = replay(
+            &config,
+            &store,
+            request(Method::GET, "http://api.test/users?b=2&a=1", ""),
+        )
+        .await
+        else {
+            panic!("expected a replayed response");
+        };
+        assert_eq!(response.status(), StatusCode::OK);
+        // `set-cookie` is redacted by default, so it is not replayed.
+        assert!(response.headers().get("set-cookie").is_none());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "v2");
+
+        let by_body = ReplayConfig {
+            match_on: Some(vec![ReplayMatchKey::Method, ReplayMatchKey::Body]),
+            ..Default::default()
+        };
+        let HandleRequestType::Response(response) = replay(
+            &by_body,
+            &store,
+            request(Method::POST, "http://api.test/other", "{}"),
+        )
+        .await
+        else {
+            panic!("expected a replayed response");
+        };
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_replay_fallbacks() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        record(
+            &store,
+            request(Method::GET, "http://api.test/users", ""),
+            200,
+            "ok",
+        )
+        .await;
+
+        let by_header = ReplayConfig {
+            match_headers: vec!["X-Tenant".to_string()],
+            fallback: ReplayFallback::NotFound,
+            ..Default::default()
+        };
+        let mut other_tenant = request(Method::GET, "http://api.test/users", "");
+        other_tenant
+            .headers_mut()
+            .insert("x-tenant", HeaderValue::from_static("b"));
+        let HandleRequestType::Response(response) = replay(&by_header, &store, other_tenant).await
+        else {
+            panic!("expected a 404");
+        };
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let passthrough = ReplayConfig::default();
+        let HandleRequestType::Request(request_back) = replay(
+            &passthrough,
+            &store,
+            request(Method::GET, "http://api.test/missing", "x"),
+        )
+        .await
+        else {
+            panic!("expected passthrough");
+        };
+        let body = request_back.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "x");
+
+        let error = ReplayConfig {
+            fallback: ReplayFallback::Error,
+            ..Default::default()
+        };
+        let result = Replay::resolve(&error, &store, "default")
+            .await
+            .unwrap()
+            .handle_request(request(Method::GET, "http://api.test/missing", ""))
+            .await;
+        assert!(matches!(result, Err(CoreError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_credentials_are_redacted_and_hosts_kept_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        let with_token = |token: &'static str, uri: &str| {
+            let mut request = request(Method::GET, uri, "");
+            request
+                .headers_mut()
+                .insert("authorization", HeaderValue::from_static(token));
+            request
+        };
+        record(
+            &store,
+            with_token("Bearer s3cret", "http://api.test/me"),
+            200,
+            "me",
+        )
+        .await;
+
+        let cassette =
+            std::fs::read_to_string(store.cassettes_dir().join("default/cassette.jsonl")).unwrap();
+        assert!(!cassette.contains("s3cret"));
+        assert!(!cassette.contains("a=1"));
+
+        let by_token = ReplayConfig {
+            match_headers: vec!["authorization".to_string()],
+            fallback: ReplayFallback::NotFound,
+            ..Default::default()
+        };
+        let HandleRequestType::Response(response) = replay(
+            &by_token,
+            &store,
+            with_token("Bearer s3cret", "http://api.test/me"),
+        )
+        .await
+        else {
+            panic!("expected a replayed response");
+        };
+        assert_eq!(response.status(), StatusCode::OK);
+        let HandleRequestType::Response(response) = replay(
+            &by_token,
+            &store,
+            with_token("Bearer other", "http://api.test/me"),
+        )
+        .await
+        else {
+            panic!("expected a 404");
+        };
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let other_host = replay(
+            &ReplayConfig::default(),
+            &store,
+            request(Method::GET, "http://staging.api.test/me", ""),
+        )
+        .await;
+        assert!(matches!(other_host, HandleRequestType::Request(_)));
+    }
+}
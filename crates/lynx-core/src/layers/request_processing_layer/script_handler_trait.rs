@@ -0,0 +1,756 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use axum::{body::Body, response::Response};
+use bytes::Bytes;
+use http::{
+    Extensions, HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+    header::CONTENT_ENCODING,
+};
+use http_body_util::BodyExt;
+use lynx_storage::dao::request_processing_dao::handlers::ScriptHandlerConfig;
+use rhai::{AST, Dynamic, Engine, EvalAltResult, Map, Scope};
+
+use super::body_codec::{content_encoding, decode_body, set_content_length};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    layers::{
+        message_package_layer::{
+            MessageEventChannel,
+            message_event_data::{ScriptLog, ScriptLogLevel},
+            message_event_store::MessageEvent,
+        },
+        trace_id_layer::service::TraceId,
+    },
+    utils::full,
+};
+
+const ON_REQUEST: &str = "onRequest";
+const ON_RESPONSE: &str = "onResponse";
+const MAX_CALL_LEVELS: usize = 64;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_CACHED_SCRIPTS: usize = 64;
+
+/// Compiled scripts keyed by their source, shared by both hooks.
+static AST_CACHE: LazyLock<Mutex<HashMap<String, Arc<AST>>>> = LazyLock::new(Default::default);
+
+/// The request as scripts see it.
+///
+/// Kept in the request extensions after `onRequest` so `onResponse` receives
+/// the request that was actually sent upstream.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// A response as scripts see it; also the value `respond(...)` returns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// What the response hook needs from the request side, captured before the
+/// request is handed to the upstream service.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    request: Option<ScriptRequest>,
+    logger: ScriptLogger,
+}
+
+impl ScriptContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            request: request.extensions().get::<ScriptRequest>().cloned(),
+            logger: ScriptLogger::from_extensions(request.extensions()),
+        }
+    }
+}
+
+/// Forwards script and plugin output to the capture of the current trace.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ScriptLogger {
+    trace_id: Option<TraceId>,
+    channel: Option<Arc<MessageEventChannel>>,
+}
+
+impl ScriptLogger {
+    pub(super) fn from_extensions(extensions: &Extensions) -> Self {
+        Self {
+            trace_id: extensions.get::<TraceId>().cloned(),
+            channel: extensions.get::<Arc<MessageEventChannel>>().cloned(),
+        }
+    }
+
+    pub(super) fn emit(&self, logs: Vec<ScriptLog>) {
+        for log in logs {
+            tracing::debug!("{} [{:?}]: {}", log.hook, log.level, log.message);
+            if let (Some(trace_id), Some(channel)) = (&self.trace_id, &self.channel) {
+                channel.sync_send_event(MessageEvent::OnScriptLog(trace_id.clone(), log));
+            }
+        }
+    }
+}
+
+enum HookArgs {
+    Request(ScriptRequest),
+    Response(ScriptRequest, ScriptResponse),
+}
+
+enum HookOutcome<T> {
+    Unchanged,
+    Continue(T),
+    Respond(ScriptResponse),
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for ScriptHandlerConfig {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let ast = compile_script(self)?;
+        let has_on_request = defines_hook(&ast, ON_REQUEST);
+        let has_on_response = defines_hook(&ast, ON_RESPONSE);
+        if !has_on_request && !has_on_response {
+            return Ok(HandleRequestType::Request(request));
+        }
+
+        let logger = ScriptLogger::from_extensions(request.extensions());
+        let (mut parts, body) = request.into_parts();
+        let mut body = body.collect().await?.to_bytes();
+        let mut snapshot = ScriptRequest {
+            method: parts.method.to_string(),
+            url: parts.uri.to_string(),
+            headers: headers_to_map(&parts.headers),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+
+        if has_on_request {
+            let value = run_hook(self, ast, HookArgs::Request(snapshot.clone()), &logger).await?;
+            match hook_outcome(value, ON_REQUEST, |map| snapshot.merged(map))? {
+                HookOutcome::Unchanged => {}
+                HookOutcome::Continue(changed) => {
+                    apply_request(&mut parts, &mut body, &snapshot, &changed)?;
+                    snapshot = changed;
+                }
+                HookOutcome::Respond(response) => {
+                    return Ok(HandleRequestType::Response(response.into_response()?));
+                }
+            }
+        }
+
+        if has_on_response {
+            parts.extensions.insert(snapshot);
+        }
+        Ok(HandleRequestType::Request(Req::from_parts(
+            parts,
+            full(body),
+        )))
+    }
+}
+
+/// Runs the script's `onResponse(req, res)` hook, if it defines one.
+pub async fn run_response_hook(
+    config: &ScriptHandlerConfig,
+    response: Response,
+    context: &ScriptContext,
+) -> CoreResult<Response> {
+    let ast = compile_script(config)?;
+    if !defines_hook(&ast, ON_RESPONSE) {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let raw = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+    let decoded = match content_encoding(&parts.headers) {
+        Some(encoding) => decode_body(&encoding, &raw).await.ok().flatten(),
+        None => None,
+    };
+    let before = ScriptResponse {
+        status: parts.status.as_u16(),
+        headers: headers_to_map(&parts.headers),
+        body: String::from_utf8_lossy(decoded.as_ref().unwrap_or(&raw)).into_owned(),
+    };
+    let request = context.request.clone().unwrap_or_default();
+
+    let value = run_hook(
+        config,
+        ast,
+        HookArgs::Response(request, before.clone()),
+        &context.logger,
+    )
+    .await?;
+    match hook_outcome(value, ON_RESPONSE, |map| before.merged(map))? {
+        HookOutcome::Unchanged => Ok(Response::from_parts(parts, Body::from(raw))),
+        HookOutcome::Respond(replacement) => replacement.into_response(),
+        HookOutcome::Continue(after) => {
+            parts.status = parse_status(after.status)?;
+            apply_headers(&mut parts.headers, &before.headers, &after.headers)?;
+            let body = if after.body != before.body {
+                // The new body is plain text, whatever the upstream encoding was.
+                parts.headers.remove(CONTENT_ENCODING);
+                set_content_length(&mut parts.headers, after.body.len());
+                Bytes::from(after.body)
+            } else {
+                raw
+            };
+            Ok(Response::from_parts(parts, Body::from(body)))
+        }
+    }
+}
+
+fn limited_engine(config: &ScriptHandlerConfig) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(config.max_operations())
+        .set_max_string_size(config.max_string_bytes())
+        .set_max_array_size(config.max_collection_size())
+        .set_max_map_size(config.max_collection_size())
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+    engine.disable_symbol("eval");
+    engine
+        .register_type_with_name::<ScriptResponse>("Response")
+        .register_fn("respond", |status: i64, body: &str| ScriptResponse {
+            status: u16::try_from(status).unwrap_or_default(),
+            headers: BTreeMap::new(),
+            body: body.to_string(),
+        })
+        .register_fn("respond", |status: i64, headers: Map, body: &str| {
+            ScriptResponse {
+                status: u16::try_from(status).unwrap_or_default(),
+                headers: headers
+                    .into_iter()
+                    .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+                    .collect(),
+                body: body.to_string(),
+            }
+        });
+    engine
+}
+
+fn compile_script(config: &ScriptHandlerConfig) -> CoreResult<Arc<AST>> {
+    if let Some(ast) = AST_CACHE
+        .lock()
+        .map_err(|e| anyhow!(e.to_string()))?
+        .get(&config.script)
+    {
+        return Ok(ast.clone());
+    }
+
+    let ast = limited_engine(config)
+        .compile(&config.script)
+        .map(Arc::new)
+        .map_err(|e| CoreError::Validation {
+            message: format!("script does not compile: {e}"),
+        })?;
+    let mut cache = AST_CACHE.lock().map_err(|e| anyhow!(e.to_string()))?;
+    // Edited scripts leave stale entries behind; start over rather than grow.
+    if cache.len() >= MAX_CACHED_SCRIPTS {
+        cache.clear();
+    }
+    cache.insert(config.script.clone(), ast.clone());
+    Ok(ast)
+}
+
+fn defines_hook(ast: &AST, hook: &str) -> bool {
+    ast.iter_functions().any(|function| function.name == hook)
+}
+
+/// Calls a hook on a blocking thread under the configured limits, forwarding
+/// anything it prints (and any failure) to the capture log.
+async fn run_hook(
+    config: &ScriptHandlerConfig,
+    ast: Arc<AST>,
+    args: HookArgs,
+    logger: &ScriptLogger,
+) -> CoreResult<Dynamic> {
+    let config = config.clone();
+    let hook = match args {
+        HookArgs::Request(_) => ON_REQUEST,
+        HookArgs::Response(..) => ON_RESPONSE,
+    };
+    let timeout_ms = config.timeout_ms();
+
+    let (result, mut logs) = tokio::task::spawn_blocking(move || {
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = limited_engine(&config);
+        let deadline = Instant::now() + Duration::from_millis(config.timeout_ms());
+        engine.on_progress(move |_| (Instant::now() >= deadline).then_some(Dynamic::UNIT));
+        let sink = logs.clone();
+        engine.on_print(move |message| push_log(&sink, ScriptLogLevel::Info, hook, message));
+        let sink = logs.clone();
+        engine.on_debug(move |message, _, _| push_log(&sink, ScriptLogLevel::Debug, hook, message));
+
+        let mut scope = Scope::new();
+        let result = match args {
+            HookArgs::Request(request) => {
+                engine.call_fn::<Dynamic>(&mut scope, &ast, hook, (request.to_dynamic(),))
+            }
+            HookArgs::Response(request, response) => engine.call_fn::<Dynamic>(
+                &mut scope,
+                &ast,
+                hook,
+                (request.to_dynamic(), response.to_dynamic()),
+            ),
+        };
+        drop(engine);
+        let logs = std::mem::take(&mut *logs.lock().unwrap());
+        (result, logs)
+    })
+    .await
+    .map_err(|e| anyhow!(e))?;
+
+    let result = result.map_err(|error| match *error {
+        EvalAltResult::ErrorTerminated(..) => format!("{hook} exceeded {timeout_ms}ms"),
+        EvalAltResult::ErrorTooManyOperations(..) => {
+            format!("{hook} exceeded its operation budget")
+        }
+        error => format!("{hook} failed: {error}"),
+    });
+    if let Err(message) = &result {
+        logs.push(script_log(ScriptLogLevel::Error, hook, message));
+    }
+    logger.emit(logs);
+    result.map_err(script_error)
+}
+
+pub(super) fn script_log(level: ScriptLogLevel, hook: &str, message: &str) -> ScriptLog {
+    ScriptLog {
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        level,
+        hook: hook.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn push_log(logs: &Mutex<Vec<ScriptLog>>, level: ScriptLogLevel, hook: &str, message: &str) {
+    if let Ok(mut logs) = logs.lock() {
+        logs.push(script_log(level, hook, message));
+    }
+}
+
+fn script_error(message: String) -> CoreError {
+    CoreError::Internal {
+        operation: "script handler",
+        source: anyhow!(message),
+    }
+}
+
+/// Interprets a hook's return value: `()` keeps the message, a map is merged
+/// into it, and `respond(...)` short-circuits.
+fn hook_outcome<T>(
+    value: Dynamic,
+    hook: &str,
+    merge: impl FnOnce(Map) -> CoreResult<T>,
+) -> CoreResult<HookOutcome<T>> {
+    if value.is_unit() {
+        return Ok(HookOutcome::Unchanged);
+    }
+    if value.is::<ScriptResponse>() {
+        return Ok(HookOutcome::Respond(value.cast::<ScriptResponse>()));
+    }
+    let type_name = value.type_name();
+    match value.try_cast::<Map>() {
+        Some(map) => merge(map).map(HookOutcome::Continue),
+        None => Err(script_error(format!(
+            "{hook} must return a map, respond(...) or (), got {type_name}"
+        ))),
+    }
+}
+
+impl ScriptRequest {
+    fn to_dynamic(&self) -> Dynamic {
+        let mut map = Map::new();
+        map.insert("method".into(), self.method.clone().into());
+        map.insert("url".into(), self.url.clone().into());
+        map.insert("headers".into(), headers_to_dynamic(&self.headers));
+        map.insert("body".into(), self.body.clone().into());
+        map.into()
+    }
+
+    fn merged(&self, mut map: Map) -> CoreResult<Self> {
+        Ok(Self {
+            method: take_string(&mut map, "method")?.unwrap_or_else(|| self.method.clone()),
+            url: take_string(&mut map, "url")?.unwrap_or_else(|| self.url.clone()),
+            headers: take_headers(&mut map)?.unwrap_or_else(|| self.headers.clone()),
+            body: take_string(&mut map, "body")?.unwrap_or_else(|| self.body.clone()),
+        })
+    }
+}
+
+impl ScriptResponse {
+    fn to_dynamic(&self) -> Dynamic {
+        let mut map = Map::new();
+        map.insert("status".into(), (self.status as i64).into());
+        map.insert("headers".into(), headers_to_dynamic(&self.headers));
+        map.insert("body".into(), self.body.clone().into());
+        map.into()
+    }
+
+    fn merged(&self, mut map: Map) -> CoreResult<Self> {
+        let status = match map.remove("status") {
+            Some(status) => {
+                let status = status
+                    .as_int()
+                    .map_err(|t| script_error(format!("status must be an integer, got {t}")))?;
+                u16::try_from(status).unwrap_or_default()
+            }
+            None => self.status,
+        };
+        Ok(Self {
+            status,
+            headers: take_headers(&mut map)?.unwrap_or_else(|| self.headers.clone()),
+            body: take_string(&mut map, "body")?.unwrap_or_else(|| self.body.clone()),
+        })
+    }
+
+    fn into_response(self) -> CoreResult<Response> {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = parse_status(self.status)?;
+        apply_headers(response.headers_mut(), &BTreeMap::new(), &self.headers)?;
+        Ok(response)
+    }
+}
+
+fn take_string(map: &mut Map, key: &str) -> CoreResult<Option<String>> {
+    map.remove(key)
+        .map(|value| {
+            value
+                .into_string()
+                .map_err(|t| script_error(format!("{key} must be a string, got {t}")))
+        })
+        .transpose()
+}
+
+fn take_headers(map: &mut Map) -> CoreResult<Option<BTreeMap<String, String>>> {
+    let Some(value) = map.remove("headers") else {
+        return Ok(None);
+    };
+    let type_name = value.type_name();
+    let headers = value
+        .try_cast::<Map>()
+        .ok_or_else(|| script_error(format!("headers must be a map, got {type_name}")))?;
+    Ok(Some(
+        headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+            .collect(),
+    ))
+}
+
+/// Header values of the same name are joined with `", "`.
+fn headers_to_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::<String, String>::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        map.entry(name.as_str().to_string())
+            .and_modify(|joined| {
+                joined.push_str(", ");
+                joined.push_str(&value);
+            })
+            .or_insert_with(|| value.into_owned());
+    }
+    map
+}
+
+fn headers_to_dynamic(headers: &BTreeMap<String, String>) -> Dynamic {
+    headers
+        .iter()
+        .map(|(name, value)| (name.as_str().into(), value.clone().into()))
+        .collect::<Map>()
+        .into()
+}
+
+/// Applies only the header names the script changed, so untouched
+/// multi-valued headers such as `set-cookie` keep their separate values.
+fn apply_headers(
+    headers: &mut HeaderMap,
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> CoreResult<()> {
+    for name in before.keys().filter(|name| !after.contains_key(*name)) {
+        headers.remove(name.as_str());
+    }
+    for (name, value) in after {
+        if before.get(name) == Some(value) {
+            continue;
+        }
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| script_error(format!("invalid header name: {name}")))?;
+        let header_value = HeaderValue::from_str(value)
+            .map_err(|_| script_error(format!("invalid value for header {name}")))?;
+        headers.insert(header_name, header_value);
+    }
+    Ok(())
+}
+
+fn apply_request(
+    parts: &mut http::request::Parts,
+    body: &mut Bytes,
+    before: &ScriptRequest,
+    after: &ScriptRequest,
+) -> CoreResult<()> {
+    if after.method != before.method {
+        parts.method = after.method.parse::<Method>()?;
+    }
+    if after.url != before.url {
+        parts.uri = merge_uri(&parts.uri, &after.url)?;
+    }
+    apply_headers(&mut parts.headers, &before.headers, &after.headers)?;
+    if after.body != before.body {
+        *body = Bytes::from(after.body.clone());
+        set_content_length(&mut parts.headers, body.len());
+    }
+    Ok(())
+}
+
+/// A URL without an authority (e.g. `/v2/users`) keeps the current scheme and host.
+pub(super) fn merge_uri(current: &Uri, url: &str) -> CoreResult<Uri> {
+    let uri: Uri = url.parse()?;
+    if uri.authority().is_some() {
+        return Ok(uri);
+    }
+    let mut parts = current.clone().into_parts();
+    parts.path_and_query = uri.into_parts().path_and_query;
+    Ok(Uri::from_parts(parts).map_err(|e| anyhow!(e))?)
+}
+
+fn parse_status(status: u16) -> CoreResult<StatusCode> {
+    StatusCode::from_u16(status).map_err(|_| script_error(format!("invalid status code {status}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use http::header::CONTENT_LENGTH;
+    use http_body_util::Empty;
+
+    fn config(script: &str) -> ScriptHandlerConfig {
+        ScriptHandlerConfig {
+            script: script.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_request(body: &str) -> Req {
+        http::Request::builder()
+            .method("POST")
+            .uri("http://example.com/api/users?page=1")
+            .header("content-type", "application/json")
+            .header("content-length", body.len())
+            .body(full(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body_text(body: Body) -> String {
+        String::from_utf8(body.collect().await.unwrap().to_bytes().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn on_request_rewrites_method_url_headers_and_body() -> Result<()> {
+        let config = config(
+            r#"
+            fn onRequest(req) {
+                req.method = "PUT";
+                req.url = "/v2/users";
+                req.headers["x-script"] = "yes";
+                req.headers.remove("content-type");
+                req.body.replace("alice", "bob");
+                req
+            }
+            "#,
+        );
+
+        let HandleRequestType::Request(request) = config
+            .handle_request(create_test_request(r#"{"name":"alice"}"#))
+            .await?
+        else {
+            panic!("expected the request to continue");
+        };
+        assert_eq!(request.method(), Method::PUT);
+        assert_eq!(request.uri().to_string(), "http://example.com/v2/users");
+        assert_eq!(request.headers()["x-script"], "yes");
+        assert!(request.headers().get("content-type").is_none());
+        assert_eq!(request.headers()[CONTENT_LENGTH], "14");
+        // No onResponse hook, so nothing is kept for the response phase.
+        assert!(request.extensions().get::<ScriptRequest>().is_none());
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, r#"{"name":"bob"}"#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn respond_short_circuits_the_request() -> Result<()> {
+        let config = config(
+            r#"
+            fn onRequest(req) {
+                if req.url.contains("/api/") {
+                    return respond(418, #{ "x-teapot": "1" }, "short and stout");
+                }
+            }
+            "#,
+        );
+
+        let HandleRequestType::Response(response) =
+            config.handle_request(create_test_request("")).await?
+        else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(response.headers()["x-teapot"], "1");
+        assert_eq!(body_text(response.into_body()).await, "short and stout");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn on_response_sees_the_request_and_decoded_body() -> Result<()> {
+        use async_compression::tokio::write::GzipEncoder;
+        use tokio::io::AsyncWriteExt;
+
+        let config = config(
+            r#"
+            fn onResponse(req, res) {
+                res.status = 201;
+                res.headers["x-request-method"] = req.method;
+                res.body = res.body + " via " + req.headers["content-type"];
+                res
+            }
+            "#,
+        );
+
+        let HandleRequestType::Request(request) =
+            config.handle_request(create_test_request("{}")).await?
+        else {
+            panic!("expected the request to continue");
+        };
+        let context = ScriptContext::from_request(&request);
+        assert_eq!(context.request.as_ref().unwrap().body, "{}");
+
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(b"upstream").await?;
+        encoder.shutdown().await?;
+        let response = Response::builder()
+            .header("content-encoding", "gzip")
+            .body(Body::from(encoder.into_inner()))?;
+
+        let response = run_response_hook(&config, response, &context).await?;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["x-request-method"], "POST");
+        assert!(response.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(
+            body_text(response.into_body()).await,
+            "upstream via application/json"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unchanged_hooks_keep_the_original_body() -> Result<()> {
+        let config = config(r#"fn onResponse(req, res) { print(res.status); }"#);
+        let response = Response::builder()
+            .header("content-encoding", "br")
+            .body(Body::from(vec![0xff, 0x00, 0x13]))?;
+
+        let response = run_response_hook(&config, response, &ScriptContext::default()).await?;
+        assert_eq!(response.headers()[CONTENT_ENCODING], "br");
+        let body = response.into_body().collect().await?.to_bytes();
+        assert_eq!(&body[..], &[0xff, 0x00, 0x13]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn runaway_scripts_hit_their_limits() -> Result<()> {
+        let mut looping = config("fn onRequest(req) { loop {} }");
+        looping.max_operations = Some(10_000);
+        let error = looping
+            .handle_request(create_test_request(""))
+            .await
+            .err()
+            .expect("operation budget should stop the loop");
+        assert!(
+            error.public_message().contains("operation budget"),
+            "{error}"
+        );
+
+        looping.max_operations = Some(u64::MAX);
+        looping.timeout_ms = Some(50);
+        let error = looping
+            .handle_request(create_test_request(""))
+            .await
+            .err()
+            .expect("timeout should stop the loop");
+        assert!(error.public_message().contains("exceeded 50ms"), "{error}");
+
+        let mut greedy = config(r#"fn onRequest(req) { let s = "x"; loop { s += s; } }"#);
+        greedy.max_string_bytes = Some(1024);
+        assert!(
+            greedy
+                .handle_request(create_test_request(""))
+                .await
+                .is_err()
+        );
+
+        let broken = config("fn onRequest(req) { ");
+        assert!(matches!(
+            broken.handle_request(create_test_request("")).await,
+            Err(CoreError::Validation { .. })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn script_output_is_sent_to_the_capture() -> Result<()> {
+        let channel = Arc::new(MessageEventChannel::new());
+        let mut events = channel.subscribe();
+        let trace_id: TraceId = Arc::new("trace-1".to_string());
+
+        let mut request = http::Request::builder().uri("http://example.com/").body(
+            Empty::new()
+                .map_err(|e| anyhow::anyhow!("Body error: {}", e))
+                .boxed(),
+        )?;
+        request.extensions_mut().insert(trace_id.clone());
+        request.extensions_mut().insert(channel.clone());
+
+        let config =
+            config(r#"fn onRequest(req) { print("saw " + req.url); debug(1); throw "nope"; }"#);
+        assert!(config.handle_request(request).await.is_err());
+
+        let mut logs = Vec::new();
+        while let Ok(MessageEvent::OnScriptLog(id, log)) = events.try_recv() {
+            assert_eq!(id, trace_id);
+            logs.push((log.level, log.hook, log.message));
+        }
+        assert_eq!(logs.len(), 3);
+        assert_eq!(
+            logs[0],
+            (
+                ScriptLogLevel::Info,
+                "onRequest".to_string(),
+                "saw http://example.com/".to_string()
+            )
+        );
+        assert_eq!(logs[1].0, ScriptLogLevel::Debug);
+        assert_eq!(logs[2].0, ScriptLogLevel::Error);
+        assert!(logs[2].2.contains("nope"));
+        Ok(())
+    }
+
+    #[test]
+    fn compiled_scripts_are_reused() -> Result<()> {
+        let script = config("fn onRequest(req) { req.headers[\"x-cached\"] = \"1\"; req }");
+        let first = compile_script(&script)?;
+        assert!(Arc::ptr_eq(&first, &compile_script(&script)?));
+        let edited = config("fn onRequest(req) { () }");
+        assert!(!Arc::ptr_eq(&first, &compile_script(&edited)?));
+        Ok(())
+    }
+}
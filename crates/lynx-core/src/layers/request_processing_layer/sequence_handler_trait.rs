@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use http::HeaderValue;
+use lynx_storage::{
+    DataStore,
+    dao::request_processing_dao::{
+        handlers::{SequenceConfig, SequenceScope},
+        matcher::RuleMatch,
+    },
+    storage::SequenceCounterKey,
+};
+
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::mock_response_handler_trait::MockResponse;
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    proxy_server::ClientAddrRequestExt,
+};
+
+/// A `Sequence` handler bound to the rule match it answers for.
+pub struct Sequence<'a> {
+    config: &'a SequenceConfig,
+    store: &'a Arc<DataStore>,
+    matched: &'a RuleMatch,
+    handler_id: i32,
+}
+
+impl<'a> Sequence<'a> {
+    /// `handler_id` tells several sequences of one rule apart.
+    pub fn new(
+        config: &'a SequenceConfig,
+        store: &'a Arc<DataStore>,
+        matched: &'a RuleMatch,
+        handler_id: i32,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            matched,
+            handler_id,
+        }
+    }
+
+    fn scope_key(&self, request: &Req) -> String {
+        match &self.config.scope {
+            SequenceScope::Rule => String::new(),
+            SequenceScope::Client => request
+                .extensions()
+                .get_client_addr()
+                .map(|client| client.addr().ip().to_string())
+                .unwrap_or_default(),
+            SequenceScope::Header { name } => request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for Sequence<'_> {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let key = SequenceCounterKey {
+            rule_id: self.matched.rule.id.unwrap_or_default(),
+            handler_id: self.handler_id,
+            scope: self.scope_key(&request),
+        };
+        let call = self.store.sequence_counters().next_call(key);
+        let step = self
+            .config
+            .on_exhausted
+            .step(call, self.config.responses.len());
+        let response = self
+            .config
+            .responses
+            .get(step)
+            .ok_or_else(|| CoreError::Validation {
+                message: "sequence has no responses".to_string(),
+            })?;
+
+        let mock = MockResponse::resolve(
+            response,
+            self.store,
+            &self.matched.rule.project,
+            &self.matched.path_params,
+        )
+        .await?;
+        match mock.handle_request(request).await? {
+            HandleRequestType::Response(mut response) => {
+                response
+                    .headers_mut()
+                    .insert("x-lynx-sequence-step", HeaderValue::from(step + 1));
+                Ok(HandleRequestType::Response(response))
+            }
+            request => Ok(request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::full;
+    use axum::http::Request;
+    use lynx_storage::dao::request_processing_dao::{
+        CaptureRule, HandlerRule, RequestProcessingDao, RequestRule,
+        handlers::{MockResponseConfig, SequenceExhausted, handler_rule::HandlerRuleType},
+    };
+
+    fn rule_match() -> RuleMatch {
+        RuleMatch {
+            rule: RequestRule {
+                id: Some(7),
+                project: "default".to_string(),
+                name: "sequence".to_string(),
+                description: None,
+                enabled: true,
+                priority: 0,
+                capture: CaptureRule {
+                    id: None,
+                    match_expr: "/".to_string(),
+                },
+                handlers: vec![],
+            },
+            sampled: None,
+            path_params: vec![],
+        }
+    }
+
+    fn status(code: u16) -> MockResponseConfig {
+        MockResponseConfig {
+            status_code: Some(code),
+            ..Default::default()
+        }
+    }
+
+    async fn call(sequence: &Sequence<'_>, tenant: &str) -> (u16, String) {
+        let request = Request::builder()
+            .uri("http://api.test/orders")
+            .header("x-tenant", tenant)
+            .body(full(""))
+            .unwrap();
+        let HandleRequestType::Response(response) = sequence.handle_request(request).await.unwrap()
+        else {
+            panic!("sequence should answer the request");
+        };
+        let step = response.headers()["x-lynx-sequence-step"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        (response.status().as_u16(), step)
+    }
+
+    #[tokio::test]
+    async fn test_sequence_counts_per_header_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        let matched = rule_match();
+        let config = SequenceConfig {
+            responses: vec![status(503), status(200)],
+            scope: SequenceScope::Header {
+                name: "x-tenant".to_string(),
+            },
+            on_exhausted: SequenceExhausted::StickAtLast,
+        };
+        let sequence = Sequence::new(&config, &store, &matched, 3);
+
+        assert_eq!(call(&sequence, "a").await, (503, "1".to_string()));
+        assert_eq!(call(&sequence, "a").await, (200, "2".to_string()));
+        assert_eq!(call(&sequence, "a").await, (200, "2".to_string()));
+        assert_eq!(call(&sequence, "b").await, (503, "1".to_string()));
+
+        assert_eq!(store.sequence_counters().reset(Some(7)), 2);
+        assert_eq!(call(&sequence, "a").await, (503, "1".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_sequences_of_one_rule_count_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        let dao = RequestProcessingDao::new(store.clone());
+        let rule_id = dao
+            .create_rule(RequestRule {
+                handlers: vec![
+                    HandlerRule::sequence_handler(vec![status(500), status(501)]),
+                    HandlerRule::sequence_handler(vec![status(502), status(503)]),
+                ],
+                ..RequestRule::default()
+            })
+            .await
+            .unwrap();
+        let mut rule = dao.get_rule(rule_id).await.unwrap().unwrap();
+        let ids: Vec<_> = rule.handlers.iter().map(|h| h.id).collect();
+        assert_eq!(ids, [Some(1), Some(2)]);
+
+        // Reordering keeps each handler's id.
+        rule.handlers.reverse();
+        dao.update_rule(rule).await.unwrap();
+        let rule = dao.get_rule(rule_id).await.unwrap().unwrap();
+        let ids: Vec<_> = rule.handlers.iter().map(|h| h.id).collect();
+        assert_eq!(ids, [Some(2), Some(1)]);
+
+        let matched = RuleMatch {
+            rule,
+            sampled: None,
+            path_params: vec![],
+        };
+        let sequences: Vec<_> = matched
+            .rule
+            .handlers
+            .iter()
+            .map(|handler| {
+                let HandlerRuleType::Sequence(config) = &handler.handler_type else {
+                    panic!("sequence handler expected");
+                };
+                Sequence::new(config, &store, &matched, handler.id.unwrap())
+            })
+            .collect();
+
+        assert_eq!(call(&sequences[0], "").await, (502, "1".to_string()));
+        assert_eq!(call(&sequences[1], "").await, (500, "1".to_string()));
+        assert_eq!(call(&sequences[1], "").await, (501, "2".to_string()));
+        assert_eq!(call(&sequences[0], "").await, (503, "2".to_string()));
+        assert_eq!(store.sequence_counters().list(Some(rule_id)).len(), 2);
+    }
+}
@@ -1,4 +1,19 @@
+use super::breakpoint_handler_trait::{BreakpointContext, breakpoint_response};
+use super::cors_handler_trait::{CorsContext, cors_response};
+use super::fault_handler_trait::inject_fault;
 use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::local_file_handler_trait::LocalFile;
+use super::mirror_handler_trait::{MirrorContext, mirror_response};
+use super::mock_response_handler_trait::MockResponse;
+use super::modify_response_handler_trait::modify_response;
+use super::proxy_forward_handler_trait::ProxyForward;
+use super::record_handler_trait::{RecordContext, record_response};
+use super::replay_handler_trait::Replay;
+use super::script_handler_trait::{ScriptContext, run_response_hook};
+use super::sequence_handler_trait::Sequence;
+use super::template::TemplateContext;
+#[cfg(feature = "wasm-plugins")]
+use super::wasm_plugin_handler_trait::{PluginContext, WasmPlugin};
 use crate::{
     common::Req,
     error::CoreError,
@@ -14,16 +29,7 @@ use tower::Service;
 use tracing::instrument;
 
 fn handler_kind_label(handler_type: &HandlerRuleType) -> &'static str {
-    match handler_type {
-        HandlerRuleType::Block(_) => "block",
-        HandlerRuleType::LocalFile(_) => "local_file",
-        HandlerRuleType::ModifyRequest(_) => "modify_request",
-        HandlerRuleType::ModifyResponse(_) => "modify_response",
-        HandlerRuleType::ProxyForward(_) => "proxy_forward",
-        HandlerRuleType::HtmlScriptInjector(_) => "html_script_injector",
-        HandlerRuleType::Delay(_) => "delay",
-        HandlerRuleType::Throttle(_) => "throttle",
-    }
+    handler_type.kind_label()
 }
 
 fn handler_rule_error<E>(handler_name: &str, handler_type: &HandlerRuleType, err: CoreError) -> E
@@ -80,8 +86,16 @@ where
 
             let dao = RequestProcessingDao::new(store.clone());
             tracing::trace!("Searching for matching rules for request");
-            let matching_rules = match dao.find_matching_rules(&request).await {
-                Ok(rules) => {
+            let matching_rules = match dao.find_rule_matches(&request).await {
+                Ok(rule_matches) => {
+                    let mut rules = Vec::with_capacity(rule_matches.len());
+                    for rule_match in rule_matches {
+                        if rule_match.sampled == Some(false) {
+                            tracing::trace!("Rule '{}' sampled out", rule_match.rule.name);
+                        } else {
+                            rules.push(rule_match);
+                        }
+                    }
                     tracing::trace!("Found {} matching rules", rules.len());
                     rules
                 }
@@ -99,7 +113,8 @@ where
 
             let mut all_handlers = Vec::new();
 
-            for rule in &matching_rules {
+            for rule_match in &matching_rules {
+                let rule = &rule_match.rule;
                 tracing::trace!(
                     "Processing rule: '{}', enabled: {}",
                     rule.name,
@@ -114,21 +129,27 @@ where
                             handler.execution_order
                         );
                         if handler.enabled {
-                            all_handlers.push(handler);
+                            all_handlers.push((rule_match, handler));
                         }
                     }
                 }
             }
 
-            all_handlers.sort_by_key(|h| h.execution_order);
+            all_handlers.sort_by_key(|(_, h)| h.execution_order);
             tracing::trace!(
                 "Collected {} enabled handlers for execution",
                 all_handlers.len()
             );
 
+            // The browser's origin, before any handler rewrites request headers.
+            let cors_context = CorsContext::from_request(&request);
+            // Plugins resolved in the request phase, by handler index, reused
+            // with their instance in the response phase.
+            #[cfg(feature = "wasm-plugins")]
+            let mut wasm_plugins = std::collections::HashMap::new();
             let mut current_request = request;
 
-            for (index, handler) in all_handlers.iter().enumerate() {
+            for (index, (matched, handler)) in all_handlers.iter().enumerate() {
                 tracing::trace!(
                     "Executing handler {}/{}: type={} (raw: {:?})",
                     index + 1,
@@ -144,7 +165,9 @@ where
                     }
                     HandlerRuleType::LocalFile(local_file_config) => {
                         tracing::trace!("Executing local file handler");
-                        local_file_config.handle_request(current_request).await
+                        LocalFile::new(local_file_config, &matched.path_params)
+                            .handle_request(current_request)
+                            .await
                     }
                     HandlerRuleType::ModifyRequest(modify_request_config) => {
                         tracing::trace!("Executing modify request handler");
@@ -156,7 +179,9 @@ where
                     }
                     HandlerRuleType::ProxyForward(proxy_forward_config) => {
                         tracing::trace!("Executing proxy forward handler");
-                        proxy_forward_config.handle_request(current_request).await
+                        ProxyForward::new(proxy_forward_config, &matched.path_params)
+                            .handle_request(current_request)
+                            .await
                     }
                     HandlerRuleType::HtmlScriptInjector(html_script_injector_config) => {
                         tracing::trace!("Executing HTML script injector handler");
@@ -179,6 +204,96 @@ where
                         );
                         throttle_config.handle_request(current_request).await
                     }
+                    HandlerRuleType::Script(script_config) => {
+                        tracing::trace!("Executing script handler");
+                        script_config.handle_request(current_request).await
+                    }
+                    #[cfg(feature = "wasm-plugins")]
+                    HandlerRuleType::WasmPlugin(plugin_config) => {
+                        tracing::trace!("Executing wasm plugin handler ({})", plugin_config.plugin);
+                        match WasmPlugin::resolve(plugin_config, &store, &matched.rule.project)
+                            .await
+                        {
+                            Ok(plugin) => {
+                                let result = plugin.handle_request(current_request).await;
+                                wasm_plugins.insert(index, plugin);
+                                result
+                            }
+                            Err(e) => Err(e),
+                        }
+                    }
+                    #[cfg(not(feature = "wasm-plugins"))]
+                    HandlerRuleType::WasmPlugin(_) => Err(CoreError::Validation {
+                        message: "this build has no wasm plugin support".to_string(),
+                    }),
+                    HandlerRuleType::MockResponse(mock_config) => {
+                        tracing::trace!("Executing mock response handler");
+                        match MockResponse::resolve(
+                            mock_config,
+                            &store,
+                            &matched.rule.project,
+                            &matched.path_params,
+                        )
+                        .await
+                        {
+                            Ok(mock) => mock.handle_request(current_request).await,
+                            Err(e) => Err(e),
+                        }
+                    }
+                    HandlerRuleType::Record(record_config) => {
+                        tracing::trace!("Executing record handler");
+                        record_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::Replay(replay_config) => {
+                        tracing::trace!("Executing replay handler");
+                        match Replay::resolve(replay_config, &store, &matched.rule.project).await {
+                            Ok(replay) => replay.handle_request(current_request).await,
+                            Err(e) => Err(e),
+                        }
+                    }
+                    HandlerRuleType::Fault(fault_config) => {
+                        tracing::trace!("Executing fault handler");
+                        fault_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::Sequence(sequence_config) => {
+                        tracing::trace!("Executing sequence handler");
+                        Sequence::new(
+                            sequence_config,
+                            &store,
+                            matched,
+                            handler.id.unwrap_or_default(),
+                        )
+                        .handle_request(current_request)
+                        .await
+                    }
+                    HandlerRuleType::UpstreamProxy(upstream_proxy_config) => {
+                        tracing::trace!("Executing upstream proxy handler");
+                        upstream_proxy_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::Mirror(mirror_config) => {
+                        tracing::trace!("Executing mirror handler");
+                        mirror_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::Breakpoint(breakpoint_config) => {
+                        tracing::trace!("Executing breakpoint handler");
+                        breakpoint_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::WebSocketIntercept(intercept_config) => {
+                        tracing::trace!("Executing WebSocket intercept handler");
+                        intercept_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::WebSocketMock(mock_config) => {
+                        tracing::trace!("Executing WebSocket mock handler");
+                        mock_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::StreamMock(stream_config) => {
+                        tracing::trace!("Executing stream mock handler");
+                        stream_config.handle_request(current_request).await
+                    }
+                    HandlerRuleType::Cors(cors_config) => {
+                        tracing::trace!("Executing CORS handler");
+                        cors_config.handle_request(current_request).await
+                    }
                 };
 
                 match handler_result {
@@ -191,6 +306,23 @@ where
                             "Handler returned a response (status: {}), short-circuiting",
                             response.status()
                         );
+                        // Local answers reach the browser too; a CORS preflight answer is already complete.
+                        if !matches!(handler.handler_type, HandlerRuleType::Cors(_)) {
+                            for (_, cors_handler) in &all_handlers {
+                                if let HandlerRuleType::Cors(cors_config) =
+                                    &cors_handler.handler_type
+                                {
+                                    response = cors_response(cors_config, response, &cors_context)
+                                        .map_err(|e| {
+                                            handler_rule_error(
+                                                handler_kind_label(&cors_handler.handler_type),
+                                                &cors_handler.handler_type,
+                                                e,
+                                            )
+                                        })?;
+                                }
+                            }
+                        }
                         response.extensions_mut().insert(trace_id.clone());
                         return Ok(response);
                     }
@@ -210,6 +342,13 @@ where
             }
 
             tracing::trace!("All handlers executed successfully, proceeding with modified request");
+            let script_context = ScriptContext::from_request(&current_request);
+            #[cfg(feature = "wasm-plugins")]
+            let plugin_context = PluginContext::from_request(&current_request);
+            let template_context = TemplateContext::from_request(&current_request);
+            let record_context = RecordContext::from_request(&current_request);
+            let mirror_context = MirrorContext::from_request(&current_request);
+            let breakpoint_context = BreakpointContext::from_request(&current_request);
             let mut response = inner.call(current_request).await?;
 
             if !all_handlers.is_empty() {
@@ -218,7 +357,7 @@ where
                     all_handlers.len()
                 );
 
-                for (index, handler) in all_handlers.iter().enumerate() {
+                for (index, (matched, handler)) in all_handlers.iter().enumerate() {
                     tracing::trace!(
                         "Executing response handler {}/{}: type={} (raw: {:?})",
                         index + 1,
@@ -230,16 +369,19 @@ where
                     match &handler.handler_type {
                         HandlerRuleType::ModifyResponse(modify_response_config) => {
                             tracing::trace!("Executing modify response handler");
-                            response = modify_response_config
-                                .handle_response(response)
-                                .await
-                                .map_err(|e| {
-                                    handler_rule_error(
-                                        handler_kind_label(&handler.handler_type),
-                                        &handler.handler_type,
-                                        e,
-                                    )
-                                })?;
+                            response = modify_response(
+                                modify_response_config,
+                                response,
+                                &template_context,
+                            )
+                            .await
+                            .map_err(|e| {
+                                handler_rule_error(
+                                    handler_kind_label(&handler.handler_type),
+                                    &handler.handler_type,
+                                    e,
+                                )
+                            })?;
                         }
                         HandlerRuleType::HtmlScriptInjector(html_script_injector_config) => {
                             tracing::trace!("Executing HTML script injector response handler");
@@ -285,6 +427,111 @@ where
                                         )
                                     })?;
                         }
+                        HandlerRuleType::Script(script_config) => {
+                            tracing::trace!("Executing script response handler");
+                            response = run_response_hook(script_config, response, &script_context)
+                                .await
+                                .map_err(|e| {
+                                    handler_rule_error(
+                                        handler_kind_label(&handler.handler_type),
+                                        &handler.handler_type,
+                                        e,
+                                    )
+                                })?;
+                        }
+                        #[cfg(feature = "wasm-plugins")]
+                        HandlerRuleType::WasmPlugin(plugin_config) => {
+                            tracing::trace!("Executing wasm plugin response handler");
+                            let handler_error = |e| {
+                                handler_rule_error(
+                                    handler_kind_label(&handler.handler_type),
+                                    &handler.handler_type,
+                                    e,
+                                )
+                            };
+                            let plugin = match wasm_plugins.remove(&index) {
+                                Some(plugin) => plugin,
+                                None => WasmPlugin::resolve(
+                                    plugin_config,
+                                    &store,
+                                    &matched.rule.project,
+                                )
+                                .await
+                                .map_err(handler_error)?,
+                            };
+                            response = plugin
+                                .handle_plugin_response(response, &plugin_context)
+                                .await
+                                .map_err(handler_error)?;
+                        }
+                        HandlerRuleType::Record(record_config) => {
+                            tracing::trace!("Executing record response handler");
+                            response = record_response(
+                                record_config,
+                                response,
+                                &record_context,
+                                &store,
+                                &matched.rule.project,
+                            )
+                            .await
+                            .map_err(|e| {
+                                handler_rule_error(
+                                    handler_kind_label(&handler.handler_type),
+                                    &handler.handler_type,
+                                    e,
+                                )
+                            })?;
+                        }
+                        HandlerRuleType::Cors(cors_config) => {
+                            tracing::trace!("Executing CORS response handler");
+                            response = cors_response(cors_config, response, &cors_context)
+                                .map_err(|e| {
+                                    handler_rule_error(
+                                        handler_kind_label(&handler.handler_type),
+                                        &handler.handler_type,
+                                        e,
+                                    )
+                                })?;
+                        }
+                        HandlerRuleType::Mirror(_) => {
+                            tracing::trace!("Executing mirror response handler");
+                            response =
+                                mirror_response(response, &mirror_context)
+                                    .await
+                                    .map_err(|e| {
+                                        handler_rule_error(
+                                            handler_kind_label(&handler.handler_type),
+                                            &handler.handler_type,
+                                            e,
+                                        )
+                                    })?;
+                        }
+                        HandlerRuleType::Breakpoint(breakpoint_config) => {
+                            tracing::trace!("Executing breakpoint response handler");
+                            response = breakpoint_response(
+                                breakpoint_config,
+                                response,
+                                &breakpoint_context,
+                            )
+                            .await
+                            .map_err(|e| {
+                                handler_rule_error(
+                                    handler_kind_label(&handler.handler_type),
+                                    &handler.handler_type,
+                                    e,
+                                )
+                            })?;
+                        }
+                        HandlerRuleType::Fault(fault_config) => {
+                            tracing::trace!("Executing fault response handler");
+                            response = inject_fault(fault_config, response).await.map_err(|e| {
+                                handler_rule_error(
+                                    handler_kind_label(&handler.handler_type),
+                                    &handler.handler_type,
+                                    e,
+                                )
+                            })?;
+                        }
                         _ => {
                             tracing::trace!("Handler type does not support response processing");
                             continue;
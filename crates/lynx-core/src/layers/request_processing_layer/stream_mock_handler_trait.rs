@@ -0,0 +1,200 @@
+use std::fmt::Write as _;
+
+use anyhow::anyhow;
+use axum::{
+    body::Body as AxumBody,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures_util::{StreamExt, stream};
+use http::{
+    HeaderName, HeaderValue, StatusCode,
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+};
+use http_body::Frame;
+use http_body_util::StreamBody;
+use lynx_storage::dao::request_processing_dao::handlers::{
+    StreamMockChunk, StreamMockConfig, StreamMockFormat,
+};
+use tokio::time::{Duration, sleep};
+
+use super::fault_handler_trait::FLUSH_GRACE;
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::throttled_body::{Schedule, ThrottledBody};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+};
+
+#[async_trait::async_trait]
+impl HandlerTrait for StreamMockConfig {
+    async fn handle_request(&self, _request: Req) -> CoreResult<HandleRequestType> {
+        let mut builder =
+            Response::builder().status(StatusCode::from_u16(self.status_code.unwrap_or(200))?);
+        let headers = builder.headers_mut().expect("fresh response builder");
+        match self.format {
+            StreamMockFormat::Sse => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+                headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+            }
+            StreamMockFormat::Raw => {
+                headers.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("text/plain; charset=utf-8"),
+                );
+            }
+        }
+        for (name, value) in &self.headers {
+            let name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| CoreError::Validation {
+                    message: format!("invalid header name {name:?}"),
+                })?;
+            let value = HeaderValue::from_str(value).map_err(|_| CoreError::Validation {
+                message: format!("invalid value for header {name}"),
+            })?;
+            headers.insert(name, value);
+        }
+
+        let response = builder.body(stream_body(self))?;
+        Ok(HandleRequestType::Response(response.into_response()))
+    }
+}
+
+/// The scripted chunks, paced by their delays, with the configured failure.
+fn stream_body(config: &StreamMockConfig) -> AxumBody {
+    let plays = match config.repeat {
+        0 => usize::MAX,
+        repeat => repeat as usize,
+    };
+    let mut total = config.chunks.len().saturating_mul(plays);
+    if let Some(fail_after) = config.fail_after {
+        total = total.min(fail_after as usize);
+    }
+
+    let data: Vec<Bytes> = config
+        .chunks
+        .iter()
+        .map(|chunk| encode_chunk(config.format, chunk))
+        .collect();
+    let delays: Vec<Duration> = config
+        .chunks
+        .iter()
+        .map(|chunk| Duration::from_millis(chunk.delay_ms))
+        .collect();
+
+    let frames =
+        stream::iter(data.into_iter().cycle().take(total)).map(|chunk| Ok(Frame::data(chunk)));
+    let frames = match config.fail_after {
+        // Wait so the chunks already sent reach the client before the cut.
+        Some(_) => frames
+            .chain(stream::once(async {
+                sleep(FLUSH_GRACE).await;
+                Err(anyhow!("stream aborted by stream mock"))
+            }))
+            .boxed(),
+        None => frames.boxed(),
+    };
+    AxumBody::new(ThrottledBody::with_pace(
+        StreamBody::new(frames),
+        Schedule(delays.into_iter().cycle().take(total)),
+    ))
+}
+
+/// Frames one chunk: an SSE event, or the raw text.
+fn encode_chunk(format: StreamMockFormat, chunk: &StreamMockChunk) -> Bytes {
+    if format == StreamMockFormat::Raw {
+        return Bytes::from(chunk.data.clone());
+    }
+    let mut event = String::new();
+    if let Some(id) = &chunk.id {
+        let _ = writeln!(event, "id: {id}");
+    }
+    if let Some(name) = &chunk.event {
+        let _ = writeln!(event, "event: {name}");
+    }
+    if let Some(retry) = chunk.retry_ms {
+        let _ = writeln!(event, "retry: {retry}");
+    }
+    for line in chunk.data.split('\n') {
+        let _ = writeln!(event, "data: {}", line.trim_end_matches('\r'));
+    }
+    event.push('\n');
+    Bytes::from(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use http::Request;
+    use http_body_util::BodyExt;
+    use std::time::Instant;
+
+    use crate::utils::empty;
+
+    fn chunk(data: &str, delay_ms: u64) -> StreamMockChunk {
+        StreamMockChunk {
+            data: data.to_string(),
+            delay_ms,
+            ..Default::default()
+        }
+    }
+
+    async fn respond(config: &StreamMockConfig) -> Result<Response> {
+        let request = Request::builder()
+            .uri("http://example.com/stream")
+            .body(empty())?;
+        match config.handle_request(request).await? {
+            HandleRequestType::Response(response) => Ok(response),
+            HandleRequestType::Request(_) => panic!("stream mock passed the request on"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sse_events_are_framed_and_paced() -> Result<()> {
+        let config = StreamMockConfig {
+            chunks: vec![
+                StreamMockChunk {
+                    id: Some("1".into()),
+                    event: Some("token".into()),
+                    ..chunk("{\"t\":\"a\"}\nsecond", 0)
+                },
+                chunk("[DONE]", 150),
+            ],
+            repeat: 2,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let response = respond(&config).await?;
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        let body = response.into_body().collect().await?.to_bytes();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+
+        let event = "id: 1\nevent: token\ndata: {\"t\":\"a\"}\ndata: second\n\ndata: [DONE]\n\n";
+        assert_eq!(body, event.repeat(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw_stream_fails_after_configured_chunks() -> Result<()> {
+        let config = StreamMockConfig {
+            format: StreamMockFormat::Raw,
+            chunks: vec![chunk("tick ", 10)],
+            repeat: 0,
+            fail_after: Some(3),
+            ..Default::default()
+        };
+        let mut body = respond(&config).await?.into_body();
+        let mut received = Vec::new();
+        let error = loop {
+            match body.frame().await {
+                Some(Ok(frame)) => received.extend_from_slice(&frame.into_data().unwrap()),
+                Some(Err(error)) => break error,
+                None => panic!("stream ended without failing"),
+            }
+        };
+        assert_eq!(received, b"tick tick tick ");
+        assert!(error.to_string().contains("aborted"));
+        Ok(())
+    }
+}
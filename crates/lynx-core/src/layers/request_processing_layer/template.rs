@@ -0,0 +1,206 @@
+use std::sync::LazyLock;
+
+use chrono::Utc;
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
+};
+use http::{HeaderMap, Method, StatusCode, Uri};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+};
+
+/// Shared registry: no HTML escaping, plus the `uuid` and `now` helpers.
+static TEMPLATES: LazyLock<Handlebars<'static>> = LazyLock::new(|| {
+    let mut registry = Handlebars::new();
+    registry.register_escape_fn(handlebars::no_escape);
+    registry.register_helper("uuid", Box::new(uuid_helper));
+    registry.register_helper("now", Box::new(now_helper));
+    registry
+});
+
+/// Request (and response) data that `{{...}}` templates can refer to.
+///
+/// Templates see `req.method`, `req.url`, `req.path`, `req.host`,
+/// `req.query.<name>` and `req.header.<name>`, and in the response phase
+/// `res.status` and `res.header.<name>`. Header names are lower-case.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    data: Map<String, Value>,
+}
+
+impl TemplateContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self::from_request_parts(request.method(), request.uri(), request.headers())
+    }
+
+    pub fn from_request_parts(method: &Method, uri: &Uri, headers: &HeaderMap) -> Self {
+        let query: Map<String, Value> = uri
+            .query()
+            .map(|query| {
+                form_urlencoded::parse(query.as_bytes())
+                    .map(|(name, value)| (name.into_owned(), Value::String(value.into_owned())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let host = uri
+            .host()
+            .or_else(|| headers.get(http::header::HOST)?.to_str().ok())
+            .unwrap_or_default();
+
+        let mut data = Map::new();
+        data.insert(
+            "req".to_string(),
+            json!({
+                "method": method.as_str(),
+                "url": uri.to_string(),
+                "path": uri.path(),
+                "host": host,
+                "query": query,
+                "header": headers_to_json(headers),
+            }),
+        );
+        Self { data }
+    }
+
+    /// Adds `res.status` and `res.header` for response-phase templates.
+    pub fn with_response(&self, status: StatusCode, headers: &HeaderMap) -> Self {
+        let mut data = self.data.clone();
+        data.insert(
+            "res".to_string(),
+            json!({ "status": status.as_u16(), "header": headers_to_json(headers) }),
+        );
+        Self { data }
+    }
+
+    /// Adds a top-level value, e.g. `params` or `body`.
+    pub fn insert(&mut self, key: &str, value: Value) {
+        self.data.insert(key.to_string(), value);
+    }
+
+    /// Renders `template`. Text without `{{` is returned as-is.
+    pub fn render(&self, template: &str) -> CoreResult<String> {
+        if !template.contains("{{") {
+            return Ok(template.to_string());
+        }
+        TEMPLATES
+            .render_template(template, &self.data)
+            .map_err(|e| CoreError::Validation {
+                message: format!("invalid template {template:?}: {e}"),
+            })
+    }
+}
+
+/// Header values by lower-case name; repeated headers are joined with `, `.
+fn headers_to_json(headers: &HeaderMap) -> Map<String, Value> {
+    let mut map = Map::new();
+    for name in headers.keys() {
+        let values: Vec<_> = headers
+            .get_all(name)
+            .iter()
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+            .collect();
+        map.insert(name.as_str().to_string(), Value::String(values.join(", ")));
+    }
+    map
+}
+
+fn uuid_helper(
+    _: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&uuid::Uuid::new_v4().to_string())?;
+    Ok(())
+}
+
+/// `{{now}}` is RFC 3339 UTC; `{{now "%s"}}` takes a strftime format.
+fn now_helper(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let now = Utc::now();
+    match helper.param(0).map(|param| param.value()) {
+        None => out.write(&now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))?,
+        Some(Value::String(format)) => {
+            let items = chrono::format::StrftimeItems::new(format)
+                .parse()
+                .map_err(|_| RenderErrorReason::Other(format!("invalid now format {format:?}")))?;
+            out.write(&now.format_with_items(items.iter()).to_string())?
+        }
+        Some(other) => {
+            return Err(RenderErrorReason::Other(format!(
+                "now expects a format string, got {other}"
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn context() -> TemplateContext {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req-1"));
+        headers.append("accept", HeaderValue::from_static("text/html"));
+        headers.append("accept", HeaderValue::from_static("application/json"));
+        let uri: Uri = "http://example.com/users/7?tab=posts&page=2"
+            .parse()
+            .unwrap();
+        TemplateContext::from_request_parts(&Method::GET, &uri, &headers)
+    }
+
+    #[test]
+    fn renders_request_fields() {
+        let context = context();
+        assert_eq!(
+            context
+                .render("{{req.method}} {{req.host}}{{req.path}} page={{req.query.page}}")
+                .unwrap(),
+            "GET example.com/users/7 page=2"
+        );
+        assert_eq!(
+            context.render("id={{req.header.x-request-id}}").unwrap(),
+            "id=req-1"
+        );
+        assert_eq!(
+            context.render("{{req.header.accept}}").unwrap(),
+            "text/html, application/json"
+        );
+        assert_eq!(context.render("{{req.header.missing}}").unwrap(), "");
+        assert_eq!(context.render("plain <b>").unwrap(), "plain <b>");
+    }
+
+    #[test]
+    fn renders_helpers_and_response_fields() {
+        let mut headers = HeaderMap::new();
+        headers.insert("etag", HeaderValue::from_static("\"v1\""));
+        let context = context().with_response(StatusCode::CREATED, &headers);
+        assert_eq!(
+            context
+                .render("{{res.status}} {{res.header.etag}}")
+                .unwrap(),
+            "201 \"v1\""
+        );
+
+        let id = context.render("{{uuid}}").unwrap();
+        assert!(uuid::Uuid::parse_str(&id).is_ok());
+        let now = context.render("{{now}}").unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(&now).is_ok());
+        let year = context.render("{{now \"%Y\"}}").unwrap();
+        assert_eq!(year, Utc::now().format("%Y").to_string());
+
+        assert!(context.render("{{#if}}").is_err());
+    }
+}
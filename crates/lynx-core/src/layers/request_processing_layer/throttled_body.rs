@@ -7,40 +7,36 @@ use std::{
 };
 use tokio::time::{Duration, Sleep};
 
-pin_project! {
-    pub struct ThrottledBody<B> {
-        #[pin]
-        inner: B,
-        bytes_per_sec: u64,
-        #[pin]
-        delay: Option<Pin<Box<Sleep>>>,
-        pending: Option<Frame<Bytes>>,
-    }
+/// Decides how long each data chunk is held before it is released.
+pub trait Pace {
+    fn delay_for(&mut self, chunk: &Bytes) -> Option<Duration>;
+}
+
+/// Holds each chunk for as long as sending it takes at a fixed bandwidth.
+pub struct Bandwidth {
+    bytes_per_sec: u64,
 }
 
-impl<B> ThrottledBody<B> {
+impl Bandwidth {
     /// `kbps` is kilobits per second; `0` means no throttling.
-    pub fn new(inner: B, kbps: u64) -> Self {
+    pub fn from_kbps(kbps: u64) -> Self {
         let bytes_per_sec = if kbps == 0 {
             0
         } else {
             kbps.saturating_mul(1000) / 8
         };
-        Self {
-            inner,
-            bytes_per_sec,
-            delay: None,
-            pending: None,
-        }
+        Self { bytes_per_sec }
     }
+}
 
-    fn delay_for_chunk(bytes_per_sec: u64, chunk_len: usize) -> Option<Duration> {
-        if bytes_per_sec == 0 || chunk_len == 0 {
+impl Pace for Bandwidth {
+    fn delay_for(&mut self, chunk: &Bytes) -> Option<Duration> {
+        if self.bytes_per_sec == 0 || chunk.is_empty() {
             return None;
         }
-        let delay_ms = (chunk_len as u64)
+        let delay_ms = (chunk.len() as u64)
             .saturating_mul(1000)
-            .saturating_div(bytes_per_sec);
+            .saturating_div(self.bytes_per_sec);
         if delay_ms == 0 {
             None
         } else {
@@ -49,9 +45,51 @@ impl<B> ThrottledBody<B> {
     }
 }
 
-impl<B> Body for ThrottledBody<B>
+/// Holds the n-th chunk for the n-th delay; chunks past the end go out at once.
+pub struct Schedule<I>(pub I);
+
+impl<I> Pace for Schedule<I>
+where
+    I: Iterator<Item = Duration>,
+{
+    fn delay_for(&mut self, _chunk: &Bytes) -> Option<Duration> {
+        self.0.next().filter(|delay| !delay.is_zero())
+    }
+}
+
+pin_project! {
+    pub struct ThrottledBody<B, P> {
+        #[pin]
+        inner: B,
+        pace: P,
+        #[pin]
+        delay: Option<Pin<Box<Sleep>>>,
+        pending: Option<Frame<Bytes>>,
+    }
+}
+
+impl<B> ThrottledBody<B, Bandwidth> {
+    /// `kbps` is kilobits per second; `0` means no throttling.
+    pub fn new(inner: B, kbps: u64) -> Self {
+        Self::with_pace(inner, Bandwidth::from_kbps(kbps))
+    }
+}
+
+impl<B, P> ThrottledBody<B, P> {
+    pub fn with_pace(inner: B, pace: P) -> Self {
+        Self {
+            inner,
+            pace,
+            delay: None,
+            pending: None,
+        }
+    }
+}
+
+impl<B, P> Body for ThrottledBody<B, P>
 where
     B: Body<Data = Bytes, Error = anyhow::Error>,
+    P: Pace,
 {
     type Data = Bytes;
     type Error = anyhow::Error;
@@ -77,10 +115,7 @@ where
             match self.as_mut().project().inner.poll_frame(cx) {
                 Poll::Ready(Some(Ok(frame))) => {
                     if let Some(chunk) = frame.data_ref()
-                        && let Some(duration) = Self::delay_for_chunk(
-                            *self.as_mut().project().bytes_per_sec,
-                            chunk.len(),
-                        )
+                        && let Some(duration) = self.as_mut().project().pace.delay_for(chunk)
                     {
                         self.as_mut().project().pending.replace(frame);
                         self.as_mut()
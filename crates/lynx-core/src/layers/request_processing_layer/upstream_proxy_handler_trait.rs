@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use http::{Method, Request, uri::Authority};
+use lynx_storage::{
+    DataStore,
+    dao::request_processing_dao::{
+        RequestProcessingDao,
+        handlers::{UpstreamProxyConfig, handler_rule::HandlerRuleType},
+    },
+};
+
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{common::Req, error::CoreResult, utils::empty};
+
+/// The upstream chosen by an `UpstreamProxy` rule for this request; the last
+/// one to run wins.
+#[derive(Debug, Clone)]
+pub struct SelectedUpstreamProxy(pub UpstreamProxyConfig);
+
+#[async_trait::async_trait]
+impl HandlerTrait for UpstreamProxyConfig {
+    async fn handle_request(&self, mut request: Req) -> CoreResult<HandleRequestType> {
+        request
+            .extensions_mut()
+            .insert(SelectedUpstreamProxy(self.clone()));
+        Ok(HandleRequestType::Request(request))
+    }
+}
+
+/// The upstream for a CONNECT tunnel that is not decrypted, from the
+/// `UpstreamProxy` rules matching `https://<authority>/`.
+pub async fn upstream_proxy_for_tunnel(
+    store: &Arc<DataStore>,
+    authority: &Authority,
+) -> Option<UpstreamProxyConfig> {
+    let scheme = if authority.port_u16() == Some(80) {
+        "http"
+    } else {
+        "https"
+    };
+    let request = Request::builder()
+        .method(Method::CONNECT)
+        .uri(format!("{scheme}://{authority}/"))
+        .body(empty())
+        .ok()?;
+    let matches = match RequestProcessingDao::new(store.clone())
+        .find_rule_matches(&request)
+        .await
+    {
+        Ok(matches) => matches,
+        Err(e) => {
+            tracing::warn!("Failed to find upstream proxy rules for tunnel: {}", e);
+            return None;
+        }
+    };
+    matches
+        .iter()
+        .filter(|matched| matched.rule.enabled && matched.sampled != Some(false))
+        .flat_map(|matched| &matched.rule.handlers)
+        .filter(|handler| handler.enabled)
+        .filter_map(|handler| match &handler.handler_type {
+            HandlerRuleType::UpstreamProxy(config) => Some((handler.execution_order, config)),
+            _ => None,
+        })
+        .max_by_key(|(order, _)| *order)
+        .map(|(_, config)| config.clone())
+}
@@ -0,0 +1,662 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use axum::{body::Body, response::Response};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header::CONTENT_ENCODING};
+use http_body_util::BodyExt;
+use lynx_storage::{
+    DataStore, dao::projects_dao::ProjectsDao,
+    dao::request_processing_dao::handlers::WasmPluginConfig,
+};
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+use super::body_codec::set_content_length;
+use super::body_codec::{content_encoding, decode_body};
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use super::script_handler_trait::{ScriptLogger, merge_uri, script_log};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    layers::message_package_layer::message_event_data::{ScriptLog, ScriptLogLevel},
+    utils::full,
+};
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "handler",
+        additional_derives: [PartialEq],
+    });
+}
+
+use bindings::{HandlerPre, HttpRequest, HttpResponse, RequestOutcome};
+
+const HANDLE_REQUEST: &str = "handle-request";
+const HANDLE_RESPONSE: &str = "handle-response";
+
+/// One engine for every plugin: compiled components are tied to the engine
+/// that built them.
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("wasm plugin engine")
+});
+
+static LINKER: LazyLock<Linker<PluginState>> = LazyLock::new(|| {
+    let mut linker = Linker::new(&ENGINE);
+    wasmtime_wasi::add_to_linker_sync(&mut linker).expect("link WASI into wasm plugins");
+    bindings::Handler::add_to_linker(&mut linker, |state: &mut PluginState| state)
+        .expect("link host functions into wasm plugins");
+    linker
+});
+
+/// Compiled plugins by path; an entry is rebuilt when the file changes.
+static PLUGIN_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedPlugin>>> =
+    LazyLock::new(Default::default);
+
+struct CachedPlugin {
+    stamp: FileStamp,
+    pre: HandlerPre<PluginState>,
+}
+
+#[derive(PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Per-instance store data. Plugins get WASI without preopened directories,
+/// environment or stdio.
+struct PluginState {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    limits: StoreLimits,
+    hook: &'static str,
+    logs: Vec<ScriptLog>,
+}
+
+impl PluginState {
+    fn new(hook: &'static str, max_memory_bytes: usize) -> Self {
+        Self {
+            wasi: WasiCtxBuilder::new().build(),
+            table: ResourceTable::new(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(max_memory_bytes)
+                .build(),
+            hook,
+            logs: Vec::new(),
+        }
+    }
+}
+
+impl IoView for PluginState {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for PluginState {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl bindings::HandlerImports for PluginState {
+    fn log(&mut self, message: String) {
+        self.logs
+            .push(script_log(ScriptLogLevel::Info, self.hook, &message));
+    }
+}
+
+/// The request as the plugin last saw it, kept for `handle-response`.
+#[derive(Debug, Clone)]
+struct PluginRequest(HttpRequest);
+
+/// What `handle-response` needs from the request side, captured before the
+/// request is handed to the upstream service.
+#[derive(Debug, Clone, Default)]
+pub struct PluginContext {
+    request: Option<HttpRequest>,
+    logger: ScriptLogger,
+}
+
+impl PluginContext {
+    pub fn from_request(request: &Req) -> Self {
+        Self {
+            request: request
+                .extensions()
+                .get::<PluginRequest>()
+                .map(|request| request.0.clone()),
+            logger: ScriptLogger::from_extensions(request.extensions()),
+        }
+    }
+}
+
+/// An instantiated plugin, kept so `handle-response` runs in the instance
+/// `handle-request` left behind.
+struct LiveInstance {
+    store: Store<PluginState>,
+    handler: bindings::Handler,
+}
+
+/// A `WasmPlugin` handler whose module path has been resolved for its rule.
+///
+/// One value serves both phases of a request: the plugin is instantiated on
+/// the first call and reused by the next, so module globals survive from
+/// `handle-request` to `handle-response`. Each call gets a fresh fuel budget.
+pub struct WasmPlugin<'a> {
+    config: &'a WasmPluginConfig,
+    path: PathBuf,
+    instance: Arc<Mutex<Option<LiveInstance>>>,
+}
+
+impl<'a> WasmPlugin<'a> {
+    /// Finds the plugin file of a rule in `project`: absolute paths as-is,
+    /// otherwise under the project directory, then `<data dir>/plugins`.
+    pub async fn resolve(
+        config: &'a WasmPluginConfig,
+        store: &std::sync::Arc<DataStore>,
+        project: &str,
+    ) -> CoreResult<Self> {
+        let path = ProjectsDao::new(store.clone())
+            .resolve_project_file(project, &config.plugin, &store.plugins_dir())
+            .await
+            .map_err(|e| CoreError::Validation {
+                message: format!("wasm plugin {e}"),
+            })?;
+        Ok(Self::new(config, path))
+    }
+
+    fn new(config: &'a WasmPluginConfig, path: PathBuf) -> Self {
+        Self {
+            config,
+            path,
+            instance: Arc::default(),
+        }
+    }
+
+    /// Runs `handle-response` on an upstream response.
+    pub async fn handle_plugin_response(
+        &self,
+        response: Response,
+        context: &PluginContext,
+    ) -> CoreResult<Response> {
+        let (mut parts, body) = response.into_parts();
+        let raw = body.collect().await.map_err(|e| anyhow!(e))?.to_bytes();
+        let decoded = match content_encoding(&parts.headers) {
+            Some(encoding) => decode_body(&encoding, &raw).await.ok().flatten(),
+            None => None,
+        };
+        let before = HttpResponse {
+            status: parts.status.as_u16(),
+            headers: headers_to_list(&parts.headers),
+            body: decoded.as_ref().unwrap_or(&raw).to_vec(),
+        };
+        let request = context.request.clone().unwrap_or_else(|| HttpRequest {
+            method: String::new(),
+            url: String::new(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        });
+
+        let config_json = self.config.config_json();
+        let response_arg = before.clone();
+        let after = self
+            .call(HANDLE_RESPONSE, &context.logger, move |handler, store| {
+                handler.call_handle_response(store, &config_json, &request, &response_arg)
+            })
+            .await?;
+        if after == before {
+            return Ok(Response::from_parts(parts, Body::from(raw)));
+        }
+
+        parts.status = parse_status(after.status)?;
+        if after.headers != before.headers {
+            parts.headers = list_to_headers(&after.headers)?;
+        }
+        let body = if after.body != before.body {
+            // The new body is plain, whatever the upstream encoding was.
+            parts.headers.remove(CONTENT_ENCODING);
+            set_content_length(&mut parts.headers, after.body.len());
+            Bytes::from(after.body)
+        } else {
+            raw
+        };
+        Ok(Response::from_parts(parts, Body::from(body)))
+    }
+
+    /// Runs one export on a blocking thread under the configured fuel and
+    /// memory limits, instantiating the plugin first if no earlier call left
+    /// a usable instance, and forwards its log lines (and any failure) to the
+    /// capture.
+    async fn call<R, F>(&self, hook: &'static str, logger: &ScriptLogger, f: F) -> CoreResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(
+                &bindings::Handler,
+                &mut Store<PluginState>,
+            ) -> wasmtime::Result<Result<R, String>>
+            + Send
+            + 'static,
+    {
+        let path = self.path.clone();
+        let instance = self.instance.clone();
+        let fuel = self.config.fuel();
+        let max_memory_bytes = self.config.max_memory_bytes();
+
+        let (result, mut logs) = tokio::task::spawn_blocking(move || {
+            let mut slot = instance.lock().map_err(|e| anyhow!(e.to_string()))?;
+            let mut live = match slot.take() {
+                Some(live) => live,
+                None => {
+                    let pre = load_plugin(&path)?;
+                    let mut store = Store::new(&ENGINE, PluginState::new(hook, max_memory_bytes));
+                    store.limiter(|state| &mut state.limits);
+                    store.set_fuel(fuel).map_err(|e| anyhow!(e))?;
+                    match pre.instantiate(&mut store) {
+                        Ok(handler) => LiveInstance { store, handler },
+                        Err(error) => {
+                            let logs = std::mem::take(&mut store.data_mut().logs);
+                            return CoreResult::Ok((Err(error), logs));
+                        }
+                    }
+                }
+            };
+            live.store.data_mut().hook = hook;
+            live.store.set_fuel(fuel).map_err(|e| anyhow!(e))?;
+            let result = f(&live.handler, &mut live.store);
+            let logs = std::mem::take(&mut live.store.data_mut().logs);
+            // A trapped instance must not be entered again.
+            if result.is_ok() {
+                *slot = Some(live);
+            }
+            CoreResult::Ok((result, logs))
+        })
+        .await
+        .map_err(|e| anyhow!(e))??;
+
+        let result = match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(message)) => Err(format!("{hook} failed: {message}")),
+            Err(error) if error.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+                Err(format!("{hook} ran out of fuel ({fuel})"))
+            }
+            Err(error) => Err(format!("{hook} trapped: {error:#}")),
+        };
+        if let Err(message) = &result {
+            logs.push(script_log(ScriptLogLevel::Error, hook, message));
+        }
+        logger.emit(logs);
+        result.map_err(plugin_error)
+    }
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for WasmPlugin<'_> {
+    async fn handle_request(&self, request: Req) -> CoreResult<HandleRequestType> {
+        let logger = ScriptLogger::from_extensions(request.extensions());
+        let (mut parts, body) = request.into_parts();
+        let mut body = body.collect().await?.to_bytes();
+        let before = HttpRequest {
+            method: parts.method.to_string(),
+            url: parts.uri.to_string(),
+            headers: headers_to_list(&parts.headers),
+            body: body.to_vec(),
+        };
+
+        let config_json = self.config.config_json();
+        let request_arg = before.clone();
+        let outcome = self
+            .call(HANDLE_REQUEST, &logger, move |handler, store| {
+                handler.call_handle_request(store, &config_json, &request_arg)
+            })
+            .await?;
+
+        let after = match outcome {
+            RequestOutcome::Continue(after) => after,
+            RequestOutcome::Respond(response) => {
+                return Ok(HandleRequestType::Response(into_response(response)?));
+            }
+        };
+        if after.method != before.method {
+            parts.method = after.method.parse::<Method>()?;
+        }
+        if after.url != before.url {
+            parts.uri = merge_uri(&parts.uri, &after.url)?;
+        }
+        if after.headers != before.headers {
+            parts.headers = list_to_headers(&after.headers)?;
+        }
+        if after.body != before.body {
+            body = Bytes::from(after.body.clone());
+            set_content_length(&mut parts.headers, body.len());
+        }
+        parts.extensions.insert(PluginRequest(after));
+        Ok(HandleRequestType::Request(Req::from_parts(
+            parts,
+            full(body),
+        )))
+    }
+}
+
+/// Compiles (or reuses) the component at `path`.
+fn load_plugin(path: &Path) -> CoreResult<HandlerPre<PluginState>> {
+    let metadata = std::fs::metadata(path)?;
+    let stamp = FileStamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    };
+    let mut cache = PLUGIN_CACHE.lock().map_err(|e| anyhow!(e.to_string()))?;
+    if let Some(cached) = cache.get(path)
+        && cached.stamp == stamp
+    {
+        return Ok(cached.pre.clone());
+    }
+
+    let invalid = |e: wasmtime::Error| CoreError::Validation {
+        message: format!("wasm plugin {} is not usable: {e:#}", path.display()),
+    };
+    let component = Component::from_file(&ENGINE, path).map_err(invalid)?;
+    let instance_pre = LINKER.instantiate_pre(&component).map_err(invalid)?;
+    let pre = HandlerPre::new(instance_pre).map_err(invalid)?;
+    cache.insert(
+        path.to_path_buf(),
+        CachedPlugin {
+            stamp,
+            pre: pre.clone(),
+        },
+    );
+    Ok(pre)
+}
+
+fn plugin_error(message: String) -> CoreError {
+    CoreError::Internal {
+        operation: "wasm plugin handler",
+        source: anyhow!(message),
+    }
+}
+
+fn headers_to_list(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+fn list_to_headers(list: &[(String, String)]) -> CoreResult<HeaderMap> {
+    let mut headers = HeaderMap::with_capacity(list.len());
+    for (name, value) in list {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| plugin_error(format!("invalid header name: {name}")))?;
+        let header_value = HeaderValue::from_str(value)
+            .map_err(|_| plugin_error(format!("invalid value for header {name}")))?;
+        headers.append(header_name, header_value);
+    }
+    Ok(headers)
+}
+
+fn into_response(response: HttpResponse) -> CoreResult<Response> {
+    let headers = list_to_headers(&response.headers)?;
+    let mut built = Response::new(Body::from(response.body));
+    *built.status_mut() = parse_status(response.status)?;
+    *built.headers_mut() = headers;
+    Ok(built)
+}
+
+fn parse_status(status: u16) -> CoreResult<StatusCode> {
+    StatusCode::from_u16(status).map_err(|_| plugin_error(format!("invalid status code {status}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use http_body_util::Full;
+    use serde_json::json;
+
+    /// A hand-written component implementing the `handler` world:
+    /// - `handle-request` logs the config, spins forever when the config is
+    ///   four bytes long, answers 418 with the config as body when the request
+    ///   has no body, and otherwise continues as `PUT` with the config as body.
+    /// - `handle-response` returns status 201 with the config as body.
+    const TEST_PLUGIN: &str = r#"
+(component
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $next (mut i32) (i32.const 1024))
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $next))
+      (global.set $next
+        (i32.and (i32.add (i32.add (local.get $ptr) (local.get 3)) (i32.const 7)) (i32.const -8)))
+      (local.get $ptr)))
+  (core instance $libc (instantiate $libc))
+  (import "log" (func $log (param "message" string)))
+  (core func $log-lower (canon lower (func $log) (memory $libc "memory")))
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "host" "log" (func $log (param i32 i32)))
+    (data (i32.const 16) "PUT")
+    (func (export "handle-request")
+      (param $cp i32) (param $cl i32)
+      (param $mp i32) (param $ml i32) (param $up i32) (param $ul i32)
+      (param $hp i32) (param $hl i32) (param $bp i32) (param $bl i32)
+      (result i32)
+      (call $log (local.get $cp) (local.get $cl))
+      (if (i32.eq (local.get $cl) (i32.const 4)) (then (loop $spin (br $spin))))
+      (i32.store8 (i32.const 64) (i32.const 0))
+      (if (i32.eqz (local.get $bl))
+        (then
+          (i32.store8 (i32.const 68) (i32.const 1))
+          (i32.store16 (i32.const 72) (i32.const 418))
+          (i32.store (i32.const 76) (local.get $hp))
+          (i32.store (i32.const 80) (local.get $hl))
+          (i32.store (i32.const 84) (local.get $cp))
+          (i32.store (i32.const 88) (local.get $cl)))
+        (else
+          (i32.store8 (i32.const 68) (i32.const 0))
+          (i32.store (i32.const 72) (i32.const 16))
+          (i32.store (i32.const 76) (i32.const 3))
+          (i32.store (i32.const 80) (local.get $up))
+          (i32.store (i32.const 84) (local.get $ul))
+          (i32.store (i32.const 88) (local.get $hp))
+          (i32.store (i32.const 92) (local.get $hl))
+          (i32.store (i32.const 96) (local.get $cp))
+          (i32.store (i32.const 100) (local.get $cl))))
+      (i32.const 64))
+    (func (export "handle-response")
+      (param $cp i32) (param $cl i32)
+      (param i32 i32 i32 i32 i32 i32 i32 i32)
+      (param $status i32) (param $hp i32) (param $hl i32) (param $bp i32) (param $bl i32)
+      (result i32)
+      (i32.store8 (i32.const 64) (i32.const 0))
+      (i32.store16 (i32.const 68) (i32.const 201))
+      (i32.store (i32.const 72) (local.get $hp))
+      (i32.store (i32.const 76) (local.get $hl))
+      (i32.store (i32.const 80) (local.get $cp))
+      (i32.store (i32.const 84) (local.get $cl))
+      (i32.const 64)))
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "host" (instance (export "log" (func $log-lower))))))
+  (type $header (tuple string string))
+  (type $request' (record (field "method" string) (field "url" string)
+    (field "headers" (list $header)) (field "body" (list u8))))
+  (export $request "http-request" (type $request'))
+  (type $response' (record (field "status" u16) (field "headers" (list $header))
+    (field "body" (list u8))))
+  (export $response "http-response" (type $response'))
+  (type $outcome' (variant (case "continue" $request) (case "respond" $response)))
+  (export $outcome "request-outcome" (type $outcome'))
+  (func (export "handle-request")
+    (param "config" string) (param "request" $request)
+    (result (result $outcome (error string)))
+    (canon lift (core func $main "handle-request")
+      (memory $libc "memory") (realloc (func $libc "realloc"))))
+  (func (export "handle-response")
+    (param "config" string) (param "request" $request) (param "response" $response)
+    (result (result $response (error string)))
+    (canon lift (core func $main "handle-response")
+      (memory $libc "memory") (realloc (func $libc "realloc")))))
+"#;
+
+    fn plugin(dir: &Path, config: WasmPluginConfig) -> (WasmPluginConfig, PathBuf) {
+        let path = dir.join("test-plugin.wat");
+        std::fs::write(&path, TEST_PLUGIN).unwrap();
+        (config, path)
+    }
+
+    fn config(value: serde_json::Value) -> WasmPluginConfig {
+        WasmPluginConfig {
+            plugin: "test-plugin.wat".to_string(),
+            config: value,
+            ..Default::default()
+        }
+    }
+
+    fn request(body: &'static str) -> Req {
+        http::Request::builder()
+            .method("POST")
+            .uri("http://example.com/api?x=1")
+            .header("x-a", "1")
+            .header("x-a", "2")
+            .body(full(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn continue_rewrites_the_request() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (config, path) = plugin(dir.path(), config(json!({"sign": true})));
+        let plugin = WasmPlugin::new(&config, path);
+
+        let HandleRequestType::Request(request) = plugin.handle_request(request("abc")).await?
+        else {
+            panic!("expected the request to continue");
+        };
+        assert_eq!(request.method(), Method::PUT);
+        assert_eq!(request.uri(), "http://example.com/api?x=1");
+        let values: Vec<_> = request.headers().get_all("x-a").iter().collect();
+        assert_eq!(values, ["1", "2"]);
+        assert_eq!(request.headers()["content-length"], "13");
+        assert!(request.extensions().get::<PluginRequest>().is_some());
+        let body = request.into_body().collect().await?.to_bytes();
+        assert_eq!(body, r#"{"sign":true}"#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn respond_short_circuits() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (config, path) = plugin(dir.path(), config(json!("teapot")));
+        let plugin = WasmPlugin::new(&config, path);
+
+        let HandleRequestType::Response(response) = plugin.handle_request(request("")).await?
+        else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(response.headers().get_all("x-a").iter().count(), 2);
+        let body = response.into_body().collect().await?.to_bytes();
+        assert_eq!(body, r#""teapot""#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fuel_bounds_runaway_plugins() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (config, path) = plugin(
+            dir.path(),
+            WasmPluginConfig {
+                fuel: Some(100_000),
+                ..config(json!(1234))
+            },
+        );
+        let plugin = WasmPlugin::new(&config, path);
+
+        let Err(error) = plugin.handle_request(request("abc")).await else {
+            panic!("expected the plugin call to fail");
+        };
+        let CoreError::Internal { source, .. } = error else {
+            panic!("expected an internal error, got {error}");
+        };
+        assert!(source.to_string().contains("ran out of fuel"), "{source}");
+        assert!(plugin.instance.lock().unwrap().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn both_phases_share_one_instance() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (config, path) = plugin(dir.path(), config(json!("shared")));
+        let plugin = WasmPlugin::new(&config, path);
+
+        let state = || {
+            plugin
+                .instance
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|live| live.store.data() as *const PluginState)
+        };
+
+        plugin.handle_request(request("abc")).await?;
+        let instantiated = state();
+        assert!(instantiated.is_some());
+        let response = Response::builder().status(200).body(Body::empty())?;
+        plugin
+            .handle_plugin_response(response, &PluginContext::default())
+            .await?;
+        assert_eq!(state(), instantiated);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_response_replaces_a_compressed_body() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let (config, path) = plugin(dir.path(), config(json!("plain")));
+        let plugin = WasmPlugin::new(&config, path);
+        let response = Response::builder()
+            .status(200)
+            .header("content-encoding", "br")
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .body(Body::new(Full::new(Bytes::from_static(b"not really br"))))?;
+
+        let response = plugin
+            .handle_plugin_response(response, &PluginContext::default())
+            .await?;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(response.headers().get_all("set-cookie").iter().count(), 2);
+        let body = response.into_body().collect().await?.to_bytes();
+        assert_eq!(body, r#""plain""#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn invalid_modules_are_config_errors() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("broken.wasm");
+        std::fs::write(&path, b"\0asm not a module")?;
+        let config = config(serde_json::Value::Null);
+        let plugin = WasmPlugin::new(&config, path);
+
+        let Err(error) = plugin.handle_request(request("abc")).await else {
+            panic!("expected the plugin call to fail");
+        };
+        assert!(matches!(error, CoreError::Validation { .. }), "{error}");
+        Ok(())
+    }
+}
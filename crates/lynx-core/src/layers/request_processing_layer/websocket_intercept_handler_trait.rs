@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use lynx_storage::dao::request_processing_dao::handlers::{
+    WebSocketFrameAction, WebSocketFrameRule, WebSocketInterceptConfig,
+};
+use regex::Regex;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::body_transform::apply_transforms;
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    proxy::proxy_ws_request::SendType,
+};
+
+/// Frame rules from every `WebSocketIntercept` handler matching an upgrade
+/// request, in execution order.
+#[derive(Debug, Clone, Default)]
+pub struct WebSocketFrameRules(Vec<CompiledFrameRule>);
+
+#[derive(Debug, Clone)]
+struct CompiledFrameRule {
+    rule: WebSocketFrameRule,
+    pattern: Option<Regex>,
+    json_path: Option<JsonPath>,
+}
+
+/// What happens to a frame once the rules have run.
+#[derive(Debug)]
+pub enum FrameVerdict {
+    /// Send `message` after waiting `delay`
+    Forward {
+        message: Message,
+        delay: Duration,
+    },
+    Drop,
+}
+
+#[async_trait::async_trait]
+impl HandlerTrait for WebSocketInterceptConfig {
+    async fn handle_request(&self, mut request: Req) -> CoreResult<HandleRequestType> {
+        let compiled = self
+            .rules
+            .iter()
+            .map(CompiledFrameRule::new)
+            .collect::<CoreResult<Vec<_>>>()?;
+        request
+            .extensions_mut()
+            .get_or_insert_default::<WebSocketFrameRules>()
+            .0
+            .extend(compiled);
+        Ok(HandleRequestType::Request(request))
+    }
+}
+
+impl CompiledFrameRule {
+    fn new(rule: &WebSocketFrameRule) -> CoreResult<Self> {
+        let invalid = |message: String| CoreError::Validation { message };
+        let pattern = rule
+            .pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| invalid(format!("invalid frame regex: {e}")))?;
+        let json_path = rule
+            .json_path
+            .as_deref()
+            .map(JsonPath::parse)
+            .transpose()
+            .map_err(|e| invalid(format!("invalid frame JSONPath: {e}")))?;
+        Ok(Self {
+            rule: rule.clone(),
+            pattern,
+            json_path,
+        })
+    }
+
+    fn applies_to(&self, send_type: &SendType) -> bool {
+        match send_type {
+            SendType::ClientToServer => self.rule.direction.includes_client_to_server(),
+            SendType::ServerToClient => self.rule.direction.includes_server_to_client(),
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(text)
+        {
+            return false;
+        }
+        let Some(path) = &self.json_path else {
+            return true;
+        };
+        let Ok(doc) = serde_json::from_str::<Value>(text) else {
+            return false;
+        };
+        let nodes = path.query(&doc);
+        match &self.rule.json_value {
+            Some(expected) => nodes.iter().any(|node| *node == expected),
+            None => !nodes.is_empty(),
+        }
+    }
+}
+
+impl WebSocketFrameRules {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Runs the rules over a frame travelling `send_type`. Only text frames
+    /// are matched; anything else is forwarded as is.
+    pub fn apply(&self, send_type: &SendType, message: Message) -> FrameVerdict {
+        let Message::Text(original) = &message else {
+            return FrameVerdict::Forward {
+                message,
+                delay: Duration::ZERO,
+            };
+        };
+        let mut text: Option<String> = None;
+        let mut delay = Duration::ZERO;
+
+        for compiled in self.0.iter().filter(|c| c.applies_to(send_type)) {
+            let current = text.as_deref().unwrap_or(original.as_str());
+            if !compiled.matches(current) {
+                continue;
+            }
+            match &compiled.rule.action {
+                WebSocketFrameAction::Drop => return FrameVerdict::Drop,
+                WebSocketFrameAction::Delay { delay_ms } => {
+                    delay += Duration::from_millis(*delay_ms);
+                }
+                WebSocketFrameAction::Rewrite {
+                    replacement,
+                    transforms,
+                } => {
+                    let base = replacement.as_deref().unwrap_or(current);
+                    match apply_transforms(Bytes::from(base.to_string()), transforms) {
+                        Ok(rewritten) => {
+                            text = Some(String::from_utf8_lossy(&rewritten).into_owned());
+                        }
+                        Err(e) => tracing::warn!("Skipping WebSocket frame rewrite: {e}"),
+                    }
+                }
+            }
+        }
+
+        let message = match text {
+            Some(text) => Message::Text(text.into()),
+            None => message,
+        };
+        FrameVerdict::Forward { message, delay }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(config: Value) -> WebSocketFrameRules {
+        let config: WebSocketInterceptConfig = serde_json::from_value(config).unwrap();
+        WebSocketFrameRules(
+            config
+                .rules
+                .iter()
+                .map(|rule| CompiledFrameRule::new(rule).unwrap())
+                .collect(),
+        )
+    }
+
+    fn forwarded(verdict: FrameVerdict) -> (String, Duration) {
+        match verdict {
+            FrameVerdict::Forward { message, delay } => {
+                (message.into_text().unwrap().to_string(), delay)
+            }
+            FrameVerdict::Drop => panic!("frame dropped"),
+        }
+    }
+
+    #[test]
+    fn rewrites_delays_and_drops_text_frames() {
+        let rules = rules(json!({
+            "rules": [
+                { "pattern": "^ping$", "action": { "type": "drop" } },
+                {
+                    "direction": "serverToClient",
+                    "jsonPath": "$.type",
+                    "jsonValue": "price",
+                    "action": {
+                        "type": "rewrite",
+                        "transforms": [{ "type": "jsonPathSet", "path": "$.price", "value": 0 }]
+                    }
+                },
+                { "jsonPath": "$.price", "action": { "type": "delay", "delayMs": 20 } },
+                {
+                    "pattern": "hello (\\w+)",
+                    "action": {
+                        "type": "rewrite",
+                        "transforms": [{ "type": "regexReplace", "pattern": "hello (\\w+)", "replacement": "bye $1" }]
+                    }
+                }
+            ]
+        }));
+
+        assert!(matches!(
+            rules.apply(&SendType::ClientToServer, Message::text("ping")),
+            FrameVerdict::Drop
+        ));
+
+        let price = r#"{"type":"price","price":12}"#;
+        let (text, delay) = forwarded(rules.apply(&SendType::ServerToClient, Message::text(price)));
+        assert_eq!(text, r#"{"price":0,"type":"price"}"#);
+        assert_eq!(delay, Duration::from_millis(20));
+
+        // Direction-limited rules leave the other direction alone.
+        let (text, _) = forwarded(rules.apply(&SendType::ClientToServer, Message::text(price)));
+        assert_eq!(text, price);
+
+        let (text, delay) =
+            forwarded(rules.apply(&SendType::ClientToServer, Message::text("hello lynx")));
+        assert_eq!(text, "bye lynx");
+        assert_eq!(delay, Duration::ZERO);
+
+        let binary = Message::binary(b"ping".to_vec());
+        assert!(matches!(
+            rules.apply(&SendType::ClientToServer, binary),
+            FrameVerdict::Forward {
+                message: Message::Binary(_),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result, anyhow};
+use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
+use bytes::Bytes;
+use futures_util::{Sink, SinkExt, StreamExt};
+use http::{
+    HeaderValue, StatusCode,
+    header::{CONNECTION, CONTENT_TYPE, SEC_WEBSOCKET_PROTOCOL, UPGRADE},
+};
+use hyper_tungstenite::{
+    HyperWebsocket,
+    tungstenite::{
+        self, Message,
+        protocol::{CloseFrame, frame::coding::CloseCode},
+    },
+};
+use lynx_storage::dao::request_processing_dao::handlers::{
+    WebSocketMockConfig, WebSocketMockFrame, WebSocketMockPeriodic,
+};
+use regex::{Captures, Regex};
+use tokio::{
+    spawn,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    time::{Instant, sleep, sleep_until},
+};
+use tracing::debug;
+
+use super::handler_trait::{HandleRequestType, HandlerTrait};
+use crate::{
+    common::Req,
+    error::{CoreError, CoreResult},
+    layers::{
+        message_package_layer::{MessageEventChannel, MessageEventLayerExt},
+        trace_id_layer::service::{TraceId, TraceIdExt},
+    },
+    proxy::proxy_ws_request::SendType,
+    utils::full,
+};
+
+#[async_trait::async_trait]
+impl HandlerTrait for WebSocketMockConfig {
+    async fn handle_request(&self, mut request: Req) -> CoreResult<HandleRequestType> {
+        if !hyper_tungstenite::is_upgrade_request(&request) {
+            let response = Response::builder()
+                .status(StatusCode::UPGRADE_REQUIRED)
+                .header(CONNECTION, "Upgrade")
+                .header(UPGRADE, "websocket")
+                .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(full("WebSocket mock: expected a WebSocket upgrade request"))?;
+            return Ok(HandleRequestType::Response(response.into_response()));
+        }
+
+        let session = MockSession::compile(self)?;
+        let message_channel = request.extensions().try_get_message_event_cannel()?;
+        let trace_id = request.extensions().get_trace_id();
+        let protocol = first_offered_protocol(&request);
+        let (upgrade_response, websocket) = hyper_tungstenite::upgrade(&mut request, None)
+            .map_err(|e| CoreError::Validation {
+                message: format!("websocket upgrade: {e}"),
+            })?;
+
+        message_channel
+            .dispatch_on_websocket_start(trace_id.clone())
+            .await;
+        spawn(async move {
+            match session.run(websocket, &message_channel, &trace_id).await {
+                Ok(()) => message_channel.dispatch_on_websocket_end(trace_id).await,
+                Err(e) => {
+                    let reason = format!("WebSocket mock error: {e}");
+                    message_channel
+                        .dispatch_on_websocket_error(trace_id, reason)
+                        .await;
+                }
+            }
+        });
+
+        let (mut parts, _) = upgrade_response.into_parts();
+        if let Some(protocol) = protocol {
+            parts.headers.insert(SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        Ok(HandleRequestType::Response(
+            Response::from_parts(parts, full(Bytes::new())).into_response(),
+        ))
+    }
+}
+
+/// The first subprotocol in the client's `Sec-WebSocket-Protocol` offer.
+fn first_offered_protocol(request: &Req) -> Option<HeaderValue> {
+    let offer = request
+        .headers()
+        .get(SEC_WEBSOCKET_PROTOCOL)?
+        .to_str()
+        .ok()?;
+    let first = offer.split(',').map(str::trim).find(|p| !p.is_empty())?;
+    HeaderValue::from_str(first).ok()
+}
+
+/// How long a scripted close waits for the client's close frame.
+const CLOSE_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `WebSocketMock` script with its reply patterns compiled.
+struct MockSession {
+    config: WebSocketMockConfig,
+    replies: Vec<(Regex, Vec<WebSocketMockFrame>)>,
+}
+
+impl MockSession {
+    fn compile(config: &WebSocketMockConfig) -> CoreResult<Self> {
+        let replies = config
+            .replies
+            .iter()
+            .map(|reply| {
+                Regex::new(&reply.pattern)
+                    .map(|pattern| (pattern, reply.frames.clone()))
+                    .map_err(|e| CoreError::Validation {
+                        message: format!("invalid reply pattern {:?}: {e}", reply.pattern),
+                    })
+            })
+            .collect::<CoreResult<_>>()?;
+        Ok(Self {
+            config: config.clone(),
+            replies,
+        })
+    }
+
+    async fn run(
+        self,
+        websocket: HyperWebsocket,
+        mc: &Arc<MessageEventChannel>,
+        trace_id: &TraceId,
+    ) -> Result<()> {
+        let opened = Instant::now();
+        let (sink, mut stream) = websocket.await?.split();
+        let mut out = Outgoing { sink, mc, trace_id };
+
+        for frame in &self.config.on_connect {
+            out.send_frame(frame, None).await?;
+        }
+
+        let (tick_tx, mut ticks) = unbounded_channel();
+        let tickers: Vec<_> = self
+            .config
+            .periodic
+            .iter()
+            .map(|periodic| spawn(tick(periodic.clone(), tick_tx.clone())))
+            .collect();
+        drop(tick_tx);
+
+        let mut close_at = self
+            .config
+            .close
+            .as_ref()
+            .map(|close| opened + Duration::from_millis(close.after_ms));
+        let mut closing = false;
+
+        let result = async {
+            loop {
+                tokio::select! {
+                    message = stream.next() => {
+                        let Some(message) = message else { break };
+                        let message = message.context("failed to receive message")?;
+                        if closing {
+                            out.capture(SendType::ClientToServer, message);
+                            continue;
+                        }
+                        self.answer(&mut out, message).await?;
+                    }
+                    Some(frame) = ticks.recv(), if !closing => {
+                        out.send_frame(&frame, None).await?;
+                    }
+                    _ = sleep_until(close_at.unwrap_or(opened)), if close_at.is_some() => {
+                        if closing {
+                            debug!("client did not complete the close handshake");
+                            break;
+                        }
+                        let close = self.config.close.as_ref().expect("close deadline set");
+                        debug!(code = close.code, "closing mocked websocket");
+                        out.send(Message::Close(Some(CloseFrame {
+                            code: CloseCode::from(close.code),
+                            reason: close.reason.clone().into(),
+                        })))
+                        .await?;
+                        closing = true;
+                        close_at = Some(Instant::now() + CLOSE_HANDSHAKE_TIMEOUT);
+                    }
+                }
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        for ticker in tickers {
+            ticker.abort();
+        }
+        result
+    }
+
+    /// Replies with the first matching entry, or echoes when enabled.
+    async fn answer<Si>(&self, out: &mut Outgoing<'_, Si>, message: Message) -> Result<()>
+    where
+        Si: Sink<Message, Error = tungstenite::Error> + Unpin,
+    {
+        out.capture(SendType::ClientToServer, message.clone());
+        if let Message::Text(text) = &message
+            && let Some((captures, frames)) = self
+                .replies
+                .iter()
+                .find_map(|(pattern, frames)| Some((pattern.captures(text)?, frames)))
+        {
+            for frame in frames {
+                out.send_frame(frame, Some(&captures)).await?;
+            }
+            return Ok(());
+        }
+        if self.config.echo && matches!(message, Message::Text(_) | Message::Binary(_)) {
+            out.send(message).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Feeds one periodic entry's frame into the session until its count runs out.
+async fn tick(periodic: WebSocketMockPeriodic, ticks: UnboundedSender<WebSocketMockFrame>) {
+    let interval = Duration::from_millis(periodic.interval_ms);
+    let mut sent = 0;
+    while periodic.count.is_none_or(|count| sent < count) {
+        sleep(interval).await;
+        if ticks.send(periodic.frame.clone()).is_err() {
+            break;
+        }
+        sent += 1;
+    }
+}
+
+/// The server half of a mocked connection; everything sent is captured.
+struct Outgoing<'a, Si> {
+    sink: Si,
+    mc: &'a Arc<MessageEventChannel>,
+    trace_id: &'a TraceId,
+}
+
+impl<Si> Outgoing<'_, Si>
+where
+    Si: Sink<Message, Error = tungstenite::Error> + Unpin,
+{
+    async fn send_frame(
+        &mut self,
+        frame: &WebSocketMockFrame,
+        captures: Option<&Captures<'_>>,
+    ) -> Result<()> {
+        if let Some(delay_ms) = frame.delay_ms {
+            sleep(Duration::from_millis(delay_ms)).await;
+        }
+        let message = match (&frame.text, &frame.binary_base64) {
+            (Some(text), _) => match captures {
+                Some(captures) => {
+                    let mut expanded = String::new();
+                    captures.expand(text, &mut expanded);
+                    Message::text(expanded)
+                }
+                None => Message::text(text.clone()),
+            },
+            (None, Some(encoded)) => Message::binary(
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .context("invalid binaryBase64")?,
+            ),
+            (None, None) => return Err(anyhow!("mock frame has neither text nor binaryBase64")),
+        };
+        self.send(message).await
+    }
+
+    async fn send(&mut self, message: Message) -> Result<()> {
+        self.sink
+            .send(message.clone())
+            .await
+            .context("failed to send message")?;
+        self.capture(SendType::ServerToClient, message);
+        Ok(())
+    }
+
+    fn capture(&self, send_type: SendType, message: Message) {
+        if !matches!(message, Message::Ping(_) | Message::Pong(_)) {
+            self.mc
+                .spawn_websocket_message_capture(self.trace_id.clone(), send_type, message);
+        }
+    }
+}
@@ -4,3 +4,4 @@ pub mod proxy_http_request;
 pub mod proxy_tunnel_request;
 pub mod proxy_ws_request;
 pub mod tunnel_proxy_by_stream;
+pub mod websocket_connections;
@@ -15,6 +15,7 @@ use tower::{ServiceBuilder, service_fn, util::Oneshot};
 use tracing::{Instrument, instrument, trace_span};
 
 use crate::{
+    client::request_client::RequestClientExt,
     common::{HyperReq, Req},
     gateway_service::proxy_gateway_service_fn,
     layers::{
@@ -46,13 +47,16 @@ async fn proxy_connect_request_future(req: Req) -> Result<()> {
     let trace_id = req.extensions().get_trace_id();
 
     let new_extension = clone_extensions(req.extensions())?;
+    let request_client = req
+        .extensions()
+        .get_request_client()
+        .ok_or_else(|| anyhow!("Missing request client in request"))?;
 
     let uri = req.uri().clone();
     let authority: http::uri::Authority = uri
         .authority()
         .cloned()
         .ok_or_else(|| anyhow::anyhow!("Missing authority in URI"))?;
-    let target_addr = uri.to_string();
 
     let server_ca_manage = req.extensions().get_server_ca_manager();
 
@@ -99,11 +103,19 @@ async fn proxy_connect_request_future(req: Req) -> Result<()> {
                 "Handling HTTPS connect request for authority: {}",
                 authority
             );
-            if !should_capture_https(store, &authority)
+            if !should_capture_https(store.clone(), &authority)
                 .await
                 .map_err(|e| anyhow!(e).context("Failed to check if should capture https"))?
             {
-                tunnel_proxy_by_stream(upgraded, target_addr, trace_id, event_cannel).await?;
+                tunnel_proxy_by_stream(
+                    upgraded,
+                    &authority,
+                    &store,
+                    &request_client,
+                    trace_id,
+                    event_cannel,
+                )
+                .await?;
                 return Ok(());
             }
 
@@ -143,7 +155,15 @@ async fn proxy_connect_request_future(req: Req) -> Result<()> {
                 .map_err(|e| anyhow!(e))?;
         }
         ConnectStreamType::Other => {
-            tunnel_proxy_by_stream(upgraded, target_addr, trace_id, event_cannel).await?;
+            tunnel_proxy_by_stream(
+                upgraded,
+                &authority,
+                &store,
+                &request_client,
+                trace_id,
+                event_cannel,
+            )
+            .await?;
         }
     }
     Ok(())
@@ -1,17 +1,23 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use axum::response::{IntoResponse, Response};
 use http::Uri;
+use hyper_util::client::legacy::connect::HttpInfo;
 use tower::{ServiceBuilder, ServiceExt, service_fn};
 use tracing::instrument;
 
 use crate::{
-    client::request_client::RequestClientExt,
+    client::{request_client::RequestClientExt, upstream_proxy::UpstreamPeerAddr},
     common::Req,
     error::{CoreError, root_cause_message},
     layers::{
         build_proxy_request::BuildProxyRequestService,
-        message_package_layer::ProxyMessageEventService,
-        request_processing_layer::RequestProcessingService, trace_id_layer::service::TraceIdExt,
+        message_package_layer::{MessageEventChannel, ProxyMessageEventService},
+        request_processing_layer::{
+            RequestProcessingService, upstream_proxy_handler_trait::SelectedUpstreamProxy,
+        },
+        trace_id_layer::service::TraceIdExt,
     },
 };
 
@@ -82,19 +88,39 @@ fn classify_upstream_error(uri: &Uri, err: anyhow::Error) -> CoreError {
 async fn proxy_http_request_inner(req: Req) -> Result<Response> {
     let trace_id = req.extensions().get_trace_id().clone();
     let uri = req.uri().clone();
-    let http_client = req
-        .extensions()
-        .try_get_http_client()
-        .map_err(anyhow::Error::from)?;
-    http_client
+    let event_cannel = req.extensions().get::<Arc<MessageEventChannel>>().cloned();
+    let http_client = match (
+        req.extensions().get::<SelectedUpstreamProxy>(),
+        req.extensions().get_request_client(),
+    ) {
+        (Some(SelectedUpstreamProxy(upstream)), Some(request_client)) => {
+            request_client.upstream_http_client(upstream)?
+        }
+        _ => req
+            .extensions()
+            .try_get_http_client()
+            .map_err(anyhow::Error::from)?,
+    };
+    let mut res = http_client
         .request(req)
         .await
-        .map_err(|e| anyhow::Error::from(classify_upstream_error(&uri, e)))
-        .map(|mut res| {
-            res.extensions_mut().insert(trace_id);
-            res
-        })
-        .map(|res| res.into_response())
+        .map_err(|e| anyhow::Error::from(classify_upstream_error(&uri, e)))?;
+    let server_addr = res
+        .extensions()
+        .get::<HttpInfo>()
+        .map(HttpInfo::remote_addr)
+        .or_else(|| {
+            res.extensions()
+                .get::<UpstreamPeerAddr>()
+                .map(|addr| addr.0)
+        });
+    if let (Some(event_cannel), Some(addr)) = (event_cannel, server_addr) {
+        event_cannel
+            .dispatch_on_server_connected(trace_id.clone(), addr)
+            .await;
+    }
+    res.extensions_mut().insert(trace_id);
+    Ok(res.into_response())
 }
 
 #[instrument(skip_all)]
@@ -5,10 +5,11 @@ use hyper::Method;
 use hyper_util::rt::TokioIo;
 use tracing::{error, instrument};
 
+use crate::client::request_client::RequestClientExt;
 use crate::common::Req;
+use crate::layers::extend_extension_layer::DataStoreExtensionsExt;
 use crate::layers::message_package_layer::MessageEventLayerExt;
 use crate::layers::trace_id_layer::service::TraceIdExt;
-use crate::utils::host_addr;
 
 use super::tunnel_proxy_by_stream::tunnel_proxy_by_stream;
 
@@ -34,11 +35,28 @@ pub async fn proxy_tunnel_proxy(req: Req) -> anyhow::Result<Response> {
 pub async fn tunnel_proxy_by_req(req: Req) -> Result<()> {
     let trace_id = req.extensions().get_trace_id();
     let event_cannel = req.extensions().get_message_event_cannel();
-    let addr = host_addr(req.uri()).ok_or_else(|| anyhow::anyhow!("Invalid URI: {}", req.uri()))?;
+    let store = req.extensions().get_data_store();
+    let request_client = req
+        .extensions()
+        .get_request_client()
+        .ok_or_else(|| anyhow::anyhow!("Missing request client in request"))?;
+    let authority = req
+        .uri()
+        .authority()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Invalid URI: {}", req.uri()))?;
 
     let upgraded = hyper::upgrade::on(req).await?;
 
-    tunnel_proxy_by_stream(TokioIo::new(upgraded), addr, trace_id, event_cannel).await?;
+    tunnel_proxy_by_stream(
+        TokioIo::new(upgraded),
+        &authority,
+        &store,
+        &request_client,
+        trace_id,
+        event_cannel,
+    )
+    .await?;
 
     Ok(())
 }
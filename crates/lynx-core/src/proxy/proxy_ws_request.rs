@@ -11,7 +11,7 @@ use http::{
 use http_body_util::BodyExt;
 use hyper_tungstenite::HyperWebsocket;
 use serde::{Deserialize, Serialize};
-use tokio::spawn;
+use tokio::{net::TcpStream, spawn, sync::mpsc::UnboundedReceiver};
 use tokio_tungstenite::{
     MaybeTlsStream, WebSocketStream,
     tungstenite::{self, client::IntoClientRequest},
@@ -20,13 +20,23 @@ use tower::{ServiceBuilder, ServiceExt, service_fn};
 use tracing::{debug, instrument};
 
 use crate::{
-    client::request_client::RequestClientExt,
+    client::{
+        request_client::{RequestClient, RequestClientExt},
+        upstream_proxy::UpstreamStream,
+        websocket_client::WebsocketClient,
+    },
     common::Req,
     layers::{
         message_package_layer::{MessageEventChannel, MessageEventLayerExt},
-        request_processing_layer::RequestProcessingService,
+        request_processing_layer::{
+            RequestProcessingService,
+            proxy_forward_handler_trait::PreservedHost,
+            upstream_proxy_handler_trait::SelectedUpstreamProxy,
+            websocket_intercept_handler_trait::{FrameVerdict, WebSocketFrameRules},
+        },
         trace_id_layer::service::{TraceId, TraceIdExt},
     },
+    proxy::websocket_connections::{InjectedFrames, WebSocketConnections},
     utils::full,
 };
 
@@ -74,7 +84,9 @@ fn align_upstream_handshake_headers(parts: &mut Parts, uri: &Uri) -> Result<()>
     parts.headers.remove(PROXY_AUTHORIZATION);
     parts.headers.remove(HOST);
 
-    if let Some(authority) = uri.authority() {
+    if let Some(PreservedHost(host)) = parts.extensions.get::<PreservedHost>() {
+        parts.headers.insert(HOST, host.clone());
+    } else if let Some(authority) = uri.authority() {
         parts.headers.insert(
             HOST,
             HeaderValue::from_str(authority.as_str())
@@ -113,6 +125,45 @@ impl IntoClientRequest for WebSocketReq {
     }
 }
 
+type UpstreamHandshake<S> = (
+    WebSocketStream<MaybeTlsStream<S>>,
+    http::Response<Option<Vec<u8>>>,
+);
+
+/// The upstream side of a proxied WebSocket.
+enum UpstreamWebSocket {
+    Direct(WebSocketStream<MaybeTlsStream<TcpStream>>),
+    /// Through the upstream proxy picked by an `UpstreamProxy` rule
+    Tunneled(WebSocketStream<MaybeTlsStream<UpstreamStream>>),
+}
+
+/// Handshakes with the target over a tunnel through `upstream`.
+async fn connect_through_upstream(
+    ws_client: &WebsocketClient,
+    request_client: &RequestClient,
+    upstream: &SelectedUpstreamProxy,
+    ws_req: WebSocketReq,
+) -> Result<UpstreamHandshake<UpstreamStream>> {
+    let uri = ws_req.0.uri();
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow!("websocket URI has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("wss") {
+            443
+        } else {
+            80
+        });
+    let stream = request_client
+        .open_upstream_tunnel(&upstream.0, &host, port)
+        .await?;
+    ws_client.request_through(ws_req, stream).await
+}
+
 pub fn is_websocket_req(req: &Req) -> bool {
     hyper_tungstenite::is_upgrade_request(req)
 }
@@ -123,15 +174,37 @@ async fn proxy_ws_inner(mut req: Req) -> Result<Response> {
     let trace_id = req.extensions().get_trace_id();
 
     let (client_res, hyper_ws) = hyper_tungstenite::upgrade(&mut req, None)?;
+    let frame_rules = req
+        .extensions()
+        .get::<WebSocketFrameRules>()
+        .cloned()
+        .unwrap_or_default();
+    let connections = req
+        .extensions()
+        .get::<Arc<WebSocketConnections>>()
+        .cloned()
+        .unwrap_or_default();
 
     message_channel
         .dispatch_on_websocket_start(trace_id.clone())
         .await;
 
     let ws_client = req.extensions().try_get_websocket_client()?;
+    let upstream = req.extensions().get::<SelectedUpstreamProxy>().cloned();
+    let request_client = req.extensions().get_request_client();
     let ws_req = prepare_upstream_websocket_request(req)?;
 
-    let upstream_result = ws_client.request(ws_req).await;
+    let upstream_result = match (upstream, request_client) {
+        (Some(upstream), Some(request_client)) => {
+            connect_through_upstream(&ws_client, &request_client, &upstream, ws_req)
+                .await
+                .map(|(ws, res)| (UpstreamWebSocket::Tunneled(ws), res))
+        }
+        _ => ws_client
+            .request(ws_req)
+            .await
+            .map(|(ws, res)| (UpstreamWebSocket::Direct(ws), res)),
+    };
     if let Err(e) = &upstream_result {
         let message_channel = message_channel.clone();
         let trace_id = trace_id.clone();
@@ -146,9 +219,23 @@ async fn proxy_ws_inner(mut req: Req) -> Result<Response> {
 
     let mc = message_channel.clone();
     let tid = trace_id.clone();
+    let injected = connections.register(trace_id.clone());
     spawn(async move {
-        match handle_hyper_and_client_websocket(hyper_ws, client_ws, mc.clone(), tid.clone()).await
-        {
+        let relay = Relay {
+            mc: mc.clone(),
+            trace_id: tid.clone(),
+            frame_rules,
+            injected,
+        };
+        let result = match client_ws {
+            UpstreamWebSocket::Direct(ws) => {
+                handle_hyper_and_client_websocket(hyper_ws, ws, relay).await
+            }
+            UpstreamWebSocket::Tunneled(ws) => {
+                handle_hyper_and_client_websocket(hyper_ws, ws, relay).await
+            }
+        };
+        match result {
             Ok(()) => {
                 mc.dispatch_on_websocket_end(tid).await;
             }
@@ -182,11 +269,19 @@ pub async fn proxy_ws_request(req: Req) -> anyhow::Result<Response> {
     Ok(res.into_response())
 }
 
+/// Per-connection state shared by both relay directions.
+struct Relay {
+    mc: Arc<MessageEventChannel>,
+    trace_id: TraceId,
+    frame_rules: WebSocketFrameRules,
+    /// Frames sent with `websocket.send`; registered while the connection is open
+    injected: InjectedFrames,
+}
+
 async fn handle_hyper_and_client_websocket<S>(
     hyper_ws: HyperWebsocket,
     client_ws: WebSocketStream<MaybeTlsStream<S>>,
-    mc: Arc<MessageEventChannel>,
-    trace_id: TraceId,
+    mut relay: Relay,
 ) -> anyhow::Result<()>
 where
     S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
@@ -196,26 +291,30 @@ where
     let (hyper_sink, hyper_stream) = hyper_ws_stream.split();
     let (client_sink, client_stream) = client_ws.split();
 
-    let mc1 = mc.clone();
-    let tid1 = trace_id.clone();
     let upstream_to_client = relay_websocket_direction(
         hyper_sink,
         client_stream,
         SendType::ServerToClient,
-        mc1,
-        tid1,
+        relay.mc.clone(),
+        relay.trace_id.clone(),
         "upstream-to-client",
+        DirectionControl {
+            frame_rules: &relay.frame_rules,
+            injected: &mut relay.injected.server_to_client,
+        },
     );
 
-    let mc2 = mc.clone();
-    let tid2 = trace_id.clone();
     let client_to_upstream = relay_websocket_direction(
         client_sink,
         hyper_stream,
         SendType::ClientToServer,
-        mc2,
-        tid2,
+        relay.mc.clone(),
+        relay.trace_id.clone(),
         "client-to-upstream",
+        DirectionControl {
+            frame_rules: &relay.frame_rules,
+            injected: &mut relay.injected.client_to_server,
+        },
     );
 
     let (_res_upstream, _res_client) = tokio::join!(upstream_to_client, client_to_upstream);
@@ -245,6 +344,12 @@ fn format_websocket_receive_error(error: &tungstenite::Error) -> String {
     }
 }
 
+/// Frame rules and injected frames for one relay direction.
+struct DirectionControl<'a> {
+    frame_rules: &'a WebSocketFrameRules,
+    injected: &'a mut UnboundedReceiver<tungstenite::Message>,
+}
+
 async fn relay_websocket_direction<Si, St>(
     mut sink: Si,
     mut stream: St,
@@ -252,12 +357,27 @@ async fn relay_websocket_direction<Si, St>(
     mc: Arc<MessageEventChannel>,
     trace_id: TraceId,
     direction: &'static str,
+    control: DirectionControl<'_>,
 ) -> Result<()>
 where
     Si: Sink<tungstenite::Message, Error = tungstenite::Error> + Unpin + Send,
     St: Stream<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin + Send,
 {
-    while let Some(message) = stream.next().await {
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            Some(injected) = control.injected.recv() => {
+                debug!(direction, "injecting websocket message");
+                sink.send(injected.clone()).await.map_err(|e| {
+                    anyhow!(e).context(format!("{direction}: failed to send injected message"))
+                })?;
+                mc.spawn_websocket_message_capture(trace_id.clone(), send_type.clone(), injected);
+                continue;
+            }
+        };
         let message = match message {
             Ok(message) => message,
             Err(error) => {
@@ -282,6 +402,19 @@ where
             );
         }
 
+        let message = match control.frame_rules.apply(&send_type, message) {
+            FrameVerdict::Forward { message, delay } => {
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                message
+            }
+            FrameVerdict::Drop => {
+                debug!(direction, "websocket message dropped by rule");
+                continue;
+            }
+        };
+
         sink.send(message.clone())
             .await
             .map_err(|e| anyhow!(e).context(format!("{direction}: failed to send message")))?;
@@ -355,6 +488,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn align_upstream_handshake_headers_keeps_preserved_host() {
+        let uri: Uri = "http://127.0.0.1:5173/socket".parse().unwrap();
+        let mut request = Request::builder()
+            .uri(uri.clone())
+            .header(HOST, "virtual.example.com")
+            .body(())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(PreservedHost(HeaderValue::from_static(
+                "virtual.example.com",
+            )));
+        let (mut parts, _) = request.into_parts();
+
+        align_upstream_handshake_headers(&mut parts, &uri).unwrap();
+
+        assert_eq!(parts.headers.get(HOST).unwrap(), "virtual.example.com");
+    }
+
     #[test]
     fn align_upstream_handshake_headers_preserves_subprotocol() {
         let uri: Uri = "http://127.0.0.1:5173/".parse().unwrap();
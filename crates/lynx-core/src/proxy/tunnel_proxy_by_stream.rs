@@ -2,14 +2,23 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
+use http::uri::Authority;
+use lynx_storage::DataStore;
 use socket2::{SockRef, TcpKeepalive};
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::{TcpStream, ToSocketAddrs},
+    net::TcpStream,
 };
 use tracing::{trace, warn};
 
-use crate::layers::{message_package_layer::MessageEventChannel, trace_id_layer::service::TraceId};
+use crate::{
+    client::RequestClient,
+    layers::{
+        message_package_layer::MessageEventChannel,
+        request_processing_layer::upstream_proxy_handler_trait::upstream_proxy_for_tunnel,
+        trace_id_layer::service::TraceId,
+    },
+};
 
 fn configure_tcp_keepalive(stream: &TcpStream) {
     let sock_ref = SockRef::from(stream);
@@ -21,17 +30,33 @@ fn configure_tcp_keepalive(stream: &TcpStream) {
     }
 }
 
-pub async fn tunnel_proxy_by_stream<
-    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
-    A: ToSocketAddrs,
->(
+/// Relays `stream` to `authority`, through the upstream of a matching
+/// `UpstreamProxy` rule when there is one.
+pub async fn tunnel_proxy_by_stream<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
     mut stream: S,
-    addr: A,
+    authority: &Authority,
+    store: &Arc<DataStore>,
+    request_client: &RequestClient,
     trace_id: TraceId,
     event_cannel: Arc<MessageEventChannel>,
 ) -> Result<()> {
-    let mut server = TcpStream::connect(addr).await?;
-    configure_tcp_keepalive(&server);
+    let upstream = upstream_proxy_for_tunnel(store, authority)
+        .await
+        .unwrap_or_default();
+    let host = authority
+        .host()
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = authority.port_u16().unwrap_or(443);
+    let mut server = request_client
+        .open_upstream_tunnel(&upstream, host, port)
+        .await?;
+    configure_tcp_keepalive(server.tcp());
+    if let Ok(addr) = server.tcp().peer_addr() {
+        event_cannel
+            .dispatch_on_server_connected(trace_id.clone(), addr)
+            .await;
+    }
 
     event_cannel
         .dispatch_on_tunnel_start(trace_id.clone())
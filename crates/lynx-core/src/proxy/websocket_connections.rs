@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{
+    error::{CoreError, CoreResult},
+    layers::trace_id_layer::service::TraceId,
+};
+
+/// A frame to inject into a live connection, as sent with `websocket.send`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketSend {
+    pub trace_id: String,
+    pub direction: WebSocketSendDirection,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub binary_base64: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WebSocketSendDirection {
+    /// Sent to the upstream server as if the client had sent it
+    ClientToServer,
+    /// Sent to the client as if the server had sent it
+    ServerToClient,
+}
+
+impl WebSocketSend {
+    fn message(&self) -> CoreResult<Message> {
+        match (&self.text, &self.binary_base64) {
+            (Some(text), None) => Ok(Message::text(text.clone())),
+            (None, Some(encoded)) => general_purpose::STANDARD
+                .decode(encoded)
+                .map(Message::binary)
+                .map_err(|e| CoreError::Validation {
+                    message: format!("invalid binaryBase64: {e}"),
+                }),
+            _ => Err(CoreError::Validation {
+                message: "exactly one of text and binaryBase64 is required".into(),
+            }),
+        }
+    }
+}
+
+struct LiveConnection {
+    client_to_server: UnboundedSender<Message>,
+    server_to_client: UnboundedSender<Message>,
+}
+
+/// Proxied WebSocket connections that are still open, by trace id, so the UI
+/// can inject frames into them.
+#[derive(Default)]
+pub struct WebSocketConnections {
+    live: Mutex<HashMap<String, LiveConnection>>,
+}
+
+/// Frames injected into one connection, per direction. Dropping it
+/// unregisters the connection.
+pub struct InjectedFrames {
+    connections: Arc<WebSocketConnections>,
+    trace_id: TraceId,
+    pub client_to_server: UnboundedReceiver<Message>,
+    pub server_to_client: UnboundedReceiver<Message>,
+}
+
+impl WebSocketConnections {
+    pub fn register(self: &Arc<Self>, trace_id: TraceId) -> InjectedFrames {
+        let (client_to_server, client_to_server_rx) = unbounded_channel();
+        let (server_to_client, server_to_client_rx) = unbounded_channel();
+        self.live.lock().expect("websocket lock poisoned").insert(
+            trace_id.to_string(),
+            LiveConnection {
+                client_to_server,
+                server_to_client,
+            },
+        );
+        InjectedFrames {
+            connections: self.clone(),
+            trace_id,
+            client_to_server: client_to_server_rx,
+            server_to_client: server_to_client_rx,
+        }
+    }
+
+    /// Queues a frame on the connection `send.trace_id`; it goes out between
+    /// the frames being relayed in that direction.
+    pub fn send(&self, send: WebSocketSend) -> CoreResult<()> {
+        let message = send.message()?;
+        let live = self.live.lock().expect("websocket lock poisoned");
+        let connection = live
+            .get(&send.trace_id)
+            .ok_or_else(|| CoreError::NotFound {
+                message: format!("no open WebSocket connection {:?}", send.trace_id),
+            })?;
+        let sender = match send.direction {
+            WebSocketSendDirection::ClientToServer => &connection.client_to_server,
+            WebSocketSendDirection::ServerToClient => &connection.server_to_client,
+        };
+        sender.send(message).map_err(|_| CoreError::NotFound {
+            message: format!("WebSocket connection {:?} is closing", send.trace_id),
+        })
+    }
+}
+
+impl Drop for InjectedFrames {
+    fn drop(&mut self) {
+        self.connections
+            .live
+            .lock()
+            .expect("websocket lock poisoned")
+            .remove(self.trace_id.as_str());
+    }
+}
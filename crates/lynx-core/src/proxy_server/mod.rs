@@ -15,6 +15,7 @@ use include_dir::Dir;
 use local_ip_address::list_afinet_netifas;
 use lynx_storage::DataStore;
 use lynx_storage::dao::client_proxy_dao::ClientProxyDao;
+use lynx_storage::dao::dns_dao::DnsDao;
 use rcgen::Certificate;
 use tokio::net::TcpListener;
 use tokio_rustls::TlsAcceptor;
@@ -22,7 +23,8 @@ use tower::util::Oneshot;
 use tower::{ServiceBuilder, service_fn};
 use tracing::{Instrument, debug, instrument, trace, trace_span, warn};
 
-use crate::client::request_client::RequestClientBuilder;
+use crate::client::dns::DnsResolverCache;
+use crate::client::request_client::{RequestClientBuilder, UpstreamClientCache};
 use crate::common::{HyperReq, is_https_tcp_stream};
 use crate::gateway_service::gateway_service_fn;
 use crate::layers::error_handle_layer::ErrorHandlerLayer;
@@ -30,7 +32,9 @@ use crate::layers::log_layer::LogLayer;
 use crate::layers::message_package_layer::message_event_store::MessageEventCache;
 use crate::layers::message_package_layer::{MessageEventChannel, RequestMessageEventService};
 use crate::layers::req_extension_layer::RequestExtensionLayer;
+use crate::layers::request_processing_layer::breakpoint_handler_trait::BreakpointRegistry;
 use crate::layers::trace_id_layer::service::{TraceIdExt, set_new_trace_id};
+use crate::proxy::websocket_connections::WebSocketConnections;
 use crate::self_service::AuthConfig;
 
 pub mod listen_info;
@@ -89,6 +93,14 @@ pub struct ProxyServer {
 
     #[builder(setter(skip))]
     pub message_event_cache: Arc<MessageEventCache>,
+
+    /// Exchanges held by `Breakpoint` handlers, resumed from the UI.
+    #[builder(setter(skip))]
+    pub breakpoints: Arc<BreakpointRegistry>,
+
+    /// Open proxied WebSocket connections, for frames injected from the UI.
+    #[builder(setter(skip))]
+    pub websocket_connections: Arc<WebSocketConnections>,
 }
 
 impl ProxyServerBuilder {
@@ -161,6 +173,8 @@ impl ProxyServerBuilder {
             auth_config,
             message_event_channel,
             message_event_cache,
+            breakpoints: Arc::new(BreakpointRegistry::default()),
+            websocket_connections: Arc::new(WebSocketConnections::default()),
         })
     }
 }
@@ -181,9 +195,14 @@ impl Default for ServerConfig {
 }
 
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct ClientAddr(SocketAddr);
 
+impl ClientAddr {
+    pub fn addr(&self) -> SocketAddr {
+        self.0
+    }
+}
+
 pub trait ClientAddrRequestExt {
     fn get_client_addr(&self) -> Option<ClientAddr>;
 }
@@ -237,6 +256,8 @@ impl ProxyServer {
         let server_config = self.config.clone();
         let message_event_store = self.message_event_cache.clone();
         let message_event_cannel = self.message_event_channel.clone();
+        let breakpoints = self.breakpoints.clone();
+        let websocket_connections = self.websocket_connections.clone();
         let static_dir = self.static_dir.clone();
         let auth_config = self.auth_config.clone();
         let addr_str = listener.local_addr()?.to_string();
@@ -251,6 +272,8 @@ impl ProxyServer {
             port: listen_port,
             local_only,
         });
+        let dns_resolvers = DnsResolverCache::default();
+        let upstream_clients = Arc::new(UpstreamClientCache::default());
 
         tokio::spawn(async move {
             loop {
@@ -285,11 +308,19 @@ impl ProxyServer {
                     client_proxy_config.api_debug.url.as_ref(),
                 );
 
+                let dns_config = DnsDao::new(data_store.clone())
+                    .get_dns_config()
+                    .await
+                    .unwrap_or_default();
+                let dns_resolver = dns_resolvers.resolver_for(dns_config);
+
                 let request_client = Arc::new(
                     RequestClientBuilder::default()
                         .custom_certs(client_custom_certs.clone())
                         .proxy_requests_config(proxy_requests_type)
                         .api_debug_proxy_config(api_debug_proxy_type)
+                        .dns_resolver(dns_resolver)
+                        .upstream_clients(upstream_clients.clone())
                         .build()
                         .expect("build request client error"),
                 );
@@ -297,6 +328,8 @@ impl ProxyServer {
                 let server_ca_manager = server_ca_manager.clone();
                 let server_config = server_config.clone();
                 let message_event_cannel = message_event_cannel.clone();
+                let breakpoints = breakpoints.clone();
+                let websocket_connections = websocket_connections.clone();
                 let data_store = data_store.clone();
                 let message_event_store = message_event_store.clone();
                 let access_addr_list = access_addr_list.clone();
@@ -313,6 +346,8 @@ impl ProxyServer {
                         .layer(RequestExtensionLayer::new(server_config))
                         .layer(RequestExtensionLayer::new(message_event_store))
                         .layer(RequestExtensionLayer::new(message_event_cannel))
+                        .layer(RequestExtensionLayer::new(breakpoints))
+                        .layer(RequestExtensionLayer::new(websocket_connections))
                         .layer(RequestExtensionLayer::new(access_addr_list))
                         .layer(RequestExtensionLayer::new(static_dir))
                         .layer(RequestExtensionLayer::new(auth_config))
@@ -24,6 +24,8 @@ pub mod op {
     pub const SETTINGS_GENERAL_SET: &str = "settings.general.set";
     pub const SETTINGS_CAPTURE_FILTER_GET: &str = "settings.captureFilter.get";
     pub const SETTINGS_CAPTURE_FILTER_SET: &str = "settings.captureFilter.set";
+    pub const SETTINGS_DNS_GET: &str = "settings.dns.get";
+    pub const SETTINGS_DNS_SET: &str = "settings.dns.set";
     pub const SETTINGS_CERTIFICATE_PATH_GET: &str = "settings.certificate.path.get";
     pub const RULES_LIST_GET: &str = "rules.list.get";
     pub const RULES_GET: &str = "rules.get";
@@ -31,6 +33,13 @@ pub mod op {
     pub const RULES_ENABLED_SET: &str = "rules.enabled.set";
     pub const RULES_DELETE: &str = "rules.delete";
     pub const RULES_TEMPLATES_GET: &str = "rules.templates.get";
+    pub const RULES_EXPLAIN: &str = "rules.explain";
+    pub const RULES_LINT: &str = "rules.lint";
+    pub const RULES_SEQUENCES_GET: &str = "rules.sequences.get";
+    pub const RULES_SEQUENCES_RESET: &str = "rules.sequences.reset";
+    pub const BREAKPOINT_LIST_GET: &str = "breakpoint.list.get";
+    pub const BREAKPOINT_RESUME: &str = "breakpoint.resume";
+    pub const WEBSOCKET_SEND: &str = "websocket.send";
     pub const PROJECTS_LIST_GET: &str = "projects.list.get";
     pub const PROJECTS_ACTIVE_SET: &str = "projects.active.set";
     pub const PROJECTS_CREATE: &str = "projects.create";
@@ -54,6 +63,9 @@ pub mod op {
     pub const NETWORK_TRAFFIC_FILTER_HISTORY_GET: &str = "network.trafficFilter.history.get";
     pub const NETWORK_TRAFFIC_FILTER_HISTORY_APPEND: &str = "network.trafficFilter.history.append";
     pub const NETWORK_TRAFFIC_FILTER_HISTORY_CLEAR: &str = "network.trafficFilter.history.clear";
+    pub const NETWORK_TRAFFIC_FILTER_SAVED_GET: &str = "network.trafficFilter.saved.get";
+    pub const NETWORK_TRAFFIC_FILTER_SAVED_SET: &str = "network.trafficFilter.saved.set";
+    pub const NETWORK_TRAFFIC_FILTER_SAVED_DELETE: &str = "network.trafficFilter.saved.delete";
     pub const CAPTURE_STATUS_CHANGED: &str = "capture.status.changed";
     pub const REQUEST_START: &str = "request.start";
     pub const REQUEST_BODY: &str = "request.body";
@@ -64,6 +76,10 @@ pub mod op {
     pub const WEBSOCKET_MESSAGE: &str = "websocket.message";
     pub const WEBSOCKET_ERROR: &str = "websocket.error";
     pub const WEBSOCKET_END: &str = "websocket.end";
+    pub const SCRIPT_LOG: &str = "script.log";
+    pub const SERVER_CONNECTED: &str = "server.connected";
+    pub const BREAKPOINT_HIT: &str = "breakpoint.hit";
+    pub const BREAKPOINT_RESOLVED: &str = "breakpoint.resolved";
     pub const SYSTEM_ERROR: &str = "system.error";
 
     pub fn is_request_op(op: &str) -> bool {
@@ -80,6 +96,8 @@ pub mod op {
                 | "settings.general.set"
                 | "settings.captureFilter.get"
                 | "settings.captureFilter.set"
+                | "settings.dns.get"
+                | "settings.dns.set"
                 | "settings.certificate.path.get"
                 | "rules.list.get"
                 | "rules.get"
@@ -87,6 +105,13 @@ pub mod op {
                 | "rules.enabled.set"
                 | "rules.delete"
                 | "rules.templates.get"
+                | "rules.explain"
+                | "rules.lint"
+                | "rules.sequences.get"
+                | "rules.sequences.reset"
+                | "breakpoint.list.get"
+                | "breakpoint.resume"
+                | "websocket.send"
                 | "projects.list.get"
                 | "projects.active.set"
                 | "projects.create"
@@ -110,6 +135,9 @@ pub mod op {
                 | "network.trafficFilter.history.get"
                 | "network.trafficFilter.history.append"
                 | "network.trafficFilter.history.clear"
+                | "network.trafficFilter.saved.get"
+                | "network.trafficFilter.saved.set"
+                | "network.trafficFilter.saved.delete"
         )
     }
 
@@ -126,6 +154,10 @@ pub mod op {
                 | "websocket.message"
                 | "websocket.error"
                 | "websocket.end"
+                | "script.log"
+                | "server.connected"
+                | "breakpoint.hit"
+                | "breakpoint.resolved"
                 | "system.error"
         )
     }
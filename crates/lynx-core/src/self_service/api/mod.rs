@@ -11,3 +11,4 @@ pub mod net_request_service;
 pub mod net_request_ws;
 pub mod projects_service;
 pub mod rules_service;
+pub mod traffic_stream_filter;
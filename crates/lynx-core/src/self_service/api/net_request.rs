@@ -1,9 +1,21 @@
 use axum::Router;
+use axum::{Json, extract::State, routing::get};
+use serde_json::{Value, json};
 
 use crate::self_service::RouteState;
 
 use super::net_request_ws;
 
+/// Distinct hosts of recent traffic, most recent first (used for editor
+/// completions).
+async fn get_recent_hosts(
+    State(RouteState {
+        net_request_cache, ..
+    }): State<RouteState>,
+) -> Json<Value> {
+    Json(json!({ "hosts": net_request_cache.recent_hosts() }))
+}
+
 pub fn router() -> Router<RouteState> {
-    net_request_ws::router()
+    net_request_ws::router().route("/hosts", get(get_recent_hosts))
 }
@@ -8,9 +8,12 @@ use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use base64::{Engine as _, engine::general_purpose};
 use futures_util::{SinkExt, StreamExt};
+use lynx_dsl::NamedMatchers;
+use lynx_storage::dao::dns_dao::{DnsConfig, DnsDao};
 use lynx_storage::dao::general_setting_dao::{GeneralSetting, GeneralSettingDao};
 use lynx_storage::dao::https_capture_dao::{CaptureFilter, HttpsCaptureDao};
 use lynx_storage::dao::net_request_dao::RecordingStatus;
+use lynx_storage::dao::projects_dao::ProjectsDao;
 use lynx_storage::dao::traffic_filter_history_dao::TrafficFilterHistoryDao;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
@@ -18,7 +21,10 @@ use tokio::sync::broadcast;
 use tracing::{debug, error, warn};
 
 use crate::adb::EnableProxyPayload;
+use crate::error::CoreError;
 use crate::layers::message_package_layer::message_event_store::MessageEvent;
+use crate::layers::request_processing_layer::breakpoint_handler_trait::BreakpointResume;
+use crate::proxy::websocket_connections::WebSocketSend;
 use crate::self_service::RouteState;
 use crate::self_service::api::adb_service;
 use crate::self_service::api::capture_rules_service;
@@ -27,6 +33,7 @@ use crate::self_service::api::generated::ws_v1::{WS_VERSION, frame_kind, op};
 use crate::self_service::api::net_request_service;
 use crate::self_service::api::projects_service;
 use crate::self_service::api::rules_service;
+use crate::self_service::api::traffic_stream_filter::TrafficStreamFilter;
 use crate::self_service::auth::{authorize_ws, unauthorized_response};
 use lynx_storage::dao::capture_rules_dao::CaptureRule;
 use lynx_storage::dao::request_processing_dao::RequestRule;
@@ -140,6 +147,13 @@ fn parse_i32_payload(payload: &Option<Value>, key: &str) -> Option<i32> {
         .and_then(|value| i32::try_from(value).ok())
 }
 
+/// Per-connection `request.stream.subscribe` state.
+#[derive(Default)]
+struct StreamSubscription {
+    subscribed: bool,
+    filter: Option<TrafficStreamFilter>,
+}
+
 fn parse_string_payload(payload: &Option<Value>, key: &str) -> Option<String> {
     payload
         .as_ref()
@@ -258,6 +272,32 @@ fn message_event_to_ws_event(event: MessageEvent) -> Option<WsFrame> {
                 "traceId": trace_id.to_string(),
             }),
         )),
+        MessageEvent::OnScriptLog(trace_id, log) => Some(event_frame(
+            op::SCRIPT_LOG.to_string(),
+            json!({
+                "traceId": trace_id.to_string(),
+                "log": log,
+            }),
+        )),
+        MessageEvent::OnServerConnected(trace_id, addr) => Some(event_frame(
+            op::SERVER_CONNECTED.to_string(),
+            json!({
+                "traceId": trace_id.to_string(),
+                "serverAddr": addr.to_string(),
+            }),
+        )),
+        MessageEvent::OnBreakpointHit(_, breakpoint) => Some(event_frame(
+            op::BREAKPOINT_HIT.to_string(),
+            serde_json::to_value(breakpoint).unwrap_or_default(),
+        )),
+        MessageEvent::OnBreakpointResolved(trace_id, resolution) => Some(event_frame(
+            op::BREAKPOINT_RESOLVED.to_string(),
+            json!({
+                "traceId": trace_id.to_string(),
+                "id": resolution.id,
+                "outcome": resolution.outcome,
+            }),
+        )),
         MessageEvent::OnError(trace_id, error_msg) => Some(event_frame(
             op::SYSTEM_ERROR.to_string(),
             json!({
@@ -268,10 +308,18 @@ fn message_event_to_ws_event(event: MessageEvent) -> Option<WsFrame> {
         MessageEvent::OnProxyStart(_)
         | MessageEvent::OnTunnelStart(_)
         | MessageEvent::OnTunnelEnd(_)
-        | MessageEvent::OnWebSocketStart(_) => None,
+        | MessageEvent::OnWebSocketStart(_)
+        | MessageEvent::OnMirrorResult(..) => None,
     }
 }
 
+fn is_breakpoint_event(event: &MessageEvent) -> bool {
+    matches!(
+        event,
+        MessageEvent::OnBreakpointHit(..) | MessageEvent::OnBreakpointResolved(..)
+    )
+}
+
 async fn send_frame(
     socket: &mut futures_util::stream::SplitSink<WebSocket, Message>,
     frame: WsFrame,
@@ -291,7 +339,7 @@ async fn send_frame(
 async fn handle_client_request(
     frame: WsFrame,
     state: &RouteState,
-    subscribed: &mut bool,
+    stream: &mut StreamSubscription,
     socket_tx: &mut futures_util::stream::SplitSink<WebSocket, Message>,
 ) {
     if frame.version != WS_VERSION {
@@ -535,9 +583,87 @@ async fn handle_client_request(
             }
         }
         op::REQUEST_STREAM_SUBSCRIBE => {
-            *subscribed = true;
+            let filter_expr = match parse_string_payload(&frame.payload, "filterName") {
+                Some(name) => {
+                    let dao = TrafficFilterHistoryDao::new(state.store.clone());
+                    match dao.get_saved(&name).await {
+                        Ok(Some(saved)) => Some(saved.expr),
+                        Ok(None) => {
+                            send_frame(
+                                socket_tx,
+                                error_frame(
+                                    frame.id,
+                                    frame.op,
+                                    "FILTER_NOT_FOUND",
+                                    "Saved traffic filter not found",
+                                    Some(json!({ "name": name })),
+                                ),
+                            )
+                            .await;
+                            return;
+                        }
+                        Err(err) => {
+                            send_frame(
+                                socket_tx,
+                                error_frame(
+                                    frame.id,
+                                    frame.op,
+                                    "DB_ERROR",
+                                    "Failed to get saved traffic filters",
+                                    Some(json!({ "reason": err.to_string() })),
+                                ),
+                            )
+                            .await;
+                            return;
+                        }
+                    }
+                }
+                None => parse_string_payload(&frame.payload, "filter"),
+            };
+            let filter_expr = filter_expr.filter(|expr| !expr.trim().is_empty());
+            // `@name` references resolve against the active project, like its rules.
+            let matchers = match filter_expr.as_ref() {
+                None => NamedMatchers::default(),
+                Some(_) => match ProjectsDao::new(state.store.clone()).get_projects().await {
+                    Ok(projects) => projects.matchers(&projects.active_project_id),
+                    Err(err) => {
+                        send_frame(
+                            socket_tx,
+                            error_frame(
+                                frame.id,
+                                frame.op,
+                                "DB_ERROR",
+                                "Failed to get projects",
+                                Some(json!({ "reason": err.to_string() })),
+                            ),
+                        )
+                        .await;
+                        return;
+                    }
+                },
+            };
+            let mut filter = match filter_expr {
+                Some(expr) => match TrafficStreamFilter::new(&expr, &matchers) {
+                    Ok(filter) => Some(filter),
+                    Err(err) => {
+                        send_frame(
+                            socket_tx,
+                            error_frame(
+                                frame.id,
+                                frame.op,
+                                "INVALID_FILTER",
+                                "Invalid traffic filter",
+                                Some(json!({ "reason": err.to_string() })),
+                            ),
+                        )
+                        .await;
+                        return;
+                    }
+                },
+                None => None,
+            };
 
-            let cached_requests =
+            let mut cached_requests =
                 match net_request_service::get_cached_requests(state, Vec::new()).await {
                     Ok(records) => records,
                     Err(err) => {
@@ -555,6 +681,12 @@ async fn handle_client_request(
                         return;
                     }
                 };
+            if let Some(filter) = filter.as_mut() {
+                cached_requests.retain(|value| filter.admit_cached(value));
+            }
+            let filter_expr = filter.as_ref().map(|filter| filter.expr().to_string());
+            stream.subscribed = true;
+            stream.filter = filter;
 
             send_frame(
                 socket_tx,
@@ -563,6 +695,7 @@ async fn handle_client_request(
                     frame.op,
                     json!({
                         "subscribed": true,
+                        "filter": filter_expr,
                         "cachedRequests": cached_requests,
                     }),
                 ),
@@ -570,7 +703,8 @@ async fn handle_client_request(
             .await;
         }
         op::REQUEST_STREAM_UNSUBSCRIBE => {
-            *subscribed = false;
+            stream.subscribed = false;
+            stream.filter = None;
             send_frame(
                 socket_tx,
                 response_frame(frame.id, frame.op, json!({ "subscribed": false })),
@@ -749,6 +883,95 @@ async fn handle_client_request(
                 }
             }
         }
+        op::SETTINGS_DNS_GET => {
+            let dao = DnsDao::new(state.store.clone());
+            match dao.get_dns_config().await {
+                Ok(config) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(
+                            frame.id,
+                            frame.op,
+                            serde_json::to_value(config).unwrap_or_default(),
+                        ),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "DB_ERROR",
+                            "Failed to get DNS settings",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+        op::SETTINGS_DNS_SET => {
+            let Some(payload) = frame.payload.clone() else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing DNS settings payload",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            let config = serde_json::from_value::<DnsConfig>(payload)
+                .map_err(|err| err.to_string())
+                .and_then(|config| config.validate().map(|()| config));
+            match config {
+                Ok(config) => {
+                    let dao = DnsDao::new(state.store.clone());
+                    match dao.update_dns_config(config).await {
+                        Ok(()) => {
+                            send_frame(
+                                socket_tx,
+                                response_frame(frame.id, frame.op, json!({ "ok": true })),
+                            )
+                            .await;
+                        }
+                        Err(err) => {
+                            send_frame(
+                                socket_tx,
+                                error_frame(
+                                    frame.id,
+                                    frame.op,
+                                    "DB_ERROR",
+                                    "Failed to update DNS settings",
+                                    Some(json!({ "reason": err.to_string() })),
+                                ),
+                            )
+                            .await;
+                        }
+                    }
+                }
+                Err(reason) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Invalid DNS settings",
+                            Some(json!({ "reason": reason })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
 
         op::RULES_LIST_GET => {
             let project_id = frame
@@ -1016,6 +1239,254 @@ async fn handle_client_request(
                 .await;
             }
         },
+        op::RULES_EXPLAIN => {
+            let Some(payload) = frame.payload.clone() else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing payload.url",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            match serde_json::from_value::<rules_service::ExplainRulesPayload>(payload) {
+                Ok(explain_payload) => {
+                    match rules_service::explain_rules(state, explain_payload).await {
+                        Ok(report) => {
+                            send_frame(
+                                socket_tx,
+                                response_frame(
+                                    frame.id,
+                                    frame.op,
+                                    serde_json::to_value(report).unwrap_or_default(),
+                                ),
+                            )
+                            .await;
+                        }
+                        Err(err) => {
+                            send_frame(
+                                socket_tx,
+                                error_frame(
+                                    frame.id,
+                                    frame.op,
+                                    "EXPLAIN_ERROR",
+                                    "Failed to explain rules",
+                                    Some(json!({ "reason": err.to_string() })),
+                                ),
+                            )
+                            .await;
+                        }
+                    }
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Failed to parse explain payload",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        op::RULES_LINT => {
+            let project_id = frame
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("projectId"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            match rules_service::lint_rules(state, project_id).await {
+                Ok(report) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(
+                            frame.id,
+                            frame.op,
+                            serde_json::to_value(report).unwrap_or_default(),
+                        ),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "LINT_ERROR",
+                            "Failed to lint rules",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        op::RULES_SEQUENCES_GET => {
+            let rule_id = parse_i32_payload(&frame.payload, "ruleId");
+            let counters = rules_service::list_sequence_counters(state, rule_id);
+            send_frame(
+                socket_tx,
+                response_frame(frame.id, frame.op, json!({ "counters": counters })),
+            )
+            .await;
+        }
+
+        op::RULES_SEQUENCES_RESET => {
+            // Only a missing `ruleId` resets every rule.
+            let rule_id = match frame.payload.as_ref().and_then(|value| value.get("ruleId")) {
+                None => None,
+                Some(value) => match value.as_i64().and_then(|id| i32::try_from(id).ok()) {
+                    Some(rule_id) => Some(rule_id),
+                    None => {
+                        send_frame(
+                            socket_tx,
+                            error_frame(
+                                frame.id,
+                                frame.op,
+                                "INVALID_PAYLOAD",
+                                "ruleId must be an integer rule id",
+                                Some(json!({ "ruleId": value })),
+                            ),
+                        )
+                        .await;
+                        return;
+                    }
+                },
+            };
+            let reset = rules_service::reset_sequence_counters(state, rule_id);
+            send_frame(
+                socket_tx,
+                response_frame(frame.id, frame.op, json!({ "reset": reset })),
+            )
+            .await;
+        }
+
+        op::BREAKPOINT_LIST_GET => {
+            let breakpoints = state.breakpoints.list();
+            send_frame(
+                socket_tx,
+                response_frame(frame.id, frame.op, json!({ "breakpoints": breakpoints })),
+            )
+            .await;
+        }
+
+        op::BREAKPOINT_RESUME => {
+            let Some(payload) = frame.payload.clone() else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing breakpoint resume payload",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            let resumed = serde_json::from_value::<BreakpointResume>(payload)
+                .map_err(|err| CoreError::Validation {
+                    message: err.to_string(),
+                })
+                .and_then(|resume| state.breakpoints.resume(resume));
+            match resumed {
+                Ok(()) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(frame.id, frame.op, json!({ "ok": true })),
+                    )
+                    .await;
+                }
+                Err(CoreError::NotFound { message }) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(frame.id, frame.op, "NOT_FOUND", &message, None),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Invalid breakpoint resume",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        op::WEBSOCKET_SEND => {
+            let Some(payload) = frame.payload.clone() else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing websocket send payload",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            let sent = serde_json::from_value::<WebSocketSend>(payload)
+                .map_err(|err| CoreError::Validation {
+                    message: err.to_string(),
+                })
+                .and_then(|send| state.websocket_connections.send(send));
+            match sent {
+                Ok(()) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(frame.id, frame.op, json!({ "ok": true })),
+                    )
+                    .await;
+                }
+                Err(CoreError::NotFound { message }) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(frame.id, frame.op, "NOT_FOUND", &message, None),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Invalid websocket send",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
 
         op::PROJECTS_LIST_GET => match projects_service::list_projects(state).await {
             Ok(file) => {
@@ -1934,6 +2405,137 @@ async fn handle_client_request(
                 }
             }
         }
+        op::NETWORK_TRAFFIC_FILTER_SAVED_GET => {
+            let dao = TrafficFilterHistoryDao::new(state.store.clone());
+            match dao.list_saved().await {
+                Ok(saved) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(
+                            frame.id,
+                            frame.op,
+                            serde_json::to_value(saved).unwrap_or_default(),
+                        ),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "DB_ERROR",
+                            "Failed to get saved traffic filters",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+        op::NETWORK_TRAFFIC_FILTER_SAVED_SET => {
+            let Some(name) = parse_string_payload(&frame.payload, "name") else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing payload.name",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+            let Some(expr) = parse_string_payload(&frame.payload, "expr") else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing payload.expr",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            let dao = TrafficFilterHistoryDao::new(state.store.clone());
+            match dao.save_filter(&name, &expr).await {
+                Ok(saved) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(
+                            frame.id,
+                            frame.op,
+                            serde_json::to_value(saved).unwrap_or_default(),
+                        ),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Failed to save traffic filter",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+        op::NETWORK_TRAFFIC_FILTER_SAVED_DELETE => {
+            let Some(name) = parse_string_payload(&frame.payload, "name") else {
+                send_frame(
+                    socket_tx,
+                    error_frame(
+                        frame.id,
+                        frame.op,
+                        "INVALID_PAYLOAD",
+                        "Missing payload.name",
+                        None,
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            let dao = TrafficFilterHistoryDao::new(state.store.clone());
+            match dao.delete_filter(&name).await {
+                Ok(saved) => {
+                    send_frame(
+                        socket_tx,
+                        response_frame(
+                            frame.id,
+                            frame.op,
+                            serde_json::to_value(saved).unwrap_or_default(),
+                        ),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    send_frame(
+                        socket_tx,
+                        error_frame(
+                            frame.id,
+                            frame.op,
+                            "INVALID_PAYLOAD",
+                            "Failed to delete traffic filter",
+                            Some(json!({ "reason": err.to_string() })),
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
         op::DEVICE_ADB_PROXY_DISABLE => {
             let Some(payload) = frame.payload.clone() else {
                 send_frame(
@@ -1990,7 +2592,7 @@ async fn handle_client_request(
 async fn message_events_ws_handler(socket: WebSocket, state: RouteState) {
     let (mut socket_tx, mut socket_rx) = socket.split();
     let mut event_rx = state.message_event_channel.subscribe();
-    let mut subscribed = false;
+    let mut stream = StreamSubscription::default();
 
     loop {
         tokio::select! {
@@ -1999,7 +2601,7 @@ async fn message_events_ws_handler(socket: WebSocket, state: RouteState) {
                     Some(Ok(Message::Text(text))) => {
                         match serde_json::from_str::<WsFrame>(&text) {
                             Ok(frame) => {
-                                handle_client_request(frame, &state, &mut subscribed, &mut socket_tx).await;
+                                handle_client_request(frame, &state, &mut stream, &mut socket_tx).await;
                             }
                             Err(err) => {
                                 debug!("invalid ws request frame: {:?}", err);
@@ -2031,15 +2633,33 @@ async fn message_events_ws_handler(socket: WebSocket, state: RouteState) {
                     None => break,
                 }
             }
-            event_result = event_rx.recv(), if subscribed => {
+            event_result = event_rx.recv() => {
                 match event_result {
                     Ok(event) => {
-                        if let Some(frame) = message_event_to_ws_event(event) {
-                            send_frame(&mut socket_tx, frame).await;
+                        // Breakpoints wait on whoever is connected, so they skip the
+                        // stream subscription and its filter.
+                        let events = if is_breakpoint_event(&event) {
+                            vec![event]
+                        } else if !stream.subscribed {
+                            continue;
+                        } else {
+                            match stream.filter.as_mut() {
+                                Some(filter) => filter.route(event),
+                                None => vec![event],
+                            }
+                        };
+                        for event in events {
+                            if let Some(frame) = message_event_to_ws_event(event) {
+                                send_frame(&mut socket_tx, frame).await;
+                            }
                         }
                     }
                     Err(broadcast::error::RecvError::Lagged(skipped)) => {
                         warn!("ws event subscriber lagged, skipped {} events", skipped);
+                        // Traces held back by the filter may have lost events for good.
+                        if let Some(filter) = stream.filter.as_mut() {
+                            filter.reset();
+                        }
                     }
                     Err(broadcast::error::RecvError::Closed) => {
                         error!("message event channel closed");
@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+
 use anyhow::{Result, anyhow};
+use lynx_storage::dao::projects_dao::ProjectsDao;
+use lynx_storage::dao::request_processing_dao::explain::probe_request;
 use lynx_storage::dao::request_processing_dao::{
     HandlerRule, RequestProcessingDao, RequestProcessingError, RequestRule, RuleValidator,
+    RulesExplainReport, RulesLintReport,
 };
+use lynx_storage::storage::SequenceCounter;
+use serde::Deserialize;
 
 use crate::self_service::RouteState;
 
@@ -32,7 +39,10 @@ pub async fn get_rule(state: &RouteState, rule_id: i32) -> Result<Option<Request
 }
 
 pub async fn save_rule(state: &RouteState, rule: RequestRule) -> Result<RequestRule> {
-    RuleValidator::validate_rule(&rule).map_err(map_validation)?;
+    let matchers = ProjectsDao::new(state.store.clone())
+        .project_matchers(&rule.project)
+        .await?;
+    RuleValidator::validate_rule_with_matchers(&rule, &matchers).map_err(map_validation)?;
     let dao = RequestProcessingDao::new(state.store.clone());
 
     if let Some(id) = rule.id {
@@ -72,3 +82,65 @@ pub async fn list_templates(state: &RouteState) -> Result<Vec<HandlerRule>> {
     let dao = RequestProcessingDao::new(state.store.clone());
     dao.get_template_handlers().await
 }
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainRulesPayload {
+    pub url: String,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Defaults to the active project.
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+pub async fn explain_rules(
+    state: &RouteState,
+    payload: ExplainRulesPayload,
+) -> Result<RulesExplainReport> {
+    let project = match payload.project_id {
+        Some(project) => project,
+        None => {
+            ProjectsDao::new(state.store.clone())
+                .active_project_id()
+                .await?
+        }
+    };
+    let headers: Vec<(String, String)> = payload.headers.into_iter().collect();
+    let request = probe_request(
+        payload.method.as_deref().unwrap_or("GET"),
+        &payload.url,
+        &headers,
+    )?;
+    RequestProcessingDao::new(state.store.clone())
+        .explain_request(&request, &project)
+        .await
+}
+
+/// Lint the rules of `project_id` (defaults to the active project).
+pub async fn lint_rules(state: &RouteState, project_id: Option<String>) -> Result<RulesLintReport> {
+    let project = match project_id {
+        Some(project) => project,
+        None => {
+            ProjectsDao::new(state.store.clone())
+                .active_project_id()
+                .await?
+        }
+    };
+    RequestProcessingDao::new(state.store.clone())
+        .lint_project(&project)
+        .await
+}
+
+/// Call counters of `Sequence` handlers, for `rule_id` or every rule.
+pub fn list_sequence_counters(state: &RouteState, rule_id: Option<i32>) -> Vec<SequenceCounter> {
+    RequestProcessingDao::new(state.store.clone()).list_sequence_counters(rule_id)
+}
+
+/// Restart the `Sequence` handlers of `rule_id`, or of every rule; returns how
+/// many counters were dropped.
+pub fn reset_sequence_counters(state: &RouteState, rule_id: Option<i32>) -> usize {
+    RequestProcessingDao::new(state.store.clone()).reset_sequence_counters(rule_id)
+}
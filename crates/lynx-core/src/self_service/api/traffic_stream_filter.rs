@@ -0,0 +1,457 @@
+//! Server-side DSL filter for `request.stream.subscribe`.
+//!
+//! Each ws connection owns one [`TrafficStreamFilter`]. A trace is decided on
+//! `request.start`; when the expression has header predicates and the request
+//! alone does not match, the trace is held back and re-evaluated with the
+//! response headers on `response.start`. Frames of a trace that matches late
+//! are flushed in their original order; once sent, a trace is never retracted.
+//!
+//! Held-back traces are bounded: a trace that buffers more than
+//! [`MAX_PENDING_EVENTS`] events before its response is dropped, and past
+//! [`MAX_TRACES`] tracked traces the oldest is forgotten.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use lynx_dsl::{
+    CompileError, MatchProgram, NamedMatchers, Predicate, RequestFacts,
+    compile_match_expr_with_matchers,
+};
+
+use crate::layers::message_package_layer::message_event_data::{
+    MessageEventRequest, MessageEventResponse,
+};
+use crate::layers::message_package_layer::message_event_store::{
+    MessageEvent, MessageEventStoreValue,
+};
+
+/// Events held for one undecided trace before it is dropped.
+const MAX_PENDING_EVENTS: usize = 256;
+/// Traces whose decision is remembered; the oldest is forgotten beyond this.
+const MAX_TRACES: usize = 4096;
+
+pub struct TrafficStreamFilter {
+    expr: String,
+    program: MatchProgram,
+    /// Header predicates may also be satisfied by response headers.
+    response_phase: bool,
+    traces: HashMap<String, TraceState>,
+    /// Trace ids in the order they were first seen; may hold ids already removed.
+    order: VecDeque<String>,
+}
+
+enum TraceState {
+    Matched,
+    Rejected,
+    /// Undecided until the response arrives; holds the request facts and the
+    /// events received so far.
+    Pending {
+        facts: Box<RequestFacts>,
+        events: Vec<MessageEvent>,
+    },
+}
+
+impl TrafficStreamFilter {
+    /// `matchers` resolves `@name` references, as in the active project's rules.
+    pub fn new(expr: &str, matchers: &NamedMatchers) -> Result<Self, CompileError> {
+        let expr = expr.trim();
+        let program = compile_match_expr_with_matchers(expr, matchers)?;
+        let response_phase = program
+            .predicates
+            .iter()
+            .any(|predicate| matches!(predicate, Predicate::HeaderEq { .. }));
+        Ok(Self {
+            expr: expr.to_string(),
+            program,
+            response_phase,
+            traces: HashMap::new(),
+            order: VecDeque::new(),
+        })
+    }
+
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Forget every trace, e.g. after the subscriber lagged and missed events.
+    pub fn reset(&mut self) {
+        self.traces.clear();
+        self.order.clear();
+    }
+
+    /// Start tracking `trace_id`, forgetting the oldest traces past [`MAX_TRACES`].
+    fn track(&mut self, trace_id: String, state: TraceState) {
+        if self.traces.insert(trace_id.clone(), state).is_none() {
+            self.order.push_back(trace_id);
+        }
+        while self.traces.len() > MAX_TRACES {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.traces.remove(&oldest);
+        }
+        // Ids of finished traces linger in `order`; drop them once they pile up.
+        if self.order.len() > 2 * MAX_TRACES {
+            let traces = &self.traces;
+            self.order.retain(|trace_id| traces.contains_key(trace_id));
+        }
+    }
+
+    /// Whether a cached entry matches, also recording the decision so later
+    /// events of in-flight traces are routed the same way.
+    pub fn admit_cached(&mut self, value: &MessageEventStoreValue) -> bool {
+        let Some(request) = &value.request else {
+            return false;
+        };
+        let mut facts = request_facts(request);
+        if let Some(response) = &value.response {
+            facts = with_response_headers(&facts, response);
+        }
+        let matched = lynx_dsl::eval_program(&self.program, &facts);
+        let state = if matched {
+            TraceState::Matched
+        } else if self.response_phase && value.response.is_none() {
+            TraceState::Pending {
+                facts: Box::new(request_facts(request)),
+                events: Vec::new(),
+            }
+        } else {
+            TraceState::Rejected
+        };
+        self.track(value.trace_id.clone(), state);
+        matched
+    }
+
+    /// Events to forward now for `event`: none, the event itself, or a flush of
+    /// a trace that matched on its response.
+    pub fn route(&mut self, event: MessageEvent) -> Vec<MessageEvent> {
+        let trace_id = event_trace_id(&event).to_string();
+
+        if let MessageEvent::OnRequestStart(_, request) = &event {
+            let facts = request_facts(request);
+            if lynx_dsl::eval_program(&self.program, &facts) {
+                self.track(trace_id, TraceState::Matched);
+                return vec![event];
+            }
+            let state = if self.response_phase {
+                TraceState::Pending {
+                    facts: Box::new(facts),
+                    events: vec![event],
+                }
+            } else {
+                TraceState::Rejected
+            };
+            self.track(trace_id, state);
+            return Vec::new();
+        }
+
+        let terminal = is_terminal(&event);
+        let (forwarded, next) = match self.traces.remove(&trace_id) {
+            None => (Vec::new(), None),
+            Some(TraceState::Rejected) => (Vec::new(), Some(TraceState::Rejected)),
+            Some(TraceState::Matched) => (vec![event], Some(TraceState::Matched)),
+            Some(TraceState::Pending { facts, mut events }) => {
+                let matched = match &event {
+                    MessageEvent::OnResponseStart(_, response) => Some(lynx_dsl::eval_program(
+                        &self.program,
+                        &with_response_headers(&facts, response),
+                    )),
+                    _ => None,
+                };
+                match matched {
+                    Some(true) => {
+                        events.push(event);
+                        (events, Some(TraceState::Matched))
+                    }
+                    Some(false) => (Vec::new(), Some(TraceState::Rejected)),
+                    // Too much to hold until the response decides; give up on the trace.
+                    None if events.len() >= MAX_PENDING_EVENTS => {
+                        (Vec::new(), Some(TraceState::Rejected))
+                    }
+                    None => {
+                        events.push(event);
+                        (Vec::new(), Some(TraceState::Pending { facts, events }))
+                    }
+                }
+            }
+        };
+        // Still listed in `order`, so this re-insert does not count as new.
+        if let Some(next) = next
+            && !terminal
+        {
+            self.traces.insert(trace_id, next);
+        }
+        forwarded
+    }
+}
+
+fn is_terminal(event: &MessageEvent) -> bool {
+    matches!(
+        event,
+        MessageEvent::OnProxyEnd(_)
+            | MessageEvent::OnWebSocketEnd(_)
+            | MessageEvent::OnTunnelEnd(_)
+            | MessageEvent::OnError(..)
+    )
+}
+
+fn event_trace_id(event: &MessageEvent) -> &str {
+    match event {
+        MessageEvent::OnRequestStart(id, _)
+        | MessageEvent::OnRequestBody(id, _)
+        | MessageEvent::OnRequestEnd(id)
+        | MessageEvent::OnResponseBody(id, _)
+        | MessageEvent::OnProxyStart(id)
+        | MessageEvent::OnProxyEnd(id)
+        | MessageEvent::OnResponseStart(id, _)
+        | MessageEvent::OnWebSocketStart(id)
+        | MessageEvent::OnWebSocketEnd(id)
+        | MessageEvent::OnWebSocketError(id, _)
+        | MessageEvent::OnWebSocketMessage(id, _)
+        | MessageEvent::OnTunnelStart(id)
+        | MessageEvent::OnTunnelEnd(id)
+        | MessageEvent::OnScriptLog(id, _)
+        | MessageEvent::OnServerConnected(id, _)
+        | MessageEvent::OnMirrorResult(id, _)
+        | MessageEvent::OnBreakpointHit(id, _)
+        | MessageEvent::OnBreakpointResolved(id, _)
+        | MessageEvent::OnError(id, _) => id.as_str(),
+    }
+}
+
+fn request_facts(request: &MessageEventRequest) -> RequestFacts {
+    let uri: http::Uri = request.url.parse().unwrap_or_default();
+    let header = |name: &str| {
+        request
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    };
+
+    let mut builder = RequestFacts::builder()
+        .method(request.method.clone())
+        .path(uri.path().to_string());
+    if let Some(scheme) = uri.scheme_str() {
+        builder = builder.scheme(scheme);
+    }
+    match uri.host() {
+        Some(host) => {
+            builder = builder.host(host);
+            if let Some(port) = uri.port_u16() {
+                builder = builder.port(port);
+            }
+        }
+        None => {
+            if let Some(authority) =
+                header("host").and_then(|host| host.parse::<http::uri::Authority>().ok())
+            {
+                builder = builder.host(authority.host());
+                if let Some(port) = authority.port_u16() {
+                    builder = builder.port(port);
+                }
+            }
+        }
+    }
+    if let Some(query) = uri.query() {
+        builder = builder.query(query);
+    }
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder.build()
+}
+
+/// Request facts plus response headers; a response header replaces a request
+/// header of the same name.
+fn with_response_headers(facts: &RequestFacts, response: &MessageEventResponse) -> RequestFacts {
+    let mut headers: BTreeMap<String, String> = facts.headers.iter().cloned().collect();
+    for (name, value) in &response.headers {
+        headers.insert(name.to_ascii_lowercase(), value.clone());
+    }
+    RequestFacts {
+        headers: headers.into_iter().collect(),
+        ..facts.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn request(url: &str, headers: &[(&str, &str)]) -> MessageEventRequest {
+        MessageEventRequest {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn response(headers: &[(&str, &str)]) -> MessageEventResponse {
+        MessageEventResponse {
+            status: 200,
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+            ..Default::default()
+        }
+    }
+
+    fn kinds(events: &[MessageEvent]) -> Vec<&'static str> {
+        events
+            .iter()
+            .map(|event| match event {
+                MessageEvent::OnRequestStart(..) => "requestStart",
+                MessageEvent::OnRequestEnd(..) => "requestEnd",
+                MessageEvent::OnResponseStart(..) => "responseStart",
+                MessageEvent::OnProxyEnd(..) => "proxyEnd",
+                _ => "other",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drops_traces_that_do_not_match_the_request() {
+        let mut filter =
+            TrafficStreamFilter::new("example.com AND -X GET", &NamedMatchers::default()).unwrap();
+        let hit = Arc::new("hit".to_string());
+        let miss = Arc::new("miss".to_string());
+
+        let sent = filter.route(MessageEvent::OnRequestStart(
+            hit.clone(),
+            request("https://api.example.com/v1", &[]),
+        ));
+        assert_eq!(kinds(&sent), vec!["requestStart"]);
+        assert!(
+            filter
+                .route(MessageEvent::OnRequestStart(
+                    miss.clone(),
+                    request("https://other.org/", &[]),
+                ))
+                .is_empty()
+        );
+        assert!(filter.route(MessageEvent::OnProxyEnd(miss)).is_empty());
+        assert_eq!(
+            kinds(&filter.route(MessageEvent::OnProxyEnd(hit))),
+            vec!["proxyEnd"]
+        );
+        assert!(filter.traces.is_empty());
+    }
+
+    #[test]
+    fn header_filters_are_re_evaluated_on_the_response() {
+        let mut filter = TrafficStreamFilter::new(
+            "-H content-type=application/json",
+            &NamedMatchers::default(),
+        )
+        .unwrap();
+        let json = Arc::new("json".to_string());
+        let html = Arc::new("html".to_string());
+
+        for trace in [&json, &html] {
+            assert!(
+                filter
+                    .route(MessageEvent::OnRequestStart(
+                        trace.clone(),
+                        request("https://example.com/", &[]),
+                    ))
+                    .is_empty()
+            );
+            assert!(
+                filter
+                    .route(MessageEvent::OnRequestEnd(trace.clone()))
+                    .is_empty()
+            );
+        }
+
+        let flushed = filter.route(MessageEvent::OnResponseStart(
+            json.clone(),
+            response(&[("Content-Type", "application/json")]),
+        ));
+        assert_eq!(
+            kinds(&flushed),
+            vec!["requestStart", "requestEnd", "responseStart"]
+        );
+        assert!(
+            filter
+                .route(MessageEvent::OnResponseStart(
+                    html.clone(),
+                    response(&[("Content-Type", "text/html")]),
+                ))
+                .is_empty()
+        );
+        assert!(filter.route(MessageEvent::OnProxyEnd(html)).is_empty());
+    }
+
+    #[test]
+    fn resolves_named_matchers() {
+        let matchers: NamedMatchers = [("staging", "api.staging.example.com")]
+            .into_iter()
+            .collect();
+        assert!(TrafficStreamFilter::new("@staging", &NamedMatchers::default()).is_err());
+        let mut filter = TrafficStreamFilter::new("@staging", &matchers).unwrap();
+        let sent = filter.route(MessageEvent::OnRequestStart(
+            Arc::new("hit".to_string()),
+            request("https://api.staging.example.com/", &[]),
+        ));
+        assert_eq!(kinds(&sent), vec!["requestStart"]);
+    }
+
+    #[test]
+    fn bounds_held_back_events_and_tracked_traces() {
+        let mut filter = TrafficStreamFilter::new(
+            "-H content-type=application/json",
+            &NamedMatchers::default(),
+        )
+        .unwrap();
+        let upload = Arc::new("upload".to_string());
+        filter.route(MessageEvent::OnRequestStart(
+            upload.clone(),
+            request("https://example.com/", &[]),
+        ));
+        for _ in 0..MAX_PENDING_EVENTS {
+            filter.route(MessageEvent::OnRequestBody(upload.clone(), None));
+        }
+        // Given up on before the response could match it.
+        assert!(matches!(
+            filter.traces.get(upload.as_str()),
+            Some(TraceState::Rejected)
+        ));
+        assert!(
+            filter
+                .route(MessageEvent::OnResponseStart(
+                    upload,
+                    response(&[("Content-Type", "application/json")]),
+                ))
+                .is_empty()
+        );
+
+        for index in 0..MAX_TRACES + 10 {
+            filter.route(MessageEvent::OnRequestStart(
+                Arc::new(format!("trace-{index}")),
+                request("https://example.com/", &[]),
+            ));
+        }
+        assert_eq!(filter.traces.len(), MAX_TRACES);
+        assert!(!filter.traces.contains_key("trace-0"));
+        assert!(filter.order.len() <= 2 * MAX_TRACES);
+
+        filter.reset();
+        assert!(filter.traces.is_empty() && filter.order.is_empty());
+    }
+
+    #[test]
+    fn host_falls_back_to_the_host_header() {
+        let facts = request_facts(&request("/api?a=1", &[("Host", "example.com:8080")]));
+        assert_eq!(facts.host, "example.com");
+        assert_eq!(facts.port, Some(8080));
+        assert_eq!(facts.query.as_deref(), Some("a=1"));
+    }
+}
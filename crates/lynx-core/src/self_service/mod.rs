@@ -10,6 +10,8 @@ use crate::layers::message_package_layer::MessageEventChannel;
 use crate::layers::message_package_layer::MessageEventLayerExt;
 use crate::layers::message_package_layer::message_event_store::MessageEventCache;
 use crate::layers::message_package_layer::message_event_store::MessageEventStoreExtensionsExt;
+use crate::layers::request_processing_layer::breakpoint_handler_trait::BreakpointRegistry;
+use crate::proxy::websocket_connections::WebSocketConnections;
 use crate::proxy_server::StaticDir;
 use crate::proxy_server::listen_info::ProxyListenInfoExtensionsExt;
 use crate::proxy_server::server_config::ProxyServerConfig;
@@ -116,6 +118,8 @@ pub struct RouteState {
     pub static_dir: Option<Arc<StaticDir>>,
     pub client: Arc<ReqwestClient>,
     pub message_event_channel: Arc<MessageEventChannel>,
+    pub breakpoints: Arc<BreakpointRegistry>,
+    pub websocket_connections: Arc<WebSocketConnections>,
     pub auth: Arc<AuthConfig>,
     pub adb: Arc<AdbManager>,
 }
@@ -140,6 +144,16 @@ pub async fn self_service_router(req: Req) -> Result<Response> {
         static_dir: static_dir.cloned().flatten(),
         client: req.extensions().get_reqwest_client(),
         message_event_channel: req.extensions().get_message_event_cannel(),
+        breakpoints: req
+            .extensions()
+            .get::<Arc<BreakpointRegistry>>()
+            .cloned()
+            .unwrap_or_default(),
+        websocket_connections: req
+            .extensions()
+            .get::<Arc<WebSocketConnections>>()
+            .cloned()
+            .unwrap_or_default(),
         auth: auth.clone(),
         adb,
     };
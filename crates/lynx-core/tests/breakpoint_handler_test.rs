@@ -0,0 +1,184 @@
+use anyhow::Result;
+use http::StatusCode;
+use lynx_core::{
+    layers::{
+        message_package_layer::{
+            message_event_data::{
+                BreakpointHeader, BreakpointOutcome, BreakpointStage, HeldBreakpoint,
+            },
+            message_event_store::MessageEvent,
+        },
+        request_processing_layer::breakpoint_handler_trait::BreakpointResume,
+    },
+    proxy_server::ProxyServer,
+};
+use lynx_storage::dao::request_processing_dao::{
+    HandlerRule,
+    handlers::{BreakpointConfig, BreakpointPhase},
+};
+use setup::{
+    mock_base_url, mock_rule::mock_test_rule,
+    setup_proxy_handler_server::setup_proxy_handler_server,
+};
+use tokio::{
+    sync::broadcast::Receiver,
+    time::{Duration, timeout},
+};
+
+mod setup;
+
+async fn next_hit(events: &mut Receiver<MessageEvent>) -> Result<HeldBreakpoint> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if let MessageEvent::OnBreakpointHit(_, held) = events.recv().await? {
+                return Ok(held);
+            }
+        }
+    })
+    .await?
+}
+
+async fn next_outcome(events: &mut Receiver<MessageEvent>) -> Result<BreakpointOutcome> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if let MessageEvent::OnBreakpointResolved(_, resolution) = events.recv().await? {
+                return Ok(resolution.outcome);
+            }
+        }
+    })
+    .await?
+}
+
+async fn breakpoint_rule(proxy_server: &ProxyServer, config: BreakpointConfig) -> Result<()> {
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::breakpoint_handler(config)],
+    )
+    .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn breakpoint_handler_resumes_edited_request() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+    breakpoint_rule(&proxy_server, BreakpointConfig::default()).await?;
+
+    let base_url = mock_base_url(&mock_server);
+    let pending = tokio::spawn(client.get(format!("{base_url}/hello")).send());
+
+    let held = next_hit(&mut events).await?;
+    assert_eq!(held.stage, BreakpointStage::Request);
+    assert_eq!(held.method, "GET");
+    assert!(held.url.ends_with("/hello"));
+    let listed = proxy_server.breakpoints.list();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, held.id);
+
+    let mut headers = held.headers.clone();
+    headers.push(BreakpointHeader {
+        name: "content-type".into(),
+        value: "application/json".into(),
+    });
+    proxy_server.breakpoints.resume(BreakpointResume {
+        id: held.id,
+        method: Some("POST".into()),
+        url: Some("/post_echo".into()),
+        headers: Some(headers),
+        body: Some(r#"{"edited":true}"#.into()),
+        ..Default::default()
+    })?;
+
+    let response = pending.await??;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, r#"{"edited":true}"#);
+    assert_eq!(next_outcome(&mut events).await?, BreakpointOutcome::Edited);
+    assert!(proxy_server.breakpoints.list().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn breakpoint_handler_edits_held_response() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+    breakpoint_rule(
+        &proxy_server,
+        BreakpointConfig {
+            phase: BreakpointPhase::Response,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let base_url = mock_base_url(&mock_server);
+    let pending = tokio::spawn(client.get(format!("{base_url}/hello")).send());
+
+    let held = next_hit(&mut events).await?;
+    assert_eq!(held.stage, BreakpointStage::Response);
+    assert_eq!(held.status, Some(200));
+    assert_eq!(held.body.as_deref(), Some("Hello, World!"));
+
+    let invalid = proxy_server.breakpoints.resume(BreakpointResume {
+        id: held.id.clone(),
+        method: Some("POST".into()),
+        ..Default::default()
+    });
+    assert!(invalid.is_err(), "method edit on a response accepted");
+
+    proxy_server.breakpoints.resume(BreakpointResume {
+        id: held.id,
+        status: Some(418),
+        body: Some("held".into()),
+        ..Default::default()
+    })?;
+
+    let response = pending.await??;
+    assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+    assert_eq!(response.text().await?, "held");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn breakpoint_handler_aborts_and_times_out() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+    breakpoint_rule(
+        &proxy_server,
+        BreakpointConfig {
+            timeout_ms: Some(200),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let base_url = mock_base_url(&mock_server);
+
+    // Nobody resumes it: the request continues unchanged.
+    let response = client.get(format!("{base_url}/hello")).send().await?;
+    assert_eq!(response.text().await?, "Hello, World!");
+    next_hit(&mut events).await?;
+    assert_eq!(
+        next_outcome(&mut events).await?,
+        BreakpointOutcome::TimedOut
+    );
+
+    let pending = tokio::spawn(client.get(format!("{base_url}/hello")).send());
+    let held = next_hit(&mut events).await?;
+    proxy_server.breakpoints.resume(BreakpointResume {
+        id: held.id,
+        abort: true,
+        ..Default::default()
+    })?;
+    let aborted = pending.await?;
+    assert!(
+        aborted.is_err(),
+        "expected a dropped connection: {aborted:?}"
+    );
+    assert_eq!(next_outcome(&mut events).await?, BreakpointOutcome::Aborted);
+
+    Ok(())
+}
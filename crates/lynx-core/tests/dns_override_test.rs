@@ -0,0 +1,117 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use http::StatusCode;
+use lynx_core::layers::message_package_layer::message_event_store::MessageEvent;
+use lynx_storage::dao::{
+    dns_dao::{DnsConfig, DnsDao, DnsOverride},
+    https_capture_dao::{CaptureFilter, HttpsCaptureDao},
+};
+use serde_json::Value;
+use setup::setup_proxy_handler_server::setup_proxy_handler_server;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::broadcast::Receiver,
+    time::{Duration, timeout},
+};
+use tokio_rustls::{TlsConnector, rustls::pki_types::ServerName};
+
+mod setup;
+
+fn override_config(host: &str) -> DnsConfig {
+    DnsConfig {
+        overrides: vec![DnsOverride {
+            host: host.to_string(),
+            ips: vec!["127.0.0.1".parse().unwrap()],
+            enabled: true,
+        }],
+        ..Default::default()
+    }
+}
+
+async fn next_server_addr(events: &mut Receiver<MessageEvent>) -> Result<SocketAddr> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if let MessageEvent::OnServerConnected(_, addr) = events.recv().await? {
+                return Ok(addr);
+            }
+        }
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn dns_override_keeps_host_header_and_records_server_addr() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+
+    DnsDao::new(proxy_server.data_store.clone())
+        .update_dns_config(override_config("*.lynx.test"))
+        .await?;
+
+    let host = format!("api.lynx.test:{}", mock_server.addr.port());
+    let response = client
+        .get(format!("http://{host}/headers"))
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await?;
+    assert_eq!(body["headers"]["host"], host);
+    assert_eq!(next_server_addr(&mut events).await?, mock_server.addr);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dns_override_applies_to_undecrypted_tunnels() -> Result<()> {
+    let (proxy_server, mock_server, _client) = setup_proxy_handler_server().await?;
+    let mut events = proxy_server.message_event_channel.subscribe();
+
+    HttpsCaptureDao::new(proxy_server.data_store.clone())
+        .update_capture_filter(CaptureFilter {
+            enabled: false,
+            include_domains: vec![],
+            exclude_domains: vec![],
+        })
+        .await?;
+    DnsDao::new(proxy_server.data_store.clone())
+        .update_dns_config(override_config("tunnel.lynx.test"))
+        .await?;
+
+    let target = format!("tunnel.lynx.test:{}", mock_server.addr.port());
+    let mut stream = TcpStream::connect(proxy_server.access_addr_list[0]).await?;
+    stream
+        .write_all(format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n\r\n").as_bytes())
+        .await?;
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await?);
+    }
+    assert!(String::from_utf8_lossy(&head).starts_with("HTTP/1.1 200"));
+
+    // The mock certificate only names localhost, so the tunnel is opened
+    // with an unresolvable name and TLS uses the certificate's.
+    let mut tls_config =
+        lynx_cert::gen_client_config_by_cert(Some(Arc::new(vec![mock_server.cert.clone()])))?;
+    tls_config.alpn_protocols.clear();
+    let mut stream = TlsConnector::from(Arc::new(tls_config))
+        .connect(ServerName::try_from("localhost")?, stream)
+        .await?;
+    stream
+        .write_all(
+            format!("GET /hello HTTP/1.1\r\nHost: {target}\r\nConnection: close\r\n\r\n")
+                .as_bytes(),
+        )
+        .await?;
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.ends_with("Hello, World!"), "{response}");
+    assert_eq!(next_server_addr(&mut events).await?, mock_server.addr);
+
+    Ok(())
+}
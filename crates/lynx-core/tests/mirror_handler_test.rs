@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{Json, Router, http::StatusCode as AxumStatus, routing::post};
+use http::StatusCode;
+use lynx_core::layers::message_package_layer::{
+    message_event_data::MirrorResult, message_event_store::MessageEvent,
+};
+use lynx_storage::dao::request_processing_dao::{HandlerRule, handlers::MirrorConfig};
+use serde_json::{Value, json};
+use setup::{
+    mock_base_url, mock_rule::mock_test_rule,
+    setup_proxy_handler_server::setup_proxy_handler_server,
+};
+use tokio::{
+    net::TcpListener,
+    sync::broadcast::Receiver,
+    time::{Duration, timeout},
+};
+
+mod setup;
+
+/// A mirror target that answers the echoed body with `name` changed.
+async fn spawn_mirror_target() -> Result<String> {
+    let app = Router::new().route(
+        "/post_echo",
+        post(|Json(mut body): Json<Value>| async move {
+            body["name"] = json!("mirror");
+            (AxumStatus::CREATED, [("x-mirror", "yes")], Json(body))
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok(format!("http://{addr}"))
+}
+
+async fn next_mirror_result(events: &mut Receiver<MessageEvent>) -> Result<(String, MirrorResult)> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if let MessageEvent::OnMirrorResult(trace_id, result) = events.recv().await? {
+                return Ok((trace_id.to_string(), result));
+            }
+        }
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn mirror_handler_diffs_mirrored_response() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+    let target = spawn_mirror_target().await?;
+
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::mirror_handler(MirrorConfig {
+            targets: vec![target.clone()],
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let response = client
+        .post(format!("{}/post_echo", mock_base_url(&mock_server)))
+        .json(&json!({ "id": 1, "name": "primary" }))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>().await?,
+        json!({ "id": 1, "name": "primary" })
+    );
+
+    let (trace_id, result) = next_mirror_result(&mut events).await?;
+    assert_eq!(result.target, format!("{target}/post_echo"));
+    assert_eq!(result.status, Some(201));
+    assert!(result.trace_id.is_some());
+    let diff = result.diff.expect("mirror diff");
+    let status = diff.status.expect("status change");
+    assert_eq!((status.primary, status.mirror), (200, 201));
+    assert!(
+        diff.headers
+            .iter()
+            .any(|h| h.name == "x-mirror" && h.primary.is_none())
+    );
+    assert!(diff.body_changed);
+    let body: Vec<_> = diff
+        .body
+        .iter()
+        .map(|c| (c.path.as_str(), c.primary.clone(), c.mirror.clone()))
+        .collect();
+    assert_eq!(
+        body,
+        vec![("/name", Some(json!("primary")), Some(json!("mirror")))]
+    );
+
+    // The result is part of the primary's detail, as `request.detail.get` returns it.
+    let detail = timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(value) = proxy_server
+                .message_event_cache
+                .get(&Arc::new(trace_id.clone()))
+                .filter(|value| !value.mirrors.is_empty())
+            {
+                return serde_json::to_value(value);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await??;
+    assert_eq!(detail["mirrors"][0]["diff"]["status"]["mirror"], 201);
+    assert_eq!(detail["mirrors"][0]["diff"]["body"][0]["path"], "/name");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn mirror_handler_reports_unreachable_targets() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let mut events = proxy_server.message_event_channel.subscribe();
+    let closed = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::mirror_handler(MirrorConfig {
+            targets: vec![format!("http://{closed}")],
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let response = client
+        .get(format!("{}/hello", mock_base_url(&mock_server)))
+        .send()
+        .await?;
+    assert_eq!(response.text().await?, "Hello, World!");
+
+    let (_, result) = next_mirror_result(&mut events).await?;
+    assert_eq!(result.status, None);
+    assert!(result.error.is_some());
+    assert!(result.diff.is_none());
+
+    Ok(())
+}
@@ -2,9 +2,13 @@ use anyhow::Result;
 use futures_util::{SinkExt, TryStreamExt};
 use http::StatusCode;
 use lynx_storage::dao::request_processing_dao::HandlerRule;
+use lynx_storage::dao::request_processing_dao::handlers::{
+    ProxyForwardConfig, handler_rule::HandlerRuleType,
+};
 use reqwest_websocket::Message;
 use setup::{
-    mock_base_url, mock_rule::mock_test_rule,
+    mock_base_url,
+    mock_rule::{mock_test_rule, mock_test_rule_with_match},
     setup_proxy_handler_server::setup_proxy_handler_server,
 };
 
@@ -262,3 +266,30 @@ async fn proxy_forward_handler_unreachable_upstream_test() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn proxy_forward_handler_maps_glob_tail_and_preserves_host() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+
+    let mut handler = HandlerRule::proxy_forward_handler(None, None, None);
+    handler.handler_type = HandlerRuleType::ProxyForward(ProxyForwardConfig {
+        target_authority: Some(mock_server.addr.to_string()),
+        target_path: Some("/{{params.[0]}}".to_string()),
+        preserve_host: true,
+        ..Default::default()
+    });
+    mock_test_rule_with_match(proxy_server.data_store, "/api/v1/**", vec![handler]).await?;
+
+    let response = client
+        .get("http://not_exist.com/api/v1/headers".to_string())
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(body["headers"]["host"], "not_exist.com");
+
+    Ok(())
+}
@@ -1,8 +1,15 @@
 use anyhow::Result;
 use http::StatusCode;
-use lynx_storage::dao::request_processing_dao::{HandlerRule, handlers::ThrottlePreset};
+use lynx_storage::dao::request_processing_dao::{
+    HandlerRule, RequestProcessingDao,
+    handlers::{
+        CorsConfig, Fault, LocalFileMode, ReplayFallback, ResetPhase, StreamMockChunk,
+        StreamMockConfig, StreamMockFormat, ThrottlePreset, handler_rule::HandlerRuleType,
+    },
+};
 use setup::{
-    mock_base_url, mock_rule::mock_test_rule,
+    mock_base_url,
+    mock_rule::{mock_test_rule, mock_test_rule_with_match},
     setup_proxy_handler_server::setup_proxy_handler_server,
 };
 use std::{collections::HashMap, path::PathBuf};
@@ -142,6 +149,306 @@ async fn local_file_handler_custom_status_code() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn local_file_handler_serves_directory_under_glob_tail() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+
+    let dist = tempfile::tempdir()?;
+    std::fs::create_dir_all(dist.path().join("assets"))?;
+    std::fs::write(dist.path().join("index.html"), "<main>spa</main>")?;
+    std::fs::write(dist.path().join("assets/app.js"), "console.log('app')")?;
+
+    let mut handler =
+        HandlerRule::local_file_handler(dist.path().to_string_lossy().to_string(), None, None);
+    if let HandlerRuleType::LocalFile(config) = &mut handler.handler_type {
+        config.mode = LocalFileMode::Directory;
+        config.spa_fallback = true;
+    }
+    mock_test_rule_with_match(proxy_server.data_store, "/app/**", vec![handler]).await?;
+
+    let response = client
+        .get(format!("{base_url}/app/assets/app.js"))
+        .header("range", "bytes=0-6")
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()["content-range"], "bytes 0-6/18");
+    assert_eq!(response.text().await?, "console");
+
+    let response = client
+        .get(format!("{base_url}/app/settings/profile"))
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "<main>spa</main>");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_response_handler_renders_path_params() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+
+    let mut handler = HandlerRule::mock_response_handler(
+        Some(201),
+        Some(r#"{"id":"{{params.[0]}}","tab":"{{req.query.tab}}"}"#.to_string()),
+    );
+    if let HandlerRuleType::MockResponse(config) = &mut handler.handler_type {
+        config
+            .headers
+            .insert("x-user".to_string(), "user-{{params.[0]}}".to_string());
+    }
+    mock_test_rule_with_match(proxy_server.data_store, "/users/*/profile", vec![handler]).await?;
+
+    let response = client
+        .get(format!("{base_url}/users/42/profile?tab=posts"))
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["x-user"], "user-42");
+    assert_eq!(response.headers()["content-type"], "application/json");
+    assert_eq!(response.text().await?, r#"{"id":"42","tab":"posts"}"#);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn record_then_replay_without_upstream() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+    let store = proxy_server.data_store.clone();
+
+    let record_rule =
+        mock_test_rule(store.clone(), vec![HandlerRule::record_handler(None)]).await?;
+    let response = client.get(format!("{base_url}/hello")).send().await?;
+    assert_eq!(response.text().await?, "Hello, World!");
+    let cassette = store.cassettes_dir().join("default").join("cassette.jsonl");
+    // The entry is written after the last body chunk, which the client may
+    // have read already.
+    let mut recorded = String::new();
+    for _ in 0..50 {
+        recorded = std::fs::read_to_string(&cassette).unwrap_or_default();
+        if recorded.contains("Hello, World!") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(recorded.contains("Hello, World!"));
+
+    let dao = RequestProcessingDao::new(store.clone());
+    dao.delete_rule(record_rule).await?;
+    mock_test_rule(
+        store,
+        vec![HandlerRule::replay_handler(None, ReplayFallback::NotFound)],
+    )
+    .await?;
+
+    let response = client.get(format!("{base_url}/hello")).send().await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-served-by"], "lynx-proxy-replay");
+    assert_eq!(response.text().await?, "Hello, World!");
+    let response = client.get(format!("{base_url}/missing")).send().await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fault_handler_resets_and_truncates() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+
+    mock_test_rule_with_match(
+        proxy_server.data_store.clone(),
+        "/hello",
+        vec![HandlerRule::fault_handler(Fault::Reset {
+            phase: ResetPhase::BeforeHeaders,
+        })],
+    )
+    .await?;
+    mock_test_rule_with_match(
+        proxy_server.data_store,
+        "/json",
+        vec![HandlerRule::fault_handler(Fault::Truncate { bytes: 4 })],
+    )
+    .await?;
+
+    let reset = client.get(format!("{base_url}/hello")).send().await;
+    assert!(reset.is_err(), "expected a dropped connection: {reset:?}");
+
+    let response = client
+        .get(format!("{base_url}/json"))
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.bytes().await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn stream_mock_handler_streams_events_and_fails_mid_stream() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+
+    mock_test_rule_with_match(
+        proxy_server.data_store.clone(),
+        "/events",
+        vec![HandlerRule::stream_mock_handler(StreamMockConfig {
+            chunks: vec![
+                StreamMockChunk {
+                    event: Some("token".into()),
+                    data: "Hel".into(),
+                    ..Default::default()
+                },
+                StreamMockChunk {
+                    delay_ms: 200,
+                    data: "[DONE]".into(),
+                    ..Default::default()
+                },
+            ],
+            repeat: 1,
+            ..Default::default()
+        })],
+    )
+    .await?;
+    mock_test_rule_with_match(
+        proxy_server.data_store,
+        "/feed",
+        vec![HandlerRule::stream_mock_handler(StreamMockConfig {
+            format: StreamMockFormat::Raw,
+            chunks: vec![StreamMockChunk {
+                delay_ms: 20,
+                data: "tick\n".into(),
+                ..Default::default()
+            }],
+            repeat: 0,
+            fail_after: Some(2),
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let mut response = client
+        .get(format!("{base_url}/events"))
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let first = response.chunk().await?.expect("first event");
+    assert_eq!(&first[..], b"event: token\ndata: Hel\n\n");
+    // The second event is held back by its delay rather than sent with the first.
+    let first_at = std::time::Instant::now();
+    let rest = response.bytes().await?;
+    assert_eq!(&rest[..], b"data: [DONE]\n\n");
+    assert!(first_at.elapsed() >= std::time::Duration::from_millis(150));
+
+    let response = client
+        .get(format!("{base_url}/feed"))
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.bytes().await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cors_handler_answers_preflight_and_patches_responses() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+    let cors = || {
+        HandlerRule::cors_handler(CorsConfig {
+            allowed_origins: vec!["http://localhost:5173".into()],
+            allow_credentials: true,
+            expose_headers: vec!["x-request-id".into()],
+            ..Default::default()
+        })
+    };
+
+    mock_test_rule_with_match(proxy_server.data_store.clone(), "/hello", vec![cors()]).await?;
+    mock_test_rule_with_match(
+        proxy_server.data_store,
+        "/mocked",
+        vec![
+            cors(),
+            HandlerRule::mock_response_handler(Some(200), Some("{}".into())),
+        ],
+    )
+    .await?;
+
+    let preflight = client
+        .request(http::Method::OPTIONS, format!("{base_url}/hello"))
+        .header("origin", "http://localhost:5173")
+        .header("access-control-request-method", "PUT")
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        preflight.headers()["access-control-allow-origin"],
+        "http://localhost:5173"
+    );
+    assert!(
+        preflight.headers()["access-control-allow-methods"]
+            .to_str()?
+            .contains("PUT")
+    );
+
+    let rejected = client
+        .request(http::Method::OPTIONS, format!("{base_url}/hello"))
+        .header("origin", "http://evil.test")
+        .header("access-control-request-method", "GET")
+        .send()
+        .await
+        .expect("send request failed");
+    assert_eq!(rejected.status(), StatusCode::FORBIDDEN);
+
+    for path in ["/hello", "/mocked"] {
+        let response = client
+            .get(format!("{base_url}{path}"))
+            .header("origin", "http://localhost:5173")
+            .send()
+            .await
+            .expect("send request failed");
+        assert_eq!(response.status(), StatusCode::OK, "{path}");
+        let headers = response.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "http://localhost:5173"
+        );
+        assert_eq!(headers["access-control-allow-credentials"], "true");
+        assert_eq!(headers["access-control-expose-headers"], "x-request-id");
+    }
+
+    let no_origin = client
+        .get(format!("{base_url}/hello"))
+        .send()
+        .await
+        .expect("send request failed");
+    assert!(
+        !no_origin
+            .headers()
+            .contains_key("access-control-allow-origin")
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn modify_request_handler_headers_only() -> Result<()> {
     let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
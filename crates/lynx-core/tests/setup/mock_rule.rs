@@ -36,6 +36,15 @@ pub fn create_basic_capture_rule() -> CaptureRule {
 
 #[allow(dead_code)]
 pub async fn mock_test_rule(store: Arc<DataStore>, handlers: Vec<HandlerRule>) -> Result<i32> {
+    mock_test_rule_with_match(store, "/", handlers).await
+}
+
+#[allow(dead_code)]
+pub async fn mock_test_rule_with_match(
+    store: Arc<DataStore>,
+    match_expr: &str,
+    handlers: Vec<HandlerRule>,
+) -> Result<i32> {
     let dao = RequestProcessingDao::new(store);
 
     let rule = RequestRule {
@@ -47,7 +56,7 @@ pub async fn mock_test_rule(store: Arc<DataStore>, handlers: Vec<HandlerRule>) -
         priority: 1,
         capture: CaptureRule {
             id: None,
-            match_expr: "/".to_string(),
+            match_expr: match_expr.to_string(),
         },
         handlers,
     };
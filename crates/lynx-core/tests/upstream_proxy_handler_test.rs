@@ -0,0 +1,250 @@
+use std::{
+    net::Ipv4Addr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use anyhow::{Result, bail};
+use futures_util::{SinkExt, TryStreamExt};
+use http::StatusCode;
+use lynx_storage::dao::{
+    https_capture_dao::{CaptureFilter, HttpsCaptureDao},
+    request_processing_dao::{
+        HandlerRule,
+        handlers::{UpstreamProxyConfig, UpstreamProxyProtocol},
+    },
+};
+use reqwest_websocket::Message;
+use setup::{
+    mock_base_url, mock_rule::mock_test_rule,
+    setup_proxy_handler_server::setup_proxy_handler_server,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, copy_bidirectional},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    time::{Duration, timeout},
+};
+
+mod setup;
+
+/// Minimal SOCKS5 server without authentication that relays every
+/// connection it accepts and counts them.
+async fn spawn_socks5_proxy() -> Result<(String, Arc<AtomicUsize>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let _ = relay_socks5(&mut socket).await;
+            });
+        }
+    });
+    Ok((addr, connections))
+}
+
+async fn relay_socks5(socket: &mut TcpStream) -> Result<()> {
+    let mut greeting = [0u8; 2];
+    socket.read_exact(&mut greeting).await?;
+    let mut methods = vec![0u8; greeting[1] as usize];
+    socket.read_exact(&mut methods).await?;
+    socket.write_all(&[5, 0]).await?;
+
+    let mut request = [0u8; 4];
+    socket.read_exact(&mut request).await?;
+    let host = match request[3] {
+        1 => {
+            let mut ip = [0u8; 4];
+            socket.read_exact(&mut ip).await?;
+            Ipv4Addr::from(ip).to_string()
+        }
+        3 => {
+            let mut name = vec![0u8; socket.read_u8().await? as usize];
+            socket.read_exact(&mut name).await?;
+            String::from_utf8(name)?
+        }
+        kind => bail!("unsupported address type {kind}"),
+    };
+    let port = socket.read_u16().await?;
+
+    let mut target = TcpStream::connect((host.as_str(), port)).await?;
+    socket.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?;
+    copy_bidirectional(socket, &mut target).await?;
+    Ok(())
+}
+
+/// HTTP proxy that answers a single request itself and hands back the
+/// request head it received.
+async fn spawn_http_proxy() -> Result<(String, oneshot::Receiver<String>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?.to_string();
+    let (head_tx, head_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            return;
+        };
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            if socket.read_exact(&mut byte).await.is_err() {
+                return;
+            }
+            head.push(byte[0]);
+        }
+        let _ = socket
+            .write_all(
+                b"HTTP/1.1 200 OK\r\ncontent-length: 12\r\nconnection: close\r\n\r\nvia upstream",
+            )
+            .await;
+        let _ = head_tx.send(String::from_utf8_lossy(&head).into_owned());
+    });
+    Ok((addr, head_rx))
+}
+
+#[tokio::test]
+async fn upstream_proxy_handler_routes_http_through_socks5() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+    let (socks_addr, connections) = spawn_socks5_proxy().await?;
+
+    mock_test_rule(
+        proxy_server.data_store,
+        vec![HandlerRule::upstream_proxy_handler(UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Socks5,
+            address: socks_addr,
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let response = client
+        .get(format!("{base_url}/hello"))
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "Hello, World!");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upstream_proxy_handler_sends_absolute_form_to_http_proxy() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let base_url = mock_base_url(&mock_server);
+    let (proxy_addr, head) = spawn_http_proxy().await?;
+
+    mock_test_rule(
+        proxy_server.data_store,
+        vec![HandlerRule::upstream_proxy_handler(UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Http,
+            address: proxy_addr,
+            username: Some("alice".to_string()),
+            password: Some("secret".to_string()),
+        })],
+    )
+    .await?;
+
+    let response = client
+        .get(format!("{base_url}/hello"))
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "via upstream");
+
+    let head = head.await?.to_ascii_lowercase();
+    assert!(
+        head.starts_with(&format!("get {base_url}/hello http/1.1\r\n")),
+        "{head}"
+    );
+    assert!(
+        head.contains("proxy-authorization: basic ywxpy2u6c2vjcmv0\r\n"),
+        "{head}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upstream_proxy_handler_applies_to_undecrypted_tunnels() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let client = client.get_proxy_client();
+    let (socks_addr, connections) = spawn_socks5_proxy().await?;
+
+    HttpsCaptureDao::new(proxy_server.data_store.clone())
+        .update_capture_filter(CaptureFilter {
+            enabled: false,
+            include_domains: vec![],
+            exclude_domains: vec![],
+        })
+        .await?;
+    mock_test_rule(
+        proxy_server.data_store,
+        vec![HandlerRule::upstream_proxy_handler(UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Socks5,
+            address: socks_addr,
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let response = client
+        .get(format!("https://{}/hello", mock_server.addr))
+        .send()
+        .await
+        .expect("send request failed");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "Hello, World!");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn upstream_proxy_handler_routes_websocket_upgrades() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let base_url = mock_base_url(&mock_server);
+    let (socks_addr, connections) = spawn_socks5_proxy().await?;
+
+    mock_test_rule(
+        proxy_server.data_store,
+        vec![HandlerRule::upstream_proxy_handler(UpstreamProxyConfig {
+            protocol: UpstreamProxyProtocol::Socks5,
+            address: socks_addr,
+            ..Default::default()
+        })],
+    )
+    .await?;
+
+    let response = client
+        .proxy_ws(&format!("{base_url}/ws"))
+        .await
+        .expect("websocket upgrade failed");
+    let mut ws = response.into_websocket().await?;
+    ws.send(Message::Text("through socks".into())).await?;
+    let echoed = loop {
+        let message = timeout(Duration::from_secs(5), ws.try_next())
+            .await??
+            .expect("stream ended early");
+        if let Message::Text(text) = message {
+            break text;
+        }
+    };
+
+    assert_eq!(echoed, "through socks");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use futures_util::{SinkExt, TryStreamExt};
+use lynx_core::{
+    error::CoreError,
+    layers::message_package_layer::message_event_store::MessageEvent,
+    proxy::websocket_connections::{WebSocketSend, WebSocketSendDirection},
+};
+use lynx_storage::dao::request_processing_dao::{
+    HandlerRule,
+    handlers::{
+        BodyTransform, WebSocketFrameAction, WebSocketFrameDirection, WebSocketFrameRule,
+        WebSocketInterceptConfig,
+    },
+};
+use reqwest_websocket::{Message, WebSocket};
+use serde_json::json;
+use setup::{
+    mock_base_url, mock_rule::mock_test_rule,
+    setup_proxy_handler_server::setup_proxy_handler_server,
+};
+use tokio::time::{Duration, timeout};
+
+mod setup;
+
+async fn next_text(ws: &mut WebSocket) -> Result<String> {
+    loop {
+        let message = timeout(Duration::from_secs(5), ws.try_next())
+            .await??
+            .expect("stream ended early");
+        if let Message::Text(text) = message {
+            return Ok(text);
+        }
+    }
+}
+
+#[tokio::test]
+async fn websocket_intercept_drops_and_rewrites_frames() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::websocket_intercept_handler(
+            WebSocketInterceptConfig {
+                rules: vec![
+                    WebSocketFrameRule {
+                        direction: WebSocketFrameDirection::ClientToServer,
+                        pattern: Some("^heartbeat$".into()),
+                        json_path: None,
+                        json_value: None,
+                        action: WebSocketFrameAction::Drop,
+                    },
+                    WebSocketFrameRule {
+                        direction: WebSocketFrameDirection::ServerToClient,
+                        pattern: None,
+                        json_path: Some("$.price".into()),
+                        json_value: None,
+                        action: WebSocketFrameAction::Rewrite {
+                            replacement: None,
+                            transforms: vec![BodyTransform::JsonPathSet {
+                                path: "$.price".into(),
+                                value: json!(0),
+                            }],
+                        },
+                    },
+                ],
+            },
+        )],
+    )
+    .await?;
+
+    let base_url = mock_base_url(&mock_server);
+    let response = client
+        .proxy_ws(&format!("{base_url}/ws"))
+        .await
+        .expect("websocket upgrade failed");
+    let mut ws = response.into_websocket().await?;
+
+    // The echo server never sees the heartbeat, and the echoed price is rewritten.
+    ws.send(Message::Text("heartbeat".into())).await?;
+    ws.send(Message::Text(r#"{"price":12}"#.into())).await?;
+    assert_eq!(next_text(&mut ws).await?, r#"{"price":0}"#);
+
+    ws.send(Message::Text("plain".into())).await?;
+    assert_eq!(next_text(&mut ws).await?, "plain");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn websocket_send_injects_frames_into_live_connection() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let mut events = proxy_server.message_event_channel.subscribe();
+
+    let base_url = mock_base_url(&mock_server);
+    let response = client
+        .proxy_ws(&format!("{base_url}/ws"))
+        .await
+        .expect("websocket upgrade failed");
+    let mut ws = response.into_websocket().await?;
+
+    let trace_id = timeout(Duration::from_secs(5), async {
+        loop {
+            if let MessageEvent::OnWebSocketStart(trace_id) = events.recv().await? {
+                return anyhow::Ok(trace_id);
+            }
+        }
+    })
+    .await??;
+    // Make sure the relay is running before injecting.
+    ws.send(Message::Text("ready".into())).await?;
+    assert_eq!(next_text(&mut ws).await?, "ready");
+
+    let connections = proxy_server.websocket_connections.clone();
+    connections.send(WebSocketSend {
+        trace_id: trace_id.to_string(),
+        direction: WebSocketSendDirection::ServerToClient,
+        text: Some("from-server".into()),
+        binary_base64: None,
+    })?;
+    assert_eq!(next_text(&mut ws).await?, "from-server");
+
+    // Frames injected towards the server are echoed back by the mock.
+    connections.send(WebSocketSend {
+        trace_id: trace_id.to_string(),
+        direction: WebSocketSendDirection::ClientToServer,
+        text: Some("from-client".into()),
+        binary_base64: None,
+    })?;
+    assert_eq!(next_text(&mut ws).await?, "from-client");
+
+    let unknown = connections.send(WebSocketSend {
+        trace_id: "missing".into(),
+        direction: WebSocketSendDirection::ServerToClient,
+        text: Some("lost".into()),
+        binary_base64: None,
+    });
+    assert!(matches!(unknown, Err(CoreError::NotFound { .. })));
+
+    let both = connections.send(WebSocketSend {
+        trace_id: trace_id.to_string(),
+        direction: WebSocketSendDirection::ServerToClient,
+        text: Some("a".into()),
+        binary_base64: Some("Yg==".into()),
+    });
+    assert!(matches!(both, Err(CoreError::Validation { .. })));
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use futures_util::{SinkExt, TryStreamExt};
+use http::StatusCode;
+use lynx_core::layers::message_package_layer::message_event_store::MessageEvent;
+use lynx_storage::dao::request_processing_dao::{
+    HandlerRule,
+    handlers::{
+        WebSocketMockClose, WebSocketMockConfig, WebSocketMockFrame, WebSocketMockPeriodic,
+        WebSocketMockReply,
+    },
+};
+use reqwest_websocket::{Message, WebSocket};
+use setup::{
+    mock_base_url, mock_rule::mock_test_rule,
+    setup_proxy_handler_server::setup_proxy_handler_server,
+};
+use tokio::time::{Duration, timeout};
+
+mod setup;
+
+fn text(text: &str) -> WebSocketMockFrame {
+    WebSocketMockFrame {
+        text: Some(text.into()),
+        ..Default::default()
+    }
+}
+
+async fn next_message(ws: &mut WebSocket) -> Result<Message> {
+    loop {
+        let message = timeout(Duration::from_secs(5), ws.try_next())
+            .await??
+            .expect("stream ended early");
+        if !matches!(message, Message::Ping(_) | Message::Pong(_)) {
+            return Ok(message);
+        }
+    }
+}
+
+#[tokio::test]
+async fn websocket_mock_runs_script_without_upstream() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    let mut events = proxy_server.message_event_channel.subscribe();
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::websocket_mock_handler(WebSocketMockConfig {
+            on_connect: vec![text("hello")],
+            replies: vec![WebSocketMockReply {
+                pattern: r"^ping (\d+)$".into(),
+                frames: vec![text("pong $1")],
+            }],
+            echo: true,
+            periodic: vec![WebSocketMockPeriodic {
+                interval_ms: 100,
+                frame: WebSocketMockFrame {
+                    binary_base64: Some("AAE=".into()),
+                    ..Default::default()
+                },
+                count: Some(1),
+            }],
+            close: Some(WebSocketMockClose {
+                after_ms: 1000,
+                code: 4000,
+                reason: "done".into(),
+            }),
+        })],
+    )
+    .await?;
+
+    // The path does not exist upstream; the mock answers the upgrade itself.
+    let base_url = mock_base_url(&mock_server);
+    let response = client
+        .proxy_ws(&format!("{base_url}/ws-mock"))
+        .await
+        .expect("websocket upgrade failed");
+    let mut ws = response.into_websocket().await?;
+
+    ws.send(Message::Text("ping 7".into())).await?;
+    ws.send(Message::Text("other".into())).await?;
+    let mut received = Vec::new();
+    loop {
+        let message = next_message(&mut ws).await?;
+        let closed = matches!(message, Message::Close { .. });
+        received.push(message);
+        if closed {
+            break;
+        }
+    }
+
+    // Reading again sends the client's half of the close handshake.
+    let _ = timeout(Duration::from_secs(5), ws.try_next()).await;
+
+    let texts: Vec<&str> = received
+        .iter()
+        .filter_map(|message| match message {
+            Message::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts.first(), Some(&"hello"));
+    assert!(texts.contains(&"pong 7"));
+    assert!(texts.contains(&"other"));
+    assert!(
+        received
+            .iter()
+            .any(|message| matches!(message, Message::Binary(bytes) if bytes[..] == [0, 1]))
+    );
+    match received.last() {
+        Some(Message::Close { code, reason }) => {
+            assert_eq!(u16::from(*code), 4000);
+            assert_eq!(reason, "done");
+        }
+        other => panic!("expected close frame, got {other:?}"),
+    }
+
+    let captured = timeout(Duration::from_secs(5), async {
+        let mut frames = 0;
+        loop {
+            match events.recv().await? {
+                MessageEvent::OnWebSocketMessage(..) => frames += 1,
+                MessageEvent::OnWebSocketEnd(_) => return anyhow::Ok(frames),
+                _ => {}
+            }
+        }
+    })
+    .await??;
+    assert!(captured >= 6, "captured {captured} frames");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn websocket_mock_rejects_plain_requests() -> Result<()> {
+    let (proxy_server, mock_server, client) = setup_proxy_handler_server().await?;
+    mock_test_rule(
+        proxy_server.data_store.clone(),
+        vec![HandlerRule::websocket_mock_handler(
+            WebSocketMockConfig::default(),
+        )],
+    )
+    .await?;
+
+    let base_url = mock_base_url(&mock_server);
+    let response = client
+        .get_proxy_client()
+        .get(format!("{base_url}/hello"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use futures_util::{SinkExt, StreamExt};
+use lynx_storage::dao::projects_dao::ProjectsDao;
 use serde_json::json;
 use setup::setup_self_service_test_server::setup_self_service_test_server;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
@@ -249,3 +250,513 @@ async fn ws_traffic_filter_history_crud() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn ws_traffic_filter_saved_set_resolves_named_matchers() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    ProjectsDao::new(server.data_store.clone())
+        .set_matchers(
+            "default",
+            [("api".to_string(), "api.example.com".to_string())].into(),
+        )
+        .await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    for (id, expr, kind) in [
+        (
+            "traffic-filter-saved-set-named",
+            "@api AND -X POST",
+            "response",
+        ),
+        ("traffic-filter-saved-set-undefined", "@missing", "error"),
+    ] {
+        let request = json!({
+            "version": "v1",
+            "kind": "request",
+            "id": id,
+            "op": "network.trafficFilter.saved.set",
+            "timestamp": 0,
+            "payload": { "name": "api writes", "expr": expr }
+        });
+        socket
+            .send(Message::Text(request.to_string().into()))
+            .await?;
+        let response = socket.next().await.expect("ws response")?.into_text()?;
+        let frame: serde_json::Value = serde_json::from_str(&response)?;
+        assert_eq!(frame["kind"], kind, "{expr}: {frame}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_request_stream_subscribe_with_saved_filter() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "traffic-filter-saved-set-1",
+        "op": "network.trafficFilter.saved.set",
+        "timestamp": 0,
+        "payload": {
+            "name": "api",
+            "expr": "api.example.com AND -X POST"
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response");
+    assert_eq!(frame["payload"]["filters"][0]["name"], "api");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "request-stream-subscribe-invalid",
+        "op": "request.stream.subscribe",
+        "timestamp": 0,
+        "payload": {
+            "filter": "example.com AND ("
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "error");
+    assert_eq!(frame["error"]["code"], "INVALID_FILTER");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "request-stream-subscribe-saved",
+        "op": "request.stream.subscribe",
+        "timestamp": 0,
+        "payload": {
+            "filterName": "api"
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response");
+    assert_eq!(frame["payload"]["subscribed"], true);
+    assert_eq!(frame["payload"]["filter"], "api.example.com AND -X POST");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "traffic-filter-saved-delete-1",
+        "op": "network.trafficFilter.saved.delete",
+        "timestamp": 0,
+        "payload": {
+            "name": "api"
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = loop {
+        let text = socket.next().await.expect("ws response")?.into_text()?;
+        let frame: serde_json::Value = serde_json::from_str(&text)?;
+        if frame["kind"] != "event" {
+            break frame;
+        }
+    };
+    assert_eq!(response["op"], "network.trafficFilter.saved.delete");
+    assert_eq!(
+        response["payload"]["filters"].as_array().map(Vec::len),
+        Some(0)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_rules_explain_reports_failed_predicate() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-save-explain",
+        "op": "rules.save.set",
+        "timestamp": 0,
+        "payload": {
+            "name": "post only",
+            "enabled": true,
+            "priority": 10,
+            "capture": { "matchExpr": "example.com -X POST" },
+            "handlers": [{
+                "handlerType": { "type": "block", "statusCode": 403 },
+                "executionOrder": 100,
+                "enabled": true
+            }]
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "save failed: {frame}");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-explain-1",
+        "op": "rules.explain",
+        "timestamp": 0,
+        "payload": {
+            "url": "https://example.com/api",
+            "method": "GET"
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response");
+    assert_eq!(frame["op"], "rules.explain");
+    let rule = &frame["payload"]["rules"][0];
+    assert_eq!(rule["matched"], false);
+    assert_eq!(rule["failed"][0]["description"], "method = POST");
+    assert_eq!(rule["failed"][0]["source"], "-X POST");
+    assert!(frame["payload"]["handlers"].as_array().unwrap().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_rules_lint_reports_shadowed_rule() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    for (name, priority, match_expr) in [
+        ("block example", 50, "example.com"),
+        ("block users", 10, "api.example.com /users"),
+    ] {
+        let request = json!({
+            "version": "v1",
+            "kind": "request",
+            "id": format!("rules-save-{priority}"),
+            "op": "rules.save.set",
+            "timestamp": 0,
+            "payload": {
+                "name": name,
+                "enabled": true,
+                "priority": priority,
+                "capture": { "matchExpr": match_expr },
+                "handlers": [{
+                    "handlerType": { "type": "block", "statusCode": 403 },
+                    "executionOrder": 100,
+                    "enabled": true
+                }]
+            }
+        });
+        socket
+            .send(Message::Text(request.to_string().into()))
+            .await?;
+        let response = socket.next().await.expect("ws response")?.into_text()?;
+        let frame: serde_json::Value = serde_json::from_str(&response)?;
+        assert_eq!(frame["kind"], "response", "save failed: {frame}");
+    }
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-lint-1",
+        "op": "rules.lint",
+        "timestamp": 0
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "lint failed: {frame}");
+    assert_eq!(frame["op"], "rules.lint");
+    assert_eq!(frame["payload"]["errors"], 1);
+    let issue = &frame["payload"]["issues"][0];
+    assert_eq!(issue["ruleName"], "block users");
+    assert_eq!(issue["relatedRuleName"], "block example");
+    assert_eq!(issue["code"], "shadowed");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_rules_sequences_get_and_reset() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-sequences-get-1",
+        "op": "rules.sequences.get",
+        "timestamp": 0,
+        "payload": { "ruleId": 1 }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "get failed: {frame}");
+    assert_eq!(frame["payload"]["counters"], json!([]));
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-sequences-reset-1",
+        "op": "rules.sequences.reset",
+        "timestamp": 0
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "reset failed: {frame}");
+    assert_eq!(frame["op"], "rules.sequences.reset");
+    assert_eq!(frame["payload"]["reset"], 0);
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "rules-sequences-reset-2",
+        "op": "rules.sequences.reset",
+        "timestamp": 0,
+        "payload": { "ruleId": "1" }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "error", "malformed reset accepted: {frame}");
+    assert_eq!(frame["error"]["code"], "INVALID_PAYLOAD");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_settings_dns_set_validates_and_get_returns_saved() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "settings-dns-set-invalid",
+        "op": "settings.dns.set",
+        "timestamp": 0,
+        "payload": {
+            "overrides": [],
+            "resolver": { "type": "dnsOverHttps", "url": "http://dns.example/dns-query" }
+        }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "error", "invalid set accepted: {frame}");
+    assert_eq!(frame["error"]["code"], "INVALID_PAYLOAD");
+
+    let config = json!({
+        "overrides": [
+            { "host": "*.staging.test", "ips": ["10.0.0.7"], "enabled": true }
+        ],
+        "resolver": { "type": "server", "address": "1.1.1.1:53" }
+    });
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "settings-dns-set",
+        "op": "settings.dns.set",
+        "timestamp": 0,
+        "payload": config
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "set failed: {frame}");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "settings-dns-get",
+        "op": "settings.dns.get",
+        "timestamp": 0
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "get failed: {frame}");
+    assert_eq!(frame["payload"], config);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_breakpoint_list_and_resume_unknown() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "breakpoint-list",
+        "op": "breakpoint.list.get",
+        "timestamp": 0
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "response", "list failed: {frame}");
+    assert_eq!(frame["payload"]["breakpoints"], json!([]));
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "breakpoint-resume-missing",
+        "op": "breakpoint.resume",
+        "timestamp": 0
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "error", "missing payload accepted: {frame}");
+    assert_eq!(frame["error"]["code"], "INVALID_PAYLOAD");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "breakpoint-resume-unknown",
+        "op": "breakpoint.resume",
+        "timestamp": 0,
+        "payload": { "id": "bp-unknown" }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(frame["kind"], "error", "unknown id accepted: {frame}");
+    assert_eq!(frame["error"]["code"], "NOT_FOUND");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ws_websocket_send_requires_open_connection() -> Result<()> {
+    let (server, _client) = setup_self_service_test_server().await?;
+    let addr = server
+        .access_addr_list
+        .first()
+        .expect("proxy listen address");
+
+    let ws_url = format!("ws://{addr}/api/net_request/ws/message-events");
+    let (mut socket, _) = connect_async(&ws_url).await?;
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "websocket-send-invalid",
+        "op": "websocket.send",
+        "timestamp": 0,
+        "payload": { "traceId": "missing", "direction": "serverToClient" }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(
+        frame["kind"], "error",
+        "frame without data accepted: {frame}"
+    );
+    assert_eq!(frame["error"]["code"], "INVALID_PAYLOAD");
+
+    let request = json!({
+        "version": "v1",
+        "kind": "request",
+        "id": "websocket-send-unknown",
+        "op": "websocket.send",
+        "timestamp": 0,
+        "payload": { "traceId": "missing", "direction": "serverToClient", "text": "hi" }
+    });
+    socket
+        .send(Message::Text(request.to_string().into()))
+        .await?;
+    let response = socket.next().await.expect("ws response")?.into_text()?;
+    let frame: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(
+        frame["kind"], "error",
+        "unknown connection accepted: {frame}"
+    );
+    assert_eq!(frame["error"]["code"], "NOT_FOUND");
+
+    Ok(())
+}
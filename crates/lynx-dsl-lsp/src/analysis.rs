@@ -0,0 +1,250 @@
+//! Language features over a single DSL expression. Offsets are bytes into
+//! the expression; the server maps them into documents.
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic as LspDiagnostic, DiagnosticSeverity,
+    SemanticTokenType,
+};
+use lynx_dsl::{HighlightSpan, NamedMatchers, format_dsl, validate_with_matchers};
+
+/// Semantic token types, indexed by [`token_type`].
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::MACRO,
+];
+
+/// Index into [`TOKEN_TYPES`] for a [`HighlightSpan::kind`].
+pub fn token_type(kind: &str) -> Option<u32> {
+    let index = match kind {
+        "AndOp" | "OrOp" | "NotOp" => 0,
+        "Scheme" => 1,
+        "Host" => 2,
+        "Port" => 3,
+        "Path" => 4,
+        "Query" => 5,
+        "LineComment" => 6,
+        "ShortFlag" | "LongFlag" => 7,
+        "CliValue" => 8,
+        "Paren" => 9,
+        "MatcherRef" => 10,
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// A diagnostic with a byte range into the expression.
+pub struct ExprDiagnostic {
+    pub from: usize,
+    pub to: usize,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+/// Syntax errors and unresolved `@name` references.
+pub fn diagnostics(source: &str, matchers: &NamedMatchers) -> Vec<ExprDiagnostic> {
+    validate_with_matchers(source, matchers)
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| ExprDiagnostic {
+            from: diagnostic.from,
+            to: diagnostic.to,
+            severity: if diagnostic.severity == "warning" {
+                DiagnosticSeverity::WARNING
+            } else {
+                DiagnosticSeverity::ERROR
+            },
+            message: diagnostic.message,
+        })
+        .collect()
+}
+
+pub fn highlights(source: &str) -> Vec<HighlightSpan> {
+    lynx_dsl::validate(source).highlights
+}
+
+/// The formatted expression, when it parses and differs from `source`.
+pub fn format(source: &str) -> Option<String> {
+    format_dsl(source)
+        .ok()
+        .flatten()
+        .filter(|formatted| formatted != source)
+}
+
+pub fn to_lsp_diagnostic(diagnostic: ExprDiagnostic, range: lsp_types::Range) -> LspDiagnostic {
+    LspDiagnostic {
+        range,
+        severity: Some(diagnostic.severity),
+        source: Some("lynx-dsl".to_string()),
+        message: diagnostic.message,
+        ..Default::default()
+    }
+}
+
+const FLAGS: &[(&str, &str)] = &[
+    ("-X", "HTTP method"),
+    ("--request", "HTTP method"),
+    ("-H", "Header name=value"),
+    ("--header", "Header name=value"),
+    ("-q", "Query key=value"),
+    ("--query", "Query key=value"),
+    ("--sample", "Percentage, e.g. 10% or 10%:client"),
+    ("--time", "Local time window HH:MM-HH:MM"),
+];
+
+const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "CONNECT", "TRACE",
+];
+
+const HEADER_NAMES: &[&str] = &[
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "authorization",
+    "cache-control",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "cookie",
+    "host",
+    "if-none-match",
+    "origin",
+    "referer",
+    "set-cookie",
+    "upgrade",
+    "user-agent",
+    "x-forwarded-for",
+    "x-request-id",
+];
+
+const KEYWORDS: &[&str] = &["AND", "OR", "NOT"];
+
+/// Where the word under the cursor starts, and the completions for it.
+pub struct Completions {
+    pub replace_from: usize,
+    pub items: Vec<CompletionItem>,
+}
+
+pub fn complete(
+    source: &str,
+    offset: usize,
+    matchers: &NamedMatchers,
+    hosts: &[String],
+) -> Completions {
+    let before = &source[..offset.min(source.len())];
+    let word_start = before
+        .rfind(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')')
+        .map_or(0, |index| index + 1);
+    let word = &before[word_start..];
+    let previous = before[..word_start].split_whitespace().next_back();
+
+    let item = |label: &str, kind: CompletionItemKind, detail: Option<&str>| CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: detail.map(ToOwned::to_owned),
+        ..Default::default()
+    };
+
+    let items = if word.starts_with('@') {
+        matchers
+            .iter()
+            .map(|(name, expr)| {
+                item(
+                    &format!("@{name}"),
+                    CompletionItemKind::REFERENCE,
+                    Some(expr),
+                )
+            })
+            .collect()
+    } else if word.starts_with('-') {
+        FLAGS
+            .iter()
+            .map(|(flag, detail)| item(flag, CompletionItemKind::KEYWORD, Some(detail)))
+            .collect()
+    } else {
+        match previous {
+            Some(flag) if is_flag(flag, "-X", "--request") => METHODS
+                .iter()
+                .map(|method| item(method, CompletionItemKind::ENUM_MEMBER, None))
+                .collect(),
+            Some(flag) if is_flag(flag, "-H", "--header") => HEADER_NAMES
+                .iter()
+                .map(|name| CompletionItem {
+                    insert_text: Some(format!("{name}=")),
+                    ..item(name, CompletionItemKind::FIELD, None)
+                })
+                .collect(),
+            Some(flag) if is_flag(flag, "-q", "--query") => Vec::new(),
+            _ => hosts
+                .iter()
+                .map(|host| item(host, CompletionItemKind::VALUE, Some("recent traffic")))
+                .chain(
+                    KEYWORDS
+                        .iter()
+                        .filter(|_| previous.is_some())
+                        .map(|keyword| item(keyword, CompletionItemKind::KEYWORD, None)),
+                )
+                .collect(),
+        }
+    };
+    Completions {
+        replace_from: word_start,
+        items,
+    }
+}
+
+fn is_flag(token: &str, short: &str, long: &str) -> bool {
+    token.eq_ignore_ascii_case(short) || token.eq_ignore_ascii_case(long)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(completions: &Completions) -> Vec<&str> {
+        completions
+            .items
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn completes_by_context() {
+        let matchers: NamedMatchers = [("staging", "api.staging.example.com")]
+            .into_iter()
+            .collect();
+        let hosts = vec!["api.example.com".to_string()];
+
+        let source = "example.com -H con";
+        let completions = complete(source, source.len(), &matchers, &hosts);
+        assert_eq!(completions.replace_from, 15);
+        assert!(labels(&completions).contains(&"content-type"));
+
+        let source = "example.com --";
+        let completions = complete(source, source.len(), &matchers, &hosts);
+        assert!(labels(&completions).contains(&"--query"));
+
+        let source = "(@st";
+        let completions = complete(source, source.len(), &matchers, &hosts);
+        assert_eq!(
+            (completions.replace_from, labels(&completions)),
+            (1, vec!["@staging"])
+        );
+
+        let completions = complete("", 0, &matchers, &hosts);
+        assert_eq!(labels(&completions), vec!["api.example.com"]);
+
+        let source = "api.example.com -X ";
+        let completions = complete(source, source.len(), &matchers, &hosts);
+        assert!(labels(&completions).contains(&"POST"));
+    }
+}
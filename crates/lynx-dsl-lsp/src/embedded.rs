@@ -0,0 +1,232 @@
+//! DSL strings embedded in `.lynx.json`: `matchExpr` values and the
+//! expressions of the `matchers` object.
+//!
+//! The scanner is deliberately tolerant: the document is usually mid-edit, so
+//! it never fails and simply stops at the first thing it cannot read.
+
+/// One DSL expression inside a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedExpr {
+    /// Decoded string content.
+    pub text: String,
+    /// Byte offset in the JSON document for each byte of `text`, plus one
+    /// trailing entry for the closing quote.
+    offsets: Vec<usize>,
+}
+
+impl EmbeddedExpr {
+    /// Byte offset in the JSON document of byte `offset` in `text`.
+    pub fn to_document(&self, offset: usize) -> usize {
+        self.offsets[offset.min(self.offsets.len() - 1)]
+    }
+
+    /// Byte offset in `text` of document offset `offset`, when it falls
+    /// inside the string (the closing quote counts as the end).
+    pub fn from_document(&self, offset: usize) -> Option<usize> {
+        if offset < self.offsets[0] || offset > *self.offsets.last()? {
+            return None;
+        }
+        Some(self.offsets.partition_point(|raw| *raw < offset))
+    }
+
+    /// Document range of the string content, without the quotes.
+    pub fn range(&self) -> (usize, usize) {
+        (
+            self.offsets[0],
+            *self.offsets.last().unwrap_or(&self.offsets[0]),
+        )
+    }
+}
+
+/// `matchExpr` values, and the definitions of the `matchers` object as
+/// `(name, expr)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddedExprs {
+    pub match_exprs: Vec<EmbeddedExpr>,
+    pub matchers: Vec<(String, EmbeddedExpr)>,
+}
+
+impl EmbeddedExprs {
+    pub fn all(&self) -> impl Iterator<Item = &EmbeddedExpr> {
+        self.match_exprs
+            .iter()
+            .chain(self.matchers.iter().map(|(_, expr)| expr))
+    }
+
+    /// The expression containing document offset `offset`.
+    pub fn at(&self, offset: usize) -> Option<&EmbeddedExpr> {
+        self.all().find(|expr| expr.from_document(offset).is_some())
+    }
+}
+
+enum Container {
+    /// An object, with the key it was stored under and the pending key of
+    /// the member being read.
+    Object {
+        key: Option<String>,
+        pending: Option<String>,
+    },
+    Array,
+}
+
+pub fn scan_embedded_exprs(json: &str) -> EmbeddedExprs {
+    let bytes = json.as_bytes();
+    let mut found = EmbeddedExprs::default();
+    let mut stack: Vec<Container> = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'{' => {
+                let key = match stack.last_mut() {
+                    Some(Container::Object { pending, .. }) => pending.take(),
+                    _ => None,
+                };
+                stack.push(Container::Object { key, pending: None });
+                index += 1;
+            }
+            b'[' => {
+                if let Some(Container::Object { pending, .. }) = stack.last_mut() {
+                    pending.take();
+                }
+                stack.push(Container::Array);
+                index += 1;
+            }
+            b'}' | b']' => {
+                stack.pop();
+                index += 1;
+            }
+            b'"' => {
+                let Some((string, end)) = read_string(json, index) else {
+                    break;
+                };
+                index = end;
+                let Some(Container::Object { key, pending }) = stack.last_mut() else {
+                    continue;
+                };
+                match pending.take() {
+                    None => {
+                        if next_significant(bytes, index) == Some(b':') {
+                            *pending = Some(string.text);
+                        }
+                    }
+                    Some(member) if member == "matchExpr" => found.match_exprs.push(string),
+                    Some(member) if key.as_deref() == Some("matchers") => {
+                        found.matchers.push((member, string))
+                    }
+                    Some(_) => {}
+                }
+            }
+            b',' => {
+                if let Some(Container::Object { pending, .. }) = stack.last_mut() {
+                    pending.take();
+                }
+                index += 1;
+            }
+            _ => index += 1,
+        }
+    }
+    found
+}
+
+fn next_significant(bytes: &[u8], mut index: usize) -> Option<u8> {
+    while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+        index += 1;
+    }
+    bytes.get(index).copied()
+}
+
+/// Read the JSON string opening at `start`; returns the string and the offset
+/// after its closing quote.
+fn read_string(json: &str, start: usize) -> Option<(EmbeddedExpr, usize)> {
+    let mut text = String::new();
+    let mut offsets = Vec::new();
+    let mut chars = json[start + 1..].char_indices().peekable();
+
+    while let Some((relative, ch)) = chars.next() {
+        let raw = start + 1 + relative;
+        match ch {
+            '"' => {
+                offsets.push(raw);
+                return Some((EmbeddedExpr { text, offsets }, raw + 1));
+            }
+            '\\' => {
+                let (_, escape) = chars.next()?;
+                let decoded = match escape {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => {
+                        let mut code = String::new();
+                        for _ in 0..4 {
+                            code.push(chars.next()?.1);
+                        }
+                        u32::from_str_radix(&code, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .unwrap_or(char::REPLACEMENT_CHARACTER)
+                    }
+                    other => other,
+                };
+                push_char(&mut text, &mut offsets, decoded, raw);
+            }
+            '\n' => return None,
+            other => push_char(&mut text, &mut offsets, other, raw),
+        }
+    }
+    None
+}
+
+fn push_char(text: &mut String, offsets: &mut Vec<usize>, ch: char, raw: usize) {
+    for byte in 0..ch.len_utf8() {
+        offsets.push(raw + byte);
+    }
+    text.push(ch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_match_exprs_and_matchers() {
+        let json = r#"{
+  "matchers": { "staging": "api.staging.example.com", "writes": "-X POST" },
+  "rules": [
+    { "name": "matchExpr", "capture": { "matchExpr": "@staging AND /v2/**" } },
+    { "capture": { "matchExpr": "-H \"x-env\"=stg" } }
+  ]
+}"#;
+        let found = scan_embedded_exprs(json);
+        let exprs: Vec<&str> = found
+            .match_exprs
+            .iter()
+            .map(|expr| expr.text.as_str())
+            .collect();
+        assert_eq!(exprs, vec!["@staging AND /v2/**", "-H \"x-env\"=stg"]);
+        let names: Vec<&str> = found
+            .matchers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["staging", "writes"]);
+
+        let first = &found.match_exprs[0];
+        let (start, end) = first.range();
+        assert_eq!(&json[start..end], "@staging AND /v2/**");
+        assert_eq!(first.from_document(start + 4), Some(4));
+        assert_eq!(first.from_document(start - 1), None);
+
+        let escaped = &found.match_exprs[1];
+        let quote = escaped.text.find("x-env").unwrap();
+        assert_eq!(&json[escaped.to_document(quote)..][..5], "x-env");
+    }
+
+    #[test]
+    fn stops_quietly_on_unterminated_strings() {
+        let found = scan_embedded_exprs(r#"{ "capture": { "matchExpr": "example.com"#);
+        assert!(found.match_exprs.is_empty());
+    }
+}
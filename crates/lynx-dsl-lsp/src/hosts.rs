@@ -0,0 +1,44 @@
+//! Host completions from a running Lynx proxy (`GET /api/net_request/hosts`).
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::server::SharedHosts;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct HostsResponse {
+    hosts: Vec<String>,
+}
+
+/// Poll `server` in the background, replacing `hosts` on each successful
+/// fetch. Failures keep the previous list; the proxy may simply not be running.
+pub fn spawn_refresh(server: String, token: Option<String>, hosts: SharedHosts) {
+    std::thread::spawn(move || {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+        let url = format!("{}/api/net_request/hosts", server.trim_end_matches('/'));
+        loop {
+            let mut request = client.get(&url);
+            if let Some(token) = &token {
+                request = request.bearer_auth(token);
+            }
+            if let Ok(response) = request
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.json::<HostsResponse>())
+                && let Ok(mut shared) = hosts.write()
+            {
+                *shared = response.hosts;
+            }
+            std::thread::sleep(REFRESH_INTERVAL);
+        }
+    });
+}
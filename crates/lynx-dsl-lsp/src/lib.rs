@@ -0,0 +1,10 @@
+//! Language server for Lynx match expressions, in `.lynxdsl` files and in the
+//! `matchExpr` strings of `.lynx.json`.
+
+pub mod analysis;
+pub mod embedded;
+pub mod hosts;
+pub mod line_index;
+pub mod server;
+
+pub use server::{Server, SharedHosts, capabilities};
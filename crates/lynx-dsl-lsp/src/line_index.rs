@@ -0,0 +1,71 @@
+//! Byte offsets to LSP positions (UTF-16 columns) and back.
+
+use lsp_types::{Position, Range};
+
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.text[start..floor_char_boundary(self.text, offset)]
+            .encode_utf16()
+            .count();
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, from: usize, to: usize) -> Range {
+        Range::new(self.position(from), self.position(to))
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.line_starts.get(position.line as usize).copied() else {
+            return self.text.len();
+        };
+        let line = &self.text[start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        let mut units = 0;
+        for (index, ch) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += ch.len_utf16();
+        }
+        start + line.len()
+    }
+}
+
+fn floor_char_boundary(text: &str, mut offset: usize) -> usize {
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_utf16_columns() {
+        let text = "# héllo 𝄞\nexample.com";
+        let index = LineIndex::new(text);
+        let end_of_comment = text.find('\n').unwrap();
+        assert_eq!(index.position(end_of_comment), Position::new(0, 10));
+        assert_eq!(index.offset(Position::new(0, 10)), end_of_comment);
+        assert_eq!(index.position(end_of_comment + 1), Position::new(1, 0));
+        assert_eq!(index.offset(Position::new(1, 7)), end_of_comment + 8);
+        assert_eq!(index.offset(Position::new(5, 0)), text.len());
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use clap::Parser;
+use lsp_server::Connection;
+use lynx_dsl_lsp::{Server, capabilities, hosts};
+
+/// Language server for Lynx match expressions (stdio).
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Self-service URL of a running Lynx proxy, used to complete hosts from
+    /// recent traffic (e.g. http://127.0.0.1:7788)
+    #[arg(long)]
+    server: Option<String>,
+
+    /// API token when the proxy has authentication enabled
+    #[arg(long)]
+    token: Option<String>,
+
+    /// Accepted for editors that always pass it; stdio is the only transport
+    #[arg(long, hide = true)]
+    stdio: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let shared_hosts = Arc::new(RwLock::new(Vec::new()));
+    if let Some(server) = args.server {
+        hosts::spawn_refresh(server, args.token, shared_hosts.clone());
+    }
+
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::new(connection, shared_hosts).run()?;
+    io_threads.join()?;
+    Ok(())
+}
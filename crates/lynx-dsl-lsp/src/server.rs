@@ -0,0 +1,380 @@
+//! Request and notification handling over an [`lsp_server::Connection`].
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{Completion, Formatting, Request as _, SemanticTokensFullRequest};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, CompletionTextEdit,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentFormattingParams, PublishDiagnosticsParams, SemanticToken, SemanticTokens,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams,
+    SemanticTokensResult, SemanticTokensServerCapabilities, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+};
+use lynx_dsl::NamedMatchers;
+
+use crate::analysis::{self, TOKEN_TYPES};
+use crate::embedded::{EmbeddedExpr, EmbeddedExprs, scan_embedded_exprs};
+use crate::line_index::LineIndex;
+
+/// Project config file whose `matchExpr` strings are checked as DSL.
+pub const PROJECT_CONFIG_FILE: &str = ".lynx.json";
+
+/// Hosts seen in recent traffic, refreshed outside the server loop.
+pub type SharedHosts = Arc<RwLock<Vec<String>>>;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["-".to_string(), "@".to_string(), " ".to_string()]),
+            ..Default::default()
+        }),
+        document_formatting_provider: Some(lsp_types::OneOf::Left(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: SemanticTokensLegend {
+                    token_types: TOKEN_TYPES.to_vec(),
+                    token_modifiers: Vec::new(),
+                },
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            },
+        )),
+        ..Default::default()
+    }
+}
+
+pub struct Server {
+    connection: Connection,
+    documents: HashMap<Url, String>,
+    hosts: SharedHosts,
+}
+
+impl Server {
+    pub fn new(connection: Connection, hosts: SharedHosts) -> Self {
+        Self {
+            connection,
+            documents: HashMap::new(),
+            hosts,
+        }
+    }
+
+    /// Serve until the client asks to shut down.
+    pub fn run(mut self) -> Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            Completion::METHOD => {
+                let params: CompletionParams = serde_json::from_value(request.params)?;
+                serde_json::to_value(self.completion(params))?
+            }
+            Formatting::METHOD => {
+                let params: DocumentFormattingParams = serde_json::from_value(request.params)?;
+                serde_json::to_value(self.formatting(&params.text_document.uri))?
+            }
+            SemanticTokensFullRequest::METHOD => {
+                let params: SemanticTokensParams = serde_json::from_value(request.params)?;
+                serde_json::to_value(self.semantic_tokens(&params.text_document.uri))?
+            }
+            _ => return self.respond_method_not_found(id, &request.method),
+        };
+        self.connection
+            .sender
+            .send(Message::Response(Response::new_ok(id, result)))?;
+        Ok(())
+    }
+
+    fn respond_method_not_found(&self, id: RequestId, method: &str) -> Result<()> {
+        self.connection
+            .sender
+            .send(Message::Response(Response::new_err(
+                id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {method}"),
+            )))?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents
+                    .insert(uri.clone(), params.text_document.text);
+                self.publish_diagnostics(&uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                if let Some(change) = params.content_changes.into_iter().next_back() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                self.publish_diagnostics(&uri)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.send_diagnostics(params.text_document.uri, Vec::new())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn publish_diagnostics(&self, uri: &Url) -> Result<()> {
+        let Some(text) = self.documents.get(uri) else {
+            return Ok(());
+        };
+        let index = LineIndex::new(text);
+        let diagnostics = match document_exprs(uri, text) {
+            Some(exprs) => {
+                let matchers = matchers_of(&exprs);
+                let mut diagnostics: Vec<_> = exprs
+                    .match_exprs
+                    .iter()
+                    .flat_map(|expr| {
+                        analysis::diagnostics(&expr.text, &matchers)
+                            .into_iter()
+                            .map(|diagnostic| {
+                                let range = index.range(
+                                    expr.to_document(diagnostic.from),
+                                    expr.to_document(diagnostic.to),
+                                );
+                                analysis::to_lsp_diagnostic(diagnostic, range)
+                            })
+                    })
+                    .collect();
+                for (name, error) in matchers.check() {
+                    let Some((_, expr)) = exprs.matchers.iter().find(|(other, _)| *other == name)
+                    else {
+                        continue;
+                    };
+                    let (from, to) = expr.range();
+                    diagnostics.push(analysis::to_lsp_diagnostic(
+                        analysis::ExprDiagnostic {
+                            from,
+                            to,
+                            severity: lsp_types::DiagnosticSeverity::ERROR,
+                            message: error.to_string(),
+                        },
+                        index.range(from, to),
+                    ));
+                }
+                diagnostics
+            }
+            None => analysis::diagnostics(text, &self.matchers_near(uri))
+                .into_iter()
+                .map(|diagnostic| {
+                    let range = index.range(diagnostic.from, diagnostic.to);
+                    analysis::to_lsp_diagnostic(diagnostic, range)
+                })
+                .collect(),
+        };
+        self.send_diagnostics(uri.clone(), diagnostics)
+    }
+
+    fn send_diagnostics(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+        Ok(())
+    }
+
+    fn semantic_tokens(&self, uri: &Url) -> Option<SemanticTokensResult> {
+        let text = self.documents.get(uri)?;
+        let mut spans: Vec<(usize, usize, u32)> = match document_exprs(uri, text) {
+            Some(exprs) => exprs
+                .all()
+                .flat_map(|expr| {
+                    analysis::highlights(&expr.text)
+                        .into_iter()
+                        .filter_map(|span| {
+                            Some((
+                                expr.to_document(span.from),
+                                expr.to_document(span.to),
+                                analysis::token_type(&span.kind)?,
+                            ))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            None => analysis::highlights(text)
+                .into_iter()
+                .filter_map(|span| Some((span.from, span.to, analysis::token_type(&span.kind)?)))
+                .collect(),
+        };
+        spans.sort_unstable();
+        Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: encode_tokens(text, &spans),
+        }))
+    }
+
+    fn formatting(&self, uri: &Url) -> Option<Vec<TextEdit>> {
+        let text = self.documents.get(uri)?;
+        let index = LineIndex::new(text);
+        let edits = match document_exprs(uri, text) {
+            Some(exprs) => exprs
+                .all()
+                .filter_map(|expr| {
+                    let formatted = analysis::format(&expr.text)?;
+                    let (from, to) = expr.range();
+                    // Replace the quotes too so the result is re-escaped as JSON.
+                    Some(TextEdit::new(
+                        index.range(from - 1, to + 1),
+                        serde_json::to_string(&formatted).ok()?,
+                    ))
+                })
+                .collect(),
+            None => analysis::format(text)
+                .map(|formatted| vec![TextEdit::new(index.range(0, text.len()), formatted)])
+                .unwrap_or_default(),
+        };
+        Some(edits)
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let uri = &params.text_document_position.text_document.uri;
+        let text = self.documents.get(uri)?;
+        let index = LineIndex::new(text);
+        let offset = index.offset(params.text_document_position.position);
+        let hosts = self
+            .hosts
+            .read()
+            .map(|hosts| hosts.clone())
+            .unwrap_or_default();
+
+        let (completions, to_document): (_, Box<dyn Fn(usize) -> usize>) =
+            match document_exprs(uri, text) {
+                Some(exprs) => {
+                    let matchers = matchers_of(&exprs);
+                    let expr = exprs.at(offset)?.clone();
+                    let completions = analysis::complete(
+                        &expr.text,
+                        expr.from_document(offset)?,
+                        &matchers,
+                        &hosts,
+                    );
+                    (completions, Box::new(move |at| expr.to_document(at)))
+                }
+                None => (
+                    analysis::complete(text, offset, &self.matchers_near(uri), &hosts),
+                    Box::new(|at| at),
+                ),
+            };
+
+        let range = index.range(to_document(completions.replace_from), offset);
+        let items = completions
+            .items
+            .into_iter()
+            .map(|mut item| {
+                let new_text = item
+                    .insert_text
+                    .take()
+                    .unwrap_or_else(|| item.label.clone());
+                item.text_edit = Some(CompletionTextEdit::Edit(TextEdit::new(range, new_text)));
+                item
+            })
+            .collect();
+        Some(CompletionResponse::Array(items))
+    }
+
+    /// Matchers from the nearest `.lynx.json` above a DSL document, preferring
+    /// the editor's unsaved copy.
+    fn matchers_near(&self, uri: &Url) -> NamedMatchers {
+        let Ok(path) = uri.to_file_path() else {
+            return NamedMatchers::new();
+        };
+        for dir in path.ancestors().skip(1) {
+            let config = dir.join(PROJECT_CONFIG_FILE);
+            let open = Url::from_file_path(&config)
+                .ok()
+                .and_then(|config_uri| self.documents.get(&config_uri).cloned());
+            if let Some(text) = open.or_else(|| std::fs::read_to_string(&config).ok()) {
+                return matchers_of(&scan_embedded_exprs(&text));
+            }
+        }
+        NamedMatchers::new()
+    }
+}
+
+/// Embedded expressions when `uri` is a JSON document; `None` for DSL files.
+fn document_exprs(uri: &Url, text: &str) -> Option<EmbeddedExprs> {
+    Path::new(uri.path())
+        .extension()
+        .is_some_and(|extension| extension == "json")
+        .then(|| scan_embedded_exprs(text))
+}
+
+fn matchers_of(exprs: &EmbeddedExprs) -> NamedMatchers {
+    exprs
+        .matchers
+        .iter()
+        .map(|(name, expr): &(String, EmbeddedExpr)| (name.as_str(), expr.text.clone()))
+        .collect()
+}
+
+/// LSP relative encoding; spans crossing a line break are split per line.
+fn encode_tokens(text: &str, spans: &[(usize, usize, u32)]) -> Vec<SemanticToken> {
+    let index = LineIndex::new(text);
+    let mut tokens = Vec::new();
+    let (mut last_line, mut last_start) = (0, 0);
+    for &(from, to, token_type) in spans {
+        let mut start = from;
+        while start < to {
+            let line_end = text[start..to].find('\n').map_or(to, |at| start + at);
+            let begin = index.position(start);
+            let end = index.position(line_end);
+            if end.character > begin.character {
+                let delta_line = begin.line - last_line;
+                let delta_start = if delta_line == 0 {
+                    begin.character - last_start
+                } else {
+                    begin.character
+                };
+                tokens.push(SemanticToken {
+                    delta_line,
+                    delta_start,
+                    length: end.character - begin.character,
+                    token_type,
+                    token_modifiers_bitset: 0,
+                });
+                (last_line, last_start) = (begin.line, begin.character);
+            }
+            start = line_end + 1;
+        }
+    }
+    tokens
+}
@@ -0,0 +1,216 @@
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{DidOpenTextDocument, Exit, Notification as _, PublishDiagnostics};
+use lsp_types::request::{
+    Completion, Formatting, Request as _, SemanticTokensFullRequest, Shutdown,
+};
+use lsp_types::{
+    CompletionResponse, DidOpenTextDocumentParams, PublishDiagnosticsParams, SemanticTokensResult,
+    TextDocumentItem, TextEdit, Url,
+};
+use lynx_dsl_lsp::Server;
+use serde_json::json;
+
+struct Client {
+    connection: Connection,
+    server: Option<JoinHandle<()>>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start(hosts: &[&str]) -> Self {
+        let (server, client) = Connection::memory();
+        let hosts = Arc::new(RwLock::new(
+            hosts.iter().map(|host| host.to_string()).collect(),
+        ));
+        let handle = std::thread::spawn(move || Server::new(server, hosts).run().unwrap());
+        Self {
+            connection: client,
+            server: Some(handle),
+            next_id: 0,
+        }
+    }
+
+    fn open(&self, uri: &Url, text: &str) -> PublishDiagnosticsParams {
+        let params = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "lynx-dsl".into(), 1, text.into()),
+        };
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                DidOpenTextDocument::METHOD.to_string(),
+                params,
+            )))
+            .unwrap();
+        match self.connection.receiver.recv().unwrap() {
+            Message::Notification(notification)
+                if notification.method == PublishDiagnostics::METHOD =>
+            {
+                serde_json::from_value(notification.params).unwrap()
+            }
+            other => panic!("expected diagnostics, got {other:?}"),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: serde_json::Value) -> serde_json::Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.connection
+            .sender
+            .send(Message::Request(Request::new(
+                id.clone(),
+                method.into(),
+                params,
+            )))
+            .unwrap();
+        match self.connection.receiver.recv().unwrap() {
+            Message::Response(Response {
+                id: response_id,
+                result: Some(result),
+                ..
+            }) if response_id == id => result,
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.request(Shutdown::METHOD, serde_json::Value::Null);
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                Exit::METHOD.to_string(),
+                (),
+            )))
+            .unwrap();
+        if let Some(server) = self.server.take() {
+            server.join().unwrap();
+        }
+    }
+}
+
+#[test]
+fn dsl_documents_get_diagnostics_tokens_and_formatting() {
+    let mut client = Client::start(&[]);
+    let uri = Url::parse("file:///work/rules/api.lynxdsl").unwrap();
+
+    let diagnostics = client.open(&uri, "example.com AND (");
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].range.start.character, 16);
+
+    let diagnostics = client.open(&uri, "example.com   and  -X POST");
+    assert!(diagnostics.diagnostics.is_empty());
+
+    let tokens: SemanticTokensResult = serde_json::from_value(client.request(
+        SemanticTokensFullRequest::METHOD,
+        json!({ "textDocument": { "uri": uri } }),
+    ))
+    .unwrap();
+    let SemanticTokensResult::Tokens(tokens) = tokens else {
+        panic!("expected full tokens");
+    };
+    let starts: Vec<(u32, u32)> = tokens
+        .data
+        .iter()
+        .map(|token| (token.delta_start, token.length))
+        .collect();
+    assert_eq!(starts, vec![(0, 11), (14, 3), (5, 2), (3, 4)]);
+
+    let edits: Vec<TextEdit> = serde_json::from_value(client.request(
+        Formatting::METHOD,
+        json!({
+            "textDocument": { "uri": uri },
+            "options": { "tabSize": 2, "insertSpaces": true }
+        }),
+    ))
+    .unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "example.com AND -X POST");
+}
+
+#[test]
+fn match_exprs_in_project_config_are_checked() {
+    let mut client = Client::start(&["api.example.com", "cdn.example.com"]);
+    let uri = Url::parse("file:///work/.lynx.json").unwrap();
+    let text = r#"{
+  "matchers": { "staging": "api.staging.example.com" },
+  "rules": [
+    { "capture": { "matchExpr": "@staging   and /v2/**" } },
+    { "capture": { "matchExpr": "@prod" } },
+    { "capture": { "matchExpr": "" } }
+  ]
+}"#;
+
+    let diagnostics = client.open(&uri, text);
+    let messages: Vec<(u32, &str)> = diagnostics
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![(4, "undefined matcher @prod"), (5, "Empty document")]
+    );
+
+    let edits: Vec<TextEdit> = serde_json::from_value(client.request(
+        Formatting::METHOD,
+        json!({
+            "textDocument": { "uri": uri },
+            "options": { "tabSize": 2, "insertSpaces": true }
+        }),
+    ))
+    .unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].new_text, "\"@staging AND /v2/**\"");
+    assert_eq!(edits[0].range.start.line, 3);
+
+    let line = text.lines().nth(4).unwrap();
+    let character = line.find("@prod").unwrap() as u32 + 1;
+    let completions: CompletionResponse = serde_json::from_value(client.request(
+        Completion::METHOD,
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": 4, "character": character }
+        }),
+    ))
+    .unwrap();
+    let CompletionResponse::Array(items) = completions else {
+        panic!("expected a completion list");
+    };
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].label, "@staging");
+
+    let character = line.find("matchExpr").unwrap() as u32;
+    let completions = client.request(
+        Completion::METHOD,
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": 4, "character": character }
+        }),
+    );
+    assert!(completions.is_null());
+}
+
+#[test]
+fn hosts_from_recent_traffic_are_offered() {
+    let mut client = Client::start(&["api.example.com"]);
+    let uri = Url::parse("file:///work/hosts.lynxdsl").unwrap();
+    client.open(&uri, "/v1 OR ");
+
+    let completions: CompletionResponse = serde_json::from_value(client.request(
+        Completion::METHOD,
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": 0, "character": 7 }
+        }),
+    ))
+    .unwrap();
+    let CompletionResponse::Array(items) = completions else {
+        panic!("expected a completion list");
+    };
+    let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, vec!["api.example.com", "AND", "OR", "NOT"]);
+}
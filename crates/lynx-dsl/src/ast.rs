@@ -34,8 +34,13 @@ pub enum NotExpr {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Primary {
     CliOnly(CliArgs),
-    Url { url: Url, cli: Option<CliArgs> },
+    Url {
+        url: Url,
+        cli: Option<CliArgs>,
+    },
     Grouped(Box<Expr>),
+    /// Reference to a named matcher (`@name`); the value excludes the `@`.
+    Ref(Spanned<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -2,11 +2,15 @@
 
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::ast::{AndExpr, CliArg, CliArgValue, CliArgs, NotExpr, OrExpr, Primary, Program, Url};
+use crate::ast::{
+    AndExpr, CliArg, CliArgValue, CliArgs, NotExpr, OrExpr, Primary, Program, Span, Spanned, Url,
+};
 use crate::error::ParseError;
-use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SegmentPattern};
+use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SampleKey, SegmentPattern};
+use crate::matchers::NamedMatchers;
 use crate::parser::parse_program;
 use crate::query::parse_query_pairs;
 
@@ -22,6 +26,36 @@ pub enum CompileError {
     InvalidPort(String),
     #[error("cli flag requires a value: {0}")]
     MissingCliValue(String),
+    #[error("invalid sample (expected e.g. 10%, 10%:client, 10%:header=x-user-id): {0}")]
+    InvalidSample(String),
+    #[error("invalid time window (expected HH:MM-HH:MM): {0}")]
+    InvalidTimeWindow(String),
+    #[error("undefined matcher @{name}")]
+    UndefinedMatcher { name: String, span: Span },
+    #[error("matcher cycle: @{}", cycle.join(" -> @"))]
+    MatcherCycle { cycle: Vec<String>, span: Span },
+    #[error("invalid matcher @{name}: {error}")]
+    InvalidMatcher {
+        name: String,
+        span: Span,
+        error: Box<CompileError>,
+    },
+}
+
+impl CompileError {
+    /// Source span of the error, when it can be located (for named matchers,
+    /// the `@name` reference).
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Parse(ParseError::Syntax { span, .. } | ParseError::TrailingInput { span }) => {
+                Some(*span)
+            }
+            Self::UndefinedMatcher { span, .. }
+            | Self::MatcherCycle { span, .. }
+            | Self::InvalidMatcher { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 /// Parse DSL source and lower AST into a cached-ready [`MatchProgram`].
@@ -29,26 +63,100 @@ pub enum CompileError {
 /// The AST exists only on the stack inside this function; callers should retain
 /// the returned IR for repeated evaluation.
 pub fn compile_match_expr(source: &str) -> Result<MatchProgram, CompileError> {
+    compile_match_expr_with_source_map(source).map(|(program, _)| program)
+}
+
+/// Like [`compile_match_expr`], resolving `@name` references against `matchers`.
+pub fn compile_match_expr_with_matchers(
+    source: &str,
+    matchers: &NamedMatchers,
+) -> Result<MatchProgram, CompileError> {
+    compile_with_matchers(source, matchers).map(|(program, _)| program)
+}
+
+/// Source spans of a compiled program, kept outside the IR for tooling.
+///
+/// `predicate_spans[i]` is the byte range in the original source that produced
+/// `program.predicates[i]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub predicate_spans: Vec<Span>,
+}
+
+impl SourceMap {
+    pub fn predicate_span(&self, index: usize) -> Option<Span> {
+        self.predicate_spans.get(index).copied()
+    }
+}
+
+/// Like [`compile_match_expr`], but also returns the [`SourceMap`] used by
+/// [`crate::explain`] to point evaluation results back at the source text.
+pub fn compile_match_expr_with_source_map(
+    source: &str,
+) -> Result<(MatchProgram, SourceMap), CompileError> {
+    compile_with_matchers(source, &NamedMatchers::default())
+}
+
+/// Compile with named matchers and keep the [`SourceMap`].
+///
+/// References are inlined: predicates coming from a matcher definition map to
+/// the span of the outermost `@name` in `source`.
+pub fn compile_with_matchers(
+    source: &str,
+    matchers: &NamedMatchers,
+) -> Result<(MatchProgram, SourceMap), CompileError> {
     let program = parse_program(source)?;
-    lower_program(&program)
+    lower_program(&program, matchers)
+}
+
+/// Resolve one reference on its own, reporting undefined names, cycles and
+/// invalid definitions reachable from it.
+pub(crate) fn resolve_matcher_ref(
+    name: &Spanned<String>,
+    matchers: &NamedMatchers,
+) -> Result<(), CompileError> {
+    LowerCtx::new(matchers).lower_ref(name).map(|_| ())
 }
 
-fn lower_program(program: &Program) -> Result<MatchProgram, CompileError> {
+fn lower_program(
+    program: &Program,
+    matchers: &NamedMatchers,
+) -> Result<(MatchProgram, SourceMap), CompileError> {
     let Some(expr) = program.expr.as_ref() else {
         return Err(CompileError::Empty);
     };
 
-    let mut ctx = LowerCtx::default();
+    let mut ctx = LowerCtx::new(matchers);
     let plan = ctx.lower_or_expr(&expr.or)?;
-    Ok(MatchProgram::new(ctx.predicates, plan))
+    Ok((
+        MatchProgram::new(ctx.predicates, plan),
+        SourceMap {
+            predicate_spans: ctx.spans,
+        },
+    ))
 }
 
-#[derive(Default)]
-struct LowerCtx {
+struct LowerCtx<'a> {
     predicates: Vec<Predicate>,
+    spans: Vec<Span>,
+    matchers: &'a NamedMatchers,
+    /// Names of the matchers being inlined, outermost first.
+    resolving: Vec<String>,
+    /// Span of the outermost `@name` while inlining its definition.
+    ref_span: Option<Span>,
 }
 
-impl LowerCtx {
+impl<'a> LowerCtx<'a> {
+    fn new(matchers: &'a NamedMatchers) -> Self {
+        Self {
+            predicates: Vec::new(),
+            spans: Vec::new(),
+            matchers,
+            resolving: Vec::new(),
+            ref_span: None,
+        }
+    }
+
     fn lower_or_expr(&mut self, expr: &OrExpr) -> Result<EvalPlan, CompileError> {
         if expr.branches.is_empty() {
             return Err(CompileError::Empty);
@@ -93,6 +201,7 @@ impl LowerCtx {
         match primary {
             Primary::Grouped(expr) => self.lower_or_expr(&expr.or),
             Primary::CliOnly(cli) => self.lower_cli(cli),
+            Primary::Ref(name) => self.lower_ref(name),
             Primary::Url { url, cli } => {
                 let mut plans = Vec::new();
                 plans.push(self.lower_url(url)?);
@@ -108,24 +217,75 @@ impl LowerCtx {
         }
     }
 
+    fn lower_ref(&mut self, name: &Spanned<String>) -> Result<EvalPlan, CompileError> {
+        let Some(definition) = self.matchers.get(&name.value) else {
+            return Err(CompileError::UndefinedMatcher {
+                name: name.value.clone(),
+                span: name.span,
+            });
+        };
+        if let Some(start) = self.resolving.iter().position(|open| *open == name.value) {
+            let mut cycle = self.resolving[start..].to_vec();
+            cycle.push(name.value.clone());
+            return Err(CompileError::MatcherCycle {
+                cycle,
+                span: name.span,
+            });
+        }
+
+        let invalid = |error: CompileError| match error {
+            CompileError::MatcherCycle { cycle, .. } => CompileError::MatcherCycle {
+                cycle,
+                span: name.span,
+            },
+            error => CompileError::InvalidMatcher {
+                name: name.value.clone(),
+                span: name.span,
+                error: Box::new(error),
+            },
+        };
+        let program = parse_program(definition).map_err(|error| invalid(error.into()))?;
+        let Some(expr) = program.expr else {
+            return Err(invalid(CompileError::Empty));
+        };
+
+        let outermost = self.ref_span.is_none();
+        if outermost {
+            self.ref_span = Some(name.span);
+        }
+        self.resolving.push(name.value.clone());
+        let plan = self.lower_or_expr(&expr.or);
+        self.resolving.pop();
+        if outermost {
+            self.ref_span = None;
+        }
+        plan.map_err(invalid)
+    }
+
     fn lower_url(&mut self, url: &Url) -> Result<EvalPlan, CompileError> {
         let mut indices = Vec::new();
 
         if let Some(scheme) = &url.scheme {
             let normalized = normalize_scheme(&scheme.value);
-            indices.push(self.push_predicate(Predicate::SchemeEq(Arc::from(normalized.as_str()))));
+            indices.push(self.push_predicate(
+                Predicate::SchemeEq(Arc::from(normalized.as_str())),
+                scheme.span,
+            ));
         }
         if let Some(host) = &url.host {
-            indices.push(self.push_predicate(Predicate::HostEq(Arc::from(
-                host.value.to_ascii_lowercase(),
-            ))));
+            indices.push(self.push_predicate(
+                Predicate::HostEq(Arc::from(host.value.to_ascii_lowercase())),
+                host.span,
+            ));
         }
         if let Some(port) = &url.port {
             let port_num = parse_port(&port.value)?;
-            indices.push(self.push_predicate(Predicate::PortEq(port_num)));
+            indices.push(self.push_predicate(Predicate::PortEq(port_num), port.span));
         }
         if let Some(path) = &url.path {
-            indices.push(self.push_predicate(Predicate::PathGlob(compile_path(&path.value)?)));
+            indices.push(
+                self.push_predicate(Predicate::PathGlob(compile_path(&path.value)?), path.span),
+            );
         }
         if let Some(query) = &url.query {
             let pairs: Vec<(Arc<str>, Arc<str>)> = parse_query_pairs(&query.value)
@@ -135,7 +295,7 @@ impl LowerCtx {
             if pairs.is_empty() {
                 return Err(CompileError::Empty);
             }
-            indices.push(self.push_predicate(Predicate::QueryParamsAll(pairs)));
+            indices.push(self.push_predicate(Predicate::QueryParamsAll(pairs), query.span));
         }
 
         if indices.is_empty() {
@@ -164,32 +324,47 @@ impl LowerCtx {
         if is_method_flag(&flag) {
             let method = cli_value(arg)?.to_ascii_uppercase();
             return Ok(Some(
-                self.push_predicate(Predicate::MethodEq(Arc::from(method))),
+                self.push_predicate(Predicate::MethodEq(Arc::from(method)), arg.span),
             ));
         }
 
         if is_header_flag(&flag) {
             let raw = cli_value(arg)?;
             let (key, value) = split_header_assignment(raw);
-            return Ok(Some(self.push_predicate(Predicate::HeaderEq {
-                key: Arc::from(key.to_ascii_lowercase()),
-                value: Arc::from(value),
-            })));
+            return Ok(Some(self.push_predicate(
+                Predicate::HeaderEq {
+                    key: Arc::from(key.to_ascii_lowercase()),
+                    value: Arc::from(value),
+                },
+                arg.span,
+            )));
         }
 
         if is_query_flag(&flag) {
             let raw = cli_value(arg)?;
-            return Ok(Some(
-                self.push_predicate(Predicate::QueryContains(Arc::from(raw))),
-            ));
+            return Ok(Some(self.push_predicate(
+                Predicate::QueryContains(Arc::from(raw)),
+                arg.span,
+            )));
+        }
+
+        if is_sample_flag(&flag) {
+            let predicate = parse_sample(cli_value(arg)?)?;
+            return Ok(Some(self.push_predicate(predicate, arg.span)));
+        }
+
+        if is_time_flag(&flag) {
+            let predicate = parse_time_window(cli_value(arg)?)?;
+            return Ok(Some(self.push_predicate(predicate, arg.span)));
         }
 
         Ok(None)
     }
 
-    fn push_predicate(&mut self, predicate: Predicate) -> usize {
+    fn push_predicate(&mut self, predicate: Predicate, span: Span) -> usize {
         let index = self.predicates.len();
         self.predicates.push(predicate);
+        self.spans.push(self.ref_span.unwrap_or(span));
         index
     }
 }
@@ -236,6 +411,65 @@ fn is_query_flag(flag: &str) -> bool {
     flag.eq_ignore_ascii_case("-q") || flag.eq_ignore_ascii_case("--query")
 }
 
+fn is_sample_flag(flag: &str) -> bool {
+    flag.eq_ignore_ascii_case("--sample")
+}
+
+fn is_time_flag(flag: &str) -> bool {
+    flag.eq_ignore_ascii_case("--time")
+}
+
+/// `10%`, `0.5%`, `10%:client` or `10%:header=x-user-id`.
+fn parse_sample(raw: &str) -> Result<Predicate, CompileError> {
+    let invalid = || CompileError::InvalidSample(raw.to_string());
+    let (percent, key) = match raw.split_once(':') {
+        Some((percent, key)) => (percent, Some(key)),
+        None => (raw, None),
+    };
+    let percent: f64 = percent
+        .strip_suffix('%')
+        .unwrap_or(percent)
+        .parse()
+        .map_err(|_| invalid())?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(invalid());
+    }
+    let key = match key {
+        None => SampleKey::Request,
+        Some(key) if key.eq_ignore_ascii_case("client") => SampleKey::Client,
+        Some(key) => match key.split_once('=') {
+            Some((basis, header)) if basis.eq_ignore_ascii_case("header") && !header.is_empty() => {
+                SampleKey::Header(Arc::from(header.to_ascii_lowercase()))
+            }
+            _ => return Err(invalid()),
+        },
+    };
+    Ok(Predicate::Sample {
+        basis_points: (percent * 100.0).round() as u16,
+        key,
+    })
+}
+
+/// `HH:MM-HH:MM` in local time; `24:00` is accepted as an end.
+fn parse_time_window(raw: &str) -> Result<Predicate, CompileError> {
+    let invalid = || CompileError::InvalidTimeWindow(raw.to_string());
+    let minutes = |value: &str| -> Option<u16> {
+        let (hours, minutes) = value.split_once(':')?;
+        let (hours, minutes): (u16, u16) = (hours.parse().ok()?, minutes.parse().ok()?);
+        (minutes < 60 && (hours < 24 || (hours == 24 && minutes == 0)))
+            .then_some(hours * 60 + minutes)
+    };
+    let (start, end) = raw.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (
+        minutes(start).ok_or_else(invalid)?,
+        minutes(end).ok_or_else(invalid)?,
+    );
+    if start == end || start == 24 * 60 {
+        return Err(invalid());
+    }
+    Ok(Predicate::TimeWindow { start, end })
+}
+
 fn split_header_assignment(raw: &str) -> (&str, &str) {
     if let Some((key, value)) = raw.split_once('=') {
         (key, value)
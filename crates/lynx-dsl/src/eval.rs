@@ -3,7 +3,7 @@
 //! This module must not import the AST; [`crate::compile`] is the sole AST entry point.
 
 use crate::facts::RequestFacts;
-use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SegmentPattern};
+use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SampleKey, SegmentPattern};
 use crate::query::query_params_subset_match;
 
 /// Evaluate a compiled match program against request facts.
@@ -11,6 +11,15 @@ pub fn eval_program(program: &MatchProgram, facts: &RequestFacts) -> bool {
     eval_plan(&program.plan, &program.predicates, facts)
 }
 
+/// Whether the program matches for some outcome of its `--sample` predicates.
+///
+/// Samples count as unknown, so `NOT --sample 10%` can still match. A program
+/// that matches here but not under [`eval_program`] was sampled out for this
+/// request.
+pub fn eval_program_ignoring_samples(program: &MatchProgram, facts: &RequestFacts) -> bool {
+    eval_plan_unknown_samples(&program.plan, &program.predicates, facts) != Some(false)
+}
+
 pub fn eval_predicate(pred: &Predicate, facts: &RequestFacts) -> bool {
     match pred {
         Predicate::HostEq(expected) => host_matches(expected, &facts.host),
@@ -29,6 +38,16 @@ pub fn eval_predicate(pred: &Predicate, facts: &RequestFacts) -> bool {
             query_params_subset_match(expected, facts.query.as_deref())
         }
         Predicate::HeaderEq { key, value } => header_matches(facts, key, value),
+        Predicate::Sample { basis_points, key } => {
+            sample_bucket(facts, key).is_some_and(|bucket| bucket < u64::from(*basis_points))
+        }
+        Predicate::TimeWindow { start, end } => facts.minute_of_day.is_some_and(|minute| {
+            if start < end {
+                (*start..*end).contains(&minute)
+            } else {
+                minute >= *start || minute < *end
+            }
+        }),
     }
 }
 
@@ -47,6 +66,68 @@ fn eval_plan(plan: &EvalPlan, predicates: &[Predicate], facts: &RequestFacts) ->
     }
 }
 
+/// Three-valued [`eval_plan`]: `None` when the result depends on a `--sample`.
+fn eval_plan_unknown_samples(
+    plan: &EvalPlan,
+    predicates: &[Predicate],
+    facts: &RequestFacts,
+) -> Option<bool> {
+    match plan {
+        EvalPlan::Pred(index) => match predicates.get(*index) {
+            Some(Predicate::Sample { .. }) => None,
+            pred => Some(pred.is_some_and(|pred| eval_predicate(pred, facts))),
+        },
+        EvalPlan::All(plans) => {
+            let mut result = Some(true);
+            for child in plans {
+                match eval_plan_unknown_samples(child, predicates, facts) {
+                    Some(false) => return Some(false),
+                    None => result = None,
+                    Some(true) => {}
+                }
+            }
+            result
+        }
+        EvalPlan::Any(plans) => {
+            let mut result = Some(false);
+            for child in plans {
+                match eval_plan_unknown_samples(child, predicates, facts) {
+                    Some(true) => return Some(true),
+                    None => result = None,
+                    Some(false) => {}
+                }
+            }
+            result
+        }
+        EvalPlan::Not(inner) => eval_plan_unknown_samples(inner, predicates, facts).map(|hit| !hit),
+    }
+}
+
+/// Stable bucket in `0..10000`; `None` when the sticky key is missing.
+///
+/// The bucket does not depend on the percentage, so a client inside a 10%
+/// sample is also inside every larger sample of the same key.
+fn sample_bucket(facts: &RequestFacts, key: &SampleKey) -> Option<u64> {
+    let hash = match key {
+        SampleKey::Request => fnv1a(&facts.sample_seed.to_le_bytes()),
+        SampleKey::Client => fnv1a(facts.client.as_deref()?.as_bytes()),
+        SampleKey::Header(name) => {
+            let index = facts
+                .headers
+                .binary_search_by(|(header_key, _)| header_key.as_str().cmp(name))
+                .ok()?;
+            fnv1a(facts.headers[index].1.as_bytes())
+        }
+    };
+    Some(hash % 10_000)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
 fn host_matches(expected: &str, actual: &str) -> bool {
     if actual.eq_ignore_ascii_case(expected) {
         return true;
@@ -90,7 +171,7 @@ fn match_path(matcher: &PathMatcher, path: &str) -> bool {
 }
 
 /// Literal paths without globs match the path itself or any deeper path under that prefix.
-fn path_prefix_match(prefix: &str, path: &str) -> bool {
+pub(crate) fn path_prefix_match(prefix: &str, path: &str) -> bool {
     if !path.starts_with(prefix) {
         return false;
     }
@@ -142,6 +223,67 @@ fn match_path_segments(pattern: &[SegmentPattern], path: &[&str], pi: usize, ti:
     }
 }
 
+/// Values captured by the first path glob in `program` that matches `path`.
+///
+/// Each `*` captures one segment and each `**` the (possibly empty) run of
+/// segments it spans, joined with `/`. Empty when no glob matches.
+pub fn path_captures(program: &MatchProgram, path: &str) -> Vec<String> {
+    let path_segments = split_path_segments(path);
+    program
+        .predicates
+        .iter()
+        .find_map(|predicate| match predicate {
+            Predicate::PathGlob(PathMatcher::Segments(segments)) => {
+                let mut captures = Vec::new();
+                capture_path_segments(segments, &path_segments, 0, 0, &mut captures)
+                    .then_some(captures)
+            }
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn capture_path_segments(
+    pattern: &[SegmentPattern],
+    path: &[&str],
+    pi: usize,
+    ti: usize,
+    captures: &mut Vec<String>,
+) -> bool {
+    if pi == pattern.len() {
+        return ti == path.len();
+    }
+
+    match &pattern[pi] {
+        SegmentPattern::Literal(literal) => {
+            ti < path.len()
+                && path[ti].eq_ignore_ascii_case(literal)
+                && capture_path_segments(pattern, path, pi + 1, ti + 1, captures)
+        }
+        SegmentPattern::SingleWildcard => {
+            if ti >= path.len() {
+                return false;
+            }
+            captures.push(path[ti].to_string());
+            if capture_path_segments(pattern, path, pi + 1, ti + 1, captures) {
+                return true;
+            }
+            captures.pop();
+            false
+        }
+        SegmentPattern::MultiWildcard => {
+            for next in ti..=path.len() {
+                captures.push(path[ti..next].join("/"));
+                if capture_path_segments(pattern, path, pi + 1, next, captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,4 +311,17 @@ mod tests {
         assert!(match_path(&matcher, "/api/v1/events/track"));
         assert!(!match_path(&matcher, "/api/track/extra"));
     }
+
+    #[test]
+    fn path_captures_follow_wildcards() {
+        let program = crate::compile_match_expr("example.com AND /users/*/files/**").unwrap();
+        assert_eq!(
+            path_captures(&program, "/users/42/files/docs/a.txt"),
+            vec!["42".to_string(), "docs/a.txt".to_string()]
+        );
+        assert!(path_captures(&program, "/teams/42").is_empty());
+
+        let literal = crate::compile_match_expr("/users").unwrap();
+        assert!(path_captures(&literal, "/users/42").is_empty());
+    }
 }
@@ -0,0 +1,210 @@
+//! Explain how a compiled [`MatchProgram`] evaluates against request facts.
+//!
+//! Unlike [`crate::eval::eval_program`], every branch is evaluated (no
+//! short-circuit) so tooling can show which predicates held and which failed.
+//! The result of the root node always equals `eval_program` for the same input.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::Span;
+use crate::compile::{CompileError, SourceMap, compile_match_expr_with_source_map};
+use crate::eval::eval_predicate;
+use crate::facts::RequestFacts;
+use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SampleKey, SegmentPattern};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExplainKind {
+    All,
+    Any,
+    Not,
+    Predicate,
+}
+
+/// One node of the evaluation tree, mirroring [`EvalPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainNode {
+    pub kind: ExplainKind,
+    pub matched: bool,
+    /// Source range; group nodes cover the union of their children.
+    pub span: Option<Span>,
+    /// Index into `MatchProgram::predicates` for predicate nodes.
+    pub predicate: Option<usize>,
+    /// Human-readable predicate summary, e.g. `method = POST`.
+    pub description: Option<String>,
+    pub children: Vec<ExplainNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explanation {
+    pub matched: bool,
+    pub root: ExplainNode,
+}
+
+impl Explanation {
+    /// Nodes responsible for a non-match: failing predicates, plus `NOT` nodes
+    /// whose inner expression matched. Empty when the program matched.
+    pub fn failed_nodes(&self) -> Vec<&ExplainNode> {
+        let mut failed = Vec::new();
+        if !self.root.matched {
+            collect_failed(&self.root, &mut failed);
+        }
+        failed
+    }
+}
+
+/// Evaluate every node of `program` and record per-node results.
+///
+/// Pass the [`SourceMap`] from [`compile_match_expr_with_source_map`] to get spans.
+pub fn explain_program(
+    program: &MatchProgram,
+    source_map: Option<&SourceMap>,
+    facts: &RequestFacts,
+) -> Explanation {
+    let root = explain_plan(&program.plan, program, source_map, facts);
+    Explanation {
+        matched: root.matched,
+        root,
+    }
+}
+
+/// Compile `source` and explain it against `facts`.
+pub fn explain_match_expr(source: &str, facts: &RequestFacts) -> Result<Explanation, CompileError> {
+    let (program, source_map) = compile_match_expr_with_source_map(source)?;
+    Ok(explain_program(&program, Some(&source_map), facts))
+}
+
+fn explain_plan(
+    plan: &EvalPlan,
+    program: &MatchProgram,
+    source_map: Option<&SourceMap>,
+    facts: &RequestFacts,
+) -> ExplainNode {
+    match plan {
+        EvalPlan::Pred(index) => {
+            let predicate = program.predicates.get(*index);
+            ExplainNode {
+                kind: ExplainKind::Predicate,
+                matched: predicate.is_some_and(|pred| eval_predicate(pred, facts)),
+                span: source_map.and_then(|map| map.predicate_span(*index)),
+                predicate: Some(*index),
+                description: predicate.map(describe_predicate),
+                children: Vec::new(),
+            }
+        }
+        EvalPlan::All(plans) => {
+            let children = explain_children(plans, program, source_map, facts);
+            group_node(
+                ExplainKind::All,
+                children.iter().all(|child| child.matched),
+                children,
+            )
+        }
+        EvalPlan::Any(plans) => {
+            let children = explain_children(plans, program, source_map, facts);
+            group_node(
+                ExplainKind::Any,
+                children.iter().any(|child| child.matched),
+                children,
+            )
+        }
+        EvalPlan::Not(inner) => {
+            let child = explain_plan(inner, program, source_map, facts);
+            group_node(ExplainKind::Not, !child.matched, vec![child])
+        }
+    }
+}
+
+fn explain_children(
+    plans: &[EvalPlan],
+    program: &MatchProgram,
+    source_map: Option<&SourceMap>,
+    facts: &RequestFacts,
+) -> Vec<ExplainNode> {
+    plans
+        .iter()
+        .map(|plan| explain_plan(plan, program, source_map, facts))
+        .collect()
+}
+
+fn group_node(kind: ExplainKind, matched: bool, children: Vec<ExplainNode>) -> ExplainNode {
+    let span = children
+        .iter()
+        .filter_map(|child| child.span)
+        .reduce(Span::merge);
+    ExplainNode {
+        kind,
+        matched,
+        span,
+        predicate: None,
+        description: None,
+        children,
+    }
+}
+
+fn collect_failed<'a>(node: &'a ExplainNode, out: &mut Vec<&'a ExplainNode>) {
+    match node.kind {
+        ExplainKind::Predicate | ExplainKind::Not => out.push(node),
+        ExplainKind::All | ExplainKind::Any => {
+            for child in node.children.iter().filter(|child| !child.matched) {
+                collect_failed(child, out);
+            }
+        }
+    }
+}
+
+/// Short human-readable summary of a predicate, used by explain output.
+pub fn describe_predicate(predicate: &Predicate) -> String {
+    match predicate {
+        Predicate::HostEq(host) => format!("host = {host}"),
+        Predicate::SchemeEq(scheme) => format!("scheme = {scheme}"),
+        Predicate::PortEq(port) => format!("port = {port}"),
+        Predicate::PathGlob(matcher) => format!("path = {}", describe_path(matcher)),
+        Predicate::MethodEq(method) => format!("method = {method}"),
+        Predicate::QueryContains(value) => format!("query contains {value}"),
+        Predicate::QueryParamsAll(pairs) => {
+            let query = pairs
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join("&");
+            format!("query has {query}")
+        }
+        Predicate::HeaderEq { key, value } if value.is_empty() => format!("header {key} present"),
+        Predicate::HeaderEq { key, value } => format!("header {key} = {value}"),
+        Predicate::Sample { basis_points, key } => {
+            let percent = f64::from(*basis_points) / 100.0;
+            match key {
+                SampleKey::Request => format!("sample {percent}% of requests"),
+                SampleKey::Client => format!("sample {percent}% of clients"),
+                SampleKey::Header(name) => format!("sample {percent}% by header {name}"),
+            }
+        }
+        Predicate::TimeWindow { start, end } => format!(
+            "time {:02}:{:02}-{:02}:{:02}",
+            start / 60,
+            start % 60,
+            end / 60,
+            end % 60
+        ),
+    }
+}
+
+fn describe_path(matcher: &PathMatcher) -> String {
+    match matcher {
+        PathMatcher::Exact(path) => path.to_string(),
+        PathMatcher::Segments(segments) => {
+            let parts = segments
+                .iter()
+                .map(|segment| match segment {
+                    SegmentPattern::Literal(literal) => literal.as_ref(),
+                    SegmentPattern::SingleWildcard => "*",
+                    SegmentPattern::MultiWildcard => "**",
+                })
+                .collect::<Vec<_>>();
+            format!("/{}", parts.join("/"))
+        }
+    }
+}
@@ -13,6 +13,16 @@ pub struct RequestFacts {
     pub query: Option<String>,
     pub method: String,
     pub headers: Vec<(String, String)>,
+    /// Client identity (usually the peer IP) for `--sample N%:client`.
+    #[serde(default)]
+    pub client: Option<String>,
+    /// Per-request value hashed by `--sample N%`; callers pick it so every
+    /// evaluation of the same request lands in the same bucket.
+    #[serde(default)]
+    pub sample_seed: u64,
+    /// Local wall-clock minute (0..1440) for `--time`; unknown never matches.
+    #[serde(default)]
+    pub minute_of_day: Option<u16>,
 }
 
 impl RequestFacts {
@@ -30,6 +40,9 @@ pub struct RequestFactsBuilder {
     query: Option<String>,
     method: Option<String>,
     headers: Vec<(String, String)>,
+    client: Option<String>,
+    sample_seed: u64,
+    minute_of_day: Option<u16>,
 }
 
 impl RequestFactsBuilder {
@@ -69,6 +82,21 @@ impl RequestFactsBuilder {
         self
     }
 
+    pub fn client(mut self, client: impl Into<String>) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    pub fn sample_seed(mut self, seed: u64) -> Self {
+        self.sample_seed = seed;
+        self
+    }
+
+    pub fn minute_of_day(mut self, minute: u16) -> Self {
+        self.minute_of_day = Some(minute);
+        self
+    }
+
     pub fn build(self) -> RequestFacts {
         let mut headers = self.headers;
         headers.sort_by(|(left, _), (right, _)| left.cmp(right));
@@ -80,6 +108,9 @@ impl RequestFactsBuilder {
             query: self.query,
             method: self.method.unwrap_or_else(|| "GET".to_string()),
             headers,
+            client: self.client,
+            sample_seed: self.sample_seed,
+            minute_of_day: self.minute_of_day,
         }
     }
 }
@@ -104,6 +104,7 @@ fn format_primary(primary: &Primary, source: &str, base_indent: &str) -> String
             }
         }
         Primary::CliOnly(cli) => slice_span(source, cli.span),
+        Primary::Ref(name) => format!("@{}", name.value),
         Primary::Url { url, cli } => {
             let mut text = format_url(url);
             if let Some(cli_args) = cli {
@@ -23,6 +23,7 @@ pub enum HighlightKind {
     LongFlag,
     CliValue,
     Paren,
+    MatcherRef,
 }
 
 impl HighlightKind {
@@ -41,6 +42,7 @@ impl HighlightKind {
             Self::LongFlag => "LongFlag",
             Self::CliValue => "CliValue",
             Self::Paren => "Paren",
+            Self::MatcherRef => "MatcherRef",
         }
     }
 }
@@ -130,6 +132,7 @@ fn highlight_not_expr(not_expr: &NotExpr, source: &str, spans: &mut Vec<Highligh
 fn highlight_primary(primary: &Primary, source: &str, spans: &mut Vec<HighlightSpan>) {
     match primary {
         Primary::CliOnly(cli) => highlight_cli_args(cli, source, spans),
+        Primary::Ref(name) => push_span(spans, name.span, HighlightKind::MatcherRef),
         Primary::Url { url, cli } => {
             highlight_url(url, spans);
             if let Some(cli_args) = cli {
@@ -263,6 +266,7 @@ fn not_expr_end(not_expr: &NotExpr) -> usize {
 fn primary_start(primary: &Primary) -> usize {
     match primary {
         Primary::CliOnly(cli) => cli.span.start,
+        Primary::Ref(name) => name.span.start,
         Primary::Url { url, cli } => cli.as_ref().map(|c| c.span.start).unwrap_or(url.span.start),
         Primary::Grouped(expr) => expr.span.start,
     }
@@ -271,6 +275,7 @@ fn primary_start(primary: &Primary) -> usize {
 fn primary_end(primary: &Primary) -> usize {
     match primary {
         Primary::CliOnly(cli) => cli.span.end,
+        Primary::Ref(name) => name.span.end,
         Primary::Url { url, .. } => url.span.end,
         Primary::Grouped(expr) => expr.span.end,
     }
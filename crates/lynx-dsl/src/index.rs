@@ -0,0 +1,371 @@
+//! Candidate pre-filter over many compiled [`MatchProgram`]s.
+//!
+//! [`RuleIndex`] derives necessary conditions from each program's plan (host,
+//! method, leading path segments) and indexes them in a host hash map, a
+//! per-method bitmap and a path-segment trie. [`RuleIndex::candidates`] returns
+//! a superset of the programs that can match, in program order; evaluating
+//! only those gives the same result as scanning every program.
+//!
+//! Anything the index cannot reason about (negations, query/header/scheme/port
+//! predicates, substring hosts) is left unconstrained rather than guessed.
+
+use std::collections::HashMap;
+
+use crate::facts::RequestFacts;
+use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SegmentPattern};
+
+const METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "CONNECT", "TRACE",
+];
+/// Slot for methods outside [`METHODS`]; any such requirement keeps this slot.
+const OTHER_METHOD: usize = METHODS.len();
+const METHOD_SLOTS: usize = METHODS.len() + 1;
+const ALL_METHODS: u16 = (1 << METHOD_SLOTS) - 1;
+
+#[derive(Debug, Clone, Default)]
+pub struct RuleIndex {
+    len: usize,
+    /// Dotted hosts (lowercase): matched by equality or as a domain suffix.
+    hosts: HashMap<String, Bitset>,
+    /// Dot-less hosts (lowercase): matched as a substring, checked linearly.
+    host_substrings: Vec<(String, usize)>,
+    any_host: Bitset,
+    methods: [Bitset; METHOD_SLOTS],
+    paths: PathTrie,
+}
+
+impl RuleIndex {
+    pub fn build<'a>(programs: impl IntoIterator<Item = &'a MatchProgram>) -> Self {
+        let mut index = Self::default();
+        for (position, program) in programs.into_iter().enumerate() {
+            index.insert(position, &program_keys(program));
+            index.len = position + 1;
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Positions of programs that may match `facts`, ascending.
+    pub fn candidates(&self, facts: &RequestFacts) -> Vec<usize> {
+        let mut selected = self.host_candidates(&facts.host);
+        selected.intersect(&self.methods[method_slot(&facts.method)]);
+        if !selected.is_empty() {
+            selected.intersect(&self.path_candidates(&facts.path));
+        }
+        selected.ones().collect()
+    }
+
+    fn insert(&mut self, position: usize, keys: &Keys) {
+        match &keys.hosts {
+            None => self.any_host.set(position),
+            Some(hosts) => {
+                for host in hosts {
+                    if host.contains('.') {
+                        self.hosts.entry(host.clone()).or_default().set(position);
+                    } else {
+                        self.host_substrings.push((host.clone(), position));
+                    }
+                }
+            }
+        }
+
+        for (slot, methods) in self.methods.iter_mut().enumerate() {
+            if keys.methods & (1 << slot) != 0 {
+                methods.set(position);
+            }
+        }
+
+        match &keys.paths {
+            None => self.paths.root.rules.set(position),
+            Some(prefixes) => {
+                for prefix in prefixes {
+                    self.paths.insert(prefix, position);
+                }
+            }
+        }
+    }
+
+    fn host_candidates(&self, host: &str) -> Bitset {
+        let mut selected = self.any_host.clone();
+        let host = host.to_ascii_lowercase();
+        if let Some(rules) = self.hosts.get(&host) {
+            selected.union(rules);
+        }
+        // `a.b.example.com` also matches keys `b.example.com`, `example.com`, `com`.
+        for (dot, _) in host.match_indices('.') {
+            if let Some(rules) = self.hosts.get(&host[dot + 1..]) {
+                selected.union(rules);
+            }
+        }
+        for (needle, position) in &self.host_substrings {
+            if host.contains(needle.as_str()) {
+                selected.set(*position);
+            }
+        }
+        selected
+    }
+
+    fn path_candidates(&self, path: &str) -> Bitset {
+        let mut selected = self.paths.root.rules.clone();
+        let mut node = &self.paths.root;
+        for segment in path_segments(path) {
+            let Some(child) = node.children.get(&segment) else {
+                break;
+            };
+            selected.union(&child.rules);
+            node = child;
+        }
+        selected
+    }
+}
+
+/// Necessary conditions of one program. `None` means unconstrained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Keys {
+    /// Any of these (lowercase) hosts must match.
+    hosts: Option<Vec<String>>,
+    /// Bitmask of [`METHODS`] slots the method must fall into.
+    methods: u16,
+    /// The path must start with one of these (lowercase) segment lists.
+    paths: Option<Vec<Vec<String>>>,
+}
+
+impl Keys {
+    fn unconstrained() -> Self {
+        Self {
+            hosts: None,
+            methods: ALL_METHODS,
+            paths: None,
+        }
+    }
+
+    /// Every condition of `self` and `other` is required: keep the tighter one.
+    fn and(mut self, other: Self) -> Self {
+        self.hosts = match (self.hosts, other.hosts) {
+            (Some(left), Some(right)) => Some(if right.len() < left.len() {
+                right
+            } else {
+                left
+            }),
+            (left, right) => left.or(right),
+        };
+        self.methods &= other.methods;
+        self.paths = match (self.paths, other.paths) {
+            (Some(left), Some(right)) => Some(if min_depth(&right) > min_depth(&left) {
+                right
+            } else {
+                left
+            }),
+            (left, right) => left.or(right),
+        };
+        self
+    }
+
+    /// Either side may hold: a dimension stays constrained only if both are.
+    fn or(mut self, other: Self) -> Self {
+        self.hosts = match (self.hosts, other.hosts) {
+            (Some(mut left), Some(right)) => {
+                left.extend(right);
+                Some(left)
+            }
+            _ => None,
+        };
+        self.methods |= other.methods;
+        self.paths = match (self.paths, other.paths) {
+            (Some(mut left), Some(right)) => {
+                left.extend(right);
+                Some(left)
+            }
+            _ => None,
+        };
+        self
+    }
+}
+
+fn min_depth(prefixes: &[Vec<String>]) -> usize {
+    prefixes.iter().map(Vec::len).min().unwrap_or(0)
+}
+
+fn program_keys(program: &MatchProgram) -> Keys {
+    plan_keys(&program.plan, &program.predicates)
+}
+
+fn plan_keys(plan: &EvalPlan, predicates: &[Predicate]) -> Keys {
+    match plan {
+        EvalPlan::Pred(index) => match predicates.get(*index) {
+            Some(predicate) => predicate_keys(predicate),
+            // Out-of-range predicates never match; leaving them unconstrained is still sound.
+            None => Keys::unconstrained(),
+        },
+        EvalPlan::All(plans) => plans
+            .iter()
+            .map(|plan| plan_keys(plan, predicates))
+            .fold(Keys::unconstrained(), Keys::and),
+        EvalPlan::Any(plans) => plans
+            .iter()
+            .map(|plan| plan_keys(plan, predicates))
+            .reduce(Keys::or)
+            // An empty `Any` never matches, any key set is sound.
+            .unwrap_or_else(Keys::unconstrained),
+        EvalPlan::Not(_) => Keys::unconstrained(),
+    }
+}
+
+fn predicate_keys(predicate: &Predicate) -> Keys {
+    let mut keys = Keys::unconstrained();
+    match predicate {
+        Predicate::HostEq(host) => keys.hosts = Some(vec![host.to_ascii_lowercase()]),
+        Predicate::MethodEq(method) => keys.methods = 1 << method_slot(method),
+        Predicate::PathGlob(matcher) => {
+            let prefix = path_prefix(matcher);
+            if !prefix.is_empty() {
+                keys.paths = Some(vec![prefix]);
+            }
+        }
+        _ => {}
+    }
+    keys
+}
+
+fn method_slot(method: &str) -> usize {
+    METHODS
+        .iter()
+        .position(|known| known.eq_ignore_ascii_case(method))
+        .unwrap_or(OTHER_METHOD)
+}
+
+/// Leading segments every matching path must start with (lowercase).
+///
+/// Literal paths match themselves and anything below, so all of their segments
+/// count; globs contribute the literal segments before the first wildcard.
+fn path_prefix(matcher: &PathMatcher) -> Vec<String> {
+    match matcher {
+        PathMatcher::Exact(path) => path_segments(path).collect(),
+        PathMatcher::Segments(segments) => segments
+            .iter()
+            .map_while(|segment| match segment {
+                SegmentPattern::Literal(literal) => Some(literal.to_ascii_lowercase()),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+fn path_segments(path: &str) -> impl Iterator<Item = String> + '_ {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+#[derive(Debug, Clone, Default)]
+struct PathTrie {
+    root: PathNode,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PathNode {
+    /// Programs whose path prefix ends at this node.
+    rules: Bitset,
+    children: HashMap<String, PathNode>,
+}
+
+impl PathTrie {
+    fn insert(&mut self, prefix: &[String], position: usize) {
+        let mut node = &mut self.root;
+        for segment in prefix {
+            node = node.children.entry(segment.clone()).or_default();
+        }
+        node.rules.set(position);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Bitset {
+    words: Vec<u64>,
+}
+
+impl Bitset {
+    fn set(&mut self, position: usize) {
+        let word = position / 64;
+        if self.words.len() <= word {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (position % 64);
+    }
+
+    fn union(&mut self, other: &Bitset) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    fn intersect(&mut self, other: &Bitset) {
+        self.words.truncate(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(index * 64 + bit)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::compile_match_expr;
+
+    fn keys(source: &str) -> Keys {
+        program_keys(&compile_match_expr(source).expect("compile"))
+    }
+
+    #[test]
+    fn conjunction_keeps_host_method_and_path() {
+        let keys = keys("api.example.com /v1/users/* -X POST");
+        assert_eq!(keys.hosts, Some(vec!["api.example.com".to_string()]));
+        assert_eq!(keys.methods, 1 << 1);
+        assert_eq!(
+            keys.paths,
+            Some(vec![vec!["v1".to_string(), "users".to_string()]])
+        );
+    }
+
+    #[test]
+    fn negation_is_unconstrained() {
+        assert_eq!(keys("NOT example.com"), Keys::unconstrained());
+    }
+
+    #[test]
+    fn bitset_ones_are_ascending() {
+        let mut bits = Bitset::default();
+        for position in [130, 3, 64, 0] {
+            bits.set(position);
+        }
+        assert_eq!(bits.ones().collect::<Vec<_>>(), vec![0, 3, 64, 130]);
+    }
+}
@@ -27,7 +27,32 @@ pub enum Predicate {
     MethodEq(Arc<str>),
     QueryContains(Arc<str>),
     QueryParamsAll(Vec<(Arc<str>, Arc<str>)>),
-    HeaderEq { key: Arc<str>, value: Arc<str> },
+    HeaderEq {
+        key: Arc<str>,
+        value: Arc<str>,
+    },
+    /// `--sample`: true for `basis_points` out of 10000 buckets of `key`.
+    Sample {
+        basis_points: u16,
+        key: SampleKey,
+    },
+    /// `--time`: local minutes of day, `start` inclusive, `end` exclusive.
+    /// Wraps past midnight when `end <= start`.
+    TimeWindow {
+        start: u16,
+        end: u16,
+    },
+}
+
+/// What a sample bucket is derived from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleKey {
+    /// Each request independently ([`crate::RequestFacts::sample_seed`]).
+    Request,
+    /// Sticky per client.
+    Client,
+    /// Sticky per value of this (lowercase) header.
+    Header(Arc<str>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -55,4 +80,11 @@ impl MatchProgram {
     pub fn new(predicates: Vec<Predicate>, plan: EvalPlan) -> Self {
         Self { predicates, plan }
     }
+
+    /// Whether any predicate is a `--sample`.
+    pub fn has_sampling(&self) -> bool {
+        self.predicates
+            .iter()
+            .any(|predicate| matches!(predicate, Predicate::Sample { .. }))
+    }
 }
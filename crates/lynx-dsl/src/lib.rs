@@ -2,11 +2,15 @@ pub mod ast;
 pub mod compile;
 pub mod error;
 pub mod eval;
+pub mod explain;
 pub mod expr_parser;
 pub mod facts;
 pub mod format;
 pub mod highlight;
+pub mod index;
 pub mod ir;
+pub mod lint;
+pub mod matchers;
 pub mod parser;
 pub mod query;
 pub mod span;
@@ -16,17 +20,34 @@ pub mod validate;
 pub mod wasm;
 
 pub use ast::{Program, Span};
-pub use compile::{CompileError, compile_match_expr};
+pub use compile::{
+    CompileError, SourceMap, compile_match_expr, compile_match_expr_with_matchers,
+    compile_match_expr_with_source_map, compile_with_matchers,
+};
 pub use error::{FormatError, ParseError};
-pub use eval::{eval_predicate, eval_program};
+pub use eval::{eval_predicate, eval_program, eval_program_ignoring_samples, path_captures};
+pub use explain::{
+    ExplainKind, ExplainNode, Explanation, describe_predicate, explain_match_expr, explain_program,
+};
 pub use facts::{RequestFacts, RequestFactsBuilder};
 pub use format::{
     DslFormatValidationResult, can_format_dsl, format_dsl, is_dsl_formatted, validate_dsl_document,
 };
 pub use highlight::HighlightSpan;
-pub use ir::{EvalPlan, MatchProgram, Predicate};
+pub use index::RuleIndex;
+pub use ir::{EvalPlan, MatchProgram, Predicate, SampleKey};
+pub use lint::{
+    LintRule, RuleDiagnostic, RuleEffect, RuleLintCode, program_subsumes, programs_may_overlap,
+    validate_rules, validate_rules_with_matchers,
+};
+pub use matchers::{
+    MatcherDefinition, NamedMatchers, matcher_diagnostics, matcher_references,
+    parse_matcher_definitions,
+};
 pub use parser::{
     ParseProgramOutcome, has_parse_errors, mask_line_comments, normalize_logic_keywords,
     parse_program, parse_program_partial, prepare_source,
 };
-pub use validate::{Diagnostic, ValidationResult, collect_syntax_diagnostics, validate};
+pub use validate::{
+    Diagnostic, ValidationResult, collect_syntax_diagnostics, validate, validate_with_matchers,
+};
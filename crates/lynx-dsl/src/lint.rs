@@ -0,0 +1,529 @@
+//! Static analysis across compiled match programs.
+//!
+//! [`program_subsumes`] and [`programs_may_overlap`] are conservative: they only
+//! answer `true` (respectively `false`) when it holds for every request. Programs
+//! are normalised to disjunctive normal form over predicate literals; programs
+//! too large to expand are treated as unknown.
+//!
+//! [`validate_rules`] uses them to report rules that can never fire because an
+//! earlier rule always answers first, and overlapping rules whose handlers
+//! write the same thing differently.
+
+use serde::{Deserialize, Serialize};
+
+use crate::compile::compile_match_expr_with_matchers;
+use crate::eval::path_prefix_match;
+use crate::ir::{EvalPlan, MatchProgram, PathMatcher, Predicate, SegmentPattern};
+use crate::matchers::NamedMatchers;
+use crate::validate::Diagnostic;
+
+/// Upper bound on DNF conjunctions before a program is treated as unknown.
+const MAX_CONJUNCTIONS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Literal {
+    predicate: usize,
+    negated: bool,
+}
+
+type Conjunction = Vec<Literal>;
+
+/// Whether every request matched by `narrow` is also matched by `broad`.
+pub fn program_subsumes(broad: &MatchProgram, narrow: &MatchProgram) -> bool {
+    let (Some(broad_dnf), Some(narrow_dnf)) = (to_dnf(broad), to_dnf(narrow)) else {
+        return false;
+    };
+    narrow_dnf.iter().all(|narrow_conj| {
+        conjunction_unsatisfiable(narrow, narrow_conj)
+            || broad_dnf.iter().any(|broad_conj| {
+                broad_conj.iter().all(|broad_lit| {
+                    narrow_conj
+                        .iter()
+                        .any(|narrow_lit| literal_implies(narrow, *narrow_lit, broad, *broad_lit))
+                })
+            })
+    })
+}
+
+/// Whether some request could be matched by both programs.
+pub fn programs_may_overlap(left: &MatchProgram, right: &MatchProgram) -> bool {
+    let (Some(left_dnf), Some(right_dnf)) = (to_dnf(left), to_dnf(right)) else {
+        return true;
+    };
+    left_dnf.iter().any(|left_conj| {
+        right_dnf.iter().any(|right_conj| {
+            !conjunction_unsatisfiable(left, left_conj)
+                && !conjunction_unsatisfiable(right, right_conj)
+                && !left_conj.iter().any(|left_lit| {
+                    right_conj
+                        .iter()
+                        .any(|right_lit| literals_exclusive(left, *left_lit, right, *right_lit))
+                })
+        })
+    })
+}
+
+fn to_dnf(program: &MatchProgram) -> Option<Vec<Conjunction>> {
+    plan_dnf(&program.plan, false)
+}
+
+fn plan_dnf(plan: &EvalPlan, negated: bool) -> Option<Vec<Conjunction>> {
+    match plan {
+        EvalPlan::Pred(predicate) => Some(vec![vec![Literal {
+            predicate: *predicate,
+            negated,
+        }]]),
+        EvalPlan::Not(inner) => plan_dnf(inner, !negated),
+        EvalPlan::All(plans) if !negated => product(plans, negated),
+        EvalPlan::Any(plans) if negated => product(plans, negated),
+        EvalPlan::All(plans) | EvalPlan::Any(plans) => {
+            let mut out = Vec::new();
+            for plan in plans {
+                out.extend(plan_dnf(plan, negated)?);
+                if out.len() > MAX_CONJUNCTIONS {
+                    return None;
+                }
+            }
+            Some(out)
+        }
+    }
+}
+
+fn product(plans: &[EvalPlan], negated: bool) -> Option<Vec<Conjunction>> {
+    let mut out: Vec<Conjunction> = vec![Vec::new()];
+    for plan in plans {
+        let child = plan_dnf(plan, negated)?;
+        if out.len() * child.len() > MAX_CONJUNCTIONS {
+            return None;
+        }
+        out = out
+            .iter()
+            .flat_map(|left| {
+                child.iter().map(move |right| {
+                    let mut conj = left.clone();
+                    conj.extend(right.iter().copied());
+                    conj
+                })
+            })
+            .collect();
+    }
+    Some(out)
+}
+
+fn conjunction_unsatisfiable(program: &MatchProgram, conj: &[Literal]) -> bool {
+    conj.iter().enumerate().any(|(index, left)| {
+        conj[index + 1..]
+            .iter()
+            .any(|right| literals_exclusive(program, *left, program, *right))
+    })
+}
+
+/// `narrow_lit` holding guarantees `broad_lit` holds.
+fn literal_implies(
+    narrow: &MatchProgram,
+    narrow_lit: Literal,
+    broad: &MatchProgram,
+    broad_lit: Literal,
+) -> bool {
+    let (Some(narrow_pred), Some(broad_pred)) = (
+        narrow.predicates.get(narrow_lit.predicate),
+        broad.predicates.get(broad_lit.predicate),
+    ) else {
+        return false;
+    };
+    match (narrow_lit.negated, broad_lit.negated) {
+        (false, false) => predicate_implies(narrow_pred, broad_pred),
+        (true, true) => predicate_implies(broad_pred, narrow_pred),
+        (false, true) => predicates_disjoint(narrow_pred, broad_pred),
+        (true, false) => false,
+    }
+}
+
+/// The two literals can never hold together.
+fn literals_exclusive(
+    left: &MatchProgram,
+    left_lit: Literal,
+    right: &MatchProgram,
+    right_lit: Literal,
+) -> bool {
+    let (Some(left_pred), Some(right_pred)) = (
+        left.predicates.get(left_lit.predicate),
+        right.predicates.get(right_lit.predicate),
+    ) else {
+        return false;
+    };
+    match (left_lit.negated, right_lit.negated) {
+        (false, false) => predicates_disjoint(left_pred, right_pred),
+        (false, true) => predicate_implies(left_pred, right_pred),
+        (true, false) => predicate_implies(right_pred, left_pred),
+        (true, true) => false,
+    }
+}
+
+/// Every request satisfying `narrow` also satisfies `broad`.
+fn predicate_implies(narrow: &Predicate, broad: &Predicate) -> bool {
+    match (narrow, broad) {
+        (Predicate::HostEq(narrow), Predicate::HostEq(broad)) => host_implies(narrow, broad),
+        (Predicate::SchemeEq(narrow), Predicate::SchemeEq(broad))
+        | (Predicate::MethodEq(narrow), Predicate::MethodEq(broad)) => {
+            narrow.eq_ignore_ascii_case(broad)
+        }
+        (Predicate::PortEq(narrow), Predicate::PortEq(broad)) => narrow == broad,
+        (Predicate::PathGlob(narrow), Predicate::PathGlob(broad)) => path_implies(narrow, broad),
+        (Predicate::QueryContains(narrow), Predicate::QueryContains(broad)) => {
+            narrow.contains(broad.as_ref())
+        }
+        (Predicate::QueryParamsAll(narrow), Predicate::QueryParamsAll(broad)) => {
+            if broad.is_empty() {
+                narrow.is_empty()
+            } else {
+                broad.iter().all(|pair| narrow.contains(pair))
+            }
+        }
+        (
+            Predicate::HeaderEq {
+                key: narrow_key,
+                value: narrow_value,
+            },
+            Predicate::HeaderEq {
+                key: broad_key,
+                value: broad_value,
+            },
+        ) => {
+            narrow_key == broad_key
+                && (broad_value.is_empty() || narrow_value.eq_ignore_ascii_case(broad_value))
+        }
+        // Buckets don't depend on the percentage, so a smaller sample of the
+        // same key is a subset of a larger one.
+        (
+            Predicate::Sample {
+                basis_points: narrow_points,
+                key: narrow_key,
+            },
+            Predicate::Sample {
+                basis_points: broad_points,
+                key: broad_key,
+            },
+        ) => narrow_key == broad_key && narrow_points <= broad_points,
+        (
+            Predicate::TimeWindow {
+                start: narrow_start,
+                end: narrow_end,
+            },
+            Predicate::TimeWindow {
+                start: broad_start,
+                end: broad_end,
+            },
+        ) => {
+            let broad = time_ranges(*broad_start, *broad_end);
+            time_ranges(*narrow_start, *narrow_end)
+                .iter()
+                .all(|(from, to)| broad.iter().any(|(start, end)| start <= from && to <= end))
+        }
+        _ => false,
+    }
+}
+
+/// No request satisfies both predicates.
+fn predicates_disjoint(left: &Predicate, right: &Predicate) -> bool {
+    match (left, right) {
+        (Predicate::HostEq(left), Predicate::HostEq(right)) => {
+            left.contains('.')
+                && right.contains('.')
+                && !host_implies(left, right)
+                && !host_implies(right, left)
+        }
+        (Predicate::SchemeEq(left), Predicate::SchemeEq(right))
+        | (Predicate::MethodEq(left), Predicate::MethodEq(right)) => {
+            !left.eq_ignore_ascii_case(right)
+        }
+        (Predicate::PortEq(left), Predicate::PortEq(right)) => left != right,
+        (Predicate::PathGlob(left), Predicate::PathGlob(right)) => paths_disjoint(left, right),
+        (
+            Predicate::HeaderEq {
+                key: left_key,
+                value: left_value,
+            },
+            Predicate::HeaderEq {
+                key: right_key,
+                value: right_value,
+            },
+        ) => {
+            left_key == right_key
+                && !left_value.is_empty()
+                && !right_value.is_empty()
+                && !left_value.eq_ignore_ascii_case(right_value)
+        }
+        (
+            Predicate::TimeWindow {
+                start: left_start,
+                end: left_end,
+            },
+            Predicate::TimeWindow {
+                start: right_start,
+                end: right_end,
+            },
+        ) => {
+            let right = time_ranges(*right_start, *right_end);
+            time_ranges(*left_start, *left_end)
+                .iter()
+                .all(|(from, to)| right.iter().all(|(start, end)| to <= start || end <= from))
+        }
+        _ => false,
+    }
+}
+
+/// A `--time` window as half-open minute ranges, split at midnight when it wraps.
+fn time_ranges(start: u16, end: u16) -> Vec<(u16, u16)> {
+    if start < end {
+        vec![(start, end)]
+    } else if end == 0 {
+        vec![(start, 24 * 60)]
+    } else {
+        vec![(start, 24 * 60), (0, end)]
+    }
+}
+
+/// Hosts with a dot match themselves and subdomains; others match as a substring.
+fn host_implies(narrow: &str, broad: &str) -> bool {
+    let narrow = narrow.to_ascii_lowercase();
+    let broad = broad.to_ascii_lowercase();
+    if broad.contains('.') {
+        narrow.contains('.') && (narrow == broad || narrow.ends_with(&format!(".{broad}")))
+    } else {
+        narrow.contains(&broad)
+    }
+}
+
+fn path_implies(narrow: &PathMatcher, broad: &PathMatcher) -> bool {
+    match (narrow, broad) {
+        (PathMatcher::Exact(narrow), PathMatcher::Exact(broad)) => path_prefix_match(broad, narrow),
+        (PathMatcher::Segments(narrow), PathMatcher::Segments(broad)) if narrow == broad => true,
+        (narrow, PathMatcher::Segments(broad)) => {
+            // Only `literal/.../**` patterns are compared: they accept any path below their literals.
+            let Some((SegmentPattern::MultiWildcard, literals)) = broad.split_last() else {
+                return false;
+            };
+            let Some(literals) = literal_segments(literals) else {
+                return false;
+            };
+            let fixed = leading_literals(narrow);
+            fixed.len() >= literals.len()
+                && literals
+                    .iter()
+                    .zip(&fixed)
+                    .all(|(broad, narrow)| broad.eq_ignore_ascii_case(narrow))
+        }
+        _ => false,
+    }
+}
+
+fn paths_disjoint(left: &PathMatcher, right: &PathMatcher) -> bool {
+    if let (PathMatcher::Exact(left), PathMatcher::Exact(right)) = (left, right) {
+        return !path_prefix_match(left, right) && !path_prefix_match(right, left);
+    }
+    if let (PathMatcher::Segments(_), PathMatcher::Segments(_)) = (left, right) {
+        return leading_literals(left)
+            .iter()
+            .zip(leading_literals(right).iter())
+            .any(|(left, right)| !left.eq_ignore_ascii_case(right));
+    }
+    false
+}
+
+fn literal_segments(segments: &[SegmentPattern]) -> Option<Vec<&str>> {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            SegmentPattern::Literal(literal) => Some(literal.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Segments every path accepted by `matcher` starts with.
+fn leading_literals(matcher: &PathMatcher) -> Vec<String> {
+    match matcher {
+        PathMatcher::Exact(path) => path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect(),
+        PathMatcher::Segments(segments) => segments
+            .iter()
+            .map_while(|segment| match segment {
+                SegmentPattern::Literal(literal) => Some(literal.to_string()),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+/// A value a rule's handlers write; two rules writing the same `slot` differently conflict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleEffect {
+    /// What is written, e.g. `response`, `upstream`, `request.header.x-env`.
+    pub slot: String,
+    pub value: String,
+}
+
+/// Input to [`validate_rules`]: one rule, listed in evaluation (priority) order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintRule {
+    pub name: String,
+    pub match_expr: String,
+    pub enabled: bool,
+    /// Execution order and label of the earliest handler that answers locally.
+    pub terminal: Option<(i32, String)>,
+    /// Execution order of the earliest enabled handler.
+    pub first_order: Option<i32>,
+    pub effects: Vec<RuleEffect>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleLintCode {
+    InvalidMatch,
+    Shadowed,
+    Conflict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDiagnostic {
+    /// Index of the reported rule in the input.
+    pub rule: usize,
+    /// Index of the rule it is shadowed by or conflicts with.
+    pub related: Option<usize>,
+    pub code: RuleLintCode,
+    /// Range within the reported rule's `match_expr`.
+    pub diagnostic: Diagnostic,
+}
+
+/// Report rules that can never fire and overlapping rules with conflicting handlers.
+///
+/// Shadowed rules are errors; conflicts are warnings. Disabled rules are skipped.
+pub fn validate_rules(rules: &[LintRule]) -> Vec<RuleDiagnostic> {
+    validate_rules_with_matchers(rules, &NamedMatchers::default())
+}
+
+/// Like [`validate_rules`], resolving `@name` references against `matchers`.
+pub fn validate_rules_with_matchers(
+    rules: &[LintRule],
+    matchers: &NamedMatchers,
+) -> Vec<RuleDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut programs: Vec<Option<MatchProgram>> = Vec::with_capacity(rules.len());
+    for (index, rule) in rules.iter().enumerate() {
+        if !rule.enabled {
+            programs.push(None);
+            continue;
+        }
+        match compile_match_expr_with_matchers(rule.match_expr.trim(), matchers) {
+            Ok(program) => programs.push(Some(program)),
+            Err(error) => {
+                diagnostics.push(rule_diagnostic(
+                    rule,
+                    index,
+                    None,
+                    RuleLintCode::InvalidMatch,
+                    "error",
+                    format!("Invalid matchExpr: {error}"),
+                ));
+                programs.push(None);
+            }
+        }
+    }
+
+    for (index, rule) in rules.iter().enumerate() {
+        let Some(program) = &programs[index] else {
+            continue;
+        };
+
+        let shadow = rule.first_order.and_then(|first_order| {
+            (0..index).find_map(|earlier| {
+                let (order, label) = rules[earlier].terminal.as_ref()?;
+                let earlier_program = programs[earlier].as_ref()?;
+                (first_order >= *order && program_subsumes(earlier_program, program))
+                    .then_some((earlier, label))
+            })
+        });
+        if let Some((earlier, label)) = shadow {
+            diagnostics.push(rule_diagnostic(
+                rule,
+                index,
+                Some(earlier),
+                RuleLintCode::Shadowed,
+                "error",
+                format!(
+                    "Rule never fires: '{}' matches every request this rule matches and answers first ({label})",
+                    rules[earlier].name
+                ),
+            ));
+            continue;
+        }
+
+        for earlier in 0..index {
+            let Some(earlier_program) = &programs[earlier] else {
+                continue;
+            };
+            let slots = conflicting_slots(&rules[earlier].effects, &rule.effects);
+            if slots.is_empty() || !programs_may_overlap(earlier_program, program) {
+                continue;
+            }
+            diagnostics.push(rule_diagnostic(
+                rule,
+                index,
+                Some(earlier),
+                RuleLintCode::Conflict,
+                "warning",
+                format!(
+                    "Overlaps '{}' and sets {} differently",
+                    rules[earlier].name,
+                    slots.join(", ")
+                ),
+            ));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.rule);
+    diagnostics
+}
+
+fn conflicting_slots(left: &[RuleEffect], right: &[RuleEffect]) -> Vec<String> {
+    let mut slots: Vec<String> = left
+        .iter()
+        .filter(|left| {
+            right
+                .iter()
+                .any(|right| right.slot == left.slot && right.value != left.value)
+        })
+        .map(|effect| effect.slot.clone())
+        .collect();
+    slots.sort();
+    slots.dedup();
+    slots
+}
+
+fn rule_diagnostic(
+    rule: &LintRule,
+    index: usize,
+    related: Option<usize>,
+    code: RuleLintCode,
+    severity: &str,
+    message: String,
+) -> RuleDiagnostic {
+    RuleDiagnostic {
+        rule: index,
+        related,
+        code,
+        diagnostic: Diagnostic {
+            from: 0,
+            to: rule.match_expr.len().max(1),
+            severity: severity.to_string(),
+            message,
+        },
+    }
+}
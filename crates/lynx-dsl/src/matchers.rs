@@ -0,0 +1,247 @@
+//! Named matchers: project-level `@name = expr` definitions that match
+//! expressions reference as `@name`.
+//!
+//! References are resolved at compile time (see
+//! [`crate::compile::compile_match_expr_with_matchers`]); the evaluator never
+//! sees them.
+
+use std::collections::BTreeMap;
+
+use crate::ast::{AndExpr, Expr, NotExpr, Primary, Program, Span, Spanned};
+use crate::compile::{CompileError, resolve_matcher_ref};
+use crate::error::ParseError;
+use crate::parser::{parse_program, prepare_source};
+use crate::validate::Diagnostic;
+
+/// Matcher definitions by name (without the leading `@`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamedMatchers {
+    definitions: BTreeMap<String, String>,
+}
+
+impl NamedMatchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a definition; a leading `@` in `name` is ignored.
+    pub fn insert(&mut self, name: impl AsRef<str>, expr: impl Into<String>) {
+        self.definitions
+            .insert(normalize_matcher_name(name.as_ref()), expr.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.definitions
+            .get(name.trim_start_matches('@'))
+            .map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.definitions
+            .iter()
+            .map(|(name, expr)| (name.as_str(), expr.as_str()))
+    }
+
+    /// Definitions that cannot be resolved, by name: invalid names or
+    /// syntax, undefined references and cycles.
+    pub fn check(&self) -> Vec<(String, CompileError)> {
+        self.definitions
+            .keys()
+            .filter_map(|name| {
+                if !is_valid_matcher_name(name) {
+                    return Some((
+                        name.clone(),
+                        CompileError::Parse(ParseError::Syntax {
+                            span: Span::new(0, name.len() + 1),
+                            message: format!("invalid matcher name @{name}"),
+                        }),
+                    ));
+                }
+                let reference = Spanned::new(name.clone(), Span::new(0, name.len() + 1));
+                resolve_matcher_ref(&reference, self)
+                    .err()
+                    .map(|error| (name.clone(), error))
+            })
+            .collect()
+    }
+}
+
+impl<K: AsRef<str>, V: Into<String>> FromIterator<(K, V)> for NamedMatchers {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut matchers = Self::new();
+        for (name, expr) in iter {
+            matchers.insert(name, expr);
+        }
+        matchers
+    }
+}
+
+pub fn normalize_matcher_name(name: &str) -> String {
+    name.trim().trim_start_matches('@').to_string()
+}
+
+/// Names are what the grammar accepts after `@`: ASCII letters, digits, `_` and `-`.
+pub fn is_valid_matcher_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+}
+
+/// One `@name = expr` definition, with spans into the definitions source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatcherDefinition {
+    pub name: Spanned<String>,
+    pub expr: Spanned<String>,
+}
+
+/// Parse a definitions document: each definition starts a line with
+/// `@name =`, and lines that do not start with `@` continue the previous
+/// expression. `#` comments are allowed anywhere.
+pub fn parse_matcher_definitions(source: &str) -> Result<Vec<MatcherDefinition>, ParseError> {
+    let prepared = prepare_source(source);
+    let mut definitions: Vec<MatcherDefinition> = Vec::new();
+    let mut offset = 0usize;
+    for line in prepared.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let content = line.trim_end();
+        let trimmed = content.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let start = line_start + (content.len() - trimmed.len());
+
+        let Some(rest) = trimmed.strip_prefix('@') else {
+            let Some(last) = definitions.last_mut() else {
+                return Err(ParseError::Syntax {
+                    span: Span::new(start, start + trimmed.len()),
+                    message: "expected `@name = <match expression>`".to_string(),
+                });
+            };
+            last.expr.span.end = start + trimmed.len();
+            last.expr.value = source[last.expr.span.start..last.expr.span.end].to_string();
+            continue;
+        };
+
+        let name_len = rest
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'))
+            .unwrap_or(rest.len());
+        let name_span = Span::new(start, start + 1 + name_len);
+        if name_len == 0 {
+            return Err(ParseError::Syntax {
+                span: name_span,
+                message: "missing matcher name after `@`".to_string(),
+            });
+        }
+        let name = rest[..name_len].to_string();
+        if definitions
+            .iter()
+            .any(|definition| definition.name.value == name)
+        {
+            return Err(ParseError::Syntax {
+                span: name_span,
+                message: format!("duplicate matcher @{name}"),
+            });
+        }
+
+        let after_name = &rest[name_len..];
+        let Some(expr_text) = after_name.trim_start().strip_prefix('=') else {
+            return Err(ParseError::Syntax {
+                span: name_span,
+                message: format!("expected `=` after @{name}"),
+            });
+        };
+        let expr_trimmed = expr_text.trim_start();
+        let expr_start = start + trimmed.len() - expr_trimmed.len();
+        definitions.push(MatcherDefinition {
+            name: Spanned::new(name, name_span),
+            expr: Spanned::new(
+                source[expr_start..expr_start + expr_trimmed.len()].to_string(),
+                Span::new(expr_start, expr_start + expr_trimmed.len()),
+            ),
+        });
+    }
+
+    if let Some(empty) = definitions
+        .iter()
+        .find(|definition| prepare_source(&definition.expr.value).trim().is_empty())
+    {
+        return Err(ParseError::Syntax {
+            span: empty.name.span,
+            message: format!("empty matcher @{}", empty.name.value),
+        });
+    }
+    Ok(definitions)
+}
+
+impl From<&[MatcherDefinition]> for NamedMatchers {
+    fn from(definitions: &[MatcherDefinition]) -> Self {
+        definitions
+            .iter()
+            .map(|definition| {
+                (
+                    definition.name.value.as_str(),
+                    definition.expr.value.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Every `@name` in `program`, in source order.
+pub fn matcher_references(program: &Program) -> Vec<Spanned<String>> {
+    let mut references = Vec::new();
+    if let Some(expr) = &program.expr {
+        collect_expr_refs(expr, &mut references);
+    }
+    references
+}
+
+fn collect_expr_refs(expr: &Expr, references: &mut Vec<Spanned<String>>) {
+    for branch in &expr.or.branches {
+        collect_and_refs(branch, references);
+    }
+}
+
+fn collect_and_refs(and: &AndExpr, references: &mut Vec<Spanned<String>>) {
+    for term in &and.terms {
+        let mut term = term;
+        while let NotExpr::Not { inner, .. } = term {
+            term = inner;
+        }
+        match term {
+            NotExpr::Primary(Primary::Ref(name)) => references.push(name.clone()),
+            NotExpr::Primary(Primary::Grouped(expr)) => collect_expr_refs(expr, references),
+            _ => {}
+        }
+    }
+}
+
+/// Errors for `@name` references in `source` that do not resolve against
+/// `matchers`, each spanning its reference.
+pub fn matcher_diagnostics(source: &str, matchers: &NamedMatchers) -> Vec<Diagnostic> {
+    let Ok(program) = parse_program(source) else {
+        return Vec::new();
+    };
+    matcher_references(&program)
+        .into_iter()
+        .filter_map(|reference| {
+            let error = resolve_matcher_ref(&reference, matchers).err()?;
+            Some(Diagnostic {
+                from: reference.span.start,
+                to: reference.span.end,
+                severity: "error".to_string(),
+                message: error.to_string(),
+            })
+        })
+        .collect()
+}
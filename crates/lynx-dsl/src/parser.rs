@@ -130,6 +130,14 @@ fn parse_primary(
     })?;
 
     match child.as_rule() {
+        Rule::matcher_ref => {
+            let span = span_from_pair(child.as_span(), source);
+            let name = child.as_str().trim_start_matches('@').to_string();
+            Ok(Primary::Ref(Spanned::new(
+                name,
+                Span::new(base_offset + span.start, base_offset + span.end),
+            )))
+        }
         Rule::cli_only => Ok(Primary::CliOnly(parse_cli_args(
             child.into_inner().next().unwrap(),
             source,
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::format::validate_dsl_document;
 use crate::highlight::{HighlightSpan, collect_highlights};
+use crate::matchers::{NamedMatchers, matcher_diagnostics};
 use crate::parser::parse_program;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -58,6 +59,17 @@ pub fn validate(source: &str) -> ValidationResult {
     }
 }
 
+/// Like [`validate`], also reporting `@name` references that are undefined,
+/// cyclic or point at an invalid definition.
+pub fn validate_with_matchers(source: &str, matchers: &NamedMatchers) -> ValidationResult {
+    let mut result = validate(source);
+    if result.is_valid {
+        result.diagnostics = matcher_diagnostics(source, matchers);
+        result.is_valid = result.diagnostics.is_empty();
+    }
+    result
+}
+
 pub fn collect_syntax_diagnostics(source: &str) -> Vec<Diagnostic> {
     validate(source).diagnostics
 }
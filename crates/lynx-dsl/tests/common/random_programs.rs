@@ -0,0 +1,101 @@
+//! Seeded random match programs and request facts for property tests.
+
+use std::sync::Arc;
+
+use lynx_dsl::ir::{PathMatcher, SegmentPattern};
+use lynx_dsl::{EvalPlan, MatchProgram, Predicate, RequestFacts};
+
+const RULE_HOSTS: &[&str] = &[
+    "example.com",
+    "api.example.com",
+    "Example.COM",
+    "example",
+    "com",
+    "other.org",
+    ".org",
+    "",
+];
+const FACT_HOSTS: &[&str] = &[
+    "example.com",
+    "API.example.com",
+    "x.other.org",
+    "localhost",
+    "exampleXcom",
+    "notexample.com",
+    "",
+];
+const RULE_METHODS: &[&str] = &["GET", "post", "PUT", "PURGE", "brew"];
+const FACT_METHODS: &[&str] = &["GET", "POST", "post", "PURGE", "BREW", "PATCH"];
+const RULE_EXACT_PATHS: &[&str] = &["/api", "/api/", "/API/v1", "/", "/api/v1/users", "api"];
+const RULE_SEGMENTS: &[&str] = &["api", "v1", "Users"];
+const FACT_PATHS: &[&str] = &[
+    "/",
+    "/api",
+    "/api/v1",
+    "/API/V1/users/9",
+    "/apix",
+    "//api//v1",
+    "/api/",
+    "/v1/users",
+];
+
+fn pick<'a>(rng: &mut fastrand::Rng, items: &[&'a str]) -> &'a str {
+    items[rng.usize(..items.len())]
+}
+
+fn random_predicate(rng: &mut fastrand::Rng) -> Predicate {
+    match rng.u8(..6) {
+        0 | 1 => Predicate::HostEq(Arc::from(pick(rng, RULE_HOSTS))),
+        2 => Predicate::MethodEq(Arc::from(pick(rng, RULE_METHODS))),
+        3 => Predicate::PathGlob(PathMatcher::Exact(Arc::from(pick(rng, RULE_EXACT_PATHS)))),
+        4 => {
+            let segments = (0..rng.usize(1..4))
+                .map(|_| match rng.u8(..4) {
+                    0 => SegmentPattern::SingleWildcard,
+                    1 => SegmentPattern::MultiWildcard,
+                    _ => SegmentPattern::Literal(Arc::from(pick(rng, RULE_SEGMENTS))),
+                })
+                .collect();
+            Predicate::PathGlob(PathMatcher::Segments(segments))
+        }
+        _ => Predicate::HeaderEq {
+            key: Arc::from("x-env"),
+            value: Arc::from(if rng.bool() { "stg" } else { "" }),
+        },
+    }
+}
+
+fn random_plan(rng: &mut fastrand::Rng, predicates: &mut Vec<Predicate>, depth: u8) -> EvalPlan {
+    let leaf = depth == 0 || rng.u8(..3) == 0;
+    if leaf {
+        predicates.push(random_predicate(rng));
+        return EvalPlan::Pred(predicates.len() - 1);
+    }
+    let children = |rng: &mut fastrand::Rng, predicates: &mut Vec<Predicate>| {
+        (0..rng.usize(1..4))
+            .map(|_| random_plan(rng, predicates, depth - 1))
+            .collect()
+    };
+    match rng.u8(..5) {
+        0 | 1 => EvalPlan::All(children(rng, predicates)),
+        2 | 3 => EvalPlan::Any(children(rng, predicates)),
+        _ => EvalPlan::Not(Box::new(random_plan(rng, predicates, depth - 1))),
+    }
+}
+
+pub fn random_program(rng: &mut fastrand::Rng) -> MatchProgram {
+    let mut predicates = Vec::new();
+    let plan = random_plan(rng, &mut predicates, 3);
+    MatchProgram::new(predicates, plan)
+}
+
+pub fn random_facts(rng: &mut fastrand::Rng) -> RequestFacts {
+    let mut builder = RequestFacts::builder()
+        .host(pick(rng, FACT_HOSTS))
+        .method(pick(rng, FACT_METHODS))
+        .path(pick(rng, FACT_PATHS));
+    if rng.bool() {
+        builder = builder.header("X-Env", if rng.bool() { "stg" } else { "prod" });
+    }
+    builder.build()
+}
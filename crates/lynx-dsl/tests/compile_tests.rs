@@ -116,6 +116,8 @@ fn predicate_kinds(program: &MatchProgram) -> Vec<&'static str> {
             Predicate::QueryContains(_) => "query",
             Predicate::QueryParamsAll(_) => "query_params",
             Predicate::HeaderEq { .. } => "header",
+            Predicate::Sample { .. } => "sample",
+            Predicate::TimeWindow { .. } => "time",
         })
         .collect()
 }
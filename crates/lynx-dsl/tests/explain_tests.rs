@@ -0,0 +1,92 @@
+use lynx_dsl::{ExplainKind, RequestFacts, compile_match_expr, eval_program, explain_match_expr};
+
+#[test]
+fn explain_reports_failing_predicate_with_span() {
+    let source = "example.com AND -X POST";
+    let facts = RequestFacts::builder()
+        .host("example.com")
+        .method("GET")
+        .build();
+
+    let explanation = explain_match_expr(source, &facts).unwrap();
+    assert!(!explanation.matched);
+    assert_eq!(explanation.root.kind, ExplainKind::All);
+    assert!(explanation.root.children[0].matched);
+    assert!(!explanation.root.children[1].matched);
+
+    let failed = explanation.failed_nodes();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].description.as_deref(), Some("method = POST"));
+    let span = failed[0].span.unwrap();
+    assert_eq!(&source[span.start..span.end], "-X POST");
+}
+
+#[test]
+fn explain_evaluates_every_branch_of_or() {
+    let source = "a.example.com OR b.example.com /api";
+    let facts = RequestFacts::builder()
+        .host("b.example.com")
+        .path("/web")
+        .build();
+
+    let explanation = explain_match_expr(source, &facts).unwrap();
+    assert!(!explanation.matched);
+    let failed = explanation
+        .failed_nodes()
+        .into_iter()
+        .filter_map(|node| node.description.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(failed, vec!["host = a.example.com", "path = /api"]);
+}
+
+#[test]
+fn explain_blames_not_when_inner_matches() {
+    let source = "NOT /health";
+    let facts = RequestFacts::builder().path("/health").build();
+
+    let explanation = explain_match_expr(source, &facts).unwrap();
+    assert!(!explanation.matched);
+    let failed = explanation.failed_nodes();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].kind, ExplainKind::Not);
+    assert!(failed[0].children[0].matched);
+}
+
+#[test]
+fn explain_result_agrees_with_eval() {
+    let cases = [
+        (
+            "example.com /api/** -X GET",
+            "example.com",
+            "/api/v1",
+            "GET",
+        ),
+        (
+            "example.com /api/** -X GET",
+            "example.com",
+            "/api/v1",
+            "PUT",
+        ),
+        ("(a.com OR b.com) AND NOT -X DELETE", "b.com", "/", "DELETE"),
+        ("(a.com OR b.com) AND NOT -X DELETE", "a.com", "/", "GET"),
+        ("https://example.com:8443/x?a=1", "example.com", "/x", "GET"),
+    ];
+    for (source, host, path, method) in cases {
+        let facts = RequestFacts::builder()
+            .scheme("https")
+            .host(host)
+            .port(8443)
+            .path(path)
+            .query("a=1")
+            .method(method)
+            .build();
+        let program = compile_match_expr(source).unwrap();
+        let explanation = explain_match_expr(source, &facts).unwrap();
+        assert_eq!(
+            explanation.matched,
+            eval_program(&program, &facts),
+            "dsl={source:?}"
+        );
+        assert!(explanation.matched || !explanation.failed_nodes().is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+//! `RuleIndex` must select exactly what a linear `eval_program` scan selects.
+
+#[path = "common/random_programs.rs"]
+mod random_programs;
+
+use lynx_dsl::{MatchProgram, RequestFacts, RuleIndex, compile_match_expr, eval_program};
+use random_programs::{random_facts, random_program};
+
+fn linear_scan(programs: &[MatchProgram], facts: &RequestFacts) -> Vec<usize> {
+    programs
+        .iter()
+        .enumerate()
+        .filter(|(_, program)| eval_program(program, facts))
+        .map(|(position, _)| position)
+        .collect()
+}
+
+fn indexed_scan(index: &RuleIndex, programs: &[MatchProgram], facts: &RequestFacts) -> Vec<usize> {
+    index
+        .candidates(facts)
+        .into_iter()
+        .filter(|position| eval_program(&programs[*position], facts))
+        .collect()
+}
+
+#[test]
+fn indexed_dispatch_equals_linear_scan_for_random_programs() {
+    for seed in 0..200 {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let programs: Vec<MatchProgram> = (0..rng.usize(1..150))
+            .map(|_| random_program(&mut rng))
+            .collect();
+        let index = RuleIndex::build(&programs);
+        assert_eq!(index.len(), programs.len());
+
+        for _ in 0..50 {
+            let facts = random_facts(&mut rng);
+            assert_eq!(
+                indexed_scan(&index, &programs, &facts),
+                linear_scan(&programs, &facts),
+                "seed={seed} facts={facts:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn indexed_dispatch_equals_linear_scan_for_dsl_rules() {
+    let sources = [
+        "example.com",
+        "api.example.com /v1/users/* -X POST",
+        "(example.com OR localhost) AND /api",
+        "NOT /health",
+        "example.com AND /api OR /health",
+        "*/rest/* AND -X POST",
+        "https://example.com/api/**",
+        "example -X GET",
+        "/api/v1",
+    ];
+    let programs: Vec<MatchProgram> = sources
+        .iter()
+        .map(|source| compile_match_expr(source).expect("compile"))
+        .collect();
+    let index = RuleIndex::build(&programs);
+
+    let mut rng = fastrand::Rng::with_seed(7);
+    for _ in 0..500 {
+        let facts = random_facts(&mut rng);
+        assert_eq!(
+            indexed_scan(&index, &programs, &facts),
+            linear_scan(&programs, &facts),
+            "facts={facts:?}"
+        );
+    }
+}
+
+#[test]
+fn index_prunes_unrelated_hosts_methods_and_paths() {
+    let programs: Vec<MatchProgram> = [
+        "api.example.com",
+        "other.org",
+        "-X POST",
+        "/admin/**",
+        "NOT other.org",
+    ]
+    .iter()
+    .map(|source| compile_match_expr(source).expect("compile"))
+    .collect();
+    let index = RuleIndex::build(&programs);
+
+    let facts = RequestFacts::builder()
+        .host("v2.api.example.com")
+        .method("GET")
+        .path("/users")
+        .build();
+    assert_eq!(index.candidates(&facts), vec![0, 4]);
+}
@@ -0,0 +1,170 @@
+#[path = "common/random_programs.rs"]
+mod random_programs;
+
+use lynx_dsl::{
+    LintRule, MatchProgram, RuleEffect, RuleLintCode, compile_match_expr, eval_program,
+    program_subsumes, programs_may_overlap, validate_rules,
+};
+use random_programs::{random_facts, random_program};
+
+fn compile(source: &str) -> MatchProgram {
+    compile_match_expr(source).unwrap_or_else(|error| panic!("compile {source:?}: {error}"))
+}
+
+fn rule(name: &str, match_expr: &str) -> LintRule {
+    LintRule {
+        name: name.to_string(),
+        match_expr: match_expr.to_string(),
+        enabled: true,
+        terminal: None,
+        first_order: Some(20),
+        effects: Vec::new(),
+    }
+}
+
+fn effect(slot: &str, value: &str) -> RuleEffect {
+    RuleEffect {
+        slot: slot.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn subsumption_and_disjointness_are_sound_for_random_programs() {
+    let mut subsumed = 0;
+    let mut disjoint = 0;
+    for seed in 0..400 {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let broad = random_program(&mut rng);
+        let narrow = random_program(&mut rng);
+        let subsumes = program_subsumes(&broad, &narrow);
+        let may_overlap = programs_may_overlap(&broad, &narrow);
+        subsumed += usize::from(subsumes);
+        disjoint += usize::from(!may_overlap);
+
+        for _ in 0..100 {
+            let facts = random_facts(&mut rng);
+            let broad_matched = eval_program(&broad, &facts);
+            let narrow_matched = eval_program(&narrow, &facts);
+            if subsumes {
+                assert!(
+                    !narrow_matched || broad_matched,
+                    "seed={seed} claimed subsumption broke for {facts:?}"
+                );
+            }
+            if !may_overlap {
+                assert!(
+                    !(narrow_matched && broad_matched),
+                    "seed={seed} claimed disjoint programs both matched {facts:?}"
+                );
+            }
+        }
+    }
+    assert!(subsumed > 0 && disjoint > 0, "{subsumed} {disjoint}");
+}
+
+#[test]
+fn subsumption_examples() {
+    let cases = [
+        ("example.com", "api.example.com /v1", true),
+        ("example.com", "example.com", true),
+        ("/api", "/api/v1/users", true),
+        ("/api/**", "example.com /api/v1", true),
+        ("example", "api.example.com", true),
+        ("example.com -X POST", "example.com", false),
+        ("api.example.com", "example.com", false),
+        ("/api", "/apix", false),
+        ("NOT /health", "/api", true),
+        ("example.com OR other.org", "other.org -X GET", true),
+    ];
+    for (broad, narrow, expected) in cases {
+        assert_eq!(
+            program_subsumes(&compile(broad), &compile(narrow)),
+            expected,
+            "{broad:?} ⊇ {narrow:?}"
+        );
+    }
+}
+
+#[test]
+fn overlap_examples() {
+    let cases = [
+        ("-X GET", "-X POST", false),
+        ("example.com", "other.org", false),
+        ("/api", "/web", false),
+        ("example.com", "example.com/api", true),
+        ("example.com", "-X POST", true),
+        ("/health", "NOT /health", false),
+    ];
+    for (left, right, expected) in cases {
+        assert_eq!(
+            programs_may_overlap(&compile(left), &compile(right)),
+            expected,
+            "{left:?} ∩ {right:?}"
+        );
+    }
+}
+
+#[test]
+fn reports_rule_shadowed_by_earlier_block() {
+    let mut block = rule("block all example", "example.com");
+    block.terminal = Some((100, "block".to_string()));
+    block.first_order = Some(100);
+    let mut mock = rule("mock users", "api.example.com /v1/users");
+    mock.first_order = Some(100);
+    let rewrite = rule("rewrite headers", "api.example.com /v1/users");
+
+    let diagnostics = validate_rules(&[block, mock, rewrite]);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.rule, 1);
+    assert_eq!(diagnostic.related, Some(0));
+    assert_eq!(diagnostic.code, RuleLintCode::Shadowed);
+    assert_eq!(diagnostic.diagnostic.severity, "error");
+    assert!(diagnostic.diagnostic.message.contains("block all example"));
+}
+
+#[test]
+fn reports_conflicting_effects_only_for_overlapping_rules() {
+    let mut first = rule("forward a", "example.com /api");
+    first.effects = vec![effect("upstream", "http://a")];
+    let mut second = rule("forward b", "api.example.com");
+    second.effects = vec![effect("upstream", "http://b")];
+    let mut post_only = rule("forward c", "-X POST");
+    post_only.effects = vec![effect("upstream", "http://c")];
+    let mut get_only = rule("forward d", "-X GET");
+    get_only.effects = vec![effect("upstream", "http://d")];
+
+    let diagnostics = validate_rules(&[first, second, post_only, get_only]);
+    let pairs: Vec<(usize, Option<usize>)> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.rule, diagnostic.related))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            (1, Some(0)),
+            (2, Some(0)),
+            (2, Some(1)),
+            (3, Some(0)),
+            (3, Some(1))
+        ]
+    );
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code == RuleLintCode::Conflict
+                && diagnostic.diagnostic.severity == "warning")
+    );
+}
+
+#[test]
+fn reports_invalid_and_skips_disabled_rules() {
+    let mut disabled = rule("disabled block", "example.com");
+    disabled.enabled = false;
+    disabled.terminal = Some((0, "block".to_string()));
+    let diagnostics = validate_rules(&[disabled, rule("broken", "(("), rule("ok", "example.com")]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].rule, 1);
+    assert_eq!(diagnostics[0].code, RuleLintCode::InvalidMatch);
+}
@@ -0,0 +1,196 @@
+use lynx_dsl::{
+    CompileError, NamedMatchers, RequestFacts, Span, compile_match_expr,
+    compile_match_expr_with_matchers, compile_with_matchers, eval_program, explain_program,
+    format_dsl, parse_matcher_definitions, validate, validate_with_matchers,
+};
+
+fn staging() -> NamedMatchers {
+    [
+        (
+            "staging",
+            "(api.staging.example.com OR cdn.staging.example.com) AND -H x-env=stg",
+        ),
+        ("writes", "-X POST OR -X PUT"),
+        ("staging-writes", "@staging AND @writes"),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn facts(host: &str, method: &str, path: &str, env: &str) -> RequestFacts {
+    RequestFacts::builder()
+        .host(host)
+        .method(method)
+        .path(path)
+        .header("x-env", env)
+        .build()
+}
+
+#[test]
+fn references_resolve_to_their_definitions() {
+    let matchers = staging();
+    let program = compile_match_expr_with_matchers("@staging and /v2/**", &matchers).unwrap();
+
+    assert!(eval_program(
+        &program,
+        &facts("cdn.staging.example.com", "GET", "/v2/assets", "stg")
+    ));
+    assert!(!eval_program(
+        &program,
+        &facts("cdn.staging.example.com", "GET", "/v1/assets", "stg")
+    ));
+    assert!(!eval_program(
+        &program,
+        &facts("api.staging.example.com", "GET", "/v2/users", "prod")
+    ));
+
+    let nested = compile_match_expr_with_matchers("NOT @staging-writes", &matchers).unwrap();
+    assert!(!eval_program(
+        &nested,
+        &facts("api.staging.example.com", "PUT", "/", "stg")
+    ));
+    assert!(eval_program(
+        &nested,
+        &facts("api.staging.example.com", "GET", "/", "stg")
+    ));
+}
+
+#[test]
+fn inlined_predicates_point_at_the_reference() {
+    let source = "@writes AND /api";
+    let (program, source_map) = compile_with_matchers(source, &staging()).unwrap();
+    let spans: Vec<&str> = source_map
+        .predicate_spans
+        .iter()
+        .map(|span| &source[span.start..span.end])
+        .collect();
+    assert_eq!(spans, vec!["@writes", "@writes", "/api"]);
+
+    let explanation = explain_program(
+        &program,
+        Some(&source_map),
+        &facts("example.com", "GET", "/api", ""),
+    );
+    assert!(!explanation.matched);
+}
+
+#[test]
+fn undefined_matchers_are_reported_with_spans() {
+    let error = compile_match_expr_with_matchers("/api AND @prod", &staging()).unwrap_err();
+    assert_eq!(
+        error,
+        CompileError::UndefinedMatcher {
+            name: "prod".to_string(),
+            span: Span::new(9, 14),
+        }
+    );
+    assert!(compile_match_expr("@staging").is_err());
+
+    let result = validate_with_matchers("/api AND @prod", &staging());
+    assert!(!result.is_valid);
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(
+        (result.diagnostics[0].from, result.diagnostics[0].to),
+        (9, 14)
+    );
+    assert_eq!(result.diagnostics[0].message, "undefined matcher @prod");
+}
+
+#[test]
+fn cycles_are_detected() {
+    let matchers: NamedMatchers = [
+        ("a", "example.com AND @b"),
+        ("b", "-X GET OR @c"),
+        ("c", "NOT @a"),
+        ("self", "@self"),
+        ("ok", "/health"),
+    ]
+    .into_iter()
+    .collect();
+
+    let error = compile_match_expr_with_matchers("/x OR @a", &matchers).unwrap_err();
+    assert_eq!(
+        error,
+        CompileError::MatcherCycle {
+            cycle: vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ],
+            span: Span::new(6, 8),
+        }
+    );
+    assert_eq!(error.to_string(), "matcher cycle: @a -> @b -> @c -> @a");
+
+    let broken: Vec<String> = matchers.check().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(broken, vec!["a", "b", "c", "self"]);
+}
+
+#[test]
+fn invalid_definitions_are_reported_at_the_reference() {
+    let matchers: NamedMatchers = [("bad", "(("), ("uses-bad", "@bad")].into_iter().collect();
+    let error = compile_match_expr_with_matchers("@uses-bad", &matchers).unwrap_err();
+    assert!(matches!(
+        &error,
+        CompileError::InvalidMatcher { name, span, .. }
+            if name == "uses-bad" && *span == Span::new(0, 9)
+    ));
+    assert!(
+        error
+            .to_string()
+            .starts_with("invalid matcher @uses-bad: invalid matcher @bad")
+    );
+}
+
+#[test]
+fn references_are_highlighted_and_formatted() {
+    let source = "@staging   and   /v2/**";
+    let result = validate(source);
+    assert!(result.is_valid);
+    let reference = result
+        .highlights
+        .iter()
+        .find(|span| span.kind == "MatcherRef")
+        .expect("matcher highlight");
+    assert_eq!((reference.from, reference.to), (0, 8));
+
+    assert_eq!(
+        format_dsl(source).unwrap().as_deref(),
+        Some("@staging AND /v2/**")
+    );
+}
+
+#[test]
+fn parses_definition_documents() {
+    let source = "# shared matchers\n@staging = (api.staging.example.com OR cdn.staging.example.com)\n    AND -H x-env=stg\n@writes=-X POST # comment\n";
+    let definitions = parse_matcher_definitions(source).unwrap();
+    assert_eq!(definitions.len(), 2);
+    assert_eq!(definitions[0].name.value, "staging");
+    assert_eq!(
+        &source[definitions[0].name.span.start..definitions[0].name.span.end],
+        "@staging"
+    );
+    assert_eq!(
+        definitions[0].expr.value,
+        "(api.staging.example.com OR cdn.staging.example.com)\n    AND -H x-env=stg"
+    );
+    assert_eq!(definitions[1].expr.value, "-X POST");
+
+    let matchers = NamedMatchers::from(definitions.as_slice());
+    assert!(matchers.check().is_empty());
+
+    for (invalid, span) in [
+        ("example.com", Span::new(0, 11)),
+        ("@a = /x\n@a = /y", Span::new(8, 10)),
+        ("@a /x", Span::new(0, 2)),
+        ("@a =", Span::new(0, 2)),
+    ] {
+        match parse_matcher_definitions(invalid) {
+            Err(lynx_dsl::ParseError::Syntax { span: actual, .. }) => {
+                assert_eq!(actual, span, "{invalid:?}")
+            }
+            other => panic!("{invalid:?}: {other:?}"),
+        }
+    }
+}
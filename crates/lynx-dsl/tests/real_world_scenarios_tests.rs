@@ -205,6 +205,9 @@ fn facts_from_http_request<T>(request: &Request<T>, extra_headers: &HeaderMap) -
         query,
         method,
         headers,
+        client: None,
+        sample_seed: 0,
+        minute_of_day: None,
     }
 }
 
@@ -0,0 +1,215 @@
+use lynx_dsl::{
+    CompileError, MatchProgram, Predicate, RequestFacts, SampleKey, compile_match_expr,
+    describe_predicate, eval_program, eval_program_ignoring_samples, program_subsumes,
+    programs_may_overlap,
+};
+
+fn compile(source: &str) -> MatchProgram {
+    compile_match_expr(source).unwrap_or_else(|error| panic!("compile {source:?}: {error}"))
+}
+
+#[test]
+fn sample_flags_compile_to_basis_points_and_keys() {
+    let cases = [
+        ("--sample 10%", 1000, SampleKey::Request),
+        ("--sample=0.5%", 50, SampleKey::Request),
+        ("--sample 25%:client", 2500, SampleKey::Client),
+        (
+            "--sample 100%:header=X-User-Id",
+            10000,
+            SampleKey::Header("x-user-id".into()),
+        ),
+    ];
+    for (source, basis_points, key) in cases {
+        assert_eq!(
+            compile(source).predicates,
+            vec![Predicate::Sample { basis_points, key }],
+            "{source}"
+        );
+    }
+
+    for source in [
+        "--sample 150%",
+        "--sample abc",
+        "--sample 10%:cookie",
+        "--sample 10%:header=",
+    ] {
+        assert!(
+            matches!(
+                compile_match_expr(source),
+                Err(CompileError::InvalidSample(_))
+            ),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn request_sampling_hits_roughly_the_requested_share() {
+    let program = compile("example.com --sample 10%");
+    let hits = (0..10_000u64)
+        .filter(|seed| {
+            let facts = RequestFacts::builder()
+                .host("example.com")
+                .sample_seed(*seed)
+                .build();
+            eval_program(&program, &facts)
+        })
+        .count();
+    assert!((800..1200).contains(&hits), "hits={hits}");
+
+    let facts = RequestFacts::builder()
+        .host("example.com")
+        .sample_seed(7)
+        .build();
+    assert_eq!(
+        eval_program(&program, &facts),
+        eval_program(&program, &facts)
+    );
+}
+
+#[test]
+fn sticky_samples_follow_the_client_or_header_value() {
+    let by_client = compile("--sample 50%:client");
+    let by_header = compile("--sample 50%:header=x-user-id");
+    let wider = compile("--sample 90%:client");
+
+    for client in ["10.0.0.1", "10.0.0.2", "10.0.0.3", "192.168.1.20"] {
+        let verdicts: Vec<bool> = (0..20)
+            .map(|seed| {
+                let facts = RequestFacts::builder()
+                    .client(client)
+                    .sample_seed(seed)
+                    .build();
+                eval_program(&by_client, &facts)
+            })
+            .collect();
+        assert!(verdicts.iter().all(|hit| *hit == verdicts[0]), "{client}");
+        if verdicts[0] {
+            let facts = RequestFacts::builder().client(client).build();
+            assert!(
+                eval_program(&wider, &facts),
+                "{client} left the wider sample"
+            );
+        }
+    }
+
+    let user = |id: &str, seed: u64| {
+        RequestFacts::builder()
+            .header("X-User-Id", id)
+            .sample_seed(seed)
+            .build()
+    };
+    assert_eq!(
+        eval_program(&by_header, &user("u-1", 1)),
+        eval_program(&by_header, &user("u-1", 2))
+    );
+
+    let anonymous = RequestFacts::builder().build();
+    assert!(!eval_program(&by_client, &anonymous));
+    assert!(!eval_program(&by_header, &anonymous));
+}
+
+#[test]
+fn ignoring_samples_shows_what_sampling_filtered_out() {
+    let program = compile("example.com --sample 0%");
+    assert!(program.has_sampling());
+    assert!(!compile("example.com").has_sampling());
+
+    let facts = RequestFacts::builder().host("example.com").build();
+    assert!(!eval_program(&program, &facts));
+    assert!(eval_program_ignoring_samples(&program, &facts));
+
+    let other = RequestFacts::builder().host("other.com").build();
+    assert!(!eval_program_ignoring_samples(&program, &other));
+}
+
+#[test]
+fn ignoring_samples_keeps_negated_samples_open() {
+    // Fires for ~90% of requests, so the pre-pass must not rule it out.
+    let program = compile("example.com AND NOT --sample 10%");
+    let facts = |seed| {
+        RequestFacts::builder()
+            .host("example.com")
+            .sample_seed(seed)
+            .build()
+    };
+    assert!(eval_program_ignoring_samples(&program, &facts(1)));
+    let fired = (0..1000)
+        .filter(|seed| eval_program(&program, &facts(*seed)))
+        .count();
+    assert!((800..1000).contains(&fired), "fired {fired} of 1000");
+
+    let other = RequestFacts::builder().host("other.com").build();
+    assert!(!eval_program_ignoring_samples(&program, &other));
+    // An OR with a sample is open even when the rest of the branch fails.
+    let either = compile("other.com OR --sample 10%");
+    assert!(eval_program_ignoring_samples(&either, &facts(1)));
+}
+
+#[test]
+fn time_windows_match_local_minutes_and_wrap_midnight() {
+    let at = |hour: u16, minute: u16| {
+        RequestFacts::builder()
+            .minute_of_day(hour * 60 + minute)
+            .build()
+    };
+    let office = compile("--time 09:00-18:00");
+    assert!(eval_program(&office, &at(9, 0)));
+    assert!(eval_program(&office, &at(17, 59)));
+    assert!(!eval_program(&office, &at(18, 0)));
+    assert!(!eval_program(&office, &at(8, 59)));
+
+    let night = compile("--time 22:30-06:00");
+    assert!(eval_program(&night, &at(23, 15)));
+    assert!(eval_program(&night, &at(3, 0)));
+    assert!(!eval_program(&night, &at(12, 0)));
+
+    let whole_day = compile("--time 00:00-24:00");
+    assert!(eval_program(&whole_day, &at(23, 59)));
+    assert!(!eval_program(&whole_day, &RequestFacts::builder().build()));
+
+    for source in ["--time 9-18", "--time 09:00-09:00", "--time 25:00-26:00"] {
+        assert!(
+            matches!(
+                compile_match_expr(source),
+                Err(CompileError::InvalidTimeWindow(_))
+            ),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn lint_understands_samples_and_windows() {
+    assert!(program_subsumes(
+        &compile("--sample 50%:client"),
+        &compile("--sample 10%:client")
+    ));
+    assert!(!program_subsumes(
+        &compile("--sample 50%"),
+        &compile("--sample 10%:client")
+    ));
+    assert!(program_subsumes(
+        &compile("--time 22:00-06:00"),
+        &compile("--time 23:00-01:00")
+    ));
+    assert!(!programs_may_overlap(
+        &compile("--time 09:00-12:00"),
+        &compile("--time 22:00-06:00")
+    ));
+    assert!(programs_may_overlap(
+        &compile("--time 09:00-12:00"),
+        &compile("--time 11:00-13:00")
+    ));
+}
+
+#[test]
+fn explain_describes_samples_and_windows() {
+    let program = compile("--sample 2.5%:header=x-user-id --time 08:05-17:00");
+    let descriptions: Vec<String> = program.predicates.iter().map(describe_predicate).collect();
+    assert_eq!(
+        descriptions,
+        vec!["sample 2.5% by header x-user-id", "time 08:05-17:00"]
+    );
+}
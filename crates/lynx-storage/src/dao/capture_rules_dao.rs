@@ -1,7 +1,9 @@
 use crate::storage::{DataStore, read_json_or_default, write_json_atomic};
 use anyhow::{Result, anyhow};
+use lynx_dsl::{MatchProgram, RequestFacts, RuleIndex, compile_match_expr, eval_program};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +31,56 @@ pub enum CaptureRuleKind {
     Ignore,
 }
 
+/// Enabled rules of one list with a valid `matchExpr`, compiled and indexed.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledCaptureRuleList {
+    programs: Vec<MatchProgram>,
+    index: RuleIndex,
+}
+
+impl CompiledCaptureRuleList {
+    pub fn compile(rules: &[CaptureRule]) -> Self {
+        let mut programs = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let expr = rule.match_expr.trim();
+            if expr.is_empty() {
+                continue;
+            }
+            match compile_match_expr(expr) {
+                Ok(program) => programs.push(program),
+                Err(err) => warn!("capture rule {} invalid DSL: {}", rule.id, err),
+            }
+        }
+        let index = RuleIndex::build(&programs);
+        Self { programs, index }
+    }
+
+    pub fn any_matches(&self, facts: &RequestFacts) -> bool {
+        self.index
+            .candidates(facts)
+            .into_iter()
+            .any(|position| eval_program(&self.programs[position], facts))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompiledCaptureRules {
+    pub focus: CompiledCaptureRuleList,
+    pub ignore: CompiledCaptureRuleList,
+    /// Any focus rule is enabled, even one whose `matchExpr` is empty or invalid.
+    pub has_focus: bool,
+}
+
+impl CompiledCaptureRules {
+    pub fn compile(rules: &CaptureRules) -> Self {
+        Self {
+            focus: CompiledCaptureRuleList::compile(&rules.focus_rules),
+            ignore: CompiledCaptureRuleList::compile(&rules.ignore_rules),
+            has_focus: rules.focus_rules.iter().any(|rule| rule.enabled),
+        }
+    }
+}
+
 pub struct CaptureRulesDao {
     store: Arc<DataStore>,
 }
@@ -46,6 +98,17 @@ impl CaptureRulesDao {
         read_json_or_default(&self.path()).await
     }
 
+    /// Compiled focus/ignore rules, cached until `capture_rules.json` changes.
+    pub async fn get_compiled_rules(&self) -> Result<Arc<CompiledCaptureRules>> {
+        self.store.get_capture_rules_cache().await
+    }
+
+    async fn save(&self, rules: &CaptureRules) -> Result<()> {
+        write_json_atomic(&self.path(), rules).await?;
+        self.store.invalidate_capture_rules_cache().await;
+        Ok(())
+    }
+
     pub async fn list(&self, kind: CaptureRuleKind) -> Result<Vec<CaptureRule>> {
         let rules = self.get_rules().await?;
         Ok(match kind {
@@ -93,7 +156,7 @@ impl CaptureRulesDao {
                 .then_with(|| b.id.cmp(&a.id))
         });
 
-        write_json_atomic(&self.path(), &rules).await?;
+        self.save(&rules).await?;
         Ok(rule)
     }
 
@@ -108,7 +171,7 @@ impl CaptureRulesDao {
         if list.len() == before {
             return Err(anyhow!("Capture rule {rule_id} not found"));
         }
-        write_json_atomic(&self.path(), &rules).await?;
+        self.save(&rules).await?;
         Ok(())
     }
 
@@ -130,7 +193,7 @@ impl CaptureRulesDao {
         item.enabled = enabled;
         item.updated_at = now;
         let out = item.clone();
-        write_json_atomic(&self.path(), &rules).await?;
+        self.save(&rules).await?;
         Ok(out)
     }
 }
@@ -184,4 +247,32 @@ mod tests {
         assert_eq!(list[0].id, saved.id);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn compiled_rules_follow_updates() -> Result<()> {
+        let (store, _dir) = setup_store().await;
+        let dao = CaptureRulesDao::new(store);
+        let facts = RequestFacts::builder().host("api.example.com").build();
+        assert!(!dao.get_compiled_rules().await?.ignore.any_matches(&facts));
+
+        let saved = dao
+            .upsert(
+                CaptureRuleKind::Ignore,
+                CaptureRule {
+                    id: 0,
+                    name: "ignore".to_string(),
+                    enabled: true,
+                    match_expr: "example.com".to_string(),
+                    created_at: 0,
+                    updated_at: 0,
+                },
+            )
+            .await?;
+        assert!(dao.get_compiled_rules().await?.ignore.any_matches(&facts));
+
+        dao.set_enabled(CaptureRuleKind::Ignore, saved.id, false)
+            .await?;
+        assert!(!dao.get_compiled_rules().await?.ignore.any_matches(&facts));
+        Ok(())
+    }
 }
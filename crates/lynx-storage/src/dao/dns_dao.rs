@@ -0,0 +1,215 @@
+use crate::storage::{DataStore, read_json_or_default, write_json_atomic};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// Points a host at fixed addresses, like a line of a hosts file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsOverride {
+    /// `api.example.com`, or `*.example.com` for every subdomain
+    pub host: String,
+    pub ips: Vec<IpAddr>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Where names without an override are looked up.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DnsResolverConfig {
+    /// The operating system's resolver
+    #[default]
+    System,
+    /// A DNS server over UDP, falling back to TCP for long answers
+    #[serde(rename_all = "camelCase")]
+    Server {
+        /// `ip` or `ip:port`; the port defaults to 53
+        address: String,
+    },
+    /// DNS-over-HTTPS (RFC 8484) at an `https://` URL
+    #[serde(rename_all = "camelCase")]
+    DnsOverHttps { url: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsConfig {
+    #[serde(default)]
+    pub overrides: Vec<DnsOverride>,
+    #[serde(default)]
+    pub resolver: DnsResolverConfig,
+}
+
+impl DnsOverride {
+    /// Whether this override applies to `host` (lower-case, no trailing dot).
+    pub fn matches(&self, host: &str) -> bool {
+        let pattern = self.host.trim().trim_end_matches('.').to_ascii_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .and_then(|rest| rest.strip_suffix('.'))
+                .is_some_and(|rest| !rest.is_empty()),
+            None => pattern == host,
+        }
+    }
+
+    fn is_wildcard(&self) -> bool {
+        self.host.trim().starts_with("*.")
+    }
+}
+
+impl DnsResolverConfig {
+    /// The server address of a `server` resolver, with the default port filled in.
+    pub fn server_addr(address: &str) -> Option<SocketAddr> {
+        address.parse::<SocketAddr>().ok().or_else(|| {
+            address
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .ok()
+                .map(|ip| SocketAddr::new(ip, 53))
+        })
+    }
+}
+
+impl DnsConfig {
+    /// Addresses of the enabled override for `host`; exact hosts win over
+    /// wildcards, and among wildcards the longest domain wins.
+    pub fn override_for(&self, host: &str) -> Option<&[IpAddr]> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let enabled = || self.overrides.iter().filter(|o| o.enabled);
+        enabled()
+            .find(|o| !o.is_wildcard() && o.matches(&host))
+            .or_else(|| {
+                enabled()
+                    .filter(|o| o.is_wildcard() && o.matches(&host))
+                    .min_by_key(|o| Reverse(o.host.trim().trim_end_matches('.').len()))
+            })
+            .map(|o| o.ips.as_slice())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (idx, entry) in self.overrides.iter().enumerate() {
+            let host = entry.host.trim();
+            let name = host.strip_prefix("*.").unwrap_or(host);
+            if name.is_empty()
+                || name.contains(['*', '/', ':', ' '])
+                || name.starts_with('.')
+                || name.contains("..")
+            {
+                return Err(format!(
+                    "override #{}: host {:?} must be a host name or *.domain",
+                    idx + 1,
+                    entry.host
+                ));
+            }
+            if entry.ips.is_empty() {
+                return Err(format!(
+                    "override #{}: {} needs at least one IP",
+                    idx + 1,
+                    entry.host
+                ));
+            }
+        }
+        match &self.resolver {
+            DnsResolverConfig::System => {}
+            DnsResolverConfig::Server { address } => {
+                if DnsResolverConfig::server_addr(address.trim()).is_none() {
+                    return Err(format!(
+                        "DNS server {address:?} must be an IP address, optionally with a port"
+                    ));
+                }
+            }
+            DnsResolverConfig::DnsOverHttps { url } => {
+                let uri: http::Uri = url
+                    .parse()
+                    .map_err(|_| format!("DNS-over-HTTPS URL {url:?} is invalid"))?;
+                if uri.scheme_str() != Some("https") || uri.host().is_none() {
+                    return Err(format!(
+                        "DNS-over-HTTPS URL {url:?} must be an https:// URL"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct DnsDao {
+    store: Arc<DataStore>,
+}
+
+impl DnsDao {
+    pub fn new(store: Arc<DataStore>) -> Self {
+        Self { store }
+    }
+
+    fn path(&self) -> std::path::PathBuf {
+        self.store.setting_path("dns")
+    }
+
+    pub async fn get_dns_config(&self) -> Result<DnsConfig> {
+        read_json_or_default(&self.path()).await
+    }
+
+    pub async fn update_dns_config(&self, config: DnsConfig) -> Result<()> {
+        write_json_atomic(&self.path(), &config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dns_config_crud_and_overrides() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dao = DnsDao::new(DataStore::new(dir.path()).await?);
+        assert_eq!(dao.get_dns_config().await?, DnsConfig::default());
+
+        let config: DnsConfig = serde_json::from_value(serde_json::json!({
+            "overrides": [
+                { "host": "*.example.com", "ips": ["10.0.0.2"] },
+                { "host": " *.eu.example.com ", "ips": ["10.0.0.4"] },
+                { "host": "api.example.com", "ips": ["10.0.0.1", "::1"] },
+                { "host": "off.example.com", "ips": ["10.0.0.3"], "enabled": false }
+            ],
+            "resolver": { "type": "server", "address": "1.1.1.1" }
+        }))?;
+        assert!(config.validate().is_ok());
+        dao.update_dns_config(config.clone()).await?;
+        let config = dao.get_dns_config().await?;
+
+        let ips = |host: &str| config.override_for(host).map(<[IpAddr]>::to_vec);
+        assert_eq!(
+            ips("API.example.com."),
+            Some(vec!["10.0.0.1".parse()?, "::1".parse()?])
+        );
+        assert_eq!(ips("cdn.eu.example.com"), Some(vec!["10.0.0.4".parse()?]));
+        assert_eq!(ips("cdn.us.example.com"), Some(vec!["10.0.0.2".parse()?]));
+        assert_eq!(ips("off.example.com"), Some(vec!["10.0.0.2".parse()?]));
+        assert_eq!(ips("example.com"), None);
+        assert_eq!(
+            DnsResolverConfig::server_addr("1.1.1.1"),
+            Some("1.1.1.1:53".parse()?)
+        );
+
+        let invalid = DnsConfig {
+            resolver: DnsResolverConfig::DnsOverHttps {
+                url: "http://dns.example/dns-query".to_string(),
+            },
+            ..config
+        };
+        assert!(invalid.validate().is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 pub mod api_studio;
 pub mod capture_rules_dao;
 pub mod client_proxy_dao;
+pub mod dns_dao;
 pub mod general_setting_dao;
 pub mod https_capture_dao;
 pub mod net_request_dao;
@@ -1,5 +1,8 @@
 use anyhow::{Result, anyhow};
+use lynx_dsl::{NamedMatchers, compile_match_expr_with_matchers};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::dao::request_processing_dao::types::DEFAULT_PROJECT_ID;
@@ -10,6 +13,13 @@ use crate::storage::{DataStore, read_json_or_default, write_json_atomic};
 pub struct RuleProject {
     pub id: String,
     pub name: String,
+    /// Named matchers (`@name` → match expression) that this project's rules can reference.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub matchers: BTreeMap<String, String>,
+    /// Directory of the project's `.lynx.json`, recorded by `rules pull`; rule
+    /// handlers resolve relative file paths against it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,11 +36,43 @@ impl Default for ProjectsFile {
             projects: vec![RuleProject {
                 id: DEFAULT_PROJECT_ID.to_string(),
                 name: "Default".to_string(),
+                matchers: BTreeMap::new(),
+                root_dir: None,
             }],
         }
     }
 }
 
+impl ProjectsFile {
+    pub fn project(&self, project_id: &str) -> Option<&RuleProject> {
+        self.projects
+            .iter()
+            .find(|project| project.id == project_id)
+    }
+
+    /// Named matchers of `project_id`; empty for unknown projects.
+    pub fn matchers(&self, project_id: &str) -> NamedMatchers {
+        self.project(project_id)
+            .map(|project| project.matchers.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Root directories of the projects that have one, by project id.
+    pub fn root_dirs(&self) -> HashMap<String, PathBuf> {
+        self.projects
+            .iter()
+            .filter_map(|project| Some((project.id.clone(), project.root_dir.clone()?)))
+            .collect()
+    }
+
+    pub fn all_matchers(&self) -> HashMap<String, NamedMatchers> {
+        self.projects
+            .iter()
+            .map(|project| (project.id.clone(), project.matchers.iter().collect()))
+            .collect()
+    }
+}
+
 pub struct ProjectsDao {
     store: Arc<DataStore>,
 }
@@ -49,7 +91,126 @@ impl ProjectsDao {
     }
 
     pub async fn save_projects(&self, file: ProjectsFile) -> Result<()> {
-        write_json_atomic(&self.path(), &file).await
+        write_json_atomic(&self.path(), &file).await?;
+        // Compiled rules inline the projects' named matchers.
+        self.store.invalidate_rules_cache().await;
+        Ok(())
+    }
+
+    pub async fn project_matchers(&self, project_id: &str) -> Result<NamedMatchers> {
+        Ok(self.get_projects().await?.matchers(project_id))
+    }
+
+    /// Replace the named matchers of `project_id`.
+    ///
+    /// Rejects definitions that do not resolve (bad names, undefined references,
+    /// cycles) and changes that would break a rule of the project.
+    pub async fn set_matchers(
+        &self,
+        project_id: &str,
+        matchers: BTreeMap<String, String>,
+    ) -> Result<RuleProject> {
+        let matchers: BTreeMap<String, String> = matchers
+            .into_iter()
+            .map(|(name, expr)| (lynx_dsl::matchers::normalize_matcher_name(&name), expr))
+            .collect();
+        let named: NamedMatchers = matchers.iter().collect();
+        if let Some((name, error)) = named.check().into_iter().next() {
+            return Err(anyhow!("matcher @{name} is invalid: {error}"));
+        }
+        for rule in self.store.get_rules_cache().await? {
+            if rule.project != project_id {
+                continue;
+            }
+            compile_match_expr_with_matchers(rule.capture.match_expr.trim(), &named).map_err(
+                |error| {
+                    anyhow!(
+                        "rule {} ({}) no longer compiles: {error}",
+                        rule.id.unwrap_or(-1),
+                        rule.name
+                    )
+                },
+            )?;
+        }
+
+        let mut file = self.ensure_default().await?;
+        let project = file
+            .projects
+            .iter_mut()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| anyhow!("project not found: {project_id}"))?;
+        project.matchers = matchers;
+        let updated = project.clone();
+        self.save_projects(file).await?;
+        Ok(updated)
+    }
+
+    /// Remember where `project_id`'s config file lives. Unknown projects are ignored.
+    pub async fn record_root_dir(&self, project_id: &str, root_dir: &Path) -> Result<()> {
+        let mut file = self.ensure_default().await?;
+        let Some(project) = file.projects.iter_mut().find(|p| p.id == project_id) else {
+            return Ok(());
+        };
+        if project.root_dir.as_deref() == Some(root_dir) {
+            return Ok(());
+        }
+        project.root_dir = Some(root_dir.to_path_buf());
+        self.save_projects(file).await
+    }
+
+    /// Locate a file referenced by one of `project_id`'s rules.
+    ///
+    /// Absolute paths are used as-is. Relative paths are tried against the
+    /// project's root directory first, then against `fallback_dir`.
+    pub async fn resolve_project_file(
+        &self,
+        project_id: &str,
+        path: &str,
+        fallback_dir: &Path,
+    ) -> Result<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+        let root_dir = self.store.project_root_dir(project_id).await?;
+        let candidates: Vec<PathBuf> = root_dir
+            .as_deref()
+            .into_iter()
+            .chain(std::iter::once(fallback_dir))
+            .map(|dir| dir.join(path))
+            .collect();
+        for candidate in &candidates {
+            if tokio::fs::try_exists(candidate).await.unwrap_or(false) {
+                return Ok(candidate.clone());
+            }
+        }
+        let tried: Vec<String> = candidates
+            .iter()
+            .map(|candidate| candidate.display().to_string())
+            .collect();
+        Err(anyhow!(
+            "{} not found (tried {})",
+            path.display(),
+            tried.join(", ")
+        ))
+    }
+
+    /// Where a file written by one of `project_id`'s rules goes.
+    ///
+    /// Absolute paths are used as-is. Relative paths go under the project's
+    /// root directory, or under `fallback_dir` when it has none.
+    pub async fn project_file_path(
+        &self,
+        project_id: &str,
+        path: &str,
+        fallback_dir: &Path,
+    ) -> Result<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+        let root_dir = self.store.project_root_dir(project_id).await?;
+        Ok(root_dir.as_deref().unwrap_or(fallback_dir).join(path))
     }
 
     pub async fn ensure_default(&self) -> Result<ProjectsFile> {
@@ -80,7 +241,12 @@ impl ProjectsDao {
         if file.projects.iter().any(|p| p.id == id) {
             return Err(anyhow!("project already exists: {id}"));
         }
-        let project = RuleProject { id, name };
+        let project = RuleProject {
+            id,
+            name,
+            matchers: BTreeMap::new(),
+            root_dir: None,
+        };
         file.projects.push(project.clone());
         self.save_projects(file).await?;
         Ok(project)
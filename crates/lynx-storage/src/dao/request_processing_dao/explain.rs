@@ -0,0 +1,187 @@
+//! Per-rule match explanations for one request (`rules.explain`, `lynx rules test`).
+
+use anyhow::{Result, anyhow};
+use axum::extract::Request;
+use bytes::Bytes;
+use http_body_util::Empty;
+use lynx_dsl::{
+    ExplainKind, Explanation, NamedMatchers, RequestFacts, Span, compile_with_matchers,
+    explain_program,
+};
+use serde::Serialize;
+
+use super::types::RequestRule;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedPredicate {
+    pub description: String,
+    pub span: Option<Span>,
+    /// The `matchExpr` text covered by `span`.
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleExplanation {
+    pub rule_id: Option<i32>,
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub match_expr: String,
+    pub matched: bool,
+    /// Matched and enabled, so its handlers take part in processing.
+    pub applied: bool,
+    pub failed: Vec<FailedPredicate>,
+    pub explanation: Option<Explanation>,
+    /// Set when `matchExpr` does not compile.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedHandler {
+    pub rule_id: Option<i32>,
+    pub rule_name: String,
+    pub handler_id: Option<i32>,
+    pub kind: String,
+    pub execution_order: i32,
+    /// False when an earlier terminal handler (block, local file) answers first.
+    pub reached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesExplainReport {
+    pub project: String,
+    pub facts: RequestFacts,
+    pub rules: Vec<RuleExplanation>,
+    pub handlers: Vec<PlannedHandler>,
+}
+
+/// Build a body-less request for explaining rules without live traffic.
+///
+/// `url` should be absolute (`https://host/path?query`) so host, scheme and port are known.
+pub fn probe_request(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+) -> Result<Request<Empty<Bytes>>> {
+    let mut builder = Request::builder()
+        .method(method.to_ascii_uppercase().as_str())
+        .uri(url);
+    for (name, value) in headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+        .body(Empty::new())
+        .map_err(|error| anyhow!("invalid probe request: {error}"))
+}
+
+/// Explain every rule against `facts`, in the order given (priority order for cached rules).
+pub fn explain_rules(
+    rules: &[RequestRule],
+    facts: &RequestFacts,
+    matchers: &NamedMatchers,
+) -> Vec<RuleExplanation> {
+    rules
+        .iter()
+        .map(|rule| explain_rule(rule, facts, matchers))
+        .collect()
+}
+
+/// Predicates inlined from a named matcher are blamed on its `@name` reference.
+pub fn explain_rule(
+    rule: &RequestRule,
+    facts: &RequestFacts,
+    matchers: &NamedMatchers,
+) -> RuleExplanation {
+    let expr = rule.capture.match_expr.trim();
+    let mut result = RuleExplanation {
+        rule_id: rule.id,
+        name: rule.name.clone(),
+        priority: rule.priority,
+        enabled: rule.enabled,
+        match_expr: expr.to_string(),
+        matched: false,
+        applied: false,
+        failed: Vec::new(),
+        explanation: None,
+        error: None,
+    };
+
+    let (program, source_map) = match compile_with_matchers(expr, matchers) {
+        Ok(compiled) => compiled,
+        Err(error) => {
+            result.error = Some(error.to_string());
+            return result;
+        }
+    };
+
+    let explanation = explain_program(&program, Some(&source_map), facts);
+    result.matched = explanation.matched;
+    result.applied = explanation.matched && rule.enabled;
+    result.failed = explanation
+        .failed_nodes()
+        .into_iter()
+        .map(|node| {
+            let source = node
+                .span
+                .and_then(|span| expr.get(span.start..span.end))
+                .map(str::to_string);
+            let description = match node.kind {
+                ExplainKind::Not => match &source {
+                    Some(text) => format!("{text} (negated expression matched)"),
+                    None => "negated expression matched".to_string(),
+                },
+                _ => node.description.clone().unwrap_or_default(),
+            };
+            FailedPredicate {
+                description,
+                span: node.span,
+                source,
+            }
+        })
+        .collect();
+    result.explanation = Some(explanation);
+    result
+}
+
+/// Handlers that would run for the applied rules, in execution order.
+///
+/// Mirrors `RequestProcessingService`: enabled handlers of applied rules are
+/// collected in rule order, then stably sorted by `execution_order`.
+pub fn plan_handlers(
+    rules: &[RequestRule],
+    explanations: &[RuleExplanation],
+) -> Vec<PlannedHandler> {
+    let mut planned = Vec::new();
+    for (rule, explanation) in rules.iter().zip(explanations) {
+        if !explanation.applied {
+            continue;
+        }
+        for handler in rule.handlers.iter().filter(|handler| handler.enabled) {
+            planned.push((rule, handler));
+        }
+    }
+    planned.sort_by_key(|(_, handler)| handler.execution_order);
+
+    let mut reached = true;
+    planned
+        .into_iter()
+        .map(|(rule, handler)| {
+            let entry = PlannedHandler {
+                rule_id: rule.id,
+                rule_name: rule.name.clone(),
+                handler_id: handler.id,
+                kind: handler.handler_type.kind_label().to_string(),
+                execution_order: handler.execution_order,
+                reached,
+            };
+            if handler.handler_type.is_terminal() {
+                reached = false;
+            }
+            entry
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A targeted edit of a request or response body.
+///
+/// Transforms run in order after `modifyBody`, on the decoded body; the result
+/// is re-encoded with the message's original `content-encoding`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum BodyTransform {
+    /// Regex find/replace over the body text; `replacement` may use `$1` or
+    /// `${name}` to refer to capture groups
+    #[serde(rename_all = "camelCase")]
+    RegexReplace {
+        pattern: String,
+        replacement: String,
+    },
+    /// RFC 6902 JSON Patch operations
+    JsonPatch { patch: Vec<Value> },
+    /// RFC 7396 JSON merge patch
+    MergePatch { patch: Value },
+    /// Sets every node a JSONPath expression matches. When nothing matches and
+    /// the last segment names a member (`$.user.role`), the member is added.
+    JsonPathSet { path: String, value: Value },
+    /// Removes every node a JSONPath expression matches
+    JsonPathDelete { path: String },
+}
+
+impl BodyTransform {
+    /// Checks patterns, patch documents and JSONPath expressions.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::RegexReplace { pattern, .. } => regex::Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("invalid regex {pattern:?}: {e}")),
+            Self::JsonPatch { patch } => {
+                serde_json::from_value::<json_patch::Patch>(Value::Array(patch.clone()))
+                    .map(|_| ())
+                    .map_err(|e| format!("invalid JSON Patch: {e}"))
+            }
+            Self::MergePatch { .. } => Ok(()),
+            Self::JsonPathSet { path, .. } | Self::JsonPathDelete { path } => {
+                serde_json_path::JsonPath::parse(path)
+                    .map(|_| ())
+                    .map_err(|e| format!("invalid JSONPath {path:?}: {e}"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_body_transform_serialization_and_validation() {
+        let transforms: Vec<BodyTransform> = serde_json::from_value(json!([
+            { "type": "regexReplace", "pattern": "(\\d+)", "replacement": "<$1>" },
+            { "type": "jsonPatch", "patch": [{ "op": "remove", "path": "/a" }] },
+            { "type": "mergePatch", "patch": { "a": null } },
+            { "type": "jsonPathSet", "path": "$.user.role", "value": "admin" },
+            { "type": "jsonPathDelete", "path": "$..secret" }
+        ]))
+        .unwrap();
+        assert!(transforms.iter().all(|t| t.validate().is_ok()));
+
+        let invalid = [
+            BodyTransform::RegexReplace {
+                pattern: "(".to_string(),
+                replacement: String::new(),
+            },
+            BodyTransform::JsonPatch {
+                patch: vec![json!({ "op": "explode", "path": "/a" })],
+            },
+            BodyTransform::JsonPathDelete {
+                path: "$[".to_string(),
+            },
+        ];
+        assert!(invalid.iter().all(|t| t.validate().is_err()));
+    }
+}
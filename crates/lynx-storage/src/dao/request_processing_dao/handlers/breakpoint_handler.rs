@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// Breakpoint handler configuration.
+///
+/// Matched requests (or their responses) are held until they are resumed from
+/// the UI with `breakpoint.resume`, optionally edited, or aborted. A held
+/// exchange continues unchanged once `timeoutMs` has passed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakpointConfig {
+    #[serde(default)]
+    pub phase: BreakpointPhase,
+    /// Continue unchanged after this long (default 60000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Where the exchange is held.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakpointPhase {
+    /// Before the request is sent upstream
+    #[default]
+    Request,
+    /// After the upstream response arrives, before the client gets it
+    Response,
+    /// At both points
+    Both,
+}
+
+impl BreakpointPhase {
+    pub fn holds_request(self) -> bool {
+        matches!(self, Self::Request | Self::Both)
+    }
+
+    pub fn holds_response(self) -> bool {
+        matches!(self, Self::Response | Self::Both)
+    }
+}
+
+impl BreakpointConfig {
+    pub const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+    /// Longest a breakpoint may hold an exchange.
+    pub const MAX_TIMEOUT_MS: u64 = 3_600_000;
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(Self::DEFAULT_TIMEOUT_MS)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.timeout_ms {
+            Some(0) => Err("timeoutMs must be greater than 0".to_string()),
+            Some(ms) if ms > Self::MAX_TIMEOUT_MS => Err(format!(
+                "timeoutMs must be at most {} (one hour)",
+                Self::MAX_TIMEOUT_MS
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_timeout_and_phases() {
+        let config: BreakpointConfig =
+            serde_json::from_value(serde_json::json!({ "phase": "both" })).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.timeout_ms(), BreakpointConfig::DEFAULT_TIMEOUT_MS);
+        assert!(config.phase.holds_request() && config.phase.holds_response());
+        assert!(!BreakpointPhase::Response.holds_request());
+
+        let zero = BreakpointConfig {
+            timeout_ms: Some(0),
+            ..config
+        };
+        assert!(zero.validate().is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+/// CORS handler configuration.
+///
+/// Preflight requests (`OPTIONS` with `Origin` and
+/// `Access-Control-Request-Method`) are answered locally: `204` with the
+/// configured headers when the origin is allowed, `403` otherwise. On every
+/// other response, upstream `Access-Control-*` headers are replaced by the
+/// configured ones.
+#[derive(Debug, Serialize, Deserialize, Clone, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CorsConfig {
+    /// Exact origins such as `http://localhost:5173`, `https://*.example.com`
+    /// for subdomains, or `*`; empty allows any origin
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_origins: Vec<String>,
+    /// Answer with the request's `Origin` instead of `*` when any origin is allowed
+    #[serde(default)]
+    pub echo_origin: bool,
+    #[serde(default = "default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    /// Empty allows whatever the preflight asks for in `Access-Control-Request-Headers`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_headers: Vec<String>,
+    /// Response headers scripts may read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expose_headers: Vec<String>,
+    /// Send `Access-Control-Allow-Credentials: true`; implies echoing the origin
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// How long browsers may cache a preflight answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u32>,
+}
+
+fn default_allowed_methods() -> Vec<String> {
+    CorsConfig::DEFAULT_METHODS
+        .iter()
+        .map(|method| method.to_string())
+        .collect()
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            echo_origin: false,
+            allowed_methods: default_allowed_methods(),
+            allowed_headers: Vec::new(),
+            expose_headers: Vec::new(),
+            allow_credentials: false,
+            max_age_secs: None,
+        }
+    }
+}
+
+impl CorsConfig {
+    pub const DEFAULT_METHODS: [&str; 7] =
+        ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+    fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.is_empty()
+            || self
+                .allowed_origins
+                .iter()
+                .any(|allowed| allowed.trim() == "*")
+    }
+
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allows_any_origin()
+            || self.allowed_origins.iter().any(|allowed| {
+                let allowed = allowed.trim();
+                match allowed.split_once("://*.") {
+                    Some((scheme, domain)) => origin
+                        .strip_prefix(scheme)
+                        .and_then(|rest| rest.strip_prefix("://"))
+                        .and_then(|host| {
+                            host.len()
+                                .checked_sub(domain.len() + 1)
+                                .map(|split| host.split_at(split))
+                        })
+                        .is_some_and(|(sub, tail)| {
+                            !sub.is_empty()
+                                && tail.starts_with('.')
+                                && tail[1..].eq_ignore_ascii_case(domain)
+                        }),
+                    None => allowed.eq_ignore_ascii_case(origin),
+                }
+            })
+    }
+
+    /// `Access-Control-Allow-Origin` for a request from `origin`, or `None`
+    /// when the origin is not allowed.
+    pub fn allow_origin_value<'a>(&self, origin: &'a str) -> Option<&'a str> {
+        if !self.allows_origin(origin) {
+            return None;
+        }
+        if self.varies_by_origin() {
+            Some(origin)
+        } else {
+            Some("*")
+        }
+    }
+
+    /// Whether the allowed origin depends on the request, so caches need `Vary: Origin`.
+    pub fn varies_by_origin(&self) -> bool {
+        self.echo_origin || self.allow_credentials || !self.allows_any_origin()
+    }
+
+    /// Short description of the allowed origins.
+    pub fn origins_label(&self) -> String {
+        if self.allows_any_origin() {
+            "*".to_string()
+        } else {
+            self.allowed_origins
+                .iter()
+                .map(|origin| origin.trim())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for origin in &self.allowed_origins {
+            let origin = origin.trim();
+            if origin == "*" || origin == "null" {
+                continue;
+            }
+            let uri: http::Uri = origin
+                .replacen("://*.", "://", 1)
+                .parse()
+                .map_err(|_| format!("allowedOrigins: {origin:?} is not an origin"))?;
+            if uri.scheme().is_none()
+                || uri.host().is_none()
+                || origin.ends_with('/')
+                || uri
+                    .path_and_query()
+                    .is_some_and(|path| path.as_str() != "/")
+            {
+                return Err(format!(
+                    "allowedOrigins: {origin:?} must look like https://example.com"
+                ));
+            }
+        }
+        if self.allowed_methods.is_empty() {
+            return Err("allowedMethods must not be empty".to_string());
+        }
+        if let Some(method) = self
+            .allowed_methods
+            .iter()
+            .find(|method| http::Method::from_bytes(method.trim().as_bytes()).is_err())
+        {
+            return Err(format!("allowedMethods: {method:?} is not a method"));
+        }
+        for (field, names) in [
+            ("allowedHeaders", &self.allowed_headers),
+            ("exposeHeaders", &self.expose_headers),
+        ] {
+            if let Some(name) = names.iter().find(|name| {
+                name.trim() != "*" && http::HeaderName::from_bytes(name.trim().as_bytes()).is_err()
+            }) {
+                return Err(format!("{field}: {name:?} is not a header name"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_origins_and_picks_allow_origin() {
+        let any = CorsConfig::default();
+        assert_eq!(any.allow_origin_value("http://localhost:5173"), Some("*"));
+        assert!(!any.varies_by_origin());
+
+        let credentials = CorsConfig {
+            allow_credentials: true,
+            ..CorsConfig::default()
+        };
+        assert_eq!(
+            credentials.allow_origin_value("http://localhost:5173"),
+            Some("http://localhost:5173")
+        );
+
+        let listed: CorsConfig = serde_json::from_value(json!({
+            "allowedOrigins": ["http://localhost:5173", "https://*.example.com"]
+        }))
+        .unwrap();
+        assert!(listed.validate().is_ok());
+        assert_eq!(
+            listed.allowed_methods.len(),
+            CorsConfig::DEFAULT_METHODS.len()
+        );
+        assert!(listed.allows_origin("http://LOCALHOST:5173"));
+        assert!(listed.allows_origin("https://app.example.com"));
+        assert!(!listed.allows_origin("https://example.com"));
+        assert!(!listed.allows_origin("http://app.example.com"));
+        assert!(!listed.allows_origin("https://evilexample.com"));
+        assert_eq!(listed.allow_origin_value("http://localhost:3000"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        for config in [
+            json!({ "allowedOrigins": ["localhost:5173"] }),
+            json!({ "allowedOrigins": ["https://example.com/app"] }),
+            json!({ "allowedMethods": [] }),
+            json!({ "allowedMethods": ["GET POST"] }),
+            json!({ "allowedHeaders": ["x bad"] }),
+            json!({ "exposeHeaders": ["x:bad"] }),
+        ] {
+            let config: CorsConfig = serde_json::from_value(config).unwrap();
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+}
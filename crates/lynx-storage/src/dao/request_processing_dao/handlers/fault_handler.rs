@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Fault injection handler configuration.
+///
+/// Faults are applied to the upstream response. Each one rolls its own
+/// `probability`; the first fault that fires is applied and the rest are
+/// skipped.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FaultConfig {
+    pub faults: Vec<FaultRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FaultRule {
+    /// Chance of the fault firing, from 0.0 to 1.0 (default 1.0)
+    #[serde(default = "default_probability")]
+    pub probability: f64,
+    #[serde(flatten)]
+    pub fault: Fault,
+}
+
+fn default_probability() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Fault {
+    /// Drops the client connection
+    Reset {
+        #[serde(default)]
+        phase: ResetPhase,
+    },
+    /// Sends the first `bytes` bytes of the body, then drops the connection
+    Truncate { bytes: u64 },
+    /// Declares a `content-length` off by `delta` bytes from the real body
+    WrongContentLength { delta: i64 },
+    /// Pauses the body after `afterBytes` bytes (default: after the first chunk)
+    #[serde(rename_all = "camelCase")]
+    Stall {
+        after_bytes: Option<u64>,
+        duration_ms: u64,
+    },
+    /// Sends the body chunked and drops the connection instead of writing the
+    /// terminating zero-length chunk
+    MalformedChunked,
+    /// Serves the body gzip-encoded with a damaged deflate stream and checksum
+    CorruptGzip,
+}
+
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ResetPhase {
+    /// Close before any response byte is written
+    #[default]
+    BeforeHeaders,
+    /// Write the status line and headers, then close
+    AfterHeaders,
+}
+
+impl FaultConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.faults.is_empty() {
+            return Err("at least one fault is required".to_string());
+        }
+        for rule in &self.faults {
+            if !(0.0..=1.0).contains(&rule.probability) {
+                return Err(format!(
+                    "probability {} is outside 0.0..=1.0",
+                    rule.probability
+                ));
+            }
+            if let Fault::Stall { duration_ms: 0, .. } = rule.fault {
+                return Err("stall durationMs must be greater than 0".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_config_serialization_and_validation() {
+        let config: FaultConfig = serde_json::from_str(
+            r#"{"faults":[
+                {"type":"reset","phase":"afterHeaders","probability":0.1},
+                {"type":"truncate","bytes":128},
+                {"type":"stall","durationMs":5000}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.faults[0].fault,
+            Fault::Reset {
+                phase: ResetPhase::AfterHeaders
+            }
+        );
+        assert_eq!(config.faults[1].probability, 1.0);
+
+        let bad_probability = FaultConfig {
+            faults: vec![FaultRule {
+                probability: 1.5,
+                fault: Fault::CorruptGzip,
+            }],
+        };
+        assert!(bad_probability.validate().is_err());
+        assert!(FaultConfig::default().validate().is_err());
+    }
+}
@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    BlockHandlerConfig, DelayHandlerConfig, DelayType, HtmlScriptInjectorConfig, LocalFileConfig,
-    ModifyRequestConfig, ThrottleHandlerConfig, ThrottlePreset,
-    modify_response_handler::ModifyResponseConfig, proxy_forward_handler::ProxyForwardConfig,
+    BlockHandlerConfig, BreakpointConfig, CorsConfig, DelayHandlerConfig, DelayType, Fault,
+    FaultConfig, FaultRule, HtmlScriptInjectorConfig, LocalFileConfig, MirrorConfig,
+    MockResponseConfig, ModifyHeaders, ModifyRequestConfig, RecordConfig, ReplayConfig,
+    ReplayFallback, ScriptHandlerConfig, SequenceConfig, StreamMockConfig, ThrottleHandlerConfig,
+    ThrottlePreset, UpstreamProxyConfig, WasmPluginConfig, WebSocketInterceptConfig,
+    WebSocketMockConfig, modify_response_handler::ModifyResponseConfig,
+    proxy_forward_handler::ProxyForwardConfig,
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone, schemars::JsonSchema)]
@@ -17,6 +21,64 @@ pub enum HandlerRuleType {
     HtmlScriptInjector(HtmlScriptInjectorConfig),
     Delay(DelayHandlerConfig),
     Throttle(ThrottleHandlerConfig),
+    Script(ScriptHandlerConfig),
+    WasmPlugin(WasmPluginConfig),
+    MockResponse(MockResponseConfig),
+    Record(RecordConfig),
+    Replay(ReplayConfig),
+    Fault(FaultConfig),
+    Sequence(SequenceConfig),
+    UpstreamProxy(UpstreamProxyConfig),
+    Mirror(MirrorConfig),
+    Breakpoint(BreakpointConfig),
+    WebSocketIntercept(WebSocketInterceptConfig),
+    WebSocketMock(WebSocketMockConfig),
+    StreamMock(StreamMockConfig),
+    Cors(CorsConfig),
+}
+
+impl HandlerRuleType {
+    /// Stable snake_case name used in logs, errors and rule explanations.
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            Self::Block(_) => "block",
+            Self::LocalFile(_) => "local_file",
+            Self::ModifyRequest(_) => "modify_request",
+            Self::ModifyResponse(_) => "modify_response",
+            Self::ProxyForward(_) => "proxy_forward",
+            Self::HtmlScriptInjector(_) => "html_script_injector",
+            Self::Delay(_) => "delay",
+            Self::Throttle(_) => "throttle",
+            Self::Script(_) => "script",
+            Self::WasmPlugin(_) => "wasm_plugin",
+            Self::MockResponse(_) => "mock_response",
+            Self::Record(_) => "record",
+            Self::Replay(_) => "replay",
+            Self::Fault(_) => "fault",
+            Self::Sequence(_) => "sequence",
+            Self::UpstreamProxy(_) => "upstream_proxy",
+            Self::Mirror(_) => "mirror",
+            Self::Breakpoint(_) => "breakpoint",
+            Self::WebSocketIntercept(_) => "websocket_intercept",
+            Self::WebSocketMock(_) => "websocket_mock",
+            Self::StreamMock(_) => "stream_mock",
+            Self::Cors(_) => "cors",
+        }
+    }
+
+    /// Whether the handler answers the request locally, so later handlers never run.
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Block(_)
+            | Self::LocalFile(_)
+            | Self::MockResponse(_)
+            | Self::Sequence(_)
+            | Self::WebSocketMock(_)
+            | Self::StreamMock(_) => true,
+            Self::Replay(config) => config.fallback != ReplayFallback::Passthrough,
+            _ => false,
+        }
+    }
 }
 
 /// Handler rule configuration
@@ -53,6 +115,7 @@ impl HandlerRule {
                 file_path,
                 content_type,
                 status_code,
+                ..Default::default()
             }),
             execution_order: 50,
             enabled: true,
@@ -68,10 +131,11 @@ impl HandlerRule {
         Self {
             id: None,
             handler_type: HandlerRuleType::ModifyRequest(ModifyRequestConfig {
-                modify_headers,
+                modify_headers: modify_headers.map(ModifyHeaders::from),
                 modify_body,
                 modify_method,
                 modify_url,
+                body_transforms: None,
             }),
             execution_order: 20,
             enabled: true,
@@ -87,10 +151,11 @@ impl HandlerRule {
         Self {
             id: None,
             handler_type: HandlerRuleType::ModifyResponse(ModifyResponseConfig {
-                modify_headers,
+                modify_headers: modify_headers.map(ModifyHeaders::from),
                 modify_body,
                 modify_method,
                 modify_status_code,
+                body_transforms: None,
             }),
             execution_order: 80,
             enabled: true,
@@ -155,6 +220,158 @@ impl HandlerRule {
             enabled: true,
         }
     }
+
+    pub fn script_handler(script: String) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Script(ScriptHandlerConfig {
+                script,
+                ..Default::default()
+            }),
+            execution_order: 30,
+            enabled: true,
+        }
+    }
+
+    pub fn wasm_plugin_handler(plugin: String, config: serde_json::Value) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::WasmPlugin(WasmPluginConfig {
+                plugin,
+                config,
+                ..Default::default()
+            }),
+            execution_order: 30,
+            enabled: true,
+        }
+    }
+
+    pub fn mock_response_handler(status_code: Option<u16>, body: Option<String>) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::MockResponse(MockResponseConfig {
+                status_code,
+                body,
+                ..Default::default()
+            }),
+            execution_order: 50,
+            enabled: true,
+        }
+    }
+
+    pub fn record_handler(cassette: Option<String>) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Record(RecordConfig {
+                cassette,
+                ..Default::default()
+            }),
+            execution_order: 90,
+            enabled: true,
+        }
+    }
+
+    pub fn replay_handler(cassette: Option<String>, fallback: ReplayFallback) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Replay(ReplayConfig {
+                cassette,
+                fallback,
+                ..Default::default()
+            }),
+            execution_order: 50,
+            enabled: true,
+        }
+    }
+
+    pub fn fault_handler(fault: Fault) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Fault(FaultConfig {
+                faults: vec![FaultRule {
+                    probability: 1.0,
+                    fault,
+                }],
+            }),
+            execution_order: 95, // Run after response rewrites and recording
+            enabled: true,
+        }
+    }
+
+    pub fn sequence_handler(responses: Vec<MockResponseConfig>) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Sequence(SequenceConfig {
+                responses,
+                ..Default::default()
+            }),
+            execution_order: 50,
+            enabled: true,
+        }
+    }
+
+    pub fn upstream_proxy_handler(upstream: UpstreamProxyConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::UpstreamProxy(upstream),
+            execution_order: 10,
+            enabled: true,
+        }
+    }
+
+    pub fn mirror_handler(mirror: MirrorConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Mirror(mirror),
+            execution_order: 10,
+            enabled: true,
+        }
+    }
+
+    pub fn breakpoint_handler(breakpoint: BreakpointConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Breakpoint(breakpoint),
+            execution_order: 10,
+            enabled: true,
+        }
+    }
+
+    pub fn websocket_intercept_handler(intercept: WebSocketInterceptConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::WebSocketIntercept(intercept),
+            execution_order: 10,
+            enabled: true,
+        }
+    }
+
+    pub fn websocket_mock_handler(mock: WebSocketMockConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::WebSocketMock(mock),
+            execution_order: 50,
+            enabled: true,
+        }
+    }
+
+    pub fn stream_mock_handler(stream: StreamMockConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::StreamMock(stream),
+            execution_order: 50,
+            enabled: true,
+        }
+    }
+
+    pub fn cors_handler(cors: CorsConfig) -> Self {
+        Self {
+            id: None,
+            handler_type: HandlerRuleType::Cors(cors),
+            execution_order: 10,
+            enabled: true,
+        }
+    }
 }
 
 impl Default for HandlerRule {
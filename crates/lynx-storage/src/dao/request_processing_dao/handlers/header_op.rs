@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// One step of a header rewrite.
+///
+/// Values and replacements may contain templates such as
+/// `{{req.header.x-request-id}}`, `{{uuid}}` or `{{now}}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "op")]
+pub enum HeaderOp {
+    /// Replaces every value of `name` with `value`
+    Set { name: String, value: String },
+    /// Adds another value for `name`, keeping the existing ones
+    Append { name: String, value: String },
+    /// Removes every value of `name`
+    Remove { name: String },
+    /// Moves every value of `from` to `to`
+    Rename { from: String, to: String },
+    /// Regex find/replace over every value of `name`
+    RegexRewrite {
+        name: String,
+        pattern: String,
+        replacement: String,
+    },
+}
+
+/// Header changes of a modify handler.
+///
+/// New rules store an ordered list of [`HeaderOp`]s; the older name-to-value
+/// map is still accepted and means one `set` per entry.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum ModifyHeaders {
+    Ops(Vec<HeaderOp>),
+    Map(HashMap<String, String>),
+}
+
+impl ModifyHeaders {
+    /// The operations to run, in order. Map entries are sorted by name.
+    pub fn ops(&self) -> Vec<HeaderOp> {
+        match self {
+            Self::Ops(ops) => ops.clone(),
+            Self::Map(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort();
+                entries
+                    .into_iter()
+                    .map(|(name, value)| HeaderOp::Set {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Ops(ops) => ops.is_empty(),
+            Self::Map(map) => map.is_empty(),
+        }
+    }
+}
+
+impl From<HashMap<String, String>> for ModifyHeaders {
+    fn from(map: HashMap<String, String>) -> Self {
+        Self::Map(map)
+    }
+}
+
+impl From<Vec<HeaderOp>> for ModifyHeaders {
+    fn from(ops: Vec<HeaderOp>) -> Self {
+        Self::Ops(ops)
+    }
+}
+
+impl HeaderOp {
+    /// Checks header names and rewrite patterns.
+    pub fn validate(&self) -> Result<(), String> {
+        let names = match self {
+            Self::Set { name, .. }
+            | Self::Append { name, .. }
+            | Self::Remove { name }
+            | Self::RegexRewrite { name, .. } => vec![name],
+            Self::Rename { from, to } => vec![from, to],
+        };
+        for name in names {
+            http::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid header name {name:?}"))?;
+        }
+        if let Self::RegexRewrite { pattern, .. } = self {
+            regex::Regex::new(pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_legacy_header_map_is_still_accepted() {
+        let legacy: ModifyHeaders =
+            serde_json::from_value(json!({ "x-b": "2", "x-a": "1" })).unwrap();
+        assert_eq!(
+            legacy.ops(),
+            vec![
+                HeaderOp::Set {
+                    name: "x-a".to_string(),
+                    value: "1".to_string()
+                },
+                HeaderOp::Set {
+                    name: "x-b".to_string(),
+                    value: "2".to_string()
+                },
+            ]
+        );
+        // Stored maps round-trip unchanged.
+        assert_eq!(
+            serde_json::to_value(&legacy).unwrap(),
+            json!({ "x-b": "2", "x-a": "1" })
+        );
+
+        let ops: ModifyHeaders = serde_json::from_value(json!([
+            { "op": "append", "name": "set-cookie", "value": "b=2" },
+            { "op": "remove", "name": "server" },
+            { "op": "rename", "from": "x-old", "to": "x-new" },
+            { "op": "regexRewrite", "name": "location", "pattern": "^http:", "replacement": "https:" }
+        ]))
+        .unwrap();
+        assert_eq!(ops.ops().len(), 4);
+        assert!(ops.ops().iter().all(|op| op.validate().is_ok()));
+
+        let invalid = HeaderOp::Remove {
+            name: "bad header".to_string(),
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -1,10 +1,91 @@
 use serde::{Deserialize, Serialize};
 
-/// Local file handler configuration
+/// Local file handler configuration.
+///
+/// In `directory` mode `file_path` is a root folder and the rest of the
+/// request path is served from under it: after `strip_prefix` when set,
+/// otherwise the last path glob capture of the rule (`/app/**`), otherwise
+/// the whole path. Directories answer with their `index.html`, and with
+/// `spa_fallback` paths without a file extension that miss fall back to the
+/// root `index.html`.
 #[derive(Debug, Serialize, Deserialize, Default, Clone, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalFileConfig {
     pub file_path: String,
     pub content_type: Option<String>,
+    /// Answer every request with this status and the whole file; `Range` and
+    /// `If-None-Match` are only honoured (206, 304) without one
     pub status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mode: LocalFileMode,
+    /// Request path prefix removed before resolving under the root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_prefix: Option<String>,
+    /// Serve the root `index.html` for missing extensionless paths
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub spa_fallback: bool,
+}
+
+/// What `file_path` points at.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalFileMode {
+    /// A single file answering every matching request
+    #[default]
+    File,
+    /// A folder the request path is resolved under
+    Directory,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl LocalFileConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.file_path.trim().is_empty() {
+            return Err("file path is required".to_string());
+        }
+        if let Some(prefix) = &self.strip_prefix
+            && !prefix.starts_with('/')
+        {
+            return Err(format!("strip prefix {prefix:?} must start with '/'"));
+        }
+        if self.mode == LocalFileMode::File && (self.strip_prefix.is_some() || self.spa_fallback) {
+            return Err("strip prefix and SPA fallback need directory mode".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_directory_options() {
+        let config: LocalFileConfig = serde_json::from_value(serde_json::json!({
+            "filePath": "/srv/dist",
+            "mode": "directory",
+            "stripPrefix": "/app",
+            "spaFallback": true
+        }))
+        .unwrap();
+        assert_eq!(config.mode, LocalFileMode::Directory);
+        assert!(config.validate().is_ok());
+
+        let relative = LocalFileConfig {
+            strip_prefix: Some("app".to_string()),
+            ..config.clone()
+        };
+        assert!(relative.validate().is_err());
+
+        let single_file = LocalFileConfig {
+            mode: LocalFileMode::File,
+            ..config
+        };
+        assert!(single_file.validate().is_err());
+    }
 }
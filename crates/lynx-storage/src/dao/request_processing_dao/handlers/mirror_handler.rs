@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+/// Mirror handler configuration.
+///
+/// Matching requests, body included, are also sent to every target in the
+/// background. The client only ever gets the primary response; each mirrored
+/// exchange is captured as its own trace and compared against the primary
+/// response.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorConfig {
+    /// Base URLs such as `http://staging:8080` or `https://canary.internal/v2`;
+    /// the request path and query are appended
+    pub targets: Vec<String>,
+    /// Response headers left out of the diff (case-insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_headers: Vec<String>,
+    /// Give up on a mirror after this long (default 30000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl MirrorConfig {
+    pub const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+    /// Headers that differ on every response and are never compared.
+    pub const ALWAYS_IGNORED_HEADERS: [&str; 2] = ["date", "content-length"];
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(Self::DEFAULT_TIMEOUT_MS)
+    }
+
+    /// Whether a response header takes part in the diff.
+    pub fn compares_header(&self, name: &str) -> bool {
+        !Self::ALWAYS_IGNORED_HEADERS
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(name))
+            && !self
+                .ignore_headers
+                .iter()
+                .any(|ignored| ignored.trim().eq_ignore_ascii_case(name))
+    }
+
+    /// The URL a request for `path_and_query` is mirrored to at `target`.
+    pub fn target_url(target: &str, path_and_query: &str) -> String {
+        let base = target.trim().trim_end_matches('/');
+        let path = if path_and_query.starts_with('/') {
+            path_and_query.to_string()
+        } else {
+            format!("/{path_and_query}")
+        };
+        format!("{base}{path}")
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.targets.is_empty() {
+            return Err("at least one target is required".to_string());
+        }
+        for (idx, target) in self.targets.iter().enumerate() {
+            let uri: http::Uri = target
+                .trim()
+                .parse()
+                .map_err(|_| format!("target #{}: {target:?} is not a URL", idx + 1))?;
+            if !matches!(uri.scheme_str(), Some("http" | "https")) || uri.host().is_none() {
+                return Err(format!(
+                    "target #{}: {target:?} must be an http:// or https:// URL",
+                    idx + 1
+                ));
+            }
+            if uri.query().is_some() {
+                return Err(format!(
+                    "target #{}: {target:?} must not have a query; the request's is used",
+                    idx + 1
+                ));
+            }
+        }
+        if self.timeout_ms == Some(0) {
+            return Err("timeoutMs must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_targets_and_builds_urls() {
+        let config: MirrorConfig = serde_json::from_value(serde_json::json!({
+            "targets": ["http://staging:8080", "https://canary.internal/v2/"],
+            "ignoreHeaders": ["X-Request-Id"]
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.timeout_ms(), MirrorConfig::DEFAULT_TIMEOUT_MS);
+        assert_eq!(
+            MirrorConfig::target_url(&config.targets[1], "/users?id=1"),
+            "https://canary.internal/v2/users?id=1"
+        );
+        assert!(config.compares_header("content-type"));
+        assert!(!config.compares_header("x-request-id"));
+        assert!(!config.compares_header("Date"));
+
+        let relative = MirrorConfig {
+            targets: vec!["/staging".to_string()],
+            ..config.clone()
+        };
+        assert!(relative.validate().is_err());
+        assert!(MirrorConfig::default().validate().is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Mock response handler configuration.
+///
+/// Status, header values and the body are handlebars templates rendered
+/// against the request: `req.*`, `params` (path glob captures), `body` (the
+/// request body, parsed when it is JSON) and the `uuid` / `now` helpers.
+/// `bodyFile` reads the template from the rule's project directory, then from
+/// `<data dir>/mocks`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MockResponseConfig {
+    /// Response status (default 200)
+    pub status_code: Option<u16>,
+    /// Response headers; values are templates
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// Inline body template
+    pub body: Option<String>,
+    /// Path of a body template file
+    pub body_file: Option<String>,
+    /// Wait this long before answering
+    pub latency_ms: Option<u64>,
+}
+
+impl MockResponseConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(status) = self.status_code
+            && http::StatusCode::from_u16(status).is_err()
+        {
+            return Err(format!("invalid status code {status}"));
+        }
+        if self.body.is_some() && self.body_file.is_some() {
+            return Err("set either body or bodyFile, not both".to_string());
+        }
+        if self
+            .body_file
+            .as_deref()
+            .is_some_and(|path| path.trim().is_empty())
+        {
+            return Err("bodyFile is empty".to_string());
+        }
+        for name in self.headers.keys() {
+            http::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid header name {name:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_response_validation() {
+        let config: MockResponseConfig = serde_json::from_str(
+            r#"{"statusCode":201,"headers":{"x-id":"{{uuid}}"},"body":"{\"id\":\"{{params.[0]}}\"}"}"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let both = MockResponseConfig {
+            body: Some("a".to_string()),
+            body_file: Some("mocks/user.json".to_string()),
+            ..Default::default()
+        };
+        assert!(both.validate().is_err());
+
+        let bad_status = MockResponseConfig {
+            status_code: Some(42),
+            ..Default::default()
+        };
+        assert!(bad_status.validate().is_err());
+    }
+}
@@ -1,19 +1,56 @@
 pub mod block_handler;
+pub mod body_transform;
+pub mod breakpoint_handler;
+pub mod cors_handler;
 pub mod delay_handler;
+pub mod fault_handler;
 pub mod handler_rule;
+pub mod header_op;
 pub mod html_script_injector;
 pub mod local_file_handler;
+pub mod mirror_handler;
+pub mod mock_response_handler;
 pub mod modify_request_handler;
 pub mod modify_response_handler;
 pub mod proxy_forward_handler;
+pub mod record_handler;
+pub mod replay_handler;
+pub mod script_handler;
+pub mod sequence_handler;
+pub mod stream_mock_handler;
 pub mod throttle_handler;
+pub mod upstream_proxy_handler;
+pub mod wasm_plugin_handler;
+pub mod websocket_intercept_handler;
+pub mod websocket_mock_handler;
 
 pub use block_handler::BlockHandlerConfig;
+pub use body_transform::BodyTransform;
+pub use breakpoint_handler::{BreakpointConfig, BreakpointPhase};
+pub use cors_handler::CorsConfig;
 pub use delay_handler::{DelayHandlerConfig, DelayType};
+pub use fault_handler::{Fault, FaultConfig, FaultRule, ResetPhase};
 pub use handler_rule::HandlerRule;
+pub use header_op::{HeaderOp, ModifyHeaders};
 pub use html_script_injector::HtmlScriptInjectorConfig;
-pub use local_file_handler::LocalFileConfig;
+pub use local_file_handler::{LocalFileConfig, LocalFileMode};
+pub use mirror_handler::MirrorConfig;
+pub use mock_response_handler::MockResponseConfig;
 pub use modify_request_handler::ModifyRequestConfig;
 pub use modify_response_handler::ModifyResponseConfig;
-pub use proxy_forward_handler::ProxyForwardConfig;
+pub use proxy_forward_handler::{ProxyForwardConfig, ProxyForwardTarget, QueryMode};
+pub use record_handler::RecordConfig;
+pub use replay_handler::{ReplayConfig, ReplayFallback, ReplayMatchKey};
+pub use script_handler::ScriptHandlerConfig;
+pub use sequence_handler::{SequenceConfig, SequenceExhausted, SequenceScope};
+pub use stream_mock_handler::{StreamMockChunk, StreamMockConfig, StreamMockFormat};
 pub use throttle_handler::{EffectiveThrottle, ThrottleHandlerConfig, ThrottlePreset};
+pub use upstream_proxy_handler::{UpstreamProxyConfig, UpstreamProxyProtocol};
+pub use wasm_plugin_handler::WasmPluginConfig;
+pub use websocket_intercept_handler::{
+    WebSocketFrameAction, WebSocketFrameDirection, WebSocketFrameRule, WebSocketInterceptConfig,
+};
+pub use websocket_mock_handler::{
+    WebSocketMockClose, WebSocketMockConfig, WebSocketMockFrame, WebSocketMockPeriodic,
+    WebSocketMockReply,
+};
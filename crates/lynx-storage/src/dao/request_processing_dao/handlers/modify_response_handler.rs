@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use super::{BodyTransform, ModifyHeaders};
+
 /// Modify request handler configuration
 #[derive(Debug, Serialize, Deserialize, Default, Clone, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModifyResponseConfig {
-    pub modify_headers: Option<std::collections::HashMap<String, String>>,
+    /// Header operations; a plain name-to-value map is read as `set` ops
+    pub modify_headers: Option<ModifyHeaders>,
     pub modify_body: Option<String>,
     pub modify_method: Option<String>,
     pub modify_status_code: Option<u16>,
+    /// Edits applied in order after `modify_body`, on the decoded body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_transforms: Option<Vec<BodyTransform>>,
 }
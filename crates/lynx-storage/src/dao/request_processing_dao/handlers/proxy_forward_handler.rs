@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Deserializer, Serialize};
 
 fn normalize_optional_string(value: Option<String>) -> Option<String> {
@@ -28,6 +30,12 @@ where
     }))
 }
 
+/// Proxy forward (map remote) configuration.
+///
+/// `target_authority` and `target_path` may be `{{...}}` templates over the
+/// request (`req.*`), the path glob captures of the rule (`params.[0]`) and
+/// the groups of `path_regex` (`groups.[1]`, `groups.<name>`). A templated
+/// path replaces the request path; a plain one is prefixed to it.
 #[derive(Debug, Serialize, Deserialize, Default, Clone, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProxyForwardConfig {
@@ -37,6 +45,62 @@ pub struct ProxyForwardConfig {
     pub target_authority: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_non_empty_string")]
     pub target_path: Option<String>,
+    /// Regex matched against the request path; its groups feed the templates
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_non_empty_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub path_regex: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub query_mode: QueryMode,
+    /// Query parameters set by `query_mode`; values are templates
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub query_params: BTreeMap<String, String>,
+    /// Keep the client's `Host` header instead of the target authority
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub preserve_host: bool,
+    /// Weighted upstreams; one is picked per request and its fields override
+    /// the ones above
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<ProxyForwardTarget>,
+}
+
+/// How `query_params` combine with the request query.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryMode {
+    /// Forward the request query unchanged
+    #[default]
+    Keep,
+    /// Set `query_params` on top of the request query
+    Merge,
+    /// Send only `query_params`
+    Replace,
+}
+
+/// One upstream of a load-split forward.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyForwardTarget {
+    #[serde(default, deserialize_with = "deserialize_optional_non_empty_string")]
+    pub target_scheme: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_non_empty_string")]
+    pub target_authority: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_non_empty_string")]
+    pub target_path: Option<String>,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
 }
 
 impl ProxyForwardConfig {
@@ -49,7 +113,18 @@ impl ProxyForwardConfig {
             target_scheme: normalize_optional_string(target_scheme),
             target_authority: normalize_optional_string(target_authority),
             target_path: normalize_optional_string(target_path),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(pattern) = &self.path_regex {
+            regex::Regex::new(pattern).map_err(|e| format!("invalid pathRegex: {e}"))?;
         }
+        if !self.targets.is_empty() && self.targets.iter().all(|target| target.weight == 0) {
+            return Err("at least one target needs a positive weight".to_string());
+        }
+        Ok(())
     }
 
     pub fn optional_field(value: &Option<String>) -> Option<&str> {
@@ -61,6 +136,33 @@ impl ProxyForwardConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn validates_path_regex_and_target_weights() {
+        let mut config: ProxyForwardConfig = serde_json::from_value(serde_json::json!({
+            "targetPath": "/{{groups.rest}}",
+            "pathRegex": "^/api/v1/(?<rest>.*)$",
+            "queryMode": "merge",
+            "queryParams": { "debug": "1" },
+            "targets": [
+                { "targetAuthority": "a.internal:3000", "weight": 3 },
+                { "targetAuthority": "b.internal:3000" }
+            ]
+        }))
+        .expect("deserialize proxy forward config");
+        assert_eq!(config.query_mode, QueryMode::Merge);
+        assert_eq!(config.targets[1].weight, 1);
+        assert!(config.validate().is_ok());
+
+        config
+            .targets
+            .iter_mut()
+            .for_each(|target| target.weight = 0);
+        assert!(config.validate().is_err());
+        config.targets.clear();
+        config.path_regex = Some("(".to_string());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn deserializes_empty_proxy_forward_fields_as_none() {
         let config: ProxyForwardConfig = serde_json::from_value(serde_json::json!({
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Record handler configuration.
+///
+/// Every matched request and the response it got are appended to a cassette
+/// (JSON Lines) that a `Replay` handler can serve later. Relative paths are
+/// under the rule's project directory, or `<data dir>/cassettes/<project>`
+/// when the project has none. Credentials are stored as digests, see
+/// [`RecordConfig::redact_headers`].
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordConfig {
+    /// Cassette file (default `cassette.jsonl`)
+    pub cassette: Option<String>,
+    /// Request and response headers recorded as a SHA-256 digest instead of
+    /// their value (default [`RecordConfig::DEFAULT_REDACTED_HEADERS`]);
+    /// empty records every header as sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact_headers: Option<Vec<String>>,
+}
+
+impl RecordConfig {
+    pub const DEFAULT_CASSETTE: &str = "cassette.jsonl";
+    pub const DEFAULT_REDACTED_HEADERS: [&str; 5] = [
+        "authorization",
+        "proxy-authorization",
+        "cookie",
+        "set-cookie",
+        "x-api-key",
+    ];
+
+    pub fn cassette(&self) -> &str {
+        self.cassette.as_deref().unwrap_or(Self::DEFAULT_CASSETTE)
+    }
+
+    /// Whether the value of header `name` must not be written to the cassette.
+    pub fn redacts(&self, name: &str) -> bool {
+        match &self.redact_headers {
+            Some(names) => names
+                .iter()
+                .any(|redacted| redacted.trim().eq_ignore_ascii_case(name)),
+            None => Self::DEFAULT_REDACTED_HEADERS
+                .iter()
+                .any(|redacted| redacted.eq_ignore_ascii_case(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_redacts_credentials_by_default() {
+        let config: RecordConfig = serde_json::from_str("{}").unwrap();
+        assert!(config.redacts("authorization"));
+        assert!(config.redacts("Set-Cookie"));
+        assert!(!config.redacts("x-tenant"));
+
+        let config: RecordConfig =
+            serde_json::from_str(r#"{"redactHeaders":["x-tenant"]}"#).unwrap();
+        assert!(config.redacts("X-Tenant"));
+        assert!(!config.redacts("authorization"));
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use super::RecordConfig;
+
+/// Request fields a cassette entry must share with the request to be replayed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayMatchKey {
+    Method,
+    /// Host and port the request was sent to
+    Host,
+    Path,
+    /// Query parameters, in any order
+    Query,
+    /// SHA-256 of the request body
+    Body,
+}
+
+/// What to do when no cassette entry matches.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayFallback {
+    /// Send the request upstream
+    #[default]
+    Passthrough,
+    /// Answer 404
+    NotFound,
+    /// Fail the rule (502)
+    Error,
+}
+
+/// Replay handler configuration: answers from a cassette written by `Record`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayConfig {
+    /// Cassette file (default `cassette.jsonl`)
+    pub cassette: Option<String>,
+    /// Matching keys (default method, host, path and query)
+    pub match_on: Option<Vec<ReplayMatchKey>>,
+    /// Request headers whose values must also match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_headers: Vec<String>,
+    #[serde(default)]
+    pub fallback: ReplayFallback,
+}
+
+impl ReplayConfig {
+    pub const DEFAULT_MATCH_ON: [ReplayMatchKey; 4] = [
+        ReplayMatchKey::Method,
+        ReplayMatchKey::Host,
+        ReplayMatchKey::Path,
+        ReplayMatchKey::Query,
+    ];
+
+    pub fn cassette(&self) -> &str {
+        self.cassette
+            .as_deref()
+            .unwrap_or(RecordConfig::DEFAULT_CASSETTE)
+    }
+
+    pub fn match_on(&self) -> &[ReplayMatchKey] {
+        self.match_on.as_deref().unwrap_or(&Self::DEFAULT_MATCH_ON)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_defaults() {
+        let config: ReplayConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.cassette(), "cassette.jsonl");
+        assert_eq!(config.match_on(), ReplayConfig::DEFAULT_MATCH_ON);
+        assert_eq!(config.fallback, ReplayFallback::Passthrough);
+
+        let config: ReplayConfig = serde_json::from_str(
+            r#"{"cassette":"staging.jsonl","matchOn":["method","body"],"matchHeaders":["x-tenant"],"fallback":"notFound"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.match_on(),
+            [ReplayMatchKey::Method, ReplayMatchKey::Body]
+        );
+        assert_eq!(config.fallback, ReplayFallback::NotFound);
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Script handler configuration.
+///
+/// `script` is Rhai source defining `onRequest(req)` and/or
+/// `onResponse(req, res)`. Each hook returns the (modified) map, `()` to leave
+/// it unchanged, or `respond(status, body)` to short-circuit with a response.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptHandlerConfig {
+    pub script: String,
+    /// Operation budget per hook call (CPU limit)
+    pub max_operations: Option<u64>,
+    /// Wall-clock limit per hook call, in milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Largest string (including bodies) a script may hold, in bytes
+    pub max_string_bytes: Option<usize>,
+    /// Largest array or object map a script may build
+    pub max_collection_size: Option<usize>,
+}
+
+impl ScriptHandlerConfig {
+    pub const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+    pub const DEFAULT_TIMEOUT_MS: u64 = 1_000;
+    pub const DEFAULT_MAX_STRING_BYTES: usize = 16 * 1024 * 1024;
+    pub const DEFAULT_MAX_COLLECTION_SIZE: usize = 10_000;
+
+    pub fn max_operations(&self) -> u64 {
+        self.max_operations.unwrap_or(Self::DEFAULT_MAX_OPERATIONS)
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(Self::DEFAULT_TIMEOUT_MS)
+    }
+
+    pub fn max_string_bytes(&self) -> usize {
+        self.max_string_bytes
+            .unwrap_or(Self::DEFAULT_MAX_STRING_BYTES)
+    }
+
+    pub fn max_collection_size(&self) -> usize {
+        self.max_collection_size
+            .unwrap_or(Self::DEFAULT_MAX_COLLECTION_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_handler_defaults_and_serialization() {
+        let config: ScriptHandlerConfig =
+            serde_json::from_str(r#"{"script":"fn onRequest(req) { req }","timeoutMs":50}"#)
+                .unwrap();
+        assert_eq!(config.timeout_ms(), 50);
+        assert_eq!(
+            config.max_operations(),
+            ScriptHandlerConfig::DEFAULT_MAX_OPERATIONS
+        );
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["script"], "fn onRequest(req) { req }");
+        assert_eq!(json["timeoutMs"], 50);
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use super::MockResponseConfig;
+
+/// Sequence handler configuration.
+///
+/// Each matching call answers with the next entry of `responses`; entries are
+/// mock responses, so they can be inline, read from a file and templated.
+/// Counters are kept in memory per rule and `scope`, and restart when the rule
+/// is saved or deleted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceConfig {
+    pub responses: Vec<MockResponseConfig>,
+    #[serde(default)]
+    pub scope: SequenceScope,
+    #[serde(default)]
+    pub on_exhausted: SequenceExhausted,
+}
+
+/// Which calls share a counter.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SequenceScope {
+    /// Every call to the rule
+    #[default]
+    Rule,
+    /// Calls from the same client IP
+    Client,
+    /// Calls carrying the same value of a request header
+    Header { name: String },
+}
+
+/// What happens after the last response was served.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum SequenceExhausted {
+    /// Keep answering with the last response
+    #[default]
+    StickAtLast,
+    /// Start over from the first response
+    Loop,
+}
+
+impl SequenceExhausted {
+    /// Index of the response for the `call`-th call (0-based) to a sequence of `len`.
+    pub fn step(self, call: u64, len: usize) -> usize {
+        let len = len.max(1) as u64;
+        let step = match self {
+            Self::StickAtLast => call.min(len - 1),
+            Self::Loop => call % len,
+        };
+        step as usize
+    }
+}
+
+impl SequenceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.responses.is_empty() {
+            return Err("at least one response is required".to_string());
+        }
+        if let SequenceScope::Header { name } = &self.scope {
+            http::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid scope header {name:?}"))?;
+        }
+        for (index, response) in self.responses.iter().enumerate() {
+            response
+                .validate()
+                .map_err(|reason| format!("response #{}: {reason}", index + 1))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_steps_and_validation() {
+        let config: SequenceConfig = serde_json::from_str(
+            r#"{"responses":[{"statusCode":503},{"statusCode":200}],"scope":{"type":"header","name":"x-user"},"onExhausted":"loop"}"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            (0..5)
+                .map(|call| config.on_exhausted.step(call, 2))
+                .collect::<Vec<_>>(),
+            [0, 1, 0, 1, 0]
+        );
+        assert_eq!(SequenceExhausted::StickAtLast.step(7, 3), 2);
+
+        assert!(SequenceConfig::default().validate().is_err());
+        let bad_header = SequenceConfig {
+            responses: vec![MockResponseConfig::default()],
+            scope: SequenceScope::Header {
+                name: "bad header".to_string(),
+            },
+            ..Default::default()
+        };
+        assert!(bad_header.validate().is_err());
+    }
+}
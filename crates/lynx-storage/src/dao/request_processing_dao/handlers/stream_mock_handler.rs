@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Streaming mock handler configuration.
+///
+/// Answers matched requests locally with a body released chunk by chunk:
+/// each chunk waits `delayMs` before it is sent. With `format: "sse"` the
+/// response is `text/event-stream` and every chunk is framed as one event;
+/// `raw` sends chunk data as is over a chunked body. `repeat` plays the chunks
+/// several times, and `failAfter` cuts the body off like a dropped connection.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamMockConfig {
+    #[serde(default)]
+    pub format: StreamMockFormat,
+    /// Response status (default 200)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Extra response headers
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    pub chunks: Vec<StreamMockChunk>,
+    /// Play the chunks this many times; `0` repeats until the client leaves
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+    /// Fail the body after this many chunks in total
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_after: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamMockFormat {
+    #[default]
+    Sse,
+    Raw,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamMockChunk {
+    /// Wait this long before sending the chunk
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Event data (one `data:` line per line) or raw chunk text
+    pub data: String,
+    /// SSE `event:` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// SSE `id:` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// SSE `retry:` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_ms: Option<u64>,
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+impl StreamMockConfig {
+    /// Longest allowed wait before a single chunk.
+    pub const MAX_DELAY_MS: u64 = 300_000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(status) = self.status_code
+            && http::StatusCode::from_u16(status).is_err()
+        {
+            return Err(format!("invalid status code {status}"));
+        }
+        for name in self.headers.keys() {
+            http::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid header name {name:?}"))?;
+        }
+        if self.chunks.is_empty() {
+            return Err("at least one chunk is required".to_string());
+        }
+        if self.repeat == 0 && self.chunks.iter().all(|chunk| chunk.delay_ms == 0) {
+            return Err("repeating forever needs a chunk with delayMs".to_string());
+        }
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            if chunk.delay_ms > Self::MAX_DELAY_MS {
+                return Err(format!(
+                    "chunks[{index}]: delayMs must be at most {}",
+                    Self::MAX_DELAY_MS
+                ));
+            }
+            if self.format == StreamMockFormat::Raw {
+                if chunk.event.is_some() || chunk.id.is_some() || chunk.retry_ms.is_some() {
+                    return Err(format!(
+                        "chunks[{index}]: event, id and retryMs only apply to sse"
+                    ));
+                }
+                continue;
+            }
+            let single_line = |field: &str, value: &Option<String>| match value {
+                Some(value) if value.contains(['\r', '\n']) => {
+                    Err(format!("chunks[{index}]: {field} must be a single line"))
+                }
+                _ => Ok(()),
+            };
+            single_line("event", &chunk.event)?;
+            single_line("id", &chunk.id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_and_validates_streams() {
+        let config: StreamMockConfig = serde_json::from_value(json!({
+            "chunks": [
+                { "data": "{\"token\":\"Hel\"}", "event": "token", "delayMs": 50 },
+                { "data": "[DONE]" }
+            ],
+            "failAfter": 1
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.format, StreamMockFormat::Sse);
+        assert_eq!(config.repeat, 1);
+
+        let invalid = [
+            json!({ "chunks": [] }),
+            json!({ "chunks": [{ "data": "tick" }], "repeat": 0 }),
+            json!({ "format": "raw", "chunks": [{ "data": "a", "event": "x" }] }),
+            json!({ "chunks": [{ "data": "a", "id": "1\n2" }] }),
+            json!({ "chunks": [{ "data": "a", "delayMs": 300001 }] }),
+            json!({ "statusCode": 42, "chunks": [{ "data": "a" }] }),
+        ];
+        for config in invalid {
+            let config: StreamMockConfig = serde_json::from_value(config).unwrap();
+            assert!(config.validate().is_err(), "{config:?} accepted");
+        }
+    }
+}
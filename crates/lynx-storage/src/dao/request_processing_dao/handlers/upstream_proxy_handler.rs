@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// Upstream proxy handler configuration.
+///
+/// Sends matching requests through another proxy instead of the global
+/// `proxyRequests` choice, or straight to the target with `direct`. It also
+/// applies to CONNECT tunnels that are not decrypted, matched on their host.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Hash, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamProxyConfig {
+    pub protocol: UpstreamProxyProtocol,
+    /// `host:port` of the proxy; unused for `direct`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+/// How the upstream proxy is spoken to.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum UpstreamProxyProtocol {
+    /// Connect to the target without a proxy
+    #[default]
+    Direct,
+    /// Plain HTTP proxy: `CONNECT` for https targets, absolute-form requests otherwise
+    Http,
+    /// HTTP proxy reached over TLS
+    Https,
+    /// SOCKS5 proxy, with username/password authentication when set
+    Socks5,
+}
+
+impl UpstreamProxyConfig {
+    /// `user:password` credentials, when a username is set.
+    pub fn credentials(&self) -> Option<(&str, &str)> {
+        self.username
+            .as_deref()
+            .filter(|username| !username.is_empty())
+            .map(|username| (username, self.password.as_deref().unwrap_or_default()))
+    }
+
+    /// Proxy address and protocol without credentials, for logs and rule lint.
+    pub fn label(&self) -> String {
+        match self.protocol {
+            UpstreamProxyProtocol::Direct => "direct".to_string(),
+            UpstreamProxyProtocol::Http => format!("http://{}", self.address),
+            UpstreamProxyProtocol::Https => format!("https://{}", self.address),
+            UpstreamProxyProtocol::Socks5 => format!("socks5://{}", self.address),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.protocol == UpstreamProxyProtocol::Direct {
+            return Ok(());
+        }
+        let authority: http::uri::Authority = self
+            .address
+            .parse()
+            .map_err(|_| format!("address {:?} must be host:port", self.address))?;
+        if authority.port_u16().is_none() {
+            return Err(format!("address {:?} must include a port", self.address));
+        }
+        if let Some((username, password)) = self.credentials()
+            && self.protocol == UpstreamProxyProtocol::Socks5
+            && (username.len() > 255 || password.len() > 255)
+        {
+            return Err("SOCKS5 username and password are limited to 255 bytes".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_proxy_address() {
+        let config: UpstreamProxyConfig = serde_json::from_value(serde_json::json!({
+            "protocol": "socks5",
+            "address": "jump.corp:1080",
+            "username": "alice",
+            "password": "secret"
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.credentials(), Some(("alice", "secret")));
+        assert_eq!(config.label(), "socks5://jump.corp:1080");
+
+        let missing_port = UpstreamProxyConfig {
+            address: "jump.corp".to_string(),
+            ..config
+        };
+        assert!(missing_port.validate().is_err());
+
+        let direct: UpstreamProxyConfig =
+            serde_json::from_value(serde_json::json!({ "protocol": "direct" })).unwrap();
+        assert!(direct.validate().is_ok());
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// WebAssembly plugin handler configuration.
+///
+/// `plugin` names a `.wasm` component implementing the `lynx:plugin/handler`
+/// world. Relative paths are looked up in the rule's project directory, then in
+/// `<data dir>/plugins`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WasmPluginConfig {
+    pub plugin: String,
+    /// Plugin-specific settings, passed to every call as JSON text
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub config: Value,
+    /// Fuel budget per plugin call (roughly one unit per instruction)
+    pub fuel: Option<u64>,
+    /// Largest linear memory a plugin may grow to, in bytes
+    pub max_memory_bytes: Option<usize>,
+}
+
+impl WasmPluginConfig {
+    pub const DEFAULT_FUEL: u64 = 500_000_000;
+    pub const DEFAULT_MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+    pub fn fuel(&self) -> u64 {
+        self.fuel.unwrap_or(Self::DEFAULT_FUEL)
+    }
+
+    pub fn max_memory_bytes(&self) -> usize {
+        self.max_memory_bytes
+            .unwrap_or(Self::DEFAULT_MAX_MEMORY_BYTES)
+    }
+
+    /// The JSON text handed to the plugin; `null` when no config is set.
+    pub fn config_json(&self) -> String {
+        self.config.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_plugin_defaults_and_serialization() {
+        let config: WasmPluginConfig =
+            serde_json::from_str(r#"{"plugin":"sign.wasm","config":{"key":"k1"}}"#).unwrap();
+        assert_eq!(config.fuel(), WasmPluginConfig::DEFAULT_FUEL);
+        assert_eq!(config.config_json(), r#"{"key":"k1"}"#);
+
+        let bare: WasmPluginConfig = serde_json::from_str(r#"{"plugin":"sign.wasm"}"#).unwrap();
+        assert_eq!(bare.config_json(), "null");
+        let json = serde_json::to_value(&bare).unwrap();
+        assert!(json.get("config").is_none());
+    }
+}
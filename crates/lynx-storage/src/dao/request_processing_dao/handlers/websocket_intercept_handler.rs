@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::BodyTransform;
+
+/// WebSocket intercept handler configuration.
+///
+/// Applies to WebSocket upgrades the rule matches. Every text frame of the
+/// connection runs through `rules` in order: a matching `rewrite` changes the
+/// text later rules see, `delay` adds to how long the frame is held, and `drop`
+/// swallows it. Binary and control frames are relayed untouched.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketInterceptConfig {
+    pub rules: Vec<WebSocketFrameRule>,
+}
+
+/// Acts on the text frames that satisfy every condition given.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketFrameRule {
+    #[serde(default)]
+    pub direction: WebSocketFrameDirection,
+    /// Regex searched for in the frame text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// JSONPath that must select at least one node; the frame must be JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    /// With `jsonPath`, one of the selected nodes must equal this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_value: Option<Value>,
+    pub action: WebSocketFrameAction,
+}
+
+/// Which frames a rule sees.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum WebSocketFrameDirection {
+    #[default]
+    Both,
+    ClientToServer,
+    ServerToClient,
+}
+
+impl WebSocketFrameDirection {
+    pub fn includes_client_to_server(self) -> bool {
+        matches!(self, Self::Both | Self::ClientToServer)
+    }
+
+    pub fn includes_server_to_client(self) -> bool {
+        matches!(self, Self::Both | Self::ServerToClient)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum WebSocketFrameAction {
+    /// Replaces the frame text with `replacement`, if given, then runs
+    /// `transforms` over it
+    Rewrite {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        replacement: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        transforms: Vec<BodyTransform>,
+    },
+    /// Swallows the frame
+    Drop,
+    /// Holds the frame; later frames in the same direction wait behind it
+    #[serde(rename_all = "camelCase")]
+    Delay { delay_ms: u64 },
+}
+
+impl WebSocketInterceptConfig {
+    /// Longest a single rule may hold a frame.
+    pub const MAX_DELAY_MS: u64 = 300_000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rules.is_empty() {
+            return Err("at least one frame rule is required".to_string());
+        }
+        for (index, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .map_err(|reason| format!("rules[{index}]: {reason}"))?;
+        }
+        Ok(())
+    }
+}
+
+impl WebSocketFrameRule {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(pattern) = &self.pattern {
+            regex::Regex::new(pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+        }
+        if let Some(path) = &self.json_path {
+            serde_json_path::JsonPath::parse(path)
+                .map_err(|e| format!("invalid JSONPath {path:?}: {e}"))?;
+        }
+        if self.json_value.is_some() && self.json_path.is_none() {
+            return Err("jsonValue requires jsonPath".to_string());
+        }
+        match &self.action {
+            WebSocketFrameAction::Rewrite {
+                replacement,
+                transforms,
+            } => {
+                if replacement.is_none() && transforms.is_empty() {
+                    return Err("rewrite needs a replacement or transforms".to_string());
+                }
+                transforms.iter().try_for_each(BodyTransform::validate)
+            }
+            WebSocketFrameAction::Drop => Ok(()),
+            WebSocketFrameAction::Delay { delay_ms } => {
+                if *delay_ms > WebSocketInterceptConfig::MAX_DELAY_MS {
+                    return Err(format!(
+                        "delayMs must be at most {}",
+                        WebSocketInterceptConfig::MAX_DELAY_MS
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_and_validates_frame_rules() {
+        let config: WebSocketInterceptConfig = serde_json::from_value(json!({
+            "rules": [
+                { "pattern": "heartbeat", "action": { "type": "drop" } },
+                {
+                    "direction": "serverToClient",
+                    "jsonPath": "$.type",
+                    "jsonValue": "price",
+                    "action": {
+                        "type": "rewrite",
+                        "transforms": [{ "type": "jsonPathSet", "path": "$.price", "value": 0 }]
+                    }
+                },
+                { "action": { "type": "delay", "delayMs": 500 } }
+            ]
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.rules[0].direction, WebSocketFrameDirection::Both);
+        assert!(!config.rules[1].direction.includes_client_to_server());
+
+        let invalid = [
+            json!({ "rules": [] }),
+            json!({ "rules": [{ "pattern": "(", "action": { "type": "drop" } }] }),
+            json!({ "rules": [{ "jsonValue": 1, "action": { "type": "drop" } }] }),
+            json!({ "rules": [{ "action": { "type": "rewrite" } }] }),
+            json!({ "rules": [{ "action": { "type": "delay", "delayMs": 300_001 } }] }),
+        ];
+        for config in invalid {
+            let config: WebSocketInterceptConfig = serde_json::from_value(config).unwrap();
+            assert!(config.validate().is_err(), "{config:?} accepted");
+        }
+    }
+}
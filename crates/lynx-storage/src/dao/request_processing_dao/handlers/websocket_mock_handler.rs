@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+/// WebSocket mock handler configuration.
+///
+/// Matched WebSocket upgrades are accepted by Lynx itself, with no upstream,
+/// and run a scripted conversation: `onConnect` frames are sent once the
+/// connection opens, each client text message gets the frames of the first
+/// `replies` entry whose pattern it matches, `periodic` frames repeat on their
+/// interval, and `close` ends the session. The first subprotocol the client
+/// offers is accepted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMockConfig {
+    /// Sent as soon as the connection opens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_connect: Vec<WebSocketMockFrame>,
+    /// Answers to client text messages; the first matching entry replies
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<WebSocketMockReply>,
+    /// Echo client messages that no reply matched
+    #[serde(default)]
+    pub echo: bool,
+    /// Frames repeated on a fixed interval
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub periodic: Vec<WebSocketMockPeriodic>,
+    /// Closes the connection from the server side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close: Option<WebSocketMockClose>,
+}
+
+/// One frame the mock sends; set exactly one of `text` and `binaryBase64`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMockFrame {
+    /// Text frame; in replies, `$1` or `${name}` refer to the pattern's groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_base64: Option<String>,
+    /// Wait this long before sending the frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMockReply {
+    /// Regex searched for in the client's text message
+    pub pattern: String,
+    pub frames: Vec<WebSocketMockFrame>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMockPeriodic {
+    pub interval_ms: u64,
+    pub frame: WebSocketMockFrame,
+    /// Stop after this many frames (default: until the connection closes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMockClose {
+    /// How long after the connection opens to close it
+    pub after_ms: u64,
+    /// Close code (default 1000)
+    #[serde(default = "default_close_code")]
+    pub code: u16,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+}
+
+fn default_close_code() -> u16 {
+    1000
+}
+
+impl WebSocketMockConfig {
+    /// Shortest allowed periodic interval.
+    pub const MIN_INTERVAL_MS: u64 = 10;
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, frame) in self.on_connect.iter().enumerate() {
+            frame
+                .validate()
+                .map_err(|reason| format!("onConnect[{index}]: {reason}"))?;
+        }
+        for (index, reply) in self.replies.iter().enumerate() {
+            regex::Regex::new(&reply.pattern)
+                .map_err(|e| format!("replies[{index}]: invalid regex {:?}: {e}", reply.pattern))?;
+            for frame in &reply.frames {
+                frame
+                    .validate()
+                    .map_err(|reason| format!("replies[{index}]: {reason}"))?;
+            }
+        }
+        for (index, periodic) in self.periodic.iter().enumerate() {
+            if periodic.interval_ms < Self::MIN_INTERVAL_MS {
+                return Err(format!(
+                    "periodic[{index}]: intervalMs must be at least {}",
+                    Self::MIN_INTERVAL_MS
+                ));
+            }
+            periodic
+                .frame
+                .validate()
+                .map_err(|reason| format!("periodic[{index}]: {reason}"))?;
+        }
+        if let Some(close) = &self.close {
+            close.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl WebSocketMockFrame {
+    fn validate(&self) -> Result<(), String> {
+        match (&self.text, &self.binary_base64) {
+            (Some(_), None) => Ok(()),
+            (None, Some(encoded)) => {
+                use base64::Engine as _;
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map(|_| ())
+                    .map_err(|e| format!("invalid binaryBase64: {e}"))
+            }
+            _ => Err("set exactly one of text and binaryBase64".to_string()),
+        }
+    }
+}
+
+impl WebSocketMockClose {
+    fn validate(&self) -> Result<(), String> {
+        // 1005, 1006 and 1015 are reserved for reporting and may not be sent.
+        let sendable = matches!(self.code, 1000..=1003 | 1007..=1014 | 3000..=4999);
+        if !sendable {
+            return Err(format!("close code {} cannot be sent", self.code));
+        }
+        if self.reason.len() > 123 {
+            return Err("close reason must be at most 123 bytes".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_and_validates_scripts() {
+        let config: WebSocketMockConfig = serde_json::from_value(json!({
+            "onConnect": [{ "text": "{\"type\":\"hello\"}" }],
+            "replies": [{ "pattern": "^ping (\\d+)$", "frames": [{ "text": "pong $1", "delayMs": 10 }] }],
+            "periodic": [{ "intervalMs": 1000, "frame": { "binaryBase64": "AAE=" }, "count": 3 }],
+            "close": { "afterMs": 5000, "reason": "bye" }
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.close.as_ref().unwrap().code, 1000);
+        assert!(!config.echo);
+
+        let invalid = [
+            json!({ "onConnect": [{}] }),
+            json!({ "onConnect": [{ "text": "a", "binaryBase64": "AA==" }] }),
+            json!({ "replies": [{ "pattern": "(", "frames": [] }] }),
+            json!({ "periodic": [{ "intervalMs": 1, "frame": { "text": "tick" } }] }),
+            json!({ "close": { "afterMs": 0, "code": 1006 } }),
+        ];
+        for config in invalid {
+            let config: WebSocketMockConfig = serde_json::from_value(config).unwrap();
+            assert!(config.validate().is_err(), "{config:?} accepted");
+        }
+    }
+}
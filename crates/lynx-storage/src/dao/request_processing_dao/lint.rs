@@ -0,0 +1,244 @@
+//! Rule-set lint (`rules.lint`, `lynx rules lint`): shadowed rules and conflicting handlers.
+
+use lynx_dsl::{LintRule, NamedMatchers, RuleEffect, RuleLintCode, validate_rules_with_matchers};
+use serde::Serialize;
+
+use super::handlers::handler_rule::HandlerRuleType;
+use super::handlers::{HeaderOp, ModifyHeaders};
+use super::types::RequestRule;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleLintIssue {
+    pub rule_id: Option<i32>,
+    pub rule_name: String,
+    pub related_rule_id: Option<i32>,
+    pub related_rule_name: Option<String>,
+    pub code: RuleLintCode,
+    /// `error` or `warning`.
+    pub severity: String,
+    pub message: String,
+    /// Byte range within the rule's `matchExpr`.
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesLintReport {
+    pub project: String,
+    pub issues: Vec<RuleLintIssue>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl RulesLintReport {
+    pub fn new(project: impl Into<String>, issues: Vec<RuleLintIssue>) -> Self {
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == "error")
+            .count();
+        Self {
+            project: project.into(),
+            warnings: issues.len() - errors,
+            errors,
+            issues,
+        }
+    }
+}
+
+/// Lint `rules` of one project, given in evaluation order (priority desc, then id).
+pub fn lint_rules(rules: &[RequestRule], matchers: &NamedMatchers) -> Vec<RuleLintIssue> {
+    let lint_input: Vec<LintRule> = rules.iter().map(lint_rule).collect();
+    validate_rules_with_matchers(&lint_input, matchers)
+        .into_iter()
+        .map(|diagnostic| {
+            let rule = &rules[diagnostic.rule];
+            let related = diagnostic.related.map(|index| &rules[index]);
+            RuleLintIssue {
+                rule_id: rule.id,
+                rule_name: rule.name.clone(),
+                related_rule_id: related.and_then(|related| related.id),
+                related_rule_name: related.map(|related| related.name.clone()),
+                code: diagnostic.code,
+                severity: diagnostic.diagnostic.severity,
+                message: diagnostic.diagnostic.message,
+                from: diagnostic.diagnostic.from,
+                to: diagnostic.diagnostic.to,
+            }
+        })
+        .collect()
+}
+
+fn lint_rule(rule: &RequestRule) -> LintRule {
+    let handlers: Vec<_> = rule
+        .handlers
+        .iter()
+        .filter(|handler| handler.enabled)
+        .collect();
+    let terminal = handlers
+        .iter()
+        .filter(|handler| handler.handler_type.is_terminal())
+        .min_by_key(|handler| handler.execution_order)
+        .map(|handler| {
+            (
+                handler.execution_order,
+                handler.handler_type.kind_label().to_string(),
+            )
+        });
+    LintRule {
+        name: rule.name.clone(),
+        match_expr: rule.capture.match_expr.clone(),
+        enabled: rule.enabled,
+        terminal,
+        first_order: handlers.iter().map(|handler| handler.execution_order).min(),
+        effects: handlers
+            .iter()
+            .flat_map(|handler| handler_effects(&handler.handler_type))
+            .collect(),
+    }
+}
+
+/// Final values that header ops write, by lower-case name; removals count as writes.
+fn header_writes(headers: Option<&ModifyHeaders>) -> Vec<(String, String)> {
+    let mut writes: Vec<(String, String)> = Vec::new();
+    for op in headers.map(ModifyHeaders::ops).unwrap_or_default() {
+        let (name, value) = match op {
+            HeaderOp::Set { name, value } => (name, value),
+            HeaderOp::Remove { name } => (name, "(removed)".to_string()),
+            HeaderOp::Append { .. } | HeaderOp::Rename { .. } | HeaderOp::RegexRewrite { .. } => {
+                continue;
+            }
+        };
+        let name = name.to_ascii_lowercase();
+        writes.retain(|(written, _)| *written != name);
+        writes.push((name, value));
+    }
+    writes
+}
+
+/// What a handler writes, keyed so that two handlers writing one slot can be compared.
+fn handler_effects(handler: &HandlerRuleType) -> Vec<RuleEffect> {
+    let mut effects = Vec::new();
+    let mut push = |slot: String, value: String| effects.push(RuleEffect { slot, value });
+    match handler {
+        HandlerRuleType::Block(_)
+        | HandlerRuleType::LocalFile(_)
+        | HandlerRuleType::MockResponse(_)
+        | HandlerRuleType::Replay(_)
+        | HandlerRuleType::Sequence(_)
+        | HandlerRuleType::WebSocketMock(_)
+        | HandlerRuleType::StreamMock(_) => push(
+            "response".to_string(),
+            format!("{} {}", handler.kind_label(), config_json(handler)),
+        ),
+        HandlerRuleType::ProxyForward(_) => push("upstream".to_string(), config_json(handler)),
+        HandlerRuleType::UpstreamProxy(config) => {
+            push("upstream.proxy".to_string(), config.label())
+        }
+        HandlerRuleType::Mirror(config) => push("mirror".to_string(), config.targets.join(", ")),
+        HandlerRuleType::Cors(config) => push(
+            "response.header.access-control-allow-origin".to_string(),
+            config.origins_label(),
+        ),
+        HandlerRuleType::ModifyRequest(config) => {
+            if let Some(url) = &config.modify_url {
+                push("request.url".to_string(), url.clone());
+            }
+            if let Some(method) = &config.modify_method {
+                push("request.method".to_string(), method.to_ascii_uppercase());
+            }
+            if let Some(body) = &config.modify_body {
+                push("request.body".to_string(), body.clone());
+            }
+            for (name, value) in header_writes(config.modify_headers.as_ref()) {
+                push(format!("request.header.{name}"), value);
+            }
+        }
+        HandlerRuleType::ModifyResponse(config) => {
+            if let Some(status) = config.modify_status_code {
+                push("response.status".to_string(), status.to_string());
+            }
+            if let Some(body) = &config.modify_body {
+                push("response.body".to_string(), body.clone());
+            }
+            for (name, value) in header_writes(config.modify_headers.as_ref()) {
+                push(format!("response.header.{name}"), value);
+            }
+        }
+        HandlerRuleType::HtmlScriptInjector(_)
+        | HandlerRuleType::Delay(_)
+        | HandlerRuleType::Throttle(_)
+        | HandlerRuleType::Script(_)
+        | HandlerRuleType::WasmPlugin(_)
+        | HandlerRuleType::Record(_)
+        | HandlerRuleType::Fault(_)
+        | HandlerRuleType::Breakpoint(_)
+        | HandlerRuleType::WebSocketIntercept(_) => {}
+    }
+    effects
+}
+
+fn config_json(handler: &HandlerRuleType) -> String {
+    serde_json::to_string(handler).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::request_processing_dao::handlers::HandlerRule;
+    use crate::dao::request_processing_dao::types::CaptureRule;
+
+    fn rule(id: i32, match_expr: &str, handlers: Vec<HandlerRule>) -> RequestRule {
+        RequestRule {
+            id: Some(id),
+            project: "default".to_string(),
+            name: format!("rule-{id}"),
+            description: None,
+            enabled: true,
+            priority: 100 - id,
+            capture: CaptureRule {
+                id: None,
+                match_expr: match_expr.to_string(),
+            },
+            handlers,
+        }
+    }
+
+    #[test]
+    fn block_shadows_narrower_rule_and_headers_conflict() {
+        let header = |value: &str| {
+            HandlerRule::modify_request_handler(
+                Some([("X-Env".to_string(), value.to_string())].into()),
+                None,
+                None,
+                None,
+            )
+        };
+        let rules = vec![
+            rule(
+                1,
+                "example.com /admin",
+                vec![HandlerRule::block_handler(None, None)],
+            ),
+            rule(
+                2,
+                "example.com /admin/users",
+                vec![HandlerRule::block_handler(Some(404), None)],
+            ),
+            rule(3, "example.com", vec![header("stg")]),
+            rule(4, "api.example.com", vec![header("prod")]),
+        ];
+
+        let report = RulesLintReport::new("default", lint_rules(&rules, &NamedMatchers::default()));
+        assert_eq!((report.errors, report.warnings), (1, 1));
+        let shadowed = &report.issues[0];
+        assert_eq!(shadowed.rule_id, Some(2));
+        assert_eq!(shadowed.related_rule_id, Some(1));
+        assert_eq!(shadowed.code, RuleLintCode::Shadowed);
+        let conflict = &report.issues[1];
+        assert_eq!(conflict.rule_id, Some(4));
+        assert_eq!(conflict.related_rule_id, Some(3));
+        assert!(conflict.message.contains("request.header.x-env"));
+    }
+}
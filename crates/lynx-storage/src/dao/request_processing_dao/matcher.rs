@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use axum::{body::HttpBody, extract::Request};
-use lynx_dsl::{MatchProgram, RequestFacts, compile_match_expr, eval_program};
+use lynx_dsl::{
+    MatchProgram, NamedMatchers, RequestFacts, RuleIndex, compile_match_expr_with_matchers,
+    eval_program, eval_program_ignoring_samples, path_captures,
+};
 
 use super::types::RequestRule;
 
@@ -10,44 +15,128 @@ pub struct CompiledRule {
     pub program: MatchProgram,
 }
 
+/// Compiled rules in priority order plus the [`RuleIndex`] used to pre-filter them.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledRules {
+    pub rules: Vec<CompiledRule>,
+    pub index: RuleIndex,
+}
+
+/// Per-request inputs for `--sample` and `--time`, carried as a request
+/// extension so every matching pass over the same request agrees.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleMatchContext {
+    pub client: Option<String>,
+    pub sample_seed: u64,
+    pub minute_of_day: Option<u16>,
+}
+
+impl RuleMatchContext {
+    /// Context for the current local time.
+    pub fn now(client: Option<String>, sample_seed: u64) -> Self {
+        Self {
+            client,
+            sample_seed,
+            minute_of_day: Some(local_minute_of_day()),
+        }
+    }
+}
+
+/// A rule whose match expression selected the request.
+///
+/// `sampled` is `None` for rules without `--sample`; otherwise it records
+/// whether the sample fired (`Some(false)`: matched but sampled out).
+/// `path_params` holds what the path glob's wildcards captured.
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    pub rule: RequestRule,
+    pub sampled: Option<bool>,
+    pub path_params: Vec<String>,
+}
+
 /// IR-based matcher (matchExpr → MatchProgram), evaluated on request facts.
 pub struct RuleMatcher;
 
 impl RuleMatcher {
-    pub fn compile_rules(rules: &[RequestRule]) -> Result<Vec<CompiledRule>> {
+    /// Compile `rules`, resolving `@name` references against the named
+    /// matchers of each rule's project (`project id → matchers`).
+    pub fn compile_rules(
+        rules: &[RequestRule],
+        matchers: &HashMap<String, NamedMatchers>,
+    ) -> Result<CompiledRules> {
+        let no_matchers = NamedMatchers::default();
         let mut compiled = Vec::with_capacity(rules.len());
         for rule in rules {
             let expr = rule.capture.match_expr.trim();
-            let program = compile_match_expr(expr).map_err(|error| {
-                anyhow::anyhow!("Rule {} matchExpr invalid: {error}", rule.id.unwrap_or(-1))
-            })?;
+            let project_matchers = matchers.get(&rule.project).unwrap_or(&no_matchers);
+            let program =
+                compile_match_expr_with_matchers(expr, project_matchers).map_err(|error| {
+                    anyhow::anyhow!("Rule {} matchExpr invalid: {error}", rule.id.unwrap_or(-1))
+                })?;
             compiled.push(CompiledRule {
                 rule: rule.clone(),
                 program,
             });
         }
-        Ok(compiled)
+        let index = RuleIndex::build(compiled.iter().map(|rule| &rule.program));
+        Ok(CompiledRules {
+            rules: compiled,
+            index,
+        })
     }
 
+    /// Evaluate only the rules the index keeps; same result as scanning every rule.
     pub fn find_matching_rules<T: HttpBody>(
-        compiled_rules: &[CompiledRule],
+        compiled_rules: &CompiledRules,
         request: &Request<T>,
     ) -> Result<Vec<RequestRule>> {
+        Ok(Self::find_rule_matches(compiled_rules, request)?
+            .into_iter()
+            .filter(|matched| matched.sampled != Some(false))
+            .map(|matched| matched.rule)
+            .collect())
+    }
+
+    /// Like [`Self::find_matching_rules`], also returning sampled rules that
+    /// would have matched under a different sampling outcome.
+    pub fn find_rule_matches<T: HttpBody>(
+        compiled_rules: &CompiledRules,
+        request: &Request<T>,
+    ) -> Result<Vec<RuleMatch>> {
         let facts = request_facts_from_request(request);
         let mut matching = Vec::new();
-        for compiled in compiled_rules {
+        for position in compiled_rules.index.candidates(&facts) {
+            let compiled = &compiled_rules.rules[position];
             if !compiled.rule.enabled {
                 continue;
             }
-            if eval_program(&compiled.program, &facts) {
-                matching.push(compiled.rule.clone());
-            }
+            let sampled = if compiled.program.has_sampling() {
+                if !eval_program_ignoring_samples(&compiled.program, &facts) {
+                    continue;
+                }
+                Some(eval_program(&compiled.program, &facts))
+            } else if eval_program(&compiled.program, &facts) {
+                None
+            } else {
+                continue;
+            };
+            matching.push(RuleMatch {
+                rule: compiled.rule.clone(),
+                sampled,
+                path_params: path_captures(&compiled.program, &facts.path),
+            });
         }
         Ok(matching)
     }
 }
 
-fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts {
+fn local_minute_of_day() -> u16 {
+    use chrono::Timelike;
+    let now = chrono::Local::now();
+    (now.hour() * 60 + now.minute()) as u16
+}
+
+pub(crate) fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts {
     let uri = request.uri();
     let scheme = uri.scheme_str().map(|s| s.to_string());
     let query = uri.query().map(|q| q.to_string());
@@ -64,6 +153,12 @@ fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts
     }
     headers.sort_by(|(l, _), (r, _)| l.cmp(r));
 
+    let context = request
+        .extensions()
+        .get::<RuleMatchContext>()
+        .cloned()
+        .unwrap_or_else(|| RuleMatchContext::now(None, fallback_sample_seed(request)));
+
     RequestFacts {
         scheme,
         host,
@@ -72,9 +167,21 @@ fn request_facts_from_request<T: HttpBody>(request: &Request<T>) -> RequestFacts
         query,
         method,
         headers,
+        client: context.client,
+        sample_seed: context.sample_seed,
+        minute_of_day: context.minute_of_day,
     }
 }
 
+/// Without a [`RuleMatchContext`], seed from the method and URL so repeated
+/// passes over the same request agree.
+fn fallback_sample_seed<T: HttpBody>(request: &Request<T>) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    std::hash::Hash::hash(request.method().as_str(), &mut hasher);
+    std::hash::Hash::hash(&request.uri().to_string(), &mut hasher);
+    std::hash::Hasher::finish(&hasher)
+}
+
 fn host_and_port(
     uri_host: Option<&str>,
     uri_port: Option<u16>,
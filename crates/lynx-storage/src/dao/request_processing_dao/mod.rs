@@ -1,20 +1,26 @@
 pub mod common;
 pub mod error;
+pub mod explain;
 pub mod handlers;
+pub mod lint;
 pub mod matcher;
 pub mod types;
 pub mod validator;
 
 pub use common::{BodyUtils, HeaderUtils};
 pub use error::RequestProcessingError;
+pub use explain::{FailedPredicate, PlannedHandler, RuleExplanation, RulesExplainReport};
 pub use handlers::{HandlerRule, HtmlScriptInjectorConfig};
-pub use matcher::RuleMatcher;
+pub use lint::{RuleLintIssue, RulesLintReport};
+pub use matcher::{CompiledRules, RuleMatch, RuleMatchContext, RuleMatcher};
 pub use types::{CaptureRule, LocalFileConfig, ModifyRequestConfig, RequestRule};
 pub use validator::RuleValidator;
 
-use crate::storage::{DataStore, read_json, write_json_atomic};
+use crate::dao::projects_dao::ProjectsDao;
+use crate::storage::{DataStore, SequenceCounter, read_json, write_json_atomic};
 use anyhow::{Result, anyhow};
 use axum::{body::HttpBody, extract::Request};
+use lynx_dsl::NamedMatchers;
 use std::sync::Arc;
 use tokio::fs;
 
@@ -31,6 +37,7 @@ impl RequestProcessingDao {
         let rule_id = self.store.next_rule_id().await?;
         rule.id = Some(rule_id);
         rule.capture.id = rule.capture.id.or(Some(rule_id));
+        rule.assign_handler_ids();
 
         write_json_atomic(&self.store.rule_path(rule_id), &rule).await?;
         self.store.invalidate_rules_cache().await;
@@ -43,13 +50,19 @@ impl RequestProcessingDao {
         }
         rule.id = Some(rule_id);
         rule.capture.id = rule.capture.id.or(Some(rule_id));
+        rule.assign_handler_ids();
         write_json_atomic(&self.store.rule_path(rule_id), &rule).await?;
         self.store.invalidate_rules_cache().await;
+        self.store.sequence_counters().reset(Some(rule_id));
         Ok(())
     }
 
     pub async fn get_rule(&self, rule_id: i32) -> Result<Option<RequestRule>> {
-        read_json(&self.store.rule_path(rule_id)).await
+        let mut rule: Option<RequestRule> = read_json(&self.store.rule_path(rule_id)).await?;
+        if let Some(rule) = &mut rule {
+            rule.assign_handler_ids();
+        }
+        Ok(rule)
     }
 
     pub async fn list_rules(&self) -> Result<Vec<RequestRule>> {
@@ -65,7 +78,7 @@ impl RequestProcessingDao {
             .collect())
     }
 
-    pub async fn update_rule(&self, rule: RequestRule) -> Result<()> {
+    pub async fn update_rule(&self, mut rule: RequestRule) -> Result<()> {
         let rule_id = rule
             .id
             .ok_or_else(|| anyhow!("Rule ID is required for update"))?;
@@ -74,8 +87,10 @@ impl RequestProcessingDao {
             return Err(anyhow!("Rule {} not found", rule_id));
         }
 
+        rule.assign_handler_ids();
         write_json_atomic(&self.store.rule_path(rule_id), &rule).await?;
         self.store.invalidate_rules_cache().await;
+        self.store.sequence_counters().reset(Some(rule_id));
         Ok(())
     }
 
@@ -85,6 +100,7 @@ impl RequestProcessingDao {
             fs::remove_file(&path).await?;
         }
         self.store.invalidate_rules_cache().await;
+        self.store.sequence_counters().reset(Some(rule_id));
         Ok(())
     }
 
@@ -113,12 +129,66 @@ impl RequestProcessingDao {
         RuleMatcher::find_matching_rules(&entry.compiled, request)
     }
 
+    /// Matching rules plus sampled-out ones; see [`RuleMatcher::find_rule_matches`].
+    pub async fn find_rule_matches<T: HttpBody>(
+        &self,
+        request: &Request<T>,
+    ) -> Result<Vec<RuleMatch>> {
+        let entry = self.store.get_rules_cache_entry().await?;
+        RuleMatcher::find_rule_matches(&entry.compiled, request)
+    }
+
+    /// Explain every rule of `project` against `request`, and the handler plan that would run.
+    pub async fn explain_request<T: HttpBody>(
+        &self,
+        request: &Request<T>,
+        project: &str,
+    ) -> Result<RulesExplainReport> {
+        let rules = self.list_rules_by_project(project).await?;
+        let matchers = self.project_matchers(project).await?;
+        let facts = matcher::request_facts_from_request(request);
+        let explanations = explain::explain_rules(&rules, &facts, &matchers);
+        let handlers = explain::plan_handlers(&rules, &explanations);
+        Ok(RulesExplainReport {
+            project: project.to_string(),
+            facts,
+            rules: explanations,
+            handlers,
+        })
+    }
+
+    /// Shadowed rules and conflicting handlers among the rules of `project`.
+    pub async fn lint_project(&self, project: &str) -> Result<RulesLintReport> {
+        let rules = self.list_rules_by_project(project).await?;
+        let matchers = self.project_matchers(project).await?;
+        Ok(RulesLintReport::new(
+            project,
+            lint::lint_rules(&rules, &matchers),
+        ))
+    }
+
+    async fn project_matchers(&self, project: &str) -> Result<NamedMatchers> {
+        ProjectsDao::new(self.store.clone())
+            .project_matchers(project)
+            .await
+    }
+
     pub async fn get_template_handlers(&self) -> Result<Vec<HandlerRule>> {
         read_json(&self.store.templates_path())
             .await
             .map(|opt| opt.unwrap_or_default())
     }
 
+    /// Call counters of `Sequence` handlers, for `rule_id` or every rule.
+    pub fn list_sequence_counters(&self, rule_id: Option<i32>) -> Vec<SequenceCounter> {
+        self.store.sequence_counters().list(rule_id)
+    }
+
+    /// Restarts the `Sequence` handlers of `rule_id`, or of every rule.
+    pub fn reset_sequence_counters(&self, rule_id: Option<i32>) -> usize {
+        self.store.sequence_counters().reset(rule_id)
+    }
+
     pub async fn toggle_rule(&self, rule_id: i32, enabled: bool) -> Result<()> {
         let mut rule = self
             .get_rule(rule_id)
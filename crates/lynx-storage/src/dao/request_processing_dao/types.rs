@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 
 use super::handlers::HandlerRule;
@@ -65,6 +67,26 @@ impl Default for RequestRule {
     }
 }
 
+impl RequestRule {
+    /// Gives every handler without an id, or with an id already taken in
+    /// this rule, the next free one. Ids stay with their handler when the
+    /// handlers are reordered, so `Sequence` counters are keyed on them.
+    pub fn assign_handler_ids(&mut self) {
+        let mut next = self.handlers.iter().filter_map(|h| h.id).max().unwrap_or(0);
+        let mut taken = HashSet::new();
+        for handler in &mut self.handlers {
+            match handler.id {
+                Some(id) if taken.insert(id) => {}
+                _ => {
+                    next += 1;
+                    handler.id = Some(next);
+                    taken.insert(next);
+                }
+            }
+        }
+    }
+}
+
 impl Default for CaptureRule {
     fn default() -> Self {
         Self {
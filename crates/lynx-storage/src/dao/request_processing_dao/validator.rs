@@ -1,9 +1,9 @@
 use super::{
     error::{RequestProcessingError, Result},
-    handlers::HandlerRule,
+    handlers::{HandlerRule, ModifyHeaders, handler_rule::HandlerRuleType},
     types::{CaptureRule, RequestRule},
 };
-use lynx_dsl::compile_match_expr;
+use lynx_dsl::{NamedMatchers, compile_match_expr_with_matchers};
 use std::collections::HashSet;
 
 /// Validator for request processing rules
@@ -12,12 +12,17 @@ pub struct RuleValidator;
 impl RuleValidator {
     /// Validate a complete request rule
     pub fn validate_rule(rule: &RequestRule) -> Result<()> {
+        Self::validate_rule_with_matchers(rule, &NamedMatchers::default())
+    }
+
+    /// Validate a rule whose `matchExpr` may reference its project's named matchers
+    pub fn validate_rule_with_matchers(rule: &RequestRule, matchers: &NamedMatchers) -> Result<()> {
         // Validate basic rule properties
         Self::validate_rule_name(&rule.name)?;
         Self::validate_priority(rule.priority)?;
 
         // Validate capture rule
-        Self::validate_capture_rule(&rule.capture)?;
+        Self::validate_capture_rule_with_matchers(&rule.capture, matchers)?;
 
         // Validate handlers
         Self::validate_handlers(&rule.handlers)?;
@@ -61,6 +66,14 @@ impl RuleValidator {
 
     /// Validate capture rule
     pub fn validate_capture_rule(capture: &CaptureRule) -> Result<()> {
+        Self::validate_capture_rule_with_matchers(capture, &NamedMatchers::default())
+    }
+
+    /// Validate capture rule, resolving `@name` references against `matchers`
+    pub fn validate_capture_rule_with_matchers(
+        capture: &CaptureRule,
+        matchers: &NamedMatchers,
+    ) -> Result<()> {
         let expr = capture.match_expr.trim();
         if expr.is_empty() {
             return Err(RequestProcessingError::RuleValidation {
@@ -68,8 +81,10 @@ impl RuleValidator {
             });
         }
 
-        compile_match_expr(expr).map_err(|error| RequestProcessingError::RuleValidation {
-            reason: format!("Invalid matchExpr: {error}"),
+        compile_match_expr_with_matchers(expr, matchers).map_err(|error| {
+            RequestProcessingError::RuleValidation {
+                reason: format!("Invalid matchExpr: {error}"),
+            }
         })?;
         Ok(())
     }
@@ -139,6 +154,178 @@ impl RuleValidator {
             });
         }
 
+        if let HandlerRuleType::Script(config) = &handler.handler_type
+            && config.script.trim().is_empty()
+        {
+            return Err(RequestProcessingError::RuleValidation {
+                reason: "Script handler requires a script".to_string(),
+            });
+        }
+
+        if let HandlerRuleType::WasmPlugin(config) = &handler.handler_type
+            && config.plugin.trim().is_empty()
+        {
+            return Err(RequestProcessingError::RuleValidation {
+                reason: "WasmPlugin handler requires a plugin path".to_string(),
+            });
+        }
+
+        if let HandlerRuleType::MockResponse(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid mock response: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::Fault(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid fault: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::ProxyForward(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid proxy forward: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::LocalFile(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid local file: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::UpstreamProxy(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid upstream proxy: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::Mirror(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid mirror: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::Breakpoint(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid breakpoint: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::WebSocketIntercept(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid WebSocket intercept: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::WebSocketMock(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid WebSocket mock: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::StreamMock(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid stream mock: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::Cors(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid CORS: {reason}"),
+                })?;
+        }
+
+        if let HandlerRuleType::Sequence(config) = &handler.handler_type {
+            config
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid sequence: {reason}"),
+                })?;
+        }
+
+        let cassette = match &handler.handler_type {
+            HandlerRuleType::Record(config) => Some(config.cassette()),
+            HandlerRuleType::Replay(config) => Some(config.cassette()),
+            _ => None,
+        };
+        if cassette.is_some_and(|cassette| cassette.trim().is_empty()) {
+            return Err(RequestProcessingError::RuleValidation {
+                reason: "Cassette path cannot be empty".to_string(),
+            });
+        }
+        if let HandlerRuleType::Replay(config) = &handler.handler_type
+            && let Some(name) = config
+                .match_headers
+                .iter()
+                .find(|name| http::HeaderName::from_bytes(name.as_bytes()).is_err())
+        {
+            return Err(RequestProcessingError::RuleValidation {
+                reason: format!("Invalid replay match header {name:?}"),
+            });
+        }
+        if let HandlerRuleType::Record(config) = &handler.handler_type
+            && let Some(name) = config
+                .redact_headers
+                .iter()
+                .flatten()
+                .find(|name| http::HeaderName::from_bytes(name.trim().as_bytes()).is_err())
+        {
+            return Err(RequestProcessingError::RuleValidation {
+                reason: format!("Invalid record redact header {name:?}"),
+            });
+        }
+
+        let (header_ops, body_transforms) = match &handler.handler_type {
+            HandlerRuleType::ModifyRequest(config) => (
+                config.modify_headers.as_ref(),
+                config.body_transforms.as_deref(),
+            ),
+            HandlerRuleType::ModifyResponse(config) => (
+                config.modify_headers.as_ref(),
+                config.body_transforms.as_deref(),
+            ),
+            _ => (None, None),
+        };
+        // Legacy header maps are left as they were: invalid entries are skipped at runtime.
+        if let Some(ModifyHeaders::Ops(ops)) = header_ops {
+            for op in ops {
+                op.validate()
+                    .map_err(|reason| RequestProcessingError::RuleValidation {
+                        reason: format!("Invalid header operation: {reason}"),
+                    })?;
+            }
+        }
+        for transform in body_transforms.unwrap_or_default() {
+            transform
+                .validate()
+                .map_err(|reason| RequestProcessingError::RuleValidation {
+                    reason: format!("Invalid body transform: {reason}"),
+                })?;
+        }
+
         Ok(())
     }
 }
@@ -165,6 +352,26 @@ mod tests {
         assert!(RuleValidator::validate_priority(10001).is_err());
     }
 
+    #[test]
+    fn test_validate_proxy_forward_path_regex() {
+        let mut handler = HandlerRule::proxy_forward_handler(
+            None,
+            Some("127.0.0.1:3000".to_string()),
+            Some("/{{groups.1}}".to_string()),
+        );
+        let rule = |handler: &HandlerRule| RequestRule {
+            handlers: vec![handler.clone()],
+            ..RequestRule::default()
+        };
+        assert!(RuleValidator::validate_rule(&rule(&handler)).is_ok());
+
+        if let HandlerRuleType::ProxyForward(config) = &mut handler.handler_type {
+            config.path_regex = Some("^/api/(".to_string());
+        }
+        let error = RuleValidator::validate_rule(&rule(&handler)).unwrap_err();
+        assert!(error.to_string().contains("pathRegex"), "{error}");
+    }
+
     #[test]
     fn test_validate_http_method() {
         assert!(RuleValidator::validate_http_method("GET").is_ok());
@@ -1,5 +1,6 @@
+use crate::dao::projects_dao::ProjectsDao;
 use crate::storage::{DataStore, read_json_or_default, write_json_atomic};
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -12,6 +13,22 @@ pub struct TrafficFilterHistory {
     pub entries: Vec<String>,
 }
 
+/// A traffic filter saved under a name, usable as `filterName` when
+/// subscribing to the request stream.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedTrafficFilter {
+    pub name: String,
+    pub expr: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedTrafficFilters {
+    #[serde(default)]
+    pub filters: Vec<SavedTrafficFilter>,
+}
+
 pub struct TrafficFilterHistoryDao {
     store: Arc<DataStore>,
 }
@@ -55,6 +72,62 @@ impl TrafficFilterHistoryDao {
     pub async fn clear(&self) -> Result<TrafficFilterHistory> {
         self.replace(Vec::new()).await
     }
+
+    fn saved_path(&self) -> std::path::PathBuf {
+        self.store.setting_path("traffic_filters")
+    }
+
+    pub async fn list_saved(&self) -> Result<SavedTrafficFilters> {
+        read_json_or_default(&self.saved_path()).await
+    }
+
+    pub async fn get_saved(&self, name: &str) -> Result<Option<SavedTrafficFilter>> {
+        let name = name.trim();
+        Ok(self
+            .list_saved()
+            .await?
+            .filters
+            .into_iter()
+            .find(|filter| filter.name == name))
+    }
+
+    /// Save or replace the filter called `name`; `expr` must compile, with
+    /// `@name` references resolved against the active project like on subscribe.
+    pub async fn save_filter(&self, name: &str, expr: &str) -> Result<SavedTrafficFilters> {
+        let name = name.trim();
+        let expr = expr.trim();
+        if name.is_empty() {
+            return Err(anyhow!("filter name is required"));
+        }
+        let projects = ProjectsDao::new(self.store.clone()).get_projects().await?;
+        let matchers = projects.matchers(&projects.active_project_id);
+        lynx_dsl::compile_match_expr_with_matchers(expr, &matchers)
+            .map_err(|error| anyhow!("filter {name} is invalid: {error}"))?;
+
+        let mut saved = self.list_saved().await?;
+        let filter = SavedTrafficFilter {
+            name: name.to_string(),
+            expr: expr.to_string(),
+        };
+        match saved.filters.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) => *entry = filter,
+            None => saved.filters.push(filter),
+        }
+        write_json_atomic(&self.saved_path(), &saved).await?;
+        Ok(saved)
+    }
+
+    pub async fn delete_filter(&self, name: &str) -> Result<SavedTrafficFilters> {
+        let name = name.trim();
+        let mut saved = self.list_saved().await?;
+        let before = saved.filters.len();
+        saved.filters.retain(|filter| filter.name != name);
+        if saved.filters.len() == before {
+            return Err(anyhow!("filter not found: {name}"));
+        }
+        write_json_atomic(&self.saved_path(), &saved).await?;
+        Ok(saved)
+    }
 }
 
 fn sanitize_entries(entries: Vec<String>) -> Vec<String> {
@@ -125,4 +198,31 @@ mod tests {
 
         assert!(history.entries.is_empty());
     }
+
+    #[tokio::test]
+    async fn saved_filters_upsert_validate_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DataStore::new(dir.path()).await.unwrap();
+        let dao = TrafficFilterHistoryDao::new(store);
+
+        dao.save_filter("api", "example.com").await.unwrap();
+        dao.save_filter("writes", "-X POST").await.unwrap();
+        let saved = dao
+            .save_filter(" api ", "api.example.com /v1")
+            .await
+            .unwrap();
+        assert_eq!(saved.filters.len(), 2);
+        assert_eq!(
+            dao.get_saved("api").await.unwrap().unwrap().expr,
+            "api.example.com /v1"
+        );
+
+        assert!(dao.save_filter("", "example.com").await.is_err());
+        assert!(dao.save_filter("broken", "((").await.is_err());
+        assert!(dao.get_saved("broken").await.unwrap().is_none());
+
+        let saved = dao.delete_filter("api").await.unwrap();
+        assert_eq!(saved.filters.len(), 1);
+        assert!(dao.delete_filter("api").await.is_err());
+    }
 }
@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
+use lynx_dsl::NamedMatchers;
 
 use super::backup::backup_rules_snapshot;
 use super::types::{
@@ -41,6 +42,11 @@ pub async fn push_rules(
 ) -> Result<PushReport> {
     let project_id = resolve_project_id(store.clone(), project).await?;
     let (mut config, created_config) = load_or_create_config(config_path, &project_id).await?;
+    let projects = ProjectsDao::new(store.clone()).get_projects().await?;
+    if let Some(runtime) = projects.project(&project_id) {
+        config.matchers.extend(runtime.matchers.clone());
+    }
+
     let dao = RequestProcessingDao::new(store);
     let runtime_rules = dao.list_rules_by_project(&project_id).await?;
 
@@ -86,13 +92,29 @@ pub async fn pull_rules(
         write_json_atomic(config_path, &config).await?;
     }
 
+    let projects = ProjectsDao::new(store.clone());
+    let mut matchers = projects
+        .get_projects()
+        .await?
+        .project(&config.project)
+        .map(|project| project.matchers.clone())
+        .unwrap_or_default();
+    matchers.extend(config.matchers.clone());
+    let named: NamedMatchers = matchers.iter().collect();
+
     for rule in &config.rules {
-        RuleValidator::validate_rule(&rule.to_request_rule(&config.project))
+        RuleValidator::validate_rule_with_matchers(&rule.to_request_rule(&config.project), &named)
             .map_err(|e| anyhow!(e.to_string()))?;
     }
 
     let dao = RequestProcessingDao::new(store.clone());
     let backup_path = backup_rules_snapshot(&store, &config.config_id).await?;
+    if !config.matchers.is_empty() {
+        projects.set_matchers(&config.project, matchers).await?;
+    }
+    if let Some(root_dir) = config_root_dir(config_path).await {
+        projects.record_root_dir(&config.project, &root_dir).await?;
+    }
 
     let existing_ids: HashSet<i32> = dao
         .list_rules()
@@ -170,6 +192,17 @@ pub async fn apply_config(config_path: &Path, store: Arc<DataStore>) -> Result<A
     Ok(report)
 }
 
+/// Absolute directory holding `config_path`.
+async fn config_root_dir(config_path: &Path) -> Option<PathBuf> {
+    let parent = config_path.parent()?;
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    tokio::fs::canonicalize(parent).await.ok()
+}
+
 pub async fn read_project_config(path: &Path) -> Result<LynxProjectConfig> {
     load_config(path).await
 }
@@ -178,6 +211,7 @@ pub async fn read_project_config(path: &Path) -> Result<LynxProjectConfig> {
 mod tests {
     use super::*;
     use crate::dao::request_processing_dao::{CaptureRule, HandlerRule, RequestRule};
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
 
     fn sample_config_rule(id: i32, name: &str, enabled: bool, match_expr: &str) -> ConfigRule {
@@ -250,6 +284,45 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn pull_records_project_dir_for_relative_files() -> Result<()> {
+        let project = tempdir()?;
+        let data = tempdir()?;
+        let config_path = project.path().join(".lynx.json");
+        let store = DataStore::new(data.path()).await?;
+        std::fs::write(project.path().join("sign.wasm"), b"")?;
+        std::fs::write(store.plugins_dir().join("shared.wasm"), b"")?;
+
+        pull_rules(&config_path, store.clone(), Some("default".to_string())).await?;
+
+        let projects = ProjectsDao::new(store.clone());
+        let fallback = store.plugins_dir();
+        let own = projects
+            .resolve_project_file("default", "sign.wasm", &fallback)
+            .await?;
+        assert_eq!(own, project.path().canonicalize()?.join("sign.wasm"));
+        let shared = projects
+            .resolve_project_file("default", "shared.wasm", &fallback)
+            .await?;
+        assert_eq!(shared, fallback.join("shared.wasm"));
+        assert!(
+            projects
+                .resolve_project_file("default", "missing.wasm", &fallback)
+                .await
+                .is_err()
+        );
+
+        // The cached project directory follows a new pull location.
+        let moved = tempdir()?;
+        std::fs::write(moved.path().join("sign.wasm"), b"")?;
+        projects.record_root_dir("default", moved.path()).await?;
+        let own = projects
+            .resolve_project_file("default", "sign.wasm", &fallback)
+            .await?;
+        assert_eq!(own, moved.path().join("sign.wasm"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn pull_creates_config_when_missing() -> Result<()> {
         let project = tempdir()?;
@@ -337,6 +410,7 @@ mod tests {
             schema_url: None,
             config_id: "test-config".to_string(),
             project: "default".to_string(),
+            matchers: BTreeMap::new(),
             rules: vec![
                 sample_config_rule(in_config_id, "in-config", false, "a.example.com"),
                 sample_config_rule(99, "new-only", true, "c.example.com"),
@@ -379,6 +453,7 @@ mod tests {
             schema_url: None,
             config_id: "test".to_string(),
             project: "default".to_string(),
+            matchers: BTreeMap::new(),
             rules: vec![sample_config_rule(99, "new", true, "new.example.com")],
         };
         write_json_atomic(&config_path, &config).await?;
@@ -390,4 +465,72 @@ mod tests {
         assert!(rules.iter().any(|r| r.name == "new"));
         Ok(())
     }
+
+    #[tokio::test]
+    async fn named_matchers_roundtrip_through_config() -> Result<()> {
+        let project = tempdir()?;
+        let data = tempdir()?;
+        let config_path = project.path().join(".lynx.json");
+        let store = DataStore::new(data.path()).await?;
+
+        let config = LynxProjectConfig {
+            schema_url: None,
+            config_id: "matchers".to_string(),
+            project: "default".to_string(),
+            matchers: BTreeMap::from([(
+                "@staging".to_string(),
+                "api.staging.example.com AND -H x-env=stg".to_string(),
+            )]),
+            rules: vec![sample_config_rule(
+                7,
+                "staging v2",
+                true,
+                "@staging AND /v2/**",
+            )],
+        };
+        write_json_atomic(&config_path, &config).await?;
+        pull_rules(&config_path, store.clone(), Some("default".to_string())).await?;
+
+        let projects = ProjectsDao::new(store.clone());
+        let matchers = projects.project_matchers("default").await?;
+        assert_eq!(
+            matchers.get("staging"),
+            Some("api.staging.example.com AND -H x-env=stg")
+        );
+        let request = axum::extract::Request::builder()
+            .uri("https://api.staging.example.com/v2/users")
+            .header("x-env", "stg")
+            .body(axum::body::Body::empty())?;
+        let matched = RequestProcessingDao::new(store.clone())
+            .find_matching_rules(&request)
+            .await?;
+        assert_eq!(matched.len(), 1);
+
+        let error = projects
+            .set_matchers("default", BTreeMap::new())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("undefined matcher @staging"));
+        let error = projects
+            .set_matchers(
+                "default",
+                BTreeMap::from([("staging".to_string(), "@staging".to_string())]),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("matcher cycle"));
+
+        std::fs::remove_file(&config_path)?;
+        push_rules(&config_path, store.clone(), Some("default".to_string())).await?;
+        let pushed = read_project_config(&config_path).await?;
+        assert_eq!(pushed.matchers.keys().collect::<Vec<_>>(), vec!["staging"]);
+        assert_eq!(pushed.rules[0].capture.match_expr, "@staging AND /v2/**");
+
+        let mut invalid = pushed;
+        invalid.rules[0].capture.match_expr = "@prod".to_string();
+        write_json_atomic(&config_path, &invalid).await?;
+        let error = pull_rules(&config_path, store, None).await.unwrap_err();
+        assert!(error.to_string().contains("undefined matcher @prod"));
+        Ok(())
+    }
 }
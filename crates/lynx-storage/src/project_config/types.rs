@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::dao::request_processing_dao::types::DEFAULT_PROJECT_ID;
@@ -17,6 +19,9 @@ pub struct LynxProjectConfig {
     pub schema_url: Option<String>,
     pub config_id: String,
     pub project: String,
+    /// Named matchers (`@name` → match expression) referenced by rule `matchExpr`s.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub matchers: BTreeMap<String, String>,
     #[serde(default)]
     pub rules: Vec<ConfigRule>,
 }
@@ -39,6 +44,7 @@ impl LynxProjectConfig {
             schema_url: Some(default_rules_export_schema_url()),
             config_id: uuid::Uuid::new_v4().to_string(),
             project: project.into(),
+            matchers: BTreeMap::new(),
             rules: Vec::new(),
         }
     }
@@ -1,6 +1,8 @@
 mod id;
 mod json_file;
+mod sequence_counters;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -8,20 +10,22 @@ use anyhow::{Result, anyhow};
 use tokio::fs;
 use tokio::sync::RwLock;
 
-use crate::dao::capture_rules_dao::CaptureRules;
+use crate::dao::capture_rules_dao::{CaptureRules, CompiledCaptureRules};
 use crate::dao::client_proxy_dao::ClientProxyConfig;
 use crate::dao::net_request_dao::CaptureSwitch;
-use crate::dao::request_processing_dao::matcher::{CompiledRule, RuleMatcher};
+use crate::dao::projects_dao::ProjectsFile;
+use crate::dao::request_processing_dao::matcher::{CompiledRules, RuleMatcher};
 use crate::dao::request_processing_dao::types::RequestRule;
 use crate::dao::traffic_filter_history_dao::TrafficFilterHistory;
 use crate::dao::{general_setting_dao::GeneralSetting, https_capture_dao::CaptureFilter};
 
 pub use json_file::{read_json, read_json_or_default, write_json_atomic};
+pub use sequence_counters::{SequenceCounter, SequenceCounterKey, SequenceCounters};
 
 #[derive(Clone)]
 pub struct RulesCacheEntry {
     pub rules: Vec<RequestRule>,
-    pub compiled: Vec<CompiledRule>,
+    pub compiled: Arc<CompiledRules>,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -36,9 +40,19 @@ struct RulesCacheState {
     fingerprint: RulesDirFingerprint,
 }
 
+#[derive(Clone)]
+struct CaptureRulesCacheState {
+    rules: Arc<CompiledCaptureRules>,
+    modified: Option<std::time::SystemTime>,
+}
+
 pub struct DataStore {
     root: PathBuf,
     rules_cache: RwLock<Option<RulesCacheState>>,
+    capture_rules_cache: RwLock<Option<CaptureRulesCacheState>>,
+    /// Project root directories, dropped and reloaded with the rules cache
+    project_root_dirs: RwLock<Option<Arc<HashMap<String, PathBuf>>>>,
+    sequence_counters: SequenceCounters,
 }
 
 impl DataStore {
@@ -47,6 +61,9 @@ impl DataStore {
         let store = Arc::new(Self {
             root: root.clone(),
             rules_cache: RwLock::new(None),
+            capture_rules_cache: RwLock::new(None),
+            project_root_dirs: RwLock::new(None),
+            sequence_counters: SequenceCounters::default(),
         });
         store.ensure_layout().await?;
         Ok(store)
@@ -64,6 +81,21 @@ impl DataStore {
         self.root.join("rules")
     }
 
+    /// Shared WebAssembly plugins, for rules whose project has no directory of its own.
+    pub fn plugins_dir(&self) -> PathBuf {
+        self.root.join("plugins")
+    }
+
+    /// Cassettes of `Record` / `Replay` rules, per project, for projects without a directory.
+    pub fn cassettes_dir(&self) -> PathBuf {
+        self.root.join("cassettes")
+    }
+
+    /// Shared mock body templates, for rules whose project has no directory of its own.
+    pub fn mocks_dir(&self) -> PathBuf {
+        self.root.join("mocks")
+    }
+
     pub fn api_studio_dir(&self) -> PathBuf {
         self.root.join("api_studio")
     }
@@ -103,6 +135,51 @@ impl DataStore {
     pub async fn invalidate_rules_cache(&self) {
         let mut cache = self.rules_cache.write().await;
         *cache = None;
+        *self.project_root_dirs.write().await = None;
+    }
+
+    /// Directory of `project_id`'s config file, which rule handlers resolve
+    /// relative paths against.
+    pub async fn project_root_dir(&self, project_id: &str) -> Result<Option<PathBuf>> {
+        if let Some(dirs) = self.project_root_dirs.read().await.clone() {
+            return Ok(dirs.get(project_id).cloned());
+        }
+        let projects: ProjectsFile = read_json_or_default(&self.setting_path("projects")).await?;
+        let dirs = Arc::new(projects.root_dirs());
+        *self.project_root_dirs.write().await = Some(dirs.clone());
+        Ok(dirs.get(project_id).cloned())
+    }
+
+    pub async fn invalidate_capture_rules_cache(&self) {
+        let mut cache = self.capture_rules_cache.write().await;
+        *cache = None;
+    }
+
+    /// Call counters of `Sequence` handlers, kept alongside the rules cache.
+    pub fn sequence_counters(&self) -> &SequenceCounters {
+        &self.sequence_counters
+    }
+
+    pub async fn get_capture_rules_cache(&self) -> Result<Arc<CompiledCaptureRules>> {
+        let path = self.setting_path("capture_rules");
+        let modified = fs::metadata(&path)
+            .await
+            .ok()
+            .and_then(|meta| meta.modified().ok());
+        if let Some(state) = self.capture_rules_cache.read().await.clone()
+            && state.modified == modified
+        {
+            return Ok(state.rules);
+        }
+
+        let rules = read_json_or_default::<CaptureRules>(&path).await?;
+        let compiled = Arc::new(CompiledCaptureRules::compile(&rules));
+        let mut cache = self.capture_rules_cache.write().await;
+        *cache = Some(CaptureRulesCacheState {
+            rules: compiled.clone(),
+            modified,
+        });
+        Ok(compiled)
     }
 
     async fn rules_dir_fingerprint(&self) -> Result<RulesDirFingerprint> {
@@ -122,6 +199,12 @@ impl DataStore {
                 max_mtime = max_mtime.max(mtime);
             }
         }
+        // Rules inline the named matchers stored with their project.
+        if let Ok(meta) = fs::metadata(self.setting_path("projects")).await
+            && let Ok(mtime) = meta.modified()
+        {
+            max_mtime = max_mtime.max(mtime);
+        }
         Ok(RulesDirFingerprint {
             file_count,
             max_mtime,
@@ -170,7 +253,9 @@ impl DataStore {
                     path.display()
                 )
             })?;
-            if let Some(rule) = rule {
+            if let Some(mut rule) = rule {
+                // Rules saved before handlers had ids get them on load.
+                rule.assign_handler_ids();
                 rules.push(rule);
             }
         }
@@ -184,12 +269,18 @@ impl DataStore {
 
     async fn load_rules_cache_entry(&self) -> Result<RulesCacheEntry> {
         let rules = self.load_all_rules().await?;
-        let compiled = RuleMatcher::compile_rules(&rules).map_err(|error| {
+        let projects: ProjectsFile = read_json_or_default(&self.setting_path("projects")).await?;
+        // `projects.json` may have changed on disk along with the rules.
+        *self.project_root_dirs.write().await = Some(Arc::new(projects.root_dirs()));
+        let compiled = RuleMatcher::compile_rules(&rules, &projects.all_matchers()).map_err(|error| {
             anyhow!(
                 "Failed to load rules: {error}. If you upgraded to matchExpr, please clear the rules directory and recreate rules."
             )
         })?;
-        Ok(RulesCacheEntry { rules, compiled })
+        Ok(RulesCacheEntry {
+            rules,
+            compiled: Arc::new(compiled),
+        })
     }
 
     pub async fn next_rule_id(&self) -> Result<i32> {
@@ -201,6 +292,7 @@ impl DataStore {
         fs::create_dir_all(self.rules_dir()).await?;
         fs::create_dir_all(self.api_studio_drafts_dir()).await?;
         fs::create_dir_all(self.api_studio_history_dir()).await?;
+        fs::create_dir_all(self.plugins_dir()).await?;
 
         self.ensure_setting_defaults().await?;
         self.ensure_collection_default().await?;
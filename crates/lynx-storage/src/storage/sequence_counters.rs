@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Identifies one counter of a `Sequence` handler.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SequenceCounterKey {
+    pub rule_id: i32,
+    /// Id of the `Sequence` handler, which tells several sequences of one rule apart
+    pub handler_id: i32,
+    /// Client IP or header value for scoped sequences; empty for rule scope
+    pub scope: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceCounter {
+    pub rule_id: i32,
+    pub handler_id: i32,
+    pub scope: String,
+    /// Calls served so far
+    pub calls: u64,
+}
+
+struct Calls {
+    count: u64,
+    /// Value of [`Counters::clock`] at the latest call
+    last_call: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    calls: HashMap<SequenceCounterKey, Calls>,
+    /// Keys by their latest call, oldest first
+    recent: BTreeMap<u64, SequenceCounterKey>,
+    clock: u64,
+}
+
+/// In-memory call counters of `Sequence` handlers.
+///
+/// Scoped sequences get a counter per client or header value, so at most
+/// [`SequenceCounters::MAX_COUNTERS`] are kept; the least recently called one
+/// is dropped to make room, and starts over on its next call.
+#[derive(Default)]
+pub struct SequenceCounters {
+    counters: Mutex<Counters>,
+}
+
+impl SequenceCounters {
+    pub const MAX_COUNTERS: usize = 10_000;
+
+    /// Counts a call and returns how many calls came before it.
+    pub fn next_call(&self, key: SequenceCounterKey) -> u64 {
+        let mut counters = self.counters.lock().expect("sequence counters poisoned");
+        let Counters {
+            calls,
+            recent,
+            clock,
+        } = &mut *counters;
+        let now = *clock;
+        *clock += 1;
+
+        let call = match calls.get_mut(&key) {
+            Some(entry) => {
+                recent.remove(&entry.last_call);
+                entry.last_call = now;
+                entry.count += 1;
+                entry.count - 1
+            }
+            None => {
+                if calls.len() >= Self::MAX_COUNTERS
+                    && let Some((_, oldest)) = recent.pop_first()
+                {
+                    calls.remove(&oldest);
+                }
+                calls.insert(
+                    key.clone(),
+                    Calls {
+                        count: 1,
+                        last_call: now,
+                    },
+                );
+                0
+            }
+        };
+        recent.insert(now, key);
+        call
+    }
+
+    /// Counters of `rule_id`, or of every rule, sorted by rule.
+    pub fn list(&self, rule_id: Option<i32>) -> Vec<SequenceCounter> {
+        let counters = self.counters.lock().expect("sequence counters poisoned");
+        let mut list: Vec<SequenceCounter> = counters
+            .calls
+            .iter()
+            .filter(|(key, _)| rule_id.is_none_or(|id| key.rule_id == id))
+            .map(|(key, calls)| SequenceCounter {
+                rule_id: key.rule_id,
+                handler_id: key.handler_id,
+                scope: key.scope.clone(),
+                calls: calls.count,
+            })
+            .collect();
+        list.sort_by(|a, b| {
+            (a.rule_id, a.handler_id, &a.scope).cmp(&(b.rule_id, b.handler_id, &b.scope))
+        });
+        list
+    }
+
+    /// Drops the counters of `rule_id`, or all of them; returns how many were dropped.
+    pub fn reset(&self, rule_id: Option<i32>) -> usize {
+        let mut counters = self.counters.lock().expect("sequence counters poisoned");
+        let keep = |key: &SequenceCounterKey| rule_id.is_some_and(|id| key.rule_id != id);
+        let before = counters.calls.len();
+        counters.calls.retain(|key, _| keep(key));
+        counters.recent.retain(|_, key| keep(key));
+        before - counters.calls.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(rule_id: i32, scope: &str) -> SequenceCounterKey {
+        SequenceCounterKey {
+            rule_id,
+            handler_id: 3,
+            scope: scope.to_string(),
+        }
+    }
+
+    #[test]
+    fn counts_lists_and_resets_per_rule() {
+        let counters = SequenceCounters::default();
+        assert_eq!(counters.next_call(key(1, "")), 0);
+        assert_eq!(counters.next_call(key(1, "")), 1);
+        assert_eq!(counters.next_call(key(2, "10.0.0.1")), 0);
+
+        let listed = counters.list(Some(1));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].calls, 2);
+        assert_eq!(counters.list(None).len(), 2);
+
+        assert_eq!(counters.reset(Some(1)), 1);
+        assert_eq!(counters.next_call(key(1, "")), 0);
+        assert_eq!(counters.reset(None), 2);
+        assert!(counters.list(None).is_empty());
+    }
+
+    #[test]
+    fn drops_the_least_recently_called_counter_when_full() {
+        let counters = SequenceCounters::default();
+        for client in 0..SequenceCounters::MAX_COUNTERS {
+            counters.next_call(key(1, &client.to_string()));
+        }
+        assert_eq!(counters.next_call(key(1, "0")), 1);
+
+        assert_eq!(counters.next_call(key(1, "new")), 0);
+        assert_eq!(counters.list(None).len(), SequenceCounters::MAX_COUNTERS);
+        assert_eq!(counters.next_call(key(1, "0")), 2);
+        assert_eq!(counters.next_call(key(1, "1")), 0);
+    }
+}
@@ -2,7 +2,9 @@ use anyhow::Result;
 use axum::body::Body;
 use axum::extract::Request;
 use http::Method;
-use lynx_storage::dao::request_processing_dao::{CaptureRule, RequestProcessingDao, RequestRule};
+use lynx_storage::dao::request_processing_dao::{
+    CaptureRule, RequestProcessingDao, RequestRule, RuleMatchContext,
+};
 use lynx_storage::storage::DataStore;
 use tempfile::tempdir;
 
@@ -84,3 +86,86 @@ async fn old_schema_rule_file_causes_load_error() -> Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn sampled_rules_report_whether_the_sample_fired() -> Result<()> {
+    let dir = tempdir()?;
+    let store = DataStore::new(dir.path()).await?;
+    let dao = RequestProcessingDao::new(store.clone());
+
+    for (name, match_expr) in [
+        ("never", "example.com --sample 0%:client"),
+        ("always", "example.com --sample 100%:client"),
+        ("inverted", "example.com AND NOT --sample 0%:client"),
+        ("office", "example.com --time 09:00-18:00"),
+        ("plain", "example.com"),
+    ] {
+        dao.create_rule(RequestRule {
+            name: name.to_string(),
+            capture: CaptureRule {
+                id: None,
+                match_expr: match_expr.to_string(),
+            },
+            ..Default::default()
+        })
+        .await?;
+    }
+
+    let mut request = make_request("GET", "https://example.com/");
+    request.extensions_mut().insert(RuleMatchContext {
+        client: Some("10.0.0.8".to_string()),
+        sample_seed: 1,
+        minute_of_day: Some(20 * 60),
+    });
+
+    let mut matches: Vec<(String, Option<bool>)> = dao
+        .find_rule_matches(&request)
+        .await?
+        .into_iter()
+        .map(|matched| (matched.rule.name, matched.sampled))
+        .collect();
+    matches.sort();
+    assert_eq!(
+        matches,
+        vec![
+            ("always".to_string(), Some(true)),
+            ("inverted".to_string(), Some(true)),
+            ("never".to_string(), Some(false)),
+            ("plain".to_string(), None),
+        ]
+    );
+
+    let mut fired: Vec<String> = dao
+        .find_matching_rules(&request)
+        .await?
+        .into_iter()
+        .map(|rule| rule.name)
+        .collect();
+    fired.sort();
+    assert_eq!(fired, vec!["always", "inverted", "plain"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn sampling_without_context_is_stable_per_request() -> Result<()> {
+    let dir = tempdir()?;
+    let store = DataStore::new(dir.path()).await?;
+    let dao = RequestProcessingDao::new(store.clone());
+    dao.create_rule(RequestRule {
+        name: "half".to_string(),
+        capture: CaptureRule {
+            id: None,
+            match_expr: "example.com --sample 50%".to_string(),
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    for index in 0..20 {
+        let url = format!("https://example.com/item/{index}");
+        let first = dao.find_rule_matches(&make_request("GET", &url)).await?;
+        let again = dao.find_rule_matches(&make_request("GET", &url)).await?;
+        assert_eq!(first[0].sampled, again[0].sampled, "{url}");
+    }
+    Ok(())
+}
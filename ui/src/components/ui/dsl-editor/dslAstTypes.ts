@@ -46,6 +46,7 @@ export type DslPrimary =
   | { CliOnly: DslCliArgs }
   | { Url: { url: DslUrl, cli: DslCliArgs | null } }
   | { Grouped: DslExpr }
+  | { Ref: DslSpanned<string> }
 
 export interface DslUrl {
   scheme: DslSpanned<string> | null
@@ -15,6 +15,7 @@ export const dslHighlightColors = {
   comment: 'var(--color-accent-foreground)',
   cliFlag: 'var(--color-primary)',
   cliValue: 'var(--color-destructive)',
+  matcherRef: 'var(--color-chart-2)',
 } as const
 
 const highlightClassByKind: Record<string, string> = {
@@ -31,6 +32,7 @@ const highlightClassByKind: Record<string, string> = {
   LongFlag: 'cm-dsl-cli-flag',
   CliValue: 'cm-dsl-cli-value',
   Paren: 'cm-dsl-paren',
+  MatcherRef: 'cm-dsl-matcher-ref',
 }
 
 function buildHighlightDecorations(doc: string) {
@@ -105,6 +107,10 @@ const dslHighlightTheme = EditorView.baseTheme({
   '.cm-dsl-paren': {
     color: dslHighlightColors.paren,
   },
+  '.cm-dsl-matcher-ref': {
+    color: dslHighlightColors.matcherRef,
+    fontStyle: 'italic',
+  },
 })
 
 export const dslLanguageExtension = [
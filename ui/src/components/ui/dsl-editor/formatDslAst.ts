@@ -104,6 +104,10 @@ function formatPrimary(lines: string[], depth: number, source: string, primary:
     }
     return
   }
+  if ('Ref' in primary) {
+    pushSpanned(lines, depth, 'MatcherRef', source, primary.Ref)
+    return
+  }
   lines.push(`${indent(depth)}Grouped${spanSuffix(source, primary.Grouped.span)}`)
   formatExpr(lines, depth + 1, source, primary.Grouped)
 }
@@ -1,6 +1,7 @@
 export { default as NetworkPanelHeader } from './NetworkPanelHeader.vue'
 export { default as NetworkRequestPanel } from './NetworkRequestPanel.vue'
 export { default as CaptureRulesPopover } from './CaptureRulesPopover.vue'
+export { default as BreakpointsPopover } from './BreakpointsPopover.vue'
 export { default as TrafficContextMenu } from './TrafficContextMenu.vue'
 export { default as TrafficMatchFilterInput } from './TrafficMatchFilterInput.vue'
 export type { RequestViewMode } from './NetworkPanelHeader.vue'
@@ -141,9 +141,14 @@ const sampleRecord3: NetworkDetailRecord = {
   url: 'https://gateway.lynx.internal/runtime/hydration/island-8/widgets/container-21/panels/panel-77/fragments/fragment-1002?traceId=trace_1002_9&locale=zh-CN&viewport=desktop',
   matchedRules: [
     { ruleId: '201', name: 'Mock · 拦截 404', reason: 'matchDsl 命中' },
-    { ruleId: '202', name: 'Mock · 延迟 800ms', reason: 'matchDsl 命中' },
+    { ruleId: '202', name: 'Mock · 延迟 800ms', reason: 'matchDsl 命中', sampled: false },
     { ruleId: '203', name: 'Mock · 代理转发到 staging', reason: 'matchDsl 命中' },
   ],
+  scriptLogs: [
+    { timestamp: 1760000000120, level: 'info', hook: 'onRequest', message: 'rewrite /fragments -> /v2/fragments' },
+    { timestamp: 1760000000121, level: 'debug', hook: 'onRequest', message: '#{"locale": "zh-CN"}' },
+    { timestamp: 1760000000354, level: 'error', hook: 'onResponse', message: 'onResponse failed: Variable not found: payload (line 3, position 9)' },
+  ],
 }
 
 const detailRecords: NetworkDetailRecord[] = [sampleRecord, sampleRecord2, sampleRecord3]
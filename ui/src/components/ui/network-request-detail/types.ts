@@ -23,6 +23,8 @@ export interface NetworkDetailMatchedRule {
   ruleId: string
   name: string
   reason?: string
+  /** Rules with `--sample`: whether the sample fired for this request. */
+  sampled?: boolean
 }
 
 export type WebSocketFrameDirection = 'clientToServer' | 'serverToClient'
@@ -39,6 +41,16 @@ export interface NetworkWebSocketFrame {
   previewLabel: string
 }
 
+export type ScriptLogLevel = 'info' | 'debug' | 'error'
+
+/** Output of a script handler hook (`print`, `debug`, or a failure). */
+export interface NetworkScriptLog {
+  timestamp: number
+  level: ScriptLogLevel
+  hook: string
+  message: string
+}
+
 export interface NetworkDetailRecord {
   id: string
   method: string
@@ -49,6 +61,7 @@ export interface NetworkDetailRecord {
   protocol?: string
   host?: string
   path?: string
+  /** Peer Lynx connected to: the resolved server, or the upstream proxy when one is used. */
   remoteAddress?: string
   startTime?: string
   durationMs?: number
@@ -70,4 +83,5 @@ export interface NetworkDetailRecord {
   matchedRules?: NetworkDetailMatchedRule[]
   websocketFrames?: NetworkWebSocketFrame[]
   websocketEnded?: boolean
+  scriptLogs?: NetworkScriptLog[]
 }
@@ -2,24 +2,53 @@ import type { Meta, StoryObj } from '@storybook/vue3-vite'
 import { ref } from 'vue'
 import {
   BlockActionConfig,
+  BreakpointActionConfig,
+  WebSocketInterceptActionConfig,
+  WebSocketMockActionConfig,
   DelayActionConfig,
+  FaultActionConfig,
   HtmlScriptInjectorActionConfig,
   LocalFileActionConfig,
+  MirrorActionConfig,
+  CorsActionConfig,
+  MockResponseActionConfig,
   ModifyRequestActionConfig,
   ModifyResponseActionConfig,
   ProxyForwardActionConfig,
+  RecordActionConfig,
+  ReplayActionConfig,
+  ScriptActionConfig,
+  SequenceActionConfig,
+  StreamMockActionConfig,
   ThrottleActionConfig,
+  UpstreamProxyActionConfig,
+  WasmPluginActionConfig,
 } from './action-configs'
 import type {
   RuleBlockActionConfig,
+  RuleBreakpointActionConfig,
+  RuleWebSocketInterceptActionConfig,
+  RuleWebSocketMockActionConfig,
   RuleDelayActionConfig,
+  RuleFaultActionConfig,
   RuleHtmlScriptInjectorActionConfig,
   RuleLocalFileActionConfig,
+  RuleMirrorActionConfig,
+  RuleCorsActionConfig,
+  RuleMockResponseActionConfig,
   RuleModifyRequestActionConfig,
   RuleModifyResponseActionConfig,
   RuleProxyForwardActionConfig,
+  RuleRecordActionConfig,
+  RuleReplayActionConfig,
+  RuleScriptActionConfig,
+  RuleSequenceActionConfig,
+  RuleStreamMockActionConfig,
   RuleThrottleActionConfig,
+  RuleUpstreamProxyActionConfig,
+  RuleWasmPluginActionConfig,
 } from './types'
+import { createFaultDraft, createMockResponseConfig } from './types'
 
 const meta = {
   title: 'Workbench/ActionConfigs',
@@ -69,7 +98,16 @@ export const ProxyForward: Story = {
   render: () => ({
     components: { ProxyForwardActionConfig },
     setup() {
-      const value = ref<RuleProxyForwardActionConfig>({ targetScheme: 'http', targetAuthority: 'example.com:5173', targetPath: '/v1' })
+      const value = ref<RuleProxyForwardActionConfig>({
+        targetScheme: 'http',
+        targetAuthority: 'example.com:5173',
+        targetPath: '/{{params.[0]}}',
+        pathRegex: '',
+        queryMode: 'merge',
+        queryParams: [{ key: 'debug', value: '1' }],
+        preserveHost: false,
+        targets: [],
+      })
       return { value }
     },
     template: renderCard('Proxy Forward', 'ProxyForwardActionConfig'),
@@ -81,10 +119,14 @@ export const ModifyRequest: Story = {
     components: { ModifyRequestActionConfig },
     setup() {
       const value = ref<RuleModifyRequestActionConfig>({
-        modifyHeaders: [{ key: 'x-env', value: 'staging' }],
+        modifyHeaders: [
+          { op: 'set', key: 'x-env', value: 'staging', to: '', pattern: '' },
+          { op: 'set', key: 'x-correlation-id', value: '{{req.header.x-request-id}}', to: '', pattern: '' },
+        ],
         modifyMethod: 'POST',
         modifyUrl: 'https://example.com/rewrite',
         modifyBody: '{"patched":true}',
+        bodyTransforms: [],
       })
       return { value }
     },
@@ -97,9 +139,17 @@ export const ModifyResponse: Story = {
     components: { ModifyResponseActionConfig },
     setup() {
       const value = ref<RuleModifyResponseActionConfig>({
-        modifyHeaders: [{ key: 'x-cache', value: 'bypass' }],
+        modifyHeaders: [
+          { op: 'append', key: 'set-cookie', value: 'debug=1', to: '', pattern: '' },
+          { op: 'remove', key: 'server', value: '', to: '', pattern: '' },
+          { op: 'regexRewrite', key: 'location', value: 'https://', to: '', pattern: '^http://' },
+        ],
         modifyStatusCode: 202,
-        modifyBody: '{"ok":true}',
+        modifyBody: '',
+        bodyTransforms: [
+          { type: 'jsonPathSet', pattern: '', replacement: '', path: '$.user.role', valueJson: '"admin"' },
+          { type: 'regexReplace', pattern: '"price":\\s*(\\d+)', replacement: '"price": $1.99', path: '', valueJson: '' },
+        ],
       })
       return { value }
     },
@@ -111,13 +161,243 @@ export const LocalFile: Story = {
   render: () => ({
     components: { LocalFileActionConfig },
     setup() {
-      const value = ref<RuleLocalFileActionConfig>({ filePath: '/tmp/mock.json', contentType: 'application/json', statusCode: 200 })
+      const value = ref<RuleLocalFileActionConfig>({
+        filePath: '/tmp/mock.json',
+        contentType: 'application/json',
+        statusCode: 200,
+        mode: 'file',
+        stripPrefix: '',
+        spaFallback: false,
+      })
       return { value }
     },
     template: renderCard('Local File', 'LocalFileActionConfig'),
   }),
 }
 
+export const MockResponse: Story = {
+  render: () => ({
+    components: { MockResponseActionConfig },
+    setup() {
+      const value = ref<RuleMockResponseActionConfig>({
+        statusCode: 201,
+        headers: [{ key: 'x-request-id', value: '{{uuid}}' }],
+        bodySource: 'inline',
+        body: '{ "id": "{{params.[0]}}", "name": "{{body.name}}", "createdAt": "{{now}}" }',
+        bodyFile: '',
+        latencyMs: 150,
+      })
+      return { value }
+    },
+    template: renderCard('Mock Response', 'MockResponseActionConfig'),
+  }),
+}
+
+export const Record: Story = {
+  render: () => ({
+    components: { RecordActionConfig },
+    setup() {
+      const value = ref<RuleRecordActionConfig>({ cassette: 'cassettes/staging.jsonl' })
+      return { value }
+    },
+    template: renderCard('Record', 'RecordActionConfig'),
+  }),
+}
+
+export const Replay: Story = {
+  render: () => ({
+    components: { ReplayActionConfig },
+    setup() {
+      const value = ref<RuleReplayActionConfig>({
+        cassette: 'cassettes/staging.jsonl',
+        matchOn: ['method', 'host', 'path', 'query'],
+        matchHeaders: ['x-tenant'],
+        fallback: 'notFound',
+      })
+      return { value }
+    },
+    template: renderCard('Replay', 'ReplayActionConfig'),
+  }),
+}
+
+export const Fault: Story = {
+  render: () => ({
+    components: { FaultActionConfig },
+    setup() {
+      const value = ref<RuleFaultActionConfig>({
+        faults: [
+          createFaultDraft({ type: 'reset', probability: 0.1, phase: 'afterHeaders' }),
+          createFaultDraft({ type: 'stall', probability: 0.25, afterBytes: 512, durationMs: 8000 }),
+          createFaultDraft({ type: 'corruptGzip', probability: 0.05 }),
+        ],
+      })
+      return { value }
+    },
+    template: renderCard('Fault', 'FaultActionConfig'),
+  }),
+}
+
+export const Sequence: Story = {
+  render: () => ({
+    components: { SequenceActionConfig },
+    setup() {
+      const value = ref<RuleSequenceActionConfig>({
+        responses: [
+          createMockResponseConfig({ statusCode: 503, body: '{"error":"warming up"}' }),
+          createMockResponseConfig({ statusCode: 200, body: '{"status":"ready"}' }),
+        ],
+        scope: 'header',
+        scopeHeader: 'x-tenant',
+        onExhausted: 'stickAtLast',
+      })
+      return { value }
+    },
+    template: renderCard('Sequence', 'SequenceActionConfig'),
+  }),
+}
+
+export const StreamMock: Story = {
+  render: () => ({
+    components: { StreamMockActionConfig },
+    setup() {
+      const value = ref<RuleStreamMockActionConfig>({
+        format: 'sse',
+        headers: [],
+        chunks: [
+          { delayMs: 0, data: '{"token":"Hel"}', event: 'token', id: '1' },
+          { delayMs: 80, data: '{"token":"lo"}', event: 'token', id: '2' },
+          { delayMs: 80, data: '[DONE]', event: '', id: '' },
+        ],
+        repeat: 1,
+      })
+      return { value }
+    },
+    template: renderCard('Stream Mock', 'StreamMockActionConfig'),
+  }),
+}
+
+export const UpstreamProxy: Story = {
+  render: () => ({
+    components: { UpstreamProxyActionConfig },
+    setup() {
+      const value = ref<RuleUpstreamProxyActionConfig>({
+        protocol: 'socks5',
+        address: 'jump.corp.example:1080',
+        username: 'alice',
+        password: 'secret',
+      })
+      return { value }
+    },
+    template: renderCard('Upstream Proxy', 'UpstreamProxyActionConfig'),
+  }),
+}
+
+export const Mirror: Story = {
+  render: () => ({
+    components: { MirrorActionConfig },
+    setup() {
+      const value = ref<RuleMirrorActionConfig>({
+        targets: ['http://staging.internal:8080', 'https://canary.example.com/v2'],
+        ignoreHeaders: ['x-request-id', 'etag'],
+        timeoutMs: 5000,
+      })
+      return { value }
+    },
+    template: renderCard('Mirror', 'MirrorActionConfig'),
+  }),
+}
+
+export const Cors: Story = {
+  render: () => ({
+    components: { CorsActionConfig },
+    setup() {
+      const value = ref<RuleCorsActionConfig>({
+        allowedOrigins: ['http://localhost:5173'],
+        echoOrigin: false,
+        allowedMethods: ['GET', 'POST', 'PUT', 'DELETE', 'OPTIONS'],
+        allowedHeaders: [],
+        exposeHeaders: ['x-request-id'],
+        allowCredentials: true,
+        maxAgeSecs: 600,
+      })
+      return { value }
+    },
+    template: renderCard('CORS', 'CorsActionConfig'),
+  }),
+}
+
+export const Breakpoint: Story = {
+  render: () => ({
+    components: { BreakpointActionConfig },
+    setup() {
+      const value = ref<RuleBreakpointActionConfig>({
+        phase: 'both',
+        timeoutMs: 120000,
+      })
+      return { value }
+    },
+    template: renderCard('Breakpoint', 'BreakpointActionConfig'),
+  }),
+}
+
+export const WebSocketIntercept: Story = {
+  render: () => ({
+    components: { WebSocketInterceptActionConfig },
+    setup() {
+      const value = ref<RuleWebSocketInterceptActionConfig>({
+        rules: [
+          {
+            direction: 'clientToServer',
+            pattern: '^heartbeat$',
+            jsonPath: '',
+            jsonValue: '',
+            action: 'drop',
+            replacement: '',
+            transforms: [],
+            delayMs: 1000,
+          },
+          {
+            direction: 'serverToClient',
+            pattern: '',
+            jsonPath: '$.type',
+            jsonValue: '"price"',
+            action: 'rewrite',
+            replacement: '',
+            transforms: [{ type: 'jsonPathSet', pattern: '', replacement: '', path: '$.price', valueJson: '0' }],
+            delayMs: 1000,
+          },
+        ],
+      })
+      return { value }
+    },
+    template: renderCard('WebSocket Intercept', 'WebSocketInterceptActionConfig'),
+  }),
+}
+
+export const WebSocketMock: Story = {
+  render: () => ({
+    components: { WebSocketMockActionConfig },
+    setup() {
+      const value = ref<RuleWebSocketMockActionConfig>({
+        onConnect: [{ kind: 'text', text: '{"type":"welcome"}', binaryBase64: '' }],
+        replies: [
+          {
+            pattern: '^ping (\\d+)$',
+            frames: [{ kind: 'text', text: 'pong $1', binaryBase64: '', delayMs: 50 }],
+          },
+        ],
+        echo: true,
+        periodic: [
+          { intervalMs: 5000, frame: { kind: 'text', text: '{"type":"tick"}', binaryBase64: '' }, count: 3 },
+        ],
+        close: { afterMs: 30000, code: 4000, reason: 'session expired' },
+      })
+      return { value }
+    },
+    template: renderCard('WebSocket Mock', 'WebSocketMockActionConfig'),
+  }),
+}
+
 export const HtmlScriptInjector: Story = {
   render: () => ({
     components: { HtmlScriptInjectorActionConfig },
@@ -147,3 +427,38 @@ export const Throttle: Story = {
     template: renderCard('Throttle', 'ThrottleActionConfig'),
   }),
 }
+
+export const Script: Story = {
+  render: () => ({
+    components: { ScriptActionConfig },
+    setup() {
+      const value = ref<RuleScriptActionConfig>({
+        script: `fn onRequest(req) {
+  if req.url.contains("/admin") {
+    return respond(403, "blocked by script");
+  }
+  req.headers["x-debug"] = "1";
+  req
+}`,
+        timeoutMs: 200,
+      })
+      return { value }
+    },
+    template: renderCard('Script', 'ScriptActionConfig'),
+  }),
+}
+
+export const WasmPlugin: Story = {
+  render: () => ({
+    components: { WasmPluginActionConfig },
+    setup() {
+      const value = ref<RuleWasmPluginActionConfig>({
+        plugin: 'plugins/hmac-sign.wasm',
+        configJson: '{\n  "keyId": "team-a",\n  "header": "x-signature"\n}',
+        fuel: 100000000,
+      })
+      return { value }
+    },
+    template: renderCard('WasmPlugin', 'WasmPluginActionConfig'),
+  }),
+}
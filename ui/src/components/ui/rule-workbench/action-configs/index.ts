@@ -1,8 +1,22 @@
 export { default as BlockActionConfig } from './BlockActionConfig.vue'
 export { default as DelayActionConfig } from './DelayActionConfig.vue'
 export { default as ProxyForwardActionConfig } from './ProxyForwardActionConfig.vue'
+export { default as UpstreamProxyActionConfig } from './UpstreamProxyActionConfig.vue'
+export { default as MirrorActionConfig } from './MirrorActionConfig.vue'
+export { default as CorsActionConfig } from './CorsActionConfig.vue'
+export { default as BreakpointActionConfig } from './BreakpointActionConfig.vue'
+export { default as WebSocketInterceptActionConfig } from './WebSocketInterceptActionConfig.vue'
+export { default as WebSocketMockActionConfig } from './WebSocketMockActionConfig.vue'
 export { default as ModifyRequestActionConfig } from './ModifyRequestActionConfig.vue'
 export { default as ModifyResponseActionConfig } from './ModifyResponseActionConfig.vue'
 export { default as LocalFileActionConfig } from './LocalFileActionConfig.vue'
+export { default as MockResponseActionConfig } from './MockResponseActionConfig.vue'
+export { default as RecordActionConfig } from './RecordActionConfig.vue'
+export { default as ReplayActionConfig } from './ReplayActionConfig.vue'
+export { default as FaultActionConfig } from './FaultActionConfig.vue'
+export { default as SequenceActionConfig } from './SequenceActionConfig.vue'
+export { default as StreamMockActionConfig } from './StreamMockActionConfig.vue'
 export { default as ThrottleActionConfig } from './ThrottleActionConfig.vue'
 export { default as HtmlScriptInjectorActionConfig } from './HtmlScriptInjectorActionConfig.vue'
+export { default as ScriptActionConfig } from './ScriptActionConfig.vue'
+export { default as WasmPluginActionConfig } from './WasmPluginActionConfig.vue'
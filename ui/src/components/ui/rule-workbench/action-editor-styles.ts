@@ -11,8 +11,22 @@ export const HANDLER_TYPES = [
   'modifyResponse',
   'block',
   'localFile',
+  'mockResponse',
+  'record',
+  'replay',
+  'fault',
+  'sequence',
+  'streamMock',
   'proxyForward',
+  'upstreamProxy',
+  'mirror',
+  'cors',
+  'breakpoint',
+  'webSocketIntercept',
+  'webSocketMock',
   'delay',
   'throttle',
   'htmlScriptInjector',
+  'script',
+  'wasmPlugin',
 ] as const
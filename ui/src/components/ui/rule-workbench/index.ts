@@ -11,20 +11,54 @@ export type {
 export type {
   RuleActionDraft,
   RuleBlockActionConfig,
+  RuleBodyTransformDraft,
+  RuleBodyTransformType,
   RuleDelayActionConfig,
+  RuleFaultActionConfig,
+  RuleFaultDraft,
+  RuleFaultType,
+  RuleHeaderOpDraft,
+  RuleHeaderOpType,
   RuleHeaderPair,
   RuleHtmlScriptInjectorActionConfig,
   RuleLocalFileActionConfig,
+  RuleMirrorActionConfig,
+  RuleCorsActionConfig,
+  RuleBreakpointActionConfig,
+  RuleWebSocketInterceptActionConfig,
+  RuleWebSocketFrameRuleDraft,
+  RuleWebSocketMockActionConfig,
+  RuleWebSocketMockFrameDraft,
+  RuleMockResponseActionConfig,
   RuleModifyRequestActionConfig,
   RuleModifyResponseActionConfig,
   RuleProxyForwardActionConfig,
+  RuleProxyForwardQueryMode,
+  RuleProxyForwardTarget,
+  RuleRecordActionConfig,
+  RuleReplayActionConfig,
+  RuleReplayFallback,
+  RuleReplayMatchKey,
+  RuleScriptActionConfig,
+  RuleSequenceActionConfig,
+  RuleSequenceScope,
+  RuleStreamMockActionConfig,
+  RuleStreamMockChunkDraft,
   RuleThrottleActionConfig,
   RuleThrottlePreset,
+  RuleUpstreamProxyActionConfig,
+  RuleUpstreamProxyProtocol,
+  RuleWasmPluginActionConfig,
   RuleDraft,
   RuleHandlerType,
 } from './types'
 export {
   createAction,
+  createFaultDraft,
+  createWebSocketFrameRuleDraft,
+  createWebSocketMockFrameDraft,
+  createStreamMockChunkDraft,
+  createMockResponseConfig,
   changeActionType,
   getActionSummary,
   getActionValidationErrors,
@@ -5,10 +5,24 @@ export type RuleHandlerType =
   | 'modifyRequest'
   | 'modifyResponse'
   | 'localFile'
+  | 'mockResponse'
+  | 'record'
+  | 'replay'
+  | 'fault'
+  | 'sequence'
+  | 'streamMock'
   | 'proxyForward'
+  | 'upstreamProxy'
+  | 'mirror'
+  | 'cors'
+  | 'breakpoint'
+  | 'webSocketIntercept'
+  | 'webSocketMock'
   | 'delay'
   | 'throttle'
   | 'htmlScriptInjector'
+  | 'script'
+  | 'wasmPlugin'
 
 interface RuleActionBase {
   id: string
@@ -28,11 +42,29 @@ export interface RuleDelayActionConfig {
   delayType: 'beforeRequest' | 'afterRequest' | 'both'
 }
 
+export interface RuleProxyForwardTarget {
+  targetScheme: string
+  targetAuthority: string
+  targetPath: string
+  weight: number
+}
+
+export type RuleProxyForwardQueryMode = 'keep' | 'merge' | 'replace'
+
 export interface RuleProxyForwardActionConfig {
   /** Empty = inherit; `http` / `https` (WS uses ws/wss via proxy). */
   targetScheme: string
+  /** May be a template, e.g. `{{req.header.x-upstream}}`. */
   targetAuthority: string
+  /** A templated path replaces the request path; a plain one is prefixed. */
   targetPath: string
+  /** Groups of this regex are exposed to templates as `groups`. */
+  pathRegex: string
+  queryMode: RuleProxyForwardQueryMode
+  queryParams: RuleHeaderPair[]
+  preserveHost: boolean
+  /** Weighted targets; one is picked per request and overrides the fields above. */
+  targets: RuleProxyForwardTarget[]
 }
 
 export interface RuleHeaderPair {
@@ -40,23 +72,233 @@ export interface RuleHeaderPair {
   value: string
 }
 
+export type RuleHeaderOpType = 'set' | 'append' | 'remove' | 'rename' | 'regexRewrite'
+
+/**
+ * Ordered header operation. `key` is the header name; `value` is the value
+ * (or the replacement for `regexRewrite`) and may use `{{...}}` templates.
+ */
+export interface RuleHeaderOpDraft extends RuleHeaderPair {
+  op: RuleHeaderOpType
+  to: string
+  pattern: string
+}
+
+export type RuleBodyTransformType =
+  | 'regexReplace'
+  | 'jsonPatch'
+  | 'mergePatch'
+  | 'jsonPathSet'
+  | 'jsonPathDelete'
+
+/** Flat editor row; `valueJson` holds the patch document or the value to set. */
+export interface RuleBodyTransformDraft {
+  type: RuleBodyTransformType
+  pattern: string
+  replacement: string
+  path: string
+  valueJson: string
+}
+
 export interface RuleModifyRequestActionConfig {
-  modifyHeaders: RuleHeaderPair[]
+  modifyHeaders: RuleHeaderOpDraft[]
   modifyMethod: string
   modifyUrl: string
   modifyBody: string
+  bodyTransforms: RuleBodyTransformDraft[]
 }
 
 export interface RuleModifyResponseActionConfig {
-  modifyHeaders: RuleHeaderPair[]
+  modifyHeaders: RuleHeaderOpDraft[]
   modifyStatusCode?: number
   modifyBody: string
+  bodyTransforms: RuleBodyTransformDraft[]
 }
 
 export interface RuleLocalFileActionConfig {
+  /** A single file, or the root folder in `directory` mode. */
   filePath: string
   contentType: string
   statusCode?: number
+  mode: 'file' | 'directory'
+  /** Request path prefix removed before resolving under the root. */
+  stripPrefix: string
+  /** Serve the root `index.html` for missing extensionless paths. */
+  spaFallback: boolean
+}
+
+export interface RuleMockResponseActionConfig {
+  statusCode?: number
+  /** Header values are templates. */
+  headers: RuleHeaderPair[]
+  /** `inline` sends `body`; `file` reads the template at `bodyFile`. */
+  bodySource: 'inline' | 'file'
+  body: string
+  bodyFile: string
+  latencyMs?: number
+}
+
+export interface RuleRecordActionConfig {
+  cassette: string
+  /** Headers recorded as a digest; undefined uses the server defaults, empty redacts nothing */
+  redactHeaders?: string[]
+}
+
+export type RuleReplayMatchKey = 'method' | 'host' | 'path' | 'query' | 'body'
+
+export type RuleReplayFallback = 'passthrough' | 'notFound' | 'error'
+
+export interface RuleReplayActionConfig {
+  cassette: string
+  matchOn: RuleReplayMatchKey[]
+  matchHeaders: string[]
+  fallback: RuleReplayFallback
+}
+
+export type RuleFaultType =
+  | 'reset'
+  | 'truncate'
+  | 'wrongContentLength'
+  | 'stall'
+  | 'malformedChunked'
+  | 'corruptGzip'
+
+/** One fault; only the fields for its `type` are sent. */
+export interface RuleFaultDraft {
+  type: RuleFaultType
+  /** 0 to 1 */
+  probability: number
+  phase: 'beforeHeaders' | 'afterHeaders'
+  bytes: number
+  delta: number
+  afterBytes?: number
+  durationMs: number
+}
+
+export interface RuleFaultActionConfig {
+  faults: RuleFaultDraft[]
+}
+
+/** Which calls share a sequence counter. */
+export type RuleSequenceScope = 'rule' | 'client' | 'header'
+
+export interface RuleSequenceActionConfig {
+  responses: RuleMockResponseActionConfig[]
+  scope: RuleSequenceScope
+  /** Header whose value keys the counter when `scope` is `header`. */
+  scopeHeader: string
+  onExhausted: 'stickAtLast' | 'loop'
+}
+
+/** One chunk or SSE event; `event`, `id` and `retryMs` only apply to `sse`. */
+export interface RuleStreamMockChunkDraft {
+  delayMs: number
+  data: string
+  event: string
+  id: string
+  retryMs?: number
+}
+
+export interface RuleStreamMockActionConfig {
+  format: 'sse' | 'raw'
+  statusCode?: number
+  headers: RuleHeaderPair[]
+  chunks: RuleStreamMockChunkDraft[]
+  /** `0` repeats until the client disconnects. */
+  repeat: number
+  /** Cut the body off after this many chunks. */
+  failAfter?: number
+}
+
+export type RuleUpstreamProxyProtocol = 'direct' | 'http' | 'https' | 'socks5'
+
+export interface RuleUpstreamProxyActionConfig {
+  protocol: RuleUpstreamProxyProtocol
+  /** `host:port` of the proxy; unused for `direct`. */
+  address: string
+  username: string
+  password: string
+}
+
+export interface RuleMirrorActionConfig {
+  /** Base URLs; the request path and query are appended. */
+  targets: string[]
+  /** Response headers left out of the diff. */
+  ignoreHeaders: string[]
+  timeoutMs?: number
+}
+
+export interface RuleCorsActionConfig {
+  /** Empty or `*` allows any origin; `https://*.example.com` matches subdomains. */
+  allowedOrigins: string[]
+  echoOrigin: boolean
+  allowedMethods: string[]
+  /** Empty allows whatever the preflight asks for. */
+  allowedHeaders: string[]
+  exposeHeaders: string[]
+  allowCredentials: boolean
+  maxAgeSecs?: number
+}
+
+export interface RuleBreakpointActionConfig {
+  phase: 'request' | 'response' | 'both'
+  timeoutMs?: number
+}
+
+export type RuleWebSocketFrameActionType = 'rewrite' | 'drop' | 'delay'
+
+/** One frame rule; only the fields for its `action` are sent. */
+export interface RuleWebSocketFrameRuleDraft {
+  direction: 'both' | 'clientToServer' | 'serverToClient'
+  pattern: string
+  jsonPath: string
+  /** JSON a node selected by `jsonPath` must equal; empty to only require a match. */
+  jsonValue: string
+  action: RuleWebSocketFrameActionType
+  /** Replaces the whole frame text when set. */
+  replacement: string
+  transforms: RuleBodyTransformDraft[]
+  delayMs: number
+}
+
+export interface RuleWebSocketInterceptActionConfig {
+  rules: RuleWebSocketFrameRuleDraft[]
+}
+
+/** One scripted frame; `kind` picks whether `text` or `binaryBase64` is sent. */
+export interface RuleWebSocketMockFrameDraft {
+  kind: 'text' | 'binary'
+  /** In replies, `$1` / `${name}` expand to the pattern's groups. */
+  text: string
+  binaryBase64: string
+  delayMs?: number
+}
+
+export interface RuleWebSocketMockReplyDraft {
+  pattern: string
+  frames: RuleWebSocketMockFrameDraft[]
+}
+
+export interface RuleWebSocketMockPeriodicDraft {
+  intervalMs: number
+  frame: RuleWebSocketMockFrameDraft
+  /** Empty repeats until the connection closes. */
+  count?: number
+}
+
+export interface RuleWebSocketMockCloseDraft {
+  afterMs: number
+  code: number
+  reason: string
+}
+
+export interface RuleWebSocketMockActionConfig {
+  onConnect: RuleWebSocketMockFrameDraft[]
+  replies: RuleWebSocketMockReplyDraft[]
+  echo: boolean
+  periodic: RuleWebSocketMockPeriodicDraft[]
+  /** Absent leaves closing to the client. */
+  close?: RuleWebSocketMockCloseDraft
 }
 
 export interface RuleHtmlScriptInjectorActionConfig {
@@ -73,6 +315,22 @@ export interface RuleThrottleActionConfig {
   latencyMs?: number
 }
 
+export interface RuleScriptActionConfig {
+  script: string
+  maxOperations?: number
+  timeoutMs?: number
+  maxStringBytes?: number
+  maxCollectionSize?: number
+}
+
+export interface RuleWasmPluginActionConfig {
+  plugin: string
+  /** JSON text handed to the plugin; empty means no config. */
+  configJson: string
+  fuel?: number
+  maxMemoryBytes?: number
+}
+
 export interface RuleBlockActionDraft extends RuleActionBase {
   type: 'block'
   config: RuleBlockActionConfig
@@ -88,6 +346,36 @@ export interface RuleProxyForwardActionDraft extends RuleActionBase {
   config: RuleProxyForwardActionConfig
 }
 
+export interface RuleUpstreamProxyActionDraft extends RuleActionBase {
+  type: 'upstreamProxy'
+  config: RuleUpstreamProxyActionConfig
+}
+
+export interface RuleMirrorActionDraft extends RuleActionBase {
+  type: 'mirror'
+  config: RuleMirrorActionConfig
+}
+
+export interface RuleCorsActionDraft extends RuleActionBase {
+  type: 'cors'
+  config: RuleCorsActionConfig
+}
+
+export interface RuleBreakpointActionDraft extends RuleActionBase {
+  type: 'breakpoint'
+  config: RuleBreakpointActionConfig
+}
+
+export interface RuleWebSocketInterceptActionDraft extends RuleActionBase {
+  type: 'webSocketIntercept'
+  config: RuleWebSocketInterceptActionConfig
+}
+
+export interface RuleWebSocketMockActionDraft extends RuleActionBase {
+  type: 'webSocketMock'
+  config: RuleWebSocketMockActionConfig
+}
+
 export interface RuleModifyRequestActionDraft extends RuleActionBase {
   type: 'modifyRequest'
   config: RuleModifyRequestActionConfig
@@ -103,6 +391,36 @@ export interface RuleLocalFileActionDraft extends RuleActionBase {
   config: RuleLocalFileActionConfig
 }
 
+export interface RuleMockResponseActionDraft extends RuleActionBase {
+  type: 'mockResponse'
+  config: RuleMockResponseActionConfig
+}
+
+export interface RuleRecordActionDraft extends RuleActionBase {
+  type: 'record'
+  config: RuleRecordActionConfig
+}
+
+export interface RuleReplayActionDraft extends RuleActionBase {
+  type: 'replay'
+  config: RuleReplayActionConfig
+}
+
+export interface RuleFaultActionDraft extends RuleActionBase {
+  type: 'fault'
+  config: RuleFaultActionConfig
+}
+
+export interface RuleSequenceActionDraft extends RuleActionBase {
+  type: 'sequence'
+  config: RuleSequenceActionConfig
+}
+
+export interface RuleStreamMockActionDraft extends RuleActionBase {
+  type: 'streamMock'
+  config: RuleStreamMockActionConfig
+}
+
 export interface RuleHtmlScriptInjectorActionDraft extends RuleActionBase {
   type: 'htmlScriptInjector'
   config: RuleHtmlScriptInjectorActionConfig
@@ -113,15 +431,39 @@ export interface RuleThrottleActionDraft extends RuleActionBase {
   config: RuleThrottleActionConfig
 }
 
+export interface RuleScriptActionDraft extends RuleActionBase {
+  type: 'script'
+  config: RuleScriptActionConfig
+}
+
+export interface RuleWasmPluginActionDraft extends RuleActionBase {
+  type: 'wasmPlugin'
+  config: RuleWasmPluginActionConfig
+}
+
 export type RuleActionDraft =
   | RuleBlockActionDraft
   | RuleDelayActionDraft
   | RuleProxyForwardActionDraft
+  | RuleUpstreamProxyActionDraft
+  | RuleMirrorActionDraft
+  | RuleCorsActionDraft
+  | RuleBreakpointActionDraft
+  | RuleWebSocketInterceptActionDraft
+  | RuleWebSocketMockActionDraft
   | RuleModifyRequestActionDraft
   | RuleModifyResponseActionDraft
   | RuleLocalFileActionDraft
+  | RuleMockResponseActionDraft
+  | RuleRecordActionDraft
+  | RuleReplayActionDraft
+  | RuleFaultActionDraft
+  | RuleSequenceActionDraft
+  | RuleStreamMockActionDraft
   | RuleThrottleActionDraft
   | RuleHtmlScriptInjectorActionDraft
+  | RuleScriptActionDraft
+  | RuleWasmPluginActionDraft
 
 export interface RuleDraft {
   id: string
@@ -154,6 +496,47 @@ function createProxyForwardConfig(seed?: Partial<RuleProxyForwardActionConfig>):
     targetScheme: seed?.targetScheme ?? '',
     targetAuthority: seed?.targetAuthority ?? '',
     targetPath: seed?.targetPath ?? '',
+    pathRegex: seed?.pathRegex ?? '',
+    queryMode: seed?.queryMode ?? 'keep',
+    queryParams: seed?.queryParams ?? [],
+    preserveHost: seed?.preserveHost ?? false,
+    targets: seed?.targets ?? [],
+  }
+}
+
+function createUpstreamProxyConfig(seed?: Partial<RuleUpstreamProxyActionConfig>): RuleUpstreamProxyActionConfig {
+  return {
+    protocol: seed?.protocol ?? 'http',
+    address: seed?.address ?? '',
+    username: seed?.username ?? '',
+    password: seed?.password ?? '',
+  }
+}
+
+function createMirrorConfig(seed?: Partial<RuleMirrorActionConfig>): RuleMirrorActionConfig {
+  return {
+    targets: seed?.targets ?? [''],
+    ignoreHeaders: seed?.ignoreHeaders ?? [],
+    timeoutMs: seed?.timeoutMs,
+  }
+}
+
+function createCorsConfig(seed?: Partial<RuleCorsActionConfig>): RuleCorsActionConfig {
+  return {
+    allowedOrigins: seed?.allowedOrigins ?? [],
+    echoOrigin: seed?.echoOrigin ?? false,
+    allowedMethods: seed?.allowedMethods ?? ['GET', 'POST', 'PUT', 'PATCH', 'DELETE', 'HEAD', 'OPTIONS'],
+    allowedHeaders: seed?.allowedHeaders ?? [],
+    exposeHeaders: seed?.exposeHeaders ?? [],
+    allowCredentials: seed?.allowCredentials ?? false,
+    maxAgeSecs: seed?.maxAgeSecs,
+  }
+}
+
+function createBreakpointConfig(seed?: Partial<RuleBreakpointActionConfig>): RuleBreakpointActionConfig {
+  return {
+    phase: seed?.phase ?? 'request',
+    timeoutMs: seed?.timeoutMs,
   }
 }
 
@@ -163,6 +546,7 @@ function createModifyRequestConfig(seed?: Partial<RuleModifyRequestActionConfig>
     modifyMethod: seed?.modifyMethod ?? '',
     modifyUrl: seed?.modifyUrl ?? '',
     modifyBody: seed?.modifyBody ?? '',
+    bodyTransforms: seed?.bodyTransforms ?? [],
   }
 }
 
@@ -171,6 +555,7 @@ function createModifyResponseConfig(seed?: Partial<RuleModifyResponseActionConfi
     modifyHeaders: seed?.modifyHeaders ?? [],
     modifyStatusCode: seed?.modifyStatusCode,
     modifyBody: seed?.modifyBody ?? '',
+    bodyTransforms: seed?.bodyTransforms ?? [],
   }
 }
 
@@ -179,6 +564,131 @@ function createLocalFileConfig(seed?: Partial<RuleLocalFileActionConfig>): RuleL
     filePath: seed?.filePath ?? '',
     contentType: seed?.contentType ?? '',
     statusCode: seed?.statusCode,
+    mode: seed?.mode ?? 'file',
+    stripPrefix: seed?.stripPrefix ?? '',
+    spaFallback: seed?.spaFallback ?? false,
+  }
+}
+
+export function createMockResponseConfig(seed?: Partial<RuleMockResponseActionConfig>): RuleMockResponseActionConfig {
+  return {
+    statusCode: seed?.statusCode,
+    headers: seed?.headers ?? [],
+    bodySource: seed?.bodySource ?? 'inline',
+    body: seed?.body ?? '',
+    bodyFile: seed?.bodyFile ?? '',
+    latencyMs: seed?.latencyMs,
+  }
+}
+
+function createRecordConfig(seed?: Partial<RuleRecordActionConfig>): RuleRecordActionConfig {
+  return {
+    cassette: seed?.cassette ?? '',
+    redactHeaders: seed?.redactHeaders,
+  }
+}
+
+function createReplayConfig(seed?: Partial<RuleReplayActionConfig>): RuleReplayActionConfig {
+  return {
+    cassette: seed?.cassette ?? '',
+    matchOn: seed?.matchOn ?? ['method', 'host', 'path', 'query'],
+    matchHeaders: seed?.matchHeaders ?? [],
+    fallback: seed?.fallback ?? 'passthrough',
+  }
+}
+
+export function createFaultDraft(seed?: Partial<RuleFaultDraft>): RuleFaultDraft {
+  return {
+    type: seed?.type ?? 'reset',
+    probability: seed?.probability ?? 1,
+    phase: seed?.phase ?? 'beforeHeaders',
+    bytes: seed?.bytes ?? 1024,
+    delta: seed?.delta ?? 100,
+    afterBytes: seed?.afterBytes,
+    durationMs: seed?.durationMs ?? 5000,
+  }
+}
+
+function createFaultConfig(seed?: Partial<RuleFaultActionConfig>): RuleFaultActionConfig {
+  return {
+    faults: (seed?.faults ?? [{}]).map(fault => createFaultDraft(fault)),
+  }
+}
+
+export function createWebSocketFrameRuleDraft(seed?: Partial<RuleWebSocketFrameRuleDraft>): RuleWebSocketFrameRuleDraft {
+  return {
+    direction: seed?.direction ?? 'both',
+    pattern: seed?.pattern ?? '',
+    jsonPath: seed?.jsonPath ?? '',
+    jsonValue: seed?.jsonValue ?? '',
+    action: seed?.action ?? 'rewrite',
+    replacement: seed?.replacement ?? '',
+    transforms: seed?.transforms ?? [],
+    delayMs: seed?.delayMs ?? 1000,
+  }
+}
+
+function createWebSocketInterceptConfig(seed?: Partial<RuleWebSocketInterceptActionConfig>): RuleWebSocketInterceptActionConfig {
+  return {
+    rules: (seed?.rules ?? [{}]).map(rule => createWebSocketFrameRuleDraft(rule)),
+  }
+}
+
+export function createWebSocketMockFrameDraft(seed?: Partial<RuleWebSocketMockFrameDraft>): RuleWebSocketMockFrameDraft {
+  return {
+    kind: seed?.kind ?? 'text',
+    text: seed?.text ?? '',
+    binaryBase64: seed?.binaryBase64 ?? '',
+    delayMs: seed?.delayMs,
+  }
+}
+
+function createWebSocketMockConfig(seed?: Partial<RuleWebSocketMockActionConfig>): RuleWebSocketMockActionConfig {
+  return {
+    onConnect: (seed?.onConnect ?? [{ text: '{"type":"welcome"}' }]).map(frame => createWebSocketMockFrameDraft(frame)),
+    replies: (seed?.replies ?? []).map(reply => ({
+      pattern: reply.pattern ?? '',
+      frames: (reply.frames ?? []).map(frame => createWebSocketMockFrameDraft(frame)),
+    })),
+    echo: seed?.echo ?? false,
+    periodic: (seed?.periodic ?? []).map(periodic => ({
+      intervalMs: periodic.intervalMs ?? 1000,
+      frame: createWebSocketMockFrameDraft(periodic.frame),
+      count: periodic.count,
+    })),
+    close: seed?.close ? { ...seed.close } : undefined,
+  }
+}
+
+function createSequenceConfig(seed?: Partial<RuleSequenceActionConfig>): RuleSequenceActionConfig {
+  return {
+    responses: (seed?.responses ?? [{}]).map(response => createMockResponseConfig(response)),
+    scope: seed?.scope ?? 'rule',
+    scopeHeader: seed?.scopeHeader ?? '',
+    onExhausted: seed?.onExhausted ?? 'stickAtLast',
+  }
+}
+
+export function createStreamMockChunkDraft(seed?: Partial<RuleStreamMockChunkDraft>): RuleStreamMockChunkDraft {
+  return {
+    delayMs: seed?.delayMs ?? 100,
+    data: seed?.data ?? '',
+    event: seed?.event ?? '',
+    id: seed?.id ?? '',
+    retryMs: seed?.retryMs,
+  }
+}
+
+function createStreamMockConfig(seed?: Partial<RuleStreamMockActionConfig>): RuleStreamMockActionConfig {
+  const chunks: Partial<RuleStreamMockChunkDraft>[] = seed?.chunks
+    ?? [{ delayMs: 0, data: '{"token":"Hello"}' }, { data: '[DONE]' }]
+  return {
+    format: seed?.format ?? 'sse',
+    statusCode: seed?.statusCode,
+    headers: seed?.headers ?? [],
+    chunks: chunks.map(chunk => createStreamMockChunkDraft(chunk)),
+    repeat: seed?.repeat ?? 1,
+    failAfter: seed?.failAfter,
   }
 }
 
@@ -198,6 +708,35 @@ function createThrottleConfig(seed?: Partial<RuleThrottleActionConfig>): RuleThr
   }
 }
 
+const DEFAULT_SCRIPT = `fn onRequest(req) {
+  req.headers["x-lynx-script"] = "1";
+  req
+}
+
+fn onResponse(req, res) {
+  print(req.method + " " + req.url + " -> " + res.status);
+}
+`
+
+function createScriptConfig(seed?: Partial<RuleScriptActionConfig>): RuleScriptActionConfig {
+  return {
+    script: seed?.script ?? DEFAULT_SCRIPT,
+    maxOperations: seed?.maxOperations,
+    timeoutMs: seed?.timeoutMs,
+    maxStringBytes: seed?.maxStringBytes,
+    maxCollectionSize: seed?.maxCollectionSize,
+  }
+}
+
+function createWasmPluginConfig(seed?: Partial<RuleWasmPluginActionConfig>): RuleWasmPluginActionConfig {
+  return {
+    plugin: seed?.plugin ?? '',
+    configJson: seed?.configJson ?? '',
+    fuel: seed?.fuel,
+    maxMemoryBytes: seed?.maxMemoryBytes,
+  }
+}
+
 export function createAction(seed?: Partial<RuleActionDraft>): RuleActionDraft {
   const id = seed?.id ?? `act-${Math.random().toString(36).slice(2, 9)}`
   const type = seed?.type ?? 'modifyRequest'
@@ -238,6 +777,78 @@ export function createAction(seed?: Partial<RuleActionDraft>): RuleActionDraft {
     }
   }
 
+  if (type === 'upstreamProxy') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createUpstreamProxyConfig(
+        (seed as Partial<RuleUpstreamProxyActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'mirror') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createMirrorConfig(
+        (seed as Partial<RuleMirrorActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'cors') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createCorsConfig(
+        (seed as Partial<RuleCorsActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'webSocketIntercept') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createWebSocketInterceptConfig(
+        (seed as Partial<RuleWebSocketInterceptActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'webSocketMock') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createWebSocketMockConfig(
+        (seed as Partial<RuleWebSocketMockActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'breakpoint') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createBreakpointConfig(
+        (seed as Partial<RuleBreakpointActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
   if (type === 'modifyRequest') {
     return {
       id,
@@ -274,6 +885,78 @@ export function createAction(seed?: Partial<RuleActionDraft>): RuleActionDraft {
     }
   }
 
+  if (type === 'mockResponse') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createMockResponseConfig(
+        (seed as Partial<RuleMockResponseActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'record') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createRecordConfig(
+        (seed as Partial<RuleRecordActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'replay') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createReplayConfig(
+        (seed as Partial<RuleReplayActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'fault') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createFaultConfig(
+        (seed as Partial<RuleFaultActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'sequence') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createSequenceConfig(
+        (seed as Partial<RuleSequenceActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'streamMock') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createStreamMockConfig(
+        (seed as Partial<RuleStreamMockActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
   if (type === 'htmlScriptInjector') {
     return {
       id,
@@ -298,6 +981,30 @@ export function createAction(seed?: Partial<RuleActionDraft>): RuleActionDraft {
     }
   }
 
+  if (type === 'script') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createScriptConfig(
+        (seed as Partial<RuleScriptActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
+  if (type === 'wasmPlugin') {
+    return {
+      id,
+      type,
+      enabled: seed?.enabled ?? true,
+      order: seed?.order ?? 1,
+      config: createWasmPluginConfig(
+        (seed as Partial<RuleWasmPluginActionDraft> | undefined)?.config,
+      ),
+    }
+  }
+
   return {
     id,
     type: 'modifyRequest',
@@ -327,17 +1034,63 @@ export function getActionSummary(action: RuleActionDraft): string {
   }
 
   if (action.type === 'proxyForward') {
+    if (action.config.targets.length > 0) {
+      return `${action.config.targets.length} 个加权目标`
+    }
     const scheme = proxyForwardSchemeSummaryLabel(action.config.targetScheme)
     const authority = action.config.targetAuthority.trim() || '<authority>'
     const path = action.config.targetPath.trim() || ''
     return `${scheme}://${authority}${path}`
   }
 
+  if (action.type === 'upstreamProxy') {
+    if (action.config.protocol === 'direct') return '直连（不经过上游代理）'
+    const auth = action.config.username ? ` (${action.config.username})` : ''
+    return `经 ${action.config.protocol}://${action.config.address.trim() || '<host:port>'}${auth}`
+  }
+
+  if (action.type === 'mirror') {
+    const targets = action.config.targets.map(target => target.trim()).filter(Boolean)
+    if (targets.length === 0) return '镜像到 <未设置目标>'
+    return targets.length === 1 ? `镜像到 ${targets[0]}` : `镜像到 ${targets[0]} 等 ${targets.length} 个目标`
+  }
+
+  if (action.type === 'cors') {
+    const origins = action.config.allowedOrigins.map(origin => origin.trim()).filter(Boolean)
+    const label = origins.length === 0 || origins.includes('*')
+      ? '任意 Origin'
+      : origins.length === 1 ? origins[0] : `${origins[0]} 等 ${origins.length} 个 Origin`
+    return `CORS 放行 ${label}${action.config.allowCredentials ? '（含凭据）' : ''}`
+  }
+
+  if (action.type === 'webSocketIntercept') {
+    const labels: Record<RuleWebSocketFrameActionType, string> = { rewrite: '改写', drop: '丢弃', delay: '延迟' }
+    const actions = [...new Set(action.config.rules.map(rule => labels[rule.action]))]
+    return actions.length > 0 ? `WebSocket 帧${actions.join('/')}（${action.config.rules.length} 条规则）` : '至少需要一条帧规则'
+  }
+
+  if (action.type === 'webSocketMock') {
+    const parts = [
+      action.config.onConnect.length > 0 ? `连接后发送 ${action.config.onConnect.length} 帧` : '',
+      action.config.replies.length > 0 ? `${action.config.replies.length} 条应答` : '',
+      action.config.echo ? '回显' : '',
+      action.config.periodic.length > 0 ? `${action.config.periodic.length} 个定时帧` : '',
+      action.config.close ? `${action.config.close.afterMs} ms 后以 ${action.config.close.code} 关闭` : '',
+    ].filter(Boolean)
+    return parts.length > 0 ? `模拟 WebSocket：${parts.join('，')}` : '模拟 WebSocket（空脚本）'
+  }
+
+  if (action.type === 'breakpoint') {
+    const phase = { request: '请求', response: '响应', both: '请求和响应' }[action.config.phase]
+    return `在${phase}处断点`
+  }
+
   if (action.type === 'modifyRequest') {
     const changes = [
       action.config.modifyMethod ? '方法' : '',
       action.config.modifyUrl ? 'URL' : '',
       action.config.modifyBody ? 'Body' : '',
+      action.config.bodyTransforms.length > 0 ? `Body 变换:${action.config.bodyTransforms.length}` : '',
       action.config.modifyHeaders.length > 0 ? `Headers:${action.config.modifyHeaders.length}` : '',
     ].filter(Boolean)
     return changes.length > 0 ? `修改请求 ${changes.join(', ')}` : '修改请求（未设置字段）'
@@ -347,13 +1100,61 @@ export function getActionSummary(action: RuleActionDraft): string {
     const changes = [
       action.config.modifyStatusCode ? `状态码:${action.config.modifyStatusCode}` : '',
       action.config.modifyBody ? 'Body' : '',
+      action.config.bodyTransforms.length > 0 ? `Body 变换:${action.config.bodyTransforms.length}` : '',
       action.config.modifyHeaders.length > 0 ? `Headers:${action.config.modifyHeaders.length}` : '',
     ].filter(Boolean)
     return changes.length > 0 ? `修改响应 ${changes.join(', ')}` : '修改响应（未设置字段）'
   }
 
   if (action.type === 'localFile') {
-    return action.config.filePath ? `提供本地文件 ${action.config.filePath}` : '本地文件路径必填'
+    if (!action.config.filePath) return '本地文件路径必填'
+    return action.config.mode === 'directory'
+      ? `映射本地目录 ${action.config.filePath}`
+      : `提供本地文件 ${action.config.filePath}`
+  }
+
+  if (action.type === 'mockResponse') {
+    const source = action.config.bodySource === 'file'
+      ? (action.config.bodyFile ? `模板 ${action.config.bodyFile}` : '模板文件必填')
+      : '内联模板'
+    const latency = action.config.latencyMs ? ` 延迟 ${action.config.latencyMs}ms` : ''
+    return `Mock ${action.config.statusCode ?? 200} ${source}${latency}`
+  }
+
+  if (action.type === 'record') {
+    return `录制到 ${action.config.cassette || 'cassette.jsonl'}`
+  }
+
+  if (action.type === 'replay') {
+    const fallback = { passthrough: '未命中继续请求', notFound: '未命中返回 404', error: '未命中报错' }[action.config.fallback]
+    return `从 ${action.config.cassette || 'cassette.jsonl'} 回放（${fallback}）`
+  }
+
+  if (action.type === 'fault') {
+    const labels: Record<RuleFaultType, string> = {
+      reset: '断开连接',
+      truncate: '截断 Body',
+      wrongContentLength: '错误 Content-Length',
+      stall: 'Body 中途停顿',
+      malformedChunked: '损坏 chunked 编码',
+      corruptGzip: '损坏 gzip',
+    }
+    const faults = action.config.faults.map(fault => `${labels[fault.type]} ${Math.round(fault.probability * 100)}%`)
+    return faults.length > 0 ? `故障注入 ${faults.join(', ')}` : '至少需要一个故障'
+  }
+
+  if (action.type === 'sequence') {
+    const statuses = action.config.responses.map(response => response.statusCode ?? 200)
+    const scope = { rule: '', client: '（按客户端）', header: `（按 ${action.config.scopeHeader || 'Header'}）` }[action.config.scope]
+    const tail = action.config.onExhausted === 'loop' ? ' 循环' : ''
+    return statuses.length > 0 ? `依次响应 ${statuses.join(' → ')}${tail}${scope}` : '至少需要一个响应'
+  }
+
+  if (action.type === 'streamMock') {
+    const unit = action.config.format === 'sse' ? '个 SSE 事件' : '个分块'
+    const repeat = action.config.repeat === 0 ? '，循环' : action.config.repeat > 1 ? `，播放 ${action.config.repeat} 次` : ''
+    const fail = action.config.failAfter !== undefined ? `，第 ${action.config.failAfter} 块后中断` : ''
+    return `流式响应 ${action.config.chunks.length} ${unit}${repeat}${fail}`
   }
 
   if (action.type === 'htmlScriptInjector') {
@@ -374,9 +1175,90 @@ export function getActionSummary(action: RuleActionDraft): string {
     return `自定义 ${download}↓/${upload}↑ ${latency}ms`
   }
 
+  if (action.type === 'script') {
+    const hooks = ['onRequest', 'onResponse'].filter(hook =>
+      new RegExp(`\\bfn\\s+${hook}\\s*\\(`).test(action.config.script),
+    )
+    return hooks.length > 0 ? `脚本 ${hooks.join(' + ')}` : '脚本未定义 onRequest / onResponse'
+  }
+
+  if (action.type === 'wasmPlugin') {
+    return action.config.plugin ? `WASM 插件 ${action.config.plugin}` : '插件路径必填'
+  }
+
   return '暂无摘要'
 }
 
+function getHeaderOpErrors(ops: RuleHeaderOpDraft[], side: string): string[] {
+  const errors: string[] = []
+  ops.forEach((header, idx) => {
+    const label = `${side} Header #${idx + 1}`
+    if (!header.key.trim()) errors.push(`${label} 的 key 必填`)
+    if (header.op === 'rename' && !header.to.trim()) errors.push(`${label} 的新名称必填`)
+    if (header.op === 'regexRewrite') {
+      try {
+        new RegExp(header.pattern)
+      }
+      catch {
+        errors.push(`${label} 的正则无效`)
+      }
+    }
+  })
+  return errors
+}
+
+function getBodyTransformErrors(transforms: RuleBodyTransformDraft[]): string[] {
+  const errors: string[] = []
+  transforms.forEach((transform, idx) => {
+    const label = `Body 变换 #${idx + 1}`
+    if (transform.type === 'regexReplace') {
+      try {
+        new RegExp(transform.pattern)
+      }
+      catch {
+        errors.push(`${label} 的正则无效`)
+      }
+      if (!transform.pattern) errors.push(`${label} 的正则必填`)
+      return
+    }
+    if (transform.type === 'jsonPathSet' || transform.type === 'jsonPathDelete') {
+      if (!transform.path.trim().startsWith('$')) errors.push(`${label} 的 JSONPath 必须以 $ 开头`)
+      if (transform.type === 'jsonPathDelete') return
+    }
+    let value: unknown
+    try {
+      value = JSON.parse(transform.valueJson)
+    }
+    catch {
+      errors.push(`${label} 的值必须是合法 JSON`)
+      return
+    }
+    if (transform.type === 'jsonPatch' && !Array.isArray(value)) {
+      errors.push(`${label} 必须是 JSON Patch 操作数组`)
+    }
+  })
+  return errors
+}
+
+function getMockResponseErrors(config: RuleMockResponseActionConfig): string[] {
+  const errors: string[] = []
+  const status = config.statusCode
+  if (status !== undefined && (!Number.isInteger(status) || status < 100 || status > 599)) {
+    errors.push('状态码必须在 100-599 之间')
+  }
+  const latency = config.latencyMs
+  if (latency !== undefined && (!Number.isInteger(latency) || latency < 0)) {
+    errors.push('延迟必须是非负整数')
+  }
+  if (config.bodySource === 'file' && !config.bodyFile.trim()) {
+    errors.push('模板文件路径必填')
+  }
+  config.headers.forEach((header, idx) => {
+    if (!header.key.trim()) errors.push(`Header #${idx + 1} 的 key 必填`)
+  })
+  return errors
+}
+
 export function getActionValidationErrors(action: RuleActionDraft): string[] {
   if (action.type === 'block') {
     if (!Number.isFinite(action.config.statusCode) || action.config.statusCode < 100 || action.config.statusCode > 599) {
@@ -397,7 +1279,166 @@ export function getActionValidationErrors(action: RuleActionDraft): string[] {
   }
 
   if (action.type === 'proxyForward') {
-    return action.config.targetAuthority.trim() ? [] : ['目标 Authority 必填']
+    const errors: string[] = []
+    const { targets } = action.config
+    if (targets.length === 0 && !action.config.targetAuthority.trim()) {
+      errors.push('目标 Authority 必填')
+    }
+    if (targets.some(target => !target.targetAuthority.trim() && !action.config.targetAuthority.trim())) {
+      errors.push('加权目标的 Authority 必填')
+    }
+    if (targets.some(target => !Number.isInteger(target.weight) || target.weight < 0)) {
+      errors.push('权重必须是非负整数')
+    }
+    if (targets.length > 0 && targets.every(target => target.weight === 0)) {
+      errors.push('至少一个目标的权重需大于 0')
+    }
+    if (action.config.pathRegex) {
+      try {
+        new RegExp(action.config.pathRegex)
+      } catch {
+        errors.push('路径正则无效')
+      }
+    }
+    if (action.config.queryParams.some(param => !param.key.trim())) {
+      errors.push('查询参数名不能为空')
+    }
+    return errors
+  }
+
+  if (action.type === 'upstreamProxy') {
+    const errors: string[] = []
+    if (action.config.protocol === 'direct') return errors
+    const address = action.config.address.trim()
+    if (!address) errors.push('代理地址必填')
+    else if (!/^(\[[0-9a-fA-F:.]+\]|[^\s:/[\]]+):\d{1,5}$/.test(address)) errors.push('代理地址必须是 host:port')
+    if (action.config.protocol === 'socks5'
+      && (new TextEncoder().encode(action.config.username).length > 255
+        || new TextEncoder().encode(action.config.password).length > 255)) {
+      errors.push('SOCKS5 用户名和密码不能超过 255 字节')
+    }
+    return errors
+  }
+
+  if (action.type === 'cors') {
+    const errors: string[] = []
+    action.config.allowedOrigins.map(origin => origin.trim()).filter(Boolean).forEach((origin, idx) => {
+      if (origin !== '*' && origin !== 'null' && !/^[a-z][a-z0-9+.-]*:\/\/(\*\.)?[^/?#\s]+$/i.test(origin)) {
+        errors.push(`Origin #${idx + 1} 必须形如 https://example.com`)
+      }
+    })
+    if (action.config.allowedMethods.length === 0) errors.push('至少需要一个允许的方法')
+    const token = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/
+    if (action.config.allowedMethods.some(method => !token.test(method))) errors.push('方法名称无效')
+    if ([...action.config.allowedHeaders, ...action.config.exposeHeaders].some(name => !token.test(name))) {
+      errors.push('Header 名称无效')
+    }
+    const maxAge = action.config.maxAgeSecs
+    if (maxAge !== undefined && (!Number.isInteger(maxAge) || maxAge < 0)) errors.push('预检缓存时间必须是非负整数')
+    return errors
+  }
+
+  if (action.type === 'mirror') {
+    const errors: string[] = []
+    const targets = action.config.targets.map(target => target.trim()).filter(Boolean)
+    if (targets.length === 0) errors.push('至少需要一个镜像目标')
+    targets.forEach((target, idx) => {
+      if (!/^https?:\/\/[^/?#\s]+[^?#\s]*$/i.test(target)) {
+        errors.push(`目标 #${idx + 1} 必须是不带查询参数的 http(s) URL`)
+      }
+    })
+    if (typeof action.config.timeoutMs === 'number' && action.config.timeoutMs <= 0) {
+      errors.push('超时必须大于 0')
+    }
+    return errors
+  }
+
+  if (action.type === 'webSocketIntercept') {
+    const errors: string[] = []
+    if (action.config.rules.length === 0) errors.push('至少需要一条帧规则')
+    action.config.rules.forEach((rule, idx) => {
+      const label = `帧规则 #${idx + 1}`
+      if (rule.pattern) {
+        try {
+          new RegExp(rule.pattern)
+        }
+        catch {
+          errors.push(`${label} 的正则无效`)
+        }
+      }
+      if (rule.jsonPath && !rule.jsonPath.trim().startsWith('$')) {
+        errors.push(`${label} 的 JSONPath 必须以 $ 开头`)
+      }
+      if (rule.jsonPath && rule.jsonValue) {
+        try {
+          JSON.parse(rule.jsonValue)
+        }
+        catch {
+          errors.push(`${label} 的匹配值必须是合法 JSON`)
+        }
+      }
+      if (rule.action === 'rewrite') {
+        if (!rule.replacement && rule.transforms.length === 0) {
+          errors.push(`${label} 需要替换文本或至少一个变换`)
+        }
+        errors.push(...getBodyTransformErrors(rule.transforms).map(error => `${label}：${error}`))
+      }
+      if (rule.action === 'delay' && (!Number.isInteger(rule.delayMs) || rule.delayMs < 0 || rule.delayMs > 300_000)) {
+        errors.push(`${label} 的延迟必须在 0 到 300000 ms 之间`)
+      }
+    })
+    return errors
+  }
+
+  if (action.type === 'webSocketMock') {
+    const errors: string[] = []
+    const frameErrors = (frame: RuleWebSocketMockFrameDraft, label: string) => {
+      if (frame.kind === 'binary' && !/^[A-Za-z0-9+/]*={0,2}$/.test(frame.binaryBase64.trim())) {
+        errors.push(`${label} 的二进制内容必须是 Base64`)
+      }
+      if (typeof frame.delayMs === 'number' && (!Number.isInteger(frame.delayMs) || frame.delayMs < 0)) {
+        errors.push(`${label} 的延迟必须是非负整数`)
+      }
+    }
+    action.config.onConnect.forEach((frame, idx) => frameErrors(frame, `连接帧 #${idx + 1}`))
+    action.config.replies.forEach((reply, idx) => {
+      const label = `应答 #${idx + 1}`
+      try {
+        new RegExp(reply.pattern)
+      }
+      catch {
+        errors.push(`${label} 的正则无效`)
+      }
+      reply.frames.forEach((frame, frameIdx) => frameErrors(frame, `${label} 帧 #${frameIdx + 1}`))
+    })
+    action.config.periodic.forEach((periodic, idx) => {
+      const label = `定时帧 #${idx + 1}`
+      if (!Number.isInteger(periodic.intervalMs) || periodic.intervalMs < 10) {
+        errors.push(`${label} 的间隔至少 10 ms`)
+      }
+      if (typeof periodic.count === 'number' && (!Number.isInteger(periodic.count) || periodic.count < 1)) {
+        errors.push(`${label} 的次数必须是正整数`)
+      }
+      frameErrors(periodic.frame, label)
+    })
+    const close = action.config.close
+    if (close) {
+      if (!Number.isInteger(close.afterMs) || close.afterMs < 0) errors.push('关闭时间必须是非负整数')
+      const code = close.code
+      const sendable = (code >= 1000 && code <= 1003) || (code >= 1007 && code <= 1014) || (code >= 3000 && code <= 4999)
+      if (!Number.isInteger(code) || !sendable) errors.push(`关闭码 ${code} 不能发送`)
+      if (new TextEncoder().encode(close.reason).length > 123) errors.push('关闭原因不能超过 123 字节')
+    }
+    return errors
+  }
+
+  if (action.type === 'breakpoint') {
+    const errors: string[] = []
+    const timeoutMs = action.config.timeoutMs
+    if (typeof timeoutMs === 'number' && (timeoutMs <= 0 || timeoutMs > 3_600_000)) {
+      errors.push('超时必须在 1 到 3600000 ms 之间')
+    }
+    return errors
   }
 
   if (action.type === 'modifyRequest') {
@@ -405,9 +1446,8 @@ export function getActionValidationErrors(action: RuleActionDraft): string[] {
     if (action.config.modifyUrl && !/^https?:\/\//i.test(action.config.modifyUrl)) {
       errors.push('修改 URL 必须以 http:// 或 https:// 开头')
     }
-    action.config.modifyHeaders.forEach((header, idx) => {
-      if (!header.key.trim()) errors.push(`请求 Header #${idx + 1} 的 key 必填`)
-    })
+    errors.push(...getHeaderOpErrors(action.config.modifyHeaders, '请求'))
+    errors.push(...getBodyTransformErrors(action.config.bodyTransforms))
     return errors
   }
 
@@ -416,14 +1456,96 @@ export function getActionValidationErrors(action: RuleActionDraft): string[] {
     if (typeof action.config.modifyStatusCode === 'number' && (action.config.modifyStatusCode < 100 || action.config.modifyStatusCode > 599)) {
       errors.push('响应状态码必须在 100-599 之间')
     }
-    action.config.modifyHeaders.forEach((header, idx) => {
-      if (!header.key.trim()) errors.push(`响应 Header #${idx + 1} 的 key 必填`)
-    })
+    errors.push(...getHeaderOpErrors(action.config.modifyHeaders, '响应'))
+    errors.push(...getBodyTransformErrors(action.config.bodyTransforms))
     return errors
   }
 
   if (action.type === 'localFile') {
-    return action.config.filePath.trim() ? [] : ['文件路径必填']
+    const errors: string[] = []
+    if (!action.config.filePath.trim()) errors.push('文件路径必填')
+    if (action.config.mode === 'directory' && action.config.stripPrefix && !action.config.stripPrefix.startsWith('/')) {
+      errors.push('去除前缀必须以 / 开头')
+    }
+    return errors
+  }
+
+  if (action.type === 'mockResponse') {
+    return getMockResponseErrors(action.config)
+  }
+
+  if (action.type === 'sequence') {
+    const errors: string[] = []
+    if (action.config.responses.length === 0) errors.push('至少需要一个响应')
+    if (action.config.scope === 'header' && !/^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/.test(action.config.scopeHeader)) {
+      errors.push('计数 Header 名称无效')
+    }
+    action.config.responses.forEach((response, idx) => {
+      errors.push(...getMockResponseErrors(response).map(error => `响应 #${idx + 1}: ${error}`))
+    })
+    return errors
+  }
+
+  if (action.type === 'streamMock') {
+    const errors: string[] = []
+    const status = action.config.statusCode
+    if (status !== undefined && (!Number.isInteger(status) || status < 100 || status > 599)) {
+      errors.push('状态码必须在 100-599 之间')
+    }
+    action.config.headers.forEach((header, idx) => {
+      if (!header.key.trim()) errors.push(`Header #${idx + 1} 的 key 必填`)
+    })
+    if (action.config.chunks.length === 0) errors.push('至少需要一个分块')
+    if (!Number.isInteger(action.config.repeat) || action.config.repeat < 0) errors.push('播放次数必须是非负整数')
+    if (action.config.repeat === 0 && action.config.chunks.every(chunk => chunk.delayMs === 0)) {
+      errors.push('无限循环时至少一个分块需要延迟')
+    }
+    const failAfter = action.config.failAfter
+    if (failAfter !== undefined && (!Number.isInteger(failAfter) || failAfter < 0)) {
+      errors.push('中断位置必须是非负整数')
+    }
+    action.config.chunks.forEach((chunk, idx) => {
+      const label = `分块 #${idx + 1}`
+      if (!Number.isInteger(chunk.delayMs) || chunk.delayMs < 0 || chunk.delayMs > 300_000) {
+        errors.push(`${label} 的延迟必须在 0 到 300000 ms 之间`)
+      }
+      if (action.config.format === 'sse' && /[\r\n]/.test(chunk.event + chunk.id)) {
+        errors.push(`${label} 的 event 和 id 不能换行`)
+      }
+    })
+    return errors
+  }
+
+  if (action.type === 'replay') {
+    return action.config.matchHeaders.some(name => !/^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/.test(name))
+      ? ['匹配 Header 名称无效']
+      : []
+  }
+
+  if (action.type === 'fault') {
+    const errors: string[] = []
+    if (action.config.faults.length === 0) errors.push('至少需要一个故障')
+    action.config.faults.forEach((fault, idx) => {
+      const label = `故障 #${idx + 1}`
+      if (!Number.isFinite(fault.probability) || fault.probability < 0 || fault.probability > 1) {
+        errors.push(`${label} 的概率必须在 0-1 之间`)
+      }
+      if (fault.type === 'truncate' && (!Number.isInteger(fault.bytes) || fault.bytes < 0)) {
+        errors.push(`${label} 的截断字节数必须是非负整数`)
+      }
+      if (fault.type === 'wrongContentLength' && !Number.isInteger(fault.delta)) {
+        errors.push(`${label} 的长度偏差必须是整数`)
+      }
+      if (fault.type === 'stall') {
+        if (!Number.isInteger(fault.durationMs) || fault.durationMs <= 0) {
+          errors.push(`${label} 的停顿时长必须是正整数`)
+        }
+        if (fault.afterBytes !== undefined && (!Number.isInteger(fault.afterBytes) || fault.afterBytes < 0)) {
+          errors.push(`${label} 的停顿位置必须是非负整数`)
+        }
+      }
+    })
+    return errors
   }
 
   if (action.type === 'htmlScriptInjector') {
@@ -449,6 +1571,50 @@ export function getActionValidationErrors(action: RuleActionDraft): string[] {
     return errors
   }
 
+  if (action.type === 'script') {
+    const errors: string[] = []
+    if (!action.config.script.trim()) errors.push('脚本内容必填')
+    const limits: Array<{ key: keyof RuleScriptActionConfig, label: string }> = [
+      { key: 'maxOperations', label: '最大操作数' },
+      { key: 'timeoutMs', label: '超时 ms' },
+      { key: 'maxStringBytes', label: '字符串上限' },
+      { key: 'maxCollectionSize', label: '集合上限' },
+    ]
+    for (const limit of limits) {
+      const value = action.config[limit.key] as number | undefined
+      if (value === undefined) continue
+      if (!Number.isInteger(value) || value <= 0) {
+        errors.push(`${limit.label} 必须是正整数`)
+      }
+    }
+    return errors
+  }
+
+  if (action.type === 'wasmPlugin') {
+    const errors: string[] = []
+    if (!action.config.plugin.trim()) errors.push('插件路径必填')
+    if (action.config.configJson.trim()) {
+      try {
+        JSON.parse(action.config.configJson)
+      }
+      catch {
+        errors.push('插件配置必须是合法 JSON')
+      }
+    }
+    const limits: Array<{ key: 'fuel' | 'maxMemoryBytes', label: string }> = [
+      { key: 'fuel', label: 'Fuel' },
+      { key: 'maxMemoryBytes', label: '内存上限' },
+    ]
+    for (const limit of limits) {
+      const value = action.config[limit.key]
+      if (value === undefined) continue
+      if (!Number.isInteger(value) || value <= 0) {
+        errors.push(`${limit.label} 必须是正整数`)
+      }
+    }
+    return errors
+  }
+
   return []
 }
 
@@ -3,4 +3,5 @@ export { default as NetworkPreferencesPanel } from './NetworkPreferencesPanel.vu
 export { default as ServerSettingsPanel } from './ServerSettingsPanel.vue'
 export { default as CertificateDownloadCard } from './CertificateDownloadCard.vue'
 export { default as DomainFilterList } from './DomainFilterList.vue'
+export { default as DnsOverrideList } from './DnsOverrideList.vue'
 export * from './types'
@@ -1,5 +1,5 @@
 import type { RequestViewMode } from '@/components/ui/network-panels'
-import type { CaptureFilter, GeneralSetting } from '@/lib/http/settings-types'
+import type { CaptureFilter, DnsConfig, GeneralSetting } from '@/lib/http/settings-types'
 import { DEFAULT_CAPTURE_FILTER } from '@/lib/http/settings-types'
 import {
   DEFAULT_SPLIT_RATIO,
@@ -25,6 +25,7 @@ export const DEFAULT_NETWORK_PREFERENCES: NetworkPreferencesState = {
 export interface SettingsPanelPreview {
   general: GeneralSetting
   capture: CaptureFilter
+  dns: DnsConfig
   certPath: string
   baseAddresses: string[]
 }
@@ -40,6 +41,13 @@ export const DEFAULT_SETTINGS_PREVIEW: SettingsPanelPreview = {
       { domain: '*.example.com', port: 443, enabled: true },
     ],
   },
+  dns: {
+    overrides: [
+      { host: 'api.example.com', ips: ['10.0.0.12'], enabled: true },
+      { host: '*.staging.example.com', ips: ['10.0.0.20', '10.0.0.21'], enabled: false },
+    ],
+    resolver: { type: 'server', address: '1.1.1.1' },
+  },
   certPath: '/var/lynx/ca/root_ca.pem',
   baseAddresses: ['127.0.0.1:8080'],
 }
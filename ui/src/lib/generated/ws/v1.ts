@@ -28,6 +28,8 @@ export const WsOp = {
   SettingsGeneralSet: 'settings.general.set',
   SettingsCaptureFilterGet: 'settings.captureFilter.get',
   SettingsCaptureFilterSet: 'settings.captureFilter.set',
+  SettingsDnsGet: 'settings.dns.get',
+  SettingsDnsSet: 'settings.dns.set',
   SettingsCertificatePathGet: 'settings.certificate.path.get',
   RulesListGet: 'rules.list.get',
   RulesGet: 'rules.get',
@@ -35,6 +37,13 @@ export const WsOp = {
   RulesEnabledSet: 'rules.enabled.set',
   RulesDelete: 'rules.delete',
   RulesTemplatesGet: 'rules.templates.get',
+  RulesExplain: 'rules.explain',
+  RulesLint: 'rules.lint',
+  RulesSequencesGet: 'rules.sequences.get',
+  RulesSequencesReset: 'rules.sequences.reset',
+  BreakpointListGet: 'breakpoint.list.get',
+  BreakpointResume: 'breakpoint.resume',
+  WebsocketSend: 'websocket.send',
   ProjectsListGet: 'projects.list.get',
   ProjectsActiveSet: 'projects.active.set',
   ProjectsCreate: 'projects.create',
@@ -58,6 +67,9 @@ export const WsOp = {
   NetworkTrafficFilterHistoryGet: 'network.trafficFilter.history.get',
   NetworkTrafficFilterHistoryAppend: 'network.trafficFilter.history.append',
   NetworkTrafficFilterHistoryClear: 'network.trafficFilter.history.clear',
+  NetworkTrafficFilterSavedGet: 'network.trafficFilter.saved.get',
+  NetworkTrafficFilterSavedSet: 'network.trafficFilter.saved.set',
+  NetworkTrafficFilterSavedDelete: 'network.trafficFilter.saved.delete',
   CaptureStatusChanged: 'capture.status.changed',
   RequestStart: 'request.start',
   RequestBody: 'request.body',
@@ -68,6 +80,10 @@ export const WsOp = {
   WebsocketMessage: 'websocket.message',
   WebsocketError: 'websocket.error',
   WebsocketEnd: 'websocket.end',
+  ScriptLog: 'script.log',
+  ServerConnected: 'server.connected',
+  BreakpointHit: 'breakpoint.hit',
+  BreakpointResolved: 'breakpoint.resolved',
   SystemError: 'system.error',
 } as const
 
@@ -83,6 +99,8 @@ export type WsRequestOp =
   | 'settings.general.set'
   | 'settings.captureFilter.get'
   | 'settings.captureFilter.set'
+  | 'settings.dns.get'
+  | 'settings.dns.set'
   | 'settings.certificate.path.get'
   | 'rules.list.get'
   | 'rules.get'
@@ -90,6 +108,13 @@ export type WsRequestOp =
   | 'rules.enabled.set'
   | 'rules.delete'
   | 'rules.templates.get'
+  | 'rules.explain'
+  | 'rules.lint'
+  | 'rules.sequences.get'
+  | 'rules.sequences.reset'
+  | 'breakpoint.list.get'
+  | 'breakpoint.resume'
+  | 'websocket.send'
   | 'projects.list.get'
   | 'projects.active.set'
   | 'projects.create'
@@ -113,6 +138,9 @@ export type WsRequestOp =
   | 'network.trafficFilter.history.get'
   | 'network.trafficFilter.history.append'
   | 'network.trafficFilter.history.clear'
+  | 'network.trafficFilter.saved.get'
+  | 'network.trafficFilter.saved.set'
+  | 'network.trafficFilter.saved.delete'
 
 export type WsEventOp =
   | 'capture.status.changed'
@@ -125,6 +153,10 @@ export type WsEventOp =
   | 'websocket.message'
   | 'websocket.error'
   | 'websocket.end'
+  | 'script.log'
+  | 'server.connected'
+  | 'breakpoint.hit'
+  | 'breakpoint.resolved'
   | 'system.error'
 
 export interface WsErrorPayload {
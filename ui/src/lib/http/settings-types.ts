@@ -15,6 +15,23 @@ export interface CaptureFilter {
   excludeDomains: DomainFilter[]
 }
 
+export interface DnsOverride {
+  /** `api.example.com`, or `*.example.com` for every subdomain. */
+  host: string
+  ips: string[]
+  enabled: boolean
+}
+
+export type DnsResolverConfig =
+  | { type: 'system' }
+  | { type: 'server'; address: string }
+  | { type: 'dnsOverHttps'; url: string }
+
+export interface DnsConfig {
+  overrides: DnsOverride[]
+  resolver: DnsResolverConfig
+}
+
 export const DEFAULT_DOMAIN_FILTER: DomainFilter = {
   domain: '',
   enabled: true,
@@ -27,5 +44,16 @@ export const DEFAULT_CAPTURE_FILTER: CaptureFilter = {
   excludeDomains: [],
 }
 
+export const DEFAULT_DNS_OVERRIDE: DnsOverride = {
+  host: '',
+  ips: [],
+  enabled: true,
+}
+
+export const DEFAULT_DNS_CONFIG: DnsConfig = {
+  overrides: [],
+  resolver: { type: 'system' },
+}
+
 export const MAX_LOG_SIZE_MIN = 60
 export const MAX_LOG_SIZE_MAX = 6000
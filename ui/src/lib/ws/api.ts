@@ -1,5 +1,5 @@
 import { WsOp, type WsEventFrame } from '@/lib/generated/ws/v1'
-import type { CaptureFilter, GeneralSetting } from '@/lib/http/settings-types'
+import type { CaptureFilter, DnsConfig, GeneralSetting } from '@/lib/http/settings-types'
 import type {
   ProjectsActiveSetPayload,
   ProjectsCreatePayload,
@@ -25,6 +25,11 @@ export interface CaptureControlPayload {
   recording: boolean
 }
 
+export interface RequestStreamSubscribePayload {
+  filter?: string
+  filterName?: string
+}
+
 export interface RequestDetailPayload {
   traceId: string
 }
@@ -46,6 +51,78 @@ export interface RequestDetailValue {
     body?: unknown
   }
   timings?: Record<string, number | undefined>
+  /** Results of `Mirror` handlers, compared against this response. */
+  mirrors?: MirrorResultValue[]
+}
+
+export interface MirrorResultValue {
+  target: string
+  /** Trace of the mirrored exchange, when it was captured. */
+  traceId?: string | null
+  status?: number | null
+  error?: string | null
+  durationMs: number
+  diff?: {
+    status?: { primary: number, mirror: number } | null
+    headers: { name: string, primary?: string | null, mirror?: string | null }[]
+    bodyChanged: boolean
+    /** A body was over the comparison limit (8 MiB), so bodies were not compared. */
+    bodyTruncated?: boolean
+    /** JSON body differences by JSON pointer; a missing side means the path is absent. */
+    body: { path: string, primary?: unknown, mirror?: unknown }[]
+  } | null
+}
+
+export interface BreakpointHeader {
+  name: string
+  value: string
+}
+
+/** An exchange held by a `Breakpoint` handler. */
+export interface HeldBreakpoint {
+  id: string
+  traceId: string
+  stage: 'request' | 'response'
+  method: string
+  url: string
+  status?: number
+  headers: BreakpointHeader[]
+  /** Decoded body, when it is text; otherwise `bodyBase64`. */
+  body?: string
+  bodyBase64?: string
+  heldAt: number
+  /** When it continues unchanged if nobody resumes it. */
+  expiresAt: number
+}
+
+export interface BreakpointListResponse {
+  breakpoints: HeldBreakpoint[]
+}
+
+/** Omitted fields keep their held values; `headers` replaces the whole list. */
+export interface BreakpointResumePayload {
+  id: string
+  abort?: boolean
+  method?: string
+  url?: string
+  status?: number
+  headers?: BreakpointHeader[]
+  body?: string
+  bodyBase64?: string
+}
+
+export interface BreakpointResolvedEvent {
+  traceId: string
+  id: string
+  outcome: 'resumed' | 'edited' | 'aborted' | 'timedOut' | 'cancelled'
+}
+
+/** A frame injected into an open proxied WebSocket connection. */
+export interface WebSocketSendPayload {
+  traceId: string
+  direction: 'clientToServer' | 'serverToClient'
+  text?: string
+  binaryBase64?: string
 }
 
 export interface RequestDetailResponse {
@@ -96,12 +173,17 @@ export interface WsApi {
   setCaptureControl: (payload: CaptureControlPayload) => Promise<unknown>
   getRequestDetail: (payload: RequestDetailPayload) => Promise<RequestDetailResponse>
   sendComposeRequest: (payload: ComposeRequestPayload) => Promise<ComposeResponse>
-  subscribeRequestStream: () => Promise<unknown>
+  subscribeRequestStream: (payload?: RequestStreamSubscribePayload) => Promise<unknown>
   unsubscribeRequestStream: () => Promise<unknown>
   getGeneralSetting: () => Promise<GeneralSetting>
   setGeneralSetting: (setting: GeneralSetting) => Promise<unknown>
   getCaptureFilter: () => Promise<CaptureFilter>
   setCaptureFilter: (filter: CaptureFilter) => Promise<unknown>
+  getDnsConfig: () => Promise<DnsConfig>
+  setDnsConfig: (config: DnsConfig) => Promise<unknown>
+  listBreakpoints: () => Promise<BreakpointListResponse>
+  resumeBreakpoint: (payload: BreakpointResumePayload) => Promise<unknown>
+  sendWebSocketFrame: (payload: WebSocketSendPayload) => Promise<unknown>
   getCertificatePath: () => Promise<CertificatePathResponse>
   listRules: (payload?: RulesListPayload) => Promise<RulesListResponse>
   getRule: (payload: RulesGetPayload) => Promise<RequestRuleDto>
@@ -128,12 +210,23 @@ export const createWsApi = (client: WsClient): WsApi => {
       ),
     sendComposeRequest: (payload) =>
       client.call<ComposeResponse, ComposeRequestPayload>(WsOp.ComposeRequestSend, payload),
-    subscribeRequestStream: () => client.call(WsOp.RequestStreamSubscribe),
+    subscribeRequestStream: (payload) =>
+      client.call<unknown, RequestStreamSubscribePayload | undefined>(
+        WsOp.RequestStreamSubscribe,
+        payload,
+      ),
     unsubscribeRequestStream: () => client.call(WsOp.RequestStreamUnsubscribe),
     getGeneralSetting: () => client.call<GeneralSetting>(WsOp.SettingsGeneralGet),
     setGeneralSetting: (setting) => client.call(WsOp.SettingsGeneralSet, setting),
     getCaptureFilter: () => client.call<CaptureFilter>(WsOp.SettingsCaptureFilterGet),
     setCaptureFilter: (filter) => client.call(WsOp.SettingsCaptureFilterSet, filter),
+    getDnsConfig: () => client.call<DnsConfig>(WsOp.SettingsDnsGet),
+    setDnsConfig: (config) => client.call(WsOp.SettingsDnsSet, config),
+    listBreakpoints: () => client.call<BreakpointListResponse>(WsOp.BreakpointListGet),
+    resumeBreakpoint: (payload) =>
+      client.call<unknown, BreakpointResumePayload>(WsOp.BreakpointResume, payload),
+    sendWebSocketFrame: (payload) =>
+      client.call<unknown, WebSocketSendPayload>(WsOp.WebsocketSend, payload),
     getCertificatePath: () => client.call<CertificatePathResponse>(WsOp.SettingsCertificatePathGet),
     listRules: (payload) =>
       client.call<RulesListResponse, RulesListPayload>(WsOp.RulesListGet, payload),
@@ -1,9 +1,9 @@
-import type { RuleDraft, RuleActionDraft, RuleHeaderPair, RuleWorkbenchRuleItem } from '@/components/ui/rule-workbench'
-import { createAction, createRuleDraft } from '@/components/ui/rule-workbench'
+import type { RuleBodyTransformDraft, RuleDraft, RuleFaultDraft, RuleActionDraft, RuleHeaderOpDraft, RuleMockResponseActionConfig, RuleSequenceActionConfig, RuleStreamMockActionConfig, RuleWebSocketFrameRuleDraft, RuleWebSocketMockFrameDraft, RuleWorkbenchRuleItem } from '@/components/ui/rule-workbench'
+import { createAction, createFaultDraft, createRuleDraft, createWebSocketFrameRuleDraft, createWebSocketMockFrameDraft } from '@/components/ui/rule-workbench'
 import { proxyForwardSchemeFromDto } from '@/components/ui/rule-workbench/proxy-forward-scheme'
 import { getRuleValidationErrors } from '@/components/ui/rule-workbench/match-validation'
 import type { ActionAssetTemplate } from '@/components/ui/rules-drawer/types'
-import type { HandlerRuleDto, HandlerRuleTypeDto, RequestRuleDto } from './rules-types'
+import type { BodyTransformDto, FaultDto, HandlerRuleDto, HandlerRuleTypeDto, HeaderOpDto, MockResponseDto, ModifyHeadersDto, RequestRuleDto, SequenceScopeDto, StreamMockConfigDto, WebSocketFrameRuleDto, WebSocketMockFrameDto } from './rules-types'
 
 function normalizeThrottlePreset(raw?: string): 'Fast3G' | 'Slow3G' | 'Offline' | 'Custom' {
   const value = (raw ?? '').trim()
@@ -23,15 +23,193 @@ function throttlePresetToDto(preset: 'Fast3G' | 'Slow3G' | 'Offline' | 'Custom')
   return 'custom'
 }
 
-function headersFromRecord(record?: Record<string, string>): RuleHeaderPair[] {
-  if (!record) return []
-  return Object.entries(record).map(([key, value]) => ({ key, value }))
+function headerOpsFromDto(headers?: ModifyHeadersDto): RuleHeaderOpDraft[] {
+  if (!headers) return []
+  const blank = { key: '', value: '', to: '', pattern: '' }
+  if (!Array.isArray(headers)) {
+    return Object.entries(headers).map(([key, value]) => ({ ...blank, op: 'set', key, value }))
+  }
+  return headers.map((h): RuleHeaderOpDraft => {
+    if (h.op === 'set' || h.op === 'append') return { ...blank, op: h.op, key: h.name, value: h.value }
+    if (h.op === 'remove') return { ...blank, op: h.op, key: h.name }
+    if (h.op === 'rename') return { ...blank, op: h.op, key: h.from, to: h.to }
+    return { ...blank, op: h.op, key: h.name, pattern: h.pattern, value: h.replacement }
+  })
+}
+
+function headerOpsToDto(ops: RuleHeaderOpDraft[]): HeaderOpDto[] | undefined {
+  const valid = ops.filter(h => h.key.trim())
+  if (valid.length === 0) return undefined
+  return valid.map((h): HeaderOpDto => {
+    if (h.op === 'set' || h.op === 'append') return { op: h.op, name: h.key, value: h.value }
+    if (h.op === 'remove') return { op: h.op, name: h.key }
+    if (h.op === 'rename') return { op: h.op, from: h.key, to: h.to }
+    return { op: h.op, name: h.key, pattern: h.pattern, replacement: h.value }
+  })
+}
+
+function bodyTransformsFromDto(transforms?: BodyTransformDto[]): RuleBodyTransformDraft[] {
+  return (transforms ?? []).map((t) => {
+    const draft: RuleBodyTransformDraft = { type: t.type, pattern: '', replacement: '', path: '', valueJson: '' }
+    if (t.type === 'regexReplace') return { ...draft, pattern: t.pattern, replacement: t.replacement }
+    if (t.type === 'jsonPatch' || t.type === 'mergePatch') return { ...draft, valueJson: JSON.stringify(t.patch, null, 2) }
+    if (t.type === 'jsonPathSet') return { ...draft, path: t.path, valueJson: JSON.stringify(t.value) }
+    return { ...draft, path: t.path }
+  })
+}
+
+// Validation rejects malformed JSON before drafts are saved.
+function bodyTransformsToDto(transforms: RuleBodyTransformDraft[]): BodyTransformDto[] | undefined {
+  if (transforms.length === 0) return undefined
+  return transforms.map((t): BodyTransformDto => {
+    if (t.type === 'regexReplace') return { type: t.type, pattern: t.pattern, replacement: t.replacement }
+    if (t.type === 'jsonPatch') return { type: t.type, patch: JSON.parse(t.valueJson) }
+    if (t.type === 'mergePatch') return { type: t.type, patch: JSON.parse(t.valueJson) }
+    if (t.type === 'jsonPathSet') return { type: t.type, path: t.path, value: JSON.parse(t.valueJson) }
+    return { type: t.type, path: t.path }
+  })
+}
+
+function faultsFromDto(faults: FaultDto[]): RuleFaultDraft[] {
+  return faults.map((f) => {
+    const draft = createFaultDraft({ type: f.type, probability: f.probability ?? 1 })
+    if (f.type === 'reset') return { ...draft, phase: f.phase ?? 'beforeHeaders' }
+    if (f.type === 'truncate') return { ...draft, bytes: f.bytes }
+    if (f.type === 'wrongContentLength') return { ...draft, delta: f.delta }
+    if (f.type === 'stall') return { ...draft, afterBytes: f.afterBytes, durationMs: f.durationMs }
+    return draft
+  })
+}
+
+function faultsToDto(faults: RuleFaultDraft[]): FaultDto[] {
+  return faults.map((f): FaultDto => {
+    const probability = f.probability
+    if (f.type === 'reset') return { type: f.type, probability, phase: f.phase }
+    if (f.type === 'truncate') return { type: f.type, probability, bytes: f.bytes }
+    if (f.type === 'wrongContentLength') return { type: f.type, probability, delta: f.delta }
+    if (f.type === 'stall') return { type: f.type, probability, afterBytes: f.afterBytes, durationMs: f.durationMs }
+    return { type: f.type, probability }
+  })
+}
+
+function frameRulesFromDto(rules: WebSocketFrameRuleDto[]): RuleWebSocketFrameRuleDraft[] {
+  return rules.map((r) => {
+    const draft = createWebSocketFrameRuleDraft({
+      direction: r.direction ?? 'both',
+      pattern: r.pattern ?? '',
+      jsonPath: r.jsonPath ?? '',
+      jsonValue: r.jsonValue === undefined ? '' : JSON.stringify(r.jsonValue),
+      action: r.action.type,
+    })
+    if (r.action.type === 'rewrite') {
+      return { ...draft, replacement: r.action.replacement ?? '', transforms: bodyTransformsFromDto(r.action.transforms) }
+    }
+    if (r.action.type === 'delay') return { ...draft, delayMs: r.action.delayMs }
+    return draft
+  })
+}
+
+// Validation rejects malformed JSON values before drafts are saved.
+function frameRulesToDto(rules: RuleWebSocketFrameRuleDraft[]): WebSocketFrameRuleDto[] {
+  return rules.map((r): WebSocketFrameRuleDto => {
+    const jsonPath = r.jsonPath.trim() || undefined
+    const base = {
+      direction: r.direction,
+      pattern: r.pattern || undefined,
+      jsonPath,
+      jsonValue: jsonPath && r.jsonValue ? JSON.parse(r.jsonValue) : undefined,
+    }
+    if (r.action === 'rewrite') {
+      return {
+        ...base,
+        action: { type: 'rewrite', replacement: r.replacement || undefined, transforms: bodyTransformsToDto(r.transforms) },
+      }
+    }
+    if (r.action === 'delay') return { ...base, action: { type: 'delay', delayMs: r.delayMs } }
+    return { ...base, action: { type: 'drop' } }
+  })
 }
 
-function headersToRecord(headers: RuleHeaderPair[]): Record<string, string> | undefined {
-  const pairs = headers.filter(h => h.key.trim())
-  if (pairs.length === 0) return undefined
-  return Object.fromEntries(pairs.map(h => [h.key, h.value]))
+function mockFrameFromDto(frame: WebSocketMockFrameDto): RuleWebSocketMockFrameDraft {
+  return createWebSocketMockFrameDraft({
+    kind: frame.binaryBase64 !== undefined ? 'binary' : 'text',
+    text: frame.text ?? '',
+    binaryBase64: frame.binaryBase64 ?? '',
+    delayMs: frame.delayMs,
+  })
+}
+
+function mockFrameToDto(frame: RuleWebSocketMockFrameDraft): WebSocketMockFrameDto {
+  return {
+    text: frame.kind === 'text' ? frame.text : undefined,
+    binaryBase64: frame.kind === 'binary' ? frame.binaryBase64.trim() : undefined,
+    delayMs: frame.delayMs,
+  }
+}
+
+function streamMockFromDto(t: StreamMockConfigDto): RuleStreamMockActionConfig {
+  return {
+    format: t.format ?? 'sse',
+    statusCode: t.statusCode,
+    headers: Object.entries(t.headers ?? {}).map(([key, value]) => ({ key, value })),
+    chunks: (t.chunks ?? []).map(chunk => ({
+      delayMs: chunk.delayMs ?? 0,
+      data: chunk.data,
+      event: chunk.event ?? '',
+      id: chunk.id ?? '',
+      retryMs: chunk.retryMs,
+    })),
+    repeat: t.repeat ?? 1,
+    failAfter: t.failAfter,
+  }
+}
+
+function streamMockToDto(config: RuleStreamMockActionConfig): StreamMockConfigDto {
+  const sse = config.format === 'sse'
+  return {
+    format: config.format,
+    statusCode: config.statusCode,
+    headers: config.headers.length > 0
+      ? Object.fromEntries(config.headers.map(header => [header.key, header.value]))
+      : undefined,
+    chunks: config.chunks.map(chunk => ({
+      delayMs: chunk.delayMs,
+      data: chunk.data,
+      event: sse ? chunk.event || undefined : undefined,
+      id: sse ? chunk.id || undefined : undefined,
+      retryMs: sse ? chunk.retryMs : undefined,
+    })),
+    repeat: config.repeat,
+    failAfter: config.failAfter,
+  }
+}
+
+function mockResponseFromDto(t: MockResponseDto): RuleMockResponseActionConfig {
+  return {
+    statusCode: t.statusCode,
+    headers: Object.entries(t.headers ?? {}).map(([key, value]) => ({ key, value })),
+    bodySource: t.bodyFile ? 'file' : 'inline',
+    body: t.body ?? '',
+    bodyFile: t.bodyFile ?? '',
+    latencyMs: t.latencyMs,
+  }
+}
+
+function mockResponseToDto(config: RuleMockResponseActionConfig): MockResponseDto {
+  return {
+    statusCode: config.statusCode,
+    headers: config.headers.length > 0
+      ? Object.fromEntries(config.headers.map(header => [header.key, header.value]))
+      : undefined,
+    body: config.bodySource === 'inline' ? config.body || undefined : undefined,
+    bodyFile: config.bodySource === 'file' ? config.bodyFile : undefined,
+    latencyMs: config.latencyMs,
+  }
+}
+
+function sequenceScopeToDto(config: RuleSequenceActionConfig): SequenceScopeDto {
+  if (config.scope === 'header') return { type: 'header', name: config.scopeHeader }
+  return { type: config.scope }
 }
 
 function optionalProxyForwardField(value: string): string | undefined {
@@ -79,6 +257,16 @@ function handlerTypeToAction(handler: HandlerRuleDto, index: number): RuleAction
           targetScheme: proxyForwardSchemeFromDto(t.targetScheme),
           targetAuthority: proxyForwardFieldFromDto(t.targetAuthority),
           targetPath: proxyForwardFieldFromDto(t.targetPath),
+          pathRegex: t.pathRegex ?? '',
+          queryMode: t.queryMode ?? 'keep',
+          queryParams: Object.entries(t.queryParams ?? {}).map(([key, value]) => ({ key, value })),
+          preserveHost: t.preserveHost ?? false,
+          targets: (t.targets ?? []).map(target => ({
+            targetScheme: proxyForwardSchemeFromDto(target.targetScheme),
+            targetAuthority: proxyForwardFieldFromDto(target.targetAuthority),
+            targetPath: proxyForwardFieldFromDto(target.targetPath),
+            weight: target.weight ?? 1,
+          })),
         },
       })
     case 'modifyRequest':
@@ -86,10 +274,11 @@ function handlerTypeToAction(handler: HandlerRuleDto, index: number): RuleAction
         ...base,
         type: 'modifyRequest',
         config: {
-          modifyHeaders: headersFromRecord(t.modifyHeaders),
+          modifyHeaders: headerOpsFromDto(t.modifyHeaders),
           modifyMethod: t.modifyMethod ?? '',
           modifyUrl: t.modifyUrl ?? '',
           modifyBody: t.modifyBody ?? '',
+          bodyTransforms: bodyTransformsFromDto(t.bodyTransforms),
         },
       })
     case 'modifyResponse':
@@ -97,9 +286,10 @@ function handlerTypeToAction(handler: HandlerRuleDto, index: number): RuleAction
         ...base,
         type: 'modifyResponse',
         config: {
-          modifyHeaders: headersFromRecord(t.modifyHeaders),
+          modifyHeaders: headerOpsFromDto(t.modifyHeaders),
           modifyStatusCode: t.modifyStatusCode,
           modifyBody: t.modifyBody ?? '',
+          bodyTransforms: bodyTransformsFromDto(t.bodyTransforms),
         },
       })
     case 'localFile':
@@ -110,6 +300,121 @@ function handlerTypeToAction(handler: HandlerRuleDto, index: number): RuleAction
           filePath: t.filePath ?? '',
           contentType: t.contentType ?? '',
           statusCode: t.statusCode,
+          mode: t.mode ?? 'file',
+          stripPrefix: t.stripPrefix ?? '',
+          spaFallback: t.spaFallback ?? false,
+        },
+      })
+    case 'mockResponse':
+      return createAction({
+        ...base,
+        type: 'mockResponse',
+        config: mockResponseFromDto(t),
+      })
+    case 'record':
+      return createAction({
+        ...base,
+        type: 'record',
+        config: { cassette: t.cassette ?? '', redactHeaders: t.redactHeaders },
+      })
+    case 'replay':
+      return createAction({
+        ...base,
+        type: 'replay',
+        config: {
+          cassette: t.cassette ?? '',
+          matchOn: t.matchOn ?? ['method', 'host', 'path', 'query'],
+          matchHeaders: t.matchHeaders ?? [],
+          fallback: t.fallback ?? 'passthrough',
+        },
+      })
+    case 'fault':
+      return createAction({
+        ...base,
+        type: 'fault',
+        config: { faults: faultsFromDto(t.faults ?? []) },
+      })
+    case 'sequence':
+      return createAction({
+        ...base,
+        type: 'sequence',
+        config: {
+          responses: (t.responses ?? []).map(mockResponseFromDto),
+          scope: t.scope?.type ?? 'rule',
+          scopeHeader: t.scope?.type === 'header' ? t.scope.name : '',
+          onExhausted: t.onExhausted ?? 'stickAtLast',
+        },
+      })
+    case 'streamMock':
+      return createAction({
+        ...base,
+        type: 'streamMock',
+        config: streamMockFromDto(t),
+      })
+    case 'webSocketIntercept':
+      return createAction({
+        ...base,
+        type: 'webSocketIntercept',
+        config: { rules: frameRulesFromDto(t.rules ?? []) },
+      })
+    case 'webSocketMock':
+      return createAction({
+        ...base,
+        type: 'webSocketMock',
+        config: {
+          onConnect: (t.onConnect ?? []).map(mockFrameFromDto),
+          replies: (t.replies ?? []).map(reply => ({ pattern: reply.pattern, frames: reply.frames.map(mockFrameFromDto) })),
+          echo: t.echo ?? false,
+          periodic: (t.periodic ?? []).map(periodic => ({
+            intervalMs: periodic.intervalMs,
+            frame: mockFrameFromDto(periodic.frame),
+            count: periodic.count,
+          })),
+          close: t.close ? { afterMs: t.close.afterMs, code: t.close.code ?? 1000, reason: t.close.reason ?? '' } : undefined,
+        },
+      })
+    case 'breakpoint':
+      return createAction({
+        ...base,
+        type: 'breakpoint',
+        config: {
+          phase: t.phase ?? 'request',
+          timeoutMs: t.timeoutMs,
+        },
+      })
+    case 'mirror':
+      return createAction({
+        ...base,
+        type: 'mirror',
+        config: {
+          targets: t.targets ?? [],
+          ignoreHeaders: t.ignoreHeaders ?? [],
+          timeoutMs: t.timeoutMs,
+        },
+      })
+    case 'cors':
+      return createAction({
+        ...base,
+        type: 'cors',
+        config: {
+          allowedOrigins: t.allowedOrigins ?? [],
+          echoOrigin: t.echoOrigin ?? false,
+          allowedMethods: t.allowedMethods ?? ['GET', 'POST', 'PUT', 'PATCH', 'DELETE', 'HEAD', 'OPTIONS'],
+          allowedHeaders: t.allowedHeaders ?? [],
+          exposeHeaders: t.exposeHeaders ?? [],
+          allowCredentials: t.allowCredentials ?? false,
+          maxAgeSecs: t.maxAgeSecs,
+        },
+      })
+    case 'upstreamProxy':
+      return createAction({
+        ...base,
+        type: 'upstreamProxy',
+        config: {
+          protocol: t.protocol ?? 'direct',
+          address: t.address ?? '',
+          username: t.username ?? '',
+          password: t.password ?? '',
         },
       })
     case 'htmlScriptInjector': {
@@ -137,6 +442,29 @@ function handlerTypeToAction(handler: HandlerRuleDto, index: number): RuleAction
           latencyMs: t.latencyMs,
         },
       })
+    case 'script':
+      return createAction({
+        ...base,
+        type: 'script',
+        config: {
+          script: t.script ?? '',
+          maxOperations: t.maxOperations,
+          timeoutMs: t.timeoutMs,
+          maxStringBytes: t.maxStringBytes,
+          maxCollectionSize: t.maxCollectionSize,
+        },
+      })
+    case 'wasmPlugin':
+      return createAction({
+        ...base,
+        type: 'wasmPlugin',
+        config: {
+          plugin: t.plugin ?? '',
+          configJson: t.config == null ? '' : JSON.stringify(t.config, null, 2),
+          fuel: t.fuel,
+          maxMemoryBytes: t.maxMemoryBytes,
+        },
+      })
     default:
       return null
   }
@@ -163,21 +491,37 @@ function actionToHandlerType(action: RuleActionDraft): HandlerRuleTypeDto {
         targetScheme: optionalProxyForwardField(action.config.targetScheme),
         targetAuthority: optionalProxyForwardField(action.config.targetAuthority),
         targetPath: optionalProxyForwardField(action.config.targetPath),
+        pathRegex: action.config.pathRegex || undefined,
+        queryMode: action.config.queryMode === 'keep' ? undefined : action.config.queryMode,
+        queryParams: action.config.queryParams.length > 0
+          ? Object.fromEntries(action.config.queryParams.map(param => [param.key, param.value]))
+          : undefined,
+        preserveHost: action.config.preserveHost || undefined,
+        targets: action.config.targets.length > 0
+          ? action.config.targets.map(target => ({
+              targetScheme: optionalProxyForwardField(target.targetScheme),
+              targetAuthority: optionalProxyForwardField(target.targetAuthority),
+              targetPath: optionalProxyForwardField(target.targetPath),
+              weight: target.weight,
+            }))
+          : undefined,
       }
     case 'modifyRequest':
       return {
         type: 'modifyRequest',
-        modifyHeaders: headersToRecord(action.config.modifyHeaders),
+        modifyHeaders: headerOpsToDto(action.config.modifyHeaders),
         modifyMethod: action.config.modifyMethod || undefined,
         modifyUrl: action.config.modifyUrl || undefined,
         modifyBody: action.config.modifyBody || undefined,
+        bodyTransforms: bodyTransformsToDto(action.config.bodyTransforms),
       }
     case 'modifyResponse':
       return {
         type: 'modifyResponse',
-        modifyHeaders: headersToRecord(action.config.modifyHeaders),
+        modifyHeaders: headerOpsToDto(action.config.modifyHeaders),
         modifyStatusCode: action.config.modifyStatusCode,
         modifyBody: action.config.modifyBody || undefined,
+        bodyTransforms: bodyTransformsToDto(action.config.bodyTransforms),
       }
     case 'localFile':
       return {
@@ -185,6 +529,99 @@ function actionToHandlerType(action: RuleActionDraft): HandlerRuleTypeDto {
         filePath: action.config.filePath,
         contentType: action.config.contentType || undefined,
         statusCode: action.config.statusCode,
+        ...(action.config.mode === 'directory'
+          ? {
+              mode: 'directory' as const,
+              stripPrefix: action.config.stripPrefix || undefined,
+              spaFallback: action.config.spaFallback || undefined,
+            }
+          : {}),
+      }
+    case 'mockResponse':
+      return {
+        type: 'mockResponse',
+        ...mockResponseToDto(action.config),
+      }
+    case 'record':
+      return {
+        type: 'record',
+        cassette: action.config.cassette || undefined,
+        redactHeaders: action.config.redactHeaders,
+      }
+    case 'replay':
+      return {
+        type: 'replay',
+        cassette: action.config.cassette || undefined,
+        matchOn: action.config.matchOn,
+        matchHeaders: action.config.matchHeaders.length > 0 ? action.config.matchHeaders : undefined,
+        fallback: action.config.fallback,
+      }
+    case 'fault':
+      return {
+        type: 'fault',
+        faults: faultsToDto(action.config.faults),
+      }
+    case 'sequence':
+      return {
+        type: 'sequence',
+        responses: action.config.responses.map(mockResponseToDto),
+        scope: sequenceScopeToDto(action.config),
+        onExhausted: action.config.onExhausted,
+      }
+    case 'streamMock':
+      return {
+        type: 'streamMock',
+        ...streamMockToDto(action.config),
+      }
+    case 'webSocketIntercept':
+      return {
+        type: 'webSocketIntercept',
+        rules: frameRulesToDto(action.config.rules),
+      }
+    case 'webSocketMock':
+      return {
+        type: 'webSocketMock',
+        onConnect: action.config.onConnect.map(mockFrameToDto),
+        replies: action.config.replies.map(reply => ({ pattern: reply.pattern, frames: reply.frames.map(mockFrameToDto) })),
+        echo: action.config.echo,
+        periodic: action.config.periodic.map(periodic => ({
+          intervalMs: periodic.intervalMs,
+          frame: mockFrameToDto(periodic.frame),
+          count: periodic.count,
+        })),
+        close: action.config.close,
+      }
+    case 'breakpoint':
+      return {
+        type: 'breakpoint',
+        phase: action.config.phase,
+        timeoutMs: action.config.timeoutMs,
+      }
+    case 'mirror':
+      return {
+        type: 'mirror',
+        targets: action.config.targets.map(target => target.trim()).filter(Boolean),
+        ignoreHeaders: action.config.ignoreHeaders.length ? action.config.ignoreHeaders : undefined,
+        timeoutMs: action.config.timeoutMs,
+      }
+    case 'cors':
+      return {
+        type: 'cors',
+        allowedOrigins: action.config.allowedOrigins.map(origin => origin.trim()).filter(Boolean),
+        echoOrigin: action.config.echoOrigin,
+        allowedMethods: action.config.allowedMethods,
+        allowedHeaders: action.config.allowedHeaders.length ? action.config.allowedHeaders : undefined,
+        exposeHeaders: action.config.exposeHeaders.length ? action.config.exposeHeaders : undefined,
+        allowCredentials: action.config.allowCredentials,
+        maxAgeSecs: action.config.maxAgeSecs,
+      }
+    case 'upstreamProxy':
+      return {
+        type: 'upstreamProxy',
+        protocol: action.config.protocol,
+        address: action.config.protocol === 'direct' ? undefined : action.config.address.trim(),
+        username: action.config.username || undefined,
+        password: action.config.username ? action.config.password : undefined,
       }
     case 'htmlScriptInjector':
       return {
@@ -200,6 +637,24 @@ function actionToHandlerType(action: RuleActionDraft): HandlerRuleTypeDto {
         uploadKbps: action.config.preset === 'Custom' ? action.config.uploadKbps : undefined,
         latencyMs: action.config.preset === 'Custom' ? action.config.latencyMs : undefined,
       }
+    case 'script':
+      return {
+        type: 'script',
+        script: action.config.script,
+        maxOperations: action.config.maxOperations,
+        timeoutMs: action.config.timeoutMs,
+        maxStringBytes: action.config.maxStringBytes,
+        maxCollectionSize: action.config.maxCollectionSize,
+      }
+    case 'wasmPlugin':
+      return {
+        type: 'wasmPlugin',
+        plugin: action.config.plugin,
+        // Validation rejects malformed JSON before drafts are saved.
+        config: action.config.configJson.trim() ? JSON.parse(action.config.configJson) : undefined,
+        fuel: action.config.fuel,
+        maxMemoryBytes: action.config.maxMemoryBytes,
+      }
   }
 }
 
@@ -3,15 +3,146 @@ export interface CaptureRuleDto {
   matchExpr: string
 }
 
+export type BodyTransformDto =
+  | { type: 'regexReplace'; pattern: string; replacement: string }
+  | { type: 'jsonPatch'; patch: unknown[] }
+  | { type: 'mergePatch'; patch: unknown }
+  | { type: 'jsonPathSet'; path: string; value: unknown }
+  | { type: 'jsonPathDelete'; path: string }
+
+export type WebSocketFrameActionDto =
+  | { type: 'rewrite'; replacement?: string; transforms?: BodyTransformDto[] }
+  | { type: 'drop' }
+  | { type: 'delay'; delayMs: number }
+
+export interface WebSocketFrameRuleDto {
+  direction?: 'both' | 'clientToServer' | 'serverToClient'
+  pattern?: string
+  jsonPath?: string
+  jsonValue?: unknown
+  action: WebSocketFrameActionDto
+}
+
+export interface WebSocketMockFrameDto {
+  text?: string
+  binaryBase64?: string
+  delayMs?: number
+}
+
+export interface WebSocketMockConfigDto {
+  onConnect?: WebSocketMockFrameDto[]
+  replies?: Array<{ pattern: string; frames: WebSocketMockFrameDto[] }>
+  echo?: boolean
+  periodic?: Array<{ intervalMs: number; frame: WebSocketMockFrameDto; count?: number }>
+  close?: { afterMs: number; code?: number; reason?: string }
+}
+
+export interface StreamMockChunkDto {
+  delayMs?: number
+  data: string
+  event?: string
+  id?: string
+  retryMs?: number
+}
+
+export interface StreamMockConfigDto {
+  format?: 'sse' | 'raw'
+  statusCode?: number
+  headers?: Record<string, string>
+  chunks: StreamMockChunkDto[]
+  repeat?: number
+  failAfter?: number
+}
+
+export type HeaderOpDto =
+  | { op: 'set'; name: string; value: string }
+  | { op: 'append'; name: string; value: string }
+  | { op: 'remove'; name: string }
+  | { op: 'rename'; from: string; to: string }
+  | { op: 'regexRewrite'; name: string; pattern: string; replacement: string }
+
+export type FaultDto = { probability?: number } & (
+  | { type: 'reset'; phase?: 'beforeHeaders' | 'afterHeaders' }
+  | { type: 'truncate'; bytes: number }
+  | { type: 'wrongContentLength'; delta: number }
+  | { type: 'stall'; afterBytes?: number; durationMs: number }
+  | { type: 'malformedChunked' }
+  | { type: 'corruptGzip' }
+)
+
+export interface MockResponseDto {
+  statusCode?: number
+  headers?: Record<string, string>
+  body?: string
+  bodyFile?: string
+  latencyMs?: number
+}
+
+export interface ProxyForwardTargetDto {
+  targetScheme?: string
+  targetAuthority?: string
+  targetPath?: string
+  weight?: number
+}
+
+export type SequenceScopeDto =
+  | { type: 'rule' }
+  | { type: 'client' }
+  | { type: 'header'; name: string }
+
+/** Header ops in order; older rules store a plain name-to-value map. */
+export type ModifyHeadersDto = HeaderOpDto[] | Record<string, string>
+
 export type HandlerRuleTypeDto =
   | { type: 'block'; statusCode?: number; reason?: string }
-  | { type: 'modifyRequest'; modifyHeaders?: Record<string, string>; modifyBody?: string; modifyMethod?: string; modifyUrl?: string }
-  | { type: 'modifyResponse'; modifyHeaders?: Record<string, string>; modifyBody?: string; modifyMethod?: string; modifyStatusCode?: number }
-  | { type: 'localFile'; filePath: string; contentType?: string; statusCode?: number }
-  | { type: 'proxyForward'; targetScheme?: string; targetAuthority?: string; targetPath?: string }
+  | { type: 'modifyRequest'; modifyHeaders?: ModifyHeadersDto; modifyBody?: string; modifyMethod?: string; modifyUrl?: string; bodyTransforms?: BodyTransformDto[] }
+  | { type: 'modifyResponse'; modifyHeaders?: ModifyHeadersDto; modifyBody?: string; modifyMethod?: string; modifyStatusCode?: number; bodyTransforms?: BodyTransformDto[] }
+  | {
+    type: 'localFile'
+    filePath: string
+    contentType?: string
+    statusCode?: number
+    mode?: 'file' | 'directory'
+    stripPrefix?: string
+    spaFallback?: boolean
+  }
+  | ({ type: 'mockResponse' } & MockResponseDto)
+  | { type: 'record'; cassette?: string; redactHeaders?: string[] }
+  | { type: 'replay'; cassette?: string; matchOn?: Array<'method' | 'host' | 'path' | 'query' | 'body'>; matchHeaders?: string[]; fallback?: 'passthrough' | 'notFound' | 'error' }
+  | { type: 'fault'; faults: FaultDto[] }
+  | { type: 'sequence'; responses: MockResponseDto[]; scope?: SequenceScopeDto; onExhausted?: 'stickAtLast' | 'loop' }
+  | {
+    type: 'proxyForward'
+    targetScheme?: string
+    targetAuthority?: string
+    targetPath?: string
+    pathRegex?: string
+    queryMode?: 'keep' | 'merge' | 'replace'
+    queryParams?: Record<string, string>
+    preserveHost?: boolean
+    targets?: ProxyForwardTargetDto[]
+  }
+  | { type: 'webSocketIntercept'; rules: WebSocketFrameRuleDto[] }
+  | ({ type: 'webSocketMock' } & WebSocketMockConfigDto)
+  | ({ type: 'streamMock' } & StreamMockConfigDto)
+  | { type: 'breakpoint'; phase?: 'request' | 'response' | 'both'; timeoutMs?: number }
+  | { type: 'mirror'; targets: string[]; ignoreHeaders?: string[]; timeoutMs?: number }
+  | {
+    type: 'cors'
+    allowedOrigins?: string[]
+    echoOrigin?: boolean
+    allowedMethods?: string[]
+    allowedHeaders?: string[]
+    exposeHeaders?: string[]
+    allowCredentials?: boolean
+    maxAgeSecs?: number
+  }
+  | { type: 'upstreamProxy'; protocol: 'direct' | 'http' | 'https' | 'socks5'; address?: string; username?: string; password?: string }
   | { type: 'htmlScriptInjector'; content?: string; injectionPosition?: string }
   | { type: 'delay'; delayMs: number; varianceMs?: number; delayType?: 'beforeRequest' | 'afterRequest' | 'both' }
   | { type: 'throttle'; preset?: string; downloadKbps?: number; uploadKbps?: number; latencyMs?: number }
+  | { type: 'script'; script: string; maxOperations?: number; timeoutMs?: number; maxStringBytes?: number; maxCollectionSize?: number }
+  | { type: 'wasmPlugin'; plugin: string; config?: unknown; fuel?: number; maxMemoryBytes?: number }
 
 export interface HandlerRuleDto {
   id?: number | null
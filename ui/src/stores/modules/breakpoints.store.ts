@@ -0,0 +1,105 @@
+import { computed, ref, watch } from 'vue'
+import { defineStore } from 'pinia'
+import { WsOp } from '@/lib/generated/ws/v1'
+import type {
+  BreakpointListResponse,
+  BreakpointResolvedEvent,
+  BreakpointResumePayload,
+  HeldBreakpoint,
+} from '@/lib/ws/api'
+import { useWsConnectionStore } from './ws-connection.store'
+
+export const useBreakpointsStore = defineStore('breakpoints', () => {
+  const open = ref(false)
+  const loading = ref(false)
+  const error = ref<string | null>(null)
+  const held = ref<HeldBreakpoint[]>([])
+  const selectedId = ref<string | null>(null)
+
+  const ws = useWsConnectionStore()
+  let detachEventListener: (() => void) | null = null
+
+  const selected = computed(
+    () => held.value.find(breakpoint => breakpoint.id === selectedId.value) ?? held.value[0] ?? null,
+  )
+
+  function remove(id: string) {
+    held.value = held.value.filter(breakpoint => breakpoint.id !== id)
+    if (selectedId.value === id) {
+      selectedId.value = null
+    }
+  }
+
+  async function refresh() {
+    loading.value = true
+    error.value = null
+    try {
+      const result = await ws.call<BreakpointListResponse>(WsOp.BreakpointListGet)
+      held.value = result?.breakpoints ?? []
+    } catch (err) {
+      error.value = String(err)
+    } finally {
+      loading.value = false
+    }
+  }
+
+  async function resume(payload: BreakpointResumePayload) {
+    error.value = null
+    try {
+      await ws.call(WsOp.BreakpointResume, payload as unknown as Record<string, unknown>)
+      remove(payload.id)
+    } catch (err) {
+      error.value = String(err)
+      throw err
+    }
+  }
+
+  const handleServerEvent = () => {
+    if (detachEventListener) {
+      return
+    }
+
+    detachEventListener = ws.onEvent((frame) => {
+      if (frame.op === WsOp.BreakpointHit) {
+        const breakpoint = frame.payload as unknown as HeldBreakpoint
+        if (!held.value.some(item => item.id === breakpoint.id)) {
+          held.value = [...held.value, breakpoint]
+        }
+        // Surface the first hit; later ones queue up in the list.
+        open.value = true
+      } else if (frame.op === WsOp.BreakpointResolved) {
+        remove((frame.payload as unknown as BreakpointResolvedEvent).id)
+      }
+    })
+  }
+
+  watch(
+    () => ws.isConnected,
+    (connected, wasConnected) => {
+      if (!connected || wasConnected) {
+        return
+      }
+
+      handleServerEvent()
+      void refresh()
+    },
+  )
+
+  const dispose = () => {
+    detachEventListener?.()
+    detachEventListener = null
+  }
+
+  return {
+    open,
+    loading,
+    error,
+    held,
+    selectedId,
+    selected,
+    refresh,
+    resume,
+    handleServerEvent,
+    dispose,
+  }
+})
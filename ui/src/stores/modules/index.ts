@@ -3,6 +3,7 @@ export * from './counter.store'
 export * from './ws-connection.store'
 export * from './capture.store'
 export * from './capture-rules.store'
+export * from './breakpoints.store'
 export * from './general-settings.store'
 export * from './request-stream.store'
 export * from './settings.store'
@@ -1,8 +1,11 @@
 import { describe, expect, it } from 'vitest'
 import {
+  MAX_SCRIPT_LOGS,
+  appendScriptLog,
   demoteTraceFromOrder,
   isRecordListable,
   mergePartialRequestRecord,
+  parseScriptLog,
   promoteTraceToOrder,
   type PartialRequestRecord,
 } from './request-stream-logic'
@@ -212,3 +215,26 @@ describe('request stream visibility', () => {
     expect(state.recordsByTrace['trace-1']?.url).toBe('https://example.com/api')
   })
 })
+
+describe('script logs', () => {
+  it('parses script.log payloads and cached entries', () => {
+    const log = { timestamp: 10, level: 'error', hook: 'onResponse', message: 'boom' }
+    expect(parseScriptLog({ traceId: 'trace-1', log })).toEqual(log)
+    expect(parseScriptLog(log)).toEqual(log)
+    expect(parseScriptLog({ traceId: 'trace-1', log: { level: 'info' } })).toBeNull()
+    expect(parseScriptLog({ log: { timestamp: 1, level: 'loud', hook: 'onRequest', message: 'hi' } })?.level).toBe('info')
+  })
+
+  it('keeps script logs across patches and caps them', () => {
+    const first = { timestamp: 1, level: 'info' as const, hook: 'onRequest', message: 'first' }
+    const merged = mergePartialRequestRecord({ scriptLogs: [first] }, { statusCode: 200 })
+    expect(merged.scriptLogs).toEqual([first])
+
+    let logs = merged.scriptLogs
+    for (let index = 0; index < MAX_SCRIPT_LOGS; index += 1) {
+      logs = appendScriptLog(logs, { ...first, message: `line ${index}` })
+    }
+    expect(logs).toHaveLength(MAX_SCRIPT_LOGS)
+    expect(logs?.[0]?.message).toBe('line 0')
+  })
+})
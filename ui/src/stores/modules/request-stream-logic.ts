@@ -1,4 +1,8 @@
-import type { NetworkDetailKeyValue, NetworkWebSocketFrame } from '@/components/ui/network-request-detail'
+import type {
+  NetworkDetailKeyValue,
+  NetworkScriptLog,
+  NetworkWebSocketFrame,
+} from '@/components/ui/network-request-detail'
 import type { TrafficRecord } from '@/components/ui/request-tree'
 
 export type PartialRequestRecord = {
@@ -11,6 +15,7 @@ export type PartialRequestRecord = {
     ruleId: string
     name: string
     priority?: number
+    sampled?: boolean
   }>
   requestHeaders?: NetworkDetailKeyValue[]
   responseHeaders?: NetworkDetailKeyValue[]
@@ -30,6 +35,7 @@ export type PartialRequestRecord = {
   protocol?: string
   websocketFrames?: NetworkWebSocketFrame[]
   websocketEnded?: boolean
+  scriptLogs?: NetworkScriptLog[]
 }
 
 export function isRecordListable(record: PartialRequestRecord | undefined): boolean {
@@ -128,6 +134,9 @@ export function mergePartialRequestRecord(
   if (patch.websocketEnded === undefined) {
     next.websocketEnded = current.websocketEnded
   }
+  if (patch.scriptLogs === undefined) {
+    next.scriptLogs = current.scriptLogs
+  }
 
   return next
 }
@@ -143,3 +152,37 @@ export function promoteTraceToOrder(traceOrder: string[], traceId: string): stri
 export function demoteTraceFromOrder(traceOrder: string[], traceId: string): string[] {
   return traceOrder.filter(id => id !== traceId)
 }
+
+/** Matches the backend's per-trace cap. */
+export const MAX_SCRIPT_LOGS = 200
+
+const SCRIPT_LOG_LEVELS = new Set<NetworkScriptLog['level']>(['info', 'debug', 'error'])
+
+/** Reads a `script.log` event payload (`{ traceId, log }`) or a cached `scriptLogs` entry. */
+export function parseScriptLog(value: unknown): NetworkScriptLog | null {
+  if (typeof value !== 'object' || value === null) {
+    return null
+  }
+  const raw = value as Record<string, unknown>
+  const log = (typeof raw.log === 'object' && raw.log !== null ? raw.log : raw) as Record<string, unknown>
+  if (typeof log.message !== 'string') {
+    return null
+  }
+  const level = SCRIPT_LOG_LEVELS.has(log.level as NetworkScriptLog['level'])
+    ? log.level as NetworkScriptLog['level']
+    : 'info'
+  return {
+    timestamp: typeof log.timestamp === 'number' ? log.timestamp : Date.now(),
+    level,
+    hook: typeof log.hook === 'string' ? log.hook : '',
+    message: log.message,
+  }
+}
+
+export function appendScriptLog(
+  current: NetworkScriptLog[] | undefined,
+  log: NetworkScriptLog,
+): NetworkScriptLog[] {
+  const next = [...(current ?? []), log]
+  return next.length > MAX_SCRIPT_LOGS ? next.slice(next.length - MAX_SCRIPT_LOGS) : next
+}
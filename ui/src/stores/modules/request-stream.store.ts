@@ -5,8 +5,13 @@ import {
   WsOp,
   type WsEventOp,
 } from '@/lib/generated/ws/v1'
-import type { NetworkDetailKeyValue, NetworkDetailRecord } from '@/components/ui/network-request-detail'
+import type {
+  NetworkDetailKeyValue,
+  NetworkDetailRecord,
+  NetworkScriptLog,
+} from '@/components/ui/network-request-detail'
 import type { TrafficRecord } from '@/components/ui/request-tree'
+import type { WebSocketSendPayload } from '@/lib/ws/api'
 import {
   appendBodyBytes,
   contentTypeFromHeaders,
@@ -26,9 +31,11 @@ import {
   parseWebSocketLogsFromSnapshot,
 } from '@/lib/ws/websocket-log'
 import {
+  appendScriptLog,
   demoteTraceFromOrder,
   isRecordListable,
   mergePartialRequestRecord,
+  parseScriptLog,
   promoteTraceToOrder,
   patchTouchesTrafficList,
   type PartialRequestRecord,
@@ -55,6 +62,8 @@ type CachedRequestSnapshot = {
   }
   status?: string | { Error?: string }
   messages?: unknown
+  scriptLogs?: unknown[]
+  serverAddr?: string
   requestType?: string
 }
 
@@ -107,7 +116,7 @@ const toHeaderRecord = (headers: unknown): Record<string, string> | undefined =>
   )
 }
 
-const toMatchedRules = (value: unknown): Array<{ ruleId: string, name: string, priority?: number }> => {
+const toMatchedRules = (value: unknown): Array<{ ruleId: string, name: string, priority?: number, sampled?: boolean }> => {
   if (!Array.isArray(value)) {
     return []
   }
@@ -125,9 +134,10 @@ const toMatchedRules = (value: unknown): Array<{ ruleId: string, name: string, p
         ruleId: String(ruleId),
         name,
         ...(priority === undefined ? {} : { priority }),
+        ...(typeof obj.sampled === 'boolean' ? { sampled: obj.sampled } : {}),
       }
     })
-    .filter(Boolean) as Array<{ ruleId: string, name: string, priority?: number }>
+    .filter(Boolean) as Array<{ ruleId: string, name: string, priority?: number, sampled?: boolean }>
 }
 
 const valueToNumber = (value: unknown): number | undefined => {
@@ -210,6 +220,8 @@ const eventOps = new Set<WsEventOp>([
   WsOp.WebsocketMessage,
   WsOp.WebsocketEnd,
   WsOp.WebsocketError,
+  WsOp.ScriptLog,
+  WsOp.ServerConnected,
   WsOp.SystemError,
 ])
 
@@ -300,6 +312,7 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
         ruleId: rule.ruleId,
         name: rule.name,
         ...(typeof rule.priority === 'number' ? { reason: `priority=${rule.priority}` } : {}),
+        ...(typeof rule.sampled === 'boolean' ? { sampled: rule.sampled } : {}),
       })),
       query: parseQueryFromUrl(resolvedUrl),
       requestHeaders: item.requestHeaders,
@@ -319,6 +332,7 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
       },
       websocketFrames: item.websocketFrames,
       websocketEnded: item.websocketEnded,
+      scriptLogs: item.scriptLogs,
     }
   }
 
@@ -555,6 +569,29 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
     })
   }
 
+  const applyScriptLog = (traceId: string, payload: Record<string, unknown> | null) => {
+    const current = recordsByTrace.value[traceId]
+    const log = parseScriptLog(payload)
+    // Script logs only decorate requests we already track.
+    if (!current || !log) {
+      return
+    }
+
+    updateRecord(traceId, {
+      scriptLogs: appendScriptLog(current.scriptLogs, log),
+    })
+  }
+
+  const applyServerConnected = (traceId: string, payload: Record<string, unknown> | null) => {
+    if (!recordsByTrace.value[traceId] || typeof payload?.serverAddr !== 'string') {
+      return
+    }
+
+    updateRecord(traceId, {
+      remoteAddress: payload.serverAddr,
+    })
+  }
+
   const applyWebsocketEnd = (traceId: string) => {
     updateRecord(traceId, {
       websocketEnded: true,
@@ -593,6 +630,10 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
       bytes: markRaw(frame.bytes),
     }))
 
+    const scriptLogs = (snapshot.scriptLogs ?? [])
+      .map(parseScriptLog)
+      .filter((log): log is NetworkScriptLog => log !== null)
+
     const requestHeaders = toHeaderRecord(snapshot.request?.headers)
     const requestMethod = snapshot.request?.method
 
@@ -613,7 +654,9 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
         snapshot.requestType ?? snapshot.request?.requestType,
       ),
       requestContentType: contentTypeFromHeaders(requestHeaders),
+      remoteAddress: snapshot.serverAddr,
       ...(websocketFrames.length > 0 ? { websocketFrames } : {}),
+      ...(scriptLogs.length > 0 ? { scriptLogs } : {}),
     })
 
     promoteToList(traceId)
@@ -656,6 +699,12 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
       case WsOp.WebsocketEnd:
         applyWebsocketEnd(traceId)
         break
+      case WsOp.ScriptLog:
+        applyScriptLog(traceId, payload)
+        break
+      case WsOp.ServerConnected:
+        applyServerConnected(traceId, payload)
+        break
       case WsOp.WebsocketError:
       case WsOp.SystemError:
         applyError(traceId)
@@ -733,6 +782,10 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
     selectedId.value = traceId
   }
 
+  const sendWebSocketFrame = async (traceId: string, frame: Omit<WebSocketSendPayload, 'traceId'>) => {
+    await wsConnectionStore.call<unknown, WebSocketSendPayload>(WsOp.WebsocketSend, { traceId, ...frame })
+  }
+
   const clear = () => {
     traceOrder.value = []
     recordsByTrace.value = {}
@@ -752,6 +805,7 @@ export const useRequestStreamStore = defineStore('requestStream', () => {
     subscribe,
     unsubscribe,
     select,
+    sendWebSocketFrame,
     clear,
   }
 })
@@ -200,6 +200,7 @@ export const useRulesStore = defineStore('rules', () => {
             modifyHeaders: [],
             modifyStatusCode: undefined,
             modifyBody: input.seedBody ?? '',
+            bodyTransforms: [],
           },
         }),
       ],